@@ -0,0 +1,101 @@
+//! Compare the diff strategies of `Screen::present` on synthetic workloads.
+//!
+//! Run with `cargo bench --bench screen`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use tuikit::attr::{Attr, Color};
+use tuikit::canvas::Canvas;
+use tuikit::screen::{DiffStrategy, Screen};
+
+const WIDTH: usize = 200;
+const HEIGHT: usize = 60;
+
+const STRATEGIES: [DiffStrategy; 2] = [DiffStrategy::CellByCell, DiffStrategy::RowHash];
+
+fn line(seed: usize) -> String {
+    (0..WIDTH)
+        .map(|col| (b'a' + ((seed + col) % 26) as u8) as char)
+        .collect()
+}
+
+fn new_screen(strategy: DiffStrategy) -> Screen {
+    let mut screen = Screen::new(WIDTH, HEIGHT);
+    screen.set_diff_strategy(strategy);
+    for row in 0..HEIGHT {
+        let _ = screen.print(row, 0, &line(row));
+    }
+    let _ = screen.present();
+    screen
+}
+
+/// every row moves up by one line, like a scrolling log
+fn scrolling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("scrolling");
+    for strategy in STRATEGIES.iter() {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{:?}", strategy)),
+            strategy,
+            |b, &strategy| {
+                let mut screen = new_screen(strategy);
+                let mut offset = 0;
+                b.iter(|| {
+                    offset += 1;
+                    for row in 0..HEIGHT {
+                        let _ = screen.print(row, 0, &line(row + offset));
+                    }
+                    screen.present()
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+/// a few cells (e.g. a clock and a spinner) change per frame
+fn sparse_updates(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sparse_updates");
+    for strategy in STRATEGIES.iter() {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{:?}", strategy)),
+            strategy,
+            |b, &strategy| {
+                let mut screen = new_screen(strategy);
+                let mut tick = 0;
+                b.iter(|| {
+                    tick += 1;
+                    let _ = screen.print(0, WIDTH - 8, &format!("{:08}", tick));
+                    let _ = screen.print(HEIGHT / 2, 0, ["|", "/", "-", "\\"][tick % 4]);
+                    screen.present()
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+/// every cell changes its color on each frame
+fn full_repaint(c: &mut Criterion) {
+    let mut group = c.benchmark_group("full_repaint");
+    for strategy in STRATEGIES.iter() {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{:?}", strategy)),
+            strategy,
+            |b, &strategy| {
+                let mut screen = new_screen(strategy);
+                let mut tick: u8 = 0;
+                b.iter(|| {
+                    tick = tick.wrapping_add(1);
+                    let attr = Attr::from(Color::AnsiValue(tick));
+                    for row in 0..HEIGHT {
+                        let _ = screen.print_with_attr(row, 0, &line(row), attr);
+                    }
+                    screen.present()
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, scrolling, sparse_updates, full_repaint);
+criterion_main!(benches);
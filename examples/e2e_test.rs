@@ -0,0 +1,93 @@
+// How an application could test itself end to end with `tuikit::testing::Script`: the
+// application's event loop runs in a thread, the script types keys and checks the screen.
+//
+// In a real project the script lives in `tests/`, running the same `run` function as `main`.
+// It needs a terminal, e.g. run it with `script -qec "cargo test" /dev/null` on CI.
+
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tuikit::prelude::*;
+use tuikit::testing::Script;
+
+const ITEMS: &[&str] = &["apple", "banana", "cherry", "grape", "lemon"];
+
+/// A tiny picker: type to filter, Up/Down to move, Enter to pick, ESC to cancel
+fn run(term: &Term) -> Option<String> {
+    let mut query = String::new();
+    let mut selected = 0;
+    loop {
+        let matches: Vec<&str> = ITEMS
+            .iter()
+            .cloned()
+            .filter(|item| item.contains(query.as_str()))
+            .collect();
+        selected = selected.min(matches.len().saturating_sub(1));
+
+        let _ = term.clear();
+        let _ = term.print(0, 0, &format!("> {}", query));
+        for (row, item) in matches.iter().enumerate() {
+            let attr = if row == selected {
+                Attr::from(Effect::REVERSE)
+            } else {
+                Attr::default()
+            };
+            let _ = term.print_with_attr(row + 1, 2, item, attr);
+        }
+        let _ = term.set_cursor(0, 2 + query.len());
+        let _ = term.present();
+
+        let event = match term.poll_event() {
+            Ok(event) => event,
+            Err(_) => return None,
+        };
+        match event.key() {
+            Some(Key::ESC) => return None,
+            Some(Key::Enter) => return matches.get(selected).map(|s| s.to_string()),
+            Some(Key::Up) => selected = selected.saturating_sub(1),
+            Some(Key::Down) => selected += 1,
+            Some(Key::Backspace) => {
+                query.pop();
+            }
+            Some(Key::Char(ch)) => query.push(*ch),
+            _ => {}
+        }
+    }
+}
+
+fn main() {
+    let term = Arc::new(Term::with_height(TermHeight::Fixed(8)).unwrap());
+    let app = {
+        let term = term.clone();
+        thread::spawn(move || run(&term))
+    };
+
+    let result = Script::new()
+        .timeout(Duration::from_secs(1))
+        .expect_line(0, ">")
+        .expect_text("banana")
+        .type_text("an")
+        .expect_line(0, "> an")
+        .expect_no_text("apple")
+        .expect_cursor(0, 4)
+        .key(Key::Down)
+        .key(Key::Enter)
+        .run(&*term);
+
+    if result.is_err() {
+        // let the application quit
+        let _ = term.inject_keys(&[Key::ESC]);
+    }
+    let picked = app.join().unwrap();
+    let _ = term.pause();
+    match result {
+        Ok(()) => {
+            assert_eq!(Some("banana".to_string()), picked);
+            println!("e2e test passed");
+        }
+        Err(err) => {
+            eprintln!("e2e test failed: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+// A fuzzy file picker with a preview of the selected file, the one picked is printed:
+//
+//     cargo run --example file_picker -- ~/src/project
+//
+// Type to filter, Up/Down to select, Enter to pick, ESC to cancel.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use tuikit::apps::{FilePicker, Pager, PickerEvent};
+use tuikit::prelude::*;
+use tuikit::term::TermOptions;
+
+/// the preview of `path` under `root`, empty if none or unreadable
+fn preview(root: &Path, path: Option<&Path>) -> Pager {
+    path.and_then(|path| Pager::from_file(root.join(path)).ok())
+        .unwrap_or_else(|| Pager::new(""))
+}
+
+fn main() {
+    let root = PathBuf::from(env::args().nth(1).unwrap_or_else(|| ".".to_string()));
+    let mut picker = FilePicker::with_dir(&root).expect("failed to list the files");
+    let mut previewed = picker.selected().map(Path::to_path_buf);
+    let mut pager = preview(&root, previewed.as_deref());
+
+    let options = TermOptions::from_config().height(TermHeight::Percent(50));
+    let term = Term::with_options(options).unwrap();
+    let picked = loop {
+        {
+            let layout = HSplit::default()
+                .split(Win::new(&picker).border(true).basis(Size::Percent(40)))
+                .split(Win::new(&pager).border(true));
+            let _ = term.clear();
+            let _ = term.draw(&layout);
+            let _ = term.present();
+        }
+
+        match term.poll_event() {
+            Ok(Event::Key(KeyEvent { key, .. })) => match picker.on_key(key) {
+                Some(PickerEvent::Picked(path)) => break Some(path),
+                Some(PickerEvent::Cancel) => break None,
+                None => {}
+            },
+            Ok(_) => {}
+            Err(_) => break None,
+        }
+
+        let selected = picker.selected().map(Path::to_path_buf);
+        if selected != previewed {
+            pager = preview(&root, selected.as_deref());
+            previewed = selected;
+        }
+    };
+
+    drop(term);
+    if let Some(path) = picked {
+        println!("{}", root.join(path).display());
+    }
+}
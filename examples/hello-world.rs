@@ -14,12 +14,12 @@ fn main() {
         let _ = term.print(0, 0, "press arrow key to move the text, (q) to quit");
 
         let (width, height) = term.term_size().unwrap();
-        match ev {
-            Event::Key(Key::ESC) | Event::Key(Key::Char('q')) => break,
-            Event::Key(Key::Up) => row = max(row - 1, 1),
-            Event::Key(Key::Down) => row = min(row + 1, height - 1),
-            Event::Key(Key::Left) => col = max(col, 1) - 1,
-            Event::Key(Key::Right) => col = min(col + 1, width - 1),
+        match ev.key() {
+            Some(Key::ESC) | Some(Key::Char('q')) => break,
+            Some(Key::Up) => row = max(row - 1, 1),
+            Some(Key::Down) => row = min(row + 1, height - 1),
+            Some(Key::Left) => col = max(col, 1) - 1,
+            Some(Key::Right) => col = min(col + 1, width - 1),
             _ => {}
         }
 
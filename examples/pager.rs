@@ -0,0 +1,51 @@
+// A `less` style pager, of the file given or of the standard input:
+//
+//     cargo run --example pager -- src/term.rs
+//     git log | cargo run --example pager
+//
+// j/k, Space/b, Ctrl-D/Ctrl-U, g g/G to scroll, / to search, n/N for the next and
+// previous match, q to quit.
+
+use std::env;
+use std::io::{self, Read};
+use std::time::Instant;
+use tuikit::apps::{Pager, PagerEvent};
+use tuikit::input::InputError;
+use tuikit::prelude::*;
+use tuikit::term::TermOptions;
+
+fn main() {
+    let mut pager = match env::args().nth(1) {
+        Some(path) => Pager::from_file(&path).expect("failed to read the file"),
+        None => {
+            let mut text = String::new();
+            io::stdin()
+                .read_to_string(&mut text)
+                .expect("failed to read the standard input");
+            Pager::new(&text).title("(stdin)")
+        }
+    };
+
+    // honours the user preferences, e.g. TUIKIT_MOUSE
+    let term = Term::with_options(TermOptions::from_config()).unwrap();
+    loop {
+        let _ = term.clear();
+        let _ = term.draw(&pager);
+        let _ = term.present();
+
+        // wait no longer than the pending `g` of `g g`
+        let event = match pager.deadline() {
+            Some(deadline) => term.peek_event(deadline.saturating_duration_since(Instant::now())),
+            None => term.poll_event(),
+        };
+        let closed = match event {
+            Ok(Event::Key(KeyEvent { key, .. })) => pager.on_key(key),
+            Ok(_) => None,
+            Err(InputError::Timeout) => pager.check_timeout(Instant::now()),
+            Err(_) => Some(PagerEvent::Quit),
+        };
+        if closed == Some(PagerEvent::Quit) {
+            break;
+        }
+    }
+}
@@ -3,7 +3,7 @@ use tuikit::prelude::*;
 struct Fit(String);
 
 impl Draw for Fit {
-    fn draw(&self, canvas: &mut Canvas) -> Result<()> {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
         let (_width, height) = canvas.size()?;
         let top = height / 2;
         let _ = canvas.print(top, 0, &self.0);
@@ -18,7 +18,7 @@ impl Draw for Fit {
 struct Model(String);
 
 impl Draw for Model {
-    fn draw(&self, canvas: &mut Canvas) -> Result<()> {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
         let (width, height) = canvas.size()?;
         let message_width = self.0.len();
         let left = (width - message_width) / 2;
@@ -34,7 +34,7 @@ fn main() {
     let fit = Fit("Short Text That Fits".to_string());
 
     while let Ok(ev) = term.poll_event() {
-        if let Event::Key(Key::Char('q')) = ev {
+        if ev.key() == Some(&Key::Char('q')) {
             break;
         }
         let _ = term.print(0, 0, "press 'q' to exit");
@@ -0,0 +1,59 @@
+use tuikit::prelude::*;
+
+struct Model(String);
+
+impl Draw for Model {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
+        let (width, height) = canvas.size()?;
+        let message_width = self.0.len();
+        let left = width.saturating_sub(message_width) / 2;
+        let top = height / 2;
+        let _ = canvas.print(top, left, &self.0);
+        Ok(())
+    }
+}
+
+struct Header(String);
+
+impl Draw for Header {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
+        let _ = canvas.print(0, 0, &self.0);
+        Ok(())
+    }
+}
+
+fn main() {
+    let term = Term::new().unwrap();
+    let _ = term.enable_mouse_support();
+    let _ = term.enable_mouse_drag_support();
+
+    let header = Header("drag the divider or press Alt-Shift-Left/Right, 'q' to exit".to_string());
+    let left = Model("left".to_string());
+    let right = Model("right".to_string());
+
+    // the splitter is placed below the header
+    let mut state = SplitterState::new(SplitterDirection::Horizontal).origin(1, 0);
+
+    while let Ok(ev) = term.poll_event() {
+        match ev {
+            Event::Key(KeyEvent {
+                key: Key::Char('q'),
+                ..
+            }) => break,
+            Event::Key(KeyEvent { key, .. }) => {
+                let _ = state.on_key(&key);
+            }
+            Event::Mouse(mouse) => {
+                let _ = state.on_key(&mouse.into());
+            }
+            _ => {}
+        }
+
+        let _ = term.clear();
+        let vsplit = VSplit::default()
+            .split(Win::new(&header).basis(1).grow(0))
+            .split(Splitter::new(&state, &left, &right).divider_attr(Color::BLUE));
+        let _ = term.draw(&vsplit);
+        let _ = term.present();
+    }
+}
@@ -5,7 +5,7 @@ use tuikit::prelude::*;
 
 extern crate env_logger;
 
-/// This example is testing tuikit with multi-threads.
+// This example is testing tuikit with multi-threads.
 
 const COL: usize = 4;
 
@@ -18,11 +18,11 @@ fn main() {
 
     let th = thread::spawn(move || {
         while let Ok(ev) = term.poll_event() {
-            if let Event::Key(Key::Char('q')) = ev {
+            if ev.key() == Some(&Key::Char('q')) {
                 break;
             }
 
-            if let Event::Key(Key::Char('r')) = ev {
+            if ev.key() == Some(&Key::Char('r')) {
                 let term = term.clone();
                 thread::spawn(move || {
                     let _ = term.pause();
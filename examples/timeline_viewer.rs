@@ -0,0 +1,84 @@
+// Step through a timeline recorded with `Term::start_recording`:
+//
+//     cargo run --example timeline_viewer -- /tmp/timeline.tktl
+//
+// Without a path, a short demo timeline is recorded first.
+
+use std::cmp::min;
+use std::env;
+use tuikit::prelude::*;
+use tuikit::screen::Screen;
+use tuikit::timeline::Timeline;
+
+fn demo_timeline() -> Timeline {
+    let mut timeline = Timeline::new();
+    let mut screen = Screen::new(30, 3);
+    timeline.record_frame(&screen);
+    for (row, text) in ["hello", "timeline", "viewer"].iter().enumerate() {
+        timeline.record_event(&Event::from_key(Key::Enter));
+        let _ = screen.print(row, 0, text);
+        timeline.record_frame(&screen);
+    }
+    timeline
+}
+
+fn main() {
+    let timeline = match env::args().nth(1) {
+        Some(path) => Timeline::load(&path).expect("failed to load the timeline"),
+        None => demo_timeline(),
+    };
+    let frames = timeline.frames();
+    if frames.is_empty() {
+        println!("no frame recorded");
+        return;
+    }
+
+    let term = Term::new().unwrap();
+    let mut current = 0;
+    loop {
+        let (snapshot, events) = &frames[current];
+        let _ = term.clear();
+        let (width, height) = term.term_size().unwrap();
+
+        let status = format!(
+            "frame {}/{} ({}x{}), events before: {} | h/l: prev/next, g/G: first/last, q: quit",
+            current + 1,
+            frames.len(),
+            snapshot.width,
+            snapshot.height,
+            events.join(", ")
+        );
+        let _ = term.print_with_attr(0, 0, &status, Effect::REVERSE);
+
+        // the frame, clipped by the terminal
+        for row in 0..min(snapshot.height, height.saturating_sub(1)) {
+            for col in 0..min(snapshot.width, width) {
+                if let Some(cell) = snapshot.cell(row, col) {
+                    let _ = term.put_cell(row + 1, col, *cell);
+                }
+            }
+        }
+        match snapshot.cursor {
+            Some((row, col)) => {
+                let _ = term.set_cursor(row + 1, col);
+            }
+            None => {
+                let _ = term.show_cursor(false);
+            }
+        }
+        let _ = term.present();
+
+        let event = match term.poll_event() {
+            Ok(event) => event,
+            Err(_) => break,
+        };
+        match event.key() {
+            Some(Key::Char('q')) | Some(Key::ESC) => break,
+            Some(Key::Char('l')) | Some(Key::Right) => current = min(current + 1, frames.len() - 1),
+            Some(Key::Char('h')) | Some(Key::Left) => current = current.saturating_sub(1),
+            Some(Key::Char('g')) | Some(Key::Home) => current = 0,
+            Some(Key::Char('G')) | Some(Key::End) => current = frames.len() - 1,
+            _ => {}
+        }
+    }
+}
@@ -3,7 +3,7 @@ use tuikit::prelude::*;
 struct Model(String);
 
 impl Draw for Model {
-    fn draw(&self, canvas: &mut Canvas) -> Result<()> {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
         let (width, height) = canvas.size()?;
         let message_width = self.0.len();
         let left = (width - message_width) / 2;
@@ -18,7 +18,7 @@ fn main() {
     let model = Model("Hey, I'm in middle!".to_string());
 
     while let Ok(ev) = term.poll_event() {
-        if let Event::Key(Key::Char('q')) = ev {
+        if ev.key() == Some(&Key::Char('q')) {
             break;
         }
         let _ = term.print(0, 0, "press 'q' to exit");
@@ -0,0 +1,195 @@
+//! Mirror semantic events to a side channel for screen readers.
+//!
+//! A terminal UI is a grid of cells, which is opaque to assistive technologies. With an
+//! `Announcer` registered on `Term`, the application could describe what happened (focus
+//! changes, selected item, status messages) in plain text, e.g. to a fifo read by a screen
+//! reader.
+//!
+//! ```no_run
+//! use tuikit::accessibility::{Announcement, FileAnnouncer};
+//! use tuikit::term::Term;
+//!
+//! let term = Term::new().unwrap();
+//! term.set_announcer(Some(Box::new(FileAnnouncer::open("/tmp/tuikit.fifo").unwrap())));
+//! term.announce(Announcement::Status("3 files found".to_string()));
+//! ```
+//!
+//! `AccessibilityOptions` are the user-facing toggles (high contrast, reduced motion, no
+//! blinking). `TermOptions::from_config` reads them from the `TUIKIT_ACCESSIBILITY` environment
+//! variable, so users could set them once for every tuikit application honouring it.
+
+use crate::attr::{Attr, Effect};
+use std::env;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+const ENV_ACCESSIBILITY: &str = "TUIKIT_ACCESSIBILITY";
+
+/// Accessibility toggles respected by `Term` and the animation engine.
+///
+/// - `high_contrast`: `DIM` is never rendered, applications/themes should check the flag to
+///   choose more contrasting colors.
+/// - `reduce_motion`: animations jump to their final values.
+/// - `disable_blink`: `BLINK` is never rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AccessibilityOptions {
+    pub high_contrast: bool,
+    pub reduce_motion: bool,
+    pub disable_blink: bool,
+}
+
+impl AccessibilityOptions {
+    /// Read options from `TUIKIT_ACCESSIBILITY`, a comma separated list of
+    /// `high-contrast`, `reduce-motion` and `no-blink`.
+    pub fn from_env() -> Self {
+        env::var(ENV_ACCESSIBILITY)
+            .map(|value| Self::parse(&value))
+            .unwrap_or_default()
+    }
+
+    /// parse a comma separated list of toggles, unknown items are ignored
+    ///
+    /// ```
+    /// use tuikit::accessibility::AccessibilityOptions;
+    ///
+    /// let options = AccessibilityOptions::parse("reduce-motion, no-blink");
+    /// assert!(!options.high_contrast);
+    /// assert!(options.reduce_motion);
+    /// assert!(options.disable_blink);
+    /// ```
+    pub fn parse(value: &str) -> Self {
+        let mut options = Self::default();
+        for item in value.split(',') {
+            match item.trim().to_lowercase().as_ref() {
+                "high-contrast" => options.high_contrast = true,
+                "reduce-motion" => options.reduce_motion = true,
+                "no-blink" => options.disable_blink = true,
+                _ => {}
+            }
+        }
+        options
+    }
+
+    /// adjust the attribute to be rendered according to the options
+    pub fn adjust_attr(&self, attr: Attr) -> Attr {
+        let mut effect = attr.effect;
+        if self.high_contrast {
+            effect.remove(Effect::DIM);
+        }
+        if self.disable_blink {
+            effect.remove(Effect::BLINK);
+        }
+        attr.effect(effect)
+    }
+}
+
+/// A semantic event, rendered as a single line of plain text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Announcement {
+    /// focus moved to the described element
+    Focus(String),
+    /// the selected item changed
+    Selection(String),
+    /// a status message
+    Status(String),
+}
+
+impl fmt::Display for Announcement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (kind, text) = match self {
+            Announcement::Focus(text) => ("focus", text),
+            Announcement::Selection(text) => ("selected", text),
+            Announcement::Status(text) => ("status", text),
+        };
+
+        // one announcement per line
+        write!(f, "{}: {}", kind, text.replace(['\r', '\n'], " "))
+    }
+}
+
+/// The side channel announcements are sent to.
+pub trait Announcer: Send {
+    fn announce(&mut self, announcement: &Announcement);
+}
+
+impl<F> Announcer for F
+where
+    F: FnMut(&Announcement) + Send,
+{
+    fn announce(&mut self, announcement: &Announcement) {
+        self(announcement)
+    }
+}
+
+/// Write announcements line by line to a file or a fifo.
+pub struct FileAnnouncer {
+    file: File,
+}
+
+impl FileAnnouncer {
+    pub fn new(file: File) -> Self {
+        Self { file }
+    }
+
+    /// open `path` for appending, it will be created if not exists
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().append(true).create(true).open(path)?;
+        Ok(Self::new(file))
+    }
+}
+
+impl Announcer for FileAnnouncer {
+    fn announce(&mut self, announcement: &Announcement) {
+        let _ = writeln!(self.file, "{}", announcement);
+        let _ = self.file.flush();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn announcement_should_be_single_line() {
+        assert_eq!(
+            "status: 2 items  removed",
+            format!("{}", Announcement::Status("2 items\r\nremoved".to_string()))
+        );
+        assert_eq!(
+            "focus: search box",
+            format!("{}", Announcement::Focus("search box".to_string()))
+        );
+    }
+
+    #[test]
+    fn options_should_strip_effects() {
+        let attr = Attr::default().effect(Effect::DIM | Effect::BLINK | Effect::BOLD);
+        let options = AccessibilityOptions {
+            high_contrast: true,
+            ..AccessibilityOptions::default()
+        };
+        assert_eq!(
+            Effect::BLINK | Effect::BOLD,
+            options.adjust_attr(attr).effect
+        );
+
+        let options = AccessibilityOptions::parse("no-blink,unknown");
+        assert_eq!(Effect::DIM | Effect::BOLD, options.adjust_attr(attr).effect);
+        assert_eq!(attr, AccessibilityOptions::default().adjust_attr(attr));
+    }
+
+    #[test]
+    fn closure_should_be_announcer() {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let lines_clone = lines.clone();
+        let mut announcer: Box<dyn Announcer> = Box::new(move |a: &Announcement| {
+            lines_clone.lock().unwrap().push(a.to_string());
+        });
+
+        announcer.announce(&Announcement::Selection("README.md".to_string()));
+        assert_eq!(vec!["selected: README.md"], *lines.lock().unwrap());
+    }
+}
@@ -0,0 +1,233 @@
+//! A small animation engine that interpolates values over time.
+//!
+//! Tuikit has no internal clock, the application drives the animations by calling
+//! `Animator::tick` (e.g. on every `peek_event` timeout) and re-draws while
+//! `Animator::is_running` is true.
+//!
+//! ```
+//! use std::time::{Duration, Instant};
+//! use tuikit::animation::{Animator, Easing};
+//!
+//! let mut animator = Animator::new();
+//! let scroll = animator.tween(0.0, 10.0, Duration::from_millis(100), Easing::Linear);
+//!
+//! let now = Instant::now();
+//! animator.tick(now + Duration::from_millis(200));
+//! assert_eq!(Some(10.0), animator.value(scroll));
+//! ```
+//!
+//! Animations could be disabled globally (e.g. for users who prefer reduced motion) with
+//! `set_animation_enabled(false)`, every tween will then jump to its final value.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+static ANIMATION_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Enable/disable all the animations globally
+pub fn set_animation_enabled(enabled: bool) {
+    ANIMATION_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Check if animations are enabled globally
+pub fn animation_enabled() -> bool {
+    ANIMATION_ENABLED.load(Ordering::Relaxed)
+}
+
+/// The easing function of a tween
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    #[default]
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// map the progress `t` (0.0 ~ 1.0) to the eased progress
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+        }
+    }
+}
+
+/// Interpolate from `from` to `to` in `duration`
+#[derive(Debug, Copy, Clone)]
+pub struct Tween {
+    from: f64,
+    to: f64,
+    start: Instant,
+    duration: Duration,
+    easing: Easing,
+}
+
+impl Tween {
+    /// create a tween starting from now
+    pub fn new(from: f64, to: f64, duration: Duration, easing: Easing) -> Self {
+        Self::starting_at(Instant::now(), from, to, duration, easing)
+    }
+
+    pub fn starting_at(
+        start: Instant,
+        from: f64,
+        to: f64,
+        duration: Duration,
+        easing: Easing,
+    ) -> Self {
+        Self {
+            from,
+            to,
+            start,
+            duration,
+            easing,
+        }
+    }
+
+    /// the progress (0.0 ~ 1.0) at time `now`
+    fn progress_at(&self, now: Instant) -> f64 {
+        if !animation_enabled() || self.duration == Duration::new(0, 0) {
+            return 1.0;
+        }
+
+        let elapsed = now.saturating_duration_since(self.start);
+        elapsed.as_secs_f64() / self.duration.as_secs_f64()
+    }
+
+    /// the interpolated value at time `now`
+    pub fn value_at(&self, now: Instant) -> f64 {
+        let eased = self.easing.apply(self.progress_at(now));
+        self.from + (self.to - self.from) * eased
+    }
+
+    /// whether the tween reached the final value at time `now`
+    pub fn is_finished_at(&self, now: Instant) -> bool {
+        self.progress_at(now) >= 1.0
+    }
+
+    pub fn target(&self) -> f64 {
+        self.to
+    }
+}
+
+/// Identifier of a tween managed by `Animator`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct TweenId(usize);
+
+/// Manage a set of tweens, their values are updated on `tick`
+#[derive(Debug)]
+pub struct Animator {
+    next_id: usize,
+    now: Instant,
+    tweens: HashMap<TweenId, Tween>,
+}
+
+impl Default for Animator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Animator {
+    pub fn new() -> Self {
+        Self {
+            next_id: 0,
+            now: Instant::now(),
+            tweens: HashMap::new(),
+        }
+    }
+
+    /// start a new tween from the current tick
+    pub fn tween(&mut self, from: f64, to: f64, duration: Duration, easing: Easing) -> TweenId {
+        let id = TweenId(self.next_id);
+        self.next_id += 1;
+        self.tweens
+            .insert(id, Tween::starting_at(self.now, from, to, duration, easing));
+        id
+    }
+
+    /// Re-target an existing tween to `to`, starting from its current value.
+    /// Useful for e.g. smooth scrolling where the destination keeps changing.
+    pub fn retarget(&mut self, id: TweenId, to: f64, duration: Duration) {
+        let now = self.now;
+        if let Some(tween) = self.tweens.get_mut(&id) {
+            let from = tween.value_at(now);
+            *tween = Tween::starting_at(now, from, to, duration, tween.easing);
+        }
+    }
+
+    /// Advance the clock of the animator, return whether there are still running tweens.
+    pub fn tick(&mut self, now: Instant) -> bool {
+        self.now = now;
+        self.is_running()
+    }
+
+    /// check if any of the tweens is not finished
+    pub fn is_running(&self) -> bool {
+        self.tweens
+            .values()
+            .any(|tween| !tween.is_finished_at(self.now))
+    }
+
+    /// the value of tween `id` as of the last tick
+    pub fn value(&self, id: TweenId) -> Option<f64> {
+        self.tweens.get(&id).map(|tween| tween.value_at(self.now))
+    }
+
+    /// remove the tween `id`, return its last value
+    pub fn remove(&mut self, id: TweenId) -> Option<f64> {
+        let now = self.now;
+        self.tweens.remove(&id).map(|tween| tween.value_at(now))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn easing_should_start_at_0_and_end_at_1() {
+        for easing in &[
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert_eq!(0.0, easing.apply(0.0));
+            assert_eq!(1.0, easing.apply(1.0));
+            assert_eq!(1.0, easing.apply(2.0));
+        }
+        assert_eq!(0.5, Easing::EaseInOut.apply(0.5));
+    }
+
+    #[test]
+    fn animator_should_interpolate_on_tick() {
+        let mut animator = Animator::new();
+        let start = animator.now;
+        let id = animator.tween(10.0, 20.0, Duration::from_millis(100), Easing::Linear);
+
+        assert_eq!(Some(10.0), animator.value(id));
+        assert!(animator.tick(start + Duration::from_millis(50)));
+        assert_eq!(Some(15.0), animator.value(id));
+
+        animator.retarget(id, 25.0, Duration::from_millis(100));
+        assert!(animator.tick(start + Duration::from_millis(100)));
+        assert_eq!(Some(20.0), animator.value(id));
+
+        assert!(!animator.tick(start + Duration::from_millis(150)));
+        assert_eq!(Some(25.0), animator.remove(id));
+        assert_eq!(None, animator.value(id));
+    }
+}
@@ -0,0 +1,28 @@
+//! Ready-made mini applications, the reusable parts of the `file_picker` and `pager` examples.
+//!
+//! Each is a widget (it implements `Draw`) driven by the keys through a `Keymap`, which the
+//! application could rebind with `keymap_mut`:
+//!
+//! ```
+//! use std::path::PathBuf;
+//! use tuikit::apps::{FilePicker, PickerEvent};
+//! use tuikit::key::Key;
+//!
+//! let files = vec![PathBuf::from("src/lib.rs"), PathBuf::from("README.md")];
+//! let mut picker = FilePicker::new(files);
+//! picker.on_key(Key::Char('r'));
+//! picker.on_key(Key::Char('m'));
+//! assert_eq!(
+//!     Some(PickerEvent::Picked(PathBuf::from("README.md"))),
+//!     picker.on_key(Key::Enter)
+//! );
+//! ```
+//!
+//! They are drawn with `Term::draw`, alone or in a layout, and adapt to the resizes through
+//! `Draw::draw_with_context`.
+
+mod pager;
+mod picker;
+
+pub use self::pager::*;
+pub use self::picker::*;
@@ -0,0 +1,474 @@
+use crate::attr::{Attr, Effect};
+use crate::canvas::{Canvas, Result};
+use crate::draw::{Draw, DrawContext};
+use crate::highlight::{print_highlighted, Span};
+use crate::key::Key;
+use crate::keymap::{Keymap, KeymapEvent};
+use crate::paste::PastePolicy;
+use crate::style::{Styled, Styles};
+use crate::width::display_width;
+use std::cell::Cell;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Instant;
+
+const TAB: &str = "    ";
+
+/// What the keys of a `Pager` are bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PagerAction {
+    LineDown,
+    LineUp,
+    PageDown,
+    PageUp,
+    HalfPageDown,
+    HalfPageUp,
+    Top,
+    Bottom,
+    /// type a pattern on the status line, then show the next line containing it
+    Search,
+    NextMatch,
+    PrevMatch,
+    Quit,
+}
+
+/// Events emitted by `Pager::on_key`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PagerEvent {
+    Quit,
+}
+
+/// A `less` style pager: the text above a status line, scrolled with the keys of `less` and
+/// vim (`j`/`k`, `Space`/`b`, `Ctrl-D`/`Ctrl-U`, `g g`/`G`, `q`).
+///
+/// `/` searches: the pattern is typed on the status line, `Enter` scrolls to the next line
+/// containing it (`ESC` cancels), then `n`/`N` to the next/previous one. The matches are
+/// highlighted.
+///
+/// `g g` is a sequence: the status line shows the pending `g`, and the event loop should call
+/// `check_timeout` once `deadline` passes.
+pub struct Pager {
+    lines: Vec<String>,
+    title: String,
+    /// the first line shown
+    top: Cell<usize>,
+    /// the number of lines shown, as last drawn
+    page_height: Cell<usize>,
+    keymap: Keymap<PagerAction>,
+    /// the pattern being typed after `/`
+    prompt: Option<String>,
+    /// the last pattern searched
+    pattern: String,
+    /// the line of the last match, the next search starts from it unless scrolled since
+    found: Option<usize>,
+    /// shown on the status line until the next key, e.g. when the pattern is not found
+    message: Option<String>,
+    styles: Styles,
+}
+
+impl Pager {
+    pub fn new(text: &str) -> Self {
+        let mut keymap = Keymap::new();
+        let bindings = [
+            ("j", PagerAction::LineDown),
+            ("Down", PagerAction::LineDown),
+            ("Enter", PagerAction::LineDown),
+            ("k", PagerAction::LineUp),
+            ("Up", PagerAction::LineUp),
+            ("Space", PagerAction::PageDown),
+            ("PageDown", PagerAction::PageDown),
+            ("b", PagerAction::PageUp),
+            ("PageUp", PagerAction::PageUp),
+            ("Ctrl-d", PagerAction::HalfPageDown),
+            ("Ctrl-u", PagerAction::HalfPageUp),
+            ("g g", PagerAction::Top),
+            ("Home", PagerAction::Top),
+            ("G", PagerAction::Bottom),
+            ("End", PagerAction::Bottom),
+            ("/", PagerAction::Search),
+            ("n", PagerAction::NextMatch),
+            ("N", PagerAction::PrevMatch),
+            ("q", PagerAction::Quit),
+            ("ESC", PagerAction::Quit),
+        ];
+        for (keys, action) in bindings.iter() {
+            keymap.bind(keys, *action).expect("invalid pager binding");
+        }
+
+        Self {
+            lines: text.lines().map(|line| line.replace('\t', TAB)).collect(),
+            title: String::new(),
+            top: Cell::new(0),
+            page_height: Cell::new(0),
+            keymap,
+            prompt: None,
+            pattern: String::new(),
+            found: None,
+            message: None,
+            styles: Styles::new(),
+        }
+    }
+
+    /// The content of the file at `path`, titled with it
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let bytes = fs::read(path)?;
+        Ok(Self::new(&String::from_utf8_lossy(&bytes)).title(&path.display().to_string()))
+    }
+
+    /// shown on the left of the status line
+    pub fn title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self
+    }
+}
+
+impl Styled for Pager {
+    /// the status line and the matches of the search
+    fn style_slots(&self) -> &'static [&'static str] {
+        &["pager.status", "pager.match"]
+    }
+
+    fn styles_mut(&mut self) -> &mut Styles {
+        &mut self.styles
+    }
+}
+
+impl Pager {
+    /// the bindings, e.g. to bind `/` to a search of the application
+    pub fn keymap_mut(&mut self) -> &mut Keymap<PagerAction> {
+        &mut self.keymap
+    }
+
+    /// the first line shown
+    pub fn top(&self) -> usize {
+        self.top.get()
+    }
+
+    /// the last pattern searched
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// when the pending keys (e.g. the first `g` of `g g`) time out, see `check_timeout`
+    pub fn deadline(&self) -> Option<Instant> {
+        self.keymap.deadline()
+    }
+
+    /// Handle `key`, return `Some(event)` once the pager should be closed
+    pub fn on_key(&mut self, key: Key) -> Option<PagerEvent> {
+        self.message = None;
+        if self.prompt.is_some() {
+            self.on_prompt_key(key);
+            return None;
+        }
+        match self.keymap.on_key(key) {
+            KeymapEvent::Resolved(action) => self.run(action),
+            _ => None,
+        }
+    }
+
+    /// edit the pattern after `/`
+    fn on_prompt_key(&mut self, key: Key) {
+        let prompt = match self.prompt.as_mut() {
+            Some(prompt) => prompt,
+            None => return,
+        };
+        match key {
+            Key::Char(ch) => prompt.push(ch),
+            Key::BracketedPaste(text) => {
+                let text = PastePolicy::default().single_line(true).apply(&text);
+                prompt.push_str(&text.unwrap_or_default());
+            }
+            Key::Backspace if !prompt.is_empty() => {
+                prompt.pop();
+            }
+            Key::Enter => {
+                let pattern = self.prompt.take().unwrap_or_default();
+                // an empty pattern repeats the last search, as in `less`
+                if !pattern.is_empty() {
+                    self.pattern = pattern;
+                }
+                self.found = None;
+                self.search(true);
+            }
+            Key::Backspace | Key::ESC | Key::Ctrl('g') | Key::Ctrl('c') => self.prompt = None,
+            _ => {}
+        }
+    }
+
+    /// scroll to the next (or previous) line containing the pattern
+    fn search(&mut self, forward: bool) {
+        if self.pattern.is_empty() {
+            return;
+        }
+        let from = self.found.unwrap_or_else(|| self.top.get());
+        let pattern = &self.pattern;
+        let found = if forward {
+            (from + 1..self.lines.len()).find(|&index| self.lines[index].contains(pattern))
+        } else {
+            (0..from.min(self.lines.len()))
+                .rev()
+                .find(|&index| self.lines[index].contains(pattern))
+        };
+        match found {
+            Some(index) => {
+                // on the top line, or on the last page
+                let page = self.page_height.get().max(1);
+                self.top.set(index.min(self.max_top(page)));
+                self.found = Some(index);
+            }
+            None => self.message = Some("Pattern not found".to_string()),
+        }
+    }
+
+    /// Drop the pending keys if they timed out at `now`
+    pub fn check_timeout(&mut self, now: Instant) -> Option<PagerEvent> {
+        match self.keymap.check_timeout(now)? {
+            KeymapEvent::Resolved(action) => self.run(action),
+            _ => None,
+        }
+    }
+
+    /// Run `action`, e.g. from a command palette
+    pub fn run(&mut self, action: PagerAction) -> Option<PagerEvent> {
+        let page = self.page_height.get().max(1);
+        let top = self.top.get();
+        let top = match action {
+            PagerAction::LineDown => top + 1,
+            PagerAction::LineUp => top.saturating_sub(1),
+            PagerAction::PageDown => top + page,
+            PagerAction::PageUp => top.saturating_sub(page),
+            PagerAction::HalfPageDown => top + page.div_ceil(2),
+            PagerAction::HalfPageUp => top.saturating_sub(page.div_ceil(2)),
+            PagerAction::Top => 0,
+            PagerAction::Bottom => usize::MAX,
+            PagerAction::Search => {
+                self.prompt = Some(String::new());
+                return None;
+            }
+            PagerAction::NextMatch => {
+                self.search(true);
+                return None;
+            }
+            PagerAction::PrevMatch => {
+                self.search(false);
+                return None;
+            }
+            PagerAction::Quit => return Some(PagerEvent::Quit),
+        };
+        self.top.set(top.min(self.max_top(page)));
+        self.found = None;
+        None
+    }
+
+    /// the top line showing the end of the text on a page of `page_height` lines
+    fn max_top(&self, page_height: usize) -> usize {
+        self.lines.len().saturating_sub(page_height)
+    }
+
+    fn draw_pager(&self, canvas: &mut dyn Canvas, resized: bool) -> Result<()> {
+        let (width, height) = canvas.size()?;
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+
+        // the end of the text stays in view across a resize, like `tail -f`
+        let page_height = height - 1;
+        let at_bottom = self.top.get() >= self.max_top(self.page_height.get());
+        let top = if resized && at_bottom {
+            self.max_top(page_height)
+        } else {
+            self.top.get().min(self.max_top(page_height))
+        };
+        self.top.set(top);
+        self.page_height.set(page_height);
+
+        let match_attr = self
+            .styles
+            .resolve("pager.match", Attr::from(Effect::REVERSE));
+        for (row, line) in self.lines.iter().skip(top).take(page_height).enumerate() {
+            let spans: Vec<Span> = if self.pattern.is_empty() {
+                Vec::new()
+            } else {
+                line.match_indices(self.pattern.as_str())
+                    .map(|(start, found)| Span::new(start..start + found.len(), match_attr))
+                    .collect()
+            };
+            print_highlighted(canvas, row, 0, line, &spans, Attr::default())?;
+        }
+
+        let status_attr = self
+            .styles
+            .resolve("pager.status", Attr::from(Effect::REVERSE));
+        if let Some(prompt) = self.prompt.as_ref() {
+            let prompt = format!("/{}", prompt);
+            canvas.print_with_attr(page_height, 0, &format!("{:width$}", prompt), status_attr)?;
+            canvas.set_cursor(page_height, display_width(&prompt).min(width - 1))?;
+            return Ok(());
+        }
+        if let Some(message) = self.message.as_ref() {
+            let message = format!(" {}", message);
+            canvas.print_with_attr(page_height, 0, &format!("{:width$}", message), status_attr)?;
+            return Ok(());
+        }
+        let last = (top + page_height).min(self.lines.len());
+        let position = format!(
+            "{} {}-{}/{} ",
+            self.keymap.pending_text(),
+            top + 1,
+            last,
+            self.lines.len()
+        );
+        let status = format!(" {}", self.title);
+        canvas.print_with_attr(page_height, 0, &format!("{:width$}", status), status_attr)?;
+        let position_col = width.saturating_sub(display_width(&position));
+        canvas.print_with_attr(page_height, position_col, &position, status_attr)?;
+        Ok(())
+    }
+}
+
+impl Draw for Pager {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
+        self.draw_pager(canvas, false)
+    }
+
+    fn draw_with_context(&self, canvas: &mut dyn Canvas, ctx: &DrawContext) -> Result<()> {
+        self.draw_pager(canvas, ctx.resized)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::{Event, KeyEvent};
+    use crate::screen::Screen;
+    use crate::term::{Term, TermHeight, TermOptions};
+    use crate::testing::FakeTerminal;
+    use std::time::Duration;
+
+    const TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// type `bytes` on the terminal, feed the `count` keys read to the pager, then draw it
+    fn type_keys(
+        fake: &FakeTerminal,
+        term: &Term,
+        pager: &mut Pager,
+        bytes: &[u8],
+        count: usize,
+    ) -> Option<PagerEvent> {
+        fake.type_bytes(bytes).unwrap();
+        let mut event = None;
+        let mut keys = 0;
+        while keys < count {
+            if let Event::Key(KeyEvent { key, .. }) = term.peek_event(TIMEOUT).unwrap() {
+                event = event.or(pager.on_key(key));
+                keys += 1;
+            }
+        }
+        term.clear().unwrap();
+        term.draw(pager).unwrap();
+        term.present().unwrap();
+        event
+    }
+
+    fn rows(screen: &Screen) -> Vec<String> {
+        let (width, height) = screen.size().unwrap();
+        let cells: Vec<char> = screen.iter_cell().map(|(_, _, cell)| cell.ch).collect();
+        (0..height)
+            .map(|row| {
+                let text: String = cells[row * width..(row + 1) * width].iter().collect();
+                text.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn scroll_and_follow_the_end() {
+        let text: Vec<String> = (1..=10).map(|n| format!("line {}", n)).collect();
+        let mut pager = Pager::new(&text.join("\n")).title("numbers");
+        let mut screen = Screen::new(20, 4);
+        pager.draw(&mut screen).unwrap();
+        assert_eq!(
+            vec!["line 1", "line 2", "line 3", " numbers     1-3/10"],
+            rows(&screen)
+        );
+
+        pager.on_key(Key::Char(' '));
+        pager.on_key(Key::Char('j'));
+        assert_eq!(4, pager.top());
+        pager.on_key(Key::Char('g'));
+        pager.draw(&mut screen).unwrap();
+        assert_eq!(" numbers   g 5-7/10", rows(&screen)[3]);
+        pager.on_key(Key::Char('g'));
+        assert_eq!(0, pager.top());
+
+        // at the end, it stays there once resized
+        pager.on_key(Key::Char('G'));
+        assert_eq!(7, pager.top());
+        let mut screen = Screen::new(20, 6);
+        let ctx = DrawContext::new(20, 6).resized(true);
+        pager.draw_with_context(&mut screen, &ctx).unwrap();
+        assert_eq!("line 6", rows(&screen)[0]);
+        assert_eq!(Some(PagerEvent::Quit), pager.on_key(Key::Char('q')));
+    }
+
+    #[test]
+    fn search_page_and_quit_on_a_terminal() {
+        let text: Vec<String> = (1..=20)
+            .map(|n| match n {
+                5 | 15 => format!("line {} needle", n),
+                n => format!("line {}", n),
+            })
+            .collect();
+        let mut pager = Pager::new(&text.join("\n")).title("numbers");
+        let fake = FakeTerminal::new(20, 4).unwrap();
+        let options = TermOptions::default()
+            .height(TermHeight::Fixed(4))
+            .tty_path(fake.path());
+        let term = Term::with_options(options).unwrap();
+        let line = |row: usize| term.snapshot().text().lines().nth(row).unwrap().to_string();
+        let is_match = |row: usize, col: usize| {
+            let snapshot = term.snapshot();
+            let attr = snapshot.cell(row, col).unwrap().attr;
+            attr.effect.contains(Effect::REVERSE)
+        };
+
+        type_keys(&fake, &term, &mut pager, b"", 0);
+        assert_eq!("line 1", line(0));
+        type_keys(&fake, &term, &mut pager, b" ", 1);
+        assert_eq!("line 4", line(0));
+        type_keys(&fake, &term, &mut pager, b"\x1b[6~", 1);
+        assert_eq!("line 7", line(0));
+        type_keys(&fake, &term, &mut pager, b"b\x1b[5~", 2);
+        assert_eq!("line 1", line(0));
+
+        // typed on the status line, then the first match is on the top line
+        type_keys(&fake, &term, &mut pager, b"/nee", 4);
+        assert_eq!("/nee", line(3).trim_end());
+        type_keys(&fake, &term, &mut pager, b"dle\r", 4);
+        assert_eq!("line 5 needle", line(0));
+        assert!(!is_match(0, 6) && is_match(0, 7) && is_match(0, 12));
+        assert_eq!(" numbers     5-7/20", line(3));
+
+        type_keys(&fake, &term, &mut pager, b"n", 1);
+        assert_eq!("line 15 needle", line(0));
+        type_keys(&fake, &term, &mut pager, b"n", 1);
+        assert_eq!("line 15 needle", line(0));
+        assert!(line(3).starts_with(" Pattern not found"));
+        type_keys(&fake, &term, &mut pager, b"N", 1);
+        assert_eq!("line 5 needle", line(0));
+
+        // a search cancelled
+        type_keys(&fake, &term, &mut pager, b"/x\x07", 3);
+        assert_eq!(
+            ("line 5 needle", "needle"),
+            (line(0).as_str(), pager.pattern())
+        );
+
+        let quit = Some(PagerEvent::Quit);
+        assert_eq!(quit, type_keys(&fake, &term, &mut pager, b"q", 1));
+        assert_eq!(quit, type_keys(&fake, &term, &mut pager, b"\x1b", 1));
+    }
+}
@@ -0,0 +1,414 @@
+use crate::attr::{Attr, Effect};
+use crate::canvas::{Canvas, Result};
+use crate::draw::{Draw, DrawContext};
+use crate::key::Key;
+use crate::keymap::{Keymap, KeymapEvent};
+use crate::paste::PastePolicy;
+use crate::style::{Styled, Styles};
+use crate::widget::fuzzy_score;
+use crate::width::display_width;
+use std::cell::Cell;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const PROMPT: &str = "> ";
+/// the files listed at most by `FilePicker::with_dir`
+const MAX_FILES: usize = 100_000;
+
+/// What the keys of a `FilePicker` are bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickerAction {
+    Up,
+    Down,
+    Pick,
+    Cancel,
+}
+
+/// Events emitted by `FilePicker::on_key`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PickerEvent {
+    /// the selected file, as listed (relative to the directory of `with_dir`)
+    Picked(PathBuf),
+    Cancel,
+}
+
+/// A fuzzy file picker: the typed query filters the files, best match first.
+///
+/// `Up`/`Down` (`Ctrl-P`/`Ctrl-N`) select a file, `Enter` picks it, `ESC` (`Ctrl-G`) cancels,
+/// the other keys edit the query.
+pub struct FilePicker {
+    files: Vec<PathBuf>,
+    query: String,
+    matches: Vec<usize>,
+    selected: usize,
+    /// the first match shown, kept by `draw` so that the selected one is visible
+    offset: Cell<usize>,
+    keymap: Keymap<PickerAction>,
+    styles: Styles,
+}
+
+impl FilePicker {
+    pub fn new(files: Vec<PathBuf>) -> Self {
+        let mut keymap = Keymap::new();
+        let bindings = [
+            (Key::Up, PickerAction::Up),
+            (Key::Ctrl('p'), PickerAction::Up),
+            (Key::Down, PickerAction::Down),
+            (Key::Ctrl('n'), PickerAction::Down),
+            (Key::Enter, PickerAction::Pick),
+            (Key::ESC, PickerAction::Cancel),
+            (Key::Ctrl('g'), PickerAction::Cancel),
+        ];
+        for (key, action) in bindings.iter() {
+            keymap.bind_keys(vec![key.clone()], *action);
+        }
+
+        let mut picker = Self {
+            files,
+            query: String::new(),
+            matches: Vec::new(),
+            selected: 0,
+            offset: Cell::new(0),
+            keymap,
+            styles: Styles::new(),
+        };
+        picker.refilter();
+        picker
+    }
+
+    /// The files under `root`, see `list_files`
+    pub fn with_dir(root: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(list_files(root.as_ref(), MAX_FILES)?))
+    }
+}
+
+impl Styled for FilePicker {
+    /// the query line, the selected file and the match count
+    fn style_slots(&self) -> &'static [&'static str] {
+        &["picker.prompt", "picker.selected", "picker.count"]
+    }
+
+    fn styles_mut(&mut self) -> &mut Styles {
+        &mut self.styles
+    }
+}
+
+impl FilePicker {
+    /// the bindings, e.g. to add `Tab` for `Down`
+    pub fn keymap_mut(&mut self) -> &mut Keymap<PickerAction> {
+        &mut self.keymap
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// the files matching the query, best match first
+    pub fn matches(&self) -> impl Iterator<Item = &Path> {
+        self.matches
+            .iter()
+            .map(move |&index| self.files[index].as_path())
+    }
+
+    pub fn selected(&self) -> Option<&Path> {
+        self.matches().nth(self.selected)
+    }
+
+    /// Handle `key`, return `Some(event)` once a file is picked or the picker cancelled
+    pub fn on_key(&mut self, key: Key) -> Option<PickerEvent> {
+        let keys = match self.keymap.on_key(key) {
+            KeymapEvent::Resolved(action) => return self.run(action),
+            KeymapEvent::Pending => return None,
+            KeymapEvent::Unbound(keys) | KeymapEvent::TimedOut(keys) => keys,
+        };
+        for key in keys {
+            match key {
+                Key::Char(ch) => self.query.push(ch),
+                Key::Backspace => {
+                    self.query.pop();
+                }
+                Key::BracketedPaste(text) => {
+                    let text = PastePolicy::default().single_line(true).apply(&text);
+                    self.query.push_str(&text.unwrap_or_default());
+                }
+                _ => continue,
+            }
+            self.selected = 0;
+            self.refilter();
+        }
+        None
+    }
+
+    fn run(&mut self, action: PickerAction) -> Option<PickerEvent> {
+        let len = self.matches.len();
+        match action {
+            PickerAction::Up => self.selected = self.selected.saturating_sub(1),
+            PickerAction::Down => self.selected = (self.selected + 1).min(len.saturating_sub(1)),
+            PickerAction::Pick => {
+                return self.selected().map(|path| PickerEvent::Picked(path.into()))
+            }
+            PickerAction::Cancel => return Some(PickerEvent::Cancel),
+        }
+        None
+    }
+
+    fn refilter(&mut self) {
+        let mut scored: Vec<(i64, usize)> = self
+            .files
+            .iter()
+            .enumerate()
+            .filter_map(|(index, path)| {
+                let score = fuzzy_score(&self.query, &path.to_string_lossy())?;
+                Some((score, index))
+            })
+            .collect();
+        // stable sort keeps the listing order for equal scores
+        scored.sort_by_key(|&(score, _)| -score);
+        self.matches = scored.into_iter().map(|(_, index)| index).collect();
+        self.selected = self.selected.min(self.matches.len().saturating_sub(1));
+    }
+
+    fn draw_picker(&self, canvas: &mut dyn Canvas, resized: bool) -> Result<()> {
+        let (width, height) = canvas.size()?;
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+
+        let prompt_attr = self
+            .styles
+            .resolve("picker.prompt", Attr::from(Effect::BOLD));
+        let selected_attr = self
+            .styles
+            .resolve("picker.selected", Attr::from(Effect::REVERSE));
+        let count_attr = self.styles.resolve("picker.count", Attr::from(Effect::DIM));
+
+        let col = canvas.print_with_attr(0, 0, PROMPT, prompt_attr)?;
+        let col = col + canvas.print_with_attr(0, col, &self.query, prompt_attr)?;
+        let count = format!(" {}/{}", self.matches.len(), self.files.len());
+        let count_col = width.saturating_sub(display_width(&count));
+        if count_col > col {
+            canvas.print_with_attr(0, count_col, &count, count_attr)?;
+        }
+        canvas.set_cursor(0, col.min(width - 1))?;
+
+        // keep the selected file in view, from the top again once resized
+        let list_height = height - 1;
+        let mut offset = if resized { 0 } else { self.offset.get() };
+        if self.selected < offset {
+            offset = self.selected;
+        } else if self.selected >= offset + list_height {
+            offset = self.selected + 1 - list_height;
+        }
+        self.offset.set(offset);
+
+        for (index, path) in self.matches().enumerate().skip(offset).take(list_height) {
+            let row = index - offset + 1;
+            let line = format!("  {}", path.display());
+            if index == self.selected {
+                canvas.print_with_attr(row, 0, &format!("{:width$}", line), selected_attr)?;
+            } else {
+                canvas.print(row, 0, &line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Draw for FilePicker {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
+        self.draw_picker(canvas, false)
+    }
+
+    fn draw_with_context(&self, canvas: &mut dyn Canvas, ctx: &DrawContext) -> Result<()> {
+        self.draw_picker(canvas, ctx.resized)
+    }
+}
+
+/// The files under `root` (recursively, at most `limit`), relative to it and sorted. The hidden
+/// files and directories (starting with `.`) are skipped, as are the unreadable directories.
+pub fn list_files(root: &Path, limit: usize) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![PathBuf::new()];
+    fs::read_dir(root)?;
+    while let Some(dir) = dirs.pop() {
+        let entries = match fs::read_dir(root.join(&dir)) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = dir.join(entry.file_name());
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => dirs.push(path),
+                Ok(_) if files.len() < limit => files.push(path),
+                _ => {}
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::{Event, KeyEvent};
+    use crate::screen::Screen;
+    use crate::term::{Term, TermHeight, TermOptions};
+    use crate::testing::FakeTerminal;
+    use std::time::Duration;
+
+    const TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// type `bytes` on the terminal, feed the `count` keys read to the picker, then draw it
+    fn type_keys(
+        fake: &FakeTerminal,
+        term: &Term,
+        picker: &mut FilePicker,
+        bytes: &[u8],
+        count: usize,
+    ) -> Option<PickerEvent> {
+        fake.type_bytes(bytes).unwrap();
+        let mut event = None;
+        let mut keys = 0;
+        while keys < count {
+            if let Event::Key(KeyEvent { key, .. }) = term.peek_event(TIMEOUT).unwrap() {
+                event = event.or(picker.on_key(key));
+                keys += 1;
+            }
+        }
+        term.clear().unwrap();
+        term.draw(picker).unwrap();
+        term.present().unwrap();
+        event
+    }
+
+    fn row_text(screen: &Screen, row: usize) -> String {
+        let text: String = screen
+            .iter_cell()
+            .filter(|(r, _, _)| *r == row)
+            .map(|(_, _, cell)| cell.ch)
+            .collect();
+        text.trim_end().to_string()
+    }
+
+    #[test]
+    fn pick_a_file() {
+        let root = std::env::temp_dir().join(format!("tuikit-picker-{}", std::process::id()));
+        fs::create_dir_all(root.join("src/widget")).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        for file in &["Cargo.toml", "src/lib.rs", "src/widget/mod.rs", ".git/HEAD"] {
+            fs::write(root.join(file), "").unwrap();
+        }
+        let files = list_files(&root, 10).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+        let names: Vec<String> = files.iter().map(|f| f.display().to_string()).collect();
+        assert_eq!(vec!["Cargo.toml", "src/lib.rs", "src/widget/mod.rs"], names);
+
+        let mut picker = FilePicker::new(files);
+        for ch in "srs".chars() {
+            picker.on_key(Key::Char(ch));
+        }
+        let matches: Vec<&Path> = picker.matches().collect();
+        assert_eq!(
+            vec![Path::new("src/lib.rs"), Path::new("src/widget/mod.rs")],
+            matches
+        );
+
+        // scrolled to keep the selection in view, and back to the top on resize
+        let mut screen = Screen::new(20, 2);
+        picker.on_key(Key::Down);
+        picker.draw(&mut screen).unwrap();
+        assert_eq!("> srs            2/3", row_text(&screen, 0));
+        assert_eq!("  src/widget/mod.rs", row_text(&screen, 1));
+        let mut screen = Screen::new(20, 3);
+        picker
+            .draw_with_context(&mut screen, &DrawContext::new(20, 3).resized(true))
+            .unwrap();
+        assert_eq!("  src/lib.rs", row_text(&screen, 1));
+
+        picker.on_key(Key::Backspace);
+        assert_eq!("sr", picker.query());
+        assert_eq!(
+            Some(PickerEvent::Picked(PathBuf::from("src/lib.rs"))),
+            picker.on_key(Key::Enter)
+        );
+        assert_eq!(Some(PickerEvent::Cancel), picker.on_key(Key::ESC));
+    }
+
+    #[test]
+    fn filter_and_pick_on_a_terminal() {
+        let files = ["Cargo.toml", "README.md", "src/lib.rs", "src/widget/mod.rs"];
+        let files = || files.iter().map(PathBuf::from).collect();
+        let fake = FakeTerminal::new(24, 4).unwrap();
+        let options = TermOptions::default()
+            .height(TermHeight::Fixed(4))
+            .tty_path(fake.path());
+        let term = Term::with_options(options).unwrap();
+        let lines = || -> Vec<String> {
+            term.snapshot()
+                .text()
+                .trim_end()
+                .lines()
+                .map(String::from)
+                .collect()
+        };
+        let is_selected = |row: usize| {
+            let snapshot = term.snapshot();
+            let attr = snapshot.cell(row, 0).unwrap().attr;
+            attr.effect.contains(Effect::REVERSE)
+        };
+
+        let mut picker = FilePicker::new(files());
+        type_keys(&fake, &term, &mut picker, b"src", 3);
+        assert_eq!(
+            vec![
+                "> src                2/4",
+                "  src/lib.rs",
+                "  src/widget/mod.rs"
+            ],
+            lines()
+        );
+        assert!(is_selected(1) && !is_selected(2));
+
+        // selected with the arrows, and the query edited
+        type_keys(&fake, &term, &mut picker, b"\x1b[B", 1);
+        assert!(!is_selected(1) && is_selected(2));
+        type_keys(&fake, &term, &mut picker, b"\x7f\x7f\x7fREAD", 7);
+        assert_eq!(vec!["> READ               1/4", "  README.md"], lines());
+        type_keys(
+            &fake,
+            &term,
+            &mut picker,
+            b"\x7f\x7f\x7f\x7f\x1b[B\x1b[B\x1b[A",
+            7,
+        );
+        assert!(is_selected(2));
+        assert_eq!(
+            Some(PickerEvent::Picked(PathBuf::from("README.md"))),
+            type_keys(&fake, &term, &mut picker, b"\r", 1)
+        );
+
+        let mut picker = FilePicker::new(files());
+        assert_eq!(
+            Some(PickerEvent::Cancel),
+            type_keys(&fake, &term, &mut picker, b"\x07", 1)
+        );
+    }
+
+    #[test]
+    fn paste_query() {
+        let files = vec![
+            PathBuf::from("src/lib.rs"),
+            PathBuf::from("src/widget/mod.rs"),
+        ];
+        let mut picker = FilePicker::new(files);
+        picker.on_key(Key::Down);
+        picker.on_key(Key::BracketedPaste("li\x1bb\r\n".to_string()));
+        assert_eq!("lib", picker.query());
+        assert_eq!(Some(Path::new("src/lib.rs")), picker.selected());
+    }
+}
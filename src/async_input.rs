@@ -0,0 +1,310 @@
+//! The keys as a `Stream`, for async applications (feature `async`).
+//!
+//! ```no_run
+//! use tuikit::async_input::AsyncKeyBoard;
+//! use tuikit::input::KeyBoard;
+//! use tuikit::key::Key;
+//!
+//! async fn run() {
+//!     let mut keyboard = AsyncKeyBoard::new(KeyBoard::new_with_tty());
+//!     while let Some(key) = keyboard.next_key().await {
+//!         if key == Key::Char('q') {
+//!             break;
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! The keys already typed are read at once, without blocking. Without a dependency on a
+//! runtime, the readiness of the input is waited for by a helper thread while the stream is
+//! pending, it only `select`s and never reads a key. An application owning a reactor could
+//! rather register the fd of the `KeyBoard` (it implements `AsRawFd`) and call
+//! `KeyBoard::try_next_key` once it is readable.
+//!
+//! `KeyboardHandler::interrupt` ends the wait of `read_key` with `InputError::Interrupted`, the
+//! stream goes on waiting.
+
+use crate::input::{Deadline, InputError, KeyBoard, Result};
+use crate::key::Key;
+use crate::sys::file::wait_until_ready;
+use futures_core::Stream;
+use std::fs::File;
+use std::future::Future;
+use std::io::prelude::*;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::pin::Pin;
+use std::sync::mpsc::{channel, Sender};
+use std::task::{Context, Poll, Waker};
+use std::thread::{self, JoinHandle};
+
+/// A `KeyBoard` read asynchronously, a stream of keys ending once the input is closed
+pub struct AsyncKeyBoard {
+    /// dropped before the keyboard, see `Watcher::drop`
+    watcher: Option<Watcher>,
+    keyboard: KeyBoard,
+    /// whether the last poll was pending, an interrupt since then ending the wait
+    waiting: bool,
+}
+
+impl AsyncKeyBoard {
+    pub fn new(keyboard: KeyBoard) -> Self {
+        Self {
+            watcher: None,
+            keyboard,
+            waiting: false,
+        }
+    }
+
+    /// the keyboard, e.g. to poll it without waiting
+    pub fn keyboard_mut(&mut self) -> &mut KeyBoard {
+        &mut self.keyboard
+    }
+
+    /// The next key, `None` once the input is closed. An interrupt doesn't end the wait.
+    pub fn next_key(&mut self) -> impl Future<Output = Option<Key>> + '_ {
+        std::future::poll_fn(move |cx| self.poll_key(cx))
+    }
+
+    /// The next key, or why none was read: `Interrupted` by `KeyboardHandler::interrupt` while
+    /// it was waited for, `Io` once the input is closed. The unknown sequences are skipped.
+    pub fn read_key(&mut self) -> impl Future<Output = Result<Key>> + '_ {
+        std::future::poll_fn(move |cx| self.poll_read(cx))
+    }
+
+    fn poll_key(&mut self, cx: &mut Context) -> Poll<Option<Key>> {
+        loop {
+            match self.poll_read(cx) {
+                Poll::Ready(Ok(key)) => return Poll::Ready(Some(key)),
+                Poll::Ready(Err(InputError::Interrupted)) => continue,
+                Poll::Ready(Err(_)) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+
+    fn poll_read(&mut self, cx: &mut Context) -> Poll<Result<Key>> {
+        // an interrupt sent while no task waited is cleared by the read, as by `KeyBoard`
+        if std::mem::take(&mut self.waiting) && self.keyboard.take_interrupt() {
+            return Poll::Ready(Err(InputError::Interrupted));
+        }
+        loop {
+            match self.keyboard.next_key_until(Deadline::Poll) {
+                // the keys after it may already be there
+                Err(InputError::UnknownSequence(_)) => continue,
+                Err(InputError::Timeout) => break,
+                result => return Poll::Ready(result),
+            }
+        }
+
+        let fds = [self.keyboard.as_raw_fd(), self.keyboard.interrupt_fd()];
+        let watcher = self.watcher.get_or_insert_with(|| Watcher::start(fds));
+        watcher.wake_when_ready(cx.waker().clone());
+        self.waiting = true;
+        Poll::Pending
+    }
+}
+
+impl Stream for AsyncKeyBoard {
+    type Item = Key;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Key>> {
+        self.get_mut().poll_key(cx)
+    }
+}
+
+/// Wakes the tasks waiting for a key once the input is readable, or the keyboard interrupted
+struct Watcher {
+    wakers: Option<Sender<Waker>>,
+    /// the self-pipe ending the wait on drop
+    stop: File,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Watcher {
+    /// `fds`: the input and the interrupt pipe of the keyboard
+    fn start([fd, interrupt_fd]: [RawFd; 2]) -> Self {
+        let (stop_rx, stop_tx) = nix::unistd::pipe().expect("failed to set pipe");
+        let stop_rx = unsafe { File::from_raw_fd(stop_rx) };
+        let (wakers, waker_rx) = channel::<Waker>();
+        let thread = thread::spawn(move || {
+            for waker in waker_rx {
+                // the input is level-triggered: a key typed since the last read wakes at once
+                let ready = wait_until_ready(fd, &[interrupt_fd, stop_rx.as_raw_fd()], None);
+                waker.wake();
+                if ready.is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            wakers: Some(wakers),
+            stop: unsafe { File::from_raw_fd(stop_tx) },
+            thread: Some(thread),
+        }
+    }
+
+    fn wake_when_ready(&self, waker: Waker) {
+        if let Some(wakers) = self.wakers.as_ref() {
+            let _ = wakers.send(waker);
+        }
+    }
+}
+
+impl Drop for Watcher {
+    /// stop the thread before the input it waits for is closed
+    fn drop(&mut self) {
+        self.wakers.take();
+        let _ = self.stop.write_all(b"x");
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::mpsc::Receiver;
+    use std::sync::Arc;
+    use std::task::Wake;
+    use std::thread::Thread;
+    use std::time::Duration;
+
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    struct Notify(Sender<()>);
+
+    impl Wake for Notify {
+        fn wake(self: Arc<Self>) {
+            let _ = self.0.send(());
+        }
+    }
+
+    fn keyboard_with_input() -> (AsyncKeyBoard, File) {
+        let (rx, tx) = nix::unistd::pipe().unwrap();
+        let tx = unsafe { File::from_raw_fd(tx) };
+        let rx = unsafe { File::from_raw_fd(rx) };
+        (AsyncKeyBoard::new(KeyBoard::new(Box::new(rx))), tx)
+    }
+
+    /// a waker sending to the receiver
+    fn notify() -> (Waker, Receiver<()>) {
+        let (tx, rx) = channel();
+        (Waker::from(Arc::new(Notify(tx))), rx)
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = Box::pin(future);
+        let waker = Waker::from(Arc::new(Unpark(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    #[test]
+    fn keys_as_a_stream() {
+        let (mut keyboard, mut tx) = keyboard_with_input();
+        tx.write_all(b"a").unwrap();
+        assert_eq!(Some(Key::Char('a')), block_on(keyboard.next_key()));
+
+        // pending until typed, then ends once the input is closed
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            tx.write_all(b"\x1b[0b").unwrap();
+        });
+        assert_eq!(Some(Key::Char('b')), block_on(keyboard.next_key()));
+        assert_eq!(None, block_on(keyboard.next_key()));
+    }
+
+    #[test]
+    fn wake_up_on_new_input() {
+        let (mut keyboard, mut tx) = keyboard_with_input();
+        let (waker, woken) = notify();
+        let mut cx = Context::from_waker(&waker);
+        let mut next = Pin::new(&mut keyboard);
+        assert_eq!(Poll::Pending, next.as_mut().poll_next(&mut cx));
+        assert!(woken.recv_timeout(Duration::from_millis(50)).is_err());
+
+        tx.write_all(b"a").unwrap();
+        assert!(woken.recv_timeout(Duration::from_secs(2)).is_ok());
+        assert_eq!(
+            Poll::Ready(Some(Key::Char('a'))),
+            next.as_mut().poll_next(&mut cx)
+        );
+
+        // woken again by the next key only
+        assert_eq!(Poll::Pending, next.as_mut().poll_next(&mut cx));
+        assert!(woken.recv_timeout(Duration::from_millis(50)).is_err());
+        tx.write_all(b"bc").unwrap();
+        assert!(woken.recv_timeout(Duration::from_secs(2)).is_ok());
+        assert_eq!(
+            Poll::Ready(Some(Key::Char('b'))),
+            next.as_mut().poll_next(&mut cx)
+        );
+        assert_eq!(
+            Poll::Ready(Some(Key::Char('c'))),
+            next.as_mut().poll_next(&mut cx)
+        );
+    }
+
+    #[test]
+    fn end_once_the_keyboard_closes() {
+        // the keys typed are read before the end
+        let (mut keyboard, mut tx) = keyboard_with_input();
+        tx.write_all(b"ab").unwrap();
+        drop(tx);
+        assert_eq!(Some(Key::Char('a')), block_on(keyboard.next_key()));
+        assert_eq!(Some(Key::Char('b')), block_on(keyboard.next_key()));
+        assert_eq!(None, block_on(keyboard.next_key()));
+        assert_eq!(None, block_on(keyboard.next_key()));
+        assert!(block_on(keyboard.read_key()).unwrap_err().is_closed());
+
+        // closed while waited for
+        let (mut keyboard, tx) = keyboard_with_input();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            drop(tx);
+        });
+        assert_eq!(None, block_on(keyboard.next_key()));
+    }
+
+    #[test]
+    fn interrupt_the_wait() {
+        let (mut keyboard, mut tx) = keyboard_with_input();
+        let handler = keyboard.keyboard_mut().get_interrupt_handler();
+        let interrupt = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            handler.interrupt();
+            handler
+        });
+        assert!(matches!(
+            block_on(keyboard.read_key()),
+            Err(InputError::Interrupted)
+        ));
+        let handler = interrupt.join().unwrap();
+
+        // sent while no task waited, it is cleared by the next read
+        handler.interrupt();
+        tx.write_all(b"a").unwrap();
+        assert_eq!(Key::Char('a'), block_on(keyboard.read_key()).unwrap());
+
+        // the stream goes on waiting
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            handler.interrupt();
+            thread::sleep(Duration::from_millis(50));
+            tx.write_all(b"b").unwrap();
+        });
+        assert_eq!(Some(Key::Char('b')), block_on(keyboard.next_key()));
+    }
+}
@@ -2,7 +2,7 @@
 
 use bitflags::bitflags;
 
-pub use crate::color::Color;
+pub use crate::color::{Color, ParseColorError};
 
 /// `Attr` is a rendering attribute that contains fg color, bg color and text effect.
 ///
@@ -11,7 +11,7 @@ pub use crate::color::Color;
 ///
 /// let attr = Attr { fg: Color::RED, effect: Effect::BOLD, ..Attr::default() };
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Attr {
     pub fg: Color,
     pub bg: Color,
@@ -86,12 +86,18 @@ bitflags! {
 
 impl From<Color> for Attr {
     fn from(fg: Color) -> Self {
-        Attr { fg, ..Default::default() }
+        Attr {
+            fg,
+            ..Default::default()
+        }
     }
 }
 
 impl From<Effect> for Attr {
     fn from(effect: Effect) -> Self {
-        Attr { effect, ..Default::default() }
+        Attr {
+            effect,
+            ..Default::default()
+        }
     }
 }
@@ -1,10 +1,62 @@
-///! A canvas is a trait defining the draw actions
+//! A canvas is a trait defining the draw actions
 use crate::attr::Attr;
 use crate::cell::Cell;
-use std::error::Error;
-use unicode_width::UnicodeWidthChar;
+use crate::region::Region;
+use crate::width::{char_width, clusters, display_width, truncate_to_width};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::error::Error;
 
-pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
+pub type Result<T> = core::result::Result<T, Box<dyn Error>>;
+
+/// An item of `Canvas::print_batch`: `(row, col, text, attr)`
+pub type PrintItem<'t> = (usize, usize, &'t str, Attr);
+
+/// Where a value sits in its column, see `Canvas::print_columns`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// A column of `Canvas::print_columns`: `width` cells, with `padding` blank cells on each
+/// side of the value, all painted with `attr`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct ColumnSpec {
+    pub width: usize,
+    pub align: Align,
+    pub padding: usize,
+    pub attr: Attr,
+}
+
+impl ColumnSpec {
+    pub fn new(width: usize) -> Self {
+        Self {
+            width,
+            ..Self::default()
+        }
+    }
+}
+
+// Builder
+impl ColumnSpec {
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn padding(mut self, padding: usize) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn attr(mut self, attr: Attr) -> Self {
+        self.attr = attr;
+        self
+    }
+}
 
 pub trait Canvas {
     /// Get the canvas size (width, height)
@@ -33,6 +85,7 @@ pub trait Canvas {
     /// print `content` starting with position `(row, col)` with `attr`
     /// - canvas should NOT wrap to y+1 if the content is too long
     /// - canvas should handle wide characters
+    ///
     /// return the printed width of the content
     fn print_with_attr(
         &mut self,
@@ -59,18 +112,123 @@ pub trait Canvas {
         self.print_with_attr(row, col, content, Attr::default())
     }
 
+    /// Print `values` on `row` in the columns of `specs`, side by side from the column 0: a
+    /// value is truncated to its column, never splitting a wide character, and the rest of
+    /// the column is filled with spaces. The values without a spec are ignored.
+    ///
+    /// return the printed width
+    fn print_columns(
+        &mut self,
+        row: usize,
+        specs: &[ColumnSpec],
+        values: &[&str],
+    ) -> Result<usize> {
+        let mut col = 0;
+        for (spec, value) in specs.iter().zip(values) {
+            let inner = spec.width.saturating_sub(2 * spec.padding);
+            let value = truncate_to_width(value, inner);
+            let blank = inner - display_width(value);
+            let before = spec.padding
+                + match spec.align {
+                    Align::Left => 0,
+                    Align::Center => blank / 2,
+                    Align::Right => blank,
+                };
+
+            let cell = Cell {
+                ch: ' ',
+                attr: spec.attr,
+            };
+            for offset in 0..spec.width {
+                self.put_cell(row, col + offset, cell)?;
+            }
+            self.print_with_attr(row, col + before, value, spec.attr)?;
+            col += spec.width;
+        }
+        Ok(col)
+    }
+
+    /// print `content` like `print_with_attr`, a tab moving to the next multiple of
+    /// `tab_width` from `col`, like the tab stops of a terminal
+    ///
+    /// return the printed width of the content
+    fn print_with_tabs(
+        &mut self,
+        row: usize,
+        col: usize,
+        content: &str,
+        attr: Attr,
+        tab_width: usize,
+    ) -> Result<usize> {
+        let mut width = 0;
+        for (index, part) in content.split('\t').enumerate() {
+            if index > 0 && tab_width > 0 {
+                width = (width / tab_width + 1) * tab_width;
+            }
+            width += self.print_with_attr(row, col + width, part, attr)?;
+        }
+        Ok(width)
+    }
+
+    /// Print every item like `print_with_attr` in order, for the views issuing thousands of
+    /// prints per frame: the canvas handles them row by row, with the bounds checked once per
+    /// row and, for `Term`, the lock taken once. On a `dyn Canvas`, use `print_batch_slice`.
+    fn print_batch<'t>(&mut self, batch: impl IntoIterator<Item = PrintItem<'t>>) -> Result<()>
+    where
+        Self: Sized,
+    {
+        let mut batch: Vec<PrintItem> = batch.into_iter().collect();
+        self.print_batch_slice(&mut batch)
+    }
+
+    /// `print_batch` on a slice (also for trait objects), which is sorted by row. The items of
+    /// a row keep their order, so the later ones still overwrite the earlier ones.
+    fn print_batch_slice(&mut self, batch: &mut [PrintItem]) -> Result<()> {
+        sort_by_row(batch);
+        for &(row, col, text, attr) in batch.iter() {
+            self.print_with_attr(row, col, text, attr)?;
+        }
+        Ok(())
+    }
+
     /// move cursor position (row, col) and show cursor
     fn set_cursor(&mut self, row: usize, col: usize) -> Result<()>;
 
     /// show/hide cursor, set `show` to `false` to hide the cursor
     fn show_cursor(&mut self, show: bool) -> Result<()>;
+
+    /// register an actionable region, see `tuikit::region`. Ignored by default.
+    fn register_region(&mut self, _region: Region) -> Result<()> {
+        Ok(())
+    }
+
+    /// Hint that `row` changes on every frame so it is not worth hashing with
+    /// `DiffStrategy::RowHash`. Ignored by default.
+    fn mark_volatile(&mut self, _row: usize) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// stable, and free when already sorted
+pub(crate) fn sort_by_row(batch: &mut [PrintItem]) {
+    if batch.windows(2).any(|pair| pair[0].0 > pair[1].0) {
+        batch.sort_by_key(|item| item.0);
+    }
+}
+
+/// the part of `text` whose characters start before the column `cols`
+fn clip_text(text: &str, cols: usize) -> &str {
+    match clusters(text).find(|cluster| cluster.col >= cols) {
+        Some(cluster) => &text[..cluster.index],
+        None => text,
+    }
 }
 
 /// A sub-area of a canvas.
 /// It will handle the adjustments of cursor movement, so that you could write
 /// to for example (0, 0) and BoundedCanvas will adjust it to real position.
 pub struct BoundedCanvas<'a> {
-    canvas: &'a mut Canvas,
+    canvas: &'a mut dyn Canvas,
     top: usize,
     left: usize,
     width: usize,
@@ -83,7 +241,7 @@ impl<'a> BoundedCanvas<'a> {
         left: usize,
         width: usize,
         height: usize,
-        canvas: &'a mut Canvas,
+        canvas: &'a mut dyn Canvas,
     ) -> Self {
         Self {
             canvas,
@@ -113,7 +271,7 @@ impl<'a> Canvas for BoundedCanvas<'a> {
     fn put_cell(&mut self, row: usize, col: usize, cell: Cell) -> Result<usize> {
         if row >= self.height || col >= self.width {
             // do nothing
-            Ok(cell.ch.width().unwrap_or(2))
+            Ok(char_width(cell.ch))
         } else {
             self.canvas.put_cell(row + self.top, col + self.left, cell)
         }
@@ -131,4 +289,31 @@ impl<'a> Canvas for BoundedCanvas<'a> {
     fn show_cursor(&mut self, show: bool) -> Result<()> {
         self.canvas.show_cursor(show)
     }
+
+    fn print_batch_slice(&mut self, batch: &mut [PrintItem]) -> Result<()> {
+        let mut translated: Vec<PrintItem> = batch
+            .iter()
+            .filter(|&&(row, col, ..)| row < self.height && col < self.width)
+            .map(|&(row, col, text, attr)| {
+                let text = clip_text(text, self.width - col);
+                (row + self.top, col + self.left, text, attr)
+            })
+            .collect();
+        self.canvas.print_batch_slice(&mut translated)
+    }
+
+    fn register_region(&mut self, region: Region) -> Result<()> {
+        match region.clip(self.top, self.left, self.width, self.height) {
+            Some(region) => self.canvas.register_region(region),
+            None => Ok(()),
+        }
+    }
+
+    fn mark_volatile(&mut self, row: usize) -> Result<()> {
+        if row >= self.height {
+            Ok(())
+        } else {
+            self.canvas.mark_volatile(row + self.top)
+        }
+    }
 }
@@ -0,0 +1,273 @@
+//! What the terminal is known to support.
+//!
+//! Some capabilities are only known by asking the terminal (e.g. the device attributes or the
+//! background color). Waiting for the replies would delay the startup, on slow links by a lot,
+//! so `Term` sends the queries without waiting (see `TermOptions::probe_capabilities`) and
+//! returns `TerminalEvent::CapabilitiesUpdated` whenever a reply arrives, the UI could then upgrade its
+//! features on the fly.
+//!
+//! The terminfo capabilities could be asked as well (XTGETTCAP, see `Term::query_termcap`),
+//! which is more reliable than the local terminfo under tmux or with an overridden `$TERM`.
+//!
+//! Under screen or tmux (`$TERM=screen*` or `tmux*`) the local terminfo describes the
+//! multiplexer, which may not pass RGB colors through. `Capabilities::attr_support` then
+//! assumes 256 colors until the terminal confirms `RGB` or `Tc`, and the effects it reports
+//! missing are not rendered, so the colors degrade to their nearest instead of being garbled.
+//! `TermOptions::color_depth` (or the `color_depth` user config) overrides the detection.
+//!
+//! On a monochrome terminal the colors are dropped, and with them what they told apart, e.g.
+//! the selected row of a list. The attributes relying on colors get effects instead, see
+//! `MONOCHROME_FALLBACK`, so that every widget still shows its selection.
+
+use crate::attr::{Attr, Color, Effect};
+use crate::config::ColorDepth;
+use crate::key::Key;
+use std::collections::BTreeMap;
+use std::env;
+
+/// The terminfo capabilities asked with `probe_capabilities`: 24-bit colors (`RGB`, or `Tc` for
+/// tmux), styled underlines (`Smulx`, `Su`) and the effects which terminals tend to lack (`dim`,
+/// `blink`)
+pub const TERMCAP_PROBES: &[&str] = &["RGB", "Tc", "Smulx", "Su", "dim", "blink"];
+
+/// The terminfo capabilities of the effects, those reported missing are not rendered
+const EFFECT_CAPS: &[(Effect, &str)] = &[
+    (Effect::BOLD, "bold"),
+    (Effect::DIM, "dim"),
+    (Effect::UNDERLINE, "smul"),
+    (Effect::BLINK, "blink"),
+    (Effect::REVERSE, "rev"),
+];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    /// 24-bit colors, from `COLORTERM` or the `RGB`/`Tc` capabilities
+    pub truecolor: bool,
+    /// styled (curly, dotted...) underlines, from the `Smulx`/`Su` capabilities
+    pub styled_underline: bool,
+    /// the reply to the primary device attributes (DA1) query, e.g. `[62, 22]`
+    pub device_attributes: Option<Vec<u16>>,
+    /// the reply to the background color (OSC 11) query
+    pub background: Option<(u8, u8, u8)>,
+    /// the flags of the kitty keyboard protocol in effect, the reply to its query, see
+    /// `TermOptions::kitty_keyboard`
+    pub keyboard_flags: Option<u8>,
+    /// setting the clipboard with OSC 52, assumed unless `TERM` is known not to
+    pub osc52: bool,
+    /// running over SSH, from `SSH_CONNECTION` or `SSH_TTY`
+    pub remote: bool,
+    /// running under screen or tmux, from `TERM`
+    pub multiplexer: bool,
+    /// the replies to XTGETTCAP by name, `None` if the terminal doesn't know the capability,
+    /// the empty string for a boolean one
+    pub termcap: BTreeMap<String, Option<String>>,
+}
+
+impl Capabilities {
+    /// the capabilities known without asking the terminal
+    pub fn from_env() -> Self {
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        let term = env::var("TERM").unwrap_or_default();
+        Self {
+            truecolor: colorterm == "truecolor" || colorterm == "24bit",
+            osc52: !matches!(term.as_str(), "" | "dumb" | "linux"),
+            remote: env::var_os("SSH_CONNECTION").is_some() || env::var_os("SSH_TTY").is_some(),
+            multiplexer: term.starts_with("screen") || term.starts_with("tmux"),
+            ..Self::default()
+        }
+    }
+
+    /// whether the terminal has the capability `name`, if it answered the XTGETTCAP query
+    pub fn has_termcap(&self, name: &str) -> Option<bool> {
+        self.termcap.get(name).map(Option::is_some)
+    }
+
+    /// What could be rendered, `color_depth` overrides the detected colors. Without a
+    /// multiplexer the RGB colors are assumed to work, as they do with most terminals.
+    pub fn attr_support(&self, color_depth: Option<ColorDepth>) -> AttrSupport {
+        let detected = if self.truecolor || !self.multiplexer {
+            ColorDepth::TrueColor
+        } else {
+            ColorDepth::Ansi256
+        };
+        let mut effects = Effect::all();
+        for (effect, name) in EFFECT_CAPS {
+            if self.has_termcap(name) == Some(false) {
+                effects.remove(*effect);
+            }
+        }
+        AttrSupport {
+            color_depth: color_depth.unwrap_or(detected),
+            effects,
+        }
+    }
+
+    /// whether the background is dark, if the background color is known
+    pub fn is_dark_background(&self) -> Option<bool> {
+        self.background.map(|(r, g, b)| {
+            let luma = 299 * r as u32 + 587 * g as u32 + 114 * b as u32;
+            luma < 128 * 1000
+        })
+    }
+
+    /// Record the reply carried by `key`, return `false` if it is not a reply
+    pub(crate) fn update(&mut self, key: &Key) -> bool {
+        match key {
+            Key::DeviceAttributes(attributes) => {
+                self.device_attributes = Some(attributes.clone());
+                true
+            }
+            Key::BackgroundColor(r, g, b) => {
+                self.background = Some((*r, *g, *b));
+                true
+            }
+            Key::KeyboardFlags(flags) => {
+                self.keyboard_flags = Some(*flags);
+                true
+            }
+            Key::TermcapReply(replies) => {
+                for (name, value) in replies {
+                    if value.is_some() {
+                        match name.as_str() {
+                            "RGB" | "Tc" => self.truecolor = true,
+                            "Smulx" | "Su" => self.styled_underline = true,
+                            _ => {}
+                        }
+                    }
+                    self.termcap.insert(name.clone(), value.clone());
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The effects standing in for the colors on a monochrome terminal: with a background color
+/// (a selection, a highlighted row), or only a foreground one (a match, an error)
+const MONOCHROME_FALLBACK: &[(bool, bool, Effect)] = &[
+    // (has a background, has a foreground, effect)
+    (true, true, Effect::REVERSE),
+    (true, false, Effect::REVERSE),
+    (false, true, Effect::BOLD),
+];
+
+/// The colors and effects the terminal renders, see `Capabilities::attr_support`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttrSupport {
+    pub color_depth: ColorDepth,
+    pub effects: Effect,
+}
+
+impl Default for AttrSupport {
+    fn default() -> Self {
+        Self {
+            color_depth: ColorDepth::TrueColor,
+            effects: Effect::all(),
+        }
+    }
+}
+
+impl AttrSupport {
+    /// `attr` with the colors converted to the nearest supported and the unsupported effects
+    /// removed. Without colors, those of `attr` are replaced by effects, see
+    /// `MONOCHROME_FALLBACK`.
+    pub fn degrade(&self, attr: Attr) -> Attr {
+        let convert = |color: Color| match self.color_depth {
+            ColorDepth::TrueColor => color,
+            ColorDepth::Ansi256 => color.to_ansi256(),
+            ColorDepth::Ansi16 => color.to_ansi16(),
+            ColorDepth::Monochrome => Color::Default,
+        };
+        let mut effect = attr.effect;
+        if self.color_depth == ColorDepth::Monochrome {
+            let colors = (attr.bg != Color::Default, attr.fg != Color::Default);
+            for (has_bg, has_fg, fallback) in MONOCHROME_FALLBACK {
+                if colors == (*has_bg, *has_fg) {
+                    effect |= *fallback;
+                }
+            }
+        }
+        Attr {
+            fg: convert(attr.fg),
+            bg: convert(attr.bg),
+            effect: effect & self.effects,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn update_from_replies() {
+        let mut capabilities = Capabilities::default();
+        assert!(!capabilities.update(&Key::Char('a')));
+        assert_eq!(None, capabilities.is_dark_background());
+
+        assert!(capabilities.update(&Key::BackgroundColor(0x28, 0x2c, 0x34)));
+        assert_eq!(Some(true), capabilities.is_dark_background());
+        assert!(capabilities.update(&Key::DeviceAttributes(vec![62, 22])));
+        assert_eq!(Some(vec![62, 22]), capabilities.device_attributes);
+        assert!(capabilities.update(&Key::KeyboardFlags(1)));
+        assert_eq!(Some(1), capabilities.keyboard_flags);
+
+        assert!(capabilities.update(&Key::TermcapReply(vec![
+            ("Tc".to_string(), Some(String::new())),
+            ("Smulx".to_string(), None),
+        ])));
+        assert!(capabilities.truecolor);
+        assert!(!capabilities.styled_underline);
+        assert_eq!(Some(true), capabilities.has_termcap("Tc"));
+        assert_eq!(Some(false), capabilities.has_termcap("Smulx"));
+        assert_eq!(None, capabilities.has_termcap("Su"));
+    }
+
+    #[test]
+    fn degrade_under_multiplexer() {
+        let attr = Attr::default()
+            .fg(Color::Rgb(255, 0, 0))
+            .bg(Color::LIGHT_BLUE)
+            .effect(Effect::DIM | Effect::BOLD);
+        let mut capabilities = Capabilities::default();
+        assert_eq!(attr, capabilities.attr_support(None).degrade(attr));
+
+        capabilities.multiplexer = true;
+        let degraded = capabilities.attr_support(None).degrade(attr);
+        assert_eq!(Color::AnsiValue(196), degraded.fg);
+        assert_eq!(Color::LIGHT_BLUE, degraded.bg);
+
+        capabilities.update(&Key::TermcapReply(vec![
+            ("RGB".to_string(), Some(String::new())),
+            ("dim".to_string(), None),
+        ]));
+        let support = capabilities.attr_support(None);
+        assert_eq!(ColorDepth::TrueColor, support.color_depth);
+        assert_eq!(attr.effect(Effect::BOLD), support.degrade(attr));
+
+        // the user knows better
+        let support = capabilities.attr_support(Some(ColorDepth::Monochrome));
+        assert_eq!(Color::Default, support.degrade(attr).bg);
+    }
+
+    #[test]
+    fn monochrome_fallback() {
+        let support = AttrSupport {
+            color_depth: ColorDepth::Monochrome,
+            effects: Effect::all(),
+        };
+        let selected = Attr::from(Color::WHITE).bg(Color::BLUE);
+        assert_eq!(Attr::from(Effect::REVERSE), support.degrade(selected));
+        let matched = Attr::from(Color::RED).effect(Effect::UNDERLINE);
+        assert_eq!(
+            Attr::from(Effect::BOLD | Effect::UNDERLINE),
+            support.degrade(matched)
+        );
+        assert_eq!(Attr::default(), support.degrade(Attr::default()));
+
+        // with colors, as they are
+        let support = AttrSupport::default();
+        assert_eq!(selected, support.degrade(selected));
+    }
+}
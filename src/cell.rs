@@ -1,10 +1,10 @@
-///! `Cell` is a cell of the terminal.
-///! It has a display character and an attribute (fg and bg color, effects).
+//! `Cell` is a cell of the terminal.
+//! It has a display character and an attribute (fg and bg color, effects).
 use crate::attr::{Attr, Color, Effect};
 
 const EMPTY_CHAR: char = '\0';
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Cell {
     pub ch: char,
     pub attr: Attr,
@@ -57,6 +57,9 @@ impl Cell {
 
 impl From<char> for Cell {
     fn from(ch: char) -> Self {
-        Cell { ch, attr: Attr::default() }
+        Cell {
+            ch,
+            attr: Attr::default(),
+        }
     }
 }
@@ -0,0 +1,269 @@
+//! Copy to (and paste from) the system clipboard, wherever the application runs.
+//!
+//! There is no single way to reach the clipboard of the user: locally an external command
+//! (`pbcopy`, `wl-copy`, `xclip`, `xsel`) works with any terminal, while over SSH only the
+//! terminal itself could, through the OSC 52 escape sequence. `detect` picks a `Clipboard`
+//! from the capabilities, falling back to a clipboard kept in the process, so copying works
+//! the same for the application in both cases:
+//!
+//! ```no_run
+//! use tuikit::prelude::*;
+//!
+//! let term = Term::new().unwrap();
+//! term.copy_to_clipboard("copied").unwrap();
+//! ```
+//!
+//! `Term` detects its clipboard on start (see `Term::set_clipboard` to replace it), it is used
+//! by `CopyTarget::Clipboard` for the mouse selection.
+
+use crate::capability::Capabilities;
+use std::env;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+pub trait Clipboard: Send {
+    /// Set the clipboard to `text`, sequences for the terminal are written to `terminal`
+    fn copy(&mut self, text: &str, terminal: &mut dyn Write) -> io::Result<()>;
+
+    /// The text of the clipboard, `None` if it could not be read
+    fn paste(&mut self) -> Option<String>;
+
+    /// e.g. `"osc52"`, for diagnostics
+    fn name(&self) -> &str;
+
+    /// Whether `copy` waits on something slow, e.g. an external command. `Term` copies the
+    /// mouse selection to such a clipboard on a thread of its own, what it writes to `terminal`
+    /// is dropped then.
+    fn blocks(&self) -> bool {
+        false
+    }
+}
+
+/// The clipboard of the terminal, through OSC 52. Works over SSH, but it could not be read:
+/// terminals either don't answer the queries or ask the user first.
+#[derive(Debug, Default)]
+pub struct Osc52Clipboard;
+
+impl Clipboard for Osc52Clipboard {
+    fn copy(&mut self, text: &str, terminal: &mut dyn Write) -> io::Result<()> {
+        terminal.write_all(&osc52(text))
+    }
+
+    fn paste(&mut self) -> Option<String> {
+        None
+    }
+
+    fn name(&self) -> &str {
+        "osc52"
+    }
+}
+
+/// The clipboard reached through external commands, e.g. `xclip -selection clipboard`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandClipboard {
+    copy: Vec<String>,
+    paste: Option<Vec<String>>,
+}
+
+impl CommandClipboard {
+    /// `copy` reads the text from its stdin, `paste` writes it to its stdout
+    pub fn new(copy: &[&str], paste: Option<&[&str]>) -> Self {
+        let to_vec = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect();
+        Self {
+            copy: to_vec(copy),
+            paste: paste.map(to_vec),
+        }
+    }
+
+    /// The clipboard command of the platform found in `PATH`, trying `pbcopy` on macOS,
+    /// `wl-copy` under Wayland, then `xclip` and `xsel` under X11
+    pub fn detect() -> Option<Self> {
+        let path = env::var_os("PATH")?;
+        let installed =
+            |command: &str| env::split_paths(&path).any(|dir| dir.join(command).is_file());
+
+        let mut candidates = Vec::new();
+        if cfg!(target_os = "macos") {
+            candidates.push(Self::new(&["pbcopy"], Some(&["pbpaste"])));
+        }
+        if env::var_os("WAYLAND_DISPLAY").is_some() {
+            candidates.push(Self::new(&["wl-copy"], Some(&["wl-paste", "--no-newline"])));
+        }
+        if env::var_os("DISPLAY").is_some() {
+            candidates.push(Self::new(
+                &["xclip", "-selection", "clipboard"],
+                Some(&["xclip", "-selection", "clipboard", "-o"]),
+            ));
+            candidates.push(Self::new(
+                &["xsel", "--clipboard", "--input"],
+                Some(&["xsel", "--clipboard", "--output"]),
+            ));
+        }
+        candidates
+            .into_iter()
+            .find(|candidate| installed(&candidate.copy[0]))
+    }
+}
+
+impl Clipboard for CommandClipboard {
+    fn copy(&mut self, text: &str, _terminal: &mut dyn Write) -> io::Result<()> {
+        let mut child = Command::new(&self.copy[0])
+            .args(&self.copy[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        // dropping stdin closes it, the command copies on EOF
+        child
+            .stdin
+            .take()
+            .expect("copy: stdin is piped")
+            .write_all(text.as_bytes())?;
+        let status = child.wait()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!(
+                "{} failed: {}",
+                self.copy[0], status
+            )))
+        }
+    }
+
+    fn paste(&mut self) -> Option<String> {
+        let paste = self.paste.as_ref()?;
+        let output = Command::new(&paste[0])
+            .args(&paste[1..])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        if output.status.success() {
+            String::from_utf8(output.stdout).ok()
+        } else {
+            None
+        }
+    }
+
+    fn name(&self) -> &str {
+        Path::new(&self.copy[0])
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("command")
+    }
+
+    fn blocks(&self) -> bool {
+        true
+    }
+}
+
+/// A clipboard kept in the process, when no other is available: copy and paste still work
+/// within the application
+#[derive(Debug, Default)]
+pub struct MemoryClipboard {
+    text: Option<String>,
+}
+
+impl Clipboard for MemoryClipboard {
+    fn copy(&mut self, text: &str, _terminal: &mut dyn Write) -> io::Result<()> {
+        self.text = Some(text.to_string());
+        Ok(())
+    }
+
+    fn paste(&mut self) -> Option<String> {
+        self.text.clone()
+    }
+
+    fn name(&self) -> &str {
+        "memory"
+    }
+}
+
+/// Pick the clipboard for `capabilities`: OSC 52 over SSH (a local command would reach the
+/// clipboard of the server), otherwise an external command if there is one, otherwise OSC 52
+/// if the terminal supports it, otherwise the in-process clipboard.
+pub fn detect(capabilities: &Capabilities) -> Box<dyn Clipboard> {
+    if capabilities.remote && capabilities.osc52 {
+        return Box::new(Osc52Clipboard);
+    }
+    if let Some(command) = CommandClipboard::detect() {
+        return Box::new(command);
+    }
+    if capabilities.osc52 {
+        return Box::new(Osc52Clipboard);
+    }
+    Box::<MemoryClipboard>::default()
+}
+
+/// The OSC 52 sequence setting the clipboard to `text`
+pub fn osc52(text: &str) -> Vec<u8> {
+    let mut sequence = b"\x1b]52;c;".to_vec();
+    sequence.extend(base64(text.as_bytes()).as_bytes());
+    sequence.push(b'\x07');
+    sequence
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn osc52_sequence() {
+        assert_eq!(b"\x1b]52;c;aGk=\x07".to_vec(), osc52("hi"));
+        assert_eq!("aGVsbG8=", base64(b"hello"));
+        assert_eq!("YWJj", base64(b"abc"));
+
+        let mut terminal = Vec::new();
+        Osc52Clipboard.copy("hi", &mut terminal).unwrap();
+        assert_eq!(osc52("hi"), terminal);
+    }
+
+    #[test]
+    fn memory() {
+        let mut clipboard = MemoryClipboard::default();
+        let mut terminal = Vec::new();
+        assert_eq!(None, clipboard.paste());
+        clipboard.copy("text", &mut terminal).unwrap();
+        assert_eq!(Some("text".to_string()), clipboard.paste());
+        assert!(terminal.is_empty());
+    }
+
+    #[test]
+    fn command() {
+        let mut clipboard = CommandClipboard::new(
+            &["sh", "-c", "cat > /dev/null"],
+            Some(&["echo", "-n", "pasted"]),
+        );
+        clipboard.copy("text", &mut Vec::new()).unwrap();
+        assert_eq!(Some("pasted".to_string()), clipboard.paste());
+        assert_eq!("sh", clipboard.name());
+        assert!(clipboard.blocks());
+        assert!(!MemoryClipboard::default().blocks());
+
+        let mut failing = CommandClipboard::new(&["false"], None);
+        assert!(failing.copy("text", &mut Vec::new()).is_err());
+        assert_eq!(None, failing.paste());
+    }
+}
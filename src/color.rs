@@ -1,3 +1,6 @@
+use core::fmt;
+use core::str::FromStr;
+
 /// Color of a character, could be 8 bit(256 color) or RGB color
 ///
 /// ```
@@ -5,8 +8,10 @@
 /// Color::RED; // predefined values
 /// Color::Rgb(255, 0, 0); // RED
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[allow(clippy::manual_non_exhaustive)]
 pub enum Color {
+    #[default]
     Default,
     AnsiValue(u8),
     Rgb(u8, u8, u8),
@@ -34,8 +39,547 @@ impl Color {
     pub const LIGHT_WHITE: Color = Color::AnsiValue(15);
 }
 
-impl Default for Color {
-    fn default() -> Self {
-        Color::Default
+impl Color {
+    /// The color named `name` in the X11/web color table (with the web values where the two
+    /// disagree, e.g. `gray`), e.g. `"dodgerblue"`. Case, spaces, `-` and `_` are ignored, so
+    /// the X11 spelling `"Dodger Blue"` works too.
+    ///
+    /// ```
+    /// use tuikit::attr::Color;
+    /// assert_eq!(Some(Color::Rgb(30, 144, 255)), Color::from_name("Dodger Blue"));
+    /// assert_eq!(None, Color::from_name("dodgerblu"));
+    /// ```
+    pub fn from_name(name: &str) -> Option<Color> {
+        let normalized = name
+            .bytes()
+            .filter(|b| !matches!(b, b' ' | b'-' | b'_'))
+            .map(|b| b.to_ascii_lowercase());
+        NAMED_COLORS
+            .binary_search_by(|(entry, ..)| entry.bytes().cmp(normalized.clone()))
+            .ok()
+            .map(|index| {
+                let (_, r, g, b) = NAMED_COLORS[index];
+                Color::Rgb(r, g, b)
+            })
+    }
+
+    /// The nearest color of the 256 color palette, for the terminals without RGB colors
+    ///
+    /// ```
+    /// use tuikit::attr::Color;
+    /// assert_eq!(Color::AnsiValue(196), Color::Rgb(255, 0, 0).to_ansi256());
+    /// assert_eq!(Color::AnsiValue(244), Color::Rgb(128, 128, 128).to_ansi256());
+    /// ```
+    pub fn to_ansi256(self) -> Color {
+        match self {
+            Color::Rgb(r, g, b) => {
+                // the 6x6x6 cube from 16 and the gray ramp from 232
+                let cube_index = |c: u8| match c {
+                    0..=47 => 0,
+                    48..=114 => 1,
+                    _ => (c - 35) / 40,
+                };
+                let (ri, gi, bi) = (cube_index(r), cube_index(g), cube_index(b));
+                let cube = 16 + 36 * ri + 6 * gi + bi;
+                let average = (r as u16 + g as u16 + b as u16) / 3;
+                let gray = if average > 238 {
+                    255
+                } else {
+                    232 + (average.saturating_sub(3) / 10) as u8
+                };
+                let nearest = if distance(ansi256_rgb(cube), (r, g, b))
+                    <= distance(ansi256_rgb(gray), (r, g, b))
+                {
+                    cube
+                } else {
+                    gray
+                };
+                Color::AnsiValue(nearest)
+            }
+            color => color,
+        }
+    }
+
+    /// The nearest of the 16 basic colors, assuming the usual xterm palette
+    ///
+    /// ```
+    /// use tuikit::attr::Color;
+    /// assert_eq!(Color::LIGHT_RED, Color::Rgb(250, 10, 20).to_ansi16());
+    /// assert_eq!(Color::BLUE, Color::AnsiValue(18).to_ansi16());
+    /// ```
+    pub fn to_ansi16(self) -> Color {
+        let rgb = match self {
+            Color::AnsiValue(n) if n < 16 => return self,
+            Color::AnsiValue(n) => ansi256_rgb(n),
+            Color::Rgb(r, g, b) => (r, g, b),
+            color => return color,
+        };
+        let nearest = (0..16)
+            .min_by_key(|&n| distance(ansi256_rgb(n), rgb))
+            .unwrap_or(0);
+        Color::AnsiValue(nearest)
+    }
+
+    /// The RGB color of hue `h` (in degrees), saturation `s` and lightness `l` (from 0 to 1)
+    ///
+    /// ```
+    /// use tuikit::attr::Color;
+    /// assert_eq!(Color::Rgb(255, 0, 0), Color::hsl(0.0, 1.0, 0.5));
+    /// assert_eq!(Color::Rgb(0, 128, 0), Color::hsl(120.0, 1.0, 0.25));
+    /// ```
+    pub fn hsl(h: f32, s: f32, l: f32) -> Color {
+        let (s, l) = (s.clamp(0.0, 1.0), l.clamp(0.0, 1.0));
+        let chroma = (1.0 - abs(2.0 * l - 1.0)) * s;
+        hue_to_rgb(h, chroma, l - chroma / 2.0)
+    }
+
+    /// The RGB color of hue `h` (in degrees), saturation `s` and value `v` (from 0 to 1)
+    ///
+    /// ```
+    /// use tuikit::attr::Color;
+    /// assert_eq!(Color::Rgb(0, 0, 255), Color::hsv(240.0, 1.0, 1.0));
+    /// assert_eq!(Color::Rgb(128, 64, 64), Color::hsv(-360.0, 0.5, 0.5));
+    /// ```
+    pub fn hsv(h: f32, s: f32, v: f32) -> Color {
+        let (s, v) = (s.clamp(0.0, 1.0), v.clamp(0.0, 1.0));
+        let chroma = v * s;
+        hue_to_rgb(h, chroma, v - chroma)
+    }
+
+    /// The hue (in degrees), saturation and lightness of the color, the palette colors are
+    /// taken with the usual xterm values. `None` for `Color::Default`.
+    pub fn to_hsl(self) -> Option<(f32, f32, f32)> {
+        let (r, g, b) = match self {
+            Color::AnsiValue(n) => ansi256_rgb(n),
+            Color::Rgb(r, g, b) => (r, g, b),
+            _ => return None,
+        };
+        let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let chroma = max - min;
+        let l = (max + min) / 2.0;
+        if chroma == 0.0 {
+            return Some((0.0, 0.0, l));
+        }
+
+        let s = chroma / (1.0 - abs(2.0 * l - 1.0));
+        let h = if max == r {
+            (g - b) / chroma
+        } else if max == g {
+            (b - r) / chroma + 2.0
+        } else {
+            (r - g) / chroma + 4.0
+        };
+        Some((wrap_hue(60.0 * h), s, l))
+    }
+
+    /// Change the lightness by `amount` (from -1 to 1), e.g. for the hover variant of a theme
+    /// color. The result is an RGB color, `Color::Default` is left as is.
+    ///
+    /// ```
+    /// use tuikit::attr::Color;
+    /// assert_eq!(Color::Rgb(255, 102, 102), Color::Rgb(255, 0, 0).lighten(0.2));
+    /// assert_eq!(Color::Rgb(0, 0, 0), Color::Rgb(255, 0, 0).darken(1.0));
+    /// ```
+    pub fn lighten(self, amount: f32) -> Color {
+        match self.to_hsl() {
+            Some((h, s, l)) => Color::hsl(h, s, l + amount),
+            None => self,
+        }
+    }
+
+    /// Change the lightness by `-amount`, see `lighten`
+    pub fn darken(self, amount: f32) -> Color {
+        self.lighten(-amount)
+    }
+
+    /// Change the saturation by `amount` (from -1 to 1), see `lighten`
+    ///
+    /// ```
+    /// use tuikit::attr::Color;
+    /// assert_eq!(Color::Rgb(128, 128, 128), Color::Rgb(191, 64, 64).saturate(-1.0));
+    /// ```
+    pub fn saturate(self, amount: f32) -> Color {
+        match self.to_hsl() {
+            Some((h, s, l)) => Color::hsl(h, s + amount, l),
+            None => self,
+        }
+    }
+
+    /// Change the saturation by `-amount`, see `lighten`
+    pub fn desaturate(self, amount: f32) -> Color {
+        self.saturate(-amount)
+    }
+}
+
+/// `f32::abs` and friends need `std`
+fn abs(x: f32) -> f32 {
+    if x < 0.0 {
+        -x
+    } else {
+        x
+    }
+}
+
+/// the hue in `[0, 360)`
+fn wrap_hue(h: f32) -> f32 {
+    let h = h % 360.0;
+    if h < 0.0 {
+        h + 360.0
+    } else {
+        h
+    }
+}
+
+/// The RGB color of hue `h` with `chroma`, `m` being added to each component (from 0 to 1)
+fn hue_to_rgb(h: f32, chroma: f32, m: f32) -> Color {
+    let sector = wrap_hue(h) / 60.0;
+    let x = chroma * (1.0 - abs(sector % 2.0 - 1.0));
+    let (r, g, b) = match sector as u8 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let component = |c: f32| ((c + m).clamp(0.0, 1.0) * 255.0 + 0.5) as u8;
+    Color::Rgb(component(r), component(g), component(b))
+}
+
+/// The xterm values of the palette color `n`
+fn ansi256_rgb(n: u8) -> (u8, u8, u8) {
+    const BASIC: [(u8, u8, u8); 16] = [
+        (0, 0, 0),
+        (205, 0, 0),
+        (0, 205, 0),
+        (205, 205, 0),
+        (0, 0, 238),
+        (205, 0, 205),
+        (0, 205, 205),
+        (229, 229, 229),
+        (127, 127, 127),
+        (255, 0, 0),
+        (0, 255, 0),
+        (255, 255, 0),
+        (92, 92, 255),
+        (255, 0, 255),
+        (0, 255, 255),
+        (255, 255, 255),
+    ];
+    let level = |i: u8| if i == 0 { 0 } else { 55 + 40 * i };
+    match n {
+        0..=15 => BASIC[n as usize],
+        16..=231 => {
+            let i = n - 16;
+            (level(i / 36), level(i / 6 % 6), level(i % 6))
+        }
+        _ => {
+            let gray = 8 + 10 * (n - 232);
+            (gray, gray, gray)
+        }
+    }
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+/// Written as `default`, `color<n>` for the palette colors and `#rrggbb` for the RGB ones,
+/// which are parsed back by `FromStr`
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Color::AnsiValue(n) => write!(f, "color{}", n),
+            Color::Rgb(r, g, b) => write!(f, "#{:02x}{:02x}{:02x}", r, g, b),
+            Color::Default | Color::__Nonexhaustive => f.write_str("default"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseColorError;
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("unknown color")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseColorError {}
+
+/// Parse `default`, `color<n>` (or `colour<n>`) for the palette color `n`, `#rrggbb`, `#rgb`
+/// or a name of the table of `from_name`. The palette colors have no names: `red` is the web
+/// one, `color1` the red of the terminal.
+///
+/// ```
+/// use tuikit::attr::Color;
+/// assert_eq!(Ok(Color::RED), "color1".parse());
+/// assert_eq!(Ok(Color::Rgb(255, 0, 0)), "red".parse());
+/// assert_eq!(Ok(Color::Rgb(0x1e, 0x90, 0xff)), "#1E90FF".parse());
+/// assert_eq!("#1e90ff", Color::Rgb(0x1e, 0x90, 0xff).to_string());
+/// ```
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("default") {
+            return Ok(Color::Default);
+        }
+        if let Some(hex) = s.strip_prefix('#') {
+            return parse_hex(hex).ok_or(ParseColorError);
+        }
+        for prefix in ["colour", "color"] {
+            let index = s
+                .get(..prefix.len())
+                .filter(|head| head.eq_ignore_ascii_case(prefix))
+                .and_then(|_| s[prefix.len()..].parse().ok());
+            if let Some(index) = index {
+                return Ok(Color::AnsiValue(index));
+            }
+        }
+        Color::from_name(s).ok_or(ParseColorError)
+    }
+}
+
+fn parse_hex(hex: &str) -> Option<Color> {
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let component = |digits: &str| u8::from_str_radix(digits, 16).ok();
+    match hex.len() {
+        6 => Some(Color::Rgb(
+            component(&hex[0..2])?,
+            component(&hex[2..4])?,
+            component(&hex[4..6])?,
+        )),
+        // `#rgb` is `#rrggbb`
+        3 => Some(Color::Rgb(
+            component(&hex[0..1])? * 17,
+            component(&hex[1..2])? * 17,
+            component(&hex[2..3])? * 17,
+        )),
+        _ => None,
+    }
+}
+
+/// The X11/web colors, sorted by name for the binary search
+const NAMED_COLORS: &[(&str, u8, u8, u8)] = &[
+    ("aliceblue", 240, 248, 255),
+    ("antiquewhite", 250, 235, 215),
+    ("aqua", 0, 255, 255),
+    ("aquamarine", 127, 255, 212),
+    ("azure", 240, 255, 255),
+    ("beige", 245, 245, 220),
+    ("bisque", 255, 228, 196),
+    ("black", 0, 0, 0),
+    ("blanchedalmond", 255, 235, 205),
+    ("blue", 0, 0, 255),
+    ("blueviolet", 138, 43, 226),
+    ("brown", 165, 42, 42),
+    ("burlywood", 222, 184, 135),
+    ("cadetblue", 95, 158, 160),
+    ("chartreuse", 127, 255, 0),
+    ("chocolate", 210, 105, 30),
+    ("coral", 255, 127, 80),
+    ("cornflowerblue", 100, 149, 237),
+    ("cornsilk", 255, 248, 220),
+    ("crimson", 220, 20, 60),
+    ("cyan", 0, 255, 255),
+    ("darkblue", 0, 0, 139),
+    ("darkcyan", 0, 139, 139),
+    ("darkgoldenrod", 184, 134, 11),
+    ("darkgray", 169, 169, 169),
+    ("darkgreen", 0, 100, 0),
+    ("darkgrey", 169, 169, 169),
+    ("darkkhaki", 189, 183, 107),
+    ("darkmagenta", 139, 0, 139),
+    ("darkolivegreen", 85, 107, 47),
+    ("darkorange", 255, 140, 0),
+    ("darkorchid", 153, 50, 204),
+    ("darkred", 139, 0, 0),
+    ("darksalmon", 233, 150, 122),
+    ("darkseagreen", 143, 188, 143),
+    ("darkslateblue", 72, 61, 139),
+    ("darkslategray", 47, 79, 79),
+    ("darkslategrey", 47, 79, 79),
+    ("darkturquoise", 0, 206, 209),
+    ("darkviolet", 148, 0, 211),
+    ("deeppink", 255, 20, 147),
+    ("deepskyblue", 0, 191, 255),
+    ("dimgray", 105, 105, 105),
+    ("dimgrey", 105, 105, 105),
+    ("dodgerblue", 30, 144, 255),
+    ("firebrick", 178, 34, 34),
+    ("floralwhite", 255, 250, 240),
+    ("forestgreen", 34, 139, 34),
+    ("fuchsia", 255, 0, 255),
+    ("gainsboro", 220, 220, 220),
+    ("ghostwhite", 248, 248, 255),
+    ("gold", 255, 215, 0),
+    ("goldenrod", 218, 165, 32),
+    ("gray", 128, 128, 128),
+    ("green", 0, 128, 0),
+    ("greenyellow", 173, 255, 47),
+    ("grey", 128, 128, 128),
+    ("honeydew", 240, 255, 240),
+    ("hotpink", 255, 105, 180),
+    ("indianred", 205, 92, 92),
+    ("indigo", 75, 0, 130),
+    ("ivory", 255, 255, 240),
+    ("khaki", 240, 230, 140),
+    ("lavender", 230, 230, 250),
+    ("lavenderblush", 255, 240, 245),
+    ("lawngreen", 124, 252, 0),
+    ("lemonchiffon", 255, 250, 205),
+    ("lightblue", 173, 216, 230),
+    ("lightcoral", 240, 128, 128),
+    ("lightcyan", 224, 255, 255),
+    ("lightgoldenrodyellow", 250, 250, 210),
+    ("lightgray", 211, 211, 211),
+    ("lightgreen", 144, 238, 144),
+    ("lightgrey", 211, 211, 211),
+    ("lightpink", 255, 182, 193),
+    ("lightsalmon", 255, 160, 122),
+    ("lightseagreen", 32, 178, 170),
+    ("lightskyblue", 135, 206, 250),
+    ("lightslategray", 119, 136, 153),
+    ("lightslategrey", 119, 136, 153),
+    ("lightsteelblue", 176, 196, 222),
+    ("lightyellow", 255, 255, 224),
+    ("lime", 0, 255, 0),
+    ("limegreen", 50, 205, 50),
+    ("linen", 250, 240, 230),
+    ("magenta", 255, 0, 255),
+    ("maroon", 128, 0, 0),
+    ("mediumaquamarine", 102, 205, 170),
+    ("mediumblue", 0, 0, 205),
+    ("mediumorchid", 186, 85, 211),
+    ("mediumpurple", 147, 112, 219),
+    ("mediumseagreen", 60, 179, 113),
+    ("mediumslateblue", 123, 104, 238),
+    ("mediumspringgreen", 0, 250, 154),
+    ("mediumturquoise", 72, 209, 204),
+    ("mediumvioletred", 199, 21, 133),
+    ("midnightblue", 25, 25, 112),
+    ("mintcream", 245, 255, 250),
+    ("mistyrose", 255, 228, 225),
+    ("moccasin", 255, 228, 181),
+    ("navajowhite", 255, 222, 173),
+    ("navy", 0, 0, 128),
+    ("oldlace", 253, 245, 230),
+    ("olive", 128, 128, 0),
+    ("olivedrab", 107, 142, 35),
+    ("orange", 255, 165, 0),
+    ("orangered", 255, 69, 0),
+    ("orchid", 218, 112, 214),
+    ("palegoldenrod", 238, 232, 170),
+    ("palegreen", 152, 251, 152),
+    ("paleturquoise", 175, 238, 238),
+    ("palevioletred", 219, 112, 147),
+    ("papayawhip", 255, 239, 213),
+    ("peachpuff", 255, 218, 185),
+    ("peru", 205, 133, 63),
+    ("pink", 255, 192, 203),
+    ("plum", 221, 160, 221),
+    ("powderblue", 176, 224, 230),
+    ("purple", 128, 0, 128),
+    ("rebeccapurple", 102, 51, 153),
+    ("red", 255, 0, 0),
+    ("rosybrown", 188, 143, 143),
+    ("royalblue", 65, 105, 225),
+    ("saddlebrown", 139, 69, 19),
+    ("salmon", 250, 128, 114),
+    ("sandybrown", 244, 164, 96),
+    ("seagreen", 46, 139, 87),
+    ("seashell", 255, 245, 238),
+    ("sienna", 160, 82, 45),
+    ("silver", 192, 192, 192),
+    ("skyblue", 135, 206, 235),
+    ("slateblue", 106, 90, 205),
+    ("slategray", 112, 128, 144),
+    ("slategrey", 112, 128, 144),
+    ("snow", 255, 250, 250),
+    ("springgreen", 0, 255, 127),
+    ("steelblue", 70, 130, 180),
+    ("tan", 210, 180, 140),
+    ("teal", 0, 128, 128),
+    ("thistle", 216, 191, 216),
+    ("tomato", 255, 99, 71),
+    ("turquoise", 64, 224, 208),
+    ("violet", 238, 130, 238),
+    ("wheat", 245, 222, 179),
+    ("white", 255, 255, 255),
+    ("whitesmoke", 245, 245, 245),
+    ("yellow", 255, 255, 0),
+    ("yellowgreen", 154, 205, 50),
+];
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn table_is_sorted() {
+        assert!(NAMED_COLORS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn round_trip() {
+        for color in [
+            Color::Default,
+            Color::RED,
+            Color::AnsiValue(255),
+            Color::Rgb(0, 128, 255),
+        ] {
+            assert_eq!(Ok(color), color.to_string().parse());
+        }
+        assert_eq!(Ok(Color::AnsiValue(12)), "Colour12".parse());
+        assert_eq!(Ok(Color::Rgb(255, 255, 255)), "#fff".parse());
+        assert_eq!(Ok(Color::Rgb(72, 61, 139)), "dark_slate_blue".parse());
+        assert_eq!(Err(ParseColorError), "color256".parse::<Color>());
+        assert_eq!(Err(ParseColorError), "#ff00".parse::<Color>());
+        assert_eq!(Err(ParseColorError), "#ffé".parse::<Color>());
+        assert_eq!(Err(ParseColorError), "colé".parse::<Color>());
+    }
+
+    #[test]
+    fn downgrade() {
+        // the palette colors are their own nearest
+        for n in 16..=255 {
+            let (r, g, b) = ansi256_rgb(n);
+            assert_eq!(Color::AnsiValue(n), Color::Rgb(r, g, b).to_ansi256());
+        }
+        assert_eq!(Color::AnsiValue(17), Color::Rgb(0, 0, 80).to_ansi256());
+        assert_eq!(Color::AnsiValue(236), Color::Rgb(50, 48, 49).to_ansi256());
+        assert_eq!(Color::Default, Color::Default.to_ansi256());
+        assert_eq!(Color::LIGHT_WHITE, Color::AnsiValue(231).to_ansi16());
+        assert_eq!(Color::RED, Color::RED.to_ansi16());
+    }
+
+    #[test]
+    fn hsl_round_trip() {
+        for (r, g, b) in [
+            (0, 0, 0),
+            (255, 255, 255),
+            (30, 144, 255),
+            (72, 61, 139),
+            (9, 200, 7),
+        ] {
+            let (h, s, l) = Color::Rgb(r, g, b).to_hsl().unwrap();
+            assert_eq!(Color::Rgb(r, g, b), Color::hsl(h, s, l));
+        }
+        assert_eq!(Some((0.0, 1.0, 0.5)), Color::Rgb(255, 0, 0).to_hsl());
+        assert_eq!(Color::hsl(300.0, 1.0, 0.5), Color::hsv(300.0, 1.0, 1.0));
+        assert_eq!(None, Color::Default.to_hsl());
+        assert_eq!(Color::Default, Color::Default.lighten(0.5));
+        // the palette colors become RGB ones
+        assert_eq!(Color::Rgb(255, 255, 255), Color::BLUE.lighten(1.0));
+        assert_eq!(Color::Rgb(0, 0, 255), Color::Rgb(50, 50, 205).saturate(0.5));
     }
 }
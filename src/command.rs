@@ -0,0 +1,59 @@
+//! The commands `Screen::present` produces, executed by `Output`.
+
+use crate::attr::{Attr, Color, Effect};
+use alloc::string::String;
+
+/// Instead of calling functions of `Output`, we could send commands.
+#[derive(Debug, Clone)]
+pub enum Command {
+    /// Put a char to screen
+    PutChar(char),
+    /// Write content to screen (escape codes will be escaped)
+    Write(String),
+    /// Set the title of the terminal
+    SetTitle(String),
+    /// Clear the title of the terminal
+    ClearTitle,
+    /// Flush all the buffered contents
+    Flush,
+    /// Erase the entire screen
+    EraseScreen,
+    /// Enter(true)/Quit(false) the alternate screen mode
+    AlternateScreen(bool),
+    /// Enable(true)/Disable(false) mouse support
+    MouseSupport(bool),
+    /// Enable(true)/Disable(false) mouse drag tracking
+    MouseDragSupport(bool),
+    /// Enable(true)/Disable(false) mouse motion tracking
+    MouseMotionSupport(bool),
+    /// Erase contents to the end of current line
+    EraseEndOfLine,
+    /// Erase contents till the bottom of the screen
+    EraseDown,
+    /// Reset attributes
+    ResetAttributes,
+    /// Set the foreground color
+    Fg(Color),
+    /// Set the background color
+    Bg(Color),
+    /// Set the effect(e.g. underline, dim, bold, ...)
+    Effect(Effect),
+    /// Set the fg, bg & effect.
+    SetAttribute(Attr),
+    /// Enable(true)/Disable(false) autowrap
+    AutoWrap(bool),
+    /// move the cursor to `(row, col)`
+    CursorGoto { row: usize, col: usize },
+    /// move cursor up `x` lines
+    CursorUp(usize),
+    /// move cursor down `x` lines
+    CursorDown(usize),
+    /// move cursor left `x` characters
+    CursorLeft(usize),
+    /// move cursor right `x` characters
+    CursorRight(usize),
+    /// Show(true)/Hide(false) cursor
+    CursorShow(bool),
+    /// Enable(true)/Disable(false) the bracketed paste mode
+    BracketedPaste(bool),
+}
@@ -0,0 +1,197 @@
+//! User-level defaults shared by every tuikit application.
+//!
+//! End users could set their preferences once instead of per application, in a config file
+//! (`$TUIKIT_CONFIG`, or `$XDG_CONFIG_HOME/tuikit/config`, or `~/.config/tuikit/config`):
+//!
+//! ```text
+//! # lines of `key = value`
+//! color_depth = 256
+//! mouse = true
+//! esc_timeout = 50
+//! theme = solarized
+//! reduce_motion = false
+//! ```
+//!
+//! or in the environment variables `TUIKIT_COLOR_DEPTH`, `TUIKIT_MOUSE`, `TUIKIT_ESC_TIMEOUT`,
+//! `TUIKIT_THEME` and `TUIKIT_REDUCE_MOTION`, which take precedence over the file.
+//!
+//! They are only applied by the applications opting in, with `TermOptions::from_config()`
+//! (or `TermOptions::config` with `Config::load()`), `TermOptions::default()` ignores them.
+//! Anything set explicitly by the application through `TermOptions` afterwards wins.
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const ENV_CONFIG: &str = "TUIKIT_CONFIG";
+const ENV_PREFIX: &str = "TUIKIT_";
+const KEYS: &[&str] = &[
+    "color_depth",
+    "mouse",
+    "esc_timeout",
+    "theme",
+    "reduce_motion",
+];
+
+/// The number of colors the user wants to be used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+    Monochrome,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+impl ColorDepth {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_ref() {
+            "1" | "2" | "mono" | "monochrome" => Some(ColorDepth::Monochrome),
+            "8" | "16" | "ansi" => Some(ColorDepth::Ansi16),
+            "256" => Some(ColorDepth::Ansi256),
+            "24bit" | "truecolor" | "16m" => Some(ColorDepth::TrueColor),
+            _ => None,
+        }
+    }
+}
+
+/// The user preferences, `None` if not set
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    /// the colors to render with, overriding the detection
+    pub color_depth: Option<ColorDepth>,
+    /// whether mouse support is enabled on start
+    pub mouse: Option<bool>,
+    /// how long to wait after `ESC` for the rest of an escape sequence
+    pub esc_timeout: Option<Duration>,
+    /// the name of the theme, interpreted by the application
+    pub theme: Option<String>,
+    pub reduce_motion: Option<bool>,
+}
+
+impl Config {
+    /// Load the user config file then the environment variables
+    pub fn load() -> Self {
+        let file = Self::user_config_path()
+            .and_then(|path| Self::from_file(&path).ok())
+            .unwrap_or_default();
+        file.merge(Self::from_env())
+    }
+
+    /// the path of the user config file, whether it exists or not
+    pub fn user_config_path() -> Option<PathBuf> {
+        if let Some(path) = env::var_os(ENV_CONFIG) {
+            return Some(PathBuf::from(path));
+        }
+        let config_home = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(config_home.join("tuikit").join("config"))
+    }
+
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        fs::read_to_string(path).map(|text| Self::parse(&text))
+    }
+
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        for key in KEYS {
+            let name = format!("{}{}", ENV_PREFIX, key.to_uppercase());
+            if let Ok(value) = env::var(name) {
+                config.set(key, &value);
+            }
+        }
+        config
+    }
+
+    /// Parse lines of `key = value`, `#` starts a comment. Unknown keys and invalid values are
+    /// ignored.
+    ///
+    /// ```
+    /// use tuikit::config::{ColorDepth, Config};
+    ///
+    /// let config = Config::parse("color_depth = 256\nmouse = yes # comment\nfoo = bar");
+    /// assert_eq!(Some(ColorDepth::Ansi256), config.color_depth);
+    /// assert_eq!(Some(true), config.mouse);
+    /// assert_eq!(None, config.theme);
+    /// ```
+    pub fn parse(text: &str) -> Self {
+        let mut config = Self::default();
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("");
+            if let Some((key, value)) = line.split_once('=') {
+                config.set(&key.trim().to_lowercase(), value.trim());
+            }
+        }
+        config
+    }
+
+    /// the values set in `other` override the ones in `self`
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            color_depth: other.color_depth.or(self.color_depth),
+            mouse: other.mouse.or(self.mouse),
+            esc_timeout: other.esc_timeout.or(self.esc_timeout),
+            theme: other.theme.or(self.theme),
+            reduce_motion: other.reduce_motion.or(self.reduce_motion),
+        }
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        match key {
+            "color_depth" => self.color_depth = ColorDepth::parse(value).or(self.color_depth),
+            "mouse" => self.mouse = parse_bool(value).or(self.mouse),
+            "esc_timeout" => {
+                self.esc_timeout = value
+                    .parse()
+                    .ok()
+                    .map(Duration::from_millis)
+                    .or(self.esc_timeout)
+            }
+            "theme" if !value.is_empty() => self.theme = Some(value.to_string()),
+            "reduce_motion" => self.reduce_motion = parse_bool(value).or(self.reduce_motion),
+            _ => {}
+        }
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_ref() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_all_keys() {
+        let config = Config::parse(
+            "color_depth = truecolor\nmouse = off\nesc_timeout = 50\n  theme=dark \n\
+             reduce_motion = 1\ncolor_depth = 7",
+        );
+        assert_eq!(
+            Config {
+                color_depth: Some(ColorDepth::TrueColor),
+                mouse: Some(false),
+                esc_timeout: Some(Duration::from_millis(50)),
+                theme: Some("dark".to_string()),
+                reduce_motion: Some(true),
+            },
+            config
+        );
+    }
+
+    #[test]
+    fn merge_should_prefer_other() {
+        let file = Config::parse("mouse = true\ntheme = light");
+        let env = Config::parse("theme = dark");
+        let config = file.merge(env);
+        assert_eq!(Some(true), config.mouse);
+        assert_eq!(Some("dark".to_string()), config.theme);
+    }
+}
@@ -1,25 +1,22 @@
-///! Various pre-defined container that implements Draw
+//! Various pre-defined container that implements Draw
 mod split;
+mod splitter;
 mod win;
 
 pub use self::split::*;
+pub use self::splitter::*;
 pub use self::win::*;
 use std::cmp::min;
 
 /// Whether fixed size or percentage
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default)]
 pub enum Size {
     Fixed(usize),
     Percent(usize),
+    #[default]
     Default,
 }
 
-impl Default for Size {
-    fn default() -> Self {
-        Size::Default
-    }
-}
-
 impl Size {
     pub fn calc_fixed_size(&self, total_size: usize, default_size: usize) -> usize {
         match *self {
@@ -1,6 +1,6 @@
 use super::Size;
-use crate::canvas::{BoundedCanvas, Canvas, Result};
-use crate::draw::Draw;
+use crate::canvas::{Canvas, Result};
+use crate::draw::{draw_child, Draw, DrawContext};
 use std::cmp::min;
 
 /// A Split item would contain 3 things
@@ -54,7 +54,7 @@ enum SplitType {
 }
 
 trait SplitContainer<'a> {
-    fn get_splits(&self) -> &[Box<Split + 'a>];
+    fn get_splits(&self) -> &[Box<dyn Split + 'a>];
 
     fn get_split_type(&self) -> SplitType;
 
@@ -108,11 +108,7 @@ trait SplitContainer<'a> {
 
         let total_factors: usize = split_factors.iter().sum();
 
-        let unit = if total_factors == 0 {
-            0
-        } else {
-            size_diff / total_factors
-        };
+        let unit = size_diff.checked_div(total_factors).unwrap_or(0);
 
         (0..split_sizes.len())
             .map(|idx| {
@@ -136,7 +132,7 @@ pub struct HSplit<'a> {
     basis: Size,
     grow: usize,
     shrink: usize,
-    splits: Vec<Box<Split + 'a>>,
+    splits: Vec<Box<dyn Split + 'a>>,
 }
 
 impl<'a> Default for HSplit<'a> {
@@ -173,7 +169,7 @@ impl<'a> HSplit<'a> {
 }
 
 impl<'a> SplitContainer<'a> for HSplit<'a> {
-    fn get_splits(&self) -> &[Box<Split + 'a>] {
+    fn get_splits(&self) -> &[Box<dyn Split + 'a>] {
         &self.splits
     }
 
@@ -182,8 +178,8 @@ impl<'a> SplitContainer<'a> for HSplit<'a> {
     }
 }
 
-impl<'a> Draw for HSplit<'a> {
-    fn draw(&self, canvas: &mut Canvas) -> Result<()> {
+impl<'a> HSplit<'a> {
+    fn draw_splits(&self, canvas: &mut dyn Canvas, ctx: Option<&DrawContext>) -> Result<()> {
         let (width, height) = canvas.size()?;
         let target_widths = self.retrieve_split_info(width);
 
@@ -192,13 +188,22 @@ impl<'a> Draw for HSplit<'a> {
         for (idx, split) in self.splits.iter().enumerate() {
             let target_width = target_widths[idx];
             let right = min(left + target_width, width);
-            let mut new_canvas = BoundedCanvas::new(0, left, right - left, height, canvas);
-            let _ = split.draw(&mut new_canvas);
+            let _ = draw_child(split, canvas, (0, left, right - left, height), ctx);
             left = right;
         }
 
         Ok(())
     }
+}
+
+impl<'a> Draw for HSplit<'a> {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
+        self.draw_splits(canvas, None)
+    }
+
+    fn draw_with_context(&self, canvas: &mut dyn Canvas, ctx: &DrawContext) -> Result<()> {
+        self.draw_splits(canvas, Some(ctx))
+    }
 
     fn size_hint(&self) -> (Option<usize>, Option<usize>) {
         let has_width_hint = self
@@ -260,7 +265,7 @@ pub struct VSplit<'a> {
     basis: Size,
     grow: usize,
     shrink: usize,
-    splits: Vec<Box<Split + 'a>>,
+    splits: Vec<Box<dyn Split + 'a>>,
 }
 
 impl<'a> Default for VSplit<'a> {
@@ -297,7 +302,7 @@ impl<'a> VSplit<'a> {
 }
 
 impl<'a> SplitContainer<'a> for VSplit<'a> {
-    fn get_splits(&self) -> &[Box<Split + 'a>] {
+    fn get_splits(&self) -> &[Box<dyn Split + 'a>] {
         &self.splits
     }
 
@@ -306,8 +311,8 @@ impl<'a> SplitContainer<'a> for VSplit<'a> {
     }
 }
 
-impl<'a> Draw for VSplit<'a> {
-    fn draw(&self, canvas: &mut Canvas) -> Result<()> {
+impl<'a> VSplit<'a> {
+    fn draw_splits(&self, canvas: &mut dyn Canvas, ctx: Option<&DrawContext>) -> Result<()> {
         let (width, height) = canvas.size()?;
         let target_heights = self.retrieve_split_info(height);
 
@@ -316,13 +321,22 @@ impl<'a> Draw for VSplit<'a> {
         for (idx, split) in self.splits.iter().enumerate() {
             let target_height = target_heights[idx];
             let bottom = min(top + target_height, height);
-            let mut new_canvas = BoundedCanvas::new(top, 0, width, bottom - top, canvas);
-            let _ = split.draw(&mut new_canvas);
+            let _ = draw_child(split, canvas, (top, 0, width, bottom - top), ctx);
             top = bottom;
         }
 
         Ok(())
     }
+}
+
+impl<'a> Draw for VSplit<'a> {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
+        self.draw_splits(canvas, None)
+    }
+
+    fn draw_with_context(&self, canvas: &mut dyn Canvas, ctx: &DrawContext) -> Result<()> {
+        self.draw_splits(canvas, Some(ctx))
+    }
 
     fn size_hint(&self) -> (Option<usize>, Option<usize>) {
         let has_width_hint = self
@@ -376,7 +390,8 @@ impl<'a> Split for VSplit<'a> {
 }
 
 #[cfg(test)]
-#[allow(dead_code)]
+// the tests predate `dyn`
+#[allow(dead_code, bare_trait_objects, clippy::derivable_impls)]
 mod test {
     use super::*;
     use crate::cell::Cell;
@@ -459,6 +474,10 @@ mod test {
         fn draw(&self, canvas: &mut Canvas) -> Result<()> {
             self.draw.draw(canvas)
         }
+
+        fn draw_with_context(&self, canvas: &mut dyn Canvas, ctx: &DrawContext) -> Result<()> {
+            self.draw.draw_with_context(canvas, ctx)
+        }
     }
 
     struct SingleWindow {
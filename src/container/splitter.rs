@@ -0,0 +1,366 @@
+use super::split::Split;
+use super::Size;
+use crate::attr::Attr;
+use crate::canvas::{Canvas, Result};
+use crate::draw::{draw_child, Draw, DrawContext};
+use crate::key::{Key, MouseButton};
+use std::cell::Cell;
+use std::cmp::min;
+
+const MIN_RATIO: f64 = 0.0;
+const MAX_RATIO: f64 = 1.0;
+
+/// The direction in which a `Splitter` lays out its two panes
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SplitterDirection {
+    /// panes are placed side by side, the divider is a vertical line
+    Horizontal,
+    /// panes are stacked, the divider is a horizontal line
+    Vertical,
+}
+
+/// Events emitted by `SplitterState` when the layout changed
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SplitterEvent {
+    /// the divider was moved, contains the new ratio of the first pane
+    Resized(f64),
+}
+
+/// The persistent part of a `Splitter`: the direction, the ratio and the dragging state.
+///
+/// Containers are normally re-created on every draw, so the state lives outside of the
+/// `Splitter` and should be kept by the application.
+///
+/// ```
+/// use tuikit::container::{SplitterDirection, SplitterEvent, SplitterState};
+/// use tuikit::key::Key;
+///
+/// let mut state = SplitterState::new(SplitterDirection::Horizontal).ratio(0.3);
+/// state.set_size(81, 10); // normally recorded by `Splitter::draw`
+///
+/// assert_eq!(Some(SplitterEvent::Resized(0.3125)), state.on_key(&Key::AltShiftRight));
+/// ```
+#[derive(Debug)]
+pub struct SplitterState {
+    direction: SplitterDirection,
+    ratio: f64,
+    step: usize,
+    origin: (usize, usize),
+    dragging: bool,
+    size: Cell<(usize, usize)>,
+}
+
+impl SplitterState {
+    pub fn new(direction: SplitterDirection) -> Self {
+        Self {
+            direction,
+            ratio: 0.5,
+            step: 1,
+            origin: (0, 0),
+            dragging: false,
+            size: Cell::new((0, 0)),
+        }
+    }
+
+    /// the ratio(0.0 ~ 1.0) of space the first pane takes
+    pub fn ratio(mut self, ratio: f64) -> Self {
+        self.set_ratio(ratio);
+        self
+    }
+
+    /// number of cells the divider moves on each key stroke
+    pub fn step(mut self, step: usize) -> Self {
+        self.step = step;
+        self
+    }
+
+    /// The `(row, col)` of the splitter's top-left corner on the terminal.
+    /// Mouse events are translated relative to it.
+    pub fn origin(mut self, row: usize, col: usize) -> Self {
+        self.origin = (row, col);
+        self
+    }
+
+    pub fn get_ratio(&self) -> f64 {
+        self.ratio
+    }
+
+    pub fn set_ratio(&mut self, ratio: f64) {
+        self.ratio = ratio.clamp(MIN_RATIO, MAX_RATIO);
+    }
+
+    pub fn get_direction(&self) -> SplitterDirection {
+        self.direction
+    }
+
+    /// record the size (width, height) the splitter was drawn with
+    pub fn set_size(&self, width: usize, height: usize) {
+        self.size.set((width, height));
+    }
+
+    /// total number of cells(excluding the divider) to share between the two panes
+    fn total(&self) -> usize {
+        let (width, height) = self.size.get();
+        let size = match self.direction {
+            SplitterDirection::Horizontal => width,
+            SplitterDirection::Vertical => height,
+        };
+        size.saturating_sub(1)
+    }
+
+    /// the position of the divider (col for horizontal, row for vertical)
+    fn divider(&self) -> usize {
+        let total = self.total();
+        min((total as f64 * self.ratio).round() as usize, total)
+    }
+
+    fn move_divider_to(&mut self, pos: usize) -> Option<SplitterEvent> {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+
+        let old_ratio = self.ratio;
+        self.set_ratio(min(pos, total) as f64 / total as f64);
+        if self.ratio != old_ratio {
+            Some(SplitterEvent::Resized(self.ratio))
+        } else {
+            None
+        }
+    }
+
+    /// Move the divider by `delta` cells
+    pub fn move_divider(&mut self, delta: isize) -> Option<SplitterEvent> {
+        let pos = self.divider() as isize + delta;
+        self.move_divider_to(if pos < 0 { 0 } else { pos as usize })
+    }
+
+    /// Handle the key/mouse event, return `Some(event)` if the layout changed.
+    ///
+    /// - `AltShift + Left/Right` (horizontal) or `AltShift + Up/Down` (vertical) move the
+    ///   divider by `step` cells.
+    /// - pressing the left button on the divider and dragging will move it.
+    pub fn on_key(&mut self, key: &Key) -> Option<SplitterEvent> {
+        let step = self.step as isize;
+        match (self.direction, key) {
+            (SplitterDirection::Horizontal, Key::AltShiftLeft)
+            | (SplitterDirection::Vertical, Key::AltShiftUp) => self.move_divider(-step),
+            (SplitterDirection::Horizontal, Key::AltShiftRight)
+            | (SplitterDirection::Vertical, Key::AltShiftDown) => self.move_divider(step),
+            (_, Key::MousePress(MouseButton::Left, x, y, _)) => {
+                self.dragging = self.relative_pos(*x, *y) == Some(self.divider());
+                None
+            }
+            (_, Key::MouseHold(x, y, _)) if self.dragging => {
+                let pos = self.relative_pos(*x, *y)?;
+                self.move_divider_to(pos)
+            }
+            (_, Key::MouseRelease(MouseButton::Left, ..)) => {
+                self.dragging = false;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// convert the (1-based) mouse position into the position along the split direction
+    fn relative_pos(&self, x: u16, y: u16) -> Option<usize> {
+        let row = (y as usize).checked_sub(1 + self.origin.0)?;
+        let col = (x as usize).checked_sub(1 + self.origin.1)?;
+        let (width, height) = self.size.get();
+        if row >= height || col >= width {
+            return None;
+        }
+
+        match self.direction {
+            SplitterDirection::Horizontal => Some(col),
+            SplitterDirection::Vertical => Some(row),
+        }
+    }
+}
+
+/// A two-pane container whose divider could be moved by mouse dragging or keys.
+///
+/// The ratio and dragging state are kept in a `SplitterState`, feed the key events to
+/// `SplitterState::on_key` to make it interactive.
+pub struct Splitter<'a> {
+    state: &'a SplitterState,
+    first: Box<dyn Draw + 'a>,
+    second: Box<dyn Draw + 'a>,
+    divider_attr: Attr,
+
+    basis: Size,
+    grow: usize,
+    shrink: usize,
+}
+
+// Builder
+impl<'a> Splitter<'a> {
+    pub fn new(state: &'a SplitterState, first: impl Draw + 'a, second: impl Draw + 'a) -> Self {
+        Self {
+            state,
+            first: Box::new(first),
+            second: Box::new(second),
+            divider_attr: Attr::default(),
+            basis: Size::Default,
+            grow: 1,
+            shrink: 1,
+        }
+    }
+
+    pub fn divider_attr(mut self, attr: impl Into<Attr>) -> Self {
+        self.divider_attr = attr.into();
+        self
+    }
+
+    pub fn basis(mut self, basis: impl Into<Size>) -> Self {
+        self.basis = basis.into();
+        self
+    }
+
+    pub fn grow(mut self, grow: usize) -> Self {
+        self.grow = grow;
+        self
+    }
+
+    pub fn shrink(mut self, shrink: usize) -> Self {
+        self.shrink = shrink;
+        self
+    }
+}
+
+impl<'a> Splitter<'a> {
+    fn draw_panes(&self, canvas: &mut dyn Canvas, ctx: Option<&DrawContext>) -> Result<()> {
+        let (width, height) = canvas.size()?;
+        self.state.set_size(width, height);
+        let divider = self.state.divider();
+
+        match self.state.get_direction() {
+            SplitterDirection::Horizontal => {
+                if width == 0 {
+                    return Ok(());
+                }
+
+                for row in 0..height {
+                    let _ = canvas.print_with_attr(row, divider, "│", self.divider_attr);
+                }
+
+                let _ = draw_child(self.first.as_ref(), canvas, (0, 0, divider, height), ctx);
+                let second = (0, divider + 1, width - divider - 1, height);
+                let _ = draw_child(self.second.as_ref(), canvas, second, ctx);
+            }
+            SplitterDirection::Vertical => {
+                if height == 0 {
+                    return Ok(());
+                }
+
+                let _ = canvas.print_with_attr(divider, 0, &"─".repeat(width), self.divider_attr);
+
+                let _ = draw_child(self.first.as_ref(), canvas, (0, 0, width, divider), ctx);
+                let second = (divider + 1, 0, width, height - divider - 1);
+                let _ = draw_child(self.second.as_ref(), canvas, second, ctx);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Draw for Splitter<'a> {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
+        self.draw_panes(canvas, None)
+    }
+
+    fn draw_with_context(&self, canvas: &mut dyn Canvas, ctx: &DrawContext) -> Result<()> {
+        self.draw_panes(canvas, Some(ctx))
+    }
+}
+
+impl<'a> Split for Splitter<'a> {
+    fn get_basis(&self) -> Size {
+        self.basis
+    }
+
+    fn get_grow(&self) -> usize {
+        self.grow
+    }
+
+    fn get_shrink(&self) -> usize {
+        self.shrink
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::key::Modifiers;
+
+    #[test]
+    fn keys_should_move_divider_by_step() {
+        let mut state = SplitterState::new(SplitterDirection::Horizontal).step(2);
+        state.set_size(11, 5);
+
+        assert_eq!(5, state.divider());
+        assert_eq!(
+            Some(SplitterEvent::Resized(0.7)),
+            state.on_key(&Key::AltShiftRight)
+        );
+        assert_eq!(7, state.divider());
+        assert_eq!(None, state.on_key(&Key::AltShiftUp));
+
+        state.move_divider(-100);
+        assert_eq!(0.0, state.get_ratio());
+        assert_eq!(None, state.on_key(&Key::AltShiftLeft));
+    }
+
+    #[test]
+    fn drag_divider_should_follow_mouse() {
+        let mut state = SplitterState::new(SplitterDirection::Vertical).origin(1, 0);
+        state.set_size(10, 11);
+        assert_eq!(5, state.divider());
+
+        // press outside of the divider won't start dragging
+        assert_eq!(
+            None,
+            state.on_key(&Key::MousePress(
+                MouseButton::Left,
+                1,
+                3,
+                Modifiers::empty()
+            ))
+        );
+        assert_eq!(
+            None,
+            state.on_key(&Key::MouseHold(1, 4, Modifiers::empty()))
+        );
+
+        // (1-based) row 7 is the divider: origin 1 + divider 5 + 1
+        assert_eq!(
+            None,
+            state.on_key(&Key::MousePress(
+                MouseButton::Left,
+                1,
+                7,
+                Modifiers::empty()
+            ))
+        );
+        assert_eq!(
+            Some(SplitterEvent::Resized(0.3)),
+            state.on_key(&Key::MouseHold(3, 5, Modifiers::empty()))
+        );
+        assert_eq!(
+            None,
+            state.on_key(&Key::MouseRelease(
+                MouseButton::Left,
+                3,
+                5,
+                Modifiers::empty()
+            ))
+        );
+        assert_eq!(
+            None,
+            state.on_key(&Key::MouseHold(3, 10, Modifiers::empty()))
+        );
+        assert_eq!(3, state.divider());
+    }
+}
@@ -1,11 +1,11 @@
 use super::split::Split;
 use super::Size;
 use crate::attr::Attr;
-use crate::canvas::{BoundedCanvas, Canvas, Result};
+use crate::canvas::{Canvas, Result};
 use crate::cell::Cell;
-use crate::draw::Draw;
+use crate::draw::{draw_child, Draw, DrawContext};
 
-///! A Win is like a div in HTML, it has its margin/padding, and border
+/// A Win is like a div in HTML, it has its margin/padding, and border
 pub struct Win<'a> {
     margin_top: Size,
     margin_right: Size,
@@ -31,12 +31,12 @@ pub struct Win<'a> {
     grow: usize,
     shrink: usize,
 
-    inner: &'a Draw,
+    inner: &'a dyn Draw,
 }
 
 // Builder
 impl<'a> Win<'a> {
-    pub fn new(draw: &'a Draw) -> Self {
+    pub fn new(draw: &'a dyn Draw) -> Self {
         Self {
             margin_top: Default::default(),
             margin_right: Default::default(),
@@ -201,18 +201,18 @@ impl<'a> Win<'a> {
         left: usize,
         width: usize,
         height: usize,
-        canvas: &mut Canvas,
+        canvas: &mut dyn Canvas,
     ) -> Result<(usize, usize, usize, usize)> {
-        if self.border_top || self.border_bottom {
-            if (height < 1) || (self.border_top && self.border_bottom && height < 2) {
-                return Err("not enough height for border".into());
-            }
+        if (self.border_top || self.border_bottom)
+            && ((height < 1) || (self.border_top && self.border_bottom && height < 2))
+        {
+            return Err("not enough height for border".into());
         }
 
-        if self.border_left || self.border_right {
-            if (width < 1) || (self.border_left && self.border_right && width < 2) {
-                return Err("not enough width for border".into());
-            }
+        if (self.border_left || self.border_right)
+            && ((width < 1) || (self.border_left && self.border_right && width < 2))
+        {
+            return Err("not enough width for border".into());
         }
 
         let bottom = top + height - 1;
@@ -290,9 +290,9 @@ impl<'a> Win<'a> {
     }
 }
 
-impl<'a> Draw for Win<'a> {
+impl<'a> Win<'a> {
     /// Reserve margin & padding, draw border.
-    fn draw(&self, canvas: &mut Canvas) -> Result<()> {
+    fn draw_inner(&self, canvas: &mut dyn Canvas, ctx: Option<&DrawContext>) -> Result<()> {
         let (width, height) = canvas.size()?;
 
         let margin_top = self.margin_top.calc_fixed_size(height, 0);
@@ -328,8 +328,17 @@ impl<'a> Draw for Win<'a> {
         let width = width - (padding_left + padding_right);
         let height = height - (padding_top + padding_bottom);
 
-        let mut new_canvas = BoundedCanvas::new(top, left, width, height, canvas);
-        self.inner.draw(&mut new_canvas)
+        draw_child(self.inner, canvas, (top, left, width, height), ctx)
+    }
+}
+
+impl<'a> Draw for Win<'a> {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
+        self.draw_inner(canvas, None)
+    }
+
+    fn draw_with_context(&self, canvas: &mut dyn Canvas, ctx: &DrawContext) -> Result<()> {
+        self.draw_inner(canvas, Some(ctx))
     }
 
     fn size_hint(&self) -> (Option<usize>, Option<usize>) {
@@ -376,7 +385,7 @@ mod test {
     }
 
     impl Draw for WinHint {
-        fn draw(&self, _canvas: &mut Canvas) -> Result<()> {
+        fn draw(&self, _canvas: &mut dyn Canvas) -> Result<()> {
             unimplemented!()
         }
 
@@ -0,0 +1,325 @@
+//! A control socket to drive a running application, for remote debugging and automation.
+//!
+//! `ControlSocket::bind` listens on a Unix domain socket (readable by the user only) and runs
+//! the commands sent to it against the `Term`, one per line, each answered by a line starting
+//! with `ok` or `error`:
+//!
+//! ```text
+//! keys ctrl-a Enter           inject keys, named as for `Key::from_str`
+//! type some text              inject the characters of the text
+//! post main/list select 4     `Term::post_to` the widget `main/list`
+//! state main/list             ok <the state published by the widget>
+//! ids                         ok <the ids of the widgets with a state>
+//! overlay regions on|off      `Term::set_debug_overlay`
+//! dump                        ok <height>, followed by the lines of the screen
+//! ```
+//!
+//! e.g. with `socat - UNIX-CONNECT:/tmp/app.sock`. The socket is removed when the
+//! `ControlSocket` is dropped.
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use tuikit::control::ControlSocket;
+//! use tuikit::prelude::*;
+//!
+//! let term = Arc::new(Term::new().unwrap());
+//! let _control = ControlSocket::bind("/tmp/app.sock", term.clone()).unwrap();
+//! while let Ok(event) = term.poll_event() {
+//!     // as usual, including the injected keys
+//! }
+//! ```
+
+use crate::key::Key;
+use crate::snapshot::ScreenSnapshot;
+use crate::term::Term;
+use crate::widget_id::WidgetId;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+/// What the control socket drives, implemented by `Term`
+pub trait ControlTarget: Send + Sync {
+    fn inject_keys(&self, keys: &[Key]) -> Result<(), String>;
+
+    fn post_to(&self, target: &WidgetId, message: &str) -> Result<(), String>;
+
+    fn widget_state(&self, id: &WidgetId) -> Option<String>;
+
+    fn widget_ids(&self) -> Vec<WidgetId>;
+
+    fn set_debug_overlay(&self, name: &str, enabled: bool);
+
+    /// the current contents of the screen
+    fn snapshot(&self) -> ScreenSnapshot;
+}
+
+impl ControlTarget for Term {
+    fn inject_keys(&self, keys: &[Key]) -> Result<(), String> {
+        Term::inject_keys(self, keys).map_err(|err| err.to_string())
+    }
+
+    fn post_to(&self, target: &WidgetId, message: &str) -> Result<(), String> {
+        Term::post_to(self, target, message).map_err(|err| err.to_string())
+    }
+
+    fn widget_state(&self, id: &WidgetId) -> Option<String> {
+        Term::widget_state(self, id)
+    }
+
+    fn widget_ids(&self) -> Vec<WidgetId> {
+        Term::widget_ids(self)
+    }
+
+    fn set_debug_overlay(&self, name: &str, enabled: bool) {
+        Term::set_debug_overlay(self, name, enabled)
+    }
+
+    fn snapshot(&self) -> ScreenSnapshot {
+        Term::snapshot(self)
+    }
+}
+
+/// The listening socket, closed and removed on drop
+pub struct ControlSocket {
+    path: PathBuf,
+    stopped: Arc<AtomicBool>,
+    listener: Option<JoinHandle<()>>,
+}
+
+impl ControlSocket {
+    /// Listen on `path`, which must not exist, running the commands against `target`
+    pub fn bind<T: ControlTarget + 'static>(
+        path: impl AsRef<Path>,
+        target: Arc<T>,
+    ) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let listener = UnixListener::bind(&path)?;
+        // the commands could type anything into the application
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600))?;
+
+        let stopped = Arc::new(AtomicBool::new(false));
+        let stopped_clone = stopped.clone();
+        let listener = thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopped_clone.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let target = target.clone();
+                    thread::spawn(move || serve(stream, &*target));
+                }
+            }
+        });
+
+        Ok(Self {
+            path,
+            stopped,
+            listener: Some(listener),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+        // wake the listener up so it sees it is stopped
+        let _ = UnixStream::connect(&self.path);
+        if let Some(listener) = self.listener.take() {
+            let _ = listener.join();
+        }
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn serve(stream: UnixStream, target: &dyn ControlTarget) {
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(_) => return,
+    };
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => return,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        if writer.write_all(execute(target, &line).as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+/// Run the command `line`, return the reply, newline terminated
+fn execute(target: &dyn ControlTarget, line: &str) -> String {
+    let (command, args) = match line.trim().split_once(' ') {
+        Some((command, args)) => (command, args.trim()),
+        None => (line.trim(), ""),
+    };
+    let result = match command {
+        "keys" => args
+            .split_whitespace()
+            .map(|name| name.parse::<Key>())
+            .collect::<Result<Vec<_>, _>>()
+            .and_then(|keys| target.inject_keys(&keys))
+            .map(|_| String::new()),
+        "type" => {
+            let keys: Vec<Key> = args.chars().map(Key::Char).collect();
+            target.inject_keys(&keys).map(|_| String::new())
+        }
+        "post" => match args.split_once(' ') {
+            Some((id, message)) => target
+                .post_to(&WidgetId::from(id), message)
+                .map(|_| String::new()),
+            None => Err("usage: post <widget id> <message>".to_string()),
+        },
+        "state" => target
+            .widget_state(&WidgetId::from(args))
+            .map(|state| state.replace('\n', " "))
+            .ok_or_else(|| format!("no state for {}", args)),
+        "ids" => Ok(target
+            .widget_ids()
+            .iter()
+            .map(WidgetId::to_string)
+            .collect::<Vec<_>>()
+            .join(" ")),
+        "overlay" => {
+            let enabled = match args.split_once(' ') {
+                Some((name, "on")) => Some((name, true)),
+                Some((name, "off")) => Some((name, false)),
+                _ => None,
+            };
+            match enabled {
+                Some((name, enabled)) => {
+                    target.set_debug_overlay(name, enabled);
+                    Ok(String::new())
+                }
+                None => Err("usage: overlay <name> on|off".to_string()),
+            }
+        }
+        "dump" => {
+            let text = target.snapshot().text();
+            let lines: Vec<&str> = text.lines().collect();
+            Ok(format!("{}\n{}", lines.len(), lines.join("\n")))
+        }
+        _ => Err(format!("unknown command: {}", command)),
+    };
+
+    match result {
+        Ok(reply) if reply.is_empty() => "ok\n".to_string(),
+        Ok(reply) => format!("ok {}\n", reply),
+        Err(err) => format!("error {}\n", err.replace('\n', " ")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::canvas::Canvas;
+    use crate::screen::Screen;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder {
+        keys: Mutex<Vec<Key>>,
+        posted: Mutex<Vec<(WidgetId, String)>>,
+        overlays: Mutex<Vec<(String, bool)>>,
+    }
+
+    impl ControlTarget for Recorder {
+        fn inject_keys(&self, keys: &[Key]) -> Result<(), String> {
+            self.keys.lock().unwrap().extend_from_slice(keys);
+            Ok(())
+        }
+
+        fn post_to(&self, target: &WidgetId, message: &str) -> Result<(), String> {
+            let posted = (target.clone(), message.to_string());
+            self.posted.lock().unwrap().push(posted);
+            Ok(())
+        }
+
+        fn widget_state(&self, id: &WidgetId) -> Option<String> {
+            if id.as_str() == "main/list" {
+                Some("selected=3".to_string())
+            } else {
+                None
+            }
+        }
+
+        fn widget_ids(&self) -> Vec<WidgetId> {
+            vec![WidgetId::from("main"), WidgetId::from("main/list")]
+        }
+
+        fn set_debug_overlay(&self, name: &str, enabled: bool) {
+            self.overlays
+                .lock()
+                .unwrap()
+                .push((name.to_string(), enabled));
+        }
+
+        fn snapshot(&self) -> ScreenSnapshot {
+            let mut screen = Screen::new(3, 2);
+            let _ = screen.print(0, 0, "ab");
+            let _ = screen.print(1, 0, "cd");
+            ScreenSnapshot::from_screen(&screen)
+        }
+    }
+
+    #[test]
+    fn commands() {
+        let target = Recorder::default();
+        assert_eq!("ok\n", execute(&target, "keys ctrl-a Enter"));
+        assert_eq!("ok\n", execute(&target, "type hi"));
+        assert_eq!(
+            vec![Key::Ctrl('a'), Key::Enter, Key::Char('h'), Key::Char('i')],
+            *target.keys.lock().unwrap()
+        );
+        assert!(execute(&target, "keys ctrl-a Foo").starts_with("error"));
+        assert_eq!(4, target.keys.lock().unwrap().len());
+
+        assert_eq!("ok\n", execute(&target, "post main/list select 4"));
+        assert_eq!(
+            vec![(WidgetId::from("main/list"), "select 4".to_string())],
+            *target.posted.lock().unwrap()
+        );
+        assert_eq!("ok selected=3\n", execute(&target, "state main/list"));
+        assert_eq!("error no state for foo\n", execute(&target, "state foo"));
+        assert_eq!("ok main main/list\n", execute(&target, "ids"));
+
+        assert_eq!("ok\n", execute(&target, "overlay regions on"));
+        assert!(execute(&target, "overlay regions").starts_with("error"));
+        assert_eq!(
+            vec![("regions".to_string(), true)],
+            *target.overlays.lock().unwrap()
+        );
+
+        assert_eq!("ok 2\nab\ncd\n", execute(&target, "dump"));
+        assert_eq!("error unknown command: foo\n", execute(&target, "foo"));
+    }
+
+    #[test]
+    fn socket() {
+        let path = std::env::temp_dir().join(format!("tuikit-control-{}", std::process::id()));
+        let target = Arc::new(Recorder::default());
+        let control = ControlSocket::bind(&path, target.clone()).unwrap();
+
+        let mut stream = UnixStream::connect(control.path()).unwrap();
+        stream.write_all(b"type a\n\nids\n").unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        assert_eq!("ok", lines.next().unwrap().unwrap());
+        assert_eq!("ok main main/list", lines.next().unwrap().unwrap());
+        assert_eq!(vec![Key::Char('a')], *target.keys.lock().unwrap());
+
+        drop(control);
+        assert!(!path.exists());
+    }
+}
@@ -1,10 +1,18 @@
-///! A trait defines something that could be drawn
+//! A trait defines something that could be drawn
 use crate::canvas::Canvas;
-use crate::canvas::Result;
+use crate::canvas::{BoundedCanvas, Result};
+use crate::style::{self, Theme};
+use std::sync::Arc;
 
 /// Something that knows how to draw itself onto the canvas
 pub trait Draw {
-    fn draw(&self, canvas: &mut Canvas) -> Result<()>;
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()>;
+
+    /// Draw knowing the frame, e.g. to reset a scroll offset on resize. `Term::draw` and the
+    /// containers call this one, which falls back to `draw` unless overridden.
+    fn draw_with_context(&self, canvas: &mut dyn Canvas, _ctx: &DrawContext) -> Result<()> {
+        self.draw(canvas)
+    }
 
     /// the (width, height) of the content
     /// it will be the hint for layouts to calculate the final size
@@ -14,21 +22,134 @@ pub trait Draw {
 }
 
 impl<T: Draw> Draw for &T {
-    fn draw(&self, canvas: &mut Canvas) -> Result<()> {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
         (*self).draw(canvas)
     }
 
+    fn draw_with_context(&self, canvas: &mut dyn Canvas, ctx: &DrawContext) -> Result<()> {
+        (*self).draw_with_context(canvas, ctx)
+    }
+
     fn size_hint(&self) -> (Option<usize>, Option<usize>) {
         (*self).size_hint()
     }
 }
 
 impl<T: Draw + ?Sized> Draw for Box<T> {
-    fn draw(&self, canvas: &mut Canvas) -> Result<()> {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
         self.as_ref().draw(canvas)
     }
 
+    fn draw_with_context(&self, canvas: &mut dyn Canvas, ctx: &DrawContext) -> Result<()> {
+        self.as_ref().draw_with_context(canvas, ctx)
+    }
+
     fn size_hint(&self) -> (Option<usize>, Option<usize>) {
         self.as_ref().size_hint()
     }
 }
+
+/// What a widget is told of the frame it is drawn in, see `Draw::draw_with_context`
+#[derive(Debug, Clone)]
+pub struct DrawContext {
+    /// the area of the canvas on the screen: `(top, left, width, height)`
+    pub rect: (usize, usize, usize, usize),
+    /// this is the first frame drawn since the terminal was resized (or the term started)
+    pub resized: bool,
+    /// the theme resolving the style slots, the global one by default
+    pub theme: Arc<Theme>,
+}
+
+impl DrawContext {
+    /// The context of a whole screen of `width` x `height`
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            rect: (0, 0, width, height),
+            resized: false,
+            theme: style::theme(),
+        }
+    }
+
+    pub fn resized(mut self, resized: bool) -> Self {
+        self.resized = resized;
+        self
+    }
+
+    pub fn theme(mut self, theme: Arc<Theme>) -> Self {
+        self.theme = theme;
+        self
+    }
+
+    /// The context of the area at `(top, left)` of this one, of `width` x `height`
+    pub fn sub(&self, top: usize, left: usize, width: usize, height: usize) -> Self {
+        let (parent_top, parent_left, _, _) = self.rect;
+        Self {
+            rect: (parent_top + top, parent_left + left, width, height),
+            resized: self.resized,
+            theme: self.theme.clone(),
+        }
+    }
+}
+
+/// Draw `child` in the area `(top, left, width, height)` of `canvas`, with the context of that
+/// area if the container is drawn with one
+pub(crate) fn draw_child(
+    child: &dyn Draw,
+    canvas: &mut dyn Canvas,
+    (top, left, width, height): (usize, usize, usize, usize),
+    ctx: Option<&DrawContext>,
+) -> Result<()> {
+    let mut canvas = BoundedCanvas::new(top, left, width, height, canvas);
+    match ctx {
+        Some(ctx) => child.draw_with_context(&mut canvas, &ctx.sub(top, left, width, height)),
+        None => child.draw(&mut canvas),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::container::{HSplit, Win};
+    use crate::screen::Screen;
+    use std::sync::Mutex;
+
+    /// the rect and resized flag of each context
+    type Contexts = Mutex<Vec<((usize, usize, usize, usize), bool)>>;
+
+    #[derive(Default)]
+    struct Recorder {
+        contexts: Contexts,
+    }
+
+    impl Draw for Recorder {
+        fn draw(&self, _canvas: &mut dyn Canvas) -> Result<()> {
+            Ok(())
+        }
+
+        fn draw_with_context(&self, canvas: &mut dyn Canvas, ctx: &DrawContext) -> Result<()> {
+            let (width, height) = canvas.size()?;
+            assert_eq!((width, height), (ctx.rect.2, ctx.rect.3));
+            self.contexts.lock().unwrap().push((ctx.rect, ctx.resized));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn context_through_containers() {
+        let left = Recorder::default();
+        let right = Recorder::default();
+        let split = HSplit::default()
+            .split(Win::new(&left).basis(4).grow(0).shrink(0))
+            .split(Win::new(&right).margin_top(1).border_left(true).basis(6));
+        let mut screen = Screen::new(10, 5);
+
+        let ctx = DrawContext::new(10, 5).resized(true);
+        split.draw_with_context(&mut screen, &ctx).unwrap();
+        assert_eq!(vec![((0, 0, 4, 5), true)], *left.contexts.lock().unwrap());
+        assert_eq!(vec![((1, 5, 5, 4), true)], *right.contexts.lock().unwrap());
+
+        // drawn without a context, the children are too
+        split.draw(&mut screen).unwrap();
+        assert_eq!(1, left.contexts.lock().unwrap().len());
+    }
+}
@@ -0,0 +1,242 @@
+//! Draw from other threads without holding the `Term`.
+//!
+//! A `DrawHandle` queues drawing commands, the queue is applied to the screen buffer on the
+//! next `Term::present`, so a worker thread could update "its" panel while the render thread
+//! owns the event loop. Commands are applied in the order they were queued.
+//!
+//! ```no_run
+//! use tuikit::prelude::*;
+//! use std::thread;
+//!
+//! let term = Term::new().unwrap();
+//! let status = term.draw_handle().area(0, 0, 20, 1);
+//! thread::spawn(move || {
+//!     status.print(0, 0, "done", Attr::default());
+//! });
+//! // ... the render thread calls `term.present()` as usual
+//! ```
+
+use crate::attr::Attr;
+use crate::canvas::{BoundedCanvas, Canvas, Result};
+use crate::cell::Cell;
+use crate::draw::Draw;
+use crate::region::Region;
+use crate::spinlock::SpinLock;
+use crate::width::char_width;
+use std::sync::Arc;
+
+type DrawCommand = Box<dyn FnOnce(&mut dyn Canvas) -> Result<()> + Send>;
+
+/// `(top, left, width, height)`, `None` for the whole screen
+type Area = Option<(usize, usize, usize, usize)>;
+
+#[derive(Default)]
+pub(crate) struct DrawQueue {
+    commands: Vec<(Area, DrawCommand)>,
+}
+
+impl DrawQueue {
+    /// apply and remove the queued commands
+    pub(crate) fn apply(&mut self, canvas: &mut dyn Canvas) {
+        for (area, command) in self.commands.drain(..) {
+            // a failing command doesn't prevent the others
+            let _ = match area {
+                Some((top, left, width, height)) => {
+                    command(&mut BoundedCanvas::new(top, left, width, height, canvas))
+                }
+                None => command(canvas),
+            };
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+enum DrawOp {
+    Clear,
+    Cell(usize, usize, Cell),
+    Cursor(usize, usize),
+    ShowCursor(bool),
+    Region(Region),
+    Volatile(usize),
+}
+
+/// A canvas of a given size recording what is drawn on it, replayed later on the real one. For
+/// the code drawing on a frame (the plugin layers, the frame hooks) to run with the term
+/// unlocked, so that it could call the term back.
+pub(crate) struct DetachedCanvas {
+    width: usize,
+    height: usize,
+    ops: Vec<DrawOp>,
+}
+
+impl DetachedCanvas {
+    pub(crate) fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            ops: Vec::new(),
+        }
+    }
+
+    /// draw the recorded operations on `canvas`, in order
+    pub(crate) fn apply(self, canvas: &mut dyn Canvas) -> Result<()> {
+        for op in self.ops {
+            match op {
+                DrawOp::Clear => canvas.clear()?,
+                DrawOp::Cell(row, col, cell) => canvas.put_cell(row, col, cell).map(|_| ())?,
+                DrawOp::Cursor(row, col) => canvas.set_cursor(row, col)?,
+                DrawOp::ShowCursor(show) => canvas.show_cursor(show)?,
+                DrawOp::Region(region) => canvas.register_region(region)?,
+                DrawOp::Volatile(row) => canvas.mark_volatile(row)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Canvas for DetachedCanvas {
+    fn size(&self) -> Result<(usize, usize)> {
+        Ok((self.width, self.height))
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.ops.push(DrawOp::Clear);
+        Ok(())
+    }
+
+    fn put_cell(&mut self, row: usize, col: usize, cell: Cell) -> Result<usize> {
+        self.ops.push(DrawOp::Cell(row, col, cell));
+        Ok(char_width(cell.ch))
+    }
+
+    fn set_cursor(&mut self, row: usize, col: usize) -> Result<()> {
+        self.ops.push(DrawOp::Cursor(row, col));
+        Ok(())
+    }
+
+    fn show_cursor(&mut self, show: bool) -> Result<()> {
+        self.ops.push(DrawOp::ShowCursor(show));
+        Ok(())
+    }
+
+    fn register_region(&mut self, region: Region) -> Result<()> {
+        self.ops.push(DrawOp::Region(region));
+        Ok(())
+    }
+
+    fn mark_volatile(&mut self, row: usize) -> Result<()> {
+        self.ops.push(DrawOp::Volatile(row));
+        Ok(())
+    }
+}
+
+/// A cloneable handle to queue drawing on (an area of) the screen, see the module doc
+#[derive(Clone)]
+pub struct DrawHandle {
+    queue: Arc<SpinLock<DrawQueue>>,
+    area: Area,
+}
+
+impl DrawHandle {
+    pub(crate) fn new(queue: Arc<SpinLock<DrawQueue>>) -> Self {
+        Self { queue, area: None }
+    }
+
+    /// A handle restricted to `width` x `height` cells at `(top, left)` of this handle's area,
+    /// drawing outside of it is clipped
+    pub fn area(&self, top: usize, left: usize, width: usize, height: usize) -> Self {
+        let area = match self.area {
+            Some((parent_top, parent_left, parent_width, parent_height)) => (
+                parent_top + top,
+                parent_left + left,
+                width.min(parent_width.saturating_sub(left)),
+                height.min(parent_height.saturating_sub(top)),
+            ),
+            None => (top, left, width, height),
+        };
+        Self {
+            queue: self.queue.clone(),
+            area: Some(area),
+        }
+    }
+
+    /// queue a drawing command
+    pub fn draw_with(&self, command: impl FnOnce(&mut dyn Canvas) -> Result<()> + Send + 'static) {
+        self.queue
+            .lock()
+            .commands
+            .push((self.area, Box::new(command)));
+    }
+
+    /// queue clearing the area and drawing `widget` on it
+    pub fn draw(&self, widget: impl Draw + Send + 'static) {
+        self.draw_with(move |canvas| {
+            canvas.clear()?;
+            widget.draw(canvas)
+        });
+    }
+
+    pub fn print(&self, row: usize, col: usize, content: &str, attr: impl Into<Attr>) {
+        let content = content.to_string();
+        let attr = attr.into();
+        self.draw_with(move |canvas| canvas.print_with_attr(row, col, &content, attr).map(|_| ()));
+    }
+
+    pub fn clear(&self) {
+        self.draw_with(|canvas| canvas.clear());
+    }
+
+    /// whether some commands are waiting for the next `present`
+    pub fn is_pending(&self) -> bool {
+        !self.queue.lock().is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::screen::Screen;
+    use std::thread;
+
+    fn row_text(screen: &Screen, row: usize) -> String {
+        screen
+            .iter_cell()
+            .filter(|(r, _, _)| *r == row)
+            .map(|(_, _, cell)| cell.ch)
+            .collect()
+    }
+
+    #[test]
+    fn commands_are_applied_in_order() {
+        let queue = Arc::new(SpinLock::new(DrawQueue::default()));
+        let handle = DrawHandle::new(queue.clone());
+        let panel = handle.area(1, 2, 3, 1);
+
+        let worker = {
+            let panel = panel.clone();
+            thread::spawn(move || {
+                panel.print(0, 0, "abcdef", Attr::default());
+                panel.print(0, 1, "X", Attr::default());
+            })
+        };
+        worker.join().unwrap();
+        handle.print(0, 0, "top", Attr::default());
+        assert!(handle.is_pending());
+
+        let mut screen = Screen::new(6, 2);
+        queue.lock().apply(&mut screen);
+        assert!(!handle.is_pending());
+        assert_eq!("top   ", row_text(&screen, 0));
+        assert_eq!("  aXc ", row_text(&screen, 1));
+    }
+
+    #[test]
+    fn nested_area_is_clipped_by_parent() {
+        let queue = Arc::new(SpinLock::new(DrawQueue::default()));
+        let panel = DrawHandle::new(queue).area(1, 1, 4, 4).area(2, 2, 10, 10);
+        assert_eq!(Some((3, 3, 2, 2)), panel.area);
+    }
+}
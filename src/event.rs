@@ -1,16 +1,310 @@
 //! events a `Term` could return
+//!
+//! The events are grouped by origin, so that matching on one group doesn't break when another
+//! grows: `Key` for the keyboard, `Mouse` for the mouse, `Terminal` for the terminal itself
+//! (resizes, restarts, replies to the capability queries, the input closed) and `App` for the
+//! events of the application, sent with `Term::send_event`. The others are the services of
+//! tuikit (timers, messages to the widgets...).
+//!
+//! ```no_run
+//! use tuikit::event::{KeyEvent, MouseEvent, TerminalEvent};
+//! use tuikit::prelude::*;
+//!
+//! struct Loaded(Vec<String>);
+//!
+//! let term = Term::new().unwrap();
+//! let _ = term.send_event(Event::app(Loaded(vec!["README.md".to_string()])));
+//! while let Ok(event) = term.poll_event() {
+//!     match event {
+//!         Event::Key(KeyEvent { key: Key::Char('q'), .. }) => break,
+//!         Event::Terminal(TerminalEvent::Closed) => break,
+//!         Event::Mouse(MouseEvent::Press(_, col, row, _)) => println!("clicked {} {}", col, row),
+//!         Event::Terminal(TerminalEvent::Resize { width, height }) => {
+//!             println!("resized to {}x{}", width, height)
+//!         }
+//!         Event::App(payload) => {
+//!             if let Some(Loaded(files)) = payload.downcast_ref::<Loaded>() {
+//!                 println!("{} files", files.len());
+//!             }
+//!         }
+//!         _ => {}
+//!     }
+//! }
+//! ```
 
-pub use crate::key::Key;
+pub use crate::key::{Key, Modifiers, MouseButton};
+use crate::widget_id::WidgetId;
+use std::any::Any;
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Event {
-    Key(Key),
+    Key(KeyEvent),
+    Mouse(MouseEvent),
+    Terminal(TerminalEvent),
+    /// an event of the application, see `Event::app`
+    App(Box<dyn Any + Send>),
+    /// a held key is considered released, see `TermOptions::synthetic_key_release`
+    KeyUp(Key),
+    /// the escape chord was typed, the pane no longer grabs the input, see `Term::grab_input`
+    InputReleased(String),
+    /// a message posted to the widget `target` with `Term::post_to`
+    Message {
+        target: WidgetId,
+        message: String,
+    },
+    /// a debug overlay was toggled with `Term::set_debug_overlay`, time to redraw
+    DebugOverlay {
+        name: String,
+        enabled: bool,
+    },
+    /// the timer of this id expired, see `Term::set_timer`
+    Timer(String),
+    /// a watched path changed, see `Term::watch_path`
+    #[cfg(feature = "fs-watch")]
+    Fs(notify::Event),
+    /// a config file changed and settled, see `Term::watch_config_file`
+    #[cfg(feature = "fs-watch")]
+    ConfigReloaded(std::path::PathBuf),
+}
+
+impl Event {
+    /// Wrap an event of the application, e.g. the result of a background job, to be sent
+    /// with `Term::send_event` and returned as `Event::App`
+    pub fn app<T: Any + Send>(payload: T) -> Self {
+        Event::App(Box::new(payload))
+    }
+
+    /// The key typed, `None` for the other events
+    ///
+    /// ```
+    /// use tuikit::prelude::*;
+    ///
+    /// assert_eq!(Some(&Key::Ctrl('c')), Event::from_key(Key::Ctrl('c')).key());
+    /// assert_eq!(None, Event::from_key(Key::FocusGained).key());
+    /// ```
+    pub fn key(&self) -> Option<&Key> {
+        match self {
+            Event::Key(event) => Some(&event.key),
+            _ => None,
+        }
+    }
+
+    /// Sort a key read from the terminal: the mouse keys are `Event::Mouse`, the focus changes
+    /// and the replies to the queries `Event::Terminal`, the others `Event::Key`
+    pub fn from_key(key: Key) -> Self {
+        match key {
+            Key::FocusGained => return Event::Terminal(TerminalEvent::FocusGained),
+            Key::FocusLost => return Event::Terminal(TerminalEvent::FocusLost),
+            Key::CursorPos(row, col) => {
+                return Event::Terminal(TerminalEvent::CursorPos { row, col })
+            }
+            Key::DeviceAttributes(_)
+            | Key::BackgroundColor(..)
+            | Key::TermcapReply(_)
+            | Key::KeyboardFlags(_) => return Event::Terminal(TerminalEvent::Reply(key)),
+            _ => {}
+        }
+        match MouseEvent::from_key(&key) {
+            Some(mouse) => Event::Mouse(mouse),
+            None => Event::Key(KeyEvent {
+                modifiers: key.modifiers(),
+                key,
+            }),
+        }
+    }
+}
+
+/// A key typed on the keyboard, i.e. a `Key` but the mouse events and the replies to the
+/// queries, see `Event::from_key`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct KeyEvent {
+    /// the key as bound in the keymaps, e.g. `Key::Ctrl('a')`
+    pub key: Key,
+    /// the modifiers held, the ones of `key` included
+    pub modifiers: Modifiers,
+}
+
+impl KeyEvent {
+    /// The key event of a keyboard key, `None` for the mouse events and the replies
+    ///
+    /// ```
+    /// use tuikit::event::KeyEvent;
+    /// use tuikit::key::{Key, Modifiers};
+    ///
+    /// let event = KeyEvent::from_key(Key::Ctrl('a')).unwrap();
+    /// assert_eq!((Key::Ctrl('a'), Modifiers::CTRL), (event.key, event.modifiers));
+    /// assert_eq!(None, KeyEvent::from_key(Key::FocusLost));
+    /// ```
+    pub fn from_key(key: Key) -> Option<Self> {
+        match Event::from_key(key) {
+            Event::Key(event) => Some(event),
+            _ => None,
+        }
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        event.key
+    }
+}
+
+/// A mouse event, at (col, row) on the screen, i.e. (x, y) from 1 as reported by the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum MouseEvent {
+    Press(MouseButton, u16, u16, Modifiers),
+    Release(MouseButton, u16, u16, Modifiers),
+    /// moved while a button is held, see `Term::enable_mouse_drag_support`
+    Hold(u16, u16, Modifiers),
+    /// moved with no button held, see `Term::enable_mouse_motion_support`
+    Move(u16, u16, Modifiers),
+    /// the second left press in a row on the same cell, see `TermOptions::click_interval`
+    DoubleClick(u16, u16),
+    /// the third one
+    TripleClick(u16, u16),
+}
+
+impl MouseEvent {
+    /// the mouse event of a mouse key, `None` for the other keys
+    pub fn from_key(key: &Key) -> Option<Self> {
+        match *key {
+            Key::MousePress(button, col, row, modifiers) => {
+                Some(MouseEvent::Press(button, col, row, modifiers))
+            }
+            Key::MouseRelease(button, col, row, modifiers) => {
+                Some(MouseEvent::Release(button, col, row, modifiers))
+            }
+            Key::MouseHold(col, row, modifiers) => Some(MouseEvent::Hold(col, row, modifiers)),
+            Key::MouseMove(col, row, modifiers) => Some(MouseEvent::Move(col, row, modifiers)),
+            Key::DoubleClick(col, row) => Some(MouseEvent::DoubleClick(col, row)),
+            Key::TripleClick(col, row) => Some(MouseEvent::TripleClick(col, row)),
+            _ => None,
+        }
+    }
+
+    /// (col, row) of the pointer, from 1
+    pub fn position(&self) -> (u16, u16) {
+        match *self {
+            MouseEvent::Press(_, col, row, _)
+            | MouseEvent::Release(_, col, row, _)
+            | MouseEvent::Hold(col, row, _)
+            | MouseEvent::Move(col, row, _)
+            | MouseEvent::DoubleClick(col, row)
+            | MouseEvent::TripleClick(col, row) => (col, row),
+        }
+    }
+
+    /// the modifier keys held, none for the clicks
+    pub fn modifiers(&self) -> Modifiers {
+        match *self {
+            MouseEvent::Press(_, _, _, modifiers)
+            | MouseEvent::Release(_, _, _, modifiers)
+            | MouseEvent::Hold(_, _, modifiers)
+            | MouseEvent::Move(_, _, modifiers) => modifiers,
+            _ => Modifiers::empty(),
+        }
+    }
+}
+
+/// The mouse key, for the widgets handling both keys and mouse in `on_key`
+impl From<MouseEvent> for Key {
+    fn from(mouse: MouseEvent) -> Self {
+        match mouse {
+            MouseEvent::Press(button, col, row, modifiers) => {
+                Key::MousePress(button, col, row, modifiers)
+            }
+            MouseEvent::Release(button, col, row, modifiers) => {
+                Key::MouseRelease(button, col, row, modifiers)
+            }
+            MouseEvent::Hold(col, row, modifiers) => Key::MouseHold(col, row, modifiers),
+            MouseEvent::Move(col, row, modifiers) => Key::MouseMove(col, row, modifiers),
+            MouseEvent::DoubleClick(col, row) => Key::DoubleClick(col, row),
+            MouseEvent::TripleClick(col, row) => Key::TripleClick(col, row),
+        }
+    }
+}
+
+/// An event of the terminal itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TerminalEvent {
     Resize {
         width: usize,
         height: usize,
     },
     Restarted,
+    /// a reply to a capability query arrived (e.g. the background color, so possibly a new
+    /// theme), see `Term::capabilities`
+    CapabilitiesUpdated,
+    /// the terminal window got the focus, see `TermOptions::focus_events`
+    FocusGained,
+    /// the terminal window lost the focus, e.g. time to pause the animations
+    FocusLost,
+    /// a reply to a cursor position query nobody waited for, from 0
+    CursorPos {
+        row: u16,
+        col: u16,
+    },
+    /// a reply to a capability query (DA1, OSC 11, XTGETTCAP, the kitty keyboard flags) as
+    /// sorted by `Event::from_key`, `Term` reads them itself and reports `CapabilitiesUpdated`
+    Reply(Key),
+    /// the input was closed, e.g. the terminal went away: no more keys would come
+    Closed,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sort_keys() {
+        assert!(matches!(
+            Event::from_key(Key::Char('a')),
+            Event::Key(KeyEvent {
+                key: Key::Char('a'),
+                ..
+            })
+        ));
+        let event = KeyEvent::from_key(Key::AltShiftUp).unwrap();
+        assert_eq!(Modifiers::ALT | Modifiers::SHIFT, event.modifiers);
+        assert_eq!(Key::AltShiftUp, Key::from(event));
+        assert_eq!(
+            None,
+            KeyEvent::from_key(Key::MouseMove(1, 1, Modifiers::empty()))
+        );
+        let press = Key::MousePress(MouseButton::Left, 3, 4, Modifiers::CTRL);
+        match Event::from_key(press.clone()) {
+            Event::Mouse(mouse) => {
+                assert_eq!(
+                    MouseEvent::Press(MouseButton::Left, 3, 4, Modifiers::CTRL),
+                    mouse
+                );
+                assert_eq!((3, 4), mouse.position());
+                assert_eq!(Modifiers::CTRL, mouse.modifiers());
+                assert_eq!(press, Key::from(mouse));
+            }
+            event => panic!("unexpected {:?}", event),
+        }
+
+        assert!(matches!(
+            Event::from_key(Key::FocusLost),
+            Event::Terminal(TerminalEvent::FocusLost)
+        ));
+        assert!(matches!(
+            Event::from_key(Key::CursorPos(2, 5)),
+            Event::Terminal(TerminalEvent::CursorPos { row: 2, col: 5 })
+        ));
+        assert!(matches!(
+            Event::from_key(Key::BackgroundColor(0, 0, 0)),
+            Event::Terminal(TerminalEvent::Reply(Key::BackgroundColor(0, 0, 0)))
+        ));
 
-    #[doc(hidden)]
-    __Nonexhaustive,
+        match Event::app(42usize) {
+            Event::App(payload) => assert_eq!(Some(&42), payload.downcast_ref::<usize>()),
+            event => panic!("unexpected {:?}", event),
+        }
+    }
 }
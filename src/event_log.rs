@@ -0,0 +1,175 @@
+//! A bounded log of the recent input, for post-mortem debugging.
+//!
+//! `Term` keeps the last N raw input bytes and events it delivered. If the environment variable
+//! `TUIKIT_EVENT_LOG` is set to a file path, the log is dumped to that file when the program
+//! panics, so that "it crashed when I pressed something" could be reproduced.
+//!
+//! ```sh
+//! TUIKIT_EVENT_LOG=/tmp/tuikit-events.log ./my-app
+//! ```
+
+use crate::spinlock::SpinLock;
+use lazy_static::lazy_static;
+use std::collections::VecDeque;
+use std::env;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Write};
+use std::panic;
+use std::sync::{Arc, Once};
+use std::time::Instant;
+
+const ENV_EVENT_LOG: &str = "TUIKIT_EVENT_LOG";
+
+/// default number of entries kept in the log
+pub const DEFAULT_EVENT_LOG_CAPACITY: usize = 256;
+
+lazy_static! {
+    /// the log to be dumped on panic, it is the one of the most recently created `Term`
+    static ref PANIC_LOG: SpinLock<Option<Arc<SpinLock<EventLog>>>> = SpinLock::new(None);
+}
+
+static PANIC_HOOK: Once = Once::new();
+
+/// An entry of the log
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogEntry {
+    /// bytes read from the input
+    Input(Vec<u8>),
+    /// the (debug format of) event returned to the application
+    Event(String),
+}
+
+impl fmt::Display for LogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LogEntry::Input(bytes) => {
+                write!(f, "input:")?;
+                for byte in bytes {
+                    write!(f, " {:02x}", byte)?;
+                }
+                write!(f, " {:?}", String::from_utf8_lossy(bytes))
+            }
+            LogEntry::Event(event) => write!(f, "event: {}", event),
+        }
+    }
+}
+
+/// Ring buffer of the last `capacity` log entries
+#[derive(Debug)]
+pub struct EventLog {
+    capacity: usize,
+    start: Instant,
+    entries: VecDeque<(Instant, LogEntry)>,
+}
+
+impl EventLog {
+    /// create a log keeping at most `capacity` entries, 0 disables logging
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            start: Instant::now(),
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn push(&mut self, entry: LogEntry) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        while self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((Instant::now(), entry));
+    }
+
+    pub fn push_input(&mut self, bytes: &[u8]) {
+        if !bytes.is_empty() {
+            self.push(LogEntry::Input(bytes.to_vec()));
+        }
+    }
+
+    pub fn push_event(&mut self, event: &impl fmt::Debug) {
+        if self.capacity > 0 {
+            self.push(LogEntry::Event(format!("{:?}", event)));
+        }
+    }
+
+    /// entries from the oldest to the newest
+    pub fn entries(&self) -> impl Iterator<Item = &LogEntry> {
+        self.entries.iter().map(|(_, entry)| entry)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// write the entries line by line, prefixed with the milliseconds since the log was created
+    pub fn dump(&self, writer: &mut dyn Write) -> io::Result<()> {
+        for (time, entry) in self.entries.iter() {
+            let elapsed = time.saturating_duration_since(self.start);
+            writeln!(writer, "[{:>10}ms] {}", elapsed.as_millis(), entry)?;
+        }
+        writer.flush()
+    }
+}
+
+/// Dump `log` to the file in `TUIKIT_EVENT_LOG` on panic. Only the log registered last is
+/// dumped, does nothing if the environment variable is not set.
+pub(crate) fn dump_on_panic(log: Arc<SpinLock<EventLog>>) {
+    let path = match env::var_os(ENV_EVENT_LOG) {
+        Some(path) => path,
+        None => return,
+    };
+
+    *PANIC_LOG.lock() = Some(log);
+
+    PANIC_HOOK.call_once(move || {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            // never block in the panic hook, the lock might be held by the panicking thread
+            if let Some(registered) = PANIC_LOG.try_lock() {
+                if let Some(log) = registered.as_ref().and_then(|log| log.try_lock()) {
+                    if let Ok(mut file) = File::create(&path) {
+                        let _ = writeln!(file, "panic: {}", info);
+                        let _ = log.dump(&mut file);
+                    }
+                }
+            }
+            default_hook(info);
+        }));
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn log_should_keep_last_entries() {
+        let mut log = EventLog::new(2);
+        log.push_input(b"\x1b[A");
+        log.push_event(&"a");
+        log.push_input(b"");
+        log.push_input(b"q");
+
+        let entries: Vec<String> = log.entries().map(|entry| entry.to_string()).collect();
+        assert_eq!(vec!["event: \"a\"", "input: 71 \"q\""], entries);
+
+        let mut dumped = Vec::new();
+        log.dump(&mut dumped).unwrap();
+        assert_eq!(2, String::from_utf8(dumped).unwrap().lines().count());
+    }
+
+    #[test]
+    fn empty_log_should_record_nothing() {
+        let mut log = EventLog::new(0);
+        log.push_input(b"a");
+        assert_eq!(0, log.entries().count());
+    }
+}
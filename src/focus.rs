@@ -0,0 +1,298 @@
+//! Move the focus between widgets, in Tab order or spatially.
+//!
+//! A `FocusRing` knows the focusable widgets by the names of their regions (see
+//! `tuikit::region`). Tab and BackTab walk them in the order given, while Alt+Arrow moves to
+//! the nearest one in that direction, found from the rectangles registered while drawing the
+//! last frame, which is what multi-pane dashboards need.
+//!
+//! ```
+//! use tuikit::focus::FocusRing;
+//! use tuikit::key::Key;
+//! use tuikit::region::Region;
+//!
+//! // +------+-------+
+//! // | list | preview|
+//! // +------+-------+
+//! // |    input     |
+//! // +--------------+
+//! let regions = vec![
+//!     Region::new("list", 0, 0, 10, 5),
+//!     Region::new("preview", 0, 10, 10, 5),
+//!     Region::new("input", 5, 0, 20, 1),
+//! ];
+//! let mut focus = FocusRing::new(&["input", "list", "preview"]);
+//! assert_eq!(Some("input"), focus.focused());
+//!
+//! assert!(focus.on_key(&Key::AltUp, &regions));
+//! assert_eq!(Some("list"), focus.focused());
+//! assert!(focus.on_key(&Key::AltRight, &regions));
+//! assert_eq!(Some("preview"), focus.focused());
+//! assert!(focus.on_key(&Key::Tab, &regions));
+//! assert_eq!(Some("input"), focus.focused());
+//! ```
+
+use crate::key::Key;
+use crate::region::Region;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+/// The focusable widgets in Tab order, with the focused one
+#[derive(Debug, Clone, Default)]
+pub struct FocusRing {
+    names: Vec<String>,
+    focused: Option<usize>,
+}
+
+impl FocusRing {
+    /// `names` in Tab order, the first one is focused
+    pub fn new(names: &[&str]) -> Self {
+        Self {
+            names: names.iter().map(|name| name.to_string()).collect(),
+            focused: if names.is_empty() { None } else { Some(0) },
+        }
+    }
+
+    pub fn focused(&self) -> Option<&str> {
+        self.focused.map(|index| self.names[index].as_str())
+    }
+
+    pub fn is_focused(&self, name: &str) -> bool {
+        self.focused() == Some(name)
+    }
+
+    /// focus `name`, return false if it is not in the ring
+    pub fn focus(&mut self, name: &str) -> bool {
+        match self.names.iter().position(|n| n == name) {
+            Some(index) => {
+                self.focused = Some(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// append `name` to the Tab order
+    pub fn push(&mut self, name: &str) {
+        self.names.push(name.to_string());
+        if self.focused.is_none() {
+            self.focused = Some(0);
+        }
+    }
+
+    /// remove `name`, the focus moves to the next one if it was focused
+    pub fn remove(&mut self, name: &str) {
+        if let Some(index) = self.names.iter().position(|n| n == name) {
+            self.names.remove(index);
+            self.focused = match self.focused {
+                _ if self.names.is_empty() => None,
+                Some(focused) if focused > index => Some(focused - 1),
+                Some(focused) => Some(focused.min(self.names.len() - 1)),
+                None => None,
+            };
+        }
+    }
+
+    /// Tab
+    pub fn focus_next(&mut self) {
+        if let Some(focused) = self.focused {
+            self.focused = Some((focused + 1) % self.names.len());
+        }
+    }
+
+    /// BackTab
+    pub fn focus_prev(&mut self) {
+        if let Some(focused) = self.focused {
+            self.focused = Some((focused + self.names.len() - 1) % self.names.len());
+        }
+    }
+
+    /// Move the focus to the nearest focusable region in `direction` of the focused one,
+    /// return false if there is none. Regions of the same name are merged, e.g. a list
+    /// registering every item.
+    pub fn focus_towards(&mut self, direction: Direction, regions: &[Region]) -> bool {
+        let current = match self.focused().and_then(|name| bounds(name, regions)) {
+            Some(current) => current,
+            None => return false,
+        };
+
+        let focused = self.focused;
+        let nearest = self
+            .names
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| Some(*index) != focused)
+            .filter_map(|(index, name)| {
+                let candidate = bounds(name, regions)?;
+                distance(&current, &candidate, direction).map(|distance| (distance, index))
+            })
+            .min();
+
+        match nearest {
+            Some((_, index)) => {
+                self.focused = Some(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Handle Tab, BackTab and Alt+Arrow, return true if the key was consumed
+    pub fn on_key(&mut self, key: &Key, regions: &[Region]) -> bool {
+        match key {
+            Key::Tab => self.focus_next(),
+            Key::BackTab => self.focus_prev(),
+            Key::AltUp => return self.focus_towards(Direction::Up, regions),
+            Key::AltDown => return self.focus_towards(Direction::Down, regions),
+            Key::AltLeft => return self.focus_towards(Direction::Left, regions),
+            Key::AltRight => return self.focus_towards(Direction::Right, regions),
+            _ => return false,
+        }
+        self.focused.is_some()
+    }
+}
+
+/// (top, left, bottom, right) of the regions named `name`, bottom and right excluded
+pub(crate) type Bounds = (usize, usize, usize, usize);
+
+pub(crate) fn bounds(name: &str, regions: &[Region]) -> Option<Bounds> {
+    regions
+        .iter()
+        .filter(|region| region.name == name && region.width > 0 && region.height > 0)
+        .map(|r| (r.row, r.col, r.row + r.height, r.col + r.width))
+        .fold(None, |acc, (top, left, bottom, right)| match acc {
+            None => Some((top, left, bottom, right)),
+            Some((t, l, b, r)) => Some((t.min(top), l.min(left), b.max(bottom), r.max(right))),
+        })
+}
+
+/// How far apart the ranges `[a_start, a_end)` and `[b_start, b_end)` are, 0 if they overlap
+/// and 1 if they are next to each other
+fn gap(a_start: usize, a_end: usize, b_start: usize, b_end: usize) -> usize {
+    if b_start >= a_end {
+        b_start - a_end + 1
+    } else if a_start >= b_end {
+        a_start - b_end + 1
+    } else {
+        0
+    }
+}
+
+/// How far `to` is from `from` in `direction`, `None` if it is not in that direction. Being
+/// off the axis costs more than being far along it, so an aligned pane wins over a closer
+/// diagonal one.
+fn distance(from: &Bounds, to: &Bounds, direction: Direction) -> Option<usize> {
+    let (top, left, bottom, right) = *from;
+    let (to_top, to_left, to_bottom, to_right) = *to;
+    let (along, across) = match direction {
+        Direction::Up if to_bottom <= top => (top - to_bottom, gap(left, right, to_left, to_right)),
+        Direction::Down if to_top >= bottom => {
+            (to_top - bottom, gap(left, right, to_left, to_right))
+        }
+        Direction::Left if to_right <= left => {
+            (left - to_right, gap(top, bottom, to_top, to_bottom))
+        }
+        Direction::Right if to_left >= right => {
+            (to_left - right, gap(top, bottom, to_top, to_bottom))
+        }
+        _ => return None,
+    };
+    Some(along + 2 * across)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // +---+---+---+
+    // | a | b | c |
+    // +---+---+---+
+    // |   d   | e |
+    // +-------+---+
+    fn regions() -> Vec<Region> {
+        vec![
+            Region::new("a", 0, 0, 10, 5),
+            Region::new("b", 0, 10, 10, 5),
+            Region::new("c", 0, 20, 10, 5),
+            Region::new("d", 5, 0, 20, 5),
+            Region::new("e", 5, 20, 10, 5),
+        ]
+    }
+
+    #[test]
+    fn tab_order() {
+        let mut focus = FocusRing::new(&["a", "b", "c"]);
+        focus.focus_prev();
+        assert_eq!(Some("c"), focus.focused());
+        focus.focus_next();
+        assert_eq!(Some("a"), focus.focused());
+
+        focus.focus("c");
+        focus.remove("c");
+        assert_eq!(Some("b"), focus.focused());
+        focus.remove("a");
+        assert_eq!(Some("b"), focus.focused());
+        focus.remove("b");
+        assert_eq!(None, focus.focused());
+        assert!(!focus.on_key(&Key::Tab, &[]));
+    }
+
+    #[test]
+    fn spatial() {
+        let regions = regions();
+        let mut focus = FocusRing::new(&["a", "b", "c", "d", "e"]);
+
+        assert!(!focus.focus_towards(Direction::Left, &regions));
+        assert!(!focus.focus_towards(Direction::Up, &regions));
+        assert!(focus.focus_towards(Direction::Right, &regions));
+        assert_eq!(Some("b"), focus.focused());
+        assert!(focus.focus_towards(Direction::Down, &regions));
+        assert_eq!(Some("d"), focus.focused());
+        assert!(focus.focus_towards(Direction::Right, &regions));
+        assert_eq!(Some("e"), focus.focused());
+        assert!(focus.focus_towards(Direction::Up, &regions));
+        assert_eq!(Some("c"), focus.focused());
+        assert!(!focus.focus_towards(Direction::Right, &regions));
+    }
+
+    #[test]
+    fn score() {
+        let regions = vec![
+            Region::new("from", 10, 0, 10, 1),
+            Region::new("diagonal", 9, 30, 10, 1),
+            Region::new("far", 10, 50, 10, 1),
+        ];
+        let mut focus = FocusRing::new(&["from", "diagonal", "far"]);
+        assert!(focus.focus_towards(Direction::Right, &regions));
+        assert_eq!(Some("diagonal"), focus.focused());
+
+        let regions = vec![
+            Region::new("from", 10, 0, 10, 1),
+            Region::new("diagonal", 0, 12, 10, 1),
+            Region::new("aligned", 10, 20, 10, 1),
+        ];
+        let mut focus = FocusRing::new(&["from", "diagonal", "aligned"]);
+        assert!(focus.focus_towards(Direction::Right, &regions));
+        assert_eq!(Some("aligned"), focus.focused());
+    }
+
+    #[test]
+    fn merge_regions_of_the_same_name() {
+        let regions = vec![
+            Region::new("list", 0, 0, 10, 1),
+            Region::new("list", 1, 0, 10, 1),
+            Region::new("list", 2, 0, 10, 1),
+            Region::new("side", 2, 10, 10, 1),
+        ];
+        let mut focus = FocusRing::new(&["side", "list"]);
+        assert!(focus.on_key(&Key::AltLeft, &regions));
+        assert_eq!(Some("list"), focus.focused());
+        assert!(!focus.on_key(&Key::AltUp, &regions));
+        assert!(!focus.on_key(&Key::Char('a'), &regions));
+    }
+}
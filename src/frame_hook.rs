@@ -0,0 +1,198 @@
+//! Hooks called around each `present`, for the concerns crossing the whole UI: recording the
+//! frames, exporting render metrics, drawing a watermark over every frame...
+//!
+//! A hook is registered on `Term` with `Term::add_frame_hook`. `pre_present` is called once
+//! the frame is drawn, just before it is written, and could still draw over it;
+//! `post_present` is called once it is written, with how long it took:
+//!
+//! ```no_run
+//! use tuikit::frame_hook::{FrameHook, FrameInfo};
+//! use tuikit::prelude::*;
+//! use tuikit::screen::Screen;
+//!
+//! struct Watermark;
+//!
+//! impl FrameHook for Watermark {
+//!     fn pre_present(&mut self, _frame: &FrameInfo, canvas: &mut dyn Canvas) -> Result<()> {
+//!         let (width, height) = canvas.size()?;
+//!         canvas.print(height - 1, width.saturating_sub(7), "PREVIEW")?;
+//!         Ok(())
+//!     }
+//!
+//!     fn post_present(&mut self, frame: &FrameInfo, _screen: &Screen) {
+//!         eprintln!("frame {} took {:?}", frame.number, frame.elapsed);
+//!     }
+//! }
+//!
+//! let term = Term::new().unwrap();
+//! term.add_frame_hook(Box::new(Watermark));
+//! ```
+//!
+//! The hooks are called with the term unlocked, they may call the `Term` back. `pre_present`
+//! draws on a detached canvas, applied to the frame once the hooks return, and `post_present`
+//! is given a copy of the screen.
+
+use crate::canvas::{Canvas, Result};
+use crate::screen::Screen;
+use crate::spinlock::SpinLock;
+use crate::term::PresentStatus;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// A frame being presented
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameInfo {
+    /// the frames presented before this one, counting the deferred ones
+    pub number: u64,
+    /// the area presented: `(top, left, width, height)`
+    pub area: (usize, usize, usize, usize),
+    pub started: Instant,
+    /// the time taken by `present`, zero before it
+    pub elapsed: Duration,
+    /// `None` before `present`
+    pub status: Option<PresentStatus>,
+}
+
+/// The hooks around each `present`, no-ops by default
+pub trait FrameHook: Send {
+    /// the frame is about to be written, drawing on `canvas` still shows in it
+    fn pre_present(&mut self, _frame: &FrameInfo, _canvas: &mut dyn Canvas) -> Result<()> {
+        Ok(())
+    }
+
+    /// the frame was written (or deferred, see `frame.status`)
+    fn post_present(&mut self, _frame: &FrameInfo, _screen: &Screen) {}
+}
+
+type SharedHook = Arc<SpinLock<Box<dyn FrameHook>>>;
+
+/// The registered hooks, called in the order of registration
+#[derive(Default)]
+pub(crate) struct FrameHooks {
+    hooks: Vec<(usize, SharedHook)>,
+    next_id: usize,
+    frames: u64,
+}
+
+impl FrameHooks {
+    /// register `hook`, return its id
+    pub fn add(&mut self, hook: Box<dyn FrameHook>) -> usize {
+        self.next_id += 1;
+        self.hooks
+            .push((self.next_id, Arc::new(SpinLock::new(hook))));
+        self.next_id
+    }
+
+    /// unregister the hook of `id`, return `false` if there is none
+    pub fn remove(&mut self, id: usize) -> bool {
+        let count = self.hooks.len();
+        self.hooks.retain(|(hook_id, _)| *hook_id != id);
+        self.hooks.len() != count
+    }
+
+    /// Start the frame of `area`, with the hooks registered now, to be called once `self` is
+    /// unlocked
+    pub fn start(&mut self, area: (usize, usize, usize, usize)) -> Frame {
+        let info = FrameInfo {
+            number: self.frames,
+            area,
+            started: Instant::now(),
+            elapsed: Duration::default(),
+            status: None,
+        };
+        self.frames += 1;
+        let hooks = self.hooks.iter().map(|(_, hook)| hook.clone()).collect();
+        Frame { info, hooks }
+    }
+}
+
+/// A frame being presented and the hooks to call around it
+pub(crate) struct Frame {
+    info: FrameInfo,
+    hooks: Vec<SharedHook>,
+}
+
+impl Frame {
+    pub fn has_hooks(&self) -> bool {
+        !self.hooks.is_empty()
+    }
+
+    /// Call the `pre_present` hooks, drawing on `canvas`
+    pub fn pre_present(&self, canvas: &mut dyn Canvas) -> Result<()> {
+        for hook in self.hooks.iter() {
+            hook.lock().pre_present(&self.info, canvas)?;
+        }
+        Ok(())
+    }
+
+    /// Call the `post_present` hooks once the frame is presented
+    pub fn post_present(mut self, status: PresentStatus, screen: &Screen) {
+        self.info.elapsed = self.info.started.elapsed();
+        self.info.status = Some(status);
+        for hook in self.hooks.iter() {
+            hook.lock().post_present(&self.info, screen);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// (number, status, screen text), the text is empty before present
+    type Frames = Arc<Mutex<Vec<(u64, Option<PresentStatus>, String)>>>;
+
+    struct Recorder {
+        frames: Frames,
+    }
+
+    impl FrameHook for Recorder {
+        fn pre_present(&mut self, frame: &FrameInfo, canvas: &mut dyn Canvas) -> Result<()> {
+            canvas.print(0, 0, "W")?;
+            self.frames
+                .lock()
+                .unwrap()
+                .push((frame.number, frame.status, String::new()));
+            Ok(())
+        }
+
+        fn post_present(&mut self, frame: &FrameInfo, screen: &Screen) {
+            let text: String = screen.iter_cell().map(|(_, _, cell)| cell.ch).collect();
+            self.frames
+                .lock()
+                .unwrap()
+                .push((frame.number, frame.status, text));
+        }
+    }
+
+    #[test]
+    fn hooks_around_present() {
+        let frames = Arc::new(Mutex::new(Vec::new()));
+        let mut hooks = FrameHooks::default();
+        let mut screen = Screen::new(2, 1);
+        let present = |hooks: &mut FrameHooks, screen: &mut Screen| {
+            let frame = hooks.start((0, 0, 2, 1));
+            frame.pre_present(screen).unwrap();
+            frame.post_present(PresentStatus::Presented, screen);
+        };
+
+        present(&mut hooks, &mut screen);
+        let id = hooks.add(Box::new(Recorder {
+            frames: frames.clone(),
+        }));
+        present(&mut hooks, &mut screen);
+        assert_eq!(
+            vec![
+                (1, None, String::new()),
+                (1, Some(PresentStatus::Presented), "W ".to_string())
+            ],
+            *frames.lock().unwrap()
+        );
+
+        assert!(hooks.remove(id));
+        assert!(!hooks.remove(id));
+        present(&mut hooks, &mut screen);
+        assert_eq!(2, frames.lock().unwrap().len());
+    }
+}
@@ -0,0 +1,184 @@
+//! Filesystem changes delivered through the event loop (feature `fs-watch`).
+//!
+//! File browsers and log viewers refresh when files change. Instead of a thread of their own
+//! waking the event loop up, `Term::watch_path` watches a file or a directory with `notify` and
+//! its events are returned by `poll_event` as `Event::Fs`:
+//!
+//! ```no_run
+//! use tuikit::prelude::*;
+//!
+//! let term = Term::new().unwrap();
+//! term.watch_path("/var/log/app.log", false).unwrap();
+//! while let Ok(event) = term.poll_event() {
+//!     if let Event::Fs(change) = event {
+//!         // reload change.paths
+//!     }
+//! }
+//! ```
+//!
+//! The errors reported by the watcher (e.g. a watched directory removed) are dropped, the
+//! paths have to be watched again.
+//!
+//! For the theme or config files edited while the application runs, `Term::watch_config_file`
+//! returns `Event::ConfigReloaded(path)` once the file settled after a change (editors write
+//! files in several steps), time to read it again:
+//!
+//! ```no_run
+//! use std::path::Path;
+//! use tuikit::prelude::*;
+//! use tuikit::style::Theme;
+//!
+//! let term = Term::new().unwrap();
+//! let mut theme = Theme::from_file(Path::new("theme")).unwrap_or_default();
+//! term.watch_config_file("theme").unwrap();
+//! while let Ok(event) = term.poll_event() {
+//!     if let Event::ConfigReloaded(path) = event {
+//!         theme = Theme::from_file(&path).unwrap_or(theme);
+//!         // redraw
+//!     }
+//! }
+//! ```
+
+use crate::event::Event;
+use crate::spinlock::SpinLock;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long a config file should be left unchanged before it is reloaded
+pub(crate) const RELOAD_DELAY: Duration = Duration::from_millis(50);
+/// the prefix of the ids of the timers reloading the config files
+const RELOAD_TIMER: &str = "tuikit:reload:";
+
+/// The watcher of `Term`, sending the changes as events
+pub(crate) struct FsWatcher {
+    watcher: RecommendedWatcher,
+}
+
+impl FsWatcher {
+    pub fn new(event_tx: Arc<SpinLock<Sender<Event>>>) -> notify::Result<Self> {
+        let watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+            if let Ok(event) = result {
+                let _ = event_tx.lock().send(Event::Fs(event));
+            }
+        })?;
+        Ok(Self { watcher })
+    }
+
+    pub fn watch(&mut self, path: &Path, recursive: bool) -> notify::Result<()> {
+        let mode = if recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        self.watcher.watch(path, mode)
+    }
+
+    pub fn unwatch(&mut self, path: &Path) -> notify::Result<()> {
+        self.watcher.unwatch(path)
+    }
+}
+
+/// The config files watched by `Term::watch_config_file`, by absolute path
+#[derive(Debug, Default)]
+pub(crate) struct ConfigFiles {
+    files: HashSet<PathBuf>,
+}
+
+impl ConfigFiles {
+    pub fn insert(&mut self, path: PathBuf) {
+        self.files.insert(path);
+    }
+
+    /// Forget `path`, return whether another file of its directory is still watched
+    pub fn remove(&mut self, path: &Path) -> bool {
+        self.files.remove(path);
+        self.files.iter().any(|file| file.parent() == path.parent())
+    }
+
+    /// The watched files changed by `event`, and whether `event` changed nothing else
+    pub fn changed(&self, event: &notify::Event) -> (Vec<PathBuf>, bool) {
+        let changed: Vec<PathBuf> = event
+            .paths
+            .iter()
+            .filter(|path| self.files.contains(*path))
+            .cloned()
+            .collect();
+        let only = changed.len() == event.paths.len();
+        (changed, only)
+    }
+}
+
+/// The absolute directory and the name of the file `path`
+pub(crate) fn split_config_path(path: &Path) -> io::Result<(PathBuf, OsString)> {
+    let file = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file"))?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    Ok((dir.canonicalize()?, file.to_os_string()))
+}
+
+/// the id of the timer reloading `path`
+pub(crate) fn reload_timer(path: &Path) -> String {
+    format!("{}{}", RELOAD_TIMER, path.display())
+}
+
+/// the file reloaded by the timer `id`, `None` if it is another timer
+pub(crate) fn reloaded_file(id: &str) -> Option<PathBuf> {
+    id.strip_prefix(RELOAD_TIMER).map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+    use std::sync::mpsc::channel;
+    use std::time::Duration;
+
+    #[test]
+    fn changes_become_events() {
+        let dir = std::env::temp_dir().join(format!("tuikit-watch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (event_tx, event_rx) = channel();
+        let mut watcher = FsWatcher::new(Arc::new(SpinLock::new(event_tx))).unwrap();
+        watcher.watch(&dir, false).unwrap();
+
+        let file = dir.join("file");
+        fs::write(&file, "changed").unwrap();
+        let event = event_rx.recv_timeout(Duration::from_secs(5));
+        fs::remove_dir_all(&dir).unwrap();
+        match event {
+            Ok(Event::Fs(event)) => assert!(event.paths.iter().any(|path| path.ends_with("file"))),
+            event => panic!("unexpected {:?}", event),
+        }
+    }
+
+    #[test]
+    fn config_files() {
+        let mut files = ConfigFiles::default();
+        files.insert(PathBuf::from("/etc/app/theme"));
+        files.insert(PathBuf::from("/etc/app/keys"));
+
+        let event = notify::Event::default()
+            .add_path(PathBuf::from("/etc/app/theme"))
+            .add_path(PathBuf::from("/etc/app/theme.swp"));
+        let (changed, only) = files.changed(&event);
+        assert_eq!(vec![PathBuf::from("/etc/app/theme")], changed);
+        assert!(!only);
+
+        let timer = reload_timer(&changed[0]);
+        assert_eq!(Some(changed[0].clone()), reloaded_file(&timer));
+        assert_eq!(None, reloaded_file("clock"));
+
+        assert!(files.remove(Path::new("/etc/app/theme")));
+        assert!(!files.remove(Path::new("/etc/app/keys")));
+    }
+}
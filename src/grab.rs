@@ -0,0 +1,204 @@
+//! Route the input to an embedded pane, tmux style.
+//!
+//! A pane running another program (e.g. a PTY widget) wants every key, including the ones the
+//! host application binds. `Term::grab_input` hands the key and mouse events over to the
+//! consumer registered for the pane until the escape chord is typed (`Ctrl-b d` by default,
+//! like detaching in tmux), then `Event::InputReleased` is returned and the host application
+//! gets the keys again. Other events (resizes...) are still returned by `poll_event`.
+//!
+//! ```no_run
+//! use tuikit::prelude::*;
+//!
+//! let term = Term::new().unwrap();
+//! term.register_input_consumer("shell", Box::new(|key| {
+//!     // forward `key` to the program of the pane
+//! }));
+//! term.grab_input("shell").unwrap();
+//! while let Ok(event) = term.poll_event() {
+//!     if let Event::InputReleased(pane) = event {
+//!         println!("back from {}", pane);
+//!         break;
+//!     }
+//! }
+//! ```
+//!
+//! The consumers are called with the grab locked, they must not call the grab methods of
+//! `Term` themselves.
+
+use crate::key::Key;
+use std::collections::HashMap;
+
+/// Receives the keys while the input is grabbed for its pane
+pub type InputConsumer = Box<dyn FnMut(&Key) + Send>;
+
+/// What happened to a key
+#[derive(Debug, PartialEq, Eq)]
+pub enum Grabbed {
+    /// the input isn't grabbed, the key is for the application
+    No(Key),
+    /// the key went to the consumer, or is part of the escape chord so far
+    Consumed,
+    /// the escape chord was typed, the pane released the input
+    Released(String),
+}
+
+/// The consumers by pane, the pane grabbing the input and the escape chord
+pub struct InputGrab {
+    consumers: HashMap<String, InputConsumer>,
+    grabbed: Option<String>,
+    escape: Vec<Key>,
+    /// the number of keys of the escape chord typed so far
+    matched: usize,
+}
+
+impl Default for InputGrab {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputGrab {
+    pub fn new() -> Self {
+        Self {
+            consumers: HashMap::new(),
+            grabbed: None,
+            escape: vec![Key::Ctrl('b'), Key::Char('d')],
+            matched: 0,
+        }
+    }
+
+    pub fn register(&mut self, pane: &str, consumer: InputConsumer) {
+        self.consumers.insert(pane.to_string(), consumer);
+    }
+
+    /// unregister `pane`, releasing the input if it was grabbed by it
+    pub fn unregister(&mut self, pane: &str) {
+        self.consumers.remove(pane);
+        if self.grabbed.as_deref() == Some(pane) {
+            self.release();
+        }
+    }
+
+    /// The keys typed in order to release the input, not forwarded to the consumer. Typing
+    /// anything else midway forwards the keys typed so far.
+    pub fn set_escape_chord(&mut self, keys: Vec<Key>) {
+        self.escape = keys;
+        self.matched = 0;
+    }
+
+    /// route the input to `pane`, return false if there is no consumer for it
+    pub fn grab(&mut self, pane: &str) -> bool {
+        if !self.consumers.contains_key(pane) {
+            return false;
+        }
+        self.grabbed = Some(pane.to_string());
+        self.matched = 0;
+        true
+    }
+
+    pub fn release(&mut self) -> Option<String> {
+        self.matched = 0;
+        self.grabbed.take()
+    }
+
+    pub fn grabbed(&self) -> Option<&str> {
+        self.grabbed.as_deref()
+    }
+
+    pub fn on_key(&mut self, key: Key) -> Grabbed {
+        let pane = match self.grabbed.as_ref() {
+            Some(pane) => pane,
+            None => return Grabbed::No(key),
+        };
+
+        if self.escape.get(self.matched) == Some(&key) {
+            self.matched += 1;
+            if self.matched == self.escape.len() {
+                return Grabbed::Released(self.release().unwrap_or_default());
+            }
+            return Grabbed::Consumed;
+        }
+
+        // not the chord after all: forward what was held, the key could start it again
+        let held = &self.escape[..self.matched];
+        let consumer = self
+            .consumers
+            .get_mut(pane)
+            .expect("grabbed pane should have a consumer");
+        for held_key in held {
+            consumer(held_key);
+        }
+        if self.escape.first() == Some(&key) {
+            self.matched = 1;
+        } else {
+            self.matched = 0;
+            consumer(&key);
+        }
+        Grabbed::Consumed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn recording_grab() -> (InputGrab, Arc<Mutex<Vec<Key>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let mut grab = InputGrab::new();
+        let sink = received.clone();
+        grab.register(
+            "pane",
+            Box::new(move |key| sink.lock().unwrap().push(key.clone())),
+        );
+        (grab, received)
+    }
+
+    #[test]
+    fn route_until_escape_chord() {
+        let (mut grab, received) = recording_grab();
+        assert_eq!(Grabbed::No(Key::Char('a')), grab.on_key(Key::Char('a')));
+        assert!(!grab.grab("other"));
+        assert!(grab.grab("pane"));
+
+        assert_eq!(Grabbed::Consumed, grab.on_key(Key::Char('a')));
+        assert_eq!(Grabbed::Consumed, grab.on_key(Key::Ctrl('b')));
+        assert_eq!(1, received.lock().unwrap().len());
+        assert_eq!(
+            Grabbed::Released("pane".to_string()),
+            grab.on_key(Key::Char('d'))
+        );
+        assert_eq!(None, grab.grabbed());
+        assert_eq!(Grabbed::No(Key::Char('d')), grab.on_key(Key::Char('d')));
+        assert_eq!(vec![Key::Char('a')], *received.lock().unwrap());
+    }
+
+    #[test]
+    fn forward_broken_chord() {
+        let (mut grab, received) = recording_grab();
+        grab.grab("pane");
+        grab.on_key(Key::Ctrl('b'));
+        grab.on_key(Key::Char('x'));
+        // Ctrl-b Ctrl-b d: the first Ctrl-b is forwarded, the second starts the chord
+        grab.on_key(Key::Ctrl('b'));
+        grab.on_key(Key::Ctrl('b'));
+        assert_eq!(
+            vec![Key::Ctrl('b'), Key::Char('x'), Key::Ctrl('b')],
+            *received.lock().unwrap()
+        );
+        assert_eq!(
+            Grabbed::Released("pane".to_string()),
+            grab.on_key(Key::Char('d'))
+        );
+
+        grab.set_escape_chord(vec![Key::F(12)]);
+        grab.grab("pane");
+        assert_eq!(
+            Grabbed::Released("pane".to_string()),
+            grab.on_key(Key::F(12))
+        );
+        grab.grab("pane");
+        grab.unregister("pane");
+        assert_eq!(None, grab.grabbed());
+    }
+}
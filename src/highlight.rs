@@ -0,0 +1,328 @@
+//! Pluggable syntax highlighting.
+//!
+//! A `Highlighter` converts a line of text into `Attr` spans. Highlighting usually depends on
+//! the previous lines (e.g. inside a block comment), so the highlighter carries a `State` from
+//! line to line. `HighlightCache` keeps the spans of every line and re-highlights only the
+//! lines that changed, and the following lines whose starting state changed.
+//!
+//! ```
+//! use tuikit::attr::{Attr, Color};
+//! use tuikit::highlight::{HighlightCache, KeywordHighlighter};
+//!
+//! let highlighter = KeywordHighlighter::new()
+//!     .keyword("fn", Color::BLUE)
+//!     .block_comment("/*", "*/", Color::LIGHT_BLACK);
+//! let mut cache = HighlightCache::new(highlighter);
+//!
+//! let lines = vec!["/* a", "fn */ fn main()"];
+//! cache.update(&lines);
+//! assert_eq!(0..5, cache.spans(1)[0].range); // still inside the comment
+//! assert_eq!(6..8, cache.spans(1)[1].range);
+//! ```
+
+use crate::attr::Attr;
+use crate::canvas::{Canvas, Result};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::ops::Range;
+
+/// `attr` applied to the bytes `range` of a line
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub range: Range<usize>,
+    pub attr: Attr,
+}
+
+impl Span {
+    pub fn new(range: Range<usize>, attr: impl Into<Attr>) -> Self {
+        Self {
+            range,
+            attr: attr.into(),
+        }
+    }
+}
+
+/// Highlight text line by line
+pub trait Highlighter {
+    /// the state carried from the end of a line to the start of the next one
+    type State: Clone + PartialEq;
+
+    /// the state at the beginning of the text
+    fn start_state(&self) -> Self::State;
+
+    /// Return the spans of `line`, ordered and non-overlapping, and update `state` to the
+    /// state at the end of the line.
+    fn highlight_line(&self, line: &str, state: &mut Self::State) -> Vec<Span>;
+}
+
+struct CachedLine<S> {
+    hash: u64,
+    start_state: S,
+    end_state: S,
+    spans: Vec<Span>,
+}
+
+/// Cache the highlighted lines and re-highlight incrementally on `update`
+pub struct HighlightCache<H: Highlighter> {
+    highlighter: H,
+    lines: Vec<CachedLine<H::State>>,
+}
+
+impl<H: Highlighter> HighlightCache<H> {
+    pub fn new(highlighter: H) -> Self {
+        Self {
+            highlighter,
+            lines: Vec::new(),
+        }
+    }
+
+    pub fn highlighter(&self) -> &H {
+        &self.highlighter
+    }
+
+    /// Sync with the current `lines`, return the number of lines that were highlighted.
+    pub fn update<S: AsRef<str>>(&mut self, lines: &[S]) -> usize {
+        self.lines.truncate(lines.len());
+
+        let mut highlighted = 0;
+        let mut state = self.highlighter.start_state();
+        for (index, line) in lines.iter().enumerate() {
+            let line = line.as_ref();
+            let hash = hash_line(line);
+
+            if let Some(cached) = self.lines.get(index) {
+                if cached.hash == hash && cached.start_state == state {
+                    state = cached.end_state.clone();
+                    continue;
+                }
+            }
+
+            let start_state = state.clone();
+            let spans = self.highlighter.highlight_line(line, &mut state);
+            let cached = CachedLine {
+                hash,
+                start_state,
+                end_state: state.clone(),
+                spans,
+            };
+            if index < self.lines.len() {
+                self.lines[index] = cached;
+            } else {
+                self.lines.push(cached);
+            }
+            highlighted += 1;
+        }
+        highlighted
+    }
+
+    /// the spans of line `index` as of the last `update`
+    pub fn spans(&self, index: usize) -> &[Span] {
+        self.lines
+            .get(index)
+            .map(|line| line.spans.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// drop the cache, e.g. after the highlighter's configuration changed
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+}
+
+fn hash_line(line: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    line.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Print `line` at `(row, col)`, the parts covered by `spans` are printed with the span's
+/// attribute extending `attr`. Return the printed width.
+pub fn print_highlighted(
+    canvas: &mut dyn Canvas,
+    row: usize,
+    col: usize,
+    line: &str,
+    spans: &[Span],
+    attr: Attr,
+) -> Result<usize> {
+    let mut width = 0;
+    let mut pos = 0;
+    for span in spans {
+        let start = span.range.start.clamp(pos, line.len());
+        let end = span.range.end.clamp(start, line.len());
+        if let (Some(plain), Some(highlighted)) = (line.get(pos..start), line.get(start..end)) {
+            width += canvas.print_with_attr(row, col + width, plain, attr)?;
+            width +=
+                canvas.print_with_attr(row, col + width, highlighted, attr.extend(span.attr))?;
+            pos = end;
+        }
+    }
+    width += canvas.print_with_attr(row, col + width, &line[pos..], attr)?;
+    Ok(width)
+}
+
+/// A simple highlighter for keywords, line comments and block comments, enough for config
+/// files and simple languages. Implement `Highlighter` for anything more complex, e.g. to
+/// bridge a full-featured highlighting library.
+#[derive(Debug, Default, Clone)]
+pub struct KeywordHighlighter {
+    keywords: Vec<(String, Attr)>,
+    line_comment: Option<(String, Attr)>,
+    block_comment: Option<(String, String, Attr)>,
+}
+
+impl KeywordHighlighter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// highlight the whole word `keyword`
+    pub fn keyword(mut self, keyword: &str, attr: impl Into<Attr>) -> Self {
+        self.keywords.push((keyword.to_string(), attr.into()));
+        self
+    }
+
+    pub fn line_comment(mut self, start: &str, attr: impl Into<Attr>) -> Self {
+        self.line_comment = Some((start.to_string(), attr.into()));
+        self
+    }
+
+    pub fn block_comment(mut self, start: &str, end: &str, attr: impl Into<Attr>) -> Self {
+        self.block_comment = Some((start.to_string(), end.to_string(), attr.into()));
+        self
+    }
+
+    fn keyword_at(&self, line: &str, pos: usize) -> Option<(usize, Attr)> {
+        let rest = &line[pos..];
+        let is_word = |ch: char| ch.is_alphanumeric() || ch == '_';
+        if line[..pos].chars().next_back().is_some_and(is_word) {
+            return None;
+        }
+
+        self.keywords.iter().find_map(|(keyword, attr)| {
+            let after = rest.strip_prefix(keyword.as_str())?;
+            if after.chars().next().is_some_and(is_word) {
+                None
+            } else {
+                Some((keyword.len(), *attr))
+            }
+        })
+    }
+}
+
+impl Highlighter for KeywordHighlighter {
+    /// whether the line starts inside a block comment
+    type State = bool;
+
+    fn start_state(&self) -> bool {
+        false
+    }
+
+    fn highlight_line(&self, line: &str, in_comment: &mut bool) -> Vec<Span> {
+        let mut spans = Vec::new();
+        let mut pos = 0;
+
+        while pos < line.len() {
+            let rest = &line[pos..];
+
+            if let Some((start, end, attr)) = self.block_comment.as_ref() {
+                if *in_comment || rest.starts_with(start.as_str()) {
+                    let search_from = if *in_comment { pos } else { pos + start.len() };
+                    let comment_end = line[search_from..]
+                        .find(end.as_str())
+                        .map(|found| search_from + found + end.len());
+                    *in_comment = comment_end.is_none();
+                    let comment_end = comment_end.unwrap_or(line.len());
+                    spans.push(Span::new(pos..comment_end, *attr));
+                    pos = comment_end;
+                    continue;
+                }
+            }
+
+            if let Some((start, attr)) = self.line_comment.as_ref() {
+                if rest.starts_with(start.as_str()) {
+                    spans.push(Span::new(pos..line.len(), *attr));
+                    break;
+                }
+            }
+
+            if let Some((len, attr)) = self.keyword_at(line, pos) {
+                spans.push(Span::new(pos..pos + len, attr));
+                pos += len;
+                continue;
+            }
+
+            pos += rest.chars().next().map_or(1, char::len_utf8);
+        }
+
+        spans
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::attr::Color;
+    use crate::screen::Screen;
+
+    fn rust() -> KeywordHighlighter {
+        KeywordHighlighter::new()
+            .keyword("fn", Color::BLUE)
+            .keyword("let", Color::BLUE)
+            .line_comment("//", Color::GREEN)
+            .block_comment("/*", "*/", Color::LIGHT_BLACK)
+    }
+
+    #[test]
+    fn keywords_and_comments() {
+        let mut state = false;
+        let spans = rust().highlight_line("let fnord = 1; // fn", &mut state);
+        assert_eq!(
+            vec![
+                Span::new(0..3, Color::BLUE),
+                Span::new(15..20, Color::GREEN)
+            ],
+            spans
+        );
+
+        let spans = rust().highlight_line("fn /* open", &mut state);
+        assert!(state);
+        assert_eq!(Span::new(3..10, Color::LIGHT_BLACK), spans[1]);
+    }
+
+    #[test]
+    fn cache_should_only_highlight_changed_lines() {
+        let mut cache = HighlightCache::new(rust());
+        let mut lines = vec!["fn a", "let b", "/* c */", "fn d"];
+        assert_eq!(4, cache.update(&lines));
+        assert_eq!(0, cache.update(&lines));
+
+        lines[1] = "let bb";
+        assert_eq!(1, cache.update(&lines));
+
+        // opening a block comment changes the state of the following lines
+        lines[0] = "/* fn a";
+        assert_eq!(3, cache.update(&lines));
+        assert_eq!(Span::new(0..6, Color::LIGHT_BLACK), cache.spans(1)[0]);
+        assert_eq!(Span::new(0..2, Color::BLUE), cache.spans(3)[0]);
+
+        lines.truncate(2);
+        assert_eq!(0, cache.update(&lines));
+        assert!(cache.spans(3).is_empty());
+    }
+
+    #[test]
+    fn print_highlighted_line() {
+        let mut screen = Screen::new(10, 1);
+        let line = "fn a";
+        let spans = vec![Span::new(0..2, Color::BLUE)];
+        assert_eq!(
+            4,
+            print_highlighted(&mut screen, 0, 0, line, &spans, Attr::default()).unwrap()
+        );
+
+        let cells: Vec<_> = screen.iter_cell().map(|(_, _, cell)| *cell).collect();
+        assert_eq!(Color::BLUE, cells[1].attr.fg);
+        assert_eq!(Color::Default, cells[3].attr.fg);
+    }
+}
@@ -0,0 +1,288 @@
+//! Keyboard-driven "link hints", like vimium or tmux-fingers.
+//!
+//! `Hints` overlays a short label on every target (a link, a file path, anything a widget
+//! considers actionable); typing a label selects its target. `scan_screen` finds the URLs and
+//! file paths shown on a screen.
+//!
+//! ```
+//! use tuikit::hints::{HintResult, HintTarget, Hints};
+//! use tuikit::key::Key;
+//!
+//! let mut hints = Hints::new(vec![
+//!     HintTarget::new(0, 0, 4, "first"),
+//!     HintTarget::new(2, 0, 4, "second"),
+//! ]);
+//! assert_eq!(vec!["a", "s"], hints.labels());
+//! assert_eq!(HintResult::Selected("second"), hints.on_key(&Key::Char('s')));
+//! ```
+
+use crate::attr::{Attr, Color, Effect};
+use crate::canvas::{Canvas, Result};
+use crate::draw::Draw;
+use crate::key::Key;
+use crate::screen::Screen;
+
+/// home row first, like most hint implementations
+const DEFAULT_ALPHABET: &str = "asdfghjkl";
+
+/// An actionable area of the screen: `width` cells starting at `(row, col)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HintTarget<T> {
+    pub row: usize,
+    pub col: usize,
+    pub width: usize,
+    pub payload: T,
+}
+
+impl<T> HintTarget<T> {
+    pub fn new(row: usize, col: usize, width: usize, payload: T) -> Self {
+        Self {
+            row,
+            col,
+            width,
+            payload,
+        }
+    }
+}
+
+/// The outcome of a key typed in hint mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HintResult<T> {
+    /// more keys are needed
+    Pending,
+    Selected(T),
+    /// `ESC` or a label that matches nothing
+    Cancelled,
+}
+
+pub struct Hints<T> {
+    targets: Vec<HintTarget<T>>,
+    labels: Vec<String>,
+    typed: String,
+    alphabet: Vec<char>,
+    attr: Attr,
+    typed_attr: Attr,
+}
+
+// Builder
+impl<T> Hints<T> {
+    pub fn new(targets: Vec<HintTarget<T>>) -> Self {
+        let mut hints = Self {
+            targets,
+            labels: Vec::new(),
+            typed: String::new(),
+            alphabet: Vec::new(),
+            attr: Attr::from(Color::BLACK)
+                .bg(Color::YELLOW)
+                .effect(Effect::BOLD),
+            typed_attr: Attr::from(Color::BLACK).bg(Color::LIGHT_YELLOW),
+        };
+        hints.set_alphabet(DEFAULT_ALPHABET);
+        hints
+    }
+
+    /// the characters the labels are made of, in order of preference
+    pub fn alphabet(mut self, alphabet: &str) -> Self {
+        self.set_alphabet(alphabet);
+        self
+    }
+
+    pub fn attr(mut self, attr: impl Into<Attr>) -> Self {
+        self.attr = attr.into();
+        self
+    }
+
+    /// the attribute of the part of the labels already typed
+    pub fn typed_attr(mut self, attr: impl Into<Attr>) -> Self {
+        self.typed_attr = attr.into();
+        self
+    }
+
+    fn set_alphabet(&mut self, alphabet: &str) {
+        let mut chars: Vec<char> = Vec::new();
+        for ch in alphabet.chars() {
+            if !chars.contains(&ch) {
+                chars.push(ch);
+            }
+        }
+        assert!(chars.len() > 1, "hint alphabet needs at least 2 characters");
+        self.labels = make_labels(&chars, self.targets.len());
+        self.alphabet = chars;
+        self.typed.clear();
+    }
+}
+
+impl<T> Hints<T> {
+    pub fn labels(&self) -> Vec<&str> {
+        self.labels.iter().map(String::as_str).collect()
+    }
+
+    pub fn targets(&self) -> &[HintTarget<T>] {
+        &self.targets
+    }
+
+    /// the keys typed so far
+    pub fn typed(&self) -> &str {
+        &self.typed
+    }
+
+    fn matching(&self) -> impl Iterator<Item = (&String, &HintTarget<T>)> {
+        self.labels
+            .iter()
+            .zip(self.targets.iter())
+            .filter(move |(label, _)| label.starts_with(&self.typed))
+    }
+}
+
+impl<T: Clone> Hints<T> {
+    pub fn on_key(&mut self, key: &Key) -> HintResult<T> {
+        match key {
+            Key::ESC => HintResult::Cancelled,
+            Key::Backspace => {
+                self.typed.pop();
+                HintResult::Pending
+            }
+            Key::Char(ch) if self.alphabet.contains(ch) => {
+                self.typed.push(*ch);
+                let mut matching = self.matching();
+                match (matching.next(), matching.next()) {
+                    (None, _) => HintResult::Cancelled,
+                    (Some((label, target)), None) if *label == self.typed => {
+                        HintResult::Selected(target.payload.clone())
+                    }
+                    _ => HintResult::Pending,
+                }
+            }
+            _ => HintResult::Pending,
+        }
+    }
+}
+
+/// `count` labels of the same length so that none is a prefix of another
+fn make_labels(alphabet: &[char], count: usize) -> Vec<String> {
+    let base = alphabet.len();
+    let mut length = 1;
+    while base.pow(length) < count {
+        length += 1;
+    }
+
+    (0..count)
+        .map(|mut index| {
+            let mut label = vec![alphabet[0]; length as usize];
+            for slot in label.iter_mut().rev() {
+                *slot = alphabet[index % base];
+                index /= base;
+            }
+            label.into_iter().collect()
+        })
+        .collect()
+}
+
+impl<T> Draw for Hints<T> {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
+        let typed_len = self.typed.len();
+        for (label, target) in self.matching() {
+            let col = target.col;
+            let width =
+                canvas.print_with_attr(target.row, col, &label[..typed_len], self.typed_attr)?;
+            canvas.print_with_attr(target.row, col + width, &label[typed_len..], self.attr)?;
+        }
+        Ok(())
+    }
+}
+
+/// Find the URLs and file paths shown on `screen`, the payload is the text found
+pub fn scan_screen(screen: &Screen) -> Vec<HintTarget<String>> {
+    let mut rows: Vec<Vec<char>> = vec![Vec::new(); screen.height()];
+    for (row, _, cell) in screen.iter_cell() {
+        rows[row].push(cell.ch);
+    }
+
+    let mut targets = Vec::new();
+    for (row, chars) in rows.iter().enumerate() {
+        let mut col = 0;
+        while col < chars.len() {
+            if is_separator(chars[col]) {
+                col += 1;
+                continue;
+            }
+            let start = col;
+            while col < chars.len() && !is_separator(chars[col]) {
+                col += 1;
+            }
+            let word: String = chars[start..col].iter().collect();
+            let word = word.trim_end_matches(['.', ',', ';', ':', '!', '?']);
+            if is_url(word) || is_path(word) {
+                let width = word.chars().count();
+                targets.push(HintTarget::new(row, start, width, word.to_string()));
+            }
+        }
+    }
+    targets
+}
+
+fn is_separator(ch: char) -> bool {
+    ch.is_whitespace() || ch == '\0' || "()[]{}<>\"'`|".contains(ch)
+}
+
+fn is_url(word: &str) -> bool {
+    ["http://", "https://", "file://"]
+        .iter()
+        .any(|scheme| word.starts_with(scheme) && word.len() > scheme.len())
+}
+
+fn is_path(word: &str) -> bool {
+    word.contains('/')
+        && word.chars().any(char::is_alphanumeric)
+        && word
+            .chars()
+            .all(|ch| ch.is_alphanumeric() || "/._-~+@:".contains(ch))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn labels_should_not_be_prefixes() {
+        let chars: Vec<char> = "ab".chars().collect();
+        assert_eq!(vec!["a"], make_labels(&chars, 1));
+        assert_eq!(vec!["aa", "ab", "ba"], make_labels(&chars, 3));
+    }
+
+    #[test]
+    fn type_label() {
+        let targets = (0..3).map(|i| HintTarget::new(i, 0, 1, i)).collect();
+        let mut hints = Hints::new(targets).alphabet("ab");
+        assert_eq!(HintResult::Pending, hints.on_key(&Key::Char('b')));
+        assert_eq!(HintResult::Pending, hints.on_key(&Key::Char('x')));
+        assert_eq!(HintResult::Pending, hints.on_key(&Key::Backspace));
+        assert_eq!(HintResult::Pending, hints.on_key(&Key::Char('a')));
+        assert_eq!(HintResult::Selected(1), hints.on_key(&Key::Char('b')));
+
+        let mut hints = Hints::new(vec![HintTarget::new(0, 0, 1, ())]).alphabet("ab");
+        assert_eq!(HintResult::Cancelled, hints.on_key(&Key::Char('b')));
+    }
+
+    #[test]
+    fn scan_urls_and_paths() {
+        let mut screen = Screen::new(40, 2);
+        screen
+            .print(0, 0, "see <https://a.io/x>, or src/lib.rs.")
+            .unwrap();
+        screen.print(1, 0, "and/or a / b").unwrap();
+
+        let found: Vec<_> = scan_screen(&screen)
+            .into_iter()
+            .map(|target| (target.row, target.col, target.payload))
+            .collect();
+        assert_eq!(
+            vec![
+                (0, 5, "https://a.io/x".to_string()),
+                (0, 25, "src/lib.rs".to_string()),
+                (1, 0, "and/or".to_string()),
+            ],
+            found
+        );
+    }
+}
@@ -7,25 +7,102 @@
 //! let mut keyboard = KeyBoard::new_with_tty();
 //! let key = keyboard.next_key();
 //! ```
+//!
+//! `next_key_until` waits until a `Deadline`: `Deadline::Poll` only returns what was already
+//! typed, without blocking, as does `try_next_key`. The `InputError` tells a timeout from an
+//! interrupt, a sequence which isn't understood (the keys after it are still read) or a failed
+//! read.
+//!
+//! ## The kitty keyboard protocol
+//!
+//! The legacy encoding can't tell some keys apart, e.g. `Ctrl+Shift+P` from `Ctrl+P`. The
+//! terminals supporting the kitty keyboard protocol (kitty, foot, WezTerm...) encode them as
+//! `CSI codepoint ; modifiers u` once asked with `Output::enable_kitty_keyboard`. In the
+//! `kitty_keyboard` mode, the keyboard decodes the keys only this protocol reports, e.g.
+//! `Key::CtrlShift` or the keypad. See `TermOptions::kitty_keyboard` to negotiate it on start.
+//!
+//! xterm configured with `modifyOtherKeys` encodes them as `CSI 27 ; modifiers ; code ~`,
+//! which is always decoded.
 
+use crate::event_log::EventLog;
 use crate::key::Key::*;
-use crate::key::{Key, MouseButton};
+use crate::key::{Key, Modifiers, MouseButton};
 use crate::raw::get_tty;
 use crate::spinlock::SpinLock;
 use crate::sys::file::wait_until_ready;
 use nix::fcntl::{fcntl, FcntlArg, OFlag};
 use std::collections::VecDeque;
 use std::error::Error;
+use std::fmt;
 use std::fs::File;
+use std::io;
 use std::io::prelude::*;
-use std::os::unix::io::AsRawFd;
 use std::os::unix::io::FromRawFd;
+use std::os::unix::io::{AsRawFd, RawFd};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 pub trait ReadAndAsRawFd: Read + AsRawFd + Send {}
 
+/// How the terminal reports the mouse, the extended ones (SGR, urxvt) are the only ones
+/// reaching past the column 223
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MouseEncoding {
+    /// `ESC [ M Cb Cx Cy`, the coordinates as characters (as UTF-8 with the mode 1005)
+    X10,
+    /// `ESC [ Cb ; Cx ; Cy M`
+    Urxvt,
+    /// `ESC [ < Cb ; Cx ; Cy (M or m)`
+    Sgr,
+}
+
+/// How long to wait for a key
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Deadline {
+    /// until a key is typed (or the keyboard is interrupted)
+    Infinite,
+    /// not at all, only the keys already typed are read
+    Poll,
+    /// until the instant at the latest
+    At(Instant),
+}
+
+impl Deadline {
+    /// the deadline `timeout` from now
+    pub fn after(timeout: Duration) -> Self {
+        Deadline::At(Instant::now() + timeout)
+    }
+
+    /// the time left, `None` if there is no deadline
+    pub fn remaining(&self) -> Option<Duration> {
+        match *self {
+            Deadline::Infinite => None,
+            Deadline::Poll => Some(Duration::new(0, 0)),
+            Deadline::At(instant) => Some(instant.saturating_duration_since(Instant::now())),
+        }
+    }
+
+    pub fn has_passed(&self) -> bool {
+        self.remaining() == Some(Duration::new(0, 0))
+    }
+
+    fn instant(&self) -> Option<Instant> {
+        match *self {
+            Deadline::Infinite => None,
+            Deadline::Poll => Some(Instant::now()),
+            Deadline::At(instant) => Some(instant),
+        }
+    }
+}
+
+/// how long to wait after `ESC` for the rest of an escape sequence, by default
 const KEY_WAIT: Duration = Duration::from_millis(10);
+/// how long a paste could take, e.g. over a slow link, before its end marker is given up
+const PASTE_TIMEOUT: Duration = Duration::from_secs(10);
+/// the size of a pasted text kept by default, see `KeyBoard::max_paste_len`
+const MAX_PASTE_LEN: usize = 1 << 20;
+/// the end marker of a bracketed paste
+const PASTE_END: &str = "\x1b[201~";
 
 impl<T> ReadAndAsRawFd for T where T: Read + AsRawFd + Send {}
 
@@ -34,9 +111,131 @@ pub struct KeyBoard {
     sig_tx: Arc<SpinLock<File>>,
     sig_rx: File,
     buf: VecDeque<char>,
+    /// the start of a UTF-8 char at the end of the last read, waiting for the rest
+    partial: Vec<u8>,
+    event_log: Option<Arc<SpinLock<EventLog>>>,
+    mouse_encoding: Option<Arc<SpinLock<Option<MouseEncoding>>>>,
+    kitty_keyboard: bool,
+    click_interval: Duration,
+    esc_timeout: Duration,
+    /// the last left press: when, where and how many in a row
+    last_click: Option<(Instant, u16, u16, u8)>,
+    /// the chars read for the key being decoded, see `InputError::UnknownSequence`
+    seq: String,
+    /// the keys read ahead (by `peek_key`, or while waiting for a cursor position report),
+    /// returned by the next reads
+    queued: VecDeque<Key>,
+    /// the cursor position queries not answered yet, see `expect_cursor_report`
+    pending_cpr: usize,
+    /// the button held, X10 and urxvt report a release without telling which one
+    held: Option<MouseButton>,
+    max_paste_len: usize,
+    /// the bracketed paste being read when the read was interrupted, resumed by the next one
+    paste: Option<Paste>,
+}
+
+/// A bracketed paste being read
+struct Paste {
+    text: String,
+    max_len: usize,
+    deadline: Deadline,
+}
+
+impl Paste {
+    fn new(max_len: usize) -> Self {
+        Self {
+            text: String::new(),
+            max_len,
+            deadline: Deadline::after(PASTE_TIMEOUT),
+        }
+    }
+
+    /// add `ch` to the text, return whether it ends the paste
+    fn push(&mut self, ch: char) -> bool {
+        self.text.push(ch);
+        if self.text.ends_with(PASTE_END) {
+            self.text.truncate(self.text.len() - PASTE_END.len());
+            return true;
+        }
+        if self.text.len() > self.max_len + PASTE_END.len() {
+            // past the limit, only the last chars are kept, to find the end marker
+            let start = floor_char_boundary(&self.text, self.max_len);
+            let mut end = self.text.len() - PASTE_END.len();
+            while !self.text.is_char_boundary(end) {
+                end += 1;
+            }
+            self.text.drain(start..end);
+        }
+        false
+    }
+
+    /// the text pasted, cut at the limit
+    fn finish(mut self) -> String {
+        let len = floor_char_boundary(&self.text, self.max_len);
+        self.text.truncate(len);
+        self.text
+    }
+}
+
+/// the largest char boundary of `text` at or before `index`
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Why no key was read
+#[derive(Debug)]
+pub enum InputError {
+    /// the deadline passed before a key was typed
+    Timeout,
+    /// the wait was interrupted by `KeyboardHandler::interrupt`
+    Interrupted,
+    /// the bytes of a sequence which isn't understood, as decoded (in UTF-8), e.g.
+    /// `b"\x1b[0"`. The keys after it are still read.
+    UnknownSequence(Vec<u8>),
+    /// reading the input failed, `UnexpectedEof` once it is closed (see `is_closed`)
+    Io(io::Error),
 }
 
-pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
+impl InputError {
+    /// Whether this is the end of the input, e.g. stdin closed: no more keys would come
+    pub fn is_closed(&self) -> bool {
+        matches!(self, InputError::Io(err) if err.kind() == io::ErrorKind::UnexpectedEof)
+    }
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputError::Timeout => write!(f, "timeout"),
+            InputError::Interrupted => write!(f, "interrupted"),
+            InputError::UnknownSequence(bytes) => {
+                write!(f, "unsupported esc sequence: {}", bytes.escape_ascii())
+            }
+            InputError::Io(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for InputError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            InputError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for InputError {
+    fn from(err: io::Error) -> Self {
+        InputError::Io(err)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, InputError>;
 
 // https://www.xfree86.org/4.8.0/ctlseqs.html
 impl KeyBoard {
@@ -61,6 +260,19 @@ impl KeyBoard {
             sig_tx: Arc::new(SpinLock::new(unsafe { File::from_raw_fd(tx) })),
             sig_rx: unsafe { File::from_raw_fd(rx) },
             buf: VecDeque::new(),
+            partial: Vec::new(),
+            event_log: None,
+            mouse_encoding: None,
+            kitty_keyboard: false,
+            click_interval: Duration::new(0, 0),
+            esc_timeout: KEY_WAIT,
+            last_click: None,
+            seq: String::new(),
+            queued: VecDeque::new(),
+            pending_cpr: 0,
+            held: None,
+            max_paste_len: MAX_PASTE_LEN,
+            paste: None,
         }
     }
 
@@ -70,57 +282,283 @@ impl KeyBoard {
         ))
     }
 
+    /// record the raw input bytes into `event_log`
+    pub fn event_log(mut self, event_log: Arc<SpinLock<EventLog>>) -> Self {
+        self.event_log = Some(event_log);
+        self
+    }
+
+    /// record the encoding of the mouse reports into `encoding`
+    pub fn mouse_encoding(mut self, encoding: Arc<SpinLock<Option<MouseEncoding>>>) -> Self {
+        self.mouse_encoding = Some(encoding);
+        self
+    }
+
+    /// Decode the keys of the kitty keyboard protocol, e.g. `Ctrl+Shift+P` as
+    /// `Key::CtrlShift('p')` instead of `Key::Ctrl('p')`
+    pub fn kitty_keyboard(mut self, enabled: bool) -> Self {
+        self.kitty_keyboard = enabled;
+        self
+    }
+
+    /// Report the left presses on the same cell within `interval` of the previous one as
+    /// `Key::DoubleClick` then `Key::TripleClick`, instead of `Key::MousePress`. Disabled with
+    /// `Duration::new(0, 0)`, the default.
+    pub fn click_interval(mut self, interval: Duration) -> Self {
+        self.click_interval = interval;
+        self
+    }
+
+    /// How long to wait after `ESC` for the rest of an escape sequence before taking it as a
+    /// plain `ESC`, 10ms by default. Over a slow link (e.g. SSH) the sequences could arrive in
+    /// pieces and need longer, a longer wait delays the `ESC` key.
+    pub fn esc_timeout(mut self, timeout: Duration) -> Self {
+        self.esc_timeout = timeout;
+        self
+    }
+
+    /// The size in bytes of a bracketed paste kept, 1MiB by default: the text past it is
+    /// dropped, up to the end of the paste.
+    pub fn max_paste_len(mut self, len: usize) -> Self {
+        self.max_paste_len = len;
+        self
+    }
+
+    fn saw_mouse_encoding(&self, encoding: MouseEncoding) {
+        if let Some(shared) = self.mouse_encoding.as_ref() {
+            shared.lock().replace(encoding);
+        }
+    }
+
     pub fn get_interrupt_handler(&self) -> KeyboardHandler {
         KeyboardHandler {
             handler: self.sig_tx.clone(),
         }
     }
 
-    fn get_chars(&mut self, timeout: Duration) -> Result<()> {
+    /// the read end of the interrupt pipe, readable once `KeyboardHandler::interrupt` is called
+    #[cfg(feature = "async")]
+    pub(crate) fn interrupt_fd(&self) -> RawFd {
+        self.sig_rx.as_raw_fd()
+    }
+
+    /// whether `KeyboardHandler::interrupt` was called since the last read, clearing it
+    pub(crate) fn take_interrupt(&mut self) -> bool {
+        let mut reader_buf = [0; 1];
+        let mut interrupted = false;
+        while let Ok(1) = self.sig_rx.read(&mut reader_buf) {
+            interrupted = true;
+        }
+        interrupted
+    }
+
+    fn get_chars(&mut self, deadline: Deadline) -> Result<()> {
         let mut reader_buf = [0; 1];
 
         // clear interrupt signal
-        while let Ok(_) = self.sig_rx.read(&mut reader_buf) {}
+        self.take_interrupt();
 
-        wait_until_ready(
-            self.file.as_raw_fd(),
-            Some(self.sig_rx.as_raw_fd()),
-            timeout,
-        )?; // wait timeout
+        loop {
+            // the rest of a char split across reads follows at once, like the rest of an escape
+            // sequence, otherwise the bytes were Latin-1 (e.g. the coordinates of X10 mouse
+            // reports)
+            let wait = if self.partial.is_empty() {
+                deadline.instant()
+            } else {
+                Some(Instant::now() + self.esc_timeout)
+            };
+            let ready = wait_until_ready(self.file.as_raw_fd(), &[self.sig_rx.as_raw_fd()], wait)?;
+            if !ready && !self.partial.is_empty() {
+                self.buf.extend(self.partial.drain(..).map(char::from));
+                return Ok(());
+            }
+            if !ready {
+                return Err(InputError::Timeout);
+            }
 
-        let mut buf = Vec::with_capacity(10);
-        while let Ok(_) = self.file.read(&mut reader_buf) {
-            buf.push(reader_buf[0]);
-        }
+            let mut buf = Vec::with_capacity(10);
+            let mut error = None;
+            loop {
+                match self.file.read(&mut reader_buf) {
+                    Ok(1) => buf.push(reader_buf[0]),
+                    Ok(_) => {
+                        let closed = io::Error::new(io::ErrorKind::UnexpectedEof, "input closed");
+                        error = Some(InputError::Io(closed));
+                        break;
+                    }
+                    Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {}
+                    Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                    Err(err) => {
+                        error = Some(InputError::Io(err));
+                        break;
+                    }
+                }
+            }
 
-        let chars = String::from_utf8(buf).expect("Non UTF8 in input");
-        for ch in chars.chars() {
-            self.buf.push_back(ch);
+            if !buf.is_empty() {
+                if let Some(event_log) = self.event_log.as_ref() {
+                    event_log.lock().push_input(&buf);
+                }
+                let mut bytes = std::mem::take(&mut self.partial);
+                bytes.extend(buf);
+                let incomplete = decode_input(&bytes, &mut self.buf);
+                self.partial = bytes.split_off(bytes.len() - incomplete);
+                if !self.buf.is_empty() {
+                    return Ok(());
+                }
+            }
+            if let Some(error) = error {
+                return Err(error);
+            }
+            if self.take_interrupt() {
+                return Err(InputError::Interrupted);
+            }
+            // woken up with nothing to read: wait again for the time left rather than return
+            // an error the caller would retry at once
         }
-        Ok(())
     }
 
     fn next_char(&mut self) -> Result<char> {
-        self.next_char_timeout(Duration::new(0, 0))
+        self.next_char_until(Deadline::Infinite)
     }
 
-    fn next_char_timeout(&mut self, timeout: Duration) -> Result<char> {
+    fn next_char_until(&mut self, deadline: Deadline) -> Result<char> {
+        let ch = self.next_raw_char_until(deadline)?;
+        self.seq.push(ch);
+        Ok(ch)
+    }
+
+    /// `next_char_until` without recording the char in the sequence of the key
+    fn next_raw_char_until(&mut self, deadline: Deadline) -> Result<char> {
         if self.buf.is_empty() {
-            self.get_chars(timeout)?;
+            self.get_chars(deadline)?;
         }
-        self.buf
-            .pop_front()
-            .ok_or("no more bytes in the buffer".into())
+        self.buf.pop_front().ok_or(InputError::Timeout)
+    }
+
+    /// the error for the sequence read so far for the key
+    fn unknown_sequence(&self) -> InputError {
+        InputError::UnknownSequence(self.seq.clone().into_bytes())
     }
 
     /// Wait next key stroke
     pub fn next_key(&mut self) -> Result<Key> {
-        self.next_key_timeout(Duration::new(0, 0))
+        self.next_key_until(Deadline::Infinite)
     }
 
-    /// Wait `timeout` until next key stroke
+    /// Wait `timeout` until next key stroke, a zero `timeout` waits forever (see
+    /// `next_key_until` to poll)
     pub fn next_key_timeout(&mut self, timeout: Duration) -> Result<Key> {
-        let ch = self.next_char_timeout(timeout)?;
+        if timeout == Duration::new(0, 0) {
+            self.next_key_until(Deadline::Infinite)
+        } else {
+            self.next_key_until(Deadline::after(timeout))
+        }
+    }
+
+    /// Wait until `deadline` for the next key stroke, fails once it passed
+    pub fn next_key_until(&mut self, deadline: Deadline) -> Result<Key> {
+        match self.queued.pop_front() {
+            Some(key) => Ok(key),
+            None => self.decode_key(deadline),
+        }
+    }
+
+    fn decode_key(&mut self, deadline: Deadline) -> Result<Key> {
+        let key = self.read_key(deadline)?;
+        match key {
+            MousePress(button @ MouseButton::Left, ..)
+            | MousePress(button @ MouseButton::Middle, ..)
+            | MousePress(button @ MouseButton::Right, ..) => self.held = Some(button),
+            MouseRelease(..) => self.held = None,
+            _ => {}
+        }
+        Ok(self.count_clicks(key))
+    }
+
+    /// The next key if one was already typed, `None` at once otherwise, e.g. to drain the
+    /// input once per frame. The sequences which aren't understood are skipped.
+    pub fn try_next_key(&mut self) -> Option<Key> {
+        loop {
+            match self.next_key_until(Deadline::Poll) {
+                Ok(key) => return Some(key),
+                Err(InputError::UnknownSequence(_)) => continue,
+                Err(_) => return None,
+            }
+        }
+    }
+
+    /// The key `try_next_key` would return, left to be read next
+    pub fn peek_key(&mut self) -> Option<&Key> {
+        if self.queued.is_empty() {
+            let key = self.try_next_key();
+            self.queued.extend(key);
+        }
+        self.queued.front()
+    }
+
+    /// A cursor position query was sent: until it is answered, `ESC [ row ; col R` is read as
+    /// `Key::CursorPos`. Otherwise `ESC [ 1 ; modifiers R` is F3 with modifiers.
+    pub fn expect_cursor_report(&mut self) {
+        self.pending_cpr += 1;
+    }
+
+    /// Wait until `deadline` for the reply to the cursor position query just sent, as (row,
+    /// col). The keys typed in the meantime are kept for the next reads. Once it timed out,
+    /// the late reply is still read as `Key::CursorPos`.
+    pub fn cursor_pos_until(&mut self, deadline: Deadline) -> Result<(u16, u16)> {
+        self.expect_cursor_report();
+        loop {
+            match self.decode_key(deadline) {
+                Ok(CursorPos(row, col)) => return Ok((row, col)),
+                Ok(key) => self.queued.push_back(key),
+                Err(InputError::UnknownSequence(_)) => continue,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// the press after a left press on the same cell, within `click_interval`, is a double
+    /// click, the one after it a triple click, then it starts over
+    fn count_clicks(&mut self, key: Key) -> Key {
+        let (x, y) = match key {
+            MousePress(MouseButton::Left, x, y, modifiers)
+                if modifiers.is_empty() && self.click_interval > Duration::new(0, 0) =>
+            {
+                (x, y)
+            }
+            MousePress(..) => {
+                self.last_click = None;
+                return key;
+            }
+            _ => return key,
+        };
+
+        let now = Instant::now();
+        let count = match self.last_click {
+            Some((at, last_x, last_y, count))
+                if (last_x, last_y) == (x, y)
+                    && count < 3
+                    && now.duration_since(at) <= self.click_interval =>
+            {
+                count + 1
+            }
+            _ => 1,
+        };
+        self.last_click = Some((now, x, y, count));
+        match count {
+            2 => DoubleClick(x, y),
+            3 => TripleClick(x, y),
+            _ => key,
+        }
+    }
+
+    fn read_key(&mut self, deadline: Deadline) -> Result<Key> {
+        self.seq.clear();
+        if let Some(paste) = self.paste.take() {
+            return self.read_paste(paste);
+        }
+        let ch = self.next_char_until(deadline)?;
         match ch {
             '\u{00}' => Ok(Ctrl(' ')),
             '\u{01}' => Ok(Ctrl('a')),
@@ -149,6 +587,7 @@ impl KeyBoard {
             '\u{18}' => Ok(Ctrl('x')),
             '\u{19}' => Ok(Ctrl('y')),
             '\u{1A}' => Ok(Ctrl('z')),
+            '\u{1F}' => Ok(Ctrl('_')),
             '\u{1B}' => self.escape_sequence(),
             '\u{7F}' => Ok(Backspace),
             ch => Ok(Char(ch)),
@@ -156,10 +595,14 @@ impl KeyBoard {
     }
 
     fn escape_sequence(&mut self) -> Result<Key> {
-        let seq1 = self.next_char_timeout(KEY_WAIT).unwrap_or('\u{1B}');
+        let seq1 = self
+            .next_char_until(Deadline::after(self.esc_timeout))
+            .unwrap_or('\u{1B}');
         match seq1 {
             '[' => self.escape_csi(),
             'O' => self.escape_o(),
+            ']' if self.is_osc_reply() => self.escape_osc(),
+            'P' if self.is_termcap_reply() => self.escape_dcs(),
             _ => self.parse_alt(seq1),
         }
     }
@@ -167,11 +610,9 @@ impl KeyBoard {
     fn parse_alt(&mut self, ch: char) -> Result<Key> {
         match ch {
             '\u{1B}' => {
-                match self.next_char_timeout(KEY_WAIT) {
+                match self.next_char_until(Deadline::after(self.esc_timeout)) {
                     Ok('[') => {}
-                    Ok(c) => {
-                        return Err(format!("unsupported esc sequence: ESC ESC {:?}", c).into());
-                    }
+                    Ok(_) => return Err(self.unknown_sequence()),
                     Err(_) => return Ok(ESC),
                 }
 
@@ -182,7 +623,8 @@ impl KeyBoard {
                     Ok(Right) => Ok(AltRight),
                     Ok(PageUp) => Ok(AltPageUp),
                     Ok(PageDown) => Ok(AltPageDown),
-                    _ => Err(format!("unsupported esc sequence: ESC ESC [ ...").into()),
+                    Ok(_) => Err(self.unknown_sequence()),
+                    Err(err) => Err(err),
                 }
             }
             '\u{00}' => Ok(CtrlAlt(' ')),
@@ -212,21 +654,36 @@ impl KeyBoard {
             '\u{18}' => Ok(CtrlAlt('x')),
             '\u{19}' => Ok(AltBackTab),
             '\u{1A}' => Ok(CtrlAlt('z')),
+            '\u{1F}' => Ok(CtrlAlt('_')),
             '\u{7F}' => Ok(AltBackspace),
             ch => Ok(Alt(ch)),
         }
     }
 
     fn escape_csi(&mut self) -> Result<Key> {
-        let cursor_pos = self.parse_cursor_report();
-        if cursor_pos.is_ok() {
-            return cursor_pos;
+        // a reply split across reads, its end follows at once like the rest of an escape
+        // sequence
+        let deadline = Deadline::after(self.esc_timeout);
+        while self.pending_cpr > 0 && self.is_partial_cursor_report() {
+            if self.get_chars(deadline).is_err() {
+                break;
+            }
+        }
+
+        if let Some(key) = self.parse_csi_u() {
+            return key;
+        }
+
+        if let Some(key) = self.parse_cursor_report() {
+            return key;
         }
 
         let seq2 = self.next_char()?;
         match seq2 {
-            '0' | '9' => Err(format!("unsupported esc sequence: ESC [ {:?}", seq2).into()),
-            '1'...'8' => self.extended_escape(seq2),
+            '?' => self.escape_csi_private(),
+            '0' => Err(self.unknown_sequence()),
+            // 9x: the wheels in the rxvt mouse encoding
+            '1'..='9' => self.extended_escape(seq2),
             '[' => {
                 // Linux Console ESC [ [ _
                 let seq3 = self.next_char()?;
@@ -236,7 +693,7 @@ impl KeyBoard {
                     'C' => Ok(F(3)),
                     'D' => Ok(F(4)),
                     'E' => Ok(F(5)),
-                    _ => Err(format!("unsupported esc sequence: ESC [ [ {:?}", seq3).into()),
+                    _ => Err(self.unknown_sequence()),
                 }
             }
             'A' => Ok(Up),    // kcuu1
@@ -246,42 +703,27 @@ impl KeyBoard {
             'H' => Ok(Home),  // khome
             'F' => Ok(End),
             'Z' => Ok(BackTab),
+            'I' => Ok(FocusGained), // with focus events enabled
+            'O' => Ok(FocusLost),
             'M' => {
                 // X10 emulation mouse encoding: ESC [ M Bxy (6 characters only)
-                let cb = self.next_char()? as u8;
-                // (1, 1) are the coords for upper left.
-                let cx = (self.next_char()? as u8).saturating_sub(32) as u16;
-                let cy = (self.next_char()? as u8).saturating_sub(32) as u16;
-                match cb & 0b11 {
-                    0 => {
-                        if cb & 0x40 != 0 {
-                            Ok(MousePress(MouseButton::WheelUp, cx, cy))
-                        } else {
-                            Ok(MousePress(MouseButton::Left, cx, cy))
-                        }
-                    }
-                    1 => {
-                        if cb & 0x40 != 0 {
-                            Ok(MousePress(MouseButton::WheelDown, cx, cy))
-                        } else {
-                            Ok(MousePress(MouseButton::Middle, cx, cy))
-                        }
-                    }
-                    2 => Ok(MousePress(MouseButton::Right, cx, cy)),
-                    3 => Ok(MouseRelease(cx, cy)),
-                    _ => Err(
-                        format!("unsupported esc sequence: ESC M {:?}{:?}{:?}", cb, cx, cy).into(),
-                    ),
-                }
+                self.saw_mouse_encoding(MouseEncoding::X10);
+                let cb = (self.next_char()? as u8).saturating_sub(32);
+                // (1, 1) are the coords for upper left, past 95 they are either single bytes
+                // (decoded as Latin-1) or UTF-8 with the mode 1005, never truncate them
+                let cx = (self.next_char()? as u32).saturating_sub(32) as u16;
+                let cy = (self.next_char()? as u32).saturating_sub(32) as u16;
+
+                mouse_key(cb.into(), cx, cy, false, self.held)
+                    .ok_or_else(|| self.unknown_sequence())
             }
             '<' => {
                 // xterm mouse encoding:
                 // ESC [ < Cb ; Cx ; Cy ; (M or m)
                 if !self.buf.contains(&'m') && !self.buf.contains(&'M') {
-                    return Err(
-                        format!("unknown esc sequence ESC [ < (not ending with m/M)").into(),
-                    );
+                    return Err(self.unknown_sequence());
                 }
+                self.saw_mouse_encoding(MouseEncoding::Sgr);
 
                 let mut str_buf = String::new();
                 let mut c = self.next_char()?;
@@ -289,62 +731,168 @@ impl KeyBoard {
                     str_buf.push(c);
                     c = self.next_char()?;
                 }
-                let nums = &mut str_buf.split(';');
-
-                let cb = nums.next().unwrap().parse::<u16>().unwrap();
-                let cx = nums.next().unwrap().parse::<u16>().unwrap();
-                let cy = nums.next().unwrap().parse::<u16>().unwrap();
-
-                match cb {
-                    0...2 | 64...65 => {
-                        let button = match cb {
-                            0 => MouseButton::Left,
-                            1 => MouseButton::Middle,
-                            2 => MouseButton::Right,
-                            64 => MouseButton::WheelUp,
-                            65 => MouseButton::WheelDown,
-                            _ => {
-                                return Err(
-                                    format!("unknown sequence: ESC [ < {} {}", str_buf, c).into()
-                                );
-                            }
-                        };
-
-                        match c {
-                            'M' => Ok(MousePress(button, cx, cy)),
-                            'm' => Ok(MouseRelease(cx, cy)),
-                            _ => Err(format!("unknown sequence: ESC [ < {} {}", str_buf, c).into()),
-                        }
-                    }
-                    32 => Ok(MouseHold(cx, cy)),
-                    _ => Err(format!("unknown sequence: ESC [ < {} {}", str_buf, c).into()),
-                }
+                mouse_params(&str_buf)
+                    .and_then(|(cb, cx, cy)| mouse_key(cb, cx, cy, c == 'm', self.held))
+                    .ok_or_else(|| self.unknown_sequence())
             }
-            _ => Err(format!("unsupported esc sequence: ESC [ {:?}", seq2).into()),
+            _ => Err(self.unknown_sequence()),
         }
     }
 
-    fn parse_cursor_report(&mut self) -> Result<Key> {
-        if self.buf.contains(&';') && self.buf.contains(&'R') {
-            let mut row = String::new();
-            let mut col = String::new();
+    // ESC [ ? Ps ; ... c: the reply to the DA1 query
+    // ESC [ ? flags u: the reply to the kitty keyboard query
+    fn escape_csi_private(&mut self) -> Result<Key> {
+        let mut params = String::new();
+        let mut c = self.next_char()?;
+        while c.is_ascii_digit() || c == ';' {
+            params.push(c);
+            c = self.next_char()?;
+        }
 
-            while self.buf.front() != Some(&';') {
-                row.push(self.buf.pop_front().unwrap());
+        if c == 'u' {
+            if let Ok(flags) = params.parse() {
+                return Ok(KeyboardFlags(flags));
             }
-            self.buf.pop_front();
+        }
+        if c != 'c' {
+            return Err(self.unknown_sequence());
+        }
+        let attributes = params
+            .split(';')
+            .filter_map(|param| param.parse().ok())
+            .collect();
+        Ok(DeviceAttributes(attributes))
+    }
+
+    /// whether the buffer holds a complete OSC reply, otherwise `ESC ]` is `Alt(']')`
+    fn is_osc_reply(&self) -> bool {
+        self.buf.front().is_some_and(char::is_ascii_digit)
+            && (self.buf.contains(&'\u{07}') || self.buf.contains(&'\u{1B}'))
+    }
 
-            while self.buf.front() != Some(&'R') {
-                col.push(self.buf.pop_front().unwrap());
+    // ESC ] Ps ; Pt (BEL or ESC \): operating system command, e.g. the reply to OSC 11
+    fn escape_osc(&mut self) -> Result<Key> {
+        let mut content = String::new();
+        loop {
+            match self.next_char()? {
+                '\u{07}' => break,
+                '\u{1B}' => {
+                    self.next_char()?; // `\`
+                    break;
+                }
+                c => content.push(c),
             }
-            self.buf.pop_front();
+        }
 
-            let row_num = row.parse::<u16>()?;
-            let col_num = col.parse::<u16>()?;
-            Ok(CursorPos(row_num - 1, col_num - 1))
-        } else {
-            Err(format!("buffer did not contain cursor position response").into())
+        content
+            .strip_prefix("11;")
+            .and_then(parse_rgb)
+            .map(|(r, g, b)| BackgroundColor(r, g, b))
+            .ok_or_else(|| self.unknown_sequence())
+    }
+
+    // ESC [ codepoint ; modifiers u: keys encoded as CSI u, e.g. by iTerm2 with "Report
+    // modifiers using CSI u", `None` if the buffer doesn't start with one
+    fn parse_csi_u(&mut self) -> Option<Result<Key>> {
+        let len = self
+            .buf
+            .iter()
+            .position(|c| !c.is_ascii_digit() && *c != ';' && *c != ':')?;
+        if len == 0 || self.buf[len] != 'u' {
+            return None;
+        }
+
+        let sequence: String = self.buf.drain(..=len).collect();
+        self.seq.push_str(&sequence);
+        let params = &sequence[..len];
+        // sub-parameters (alternate keys, event types) are separated by `:`
+        let mut fields = params
+            .split(';')
+            .map(|field| field.split(':').next().unwrap_or(""));
+        let code = fields.next().and_then(|code| code.parse::<u32>().ok());
+        let modifiers = match fields.next() {
+            Some(modifiers) => modifiers.parse::<u8>().ok(),
+            None => Some(1),
+        };
+
+        Some(match (code, modifiers) {
+            (Some(code), Some(modifiers)) => csi_u_key(code, modifiers, self.kitty_keyboard)
+                .ok_or_else(|| self.unknown_sequence()),
+            _ => Err(self.unknown_sequence()),
+        })
+    }
+
+    /// whether the buffer holds a complete XTGETTCAP reply, otherwise `ESC P` is `Alt('P')`
+    fn is_termcap_reply(&self) -> bool {
+        matches!(self.buf.front(), Some('0') | Some('1'))
+            && self.buf.get(1) == Some(&'+')
+            && self.buf.get(2) == Some(&'r')
+            && self.buf.contains(&'\u{1B}')
+    }
+
+    // ESC P Ps + r Pt ESC \: device control string, the reply to XTGETTCAP
+    fn escape_dcs(&mut self) -> Result<Key> {
+        let mut content = String::new();
+        loop {
+            match self.next_char()? {
+                '\u{1B}' => {
+                    self.next_char()?; // `\`
+                    break;
+                }
+                c => content.push(c),
+            }
         }
+
+        parse_termcap_reply(&content)
+            .map(TermcapReply)
+            .ok_or_else(|| self.unknown_sequence())
+    }
+
+    /// whether the buffer holds the start of a cursor position report and nothing more, e.g.
+    /// `12;4` with the rest still on its way
+    fn is_partial_cursor_report(&self) -> bool {
+        let mut fields = self.buf.iter().collect::<String>();
+        let col = match fields.find(';') {
+            Some(at) => fields.split_off(at + 1),
+            None => String::new(),
+        };
+        let row = fields.trim_end_matches(';');
+        !row.is_empty()
+            && row.chars().all(|c| c.is_ascii_digit())
+            && col.chars().all(|c| c.is_ascii_digit())
+    }
+
+    // ESC [ row ; col R: the reply to the cursor position query, `None` if the sequence isn't
+    // one. Only the sequence at the front of the buffer is matched, the keys typed after it
+    // can't be taken for a part of it. Without a query pending, `ESC [ 1 ; modifiers R` is F3.
+    fn parse_cursor_report(&mut self) -> Option<Result<Key>> {
+        let row_len = self.buf.iter().position(|c| !c.is_ascii_digit())?;
+        if row_len == 0 || self.buf[row_len] != ';' {
+            return None;
+        }
+        let col_len = self
+            .buf
+            .iter()
+            .skip(row_len + 1)
+            .position(|c| !c.is_ascii_digit())?;
+        let len = row_len + 1 + col_len;
+        if col_len == 0 || self.buf[len] != 'R' {
+            return None;
+        }
+        let is_f3 = row_len == 1 && self.buf[0] == '1' && col_len == 1;
+        if self.pending_cpr == 0 && is_f3 {
+            return None;
+        }
+
+        let sequence: String = self.buf.drain(..=len).collect();
+        self.seq.push_str(&sequence);
+        self.pending_cpr = self.pending_cpr.saturating_sub(1);
+        let row = sequence[..row_len].parse::<u16>();
+        let col = sequence[row_len + 1..len].parse::<u16>();
+        Some(match (row, col) {
+            (Ok(row), Ok(col)) => Ok(CursorPos(row.saturating_sub(1), col.saturating_sub(1))),
+            _ => Err(self.unknown_sequence()),
+        })
     }
 
     fn extended_escape(&mut self, seq2: char) -> Result<Key> {
@@ -357,9 +905,9 @@ impl KeyBoard {
                 '4' | '8' => Ok(End), // tmux, xrvt
                 '5' => Ok(PageUp),    // kpp
                 '6' => Ok(PageDown),  // knp
-                _ => Err(format!("unsupported esc sequence: ESC [ {} ~", seq2).into()),
+                _ => Err(self.unknown_sequence()),
             }
-        } else if seq3.is_digit(10) {
+        } else if seq3.is_ascii_digit() {
             let mut str_buf = String::new();
             str_buf.push(seq2);
             str_buf.push(seq3);
@@ -374,36 +922,51 @@ impl KeyBoard {
                 'M' => {
                     // rxvt mouse encoding:
                     // ESC [ Cb ; Cx ; Cy ; M
-                    let mut nums = str_buf.split(';');
-
-                    let cb = nums.next().unwrap().parse::<u16>().unwrap();
-                    let cx = nums.next().unwrap().parse::<u16>().unwrap();
-                    let cy = nums.next().unwrap().parse::<u16>().unwrap();
-
-                    match cb {
-                        32 => Ok(MousePress(MouseButton::Left, cx, cy)),
-                        33 => Ok(MousePress(MouseButton::Middle, cx, cy)),
-                        34 => Ok(MousePress(MouseButton::Right, cx, cy)),
-                        35 => Ok(MouseRelease(cx, cy)),
-                        64 => Ok(MouseHold(cx, cy)),
-                        96 | 97 => Ok(MousePress(MouseButton::WheelUp, cx, cy)),
-                        _ => Err(format!("unsupported esc sequence: ESC [ {} M", str_buf).into()),
+                    self.saw_mouse_encoding(MouseEncoding::Urxvt);
+                    mouse_params(&str_buf)
+                        .and_then(|(cb, cx, cy)| {
+                            mouse_key(cb.saturating_sub(32), cx, cy, false, self.held)
+                        })
+                        .ok_or_else(|| self.unknown_sequence())
+                }
+                '~' if str_buf == "200" => self.bracketed_paste(),
+                '~' if str_buf.starts_with("27;") => {
+                    // xterm's modifyOtherKeys: ESC [ 27 ; modifiers ; code ~
+                    let mut fields = str_buf.split(';').skip(1);
+                    let modifiers = fields.next().and_then(|field| field.parse::<u8>().ok());
+                    let code = fields.next().and_then(|field| field.parse::<u32>().ok());
+                    match (modifiers, code) {
+                        (Some(modifiers), Some(code)) => {
+                            csi_u_key(code, modifiers, true).ok_or_else(|| self.unknown_sequence())
+                        }
+                        _ => Err(self.unknown_sequence()),
                     }
                 }
                 '~' => {
-                    let num: u8 = str_buf.parse().unwrap();
+                    // the modifiers, e.g. `ESC [ 15 ; 5 ~` for Ctrl+F5, are ignored except
+                    // shift: xterm sends F13-F24 as Shift+F1-F12, e.g. `ESC [ 15 ; 2 ~` for F17
+                    let mut fields = str_buf.split(';');
+                    let num: u8 = match fields.next().unwrap_or("").parse() {
+                        Ok(num) => num,
+                        Err(_) => return Err(self.unknown_sequence()),
+                    };
+                    let shifted = if fields.next() == Some("2") { 12 } else { 0 };
                     match num {
-                        v @ 11...15 => Ok(F(v - 10)),
-                        v @ 17...21 => Ok(F(v - 11)),
-                        v @ 23...24 => Ok(F(v - 12)),
-                        _ => Err(format!("unsupported esc sequence: ESC [ {} ~", str_buf).into()),
+                        v @ 11..=15 => Ok(F(v - 10 + shifted)),
+                        v @ 17..=21 => Ok(F(v - 11 + shifted)),
+                        v @ 23..=24 => Ok(F(v - 12 + shifted)),
+                        // rxvt, vt220
+                        v @ 25..=26 => Ok(F(v - 12)),
+                        v @ 28..=29 => Ok(F(v - 13)),
+                        v @ 31..=34 => Ok(F(v - 14)),
+                        _ => Err(self.unknown_sequence()),
                     }
                 }
                 _ => unreachable!(),
             }
         } else if seq3 == ';' {
             let seq4 = self.next_char()?;
-            if seq4.is_digit(10) {
+            if seq4.is_ascii_digit() {
                 let seq5 = self.next_char()?;
                 if seq2 == '1' {
                     match (seq4, seq5) {
@@ -421,21 +984,24 @@ impl KeyBoard {
                         ('2', 'B') => Ok(ShiftDown),
                         ('2', 'C') => Ok(ShiftRight),
                         ('2', 'D') => Ok(ShiftLeft),
-                        _ => Err(format!(
-                            "unsupported esc sequence: ESC [ 1 ; {} {:?}",
-                            seq4, seq5
-                        )
-                        .into()),
+                        // xterm's F13-F16 are Shift+F1-F4, `R` only without a cursor position
+                        // query pending
+                        ('2', 'P') => Ok(F(13)),
+                        ('2', 'Q') => Ok(F(14)),
+                        ('2', 'R') => Ok(F(15)),
+                        ('2', 'S') => Ok(F(16)),
+                        // kitty, with any modifiers, F3 is `ESC [ 13 ~`
+                        (_, 'P') => Ok(F(1)),
+                        (_, 'Q') => Ok(F(2)),
+                        (_, 'R') => Ok(F(3)),
+                        (_, 'S') => Ok(F(4)),
+                        _ => Err(self.unknown_sequence()),
                     }
                 } else {
-                    Err(format!(
-                        "unsupported esc sequence: ESC [ {} ; {} {:?}",
-                        seq2, seq4, seq5
-                    )
-                    .into())
+                    Err(self.unknown_sequence())
                 }
             } else {
-                Err(format!("unsupported esc sequence: ESC [ {} ; {:?}", seq2, seq4).into())
+                Err(self.unknown_sequence())
             }
         } else {
             match (seq2, seq3) {
@@ -443,11 +1009,38 @@ impl KeyBoard {
                 ('5', 'B') => Ok(CtrlDown),
                 ('5', 'C') => Ok(CtrlRight),
                 ('5', 'D') => Ok(CtrlLeft),
-                _ => Err(format!("unsupported esc sequence: ESC [ {} {:?}", seq2, seq3).into()),
+                _ => Err(self.unknown_sequence()),
             }
         }
     }
 
+    // ESC [ 200 ~ text ESC [ 201 ~: the text pasted while bracketed paste is enabled, see
+    // `Output::enable_bracketed_paste`
+    fn bracketed_paste(&mut self) -> Result<Key> {
+        self.read_paste(Paste::new(self.max_paste_len))
+    }
+
+    /// read the paste up to its end marker, it is given up once `PASTE_TIMEOUT` passed or the
+    /// input is closed. Interrupted, it is resumed by the next read.
+    fn read_paste(&mut self, mut paste: Paste) -> Result<Key> {
+        // a large paste spans several reads
+        loop {
+            match self.next_raw_char_until(paste.deadline) {
+                Ok(ch) => {
+                    if paste.push(ch) {
+                        break;
+                    }
+                }
+                Err(InputError::Interrupted) => {
+                    self.paste = Some(paste);
+                    return Err(InputError::Interrupted);
+                }
+                Err(_) => break,
+            }
+        }
+        Ok(BracketedPaste(paste.finish()))
+    }
+
     // SSS3
     fn escape_o(&mut self) -> Result<Key> {
         let seq2 = self.next_char()?;
@@ -466,9 +1059,118 @@ impl KeyBoard {
             'b' => Ok(CtrlDown),
             'c' => Ok(CtrlRight), // rxvt
             'd' => Ok(CtrlLeft),  // rxvt
-            _ => Err(format!("unsupported esc sequence: ESC O {:?}", seq2).into()),
+            // the keypad in application mode (`smkx`), as the keys it types
+            'p'..='y' => Ok(Char((seq2 as u8 - b'p' + b'0') as char)),
+            'M' => Ok(Enter),
+            'j' => Ok(Char('*')),
+            'k' => Ok(Char('+')),
+            'l' => Ok(Char(',')),
+            'm' => Ok(Char('-')),
+            'n' => Ok(Char('.')),
+            'o' => Ok(Char('/')),
+            'X' => Ok(Char('=')),
+            // with modifiers, e.g. `ESC O 2 P` for F13 (Shift+F1) by konsole
+            '2'..='8' => {
+                let seq3 = self.next_char()?;
+                let shifted = if seq2 == '2' { 12 } else { 0 };
+                match seq3 {
+                    'P'..='S' => Ok(F(seq3 as u8 - b'P' + 1 + shifted)),
+                    _ => Err(self.unknown_sequence()),
+                }
+            }
+            _ => Err(self.unknown_sequence()),
+        }
+    }
+}
+
+/// parse the X11 color `rgb:RRRR/GGGG/BBBB` (1 to 4 hex digits per component)
+fn parse_rgb(color: &str) -> Option<(u8, u8, u8)> {
+    let mut components = color.strip_prefix("rgb:")?.split('/').map(|component| {
+        let value = u32::from_str_radix(component, 16).ok()?;
+        let max = (1u32 << (4 * component.len().clamp(1, 4))) - 1;
+        Some((value * 255 / max) as u8)
+    });
+    let r = components.next()??;
+    let g = components.next()??;
+    let b = components.next()??;
+    Some((r, g, b))
+}
+
+/// Decode the input bytes as UTF-8, an invalid byte is taken as Latin-1: the X10 mouse reports
+/// send the coordinates past 95 as single bytes. Return the length of the incomplete char at
+/// the end, left undecoded.
+fn decode_input(mut bytes: &[u8], chars: &mut VecDeque<char>) -> usize {
+    while !bytes.is_empty() {
+        match std::str::from_utf8(bytes) {
+            Ok(valid) => {
+                chars.extend(valid.chars());
+                break;
+            }
+            Err(err) => {
+                let valid_up_to = err.valid_up_to();
+                let valid = std::str::from_utf8(&bytes[..valid_up_to]).unwrap_or_default();
+                chars.extend(valid.chars());
+                if err.error_len().is_none() {
+                    return bytes.len() - valid_up_to;
+                }
+                chars.push_back(char::from(bytes[valid_up_to]));
+                bytes = &bytes[valid_up_to + 1..];
+            }
         }
     }
+    0
+}
+
+/// the button and coordinates `Cb;Cx;Cy` of the SGR and urxvt mouse reports
+fn mouse_params(params: &str) -> Option<(u16, u16, u16)> {
+    let mut nums = params.split(';').map(|num| num.parse::<u16>().ok());
+    let (cb, cx, cy) = (nums.next()??, nums.next()??, nums.next()??);
+    match nums.next() {
+        None => Some((cb, cx, cy)),
+        Some(_) => None,
+    }
+}
+
+/// `1+r` then the hex encoded `name=value` pairs separated by `;` (a boolean capability has no
+/// value), or `0+r` then the names the terminal doesn't know
+fn parse_termcap_reply(content: &str) -> Option<Vec<(String, Option<String>)>> {
+    let (known, capabilities) = match content.strip_prefix("1+r") {
+        Some(capabilities) => (true, capabilities),
+        None => (false, content.strip_prefix("0+r")?),
+    };
+
+    capabilities
+        .split(';')
+        .filter(|capability| !capability.is_empty())
+        .map(|capability| {
+            let mut parts = capability.splitn(2, '=');
+            let name = decode_hex(parts.next()?)?;
+            let value = match parts.next() {
+                _ if !known => None,
+                Some(value) => Some(decode_hex(value)?),
+                None => Some(String::new()),
+            };
+            Some((name, value))
+        })
+        .collect()
+}
+
+fn decode_hex(hex: &str) -> Option<String> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    String::from_utf8(bytes).ok()
+}
+
+/// The input read, e.g. to wait for its readiness in an event loop then call `try_next_key`
+impl AsRawFd for KeyBoard {
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
 }
 
 pub struct KeyboardHandler {
@@ -481,3 +1183,673 @@ impl KeyboardHandler {
         let _ = handler.write_all(b"x\n");
     }
 }
+
+/// The mouse key of the button byte `cb` of a report (less the offset of 32 of X10 and urxvt):
+/// the button in bits 1, 2 and 64 (64 to 67 for the wheels), the modifiers in bits 4, 8 and 16, 32 for a motion.
+/// `release` is SGR's final `m`, the others report a release as the button 3: the `held` one
+/// (left if unknown).
+fn mouse_key(cb: u16, cx: u16, cy: u16, release: bool, held: Option<MouseButton>) -> Option<Key> {
+    let modifiers = Modifiers::from_bits_truncate((cb >> 2) as u8 & 0b111);
+    let cb = cb & !0b11100;
+    if cb & 32 != 0 {
+        // motion while a button is held (button-event tracking), or with none held (any-event
+        // tracking)
+        return match cb & 0b11 {
+            3 => Some(MouseMove(cx, cy, modifiers)),
+            _ => Some(MouseHold(cx, cy, modifiers)),
+        };
+    }
+    let button = match cb {
+        0 => MouseButton::Left,
+        1 => MouseButton::Middle,
+        2 => MouseButton::Right,
+        3 => {
+            let button = held.unwrap_or(MouseButton::Left);
+            return Some(MouseRelease(button, cx, cy, modifiers));
+        }
+        64 => MouseButton::WheelUp,
+        65 => MouseButton::WheelDown,
+        66 => MouseButton::WheelLeft,
+        67 => MouseButton::WheelRight,
+        _ => return None,
+    };
+    if release {
+        return Some(MouseRelease(button, cx, cy, modifiers));
+    }
+    Some(MousePress(button, cx, cy, modifiers))
+}
+
+/// The key of a CSI u sequence. `modifiers` is 1 + the bits of shift (1), alt (2) and ctrl (4),
+/// the others (super, caps lock...) are ignored, as is shift where `Key` has no variant for it.
+/// With `extended` (the kitty keyboard protocol, xterm's modifyOtherKeys), the keys only these
+/// encodings tell apart are decoded as well, e.g. `Key::CtrlShift`.
+fn csi_u_key(code: u32, modifiers: u8, extended: bool) -> Option<Key> {
+    let bits = modifiers.saturating_sub(1);
+    let (shift, alt, ctrl) = (bits & 1 != 0, bits & 2 != 0, bits & 4 != 0);
+    let key = match code {
+        9 => match (shift, alt) {
+            (false, false) => Tab,
+            (true, false) => BackTab,
+            (false, true) => AltTab,
+            (true, true) => AltBackTab,
+        },
+        13 if alt => AltEnter,
+        13 => Enter,
+        27 => ESC,
+        8 | 127 if alt => AltBackspace,
+        8 | 127 => Backspace,
+        code if extended && KITTY_FUNCTIONAL_KEYS.contains(&code) => {
+            return kitty_functional_key(code);
+        }
+        code => {
+            let ch = char::from_u32(code).filter(|ch| !ch.is_control())?;
+            match (ctrl, alt) {
+                (true, true) => CtrlAlt(ch.to_ascii_lowercase()),
+                (true, false) if extended && shift => CtrlShift(ch.to_ascii_lowercase()),
+                (true, false) => Ctrl(ch.to_ascii_lowercase()),
+                (false, alt) => {
+                    // terminals report the unshifted key
+                    let ch = if shift {
+                        ch.to_uppercase().next().unwrap_or(ch)
+                    } else {
+                        ch
+                    };
+                    if alt {
+                        Alt(ch)
+                    } else {
+                        Char(ch)
+                    }
+                }
+            }
+        }
+    };
+    Some(key)
+}
+
+/// the codes of the keys without a character in the kitty keyboard protocol (private use)
+const KITTY_FUNCTIONAL_KEYS: std::ops::RangeInclusive<u32> = 57344..=63743;
+
+/// The functional keys of the kitty keyboard protocol, the keypad as the keys it types. The
+/// modifier keys alone, the lock keys and the media keys are not reported as `Key`.
+fn kitty_functional_key(code: u32) -> Option<Key> {
+    let key = match code {
+        57376..=57398 => F((code - 57376 + 13) as u8),
+        57399..=57408 => Char(char::from_digit(code - 57399, 10)?),
+        57409 => Char('.'),
+        57410 => Char('/'),
+        57411 => Char('*'),
+        57412 => Char('-'),
+        57413 => Char('+'),
+        57414 => Enter,
+        57415 => Char('='),
+        57416 => Char(','),
+        57417 => Left,
+        57418 => Right,
+        57419 => Up,
+        57420 => Down,
+        57421 => PageUp,
+        57422 => PageDown,
+        57423 => Home,
+        57424 => End,
+        57425 => Insert,
+        57426 => Delete,
+        _ => return None,
+    };
+    Some(key)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::thread;
+
+    /// keyboard reading from a pipe, the write end should be kept alive, otherwise the read
+    /// end would reach EOF
+    fn keyboard_with_input(input: &[u8]) -> (KeyBoard, File) {
+        let (rx, tx) = nix::unistd::pipe().unwrap();
+        let mut tx = unsafe { File::from_raw_fd(tx) };
+        tx.write_all(input).unwrap();
+        (
+            KeyBoard::new(Box::new(unsafe { File::from_raw_fd(rx) })),
+            tx,
+        )
+    }
+
+    fn next_key(keyboard: &mut KeyBoard) -> Key {
+        keyboard
+            .next_key_timeout(Duration::from_millis(100))
+            .unwrap()
+    }
+
+    #[test]
+    fn ctrl_underscore() {
+        let (mut keyboard, _tx) = keyboard_with_input(b"\x1f\x1b\x1fa");
+        assert_eq!(Ctrl('_'), next_key(&mut keyboard));
+        assert_eq!(CtrlAlt('_'), next_key(&mut keyboard));
+        assert_eq!(Char('a'), next_key(&mut keyboard));
+    }
+
+    #[test]
+    fn termcap_reply() {
+        // RGB (boolean), Smulx=\E[4:%p1%dm, then an unknown name
+        let (mut keyboard, _tx) = keyboard_with_input(
+            b"\x1bP1+r524742;536d756c78=1B5B343A25703125646D\x1b\\\x1bP0+r5375\x1b\\\x1bPa",
+        );
+        assert_eq!(
+            TermcapReply(vec![
+                ("RGB".to_string(), Some(String::new())),
+                ("Smulx".to_string(), Some("\x1b[4:%p1%dm".to_string())),
+            ]),
+            next_key(&mut keyboard)
+        );
+        assert_eq!(
+            TermcapReply(vec![("Su".to_string(), None)]),
+            next_key(&mut keyboard)
+        );
+        assert_eq!(Alt('P'), next_key(&mut keyboard));
+    }
+
+    #[test]
+    fn csi_u() {
+        let (mut keyboard, _tx) = keyboard_with_input(
+            b"\x1b[97;5u\x1b[97;7u\x1b[97;3u\x1b[97;2u\x1b[97u\x1b[13;3u\x1b[9;2u\x1b[27u\x1b[127;1u",
+        );
+        assert_eq!(Ctrl('a'), next_key(&mut keyboard));
+        assert_eq!(CtrlAlt('a'), next_key(&mut keyboard));
+        assert_eq!(Alt('a'), next_key(&mut keyboard));
+        assert_eq!(Char('A'), next_key(&mut keyboard));
+        assert_eq!(Char('a'), next_key(&mut keyboard));
+        assert_eq!(AltEnter, next_key(&mut keyboard));
+        assert_eq!(BackTab, next_key(&mut keyboard));
+        assert_eq!(ESC, next_key(&mut keyboard));
+        assert_eq!(Backspace, next_key(&mut keyboard));
+
+        // sub-parameters, and the caps lock bit ignored
+        let (mut keyboard, _tx) = keyboard_with_input(b"\x1b[97:65;69u\x1b[1;5A");
+        assert_eq!(Ctrl('a'), next_key(&mut keyboard));
+        assert_eq!(CtrlUp, next_key(&mut keyboard));
+
+        let (mut keyboard, _tx) = keyboard_with_input(b"\x1b[1u");
+        assert!(keyboard
+            .next_key_timeout(Duration::from_millis(100))
+            .is_err());
+    }
+
+    #[test]
+    fn kitty_keyboard() {
+        let input =
+            b"\x1b[?1u\x1b[112;6u\x1b[44;5u\x1b[57399u\x1b[57376;5u\x1b[1;5P\x1b[15;5~\x1b[57441u";
+        let (keyboard, _tx) = keyboard_with_input(input);
+        let mut keyboard = keyboard.kitty_keyboard(true);
+        assert_eq!(KeyboardFlags(1), next_key(&mut keyboard));
+        assert_eq!(CtrlShift('p'), next_key(&mut keyboard));
+        assert_eq!(Ctrl(','), next_key(&mut keyboard));
+        assert_eq!(Char('0'), next_key(&mut keyboard));
+        assert_eq!(F(13), next_key(&mut keyboard));
+        assert_eq!(F(1), next_key(&mut keyboard));
+        assert_eq!(F(5), next_key(&mut keyboard));
+        // left shift alone
+        assert!(keyboard
+            .next_key_timeout(Duration::from_millis(100))
+            .is_err());
+
+        // without the mode, shift is dropped as before
+        let (mut keyboard, _tx) = keyboard_with_input(b"\x1b[112;6u");
+        assert_eq!(Ctrl('p'), next_key(&mut keyboard));
+    }
+
+    #[test]
+    fn bracketed_paste() {
+        let (mut keyboard, mut tx) =
+            keyboard_with_input(b"\x1b[200~ls -l\r\n\x1b[Acd \xe4\xb8\xad");
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            tx.write_all("\u{6587}\x1b[201~a".as_bytes()).unwrap();
+            thread::sleep(Duration::from_millis(200));
+        });
+        assert_eq!(
+            BracketedPaste("ls -l\r\n\x1b[Acd \u{4e2d}\u{6587}".to_string()),
+            next_key(&mut keyboard)
+        );
+        assert_eq!(Char('a'), next_key(&mut keyboard));
+    }
+
+    #[test]
+    fn stalled_paste() {
+        let (mut keyboard, mut tx) = keyboard_with_input(b"\x1b[200~abc");
+        let handler = keyboard.get_interrupt_handler();
+        let writer = thread::spawn(move || {
+            // longer than a key takes, the paste is still read to its end
+            thread::sleep(Duration::from_millis(700));
+            handler.interrupt();
+            thread::sleep(Duration::from_millis(100));
+            tx.write_all(b"def\x1b[201~x").unwrap();
+            tx
+        });
+        assert!(matches!(
+            keyboard.next_key_timeout(Duration::from_millis(100)),
+            Err(InputError::Interrupted)
+        ));
+        // resumed by the next read
+        assert_eq!(
+            BracketedPaste("abcdef".to_string()),
+            next_key(&mut keyboard)
+        );
+        assert_eq!(Char('x'), next_key(&mut keyboard));
+
+        // given up once the input is closed
+        let mut tx = writer.join().unwrap();
+        tx.write_all(b"\x1b[200~ghi").unwrap();
+        drop(tx);
+        assert_eq!(BracketedPaste("ghi".to_string()), next_key(&mut keyboard));
+    }
+
+    #[test]
+    fn oversized_paste() {
+        let (keyboard, mut tx) = keyboard_with_input(b"");
+        let mut keyboard = keyboard.max_paste_len(8);
+        thread::spawn(move || {
+            tx.write_all(b"\x1b[200~0123456").unwrap();
+            tx.write_all("\u{4e2d}".repeat(1000).as_bytes()).unwrap();
+            // dropped up to the end marker, even split across reads
+            tx.write_all(b"\x1b[20").unwrap();
+            thread::sleep(Duration::from_millis(50));
+            tx.write_all(b"1~x").unwrap();
+            thread::sleep(Duration::from_millis(200));
+        });
+        assert_eq!(
+            BracketedPaste("0123456".to_string()),
+            next_key(&mut keyboard)
+        );
+        assert_eq!(Char('x'), next_key(&mut keyboard));
+    }
+
+    #[test]
+    fn try_and_peek() {
+        let (mut keyboard, _tx) = keyboard_with_input(b"ab\x1b[0x");
+        assert_eq!(Some(&Char('a')), keyboard.peek_key());
+        assert_eq!(Some(&Char('a')), keyboard.peek_key());
+        assert_eq!(Some(Char('a')), keyboard.try_next_key());
+        assert_eq!(Char('b'), next_key(&mut keyboard));
+        // past the unknown sequence
+        assert_eq!(Some(Char('x')), keyboard.try_next_key());
+
+        let started = Instant::now();
+        assert_eq!(None, keyboard.peek_key());
+        assert_eq!(None, keyboard.try_next_key());
+        assert!(started.elapsed() < Duration::from_millis(50));
+    }
+
+    #[test]
+    fn deadline() {
+        assert!(Deadline::Poll.has_passed());
+        assert_eq!(None, Deadline::Infinite.remaining());
+        assert!(!Deadline::after(Duration::from_secs(60)).has_passed());
+        assert!(Deadline::At(Instant::now()).has_passed());
+    }
+
+    #[test]
+    fn wait_until_deadline() {
+        let (mut keyboard, mut tx) = keyboard_with_input(b"a");
+        assert_eq!(Char('a'), keyboard.next_key_until(Deadline::Poll).unwrap());
+
+        // idle: polling doesn't block, a deadline blocks until it passes, not before
+        let started = Instant::now();
+        assert!(matches!(
+            keyboard.next_key_until(Deadline::Poll),
+            Err(InputError::Timeout)
+        ));
+        assert!(started.elapsed() < Duration::from_millis(50));
+        for wait in &[Duration::from_micros(1500), Duration::from_millis(50)] {
+            let deadline = Deadline::after(*wait);
+            assert!(keyboard.next_key_until(deadline).is_err());
+            assert!(deadline.has_passed());
+        }
+
+        // an interrupt ends the wait, even with no deadline
+        let handler = keyboard.get_interrupt_handler();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            handler.interrupt();
+        });
+        assert!(matches!(
+            keyboard.next_key_until(Deadline::Infinite),
+            Err(InputError::Interrupted)
+        ));
+
+        // a closed input fails rather than being waited for
+        tx.write_all(b"b").unwrap();
+        drop(tx);
+        assert_eq!(
+            Char('b'),
+            keyboard.next_key_until(Deadline::Infinite).unwrap()
+        );
+        let err = keyboard.next_key_until(Deadline::Infinite).unwrap_err();
+        assert!(err.is_closed());
+    }
+
+    #[test]
+    fn clicks() {
+        let press = b"\x1b[<0;3;4M\x1b[<0;3;4m";
+        let (keyboard, mut tx) = keyboard_with_input(&press.repeat(4));
+        let mut keyboard = keyboard.click_interval(Duration::from_millis(200));
+        let mut presses = || {
+            let press = next_key(&mut keyboard);
+            assert_eq!(
+                MouseRelease(MouseButton::Left, 3, 4, Modifiers::empty()),
+                next_key(&mut keyboard)
+            );
+            press
+        };
+        assert_eq!(
+            MousePress(MouseButton::Left, 3, 4, Modifiers::empty()),
+            presses()
+        );
+        assert_eq!(DoubleClick(3, 4), presses());
+        assert_eq!(TripleClick(3, 4), presses());
+        assert_eq!(
+            MousePress(MouseButton::Left, 3, 4, Modifiers::empty()),
+            presses()
+        );
+
+        // elsewhere, with another button in between, or too late
+        tx.write_all(b"\x1b[<0;5;4M\x1b[<2;5;4M\x1b[<0;5;4M")
+            .unwrap();
+        assert_eq!(
+            MousePress(MouseButton::Left, 5, 4, Modifiers::empty()),
+            next_key(&mut keyboard)
+        );
+        assert_eq!(
+            MousePress(MouseButton::Right, 5, 4, Modifiers::empty()),
+            next_key(&mut keyboard)
+        );
+        assert_eq!(
+            MousePress(MouseButton::Left, 5, 4, Modifiers::empty()),
+            next_key(&mut keyboard)
+        );
+        thread::sleep(Duration::from_millis(250));
+        tx.write_all(b"\x1b[<0;5;4M").unwrap();
+        assert_eq!(
+            MousePress(MouseButton::Left, 5, 4, Modifiers::empty()),
+            next_key(&mut keyboard)
+        );
+    }
+
+    #[test]
+    fn focus_events() {
+        let (mut keyboard, _tx) = keyboard_with_input(b"\x1b[O\x1b[I");
+        assert_eq!(FocusLost, next_key(&mut keyboard));
+        assert_eq!(FocusGained, next_key(&mut keyboard));
+    }
+
+    #[test]
+    fn modify_other_keys() {
+        let (mut keyboard, _tx) = keyboard_with_input(
+            b"\x1b[27;6;80~\x1b[27;5;44~\x1b[27;3;97~\x1b[27;2;33~\x1b[27;5;13~\x1b[27;5~",
+        );
+        assert_eq!(CtrlShift('p'), next_key(&mut keyboard));
+        assert_eq!(Ctrl(','), next_key(&mut keyboard));
+        assert_eq!(Alt('a'), next_key(&mut keyboard));
+        assert_eq!(Char('!'), next_key(&mut keyboard));
+        assert_eq!(Enter, next_key(&mut keyboard));
+        assert!(keyboard
+            .next_key_timeout(Duration::from_millis(100))
+            .is_err());
+    }
+
+    #[test]
+    fn mouse_on_wide_terminal() {
+        // a 400 columns terminal, the X10 reports as UTF-8 (mode 1005) and as raw bytes
+        let mut input = b"\x1b[<0;400;10M\x1b[32;400;10M\x1b[M ".to_vec();
+        input.extend("\u{14c}*".as_bytes()); // 300 + 32
+        input.extend(b"\x1b[M #\xe0");
+        let (keyboard, _tx) = keyboard_with_input(&input);
+        let encoding = Arc::new(SpinLock::new(None));
+        let mut keyboard = keyboard.mouse_encoding(encoding.clone());
+
+        assert_eq!(
+            MousePress(MouseButton::Left, 400, 10, Modifiers::empty()),
+            next_key(&mut keyboard)
+        );
+        assert_eq!(Some(MouseEncoding::Sgr), *encoding.lock());
+        assert_eq!(
+            MousePress(MouseButton::Left, 400, 10, Modifiers::empty()),
+            next_key(&mut keyboard)
+        );
+        assert_eq!(Some(MouseEncoding::Urxvt), *encoding.lock());
+        assert_eq!(
+            MousePress(MouseButton::Left, 300, 10, Modifiers::empty()),
+            next_key(&mut keyboard)
+        );
+        assert_eq!(Some(MouseEncoding::X10), *encoding.lock());
+        assert_eq!(
+            MousePress(MouseButton::Left, 3, 192, Modifiers::empty()),
+            next_key(&mut keyboard)
+        );
+    }
+
+    #[test]
+    fn mouse_drag_sgr() {
+        let (mut keyboard, _tx) =
+            keyboard_with_input(b"\x1b[<0;3;4M\x1b[<32;4;4M\x1b[<34;5;4M\x1b[<0;5;4m");
+        assert_eq!(
+            MousePress(MouseButton::Left, 3, 4, Modifiers::empty()),
+            next_key(&mut keyboard)
+        );
+        assert_eq!(MouseHold(4, 4, Modifiers::empty()), next_key(&mut keyboard));
+        assert_eq!(MouseHold(5, 4, Modifiers::empty()), next_key(&mut keyboard));
+        assert_eq!(
+            MouseRelease(MouseButton::Left, 5, 4, Modifiers::empty()),
+            next_key(&mut keyboard)
+        );
+    }
+
+    #[test]
+    fn invalid_or_split_input() {
+        // garbage is reported as errors, the keys after it are still read
+        let (mut keyboard, _tx) = keyboard_with_input(b"\x1b[<1;2M\x1b[<a;b;cM\x1b[32;1M\xff\xfex");
+        for sequence in &[&b"\x1b[<1;2M"[..], b"\x1b[<a;b;cM", b"\x1b[32;1M"] {
+            match keyboard.next_key_timeout(Duration::from_millis(100)) {
+                Err(InputError::UnknownSequence(bytes)) => assert_eq!(sequence, &bytes),
+                other => panic!("unexpected {:?}", other),
+            }
+        }
+        assert_eq!(Char('ÿ'), next_key(&mut keyboard));
+        assert_eq!(Char('þ'), next_key(&mut keyboard));
+        assert_eq!(Char('x'), next_key(&mut keyboard));
+
+        // a char split across reads
+        let (mut keyboard, mut tx) = keyboard_with_input("é€".as_bytes()[..3].as_ref());
+        let writer = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(5));
+            tx.write_all(&"€".as_bytes()[1..]).unwrap();
+            tx
+        });
+        assert_eq!(Char('é'), next_key(&mut keyboard));
+        assert_eq!(Char('€'), next_key(&mut keyboard));
+        let _tx = writer.join().unwrap();
+
+        // or a Latin-1 byte of a X10 mouse report (col 163) at the end of a read
+        let (mut keyboard, _tx) = keyboard_with_input(b"\x1b[M $\xc3");
+        assert_eq!(
+            MousePress(MouseButton::Left, 4, 163, Modifiers::empty()),
+            next_key(&mut keyboard)
+        );
+    }
+
+    #[test]
+    fn mouse_drag_x10() {
+        // button byte, col & row are offset by 32
+        let (mut keyboard, _tx) = keyboard_with_input(b"\x1b[M #$\x1b[M@$$\x1b[M`$$\x1b[M#$$");
+        assert_eq!(
+            MousePress(MouseButton::Left, 3, 4, Modifiers::empty()),
+            next_key(&mut keyboard)
+        );
+        assert_eq!(MouseHold(4, 4, Modifiers::empty()), next_key(&mut keyboard));
+        assert_eq!(
+            MousePress(MouseButton::WheelUp, 4, 4, Modifiers::empty()),
+            next_key(&mut keyboard)
+        );
+        assert_eq!(
+            MouseRelease(MouseButton::Left, 4, 4, Modifiers::empty()),
+            next_key(&mut keyboard)
+        );
+    }
+
+    #[test]
+    fn released_button() {
+        // SGR tells the button released, X10 only that one was: the one held
+        let (mut keyboard, _tx) = keyboard_with_input(b"\x1b[<2;3;4m\x1b[M\"$$\x1b[M#$$\x1b[M#$$");
+        let release = |button| MouseRelease(button, 3, 4, Modifiers::empty());
+        assert_eq!(release(MouseButton::Right), next_key(&mut keyboard));
+        assert_eq!(
+            MousePress(MouseButton::Right, 4, 4, Modifiers::empty()),
+            next_key(&mut keyboard)
+        );
+        assert_eq!(
+            MouseRelease(MouseButton::Right, 4, 4, Modifiers::empty()),
+            next_key(&mut keyboard)
+        );
+        assert_eq!(
+            MouseRelease(MouseButton::Left, 4, 4, Modifiers::empty()),
+            next_key(&mut keyboard)
+        );
+    }
+
+    #[test]
+    fn cursor_reports() {
+        // the keys typed before the reply are kept, `;` and `R` typed after it are keys
+        let (mut keyboard, _tx) = keyboard_with_input(b"a\x1b[1;5A\x1b[1;2R;R\x1b[1;2R");
+        assert_eq!(
+            Ok((0, 1)),
+            keyboard.cursor_pos_until(Deadline::Poll).map_err(|_| ())
+        );
+        assert_eq!(Char('a'), next_key(&mut keyboard));
+        assert_eq!(CtrlUp, next_key(&mut keyboard));
+        assert_eq!(Char(';'), next_key(&mut keyboard));
+        assert_eq!(Char('R'), next_key(&mut keyboard));
+        // no query pending: Shift+F3
+        assert_eq!(F(15), next_key(&mut keyboard));
+
+        // a late reply is still one, elsewhere than on the first row it's never a key
+        let (mut keyboard, mut tx) = keyboard_with_input(b"");
+        assert!(matches!(
+            keyboard.cursor_pos_until(Deadline::Poll),
+            Err(InputError::Timeout)
+        ));
+        tx.write_all(b"\x1b[1;5R\x1b[12;40R\x1b[1;5R").unwrap();
+        assert_eq!(CursorPos(0, 4), next_key(&mut keyboard));
+        assert_eq!(CursorPos(11, 39), next_key(&mut keyboard));
+        assert_eq!(F(3), next_key(&mut keyboard));
+    }
+
+    #[test]
+    fn split_cursor_report() {
+        let (keyboard, mut tx) = keyboard_with_input(b"\x1b[12;4");
+        let mut keyboard = keyboard.esc_timeout(Duration::from_secs(1));
+        let writer = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            tx.write_all(b"0R").unwrap();
+            tx
+        });
+        assert_eq!(
+            Ok((11, 39)),
+            keyboard
+                .cursor_pos_until(Deadline::after(Duration::from_secs(2)))
+                .map_err(|_| ())
+        );
+        let _tx = writer.join().unwrap();
+    }
+
+    #[test]
+    fn esc_timeout() {
+        // the rest of the sequence arrives late, e.g. over SSH
+        let split_up = |keyboard: KeyBoard, mut tx: File| {
+            let writer = thread::spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                tx.write_all(b"[A").unwrap();
+                tx
+            });
+            (keyboard, writer)
+        };
+
+        let (keyboard, tx) = keyboard_with_input(b"\x1b");
+        let (mut keyboard, writer) = split_up(keyboard, tx);
+        assert_eq!(ESC, next_key(&mut keyboard));
+        let _tx = writer.join().unwrap();
+        assert_eq!(Char('['), next_key(&mut keyboard));
+
+        let (keyboard, tx) = keyboard_with_input(b"\x1b");
+        let (mut keyboard, writer) = split_up(keyboard.esc_timeout(Duration::from_secs(1)), tx);
+        assert_eq!(Up, next_key(&mut keyboard));
+        let _tx = writer.join().unwrap();
+    }
+
+    #[test]
+    fn keypad_application_mode() {
+        let (mut keyboard, _tx) = keyboard_with_input(b"\x1bOq\x1bOy\x1bOn\x1bOk\x1bOM");
+        for key in [Char('1'), Char('9'), Char('.'), Char('+'), Enter] {
+            assert_eq!(key, next_key(&mut keyboard));
+        }
+    }
+
+    #[test]
+    fn function_keys_past_f12() {
+        // rxvt, xterm's Shift+F1 and Shift+F12, konsole's SS3 and Ctrl+F5 for comparison
+        let (mut keyboard, _tx) =
+            keyboard_with_input(b"\x1b[25~\x1b[34~\x1b[1;2P\x1b[24;2~\x1bO2Q\x1bO5P\x1b[15;5~");
+        for num in [13, 20, 13, 24, 14, 1, 5] {
+            assert_eq!(F(num), next_key(&mut keyboard));
+        }
+    }
+
+    #[test]
+    fn horizontal_wheel() {
+        let (mut keyboard, _tx) = keyboard_with_input(b"\x1b[<66;3;4M\x1b[<67;3;4M\x1b[99;3;4M");
+        let wheel = |button| MousePress(button, 3, 4, Modifiers::empty());
+        assert_eq!(wheel(MouseButton::WheelLeft), next_key(&mut keyboard));
+        assert_eq!(wheel(MouseButton::WheelRight), next_key(&mut keyboard));
+        assert_eq!(wheel(MouseButton::WheelRight), next_key(&mut keyboard));
+    }
+
+    #[test]
+    fn mouse_modifiers() {
+        // ctrl-click, shift-drag, then alt with X10 and urxvt
+        let (mut keyboard, _tx) =
+            keyboard_with_input(b"\x1b[<16;3;4M\x1b[<36;4;4M\x1b[M(#$\x1b[41;3;4M");
+        assert_eq!(
+            MousePress(MouseButton::Left, 3, 4, Modifiers::CTRL),
+            next_key(&mut keyboard)
+        );
+        assert_eq!(MouseHold(4, 4, Modifiers::SHIFT), next_key(&mut keyboard));
+        assert_eq!(
+            MousePress(MouseButton::Left, 3, 4, Modifiers::ALT),
+            next_key(&mut keyboard)
+        );
+        assert_eq!(
+            MousePress(MouseButton::Middle, 3, 4, Modifiers::ALT),
+            next_key(&mut keyboard)
+        );
+    }
+
+    #[test]
+    fn mouse_move() {
+        // no button held: 35 (+ 32 with X10 and urxvt)
+        let (mut keyboard, _tx) = keyboard_with_input(b"\x1b[<35;3;4M\x1b[MC%$\x1b[67;6;4M");
+        assert_eq!(MouseMove(3, 4, Modifiers::empty()), next_key(&mut keyboard));
+        assert_eq!(MouseMove(5, 4, Modifiers::empty()), next_key(&mut keyboard));
+        assert_eq!(MouseMove(6, 4, Modifiers::empty()), next_key(&mut keyboard));
+    }
+
+    #[test]
+    fn capability_replies() {
+        let (mut keyboard, _tx) = keyboard_with_input(
+            b"\x1b[?62;22c\x1b]11;rgb:2828/2c2c/3434\x07\x1b]11;rgb:f/f/f\x1b\\\x1b]a",
+        );
+        assert_eq!(DeviceAttributes(vec![62, 22]), next_key(&mut keyboard));
+        assert_eq!(BackgroundColor(0x28, 0x2c, 0x34), next_key(&mut keyboard));
+        assert_eq!(BackgroundColor(255, 255, 255), next_key(&mut keyboard));
+        // not an OSC reply
+        assert_eq!(Alt(']'), next_key(&mut keyboard));
+    }
+}
@@ -1,9 +1,41 @@
 //! Defines all the keys `tuikit` recognizes.
 
+use bitflags::bitflags;
+use std::str::FromStr;
+
+/// The key named by a literal, as parsed by `Key::from_str`, e.g. `key!("ctrl-a")` or
+/// `key!("Alt+Shift+Up")`. The name is parsed once per call site, an unknown name panics the
+/// first time it is evaluated. Use it in match guards:
+///
+/// ```
+/// use tuikit::key;
+/// use tuikit::key::Key;
+///
+/// let pressed = Key::AltShiftLeft;
+/// let action = match pressed {
+///     k if k == key!("alt-shift-left") => "shrink",
+///     k if k == key!("Alt+Shift+Right") => "grow",
+///     _ => "none",
+/// };
+/// assert_eq!("shrink", action);
+/// ```
+#[macro_export]
+macro_rules! key {
+    ($name:literal) => {{
+        static KEY: ::std::sync::OnceLock<$crate::key::Key> = ::std::sync::OnceLock::new();
+        KEY.get_or_init(|| match $name.parse() {
+            Ok(key) => key,
+            Err(err) => panic!("key!: {}", err),
+        })
+        .clone()
+    }};
+}
+
 // http://ascii-table.com/ansi-escape-sequences.php
 /// Single key
 #[rustfmt::skip]
-#[derive(Eq, PartialEq, Hash, Debug)]
+#[derive(Eq, PartialEq, Hash, Debug, Clone)]
+#[allow(clippy::manual_non_exhaustive)]
 pub enum Key {
     Null,
     ESC,
@@ -23,15 +55,24 @@ pub enum Key {
     F(u8),
 
     CtrlAlt(char), // chars are lower case
+    CtrlShift(char), // chars are lower case, only with kitty keyboard or modifyOtherKeys
     AltEnter,
     AltBackspace,
     AltTab,
     Alt(char), // chars could be lower or upper case
     Char(char), // chars are lower case
+    BracketedPaste(String), // the text pasted while bracketed paste is enabled
     CursorPos(u16, u16), // row, col
-    MousePress(MouseButton, u16, u16),
-    MouseRelease(u16, u16),
-    MouseHold(u16, u16),
+    DeviceAttributes(Vec<u16>), // reply to the DA1 query
+    BackgroundColor(u8, u8, u8), // reply to the OSC 11 query
+    TermcapReply(Vec<(String, Option<String>)>), // reply to XTGETTCAP, None if unknown
+    KeyboardFlags(u8), // reply to the kitty keyboard query (CSI ? u)
+    FocusGained, FocusLost, // the terminal window got or lost the focus (mode 1004)
+    MousePress(MouseButton, u16, u16, Modifiers),
+    MouseRelease(MouseButton, u16, u16, Modifiers),
+    MouseHold(u16, u16, Modifiers),
+    MouseMove(u16, u16, Modifiers), // no button held, see Output::enable_mouse_motion_support
+    DoubleClick(u16, u16), TripleClick(u16, u16), // left presses, see KeyBoard::click_interval
 
     #[doc(hidden)]
     __Nonexhaustive,
@@ -55,6 +96,24 @@ pub enum MouseButton {
     ///
     /// This event is typically only used with MousePress.
     WheelDown,
+    /// Mouse wheel is tilted left (horizontal scroll).
+    ///
+    /// This event is typically only used with MousePress.
+    WheelLeft,
+    /// Mouse wheel is tilted right (horizontal scroll).
+    ///
+    /// This event is typically only used with MousePress.
+    WheelRight,
+}
+
+bitflags! {
+    /// The modifier keys held during a mouse event, e.g. for ctrl-click or shift-drag. Some
+    /// terminals keep some combinations for themselves, e.g. shift to select text.
+    pub struct Modifiers: u8 {
+        const SHIFT = 0b001;
+        const ALT = 0b010;
+        const CTRL = 0b100;
+    }
 }
 
 #[rustfmt::skip]
@@ -116,11 +175,13 @@ pub fn from_keyname(keyname: &str) -> Option<Key> {
         "ctrl-alt-z" => Some(CtrlAlt('z')),
 
         "esc"                => Some(ESC),
+        "space"              => Some(Char(' ')),
         "btab" | "shift-tab" => Some(BackTab),
-        "bspace" | "bs"      => Some(Backspace),
-        "del"                => Some(Delete),
-        "pgup" | "page-up"   => Some(PageUp),
-        "pgdn" | "page-down" => Some(PageDown),
+        "bspace" | "bs" | "backspace" => Some(Backspace),
+        "del" | "delete"     => Some(Delete),
+        "ins" | "insert"     => Some(Insert),
+        "pgup" | "page-up" | "pageup"   => Some(PageUp),
+        "pgdn" | "page-down" | "pagedown" => Some(PageDown),
         "up"                 => Some(Up),
         "down"               => Some(Down),
         "left"               => Some(Left),
@@ -131,6 +192,10 @@ pub fn from_keyname(keyname: &str) -> Option<Key> {
         "shift-down"         => Some(ShiftDown),
         "shift-left"         => Some(ShiftLeft),
         "shift-right"        => Some(ShiftRight),
+        "ctrl-up"            => Some(CtrlUp),
+        "ctrl-down"          => Some(CtrlDown),
+        "ctrl-left"          => Some(CtrlLeft),
+        "ctrl-right"         => Some(CtrlRight),
 
         "f1"  => Some(F(1)),
         "f2"  => Some(F(2)),
@@ -144,6 +209,18 @@ pub fn from_keyname(keyname: &str) -> Option<Key> {
         "f10" => Some(F(10)),
         "f11" => Some(F(11)),
         "f12" => Some(F(12)),
+        "f13" => Some(F(13)),
+        "f14" => Some(F(14)),
+        "f15" => Some(F(15)),
+        "f16" => Some(F(16)),
+        "f17" => Some(F(17)),
+        "f18" => Some(F(18)),
+        "f19" => Some(F(19)),
+        "f20" => Some(F(20)),
+        "f21" => Some(F(21)),
+        "f22" => Some(F(22)),
+        "f23" => Some(F(23)),
+        "f24" => Some(F(24)),
 
         "alt-a" => Some(Alt('a')),
         "alt-b" => Some(Alt('b')),
@@ -201,9 +278,9 @@ pub fn from_keyname(keyname: &str) -> Option<Key> {
         "alt-shift-z" => Some(Alt('Z')),
 
         "alt-btab" | "alt-shift-tab" => Some(AltBackTab),
-        "alt-bspace" | "alt-bs"      => Some(AltBackspace),
-        "alt-pgup" | "alt-page-up"   => Some(AltPageUp),
-        "alt-pgdn" | "alt-page-down" => Some(AltPageDown),
+        "alt-bspace" | "alt-bs" | "alt-backspace" => Some(AltBackspace),
+        "alt-pgup" | "alt-page-up" | "alt-pageup"     => Some(AltPageUp),
+        "alt-pgdn" | "alt-page-down" | "alt-pagedown" => Some(AltPageDown),
         "alt-up"                     => Some(AltUp),
         "alt-down"                   => Some(AltDown),
         "alt-left"                   => Some(AltLeft),
@@ -220,6 +297,355 @@ pub fn from_keyname(keyname: &str) -> Option<Key> {
         ch if ch.chars().count() == 1 => {
             Some(Char(ch.chars().next().expect("input:parse_key: no key is specified")))
         },
-        _ => None,
+        name => from_generic_keyname(name),
+    }
+}
+
+/// keys that are not listed in `from_keyname`, e.g. `f20`, `alt-1`, `ctrl-alt-.`, `ctrl-,`
+fn from_generic_keyname(keyname: &str) -> Option<Key> {
+    fn single_char(s: &str) -> Option<char> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(ch), None) => Some(ch),
+            _ => None,
+        }
+    }
+
+    if let Some(ch) = keyname.strip_prefix("ctrl-alt-").and_then(single_char) {
+        Some(Key::CtrlAlt(ch))
+    } else if let Some(ch) = keyname.strip_prefix("ctrl-shift-").and_then(single_char) {
+        Some(Key::CtrlShift(ch))
+    } else if let Some(ch) = keyname.strip_prefix("ctrl-").and_then(single_char) {
+        Some(Key::Ctrl(ch))
+    } else if let Some(ch) = keyname.strip_prefix("alt-shift-").and_then(single_char) {
+        Some(Key::Alt(ch.to_ascii_uppercase()))
+    } else if let Some(ch) = keyname.strip_prefix("alt-").and_then(single_char) {
+        Some(Key::Alt(ch))
+    } else {
+        keyname
+            .strip_prefix('f')
+            .and_then(|num| num.parse::<u8>().ok())
+            .filter(|&num| num > 0)
+            .map(Key::F)
+    }
+}
+
+impl Key {
+    /// Human friendly name of the key, e.g. "Ctrl+Shift+Left", "F5", "Alt+Enter".
+    /// The name could be parsed back with `str::parse`.
+    ///
+    /// ```
+    /// use tuikit::key::Key;
+    ///
+    /// assert_eq!("Ctrl+A", Key::Ctrl('a').describe());
+    /// assert_eq!("Alt+Shift+Up", Key::AltShiftUp.describe());
+    /// assert_eq!(Ok(Key::AltShiftUp), "Alt+Shift+Up".parse());
+    /// ```
+    pub fn describe(&self) -> String {
+        use self::Key::*;
+
+        fn char_name(ch: char) -> String {
+            match ch {
+                ' ' => "Space".to_string(),
+                ch => ch.to_uppercase().to_string(),
+            }
+        }
+
+        match self {
+            ESC => "Esc".to_string(),
+            Ctrl(ch) => format!("Ctrl+{}", char_name(*ch)),
+            Tab => "Tab".to_string(),
+            Enter => "Enter".to_string(),
+            BackTab => "Shift+Tab".to_string(),
+            Backspace => "Backspace".to_string(),
+            AltBackTab => "Alt+Shift+Tab".to_string(),
+            Up => "Up".to_string(),
+            Down => "Down".to_string(),
+            Left => "Left".to_string(),
+            Right => "Right".to_string(),
+            Home => "Home".to_string(),
+            End => "End".to_string(),
+            Insert => "Insert".to_string(),
+            Delete => "Delete".to_string(),
+            PageUp => "PageUp".to_string(),
+            PageDown => "PageDown".to_string(),
+            CtrlUp => "Ctrl+Up".to_string(),
+            CtrlDown => "Ctrl+Down".to_string(),
+            CtrlLeft => "Ctrl+Left".to_string(),
+            CtrlRight => "Ctrl+Right".to_string(),
+            ShiftUp => "Shift+Up".to_string(),
+            ShiftDown => "Shift+Down".to_string(),
+            ShiftLeft => "Shift+Left".to_string(),
+            ShiftRight => "Shift+Right".to_string(),
+            AltUp => "Alt+Up".to_string(),
+            AltDown => "Alt+Down".to_string(),
+            AltLeft => "Alt+Left".to_string(),
+            AltRight => "Alt+Right".to_string(),
+            AltHome => "Alt+Home".to_string(),
+            AltEnd => "Alt+End".to_string(),
+            AltPageUp => "Alt+PageUp".to_string(),
+            AltPageDown => "Alt+PageDown".to_string(),
+            AltShiftUp => "Alt+Shift+Up".to_string(),
+            AltShiftDown => "Alt+Shift+Down".to_string(),
+            AltShiftLeft => "Alt+Shift+Left".to_string(),
+            AltShiftRight => "Alt+Shift+Right".to_string(),
+            F(num) => format!("F{}", num),
+            CtrlAlt(ch) => format!("Ctrl+Alt+{}", char_name(*ch)),
+            CtrlShift(ch) => format!("Ctrl+Shift+{}", char_name(*ch)),
+            AltEnter => "Alt+Enter".to_string(),
+            AltBackspace => "Alt+Backspace".to_string(),
+            AltTab => "Alt+Tab".to_string(),
+            Alt(ch) if ch.is_uppercase() => format!("Alt+Shift+{}", ch),
+            Alt(ch) => format!("Alt+{}", char_name(*ch)),
+            Char(' ') => "Space".to_string(),
+            Char(ch) => ch.to_string(),
+            key => format!("{:?}", key),
+        }
+    }
+
+    /// whether the key types a character, i.e. `Key::Char`
+    pub fn is_char(&self) -> bool {
+        matches!(self, Key::Char(_))
+    }
+
+    /// The character of the key, with or without modifiers, e.g. `a` for `Ctrl('a')`
+    ///
+    /// ```
+    /// use tuikit::key::Key;
+    ///
+    /// assert_eq!(Some('a'), Key::Ctrl('a').char_value());
+    /// assert_eq!(Some('A'), Key::Alt('A').char_value());
+    /// assert_eq!(None, Key::Enter.char_value());
+    /// ```
+    pub fn char_value(&self) -> Option<char> {
+        match *self {
+            Key::Char(ch)
+            | Key::Ctrl(ch)
+            | Key::Alt(ch)
+            | Key::CtrlAlt(ch)
+            | Key::CtrlShift(ch) => Some(ch),
+            _ => None,
+        }
+    }
+
+    pub fn has_ctrl(&self) -> bool {
+        use self::Key::*;
+        matches!(
+            self,
+            Ctrl(_) | CtrlAlt(_) | CtrlShift(_) | CtrlUp | CtrlDown | CtrlLeft | CtrlRight
+        )
+    }
+
+    pub fn has_alt(&self) -> bool {
+        use self::Key::*;
+        matches!(
+            self,
+            Alt(_)
+                | CtrlAlt(_)
+                | AltBackTab
+                | AltUp
+                | AltDown
+                | AltLeft
+                | AltRight
+                | AltHome
+                | AltEnd
+                | AltPageUp
+                | AltPageDown
+                | AltShiftUp
+                | AltShiftDown
+                | AltShiftLeft
+                | AltShiftRight
+                | AltEnter
+                | AltBackspace
+                | AltTab
+        )
+    }
+
+    /// whether shift is held, `Alt('A')` is `Alt+Shift+A` but `Char('A')` is just `A`
+    pub fn has_shift(&self) -> bool {
+        use self::Key::*;
+        match self {
+            Alt(ch) => ch.is_uppercase(),
+            key => matches!(
+                key,
+                CtrlShift(_)
+                    | BackTab
+                    | AltBackTab
+                    | ShiftUp
+                    | ShiftDown
+                    | ShiftLeft
+                    | ShiftRight
+                    | AltShiftUp
+                    | AltShiftDown
+                    | AltShiftLeft
+                    | AltShiftRight
+            ),
+        }
+    }
+
+    /// The modifiers held, e.g. `CTRL | ALT` for `CtrlAlt('a')` or those of a mouse event
+    ///
+    /// ```
+    /// use tuikit::key::{Key, Modifiers};
+    ///
+    /// assert_eq!(Modifiers::CTRL | Modifiers::ALT, Key::CtrlAlt('a').modifiers());
+    /// assert_eq!(Modifiers::empty(), Key::Char('A').modifiers());
+    /// ```
+    pub fn modifiers(&self) -> Modifiers {
+        match *self {
+            Key::MousePress(_, _, _, modifiers)
+            | Key::MouseRelease(_, _, _, modifiers)
+            | Key::MouseHold(_, _, modifiers)
+            | Key::MouseMove(_, _, modifiers) => modifiers,
+            _ => {
+                let mut modifiers = Modifiers::empty();
+                modifiers.set(Modifiers::CTRL, self.has_ctrl());
+                modifiers.set(Modifiers::ALT, self.has_alt());
+                modifiers.set(Modifiers::SHIFT, self.has_shift());
+                modifiers
+            }
+        }
+    }
+
+    /// whether the key is a mouse event, see `event::MouseEvent`
+    pub fn is_mouse(&self) -> bool {
+        matches!(
+            self,
+            Key::MousePress(..)
+                | Key::MouseRelease(..)
+                | Key::MouseHold(..)
+                | Key::MouseMove(..)
+                | Key::DoubleClick(..)
+                | Key::TripleClick(..)
+        )
+    }
+}
+
+impl FromStr for Key {
+    type Err = String;
+
+    /// Parse key names such as "ctrl-a", "Ctrl+A" or "Alt+Shift+Up" (case insensitive except
+    /// for single characters)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.chars().count() == 1 {
+            return Ok(Key::Char(s.chars().next().unwrap()));
+        }
+
+        // "Alt++" means alt and the `+` key
+        let keyname = match s.strip_suffix("++") {
+            Some(prefix) => format!("{}-+", prefix.replace('+', "-")),
+            None => s.replace('+', "-"),
+        };
+
+        // `from_keyname` reads it as `Ctrl(' ')`, kept as is for the existing bindings
+        if keyname.eq_ignore_ascii_case("ctrl-alt-space") {
+            return Ok(Key::CtrlAlt(' '));
+        }
+
+        from_keyname(&keyname).ok_or_else(|| format!("unknown key: {}", s))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Key::*;
+    use super::*;
+
+    #[test]
+    fn described_key_should_be_parsed_back() {
+        let keys = vec![
+            ESC,
+            Ctrl(' '),
+            Ctrl('a'),
+            Tab,
+            Enter,
+            BackTab,
+            Backspace,
+            AltBackTab,
+            Up,
+            Down,
+            Left,
+            Right,
+            Home,
+            End,
+            Insert,
+            Delete,
+            PageUp,
+            PageDown,
+            CtrlUp,
+            CtrlDown,
+            CtrlLeft,
+            CtrlRight,
+            ShiftUp,
+            ShiftDown,
+            ShiftLeft,
+            ShiftRight,
+            AltUp,
+            AltDown,
+            AltLeft,
+            AltRight,
+            AltHome,
+            AltEnd,
+            AltPageUp,
+            AltPageDown,
+            AltShiftUp,
+            AltShiftDown,
+            AltShiftLeft,
+            AltShiftRight,
+            F(1),
+            F(12),
+            F(20),
+            CtrlAlt(' '),
+            CtrlAlt('x'),
+            CtrlShift('p'),
+            Ctrl(','),
+            AltEnter,
+            AltBackspace,
+            AltTab,
+            Alt('a'),
+            Alt('A'),
+            Alt('1'),
+            Alt('+'),
+            Char(' '),
+            Char('a'),
+            Char('A'),
+            Char('+'),
+        ];
+
+        for key in keys {
+            assert_eq!(Ok(&key), key.describe().parse::<Key>().as_ref());
+        }
+    }
+
+    #[test]
+    fn parse_key_names() {
+        assert_eq!(Ok(Ctrl('a')), "ctrl-a".parse());
+        assert_eq!(Ok(F(5)), "F5".parse());
+        assert_eq!(Ok(AltEnter), "Alt+Enter".parse());
+        assert_eq!(Ok(Alt('+')), "Alt++".parse());
+        assert!("Ctrl+Shift+Foo".parse::<Key>().is_err());
+        assert!("F0".parse::<Key>().is_err());
+        assert_eq!(Ok(CtrlAlt(' ')), "Ctrl+Alt+Space".parse());
+        assert_eq!(Some(Ctrl(' ')), from_keyname("ctrl-alt-space"));
+    }
+
+    #[test]
+    fn predicates() {
+        assert_eq!(Ctrl('a'), key!("ctrl-a"));
+        for _ in 0..2 {
+            assert_eq!(AltShiftLeft, key!("Alt+Shift+Left"));
+        }
+
+        assert!(Char('a').is_char());
+        assert!(!Alt('a').is_char());
+        assert_eq!(Some('x'), CtrlAlt('x').char_value());
+        assert!(CtrlAlt('x').has_ctrl() && CtrlAlt('x').has_alt());
+        assert!(!CtrlAlt('x').has_shift());
+        assert!(Alt('A').has_shift() && !Alt('a').has_shift());
+        assert!(CtrlShift('p').has_ctrl() && CtrlShift('p').has_shift());
+        assert!(AltShiftUp.has_alt() && AltShiftUp.has_shift() && !AltShiftUp.has_ctrl());
+        assert!(!Char('A').has_shift());
+        assert!(MouseHold(1, 1, Modifiers::empty()).is_mouse() && !Enter.is_mouse());
+        assert!(MouseMove(1, 1, Modifiers::empty()).is_mouse());
     }
 }
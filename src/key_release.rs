@@ -0,0 +1,116 @@
+//! Synthesize key releases from the auto-repeat of ordinary terminals.
+//!
+//! Terminals only report presses, but holding a key makes the terminal repeat it at a steady
+//! rate. Once the repeats stop for a quiet period the key is considered released. Before the
+//! first repeat the terminal waits for its repeat delay, so a single press is released after
+//! `INITIAL_REPEAT_DELAY` plus the quiet period.
+
+use crate::key::Key;
+use std::time::{Duration, Instant};
+
+/// the usual auto-repeat delay of terminals/keyboards
+const INITIAL_REPEAT_DELAY: Duration = Duration::from_millis(500);
+
+pub(crate) struct KeyReleaseDetector {
+    quiet: Duration,
+    /// the key held, when it was last seen and whether it has been repeated
+    held: Option<(Key, Instant, bool)>,
+}
+
+impl KeyReleaseDetector {
+    pub(crate) fn new(quiet: Duration) -> Self {
+        Self { quiet, held: None }
+    }
+
+    /// Record a key press, return the key implicitly released by pressing another one
+    pub(crate) fn on_key(&mut self, key: &Key, now: Instant) -> Option<Key> {
+        if let Some((held, seen_at, repeated)) = self.held.as_mut() {
+            if held == key {
+                *seen_at = now;
+                *repeated = true;
+                return None;
+            }
+        }
+
+        let released = self.held.take().map(|(held, _, _)| held);
+        if is_releasable(key) {
+            self.held = Some((key.clone(), now, false));
+        }
+        released
+    }
+
+    /// when the held key would be released if no other key arrives
+    pub(crate) fn release_at(&self) -> Option<Instant> {
+        self.held.as_ref().map(|(_, seen_at, repeated)| {
+            if *repeated {
+                *seen_at + self.quiet
+            } else {
+                *seen_at + INITIAL_REPEAT_DELAY + self.quiet
+            }
+        })
+    }
+
+    /// Release the held key if its quiet period is over
+    pub(crate) fn expire(&mut self, now: Instant) -> Option<Key> {
+        if self
+            .release_at()
+            .is_some_and(|release_at| release_at <= now)
+        {
+            self.held.take().map(|(held, _, _)| held)
+        } else {
+            None
+        }
+    }
+}
+
+/// mouse events, pastes, focus changes and replies to queries are not held
+fn is_releasable(key: &Key) -> bool {
+    !key.is_mouse()
+        && !matches!(
+            key,
+            Key::CursorPos(..)
+                | Key::DeviceAttributes(_)
+                | Key::BackgroundColor(..)
+                | Key::TermcapReply(_)
+                | Key::KeyboardFlags(_)
+                | Key::BracketedPaste(_)
+                | Key::FocusGained
+                | Key::FocusLost
+        )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn released_after_quiet_period() {
+        let quiet = Duration::from_millis(100);
+        let start = Instant::now();
+        let mut detector = KeyReleaseDetector::new(quiet);
+        assert_eq!(None, detector.release_at());
+
+        assert_eq!(None, detector.on_key(&Key::Down, start));
+        assert_eq!(
+            Some(start + INITIAL_REPEAT_DELAY + quiet),
+            detector.release_at()
+        );
+
+        let repeat = start + INITIAL_REPEAT_DELAY;
+        assert_eq!(None, detector.on_key(&Key::Down, repeat));
+        assert_eq!(Some(repeat + quiet), detector.release_at());
+        assert_eq!(None, detector.expire(repeat + quiet / 2));
+        assert_eq!(Some(Key::Down), detector.expire(repeat + quiet));
+        assert_eq!(None, detector.release_at());
+    }
+
+    #[test]
+    fn another_key_releases_the_held_one() {
+        let now = Instant::now();
+        let mut detector = KeyReleaseDetector::new(Duration::from_millis(100));
+        detector.on_key(&Key::Down, now);
+        assert_eq!(Some(Key::Down), detector.on_key(&Key::Up, now));
+        assert_eq!(Some(Key::Up), detector.on_key(&Key::CursorPos(0, 0), now));
+        assert_eq!(None, detector.release_at());
+    }
+}
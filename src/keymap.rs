@@ -0,0 +1,238 @@
+//! Multi-key bindings, like `g g` in vim or `Ctrl-x Ctrl-s` in emacs.
+//!
+//! `Keymap` matches the keys against the bound sequences. While the keys typed so far are only
+//! the prefix of a binding, they are pending: `pending_text` shows them in a status bar (like
+//! vim's `showcmd`) and the result of each key tells when they resolve to an action, turn out
+//! to be unbound, or time out. When a binding is also the prefix of a longer one (`g` and
+//! `g g`), the shorter one is only resolved on timeout.
+//!
+//! ```
+//! use std::time::{Duration, Instant};
+//! use tuikit::key::Key;
+//! use tuikit::keymap::{Keymap, KeymapEvent};
+//!
+//! let mut keymap = Keymap::new().timeout(Duration::from_millis(500));
+//! keymap.bind("g g", "top").unwrap();
+//! keymap.bind("Ctrl-x Ctrl-s", "save").unwrap();
+//!
+//! assert_eq!(KeymapEvent::Pending, keymap.on_key(Key::Ctrl('x')));
+//! assert_eq!("Ctrl+X", keymap.pending_text());
+//! assert_eq!(KeymapEvent::Resolved("save"), keymap.on_key(Key::Ctrl('s')));
+//!
+//! keymap.on_key(Key::Char('g'));
+//! let later = Instant::now() + Duration::from_secs(1);
+//! assert_eq!(
+//!     Some(KeymapEvent::TimedOut(vec![Key::Char('g')])),
+//!     keymap.check_timeout(later)
+//! );
+//! ```
+//!
+//! The keymap doesn't wait by itself: poll the term with a timeout until `deadline` and call
+//! `check_timeout` when it returns.
+
+use crate::key::Key;
+use std::time::{Duration, Instant};
+
+/// vim's default `timeoutlen`
+const DEFAULT_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// What the keys typed so far amount to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeymapEvent<A> {
+    /// the keys are the prefix of a binding, more are expected, see `Keymap::pending`
+    Pending,
+    /// the keys of a binding were typed
+    Resolved(A),
+    /// the keys are bound to nothing, they are handed back to the application
+    Unbound(Vec<Key>),
+    /// the pending keys timed out without completing a binding, they are handed back
+    TimedOut(Vec<Key>),
+}
+
+/// Key sequences bound to actions of type `A`
+#[derive(Debug, Clone)]
+pub struct Keymap<A> {
+    bindings: Vec<(Vec<Key>, A)>,
+    pending: Vec<Key>,
+    pending_since: Option<Instant>,
+    timeout: Duration,
+}
+
+impl<A: Clone> Default for Keymap<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: Clone> Keymap<A> {
+    pub fn new() -> Self {
+        Self {
+            bindings: Vec::new(),
+            pending: Vec::new(),
+            pending_since: None,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// how long the keys may be pending, one second by default
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Bind the keys named in `keys`, separated by spaces (e.g. `"Ctrl-x Ctrl-s"`, see the
+    /// key names of `Key::from_str`), to `action`
+    pub fn bind(&mut self, keys: &str, action: A) -> Result<(), String> {
+        let keys = keys
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<Key>, String>>()?;
+        if keys.is_empty() {
+            return Err("no key to bind".to_string());
+        }
+        self.bind_keys(keys, action);
+        Ok(())
+    }
+
+    /// bind `keys` to `action`, replacing their previous binding if any
+    pub fn bind_keys(&mut self, keys: Vec<Key>, action: A) {
+        self.unbind(&keys);
+        self.bindings.push((keys, action));
+    }
+
+    /// remove the binding of `keys`, return `false` if there is none
+    pub fn unbind(&mut self, keys: &[Key]) -> bool {
+        let count = self.bindings.len();
+        self.bindings.retain(|(bound, _)| bound != keys);
+        self.bindings.len() != count
+    }
+
+    /// the keys typed so far of an incomplete binding
+    pub fn pending(&self) -> &[Key] {
+        &self.pending
+    }
+
+    /// The pending keys for display, e.g. `"Ctrl+X g"`, empty if none
+    pub fn pending_text(&self) -> String {
+        self.pending
+            .iter()
+            .map(Key::describe)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// when the pending keys time out, `None` if none is pending
+    pub fn deadline(&self) -> Option<Instant> {
+        self.pending_since.map(|since| since + self.timeout)
+    }
+
+    /// drop the pending keys, e.g. on `Esc`
+    pub fn cancel(&mut self) -> Vec<Key> {
+        self.pending_since = None;
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Feed `key`, typed now
+    pub fn on_key(&mut self, key: Key) -> KeymapEvent<A> {
+        self.on_key_at(key, Instant::now())
+    }
+
+    /// Feed `key`, typed at `now`. The pending keys timed out by then are resolved first, the
+    /// result of `check_timeout` is lost: call it before when it matters.
+    pub fn on_key_at(&mut self, key: Key, now: Instant) -> KeymapEvent<A> {
+        let _ = self.check_timeout(now);
+        self.pending.push(key);
+
+        let longer = self
+            .bindings
+            .iter()
+            .any(|(keys, _)| keys.len() > self.pending.len() && keys.starts_with(&self.pending));
+        if longer {
+            self.pending_since = Some(now);
+            return KeymapEvent::Pending;
+        }
+
+        match self.action(&self.pending) {
+            Some(action) => {
+                self.cancel();
+                KeymapEvent::Resolved(action)
+            }
+            None => KeymapEvent::Unbound(self.cancel()),
+        }
+    }
+
+    /// Resolve the pending keys if they timed out at `now`: to the binding they complete if any
+    /// (`g` while `g g` is bound too), or hand them back. `None` if nothing timed out.
+    pub fn check_timeout(&mut self, now: Instant) -> Option<KeymapEvent<A>> {
+        if self.deadline()? > now {
+            return None;
+        }
+        let event = match self.action(&self.pending) {
+            Some(action) => KeymapEvent::Resolved(action),
+            None => KeymapEvent::TimedOut(self.pending.clone()),
+        };
+        self.cancel();
+        Some(event)
+    }
+
+    fn action(&self, keys: &[Key]) -> Option<A> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| bound == keys)
+            .map(|(_, action)| action.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn resolve_sequences() {
+        let mut keymap = Keymap::new();
+        keymap.bind("g", 1).unwrap();
+        keymap.bind("g g", 2).unwrap();
+        keymap.bind("d i w", 3).unwrap();
+        assert!(keymap.bind("d Foo", 4).is_err());
+
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+        assert_eq!(
+            KeymapEvent::Pending,
+            keymap.on_key_at(Key::Char('g'), at(0))
+        );
+        assert_eq!(
+            KeymapEvent::Resolved(2),
+            keymap.on_key_at(Key::Char('g'), at(10))
+        );
+        assert!(keymap.pending().is_empty());
+
+        // a prefix which is a binding too resolves on timeout
+        keymap.on_key_at(Key::Char('g'), at(20));
+        assert_eq!(None, keymap.check_timeout(at(500)));
+        assert_eq!(
+            Some(KeymapEvent::Resolved(1)),
+            keymap.check_timeout(at(1020))
+        );
+        assert_eq!(None, keymap.deadline());
+
+        keymap.on_key_at(Key::Char('d'), at(0));
+        keymap.on_key_at(Key::Char('i'), at(0));
+        assert_eq!("d i", keymap.pending_text());
+        assert_eq!(
+            KeymapEvent::Unbound(vec![Key::Char('d'), Key::Char('i'), Key::Char('x')]),
+            keymap.on_key_at(Key::Char('x'), at(0))
+        );
+        assert_eq!(
+            KeymapEvent::Unbound(vec![Key::Char('x')]),
+            keymap.on_key_at(Key::Char('x'), at(0))
+        );
+
+        assert!(keymap.unbind(&[Key::Char('g')]));
+        keymap.on_key_at(Key::Char('g'), at(0));
+        assert_eq!(
+            Some(KeymapEvent::TimedOut(vec![Key::Char('g')])),
+            keymap.check_timeout(at(1000))
+        );
+    }
+}
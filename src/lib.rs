@@ -41,12 +41,12 @@
 //!         let _ = term.print(0, 0, "press arrow key to move the text, (q) to quit");
 //!
 //!         let (width, height) = term.term_size().unwrap();
-//!         match ev {
-//!             Event::Key(Key::ESC) | Event::Key(Key::Char('q')) => break,
-//!             Event::Key(Key::Up) => row = max(row-1, 1),
-//!             Event::Key(Key::Down) => row = min(row+1, height-1),
-//!             Event::Key(Key::Left) => col = max(col, 1)-1,
-//!             Event::Key(Key::Right) => col = min(col+1, width-1),
+//!         match ev.key() {
+//!             Some(Key::ESC) | Some(Key::Char('q')) => break,
+//!             Some(Key::Up) => row = max(row-1, 1),
+//!             Some(Key::Down) => row = min(row+1, height-1),
+//!             Some(Key::Left) => col = max(col, 1)-1,
+//!             Some(Key::Right) => col = min(col+1, width-1),
 //!             _ => {}
 //!         }
 //!
@@ -57,19 +57,126 @@
 //!     }
 //! }
 //! ```
+//!
+//! ## no_std
+//!
+//! The cell buffer, the attributes and the diffing of `Screen` (modules `attr`, `canvas`,
+//! `cell`, `command`, `region` and `screen`) only need `alloc`. Without the default `std`
+//! feature only they are built, e.g. to drive a memory-mapped text framebuffer by executing
+//! the `Command`s returned by `Screen::present`:
+//!
+//! ```toml
+//! [dependencies]
+//! tuikit = { version = "*", default-features = false }
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod accessibility;
+#[cfg(feature = "std")]
+pub mod animation;
+#[cfg(feature = "std")]
+pub mod apps;
+#[cfg(feature = "async")]
+pub mod async_input;
 pub mod attr;
 pub mod canvas;
+#[cfg(feature = "std")]
+pub mod capability;
 pub mod cell;
+#[cfg(feature = "std")]
+pub mod clipboard;
 mod color;
+pub mod command;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "std")]
 pub mod container;
+#[cfg(feature = "std")]
+pub mod control;
+#[cfg(feature = "std")]
 pub mod draw;
+#[cfg(feature = "std")]
+pub mod draw_handle;
+#[cfg(feature = "std")]
 pub mod event;
+#[cfg(feature = "std")]
+pub mod event_log;
+#[cfg(feature = "std")]
+pub mod focus;
+#[cfg(feature = "std")]
+pub mod frame_hook;
+#[cfg(feature = "fs-watch")]
+pub mod fs_watch;
+#[cfg(feature = "std")]
+pub mod grab;
+#[cfg(feature = "std")]
+pub mod highlight;
+#[cfg(feature = "std")]
+pub mod hints;
+#[cfg(feature = "std")]
 pub mod input;
+#[cfg(feature = "std")]
 pub mod key;
+#[cfg(feature = "std")]
+mod key_release;
+#[cfg(feature = "std")]
+pub mod keymap;
+#[cfg(feature = "std")]
+pub mod lifecycle;
+#[cfg(feature = "std")]
+pub mod locale;
+#[cfg(feature = "std")]
+pub mod markdown;
+#[cfg(feature = "std")]
+pub mod momentum;
+#[cfg(feature = "std")]
 pub mod output;
+#[cfg(feature = "std")]
+pub mod paste;
+#[cfg(feature = "std")]
+pub mod plugin;
+#[cfg(feature = "std")]
 pub mod prelude;
+#[cfg(feature = "std")]
 pub mod raw;
+pub mod region;
+#[cfg(feature = "std")]
+mod resize;
 pub mod screen;
+#[cfg(feature = "std")]
+pub mod selection;
+#[cfg(feature = "std")]
+pub mod session;
+#[cfg(feature = "std")]
+pub mod snapshot;
+#[cfg(feature = "std")]
 mod spinlock;
+#[cfg(feature = "std")]
+pub mod style;
+#[cfg(feature = "std")]
 mod sys;
+#[cfg(feature = "std")]
 pub mod term;
+#[cfg(feature = "static-terminfo")]
+mod terminfo;
+#[cfg(feature = "std")]
+pub mod testing;
+#[cfg(feature = "std")]
+pub mod text;
+#[cfg(feature = "std")]
+pub mod timeline;
+#[cfg(feature = "std")]
+pub mod timer;
+#[cfg(feature = "std")]
+pub mod undo;
+#[cfg(feature = "std")]
+pub mod widget;
+#[cfg(feature = "std")]
+pub mod widget_id;
+pub mod width;
+#[cfg(feature = "std")]
+pub mod word;
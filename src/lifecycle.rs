@@ -0,0 +1,241 @@
+//! Lifecycle hooks of the widgets: mount, unmount, focus, blur and resize.
+//!
+//! Widgets implementing `Lifecycle` could start and stop their background work (e.g. a file
+//! watcher in a directory pane) with their visibility. A widget is mounted while it registers
+//! a region of its name when drawn (see `tuikit::region`) and focused while it is focused in the
+//! `FocusRing`, so after each frame the `LifecycleTracker` compares the regions and the focus to
+//! the previous frame and returns what changed, for the application to dispatch:
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use tuikit::lifecycle::{Lifecycle, LifecycleTracker};
+//! use tuikit::region::Region;
+//!
+//! #[derive(Default)]
+//! struct DirectoryPane {
+//!     watching: bool,
+//! }
+//!
+//! impl Lifecycle for DirectoryPane {
+//!     fn on_mount(&mut self) {
+//!         self.watching = true;
+//!     }
+//!     fn on_unmount(&mut self) {
+//!         self.watching = false;
+//!     }
+//! }
+//!
+//! let mut widgets: HashMap<String, DirectoryPane> = HashMap::new();
+//! widgets.insert("files".to_string(), DirectoryPane::default());
+//! let mut tracker = LifecycleTracker::new();
+//!
+//! // after presenting a frame, with the regions from `Term::regions`
+//! let regions = vec![Region::new("files", 0, 0, 20, 10)];
+//! for event in tracker.update(&regions, Some("files")) {
+//!     if let Some(widget) = widgets.get_mut(event.name()) {
+//!         event.dispatch(widget);
+//!     }
+//! }
+//! assert!(widgets["files"].watching);
+//!
+//! // the pane is not drawn anymore
+//! for event in tracker.update(&[], None) {
+//!     if let Some(widget) = widgets.get_mut(event.name()) {
+//!         event.dispatch(widget);
+//!     }
+//! }
+//! assert!(!widgets["files"].watching);
+//! ```
+
+use crate::focus;
+use crate::region::Region;
+use std::collections::BTreeMap;
+
+/// The area of the screen taken by a widget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Rect {
+    pub row: usize,
+    pub col: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// The hooks of a widget, all no-ops by default
+pub trait Lifecycle {
+    /// the widget is drawn, for the first time or again
+    fn on_mount(&mut self) {}
+
+    /// the widget is no longer drawn
+    fn on_unmount(&mut self) {}
+
+    fn on_focus(&mut self) {}
+
+    fn on_blur(&mut self) {}
+
+    /// the widget was mounted at `rect`, or moved or resized to it
+    fn on_resize(&mut self, _rect: Rect) {}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LifecycleEvent {
+    Mount(String),
+    Unmount(String),
+    Focus(String),
+    Blur(String),
+    Resize(String, Rect),
+}
+
+impl LifecycleEvent {
+    /// the name of the widget
+    pub fn name(&self) -> &str {
+        match self {
+            LifecycleEvent::Mount(name)
+            | LifecycleEvent::Unmount(name)
+            | LifecycleEvent::Focus(name)
+            | LifecycleEvent::Blur(name)
+            | LifecycleEvent::Resize(name, _) => name,
+        }
+    }
+
+    /// call the hook of `widget` for this event
+    pub fn dispatch(&self, widget: &mut dyn Lifecycle) {
+        match self {
+            LifecycleEvent::Mount(_) => widget.on_mount(),
+            LifecycleEvent::Unmount(_) => widget.on_unmount(),
+            LifecycleEvent::Focus(_) => widget.on_focus(),
+            LifecycleEvent::Blur(_) => widget.on_blur(),
+            LifecycleEvent::Resize(_, rect) => widget.on_resize(*rect),
+        }
+    }
+}
+
+/// The widgets mounted and focused in the last frame
+#[derive(Debug, Default)]
+pub struct LifecycleTracker {
+    mounted: BTreeMap<String, Rect>,
+    focused: Option<String>,
+}
+
+impl LifecycleTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `name` was drawn in the last frame
+    pub fn is_mounted(&self, name: &str) -> bool {
+        self.mounted.contains_key(name)
+    }
+
+    /// Compare the `regions` registered while drawing the frame (those of the same name are
+    /// merged) and the `focused` widget to the previous frame. The events are in the order: blur,
+    /// unmount, mount, resize, focus, so a widget is never focused while unmounted.
+    pub fn update(&mut self, regions: &[Region], focused: Option<&str>) -> Vec<LifecycleEvent> {
+        let mut mounted = BTreeMap::new();
+        for region in regions {
+            if mounted.contains_key(&region.name) {
+                continue;
+            }
+            if let Some((top, left, bottom, right)) = focus::bounds(&region.name, regions) {
+                let rect = Rect {
+                    row: top,
+                    col: left,
+                    width: right - left,
+                    height: bottom - top,
+                };
+                mounted.insert(region.name.clone(), rect);
+            }
+        }
+        // only a mounted widget is focused
+        let focused = focused
+            .filter(|name| mounted.contains_key(*name))
+            .map(str::to_string);
+
+        let mut events = Vec::new();
+        if self.focused.is_some() && self.focused != focused {
+            events.extend(self.focused.take().map(LifecycleEvent::Blur));
+        }
+        for name in self.mounted.keys() {
+            if !mounted.contains_key(name) {
+                events.push(LifecycleEvent::Unmount(name.clone()));
+            }
+        }
+        for name in mounted.keys() {
+            if !self.mounted.contains_key(name) {
+                events.push(LifecycleEvent::Mount(name.clone()));
+            }
+        }
+        for (name, rect) in mounted.iter() {
+            if self.mounted.get(name) != Some(rect) {
+                events.push(LifecycleEvent::Resize(name.clone(), *rect));
+            }
+        }
+        if focused.is_some() && self.focused != focused {
+            events.extend(focused.clone().map(LifecycleEvent::Focus));
+        }
+
+        self.mounted = mounted;
+        self.focused = focused;
+        events
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use LifecycleEvent::*;
+
+    fn rect(row: usize, col: usize, width: usize, height: usize) -> Rect {
+        Rect {
+            row,
+            col,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn events() {
+        let mut tracker = LifecycleTracker::new();
+        let regions = vec![
+            Region::new("list", 0, 0, 10, 1),
+            Region::new("list", 1, 0, 10, 1),
+            Region::new("preview", 0, 10, 10, 2),
+        ];
+        assert_eq!(
+            vec![
+                Mount("list".to_string()),
+                Mount("preview".to_string()),
+                Resize("list".to_string(), rect(0, 0, 10, 2)),
+                Resize("preview".to_string(), rect(0, 10, 10, 2)),
+                Focus("list".to_string()),
+            ],
+            tracker.update(&regions, Some("list"))
+        );
+        assert!(tracker.update(&regions, Some("list")).is_empty());
+
+        // the preview is hidden and the list takes the whole width
+        let regions = vec![Region::new("list", 0, 0, 20, 2)];
+        assert_eq!(
+            vec![
+                Unmount("preview".to_string()),
+                Resize("list".to_string(), rect(0, 0, 20, 2)),
+            ],
+            tracker.update(&regions, Some("list"))
+        );
+        assert!(!tracker.is_mounted("preview"));
+
+        // focusing a widget which is not drawn blurs
+        assert_eq!(
+            vec![Blur("list".to_string())],
+            tracker.update(&regions, Some("preview"))
+        );
+        assert_eq!(
+            vec![Focus("list".to_string())],
+            tracker.update(&regions, Some("list"))
+        );
+        assert_eq!(
+            vec![Blur("list".to_string()), Unmount("list".to_string())],
+            tracker.update(&[], Some("list"))
+        );
+    }
+}
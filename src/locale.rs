@@ -0,0 +1,158 @@
+//! The built-in strings of the widgets, and the direction their text is laid out in.
+//!
+//! The widgets show a few strings of their own, e.g. the "N lines dropped" marker of a
+//! `LogPane` or the "loading..." line of a `CompletionMenu`. They get them from the global
+//! `Localizer` (set by `set_localizer`) as a `Message`, the ones it doesn't translate are shown
+//! in English. A right-to-left localizer also aligns the lines of the widgets (`LogPane`,
+//! `ListView`, `CompletionMenu`) on the right, the terminal orders the characters.
+//!
+//! ```
+//! use tuikit::locale::{self, Localizer, Message, TextDirection};
+//! use tuikit::prelude::*;
+//! use tuikit::screen::Screen;
+//! use tuikit::widget::LogPane;
+//!
+//! struct Hebrew;
+//!
+//! impl Localizer for Hebrew {
+//!     fn translate(&self, message: &Message) -> Option<String> {
+//!         match message {
+//!             Message::LinesDropped(count) => Some(format!("[{} שורות הושמטו]", count)),
+//!             _ => None,
+//!         }
+//!     }
+//!
+//!     fn direction(&self) -> TextDirection {
+//!         TextDirection::RightToLeft
+//!     }
+//! }
+//!
+//! locale::set_localizer(Hebrew);
+//! assert_eq!("[2 שורות הושמטו]", locale::text(&Message::LinesDropped(2)));
+//!
+//! let mut pane = LogPane::new().capacity(1);
+//! pane.extend(vec!["a".into(), "b".into()]);
+//! let mut screen = Screen::new(20, 2);
+//! pane.draw(&mut screen).unwrap();
+//! let last = screen.iter_cell().find(|&(row, col, _)| (row, col) == (1, 19));
+//! assert_eq!(Some('b'), last.map(|(_, _, cell)| cell.ch));
+//! ```
+
+use crate::width::display_width;
+use lazy_static::lazy_static;
+use std::sync::{Arc, RwLock};
+
+/// The strings shown by the widgets, with what they are about
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::manual_non_exhaustive)]
+pub enum Message {
+    /// the marker of a `LogPane` over the lines dropped
+    LinesDropped(usize),
+    /// a `CompletionMenu` waiting for its candidates
+    Loading,
+    /// the folded unchanged lines of a `DiffView`
+    UnchangedLines(usize),
+    /// an array of a `DataInspector`, by its number of items
+    ArrayItems(usize),
+    /// an object of a `DataInspector`, by its number of keys
+    ObjectKeys(usize),
+
+    #[doc(hidden)]
+    __Nonexhaustive,
+}
+
+impl Message {
+    /// the message as shown without a translation
+    pub fn english(&self) -> String {
+        match self {
+            Message::LinesDropped(1) => "[1 line dropped]".to_string(),
+            Message::LinesDropped(count) => format!("[{} lines dropped]", count),
+            Message::Loading => "loading...".to_string(),
+            Message::UnchangedLines(1) => "⋯ 1 unchanged line".to_string(),
+            Message::UnchangedLines(count) => format!("⋯ {} unchanged lines", count),
+            Message::ArrayItems(count) => format!("[{} items]", count),
+            Message::ObjectKeys(count) => format!("{{{} keys}}", count),
+            Message::__Nonexhaustive => String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextDirection {
+    #[default]
+    LeftToRight,
+    RightToLeft,
+}
+
+/// Translates the built-in strings, and tells the direction of the text
+pub trait Localizer: Send + Sync {
+    /// `message` translated, `None` to show it in English
+    fn translate(&self, message: &Message) -> Option<String>;
+
+    fn direction(&self) -> TextDirection {
+        TextDirection::LeftToRight
+    }
+}
+
+/// The default localizer: everything in English, left to right
+struct English;
+
+impl Localizer for English {
+    fn translate(&self, _message: &Message) -> Option<String> {
+        None
+    }
+}
+
+lazy_static! {
+    static ref LOCALIZER: RwLock<Arc<dyn Localizer>> = RwLock::new(Arc::new(English));
+}
+
+/// Replace the global localizer, used by all the widgets
+pub fn set_localizer(localizer: impl Localizer + 'static) {
+    *LOCALIZER
+        .write()
+        .expect("set_localizer: failed to get lock") = Arc::new(localizer);
+}
+
+/// The global localizer
+pub fn localizer() -> Arc<dyn Localizer> {
+    LOCALIZER
+        .read()
+        .expect("localizer: failed to get lock")
+        .clone()
+}
+
+/// `message` as shown by the widgets: translated, in English otherwise
+pub fn text(message: &Message) -> String {
+    localizer()
+        .translate(message)
+        .unwrap_or_else(|| message.english())
+}
+
+/// The direction of the text of the global localizer
+pub fn direction() -> TextDirection {
+    localizer().direction()
+}
+
+/// The column to print `text` at in a line of `width` columns: 0 left to right, against the
+/// right edge right to left
+pub fn align(text: &str, width: usize, direction: TextDirection) -> usize {
+    match direction {
+        TextDirection::LeftToRight => 0,
+        TextDirection::RightToLeft => width.saturating_sub(display_width(text)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn english_and_alignment() {
+        assert_eq!("[1 line dropped]", Message::LinesDropped(1).english());
+        assert_eq!("{3 keys}", Message::ObjectKeys(3).english());
+        assert_eq!(0, align("abc", 10, TextDirection::LeftToRight));
+        assert_eq!(7, align("abc", 10, TextDirection::RightToLeft));
+        assert_eq!(0, align("abcdef", 4, TextDirection::RightToLeft));
+    }
+}
@@ -0,0 +1,277 @@
+//! Render a safe subset of Markdown into `AttrText` lines.
+//!
+//! Supported: headings, `**strong**`, `*emphasis*`, `` `code` ``, fenced code blocks, lists,
+//! block quotes and links. Control characters in the input are dropped so that the rendered
+//! text could never emit escape sequences. Link targets are kept in `AttrText::links`.
+//!
+//! ```
+//! use tuikit::markdown::render;
+//!
+//! let lines = render("# Help\n\n- press `q` to [quit](https://example.com)");
+//! assert_eq!("Help", lines[0].text);
+//! assert_eq!("• press q to quit", lines[2].text);
+//! assert_eq!(Some("https://example.com"), lines[2].link_at(16));
+//! ```
+
+use crate::attr::{Attr, Color, Effect};
+use crate::text::AttrText;
+
+const TAB: &str = "    ";
+const CODE_INDENT: &str = "  ";
+const BULLET: &str = "• ";
+const QUOTE: &str = "│ ";
+
+/// The attributes used for the Markdown elements
+#[derive(Debug, Clone)]
+pub struct MarkdownStyle {
+    pub heading: Attr,
+    /// the attribute of level 1 headings, extending `heading`
+    pub title: Attr,
+    pub strong: Attr,
+    pub emphasis: Attr,
+    pub code: Attr,
+    pub link: Attr,
+    pub quote: Attr,
+    pub bullet: Attr,
+}
+
+impl Default for MarkdownStyle {
+    fn default() -> Self {
+        Self {
+            heading: Attr::from(Effect::BOLD),
+            title: Attr::from(Effect::UNDERLINE),
+            strong: Attr::from(Effect::BOLD),
+            emphasis: Attr::from(Effect::UNDERLINE),
+            code: Attr::from(Color::YELLOW),
+            link: Attr::from(Color::BLUE).effect(Effect::UNDERLINE),
+            quote: Attr::from(Color::LIGHT_BLACK),
+            bullet: Attr::from(Color::CYAN),
+        }
+    }
+}
+
+/// render `markdown` with the default style
+pub fn render(markdown: &str) -> Vec<AttrText> {
+    render_with_style(markdown, &MarkdownStyle::default())
+}
+
+pub fn render_with_style(markdown: &str, style: &MarkdownStyle) -> Vec<AttrText> {
+    let mut lines = Vec::new();
+    let mut in_code_block = false;
+
+    for line in markdown.lines() {
+        let line = sanitize(line);
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+
+        let mut text = AttrText::new();
+        if in_code_block {
+            text.push_str(CODE_INDENT, Attr::default());
+            text.push_str(&line, style.code);
+        } else if let Some((level, heading)) = parse_heading(trimmed) {
+            let attr = if level == 1 {
+                style.heading.extend(style.title)
+            } else {
+                style.heading
+            };
+            render_inline(&mut text, heading, attr, style);
+        } else if let Some(quoted) = trimmed.strip_prefix('>') {
+            text.push_str(QUOTE, style.quote);
+            render_inline(&mut text, quoted.trim_start(), style.quote, style);
+        } else if let Some((marker, item)) = parse_list_item(trimmed) {
+            let indent = &line[..line.len() - trimmed.len()];
+            text.push_str(indent, Attr::default());
+            text.push_str(&marker, style.bullet);
+            render_inline(&mut text, item, Attr::default(), style);
+        } else {
+            render_inline(&mut text, &line, Attr::default(), style);
+        }
+        lines.push(text);
+    }
+
+    lines
+}
+
+/// drop control characters, expand tabs
+fn sanitize(line: &str) -> String {
+    line.chars()
+        .filter(|ch| *ch == '\t' || !ch.is_control())
+        .collect::<String>()
+        .replace('\t', TAB)
+}
+
+fn parse_heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|ch| *ch == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &line[level..];
+    if rest.is_empty() {
+        Some((level, rest))
+    } else {
+        rest.strip_prefix(' ')
+            .map(|heading| (level, heading.trim_end_matches('#').trim()))
+    }
+}
+
+/// return the marker to display and the content of the list item
+fn parse_list_item(line: &str) -> Option<(String, &str)> {
+    for bullet in &["- ", "* ", "+ "] {
+        if let Some(item) = line.strip_prefix(bullet) {
+            return Some((BULLET.to_string(), item));
+        }
+    }
+
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    let rest = &line[digits..];
+    if digits > 0 && (rest.starts_with(". ") || rest.starts_with(") ")) {
+        Some((format!("{}. ", &line[..digits]), &rest[2..]))
+    } else {
+        None
+    }
+}
+
+/// render the inline elements (strong, emphasis, code, links) of `line` onto `text`
+fn render_inline(text: &mut AttrText, line: &str, base: Attr, style: &MarkdownStyle) {
+    let chars: Vec<char> = line.chars().collect();
+    let rest = |from: usize| chars[from..].iter().collect::<String>();
+
+    let mut strong = false;
+    let mut emphasis = false;
+    let mut plain = String::new();
+    let current = |strong: bool, emphasis: bool| {
+        let mut attr = base;
+        if strong {
+            attr = attr.extend(style.strong);
+        }
+        if emphasis {
+            attr = attr.extend(style.emphasis);
+        }
+        attr
+    };
+
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+        let next = chars.get(i + 1).copied();
+        let prev_is_word = i > 0 && chars[i - 1].is_alphanumeric();
+
+        match ch {
+            '\\' if next.is_some_and(|ch| ch.is_ascii_punctuation()) => {
+                plain.push(next.unwrap());
+                i += 2;
+                continue;
+            }
+            '`' => {
+                if let Some(len) = rest(i + 1).find('`') {
+                    text.push_str(&plain, current(strong, emphasis));
+                    plain.clear();
+                    let code = rest(i + 1)[..len].to_string();
+                    text.push_str(&code, base.extend(style.code));
+                    i += code.chars().count() + 2;
+                    continue;
+                }
+            }
+            '*' | '_' if next == Some(ch) => {
+                let marker: String = [ch, ch].iter().collect();
+                if strong || rest(i + 2).contains(&marker) {
+                    text.push_str(&plain, current(strong, emphasis));
+                    plain.clear();
+                    strong = !strong;
+                    i += 2;
+                    continue;
+                }
+            }
+            '*' | '_' => {
+                // `_` only counts at word boundaries, e.g. not in `snake_case`
+                let next_is_word = next.is_some_and(char::is_alphanumeric);
+                let opens = !emphasis && (ch == '*' || !prev_is_word) && rest(i + 1).contains(ch);
+                let closes = emphasis && (ch == '*' || !next_is_word);
+                if opens || closes {
+                    text.push_str(&plain, current(strong, emphasis));
+                    plain.clear();
+                    emphasis = !emphasis;
+                    i += 1;
+                    continue;
+                }
+            }
+            '[' => {
+                let remaining = rest(i + 1);
+                if let Some(text_end) = remaining.find("](") {
+                    if let Some(url_len) = remaining[text_end + 2..].find(')') {
+                        text.push_str(&plain, current(strong, emphasis));
+                        plain.clear();
+                        let label = &remaining[..text_end];
+                        let url = &remaining[text_end + 2..text_end + 2 + url_len];
+                        text.push_link(label, url, current(strong, emphasis).extend(style.link));
+                        i += 1 + remaining[..text_end + 3 + url_len].chars().count();
+                        continue;
+                    }
+                }
+            }
+            '<' => {
+                let remaining = rest(i + 1);
+                if remaining.starts_with("http://") || remaining.starts_with("https://") {
+                    if let Some(end) = remaining.find('>') {
+                        text.push_str(&plain, current(strong, emphasis));
+                        plain.clear();
+                        let url = &remaining[..end];
+                        text.push_link(url, url, current(strong, emphasis).extend(style.link));
+                        i += url.chars().count() + 2;
+                        continue;
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        plain.push(ch);
+        i += 1;
+    }
+
+    text.push_str(&plain, current(strong, emphasis));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::highlight::Span;
+
+    #[test]
+    fn inline_elements() {
+        let style = MarkdownStyle::default();
+        let lines = render("a **b** *c* `d` snake_case_name \\*e\\* _f_");
+        let line = &lines[0];
+        assert_eq!("a b c d snake_case_name *e* f", line.text);
+        assert_eq!(
+            vec![
+                Span::new(2..3, style.strong),
+                Span::new(4..5, style.emphasis),
+                Span::new(6..7, style.code),
+                Span::new(28..29, style.emphasis)
+            ],
+            line.spans
+        );
+    }
+
+    #[test]
+    fn block_elements() {
+        let lines = render("## Title ##\n> quote\n  1. first\n```\nlet a = `b`;\n```\n");
+        let texts: Vec<&str> = lines.iter().map(|line| line.text.as_str()).collect();
+        assert_eq!(
+            vec!["Title", "│ quote", "  1. first", "  let a = `b`;"],
+            texts
+        );
+    }
+
+    #[test]
+    fn control_chars_should_be_dropped() {
+        let lines = render("\x1b]0;pwned\x07title\tend <https://a.b>");
+        assert_eq!("]0;pwnedtitle    end https://a.b", lines[0].text);
+        assert_eq!(Some("https://a.b"), lines[0].link_at(22));
+    }
+}
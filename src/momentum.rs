@@ -0,0 +1,178 @@
+//! Turn the discrete wheel notches into smooth momentum scrolling.
+//!
+//! A trackpad sends bursts of `WheelUp`/`WheelDown` events, scrolling 3 lines per event feels
+//! notchy. `MomentumScroll` converts every notch into an impulse that decays exponentially,
+//! the application asks for the lines to scroll on every frame:
+//!
+//! ```
+//! use std::time::{Duration, Instant};
+//! use tuikit::key::{Key, Modifiers, MouseButton};
+//! use tuikit::momentum::MomentumScroll;
+//!
+//! let mut momentum = MomentumScroll::new();
+//! assert!(momentum.on_key(&Key::MousePress(MouseButton::WheelDown, 1, 1, Modifiers::empty())));
+//!
+//! // e.g. on every `peek_event` timeout
+//! let lines = momentum.tick(Instant::now() + Duration::from_millis(16));
+//! assert!(lines >= 0);
+//! ```
+//!
+//! Every notch eventually scrolls exactly `lines_per_notch` lines. With animations disabled
+//! (see `animation::set_animation_enabled`) they are scrolled on the next `tick`.
+
+use crate::animation::animation_enabled;
+use crate::key::{Key, MouseButton};
+use std::time::{Duration, Instant};
+
+const DEFAULT_LINES_PER_NOTCH: f64 = 3.0;
+const DEFAULT_HALF_LIFE: Duration = Duration::from_millis(100);
+/// below this speed (lines/second) the rest of the distance is scrolled at once
+const MIN_VELOCITY: f64 = 2.0;
+
+pub struct MomentumScroll {
+    lines_per_notch: f64,
+    half_life: Duration,
+    /// lines per second, negative is up
+    velocity: f64,
+    /// the fraction of a line scrolled but not yet reported
+    remainder: f64,
+    last_tick: Option<Instant>,
+}
+
+impl Default for MomentumScroll {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Builder
+impl MomentumScroll {
+    pub fn new() -> Self {
+        Self {
+            lines_per_notch: DEFAULT_LINES_PER_NOTCH,
+            half_life: DEFAULT_HALF_LIFE,
+            velocity: 0.0,
+            remainder: 0.0,
+            last_tick: None,
+        }
+    }
+
+    /// the total lines scrolled by one wheel notch
+    pub fn lines_per_notch(mut self, lines: f64) -> Self {
+        self.lines_per_notch = lines;
+        self
+    }
+
+    /// how fast the scrolling slows down: the speed halves every `half_life`
+    pub fn half_life(mut self, half_life: Duration) -> Self {
+        self.half_life = half_life.max(Duration::from_millis(1));
+        self
+    }
+}
+
+impl MomentumScroll {
+    /// Feed a key, return `true` if it is a wheel event that was consumed
+    pub fn on_key(&mut self, key: &Key) -> bool {
+        self.on_key_at(key, Instant::now())
+    }
+
+    fn on_key_at(&mut self, key: &Key, now: Instant) -> bool {
+        let direction = match key {
+            Key::MousePress(MouseButton::WheelUp, ..) => -1.0,
+            Key::MousePress(MouseButton::WheelDown, ..) => 1.0,
+            _ => return false,
+        };
+
+        if !animation_enabled() {
+            self.remainder += direction * self.lines_per_notch;
+            return true;
+        }
+
+        if !self.is_running() {
+            self.last_tick = Some(now);
+        }
+        // the integral of `v * e^(-k * t)` is `v / k`, i.e. the notch's lines in total
+        self.velocity += direction * self.lines_per_notch * self.decay_rate();
+        true
+    }
+
+    /// Advance to `now`, return the whole lines to scroll, negative for up
+    pub fn tick(&mut self, now: Instant) -> i32 {
+        if let Some(last_tick) = self.last_tick {
+            let elapsed = now.saturating_duration_since(last_tick).as_secs_f64();
+            let k = self.decay_rate();
+            let kept = (-k * elapsed).exp();
+            self.remainder += self.velocity / k * (1.0 - kept);
+            self.velocity *= kept;
+            self.last_tick = Some(now);
+
+            if self.velocity.abs() < MIN_VELOCITY {
+                self.remainder += self.velocity / k;
+                self.remainder = self.remainder.round();
+                self.velocity = 0.0;
+                self.last_tick = None;
+            }
+        }
+
+        let lines = self.remainder.trunc();
+        self.remainder -= lines;
+        lines as i32
+    }
+
+    /// whether there is scrolling left, i.e. `tick` should be called again
+    pub fn is_running(&self) -> bool {
+        self.last_tick.is_some() || self.remainder.abs() >= 1.0
+    }
+
+    /// stop scrolling, e.g. when the user clicks
+    pub fn stop(&mut self) {
+        self.velocity = 0.0;
+        self.remainder = 0.0;
+        self.last_tick = None;
+    }
+
+    fn decay_rate(&self) -> f64 {
+        std::f64::consts::LN_2 / self.half_life.as_secs_f64()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::key::Modifiers;
+
+    #[test]
+    fn notches_scroll_exact_lines_smoothly() {
+        let start = Instant::now();
+        let mut momentum = MomentumScroll::new().lines_per_notch(3.0);
+        let wheel_down = Key::MousePress(MouseButton::WheelDown, 1, 1, Modifiers::empty());
+        assert!(!momentum.on_key_at(&Key::Char('j'), start));
+        assert!(momentum.on_key_at(&wheel_down, start));
+        assert!(momentum.on_key_at(&wheel_down, start));
+
+        let mut frames = Vec::new();
+        let mut now = start;
+        while momentum.is_running() {
+            now += Duration::from_millis(16);
+            frames.push(momentum.tick(now));
+        }
+        assert_eq!(6, frames.iter().sum::<i32>());
+        assert!(frames.len() > 3);
+        assert!(frames.iter().all(|&lines| lines <= 2));
+    }
+
+    #[test]
+    fn wheel_up_is_negative() {
+        let start = Instant::now();
+        let mut momentum = MomentumScroll::new();
+        momentum.on_key_at(
+            &Key::MousePress(MouseButton::WheelUp, 1, 1, Modifiers::empty()),
+            start,
+        );
+        let total: i32 = (1..100)
+            .map(|frame| momentum.tick(start + Duration::from_millis(frame * 16)))
+            .sum();
+        assert_eq!(-3, total);
+        assert!(!momentum.is_running());
+    }
+}
@@ -13,11 +13,13 @@
 //!
 //! ```
 
+use std::cmp::min;
 use std::io;
 use std::io::Write;
 use std::os::unix::io::AsRawFd;
 
 use crate::attr::{Attr, Color, Effect};
+pub use crate::command::Command;
 use crate::sys::size::terminal_size;
 
 use term::terminfo::parm::{expand, Param, Variables};
@@ -28,6 +30,129 @@ use term::terminfo::TermInfo;
 
 const DEFAULT_BUFFER_SIZE: usize = 1024;
 
+/// The xterm cursor movement capabilities, written without terminfo expansion when the
+/// terminfo has exactly these (expansion dominates `present` when scrolling heavily)
+const XTERM_CURSOR_CAPS: &[(&str, &[u8])] = &[
+    ("cup", b"\x1b[%i%p1%d;%p2%dH"),
+    ("cuu", b"\x1b[%p1%dA"),
+    ("cud", b"\x1b[%p1%dB"),
+    ("cuf", b"\x1b[%p1%dC"),
+    ("cub", b"\x1b[%p1%dD"),
+];
+
+/// How `Output::write_osc` ends the sequences
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OscTerminator {
+    /// `BEL`, understood by the most terminals
+    Bel,
+    /// `ESC \`, the standard one
+    St,
+}
+
+/// The line-drawing characters and the VT100 alternate characters they are drawn with
+#[rustfmt::skip]
+const ACS_CHARS: &[(char, u8)] = &[
+    ('─', b'q'), ('━', b'q'), ('═', b'q'),
+    ('│', b'x'), ('┃', b'x'), ('║', b'x'),
+    ('┌', b'l'), ('┏', b'l'), ('╔', b'l'), ('╭', b'l'),
+    ('┐', b'k'), ('┓', b'k'), ('╗', b'k'), ('╮', b'k'),
+    ('└', b'm'), ('┗', b'm'), ('╚', b'm'), ('╰', b'm'),
+    ('┘', b'j'), ('┛', b'j'), ('╝', b'j'), ('╯', b'j'),
+    ('├', b't'), ('┣', b't'), ('╠', b't'),
+    ('┤', b'u'), ('┫', b'u'), ('╣', b'u'),
+    ('┬', b'w'), ('┳', b'w'), ('╦', b'w'),
+    ('┴', b'v'), ('┻', b'v'), ('╩', b'v'),
+    ('┼', b'n'), ('╋', b'n'), ('╬', b'n'),
+    ('◆', b'`'), ('▒', b'a'), ('░', b'h'), ('█', b'0'),
+    ('°', b'f'), ('±', b'g'), ('·', b'~'),
+    ('≤', b'y'), ('≥', b'z'), ('π', b'{'), ('≠', b'|'), ('£', b'}'),
+    ('←', b','), ('→', b'+'), ('↓', b'.'), ('↑', b'-'),
+];
+
+/// What the alternate characters are written as without a mapping, as ncurses does
+#[rustfmt::skip]
+const ACS_ASCII: &[(u8, u8)] = &[
+    (b'q', b'-'), (b'x', b'|'),
+    (b'l', b'+'), (b'k', b'+'), (b'm', b'+'), (b'j', b'+'),
+    (b't', b'+'), (b'u', b'+'), (b'w', b'+'), (b'v', b'+'), (b'n', b'+'),
+    (b'`', b'+'), (b'a', b':'), (b'h', b'#'), (b'0', b'#'),
+    (b'f', b'\''), (b'g', b'#'), (b'~', b'o'),
+    (b'y', b'<'), (b'z', b'>'), (b'{', b'*'), (b'|', b'!'), (b'}', b'f'),
+    (b',', b'<'), (b'+', b'>'), (b'.', b'v'), (b'-', b'^'),
+];
+
+/// How the line-drawing characters (box borders, blocks, arrows) are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineDrawing {
+    /// as UTF-8
+    #[default]
+    Unicode,
+    /// Through the alternate character set of the terminfo (`smacs`/`rmacs` with the `acsc`
+    /// mapping), for the terminals or fonts without the Unicode box characters, e.g. the
+    /// linux console. The characters the terminal has no mapping for are written as ASCII.
+    Acs,
+}
+
+/// The alternate character set of the terminal
+#[derive(Debug, Clone, Default)]
+struct Acs {
+    enter: Vec<u8>,
+    exit: Vec<u8>,
+    /// the VT100 characters and those the terminal draws them with
+    chars: Vec<(u8, u8)>,
+}
+
+impl Acs {
+    fn from_terminfo(terminfo: &TermInfo) -> Self {
+        let cap = |name: &str| terminfo.strings.get(name).cloned();
+        match (cap("smacs"), cap("rmacs"), cap("acsc")) {
+            (Some(enter), Some(exit), Some(acsc)) => Self {
+                enter,
+                exit,
+                chars: acsc
+                    .chunks_exact(2)
+                    .map(|pair| (pair[0], pair[1]))
+                    .collect(),
+            },
+            // nothing is drawn with the alternate characters
+            _ => Self::default(),
+        }
+    }
+
+    /// append `text` with the line-drawing characters translated
+    fn write(&self, buffer: &mut Vec<u8>, text: &str) {
+        let mut alternate = false;
+        let mut utf8 = [0; 4];
+        for ch in text.chars() {
+            let vt100 = ACS_CHARS
+                .iter()
+                .find(|(line, _)| *line == ch)
+                .map(|(_, vt100)| *vt100);
+            let mapped = vt100.and_then(|vt100| {
+                self.chars
+                    .iter()
+                    .find(|(from, _)| *from == vt100)
+                    .map(|(_, to)| *to)
+            });
+            if mapped.is_some() != alternate {
+                buffer.extend(if alternate { &self.exit } else { &self.enter });
+                alternate = !alternate;
+            }
+            match (mapped, vt100) {
+                (Some(byte), _) => buffer.push(byte),
+                (None, Some(vt100)) => {
+                    let ascii = ACS_ASCII.iter().find(|(from, _)| *from == vt100);
+                    buffer.push(ascii.map_or(b'?', |(_, to)| *to));
+                }
+                (None, None) => buffer.extend(ch.encode_utf8(&mut utf8).as_bytes()),
+            }
+        }
+        if alternate {
+            buffer.extend(&self.exit);
+        }
+    }
+}
+
 /// Output is an abstraction over the ANSI codes.
 pub struct Output {
     /// A callable which returns the `Size` of the output terminal.
@@ -35,21 +160,106 @@ pub struct Output {
     stdout: Box<dyn WriteAndAsRawFdAndSend>,
     /// The terminal environment variable. (xterm, xterm-256color, linux, ...)
     terminfo: TermInfo,
+    /// the cursor movement capabilities are the xterm ones
+    xterm_cursor: bool,
+    osc_terminator: OscTerminator,
+    /// wrap the OSC/DCS sequences for tmux to pass them through
+    tmux_passthrough: bool,
+    /// the terminal went away (hangup, closed pty), nothing is written anymore
+    disconnected: bool,
+    /// the alternate character set, with `LineDrawing::Acs`
+    acs: Option<Acs>,
 }
 
 pub trait WriteAndAsRawFdAndSend: Write + AsRawFd + Send {}
 
 impl<T> WriteAndAsRawFdAndSend for T where T: Write + AsRawFd + Send {}
 
+/// Load the terminfo of `$TERM`, the built-in tables are preferred if enabled
+#[cfg(feature = "static-terminfo")]
+fn load_terminfo() -> io::Result<TermInfo> {
+    let builtin = std::env::var("TERM")
+        .ok()
+        .and_then(|name| crate::terminfo::builtin_terminfo(&name));
+    match builtin {
+        Some(terminfo) => Ok(terminfo),
+        None => Ok(TermInfo::from_env()?),
+    }
+}
+
+#[cfg(not(feature = "static-terminfo"))]
+fn load_terminfo() -> io::Result<TermInfo> {
+    Ok(TermInfo::from_env()?)
+}
+
 impl Output {
     pub fn new(stdout: Box<dyn WriteAndAsRawFdAndSend>) -> io::Result<Self> {
+        let terminfo = load_terminfo()?;
         Result::Ok(Self {
             buffer: Vec::with_capacity(DEFAULT_BUFFER_SIZE),
             stdout,
-            terminfo: TermInfo::from_env()?,
+            xterm_cursor: has_xterm_cursor(&terminfo),
+            terminfo,
+            osc_terminator: OscTerminator::Bel,
+            tmux_passthrough: std::env::var_os("TMUX").is_some(),
+            disconnected: false,
+            acs: None,
         })
     }
 
+    /// How the line-drawing characters are written, `LineDrawing::Unicode` by default
+    pub fn set_line_drawing(&mut self, line_drawing: LineDrawing) {
+        self.acs = match line_drawing {
+            LineDrawing::Unicode => None,
+            LineDrawing::Acs => Some(Acs::from_terminfo(&self.terminfo)),
+        };
+    }
+
+    pub fn set_osc_terminator(&mut self, terminator: OscTerminator) {
+        self.osc_terminator = terminator;
+    }
+
+    /// Wrap the sequences of `write_osc`/`write_dcs` for tmux (`ESC P tmux; ... ESC \`), on by
+    /// default when running in tmux. tmux forwards them only with `allow-passthrough` on.
+    pub fn set_tmux_passthrough(&mut self, passthrough: bool) {
+        self.tmux_passthrough = passthrough;
+    }
+
+    /// Write the operating system command `ESC ] code ; payload` with the terminator, for the
+    /// features not wrapped by tuikit, e.g. `write_osc(0, "title")` sets the window title.
+    /// `ESC` and `BEL` are removed from `payload` as they would end the sequence early.
+    pub fn write_osc(&mut self, code: usize, payload: &str) {
+        let sequence = osc(code, payload, self.osc_terminator);
+        self.write_passthrough(&sequence);
+    }
+
+    /// Write the device control string `ESC P payload ESC \`, `ESC` is removed from `payload`
+    pub fn write_dcs(&mut self, payload: &str) {
+        let sequence = dcs(payload);
+        self.write_passthrough(&sequence);
+    }
+
+    fn write_passthrough(&mut self, sequence: &[u8]) {
+        if self.tmux_passthrough {
+            let wrapped = tmux_wrap(sequence);
+            self.write_raw(&wrapped);
+        } else {
+            self.write_raw(sequence);
+        }
+    }
+
+    /// write `CSI {params separated by ;} {final_byte}`
+    fn write_csi(&mut self, params: &[usize], final_byte: u8) {
+        self.buffer.extend_from_slice(b"\x1b[");
+        for (i, &param) in params.iter().enumerate() {
+            if i > 0 {
+                self.buffer.push(b';');
+            }
+            push_number(&mut self.buffer, param);
+        }
+        self.buffer.push(final_byte);
+    }
+
     fn write_cap(&mut self, cmd: &str) {
         self.write_cap_with_params(cmd, &[])
     }
@@ -64,7 +274,11 @@ impl Output {
 
     /// Write text (Terminal escape sequences will be removed/escaped.)
     pub fn write(&mut self, data: &str) {
-        self.buffer.extend(data.replace("\x1b", "?").as_bytes());
+        let data = data.replace("\x1b", "?");
+        match &self.acs {
+            Some(acs) => acs.write(&mut self.buffer, &data),
+            None => self.buffer.extend(data.as_bytes()),
+        }
     }
 
     /// Write raw texts to the terminal.
@@ -97,11 +311,48 @@ impl Output {
         self.set_title("");
     }
 
-    /// Write to output stream and flush.
+    /// Write to output stream and flush. Once the terminal is gone (see `is_disconnected`) the
+    /// buffer is dropped instead.
     pub fn flush(&mut self) {
-        let _ = self.stdout.write(&self.buffer);
+        if self.disconnected {
+            self.buffer.clear();
+            return;
+        }
+        let written = self.stdout.write(&self.buffer);
         self.buffer.clear();
-        let _ = self.stdout.flush();
+        let flushed = written.and_then(|_| self.stdout.flush());
+        if let Err(err) = flushed {
+            self.disconnected = is_disconnection(&err);
+        }
+    }
+
+    /// Write at most `max` bytes of the buffer, the rest is kept for the next calls, so that
+    /// a large frame over a slow link doesn't block for long. Return whether some is left.
+    pub fn flush_chunk(&mut self, max: usize) -> bool {
+        if self.disconnected {
+            self.buffer.clear();
+            return false;
+        }
+        let len = min(max.max(1), self.buffer.len());
+        let written = self.stdout.write_all(&self.buffer[..len]);
+        self.buffer.drain(..len);
+        if let Err(err) = written.and_then(|_| self.stdout.flush()) {
+            self.disconnected = is_disconnection(&err);
+            if self.disconnected {
+                self.buffer.clear();
+            }
+        }
+        !self.buffer.is_empty()
+    }
+
+    /// the number of bytes buffered, not written yet
+    pub fn pending(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Whether a write failed because the terminal went away, e.g. the pty was closed
+    pub fn is_disconnected(&self) -> bool {
+        self.disconnected
     }
 
     /// Erases the screen with the background colour and moves the cursor to home.
@@ -126,7 +377,8 @@ impl Output {
         // Enable urxvt Mouse mode. (For terminals that understand this.)
         self.write_raw("\x1b[?1015h".as_bytes());
 
-        // Also enable Xterm SGR mouse mode. (For terminals that understand this.)
+        // Also enable Xterm SGR mouse mode, last so that it is preferred: unlike X10 it reports
+        // the columns past 223. (For terminals that understand this.)
         self.write_raw("\x1b[?1006h".as_bytes());
 
         // Note: E.g. lxterminal understands 1000h, but not the urxvt or sgr extensions.
@@ -139,6 +391,43 @@ impl Output {
         self.write_raw("\x1b[?1006l".as_bytes());
     }
 
+    /// Enable mouse drag tracking (button-event tracking).
+    ///
+    /// Motion will be reported as `MouseHold` while a button is pressed, note that
+    /// `enable_mouse_support` should also be called to receive press/release events.
+    pub fn enable_mouse_drag_support(&mut self) {
+        self.write_raw("\x1b[?1002h".as_bytes());
+    }
+
+    /// Disable mouse drag tracking.
+    pub fn disable_mouse_drag_support(&mut self) {
+        self.write_raw("\x1b[?1002l".as_bytes());
+    }
+
+    /// Enable mouse motion tracking (any-event tracking).
+    ///
+    /// Motion will be reported as `MouseMove` when no button is pressed, and as `MouseHold`
+    /// while one is, e.g. for hover effects. `enable_mouse_support` should also be called.
+    pub fn enable_mouse_motion_support(&mut self) {
+        self.write_raw("\x1b[?1003h".as_bytes());
+    }
+
+    /// Disable mouse motion tracking.
+    pub fn disable_mouse_motion_support(&mut self) {
+        self.write_raw("\x1b[?1003l".as_bytes());
+    }
+
+    /// Report the focus changes of the terminal window as `Key::FocusGained` and
+    /// `Key::FocusLost` (mode 1004)
+    pub fn enable_focus_events(&mut self) {
+        self.write_raw(b"\x1b[?1004h");
+    }
+
+    /// Disable the focus events.
+    pub fn disable_focus_events(&mut self) {
+        self.write_raw(b"\x1b[?1004l");
+    }
+
     /// Erases from the current cursor position to the end of the current line.
     pub fn erase_end_of_line(&mut self) {
         self.write_cap("el");
@@ -224,6 +513,10 @@ impl Output {
 
     /// Move cursor position.
     pub fn cursor_goto(&mut self, row: usize, column: usize) {
+        if self.xterm_cursor {
+            self.write_csi(&[row + 1, column + 1], b'H');
+            return;
+        }
         self.write_cap_with_params(
             "cup",
             &[Param::Number(row as i32), Param::Number(column as i32)],
@@ -235,6 +528,7 @@ impl Output {
         match amount {
             0 => {}
             1 => self.write_cap("cuu1"),
+            _ if self.xterm_cursor => self.write_csi(&[amount], b'A'),
             _ => self.write_cap_with_params("cuu", &[Param::Number(amount as i32)]),
         }
     }
@@ -244,6 +538,7 @@ impl Output {
         match amount {
             0 => {}
             1 => self.write_cap("cud1"),
+            _ if self.xterm_cursor => self.write_csi(&[amount], b'B'),
             _ => self.write_cap_with_params("cud", &[Param::Number(amount as i32)]),
         }
     }
@@ -253,6 +548,7 @@ impl Output {
         match amount {
             0 => {}
             1 => self.write_cap("cuf1"),
+            _ if self.xterm_cursor => self.write_csi(&[amount], b'C'),
             _ => self.write_cap_with_params("cuf", &[Param::Number(amount as i32)]),
         }
     }
@@ -262,6 +558,7 @@ impl Output {
         match amount {
             0 => {}
             1 => self.write_cap("cub1"),
+            _ if self.xterm_cursor => self.write_csi(&[amount], b'D'),
             _ => self.write_cap_with_params("cub", &[Param::Number(amount as i32)]),
         }
     }
@@ -282,6 +579,44 @@ impl Output {
         self.flush()
     }
 
+    /// Asks for the primary device attributes (DA1), answered by `Key::DeviceAttributes`
+    pub fn ask_for_device_attributes(&mut self) {
+        self.write_raw(b"\x1b[c");
+    }
+
+    /// Asks for the background color (OSC 11), answered by `Key::BackgroundColor`
+    pub fn ask_for_background_color(&mut self) {
+        self.write_raw(b"\x1b]11;?\x07");
+    }
+
+    /// Asks for the terminfo capability `name` (XTGETTCAP), answered by `Key::TermcapReply`.
+    /// Unlike the local terminfo, it is right under tmux or with an overridden `$TERM`.
+    pub fn ask_for_termcap(&mut self, name: &str) {
+        let mut query = b"\x1bP+q".to_vec();
+        for byte in name.bytes() {
+            query.extend(format!("{:02X}", byte).as_bytes());
+        }
+        query.extend(b"\x1b\\");
+        self.write_raw(&query);
+    }
+
+    /// Asks for the flags of the kitty keyboard protocol in effect (`CSI ? u`), answered by
+    /// `Key::KeyboardFlags` if the terminal supports it
+    pub fn ask_for_keyboard_flags(&mut self) {
+        self.write_raw(b"\x1b[?u");
+    }
+
+    /// Push the kitty keyboard protocol with the escape codes disambiguated (`CSI > 1 u`), see
+    /// `KeyBoard::kitty_keyboard`. Ignored by the terminals without it.
+    pub fn enable_kitty_keyboard(&mut self) {
+        self.write_raw(b"\x1b[>1u");
+    }
+
+    /// Pop the flags pushed by `enable_kitty_keyboard`
+    pub fn disable_kitty_keyboard(&mut self) {
+        self.write_raw(b"\x1b[<u");
+    }
+
     /// Sound bell.
     pub fn bell(&mut self) {
         self.write_cap("bel");
@@ -293,7 +628,7 @@ impl Output {
         terminal_size(self.stdout.as_raw_fd())
     }
 
-    /// For vt100/xterm etc.
+    /// For vt100/xterm etc. A paste is then read as one `Key::BracketedPaste`.
     pub fn enable_bracketed_paste(&mut self) {
         self.write_raw("\x1b[?2004h".as_bytes());
     }
@@ -326,6 +661,20 @@ impl Output {
                     self.disable_mouse_support();
                 }
             }
+            Command::MouseDragSupport(enable) => {
+                if enable {
+                    self.enable_mouse_drag_support();
+                } else {
+                    self.disable_mouse_drag_support();
+                }
+            }
+            Command::MouseMotionSupport(enable) => {
+                if enable {
+                    self.enable_mouse_motion_support();
+                } else {
+                    self.disable_mouse_motion_support();
+                }
+            }
             Command::EraseEndOfLine => self.erase_end_of_line(),
             Command::EraseDown => self.erase_down(),
             Command::ResetAttributes => self.reset_attributes(),
@@ -363,53 +712,132 @@ impl Output {
     }
 }
 
-/// Instead of calling functions of `Output`, we could send commands.
-#[derive(Debug, Clone)]
-pub enum Command {
-    /// Put a char to screen
-    PutChar(char),
-    /// Write content to screen (escape codes will be escaped)
-    Write(String),
-    /// Set the title of the terminal
-    SetTitle(String),
-    /// Clear the title of the terminal
-    ClearTitle,
-    /// Flush all the buffered contents
-    Flush,
-    /// Erase the entire screen
-    EraseScreen,
-    /// Enter(true)/Quit(false) the alternate screen mode
-    AlternateScreen(bool),
-    /// Enable(true)/Disable(false) mouse support
-    MouseSupport(bool),
-    /// Erase contents to the end of current line
-    EraseEndOfLine,
-    /// Erase contents till the bottom of the screen
-    EraseDown,
-    /// Reset attributes
-    ResetAttributes,
-    /// Set the foreground color
-    Fg(Color),
-    /// Set the background color
-    Bg(Color),
-    /// Set the effect(e.g. underline, dim, bold, ...)
-    Effect(Effect),
-    /// Set the fg, bg & effect.
-    SetAttribute(Attr),
-    /// Enable(true)/Disable(false) autowrap
-    AutoWrap(bool),
-    /// move the cursor to `(row, col)`
-    CursorGoto { row: usize, col: usize },
-    /// move cursor up `x` lines
-    CursorUp(usize),
-    /// move cursor down `x` lines
-    CursorDown(usize),
-    /// move cursor left `x` characters
-    CursorLeft(usize),
-    /// move cursor right `x` characters
-    CursorRight(usize),
-    /// Show(true)/Hide(false) cursor
-    CursorShow(bool),
-    /// Enable(true)/Disable(false) the bracketed paste mode
-    BracketedPaste(bool),
+fn has_xterm_cursor(terminfo: &TermInfo) -> bool {
+    XTERM_CURSOR_CAPS.iter().all(|(cap, expected)| {
+        terminfo
+            .strings
+            .get(*cap)
+            .is_some_and(|value| value.as_slice() == *expected)
+    })
+}
+
+/// append the decimal digits of `number`
+fn push_number(buffer: &mut Vec<u8>, mut number: usize) {
+    let mut digits = [0u8; 20];
+    let mut start = digits.len();
+    loop {
+        start -= 1;
+        digits[start] = b'0' + (number % 10) as u8;
+        number /= 10;
+        if number == 0 {
+            break;
+        }
+    }
+    buffer.extend_from_slice(&digits[start..]);
+}
+
+fn osc(code: usize, payload: &str, terminator: OscTerminator) -> Vec<u8> {
+    let mut sequence = b"\x1b]".to_vec();
+    push_number(&mut sequence, code);
+    sequence.push(b';');
+    sequence.extend(payload.bytes().filter(|&b| b != b'\x1b' && b != b'\x07'));
+    match terminator {
+        OscTerminator::Bel => sequence.push(b'\x07'),
+        OscTerminator::St => sequence.extend(b"\x1b\\"),
+    }
+    sequence
+}
+
+fn dcs(payload: &str) -> Vec<u8> {
+    let mut sequence = b"\x1bP".to_vec();
+    sequence.extend(payload.bytes().filter(|&b| b != b'\x1b'));
+    sequence.extend(b"\x1b\\");
+    sequence
+}
+
+/// `sequence` inside a tmux passthrough DCS, where `ESC` is doubled
+fn tmux_wrap(sequence: &[u8]) -> Vec<u8> {
+    let mut wrapped = b"\x1bPtmux;".to_vec();
+    for &byte in sequence {
+        if byte == b'\x1b' {
+            wrapped.push(byte);
+        }
+        wrapped.push(byte);
+    }
+    wrapped.extend(b"\x1b\\");
+    wrapped
+}
+
+/// `EIO` is what writing to a tty returns after a hangup
+fn is_disconnection(err: &io::Error) -> bool {
+    err.kind() == io::ErrorKind::BrokenPipe || err.raw_os_error() == Some(nix::libc::EIO)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn osc_and_dcs() {
+        assert_eq!(
+            b"\x1b]0;title\x07".to_vec(),
+            osc(0, "ti\x07t\x1ble", OscTerminator::Bel)
+        );
+        assert_eq!(
+            b"\x1b]8;;http://a\x1b\\".to_vec(),
+            osc(8, ";http://a", OscTerminator::St)
+        );
+        assert_eq!(b"\x1bPq#0\x1b\\".to_vec(), dcs("q#0\x1b"));
+        assert_eq!(
+            b"\x1bPtmux;\x1b\x1b]0;t\x07\x1b\\".to_vec(),
+            tmux_wrap(&osc(0, "t", OscTerminator::Bel))
+        );
+    }
+
+    #[test]
+    fn disconnection() {
+        assert!(is_disconnection(&io::Error::from_raw_os_error(
+            nix::libc::EIO
+        )));
+        assert!(is_disconnection(&io::ErrorKind::BrokenPipe.into()));
+        assert!(!is_disconnection(&io::ErrorKind::WouldBlock.into()));
+    }
+
+    #[test]
+    fn alternate_characters() {
+        let acs = Acs {
+            enter: b"<".to_vec(),
+            exit: b">".to_vec(),
+            chars: b"qqxxlzkk"
+                .chunks(2)
+                .map(|pair| (pair[0], pair[1]))
+                .collect(),
+        };
+        let mut buffer = Vec::new();
+        acs.write(&mut buffer, "┌─┐ q│é╯");
+        assert_eq!("<zqk> q<x>é+".as_bytes(), buffer.as_slice());
+
+        // no alternate characters at all
+        let mut buffer = Vec::new();
+        Acs::default().write(&mut buffer, "╭─╮");
+        assert_eq!(b"+-+", buffer.as_slice());
+    }
+
+    #[test]
+    fn fast_path_matches_terminfo_expansion() {
+        for &(row, col) in [(0, 0), (9, 10), (122, 4096)].iter() {
+            let expanded = expand(
+                XTERM_CURSOR_CAPS[0].1,
+                &[Param::Number(row as i32), Param::Number(col as i32)],
+                &mut Variables::new(),
+            )
+            .unwrap();
+            let mut fast = b"\x1b[".to_vec();
+            push_number(&mut fast, row + 1);
+            fast.push(b';');
+            push_number(&mut fast, col + 1);
+            fast.push(b'H');
+            assert_eq!(expanded, fast);
+        }
+    }
 }
@@ -0,0 +1,128 @@
+//! Sanitize pasted text before it reaches the application.
+//!
+//! Pasted text comes from anywhere: it could carry escape sequences that end up echoed to the
+//! terminal, `\r` line endings, or a megabyte of log by accident. A `PastePolicy` decides what
+//! to keep.
+//!
+//! ```
+//! use tuikit::paste::PastePolicy;
+//!
+//! let policy = PastePolicy::default().single_line(true).max_len(Some(64));
+//! assert_eq!(Some("ls -l /tmp".to_string()), policy.apply("ls -l\r\n/tmp\x07"));
+//! assert_eq!(None, policy.apply(&"x".repeat(65)));
+//! ```
+
+/// How pasted text is sanitized, see `apply`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PastePolicy {
+    strip_control: bool,
+    normalize_newlines: bool,
+    single_line: bool,
+    max_len: Option<usize>,
+}
+
+impl Default for PastePolicy {
+    /// strip control characters and normalize newlines, no size limit
+    fn default() -> Self {
+        Self {
+            strip_control: true,
+            normalize_newlines: true,
+            single_line: false,
+            max_len: None,
+        }
+    }
+}
+
+// Builder
+impl PastePolicy {
+    /// keep the text as is
+    pub fn raw() -> Self {
+        Self {
+            strip_control: false,
+            normalize_newlines: false,
+            single_line: false,
+            max_len: None,
+        }
+    }
+
+    /// drop the control characters (including `ESC`) other than `\n` and `\t`
+    pub fn strip_control(mut self, strip: bool) -> Self {
+        self.strip_control = strip;
+        self
+    }
+
+    /// convert `\r\n` and `\r` to `\n`
+    pub fn normalize_newlines(mut self, normalize: bool) -> Self {
+        self.normalize_newlines = normalize;
+        self
+    }
+
+    /// join the lines with a space, e.g. for single-line prompts
+    pub fn single_line(mut self, single_line: bool) -> Self {
+        self.single_line = single_line;
+        self
+    }
+
+    /// reject the pastes longer than `max_len` bytes
+    pub fn max_len(mut self, max_len: Option<usize>) -> Self {
+        self.max_len = max_len;
+        self
+    }
+}
+
+impl PastePolicy {
+    /// Return the sanitized text, `None` if the paste is rejected
+    pub fn apply(&self, text: &str) -> Option<String> {
+        if self.max_len.is_some_and(|max_len| text.len() > max_len) {
+            return None;
+        }
+
+        let mut text = text.to_string();
+        if self.normalize_newlines || self.single_line {
+            text = text.replace("\r\n", "\n").replace('\r', "\n");
+        }
+        if self.strip_control {
+            text.retain(|ch| ch == '\n' || ch == '\t' || !ch.is_control());
+        }
+        if self.single_line {
+            text = text
+                .split('\n')
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+        }
+        Some(text)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn default_policy() {
+        let policy = PastePolicy::default();
+        assert_eq!(
+            Some("a\nb\nc\td".to_string()),
+            policy.apply("a\r\nb\rc\td\x07")
+        );
+        assert_eq!(
+            Some("\x1b[2J".to_string()),
+            PastePolicy::raw().apply("\x1b[2J")
+        );
+    }
+
+    #[test]
+    fn single_line() {
+        let policy = PastePolicy::raw().single_line(true);
+        assert_eq!(Some("a b".to_string()), policy.apply("  a\r\n\n b \n"));
+    }
+
+    #[test]
+    fn size_limit() {
+        let policy = PastePolicy::default().max_len(Some(3));
+        assert_eq!(Some("abc".to_string()), policy.apply("abc"));
+        assert_eq!(None, policy.apply("abcd"));
+    }
+}
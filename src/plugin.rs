@@ -0,0 +1,412 @@
+//! Plugins extending a running `Term` from other crates.
+//!
+//! A plugin could feed its own events (e.g. a git status watcher), draw a layer over every
+//! frame (e.g. a clock in the corner) with the regions it registers, and offer commands to the
+//! command palette. Each is optional:
+//!
+//! ```no_run
+//! use std::thread;
+//! use std::time::Duration;
+//! use tuikit::plugin::{EventSender, Plugin};
+//! use tuikit::prelude::*;
+//! use tuikit::widget::{CommandPalette, PaletteCommand, PaletteEvent};
+//!
+//! struct Ticks(usize);
+//!
+//! impl Plugin for Ticks {
+//!     fn name(&self) -> &str {
+//!         "ticks"
+//!     }
+//!
+//!     fn start(&mut self, events: EventSender) {
+//!         thread::spawn(move || {
+//!             while events.send(Event::app(Ticks(1))).is_ok() {
+//!                 thread::sleep(Duration::from_secs(1));
+//!             }
+//!         });
+//!     }
+//!
+//!     fn draw_layer(&mut self, canvas: &mut dyn Canvas) -> Result<()> {
+//!         let (width, _) = canvas.size()?;
+//!         canvas.print(0, width.saturating_sub(8), &format!("{:>8}", self.0))?;
+//!         Ok(())
+//!     }
+//!
+//!     fn commands(&self) -> Vec<PaletteCommand> {
+//!         vec![PaletteCommand {
+//!             name: "Reset ticks".to_string(),
+//!             key: None,
+//!             description: String::new(),
+//!         }]
+//!     }
+//!
+//!     fn run_command(&mut self, _name: &str) {
+//!         self.0 = 0;
+//!     }
+//! }
+//!
+//! let term = Term::new().unwrap();
+//! term.add_plugin(Box::new(Ticks(0)));
+//!
+//! // the palette only lists the commands, they are run by the term
+//! let mut palette = CommandPalette::new();
+//! for command in term.plugin_commands() {
+//!     palette.add_command(&command.name, command.key, &command.description, || {});
+//! }
+//! palette.open();
+//! while let Ok(Event::Key(KeyEvent { key, .. })) = term.poll_event() {
+//!     if let Some(PaletteEvent::Executed(name)) = palette.on_key(&key) {
+//!         term.run_plugin_command(&name);
+//!     }
+//! }
+//! ```
+//!
+//! The layers are drawn by `present` over the frame, in the order the plugins were added. They
+//! are drawn, and the commands run, with the term unlocked: a plugin may call the `Term` back.
+
+use crate::canvas::{Canvas, Result};
+use crate::event::Event;
+use crate::spinlock::SpinLock;
+use crate::widget::PaletteCommand;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+
+/// The extension points of a plugin, all no-ops by default
+pub trait Plugin: Send {
+    /// a unique name, a plugin replaces the one of the same name
+    fn name(&self) -> &str;
+
+    /// the plugin is added, `events` sends events to the term, e.g. from a thread
+    fn start(&mut self, _events: EventSender) {}
+
+    /// the plugin is removed, time to stop its threads
+    fn stop(&mut self) {}
+
+    /// draw over each frame before it is presented
+    fn draw_layer(&mut self, _canvas: &mut dyn Canvas) -> Result<()> {
+        Ok(())
+    }
+
+    /// the commands offered to the command palette
+    fn commands(&self) -> Vec<PaletteCommand> {
+        Vec::new()
+    }
+
+    /// the command `name` of `commands` was chosen
+    fn run_command(&mut self, _name: &str) {}
+}
+
+/// Sends events to a `Term` from any thread, see `Plugin::start`
+#[derive(Clone)]
+pub struct EventSender {
+    event_tx: Arc<SpinLock<Sender<Event>>>,
+}
+
+impl EventSender {
+    pub(crate) fn new(event_tx: Arc<SpinLock<Sender<Event>>>) -> Self {
+        Self { event_tx }
+    }
+
+    /// fails once the term is dropped
+    pub fn send(&self, event: Event) -> Result<()> {
+        let event_tx = self.event_tx.lock();
+        event_tx.send(event).map_err(|err| err.to_string().into())
+    }
+}
+
+pub(crate) type SharedPlugin = Arc<SpinLock<Box<dyn Plugin>>>;
+
+/// The plugins of a term, in the order they were added. Each is locked on its own, so that
+/// the layers are drawn and the commands run with `Plugins` unlocked.
+#[derive(Default)]
+pub(crate) struct Plugins {
+    plugins: Vec<(String, SharedPlugin)>,
+}
+
+impl Plugins {
+    /// add the started `plugin`, return the one of the same name it replaces, to be stopped
+    pub fn add(&mut self, plugin: Box<dyn Plugin>) -> Option<SharedPlugin> {
+        let name = plugin.name().to_string();
+        let replaced = self.remove(&name);
+        self.plugins.push((name, Arc::new(SpinLock::new(plugin))));
+        replaced
+    }
+
+    /// remove the plugin `name`, to be stopped
+    pub fn remove(&mut self, name: &str) -> Option<SharedPlugin> {
+        let index = self.plugins.iter().position(|(other, _)| other == name)?;
+        Some(self.plugins.remove(index).1)
+    }
+
+    /// the plugins, bottom layer first
+    pub fn layers(&self) -> Vec<SharedPlugin> {
+        self.plugins
+            .iter()
+            .map(|(_, plugin)| plugin.clone())
+            .collect()
+    }
+
+    pub fn commands(&self) -> Vec<PaletteCommand> {
+        self.plugins
+            .iter()
+            .flat_map(|(_, plugin)| plugin.lock().commands())
+            .collect()
+    }
+
+    /// the first plugin offering the command `name`
+    pub fn command_owner(&self, name: &str) -> Option<SharedPlugin> {
+        self.plugins
+            .iter()
+            .find(|(_, plugin)| {
+                let commands = plugin.lock().commands();
+                commands.iter().any(|command| command.name == name)
+            })
+            .map(|(_, plugin)| plugin.clone())
+    }
+}
+
+/// draw the layers of `plugins` in order, the later ones over the earlier ones
+pub(crate) fn draw_layers(plugins: &[SharedPlugin], canvas: &mut dyn Canvas) -> Result<()> {
+    for plugin in plugins.iter() {
+        plugin.lock().draw_layer(canvas)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::key::Key;
+    use crate::screen::Screen;
+    use crate::term::{Term, TermHeight, TermOptions};
+    use crate::testing::FakeTerminal;
+    use crate::widget::{CommandPalette, PaletteEvent};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc::{channel, Receiver};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    const TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// prints `text` at `col` of the first row, runs its command by counting it
+    struct Printer {
+        name: &'static str,
+        col: usize,
+        text: &'static str,
+        runs: Arc<AtomicUsize>,
+    }
+
+    fn printer(name: &'static str, col: usize, text: &'static str) -> Box<Printer> {
+        Box::new(Printer {
+            name,
+            col,
+            text,
+            runs: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    impl Plugin for Printer {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn draw_layer(&mut self, canvas: &mut dyn Canvas) -> Result<()> {
+            canvas.print(0, self.col, self.text)?;
+            Ok(())
+        }
+
+        fn commands(&self) -> Vec<PaletteCommand> {
+            vec![PaletteCommand {
+                name: format!("Run {}", self.name),
+                key: None,
+                description: String::new(),
+            }]
+        }
+
+        fn run_command(&mut self, _name: &str) {
+            self.runs.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// sends `count` app events from its own thread, until stopped
+    struct Source {
+        count: usize,
+        stopped: Arc<AtomicUsize>,
+    }
+
+    impl Plugin for Source {
+        fn name(&self) -> &str {
+            "source"
+        }
+
+        fn start(&mut self, events: EventSender) {
+            let count = self.count;
+            thread::spawn(move || {
+                for index in 0..count {
+                    if events.send(Event::app(index)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        fn stop(&mut self) {
+            self.stopped.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// draws the size of the term, asked with the term unlocked
+    struct Size {
+        term: Arc<Term>,
+    }
+
+    impl Plugin for Size {
+        fn name(&self) -> &str {
+            "size"
+        }
+
+        fn draw_layer(&mut self, canvas: &mut dyn Canvas) -> Result<()> {
+            let (width, height) = self.term.term_size()?;
+            canvas.print(0, 0, &format!("{}x{}", width, height))?;
+            Ok(())
+        }
+    }
+
+    fn fake_term(fake: &FakeTerminal) -> Term {
+        let options = TermOptions::default()
+            .height(TermHeight::Fixed(4))
+            .tty_path(fake.path());
+        Term::with_options(options).unwrap()
+    }
+
+    fn row_text(screen: &Screen) -> String {
+        screen.iter_cell().map(|(_, _, cell)| cell.ch).collect()
+    }
+
+    /// the app events polled within `TIMEOUT`, skipping the others
+    fn poll_app_events(term: &Term, count: usize) -> Vec<usize> {
+        let deadline = Instant::now() + TIMEOUT;
+        let mut polled = Vec::new();
+        while polled.len() < count && Instant::now() < deadline {
+            if let Ok(Event::App(payload)) = term.peek_event(Duration::from_millis(50)) {
+                polled.extend(payload.downcast_ref::<usize>());
+            }
+        }
+        polled
+    }
+
+    #[test]
+    fn events_are_polled_from_the_plugin_thread() {
+        let fake = FakeTerminal::new(20, 4).unwrap();
+        let term = fake_term(&fake);
+        let stopped = Arc::new(AtomicUsize::new(0));
+        term.add_plugin(Box::new(Source {
+            count: 3,
+            stopped: stopped.clone(),
+        }));
+        assert_eq!(vec![0, 1, 2], poll_app_events(&term, 3));
+
+        // replacing the plugin stops the previous one
+        term.add_plugin(Box::new(Source {
+            count: 1,
+            stopped: stopped.clone(),
+        }));
+        assert_eq!(1, stopped.load(Ordering::SeqCst));
+        assert_eq!(vec![0], poll_app_events(&term, 1));
+        assert!(term.remove_plugin("source") && !term.remove_plugin("source"));
+        assert_eq!(2, stopped.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn sending_fails_once_the_term_is_dropped() {
+        let (event_tx, event_rx) = channel();
+        let events = EventSender::new(Arc::new(SpinLock::new(event_tx)));
+        assert!(events.send(Event::app(0usize)).is_ok());
+        drop(event_rx);
+        assert!(events.send(Event::app(1usize)).is_err());
+    }
+
+    #[test]
+    fn layers_are_drawn_in_order_over_the_frame() {
+        let mut plugins = Plugins::default();
+        plugins.add(printer("a", 0, "aaa"));
+        plugins.add(printer("b", 1, "b"));
+        let mut screen = Screen::new(4, 1);
+        screen.print(0, 0, "xxxx").unwrap();
+        draw_layers(&plugins.layers(), &mut screen).unwrap();
+        assert_eq!("abax", row_text(&screen));
+
+        // a replaced plugin is drawn on top
+        assert!(plugins.add(printer("a", 0, "AAA")).is_some());
+        draw_layers(&plugins.layers(), &mut screen).unwrap();
+        assert_eq!("AAAx", row_text(&screen));
+        assert!(plugins.remove("a").is_some() && plugins.remove("a").is_none());
+        assert_eq!(1, plugins.layers().len());
+    }
+
+    #[test]
+    fn layer_calls_back_into_term() {
+        let fake = FakeTerminal::new(20, 4).unwrap();
+        let term = Arc::new(fake_term(&fake));
+        term.add_plugin(Box::new(Size { term: term.clone() }));
+
+        // the layer deadlocks if drawn with the term locked
+        let (presented_tx, presented_rx) = channel();
+        let presenting = term.clone();
+        thread::spawn(move || {
+            let _ = presented_tx.send(presenting.present().is_ok());
+        });
+        assert_eq!(Ok(true), presented_rx.recv_timeout(TIMEOUT));
+        assert!(fake.wait_for_output(b"20x4", TIMEOUT));
+
+        // drops the plugin, and the term it holds
+        assert!(term.remove_plugin("size"));
+    }
+
+    fn palette_of(term: &Term) -> (CommandPalette, Receiver<String>) {
+        let (chosen_tx, chosen_rx) = channel();
+        let mut palette = CommandPalette::new();
+        for command in term.plugin_commands() {
+            let chosen_tx = chosen_tx.clone();
+            let name = command.name.clone();
+            palette.add_command(
+                &command.name,
+                command.key,
+                &command.description,
+                move || {
+                    let _ = chosen_tx.send(name.clone());
+                },
+            );
+        }
+        (palette, chosen_rx)
+    }
+
+    #[test]
+    fn palette_commands_are_dispatched_to_their_plugin() {
+        let fake = FakeTerminal::new(20, 4).unwrap();
+        let term = fake_term(&fake);
+        let (a, b) = (printer("a", 0, "a"), printer("b", 0, "b"));
+        let (a_runs, b_runs) = (a.runs.clone(), b.runs.clone());
+        term.add_plugin(a);
+        term.add_plugin(b);
+
+        let (mut palette, chosen) = palette_of(&term);
+        let names: Vec<&str> = palette.commands().iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(vec!["Run a", "Run b"], names);
+
+        palette.open();
+        palette.on_key(&Key::Char('b'));
+        let event = palette.on_key(&Key::Enter);
+        assert_eq!(Some(PaletteEvent::Executed("Run b".to_string())), event);
+        assert!(term.run_plugin_command(&chosen.try_recv().unwrap()));
+        assert_eq!(
+            (0, 1),
+            (a_runs.load(Ordering::SeqCst), b_runs.load(Ordering::SeqCst))
+        );
+
+        // the command of a removed plugin isn't run
+        assert!(term.remove_plugin("b"));
+        assert!(!term.run_plugin_command("Run b"));
+        assert_eq!(1, b_runs.load(Ordering::SeqCst));
+    }
+}
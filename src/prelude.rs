@@ -1,7 +1,9 @@
 pub use crate::attr::{Attr, Color, Effect};
-pub use crate::canvas::{Canvas, Result};
+pub use crate::canvas::{Align, Canvas, ColumnSpec, Result};
 pub use crate::cell::Cell;
-pub use crate::container::{HSplit, Size, Split, VSplit, Win};
-pub use crate::draw::Draw;
-pub use crate::event::{Event, Key};
-pub use crate::term::{Term, TermHeight};
+pub use crate::container::{
+    HSplit, Size, Split, Splitter, SplitterDirection, SplitterState, VSplit, Win,
+};
+pub use crate::draw::{Draw, DrawContext};
+pub use crate::event::{Event, Key, KeyEvent, MouseEvent, TerminalEvent};
+pub use crate::term::{PresentStatus, Term, TermHeight};
@@ -19,16 +19,38 @@
 //!     write!(stdout, "Hey there.").unwrap();
 //! }
 //! ```
+//!
+//! # Finding the terminal
+//!
+//! `open_tty` tries `/dev/tty` first, which is the terminal even when stdin or stdout are
+//! redirected. Without a controlling terminal (e.g. after `setsid`, or in some containers) it
+//! can't be opened; with `TtyFallback::Stdin` stdin is used instead if it is a terminal.
+//! Otherwise the error is `TtyError::NotATty`, for the application to choose between a
+//! degraded mode (e.g. printing plain text) and a clear error message.
+//!
+//! # A single writer
+//!
+//! Two applications drawing on the same terminal garble it, e.g. when one is launched twice in
+//! the same pane. `lock_tty` takes an advisory lock on the terminal which fails with
+//! `TtyError::InUse` while another process holds it, see `TermOptions::lock_tty`.
 
+use std::error::Error;
+use std::fmt;
 use std::io::{self, Write};
 use std::ops;
 
+use nix::errno::Errno;
+use nix::fcntl::{flock, FlockArg};
+use nix::sys::stat::fstat;
 use nix::sys::termios::{tcgetattr, tcsetattr, SetArg, Termios};
-use nix::unistd::isatty;
+use nix::unistd::{dup, isatty};
 use nix::Error::Sys;
+use std::env;
 use std::fs;
-use std::io::ErrorKind;
-use std::os::unix::io::{AsRawFd, RawFd};
+use std::io::{ErrorKind, Read};
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::path::{Path, PathBuf};
 
 // taken from termion
 /// Get the TTY device.
@@ -41,6 +63,147 @@ pub fn get_tty() -> io::Result<fs::File> {
         .open("/dev/tty")
 }
 
+/// What `open_tty` falls back to when `/dev/tty` can't be opened
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum TtyFallback {
+    /// stdin, if it is a terminal
+    #[default]
+    Stdin,
+    /// no fallback, `/dev/tty` or an error
+    None,
+}
+
+/// Why the terminal couldn't be opened, see `open_tty`
+#[derive(Debug)]
+pub enum TtyError {
+    /// there is no terminal to use: `/dev/tty` failed with this error, and stdin is not a
+    /// terminal or the fallback is disabled
+    NotATty(io::Error),
+    /// the terminal was found but couldn't be used
+    Io(io::Error),
+    /// another process holds the lock of the terminal, see `lock_tty`, with its pid if known
+    InUse(Option<u32>),
+}
+
+impl fmt::Display for TtyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TtyError::NotATty(err) => write!(f, "not a terminal (/dev/tty: {})", err),
+            TtyError::Io(err) => write!(f, "failed to use the terminal: {}", err),
+            TtyError::InUse(Some(pid)) => {
+                write!(
+                    f,
+                    "the terminal is used by another application (pid {})",
+                    pid
+                )
+            }
+            TtyError::InUse(None) => f.write_str("the terminal is used by another application"),
+        }
+    }
+}
+
+impl Error for TtyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TtyError::NotATty(err) | TtyError::Io(err) => Some(err),
+            TtyError::InUse(_) => None,
+        }
+    }
+}
+
+/// Open the terminal for reading and writing: `/dev/tty`, then stdin if it is a terminal and
+/// `fallback` allows it. Stdin is duplicated, dropping the file leaves it open.
+pub fn open_tty(fallback: TtyFallback) -> Result<fs::File, TtyError> {
+    let err = match get_tty() {
+        Ok(tty) => return Ok(tty),
+        Err(err) => err,
+    };
+
+    let stdin = io::stdin().as_raw_fd();
+    if fallback == TtyFallback::None || !isatty(stdin).unwrap_or(false) {
+        return Err(TtyError::NotATty(err));
+    }
+    let fd = dup(stdin).map_err(|err| TtyError::Io(nix_err_to_io_err(err)))?;
+    Ok(unsafe { fs::File::from_raw_fd(fd) })
+}
+
+/// Open the terminal at `path` instead of looking for it, e.g. a pseudo terminal, see
+/// `TermOptions::tty_path`
+pub fn open_tty_path(path: &Path) -> Result<fs::File, TtyError> {
+    let tty = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .map_err(TtyError::Io)?;
+    if !isatty(tty.as_raw_fd()).unwrap_or(false) {
+        let err = io::Error::other("not a terminal");
+        return Err(TtyError::Io(err));
+    }
+    Ok(tty)
+}
+
+/// An advisory lock on a terminal, see `lock_tty`, released when dropped
+#[derive(Debug)]
+pub struct TtyLock {
+    /// `None` if the lock file couldn't be used
+    _file: Option<fs::File>,
+}
+
+/// Lock the terminal `tty` for this process. The lock is a file named after the terminal
+/// device in `$XDG_RUNTIME_DIR` (or the temporary directory), holding the pid of its owner.
+/// Fails with `TtyError::InUse` while another process holds it. The lock is advisory: if the
+/// lock file can't be used (e.g. it belongs to another user) the terminal is deemed free.
+pub fn lock_tty(tty: &fs::File) -> Result<TtyLock, TtyError> {
+    let device = tty_device(tty).map_err(TtyError::Io)?;
+    let path = env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(env::temp_dir)
+        .join(format!("tuikit-tty-{:x}.lock", device));
+    let mut file = match fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+    {
+        Ok(file) => file,
+        Err(_) => return Ok(TtyLock { _file: None }),
+    };
+
+    match flock(file.as_raw_fd(), FlockArg::LockExclusiveNonblock) {
+        Ok(()) => {}
+        Err(Sys(Errno::EAGAIN)) => {
+            let mut pid = String::new();
+            let _ = file.read_to_string(&mut pid);
+            return Err(TtyError::InUse(pid.trim().parse().ok()));
+        }
+        Err(_) => return Ok(TtyLock { _file: None }),
+    }
+    let _ = file.set_len(0);
+    let _ = write!(file, "{}", std::process::id());
+    Ok(TtyLock { _file: Some(file) })
+}
+
+/// The device of the terminal. `/dev/tty` stands for the controlling terminal of any process,
+/// the standard streams tell which one it is.
+fn tty_device(tty: &fs::File) -> io::Result<u64> {
+    let device = tty.metadata()?.rdev();
+    if fs::metadata("/dev/tty").map(|alias| alias.rdev()).ok() != Some(device) {
+        return Ok(device);
+    }
+    let std_device = (0..3)
+        .filter(|&fd| isatty(fd).unwrap_or(false))
+        .filter_map(|fd| fstat(fd).ok())
+        // `dev_t` is not `u64` everywhere
+        .map(|stat| {
+            #[allow(clippy::unnecessary_cast)]
+            let std_device = stat.st_rdev as u64;
+            std_device
+        })
+        .find(|&std_device| std_device != device);
+    Ok(std_device.unwrap_or(device))
+}
+
 /// A terminal restorer, which keeps the previous state of the terminal, and restores it, when
 /// dropped.
 ///
@@ -82,7 +245,7 @@ impl<W: Write + AsRawFd> Write for RawTerminal<W> {
 
 impl<W: Write + AsRawFd> AsRawFd for RawTerminal<W> {
     fn as_raw_fd(&self) -> RawFd {
-        return self.output.as_raw_fd();
+        self.output.as_raw_fd()
     }
 }
 
@@ -164,4 +327,37 @@ mod test {
 
         drop(out);
     }
+
+    #[test]
+    fn tty_fallback() {
+        if get_tty().is_ok() {
+            assert!(open_tty(TtyFallback::None).is_ok());
+            return;
+        }
+        // without a controlling terminal, as under most CI runners
+        match open_tty(TtyFallback::None) {
+            Err(err @ TtyError::NotATty(_)) => {
+                assert!(err.to_string().starts_with("not a terminal"))
+            }
+            result => panic!("unexpected {:?}", result),
+        }
+        let stdin_is_tty = isatty(io::stdin().as_raw_fd()).unwrap_or(false);
+        assert_eq!(stdin_is_tty, open_tty(TtyFallback::Stdin).is_ok());
+    }
+
+    #[test]
+    fn single_writer() {
+        let terminal = crate::testing::FakeTerminal::new(10, 2).unwrap();
+        let tty = open_tty_path(&terminal.path()).unwrap();
+        let lock = lock_tty(&tty).unwrap();
+
+        // flock locks are per open file, a second open stands for another process
+        let other = open_tty_path(&terminal.path()).unwrap();
+        match lock_tty(&other) {
+            Err(TtyError::InUse(pid)) => assert_eq!(Some(std::process::id()), pid),
+            result => panic!("unexpected {:?}", result),
+        }
+        drop(lock);
+        assert!(lock_tty(&other).is_ok());
+    }
 }
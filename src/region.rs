@@ -0,0 +1,190 @@
+//! Actionable areas of the screen registered by widgets while drawing.
+//!
+//! A widget calls `Canvas::register_region` for the parts of it that could be acted upon (a
+//! link, a button, a list item), the containers translate and clip the region like any other
+//! drawing. `Term` keeps the registered regions until the next `clear` and answers
+//! `Term::regions_at` for mouse handling, `Term::region_hints` for the link hints overlay and
+//! `Term::regions` for the spatial focus movement of `tuikit::focus`.
+
+#[cfg(feature = "std")]
+use crate::hints::{HintTarget, Hints};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// A named rectangle of the screen with a payload for the application
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub name: String,
+    pub row: usize,
+    pub col: usize,
+    pub width: usize,
+    pub height: usize,
+    pub payload: String,
+}
+
+impl Region {
+    pub fn new(name: &str, row: usize, col: usize, width: usize, height: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            row,
+            col,
+            width,
+            height,
+            payload: String::new(),
+        }
+    }
+
+    pub fn payload(mut self, payload: &str) -> Self {
+        self.payload = payload.to_string();
+        self
+    }
+
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        (self.row..self.row + self.height).contains(&row)
+            && (self.col..self.col + self.width).contains(&col)
+    }
+
+    /// The part of the region inside `width` x `height` moved by `(top, left)`, `None` if
+    /// nothing is left
+    pub(crate) fn clip(
+        &self,
+        top: usize,
+        left: usize,
+        width: usize,
+        height: usize,
+    ) -> Option<Self> {
+        let row_end = (self.row + self.height).min(height);
+        let col_end = (self.col + self.width).min(width);
+        if self.row >= row_end || self.col >= col_end {
+            return None;
+        }
+
+        Some(Self {
+            row: self.row + top,
+            col: self.col + left,
+            width: col_end - self.col,
+            height: row_end - self.row,
+            ..self.clone()
+        })
+    }
+}
+
+/// The regions registered for the current frame
+#[derive(Debug, Default)]
+pub struct RegionRegistry {
+    regions: Vec<Region>,
+}
+
+impl RegionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, region: Region) {
+        self.regions.push(region);
+    }
+
+    pub fn clear(&mut self) {
+        self.regions.clear();
+    }
+
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    /// the regions containing `(row, col)`, the last registered (i.e. drawn on top) first
+    pub fn regions_at(&self, row: usize, col: usize) -> Vec<&Region> {
+        self.regions
+            .iter()
+            .rev()
+            .filter(|region| region.contains(row, col))
+            .collect()
+    }
+
+    /// a hint for every region, at its top left corner
+    #[cfg(feature = "std")]
+    pub fn hints(&self) -> Hints<Region> {
+        let targets = self
+            .regions
+            .iter()
+            .map(|region| HintTarget::new(region.row, region.col, region.width, region.clone()))
+            .collect();
+        Hints::new(targets)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::canvas::{BoundedCanvas, Canvas};
+    use crate::screen::Screen;
+
+    #[test]
+    fn regions_at() {
+        let mut registry = RegionRegistry::new();
+        registry.register(Region::new("list", 0, 0, 10, 5));
+        registry.register(Region::new("item", 2, 0, 10, 1).payload("2"));
+
+        let names: Vec<&str> = registry
+            .regions_at(2, 3)
+            .iter()
+            .map(|region| region.name.as_str())
+            .collect();
+        assert_eq!(vec!["item", "list"], names);
+        assert!(registry.regions_at(5, 0).is_empty());
+        assert_eq!(2, registry.hints().labels().len());
+    }
+
+    #[test]
+    fn clip() {
+        let region = Region::new("a", 1, 2, 10, 3);
+        assert_eq!(
+            Some(Region::new("a", 11, 22, 3, 1)),
+            region.clip(10, 20, 5, 2)
+        );
+        assert_eq!(None, region.clip(0, 0, 2, 10));
+    }
+
+    #[test]
+    fn bounded_canvas_should_translate() {
+        struct Recorder(Screen, Vec<Region>);
+        impl Canvas for Recorder {
+            fn size(&self) -> crate::canvas::Result<(usize, usize)> {
+                self.0.size()
+            }
+            fn clear(&mut self) -> crate::canvas::Result<()> {
+                self.0.clear()
+            }
+            fn put_cell(
+                &mut self,
+                row: usize,
+                col: usize,
+                cell: crate::cell::Cell,
+            ) -> crate::canvas::Result<usize> {
+                self.0.put_cell(row, col, cell)
+            }
+            fn set_cursor(&mut self, row: usize, col: usize) -> crate::canvas::Result<()> {
+                self.0.set_cursor(row, col)
+            }
+            fn show_cursor(&mut self, show: bool) -> crate::canvas::Result<()> {
+                self.0.show_cursor(show)
+            }
+            fn register_region(&mut self, region: Region) -> crate::canvas::Result<()> {
+                self.1.push(region);
+                Ok(())
+            }
+        }
+
+        let mut recorder = Recorder(Screen::new(10, 10), Vec::new());
+        {
+            let mut canvas = BoundedCanvas::new(2, 3, 4, 4, &mut recorder);
+            canvas
+                .register_region(Region::new("a", 1, 1, 10, 1))
+                .unwrap();
+            canvas
+                .register_region(Region::new("b", 5, 0, 1, 1))
+                .unwrap();
+        }
+        assert_eq!(vec![Region::new("a", 3, 4, 3, 1)], recorder.1);
+    }
+}
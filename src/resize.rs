@@ -0,0 +1,174 @@
+//! Settle the bursts of resizes before handling them.
+//!
+//! Dragging a window border sends a storm of SIGWINCH. Handling each of them (clearing the
+//! drawing area, repainting) makes the repaints fight each other, and in inline mode late
+//! cursor position replies get mixed in. So the size change listener only reports a resize once
+//! the signals stopped for a while (`ResizeDebouncer`), and `Term` drops the resizes that end up
+//! at the size already handled as well as the cursor position replies that came after the
+//! query timed out (`ResizeState`): at most one repaint per settled size.
+//!
+//! Where SIGWINCH isn't delivered reliably (some containers, mosh), the size could also be
+//! polled (`SizePoller`), a change counts as a signal.
+
+use crate::key::Key;
+use std::time::{Duration, Instant};
+
+/// Reports a burst of signals once, `quiet` after the last one
+pub(crate) struct ResizeDebouncer {
+    quiet: Duration,
+    settle_at: Option<Instant>,
+}
+
+impl ResizeDebouncer {
+    pub fn new(quiet: Duration) -> Self {
+        Self {
+            quiet,
+            settle_at: None,
+        }
+    }
+
+    pub fn on_signal(&mut self, now: Instant) {
+        self.settle_at = Some(now + self.quiet);
+    }
+
+    /// how long to wait for the next signal, `None` to wait indefinitely
+    pub fn timeout(&self, now: Instant) -> Option<Duration> {
+        self.settle_at
+            .map(|settle_at| settle_at.saturating_duration_since(now))
+    }
+
+    /// whether a burst just settled, reported once
+    pub fn settled(&mut self, now: Instant) -> bool {
+        match self.settle_at {
+            Some(settle_at) if settle_at <= now => {
+                self.settle_at = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Compares the terminal size every `interval`
+pub(crate) struct SizePoller {
+    interval: Duration,
+    next_poll: Instant,
+    size: Option<(usize, usize)>,
+}
+
+impl SizePoller {
+    pub fn new(interval: Duration, now: Instant, size: Option<(usize, usize)>) -> Self {
+        Self {
+            interval,
+            next_poll: now + interval,
+            size,
+        }
+    }
+
+    /// how long till the next poll
+    pub fn timeout(&self, now: Instant) -> Duration {
+        self.next_poll.saturating_duration_since(now)
+    }
+
+    /// Poll with `terminal_size` if it is time to, return whether the size changed
+    pub fn poll(
+        &mut self,
+        now: Instant,
+        terminal_size: impl FnOnce() -> Option<(usize, usize)>,
+    ) -> bool {
+        if now < self.next_poll {
+            return false;
+        }
+        self.next_poll = now + self.interval;
+        match terminal_size() {
+            Some(size) if self.size != Some(size) => {
+                self.size = Some(size);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The size handled last and the cursor position queries left unanswered
+#[derive(Debug, Default)]
+pub(crate) struct ResizeState {
+    size: Option<(usize, usize)>,
+    unanswered_cpr: usize,
+}
+
+impl ResizeState {
+    /// record `size` as handled, return false if it already was
+    pub fn size_changed(&mut self, size: (usize, usize)) -> bool {
+        if self.size == Some(size) {
+            false
+        } else {
+            self.size = Some(size);
+            true
+        }
+    }
+
+    /// a cursor position query timed out, its reply would come as a key
+    pub fn cpr_timed_out(&mut self) {
+        self.unanswered_cpr += 1;
+    }
+
+    /// whether `key` is the late reply to a query that timed out
+    pub fn is_late_reply(&mut self, key: &Key) -> bool {
+        match key {
+            Key::CursorPos(..) if self.unanswered_cpr > 0 => {
+                self.unanswered_cpr -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn debounce() {
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let mut debouncer = ResizeDebouncer::new(ms(50));
+        assert_eq!(None, debouncer.timeout(start));
+
+        debouncer.on_signal(start);
+        debouncer.on_signal(start + ms(30));
+        assert_eq!(Some(ms(50)), debouncer.timeout(start + ms(30)));
+        assert!(!debouncer.settled(start + ms(60)));
+        assert!(debouncer.settled(start + ms(80)));
+        assert!(!debouncer.settled(start + ms(90)));
+        assert_eq!(None, debouncer.timeout(start + ms(90)));
+    }
+
+    #[test]
+    fn poll() {
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let mut poller = SizePoller::new(ms(500), start, Some((80, 24)));
+        assert_eq!(ms(400), poller.timeout(start + ms(100)));
+        assert!(!poller.poll(start + ms(100), || panic!("polled too early")));
+        assert!(!poller.poll(start + ms(500), || Some((80, 24))));
+        assert!(!poller.poll(start + ms(600), || Some((100, 24))));
+        assert!(poller.poll(start + ms(1000), || Some((100, 24))));
+        assert!(!poller.poll(start + ms(1500), || None));
+    }
+
+    #[test]
+    fn dedupe() {
+        let mut state = ResizeState::default();
+        assert!(state.size_changed((80, 24)));
+        assert!(!state.size_changed((80, 24)));
+        assert!(state.size_changed((100, 24)));
+
+        assert!(!state.is_late_reply(&Key::CursorPos(1, 1)));
+        state.cpr_timed_out();
+        assert!(!state.is_late_reply(&Key::Char('a')));
+        assert!(state.is_late_reply(&Key::CursorPos(1, 1)));
+        assert!(!state.is_late_reply(&Key::CursorPos(1, 1)));
+    }
+}
@@ -1,16 +1,132 @@
 //! Buffering screen cells and try to optimize rendering contents
-use crate::attr::Attr;
-use crate::canvas::{Canvas, Result};
+use crate::attr::{Attr, Effect};
+use crate::canvas::{sort_by_row, Canvas, PrintItem, Result};
 use crate::cell::Cell;
-use crate::output::Command;
-use std::cmp::{max, min};
-use unicode_width::UnicodeWidthChar;
+use crate::command::Command;
+use crate::width::char_width;
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::{max, min};
+use core::hash::{Hash, Hasher};
+use core::ops::Range;
 
 // much of the code comes from https://github.com/agatan/termfest/blob/master/src/screen.rs
 
+/// A fast, non-cryptographic hasher (the one used by rustc) for hashing rows of cells
+#[derive(Default)]
+struct RowHasher(u64);
+
+impl Hasher for RowHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.write_u64(u64::from(*byte));
+        }
+    }
+
+    fn write_u8(&mut self, i: u8) {
+        self.write_u64(u64::from(i));
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write_u64(u64::from(i));
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.0 = (self.0.rotate_left(5) ^ i).wrapping_mul(0x51_7c_c1_b7_27_22_0a_95);
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// How `Screen::present` finds the cells to be repainted
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum DiffStrategy {
+    /// compare every cell with the painted one
+    #[default]
+    CellByCell,
+    /// Skip the rows whose hash is unchanged, repaint the changed rows from the first to the
+    /// last changed cell in one run (fewer cursor moves). See `benches/screen.rs` for a
+    /// comparison.
+    ///
+    /// Rows that change on every frame (clocks, progress bars, logs) gain nothing from the hash,
+    /// widgets could mark them with `Canvas::mark_volatile` to be compared cell by cell.
+    RowHash,
+}
+
+/// How `Screen::highlight_row` treats a wide character straddling an end of the range
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WideCharBoundary {
+    /// highlight the whole character
+    Extend,
+    /// leave the whole character out
+    Shrink,
+}
+
+/// How the cells with the `BLINK` effect look in the "off" phase of the software blink
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BlinkStyle {
+    /// the character is hidden
+    Hide,
+    /// the colors are reversed
+    Reverse,
+}
+
+/// How a ghost cursor is drawn over its cell
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GhostStyle {
+    /// the colors of the cell are reversed, like a block cursor
+    Reverse,
+    /// the character is replaced by a bar glyph, e.g. `'▏'`, keeping the attributes
+    Bar(char),
+    /// the attributes of the cell are extended with these, see `Attr::extend`
+    Style(Attr),
+}
+
+/// An additional cursor drawn by the screen, e.g. of another user in a collaborative editor,
+/// while the terminal cursor stays at the primary position
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct GhostCursor {
+    pub row: usize,
+    pub col: usize,
+    pub style: GhostStyle,
+}
+
+impl GhostCursor {
+    pub fn new(row: usize, col: usize, style: GhostStyle) -> Self {
+        Self { row, col, style }
+    }
+}
+
+/// A cell the next `present` would write, see `Screen::pending_diff`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CellChange {
+    pub row: usize,
+    pub col: usize,
+    /// the cell as last presented
+    pub before: Cell,
+    /// the cell as it would be painted, i.e. with the software blink and the ghost cursors, an
+    /// erased cell is `Cell::empty()`
+    pub after: Cell,
+}
+
+impl CellChange {
+    /// whether the cell would look different, a cell could be written again unchanged (see
+    /// `DiffStrategy::RowHash`)
+    pub fn is_visible(&self) -> bool {
+        self.before != self.after
+    }
+}
+
 /// A Screen is a table of cells to draw on.
 /// It's a buffer holding the contents
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Screen {
     width: usize,
     height: usize,
@@ -19,6 +135,17 @@ pub struct Screen {
 
     painted_cells: Vec<Cell>,
     painted_cursor: Cursor,
+
+    diff_strategy: DiffStrategy,
+    // hash of the painted rows, `None` if unknown
+    painted_hashes: Vec<Option<u64>>,
+    // rows not worth hashing, until the next `clear`
+    volatile_rows: Vec<bool>,
+
+    software_blink: Option<BlinkStyle>,
+    blink_off: bool,
+
+    ghost_cursors: Vec<GhostCursor>,
 }
 
 impl Screen {
@@ -31,9 +158,51 @@ impl Screen {
             cursor: Cursor::default(),
             painted_cells: vec![Cell::default(); width * height],
             painted_cursor: Cursor::default(),
+            diff_strategy: DiffStrategy::default(),
+            painted_hashes: vec![None; height],
+            volatile_rows: vec![false; height],
+            software_blink: None,
+            blink_off: false,
+            ghost_cursors: Vec::new(),
         }
     }
 
+    pub fn diff_strategy(&self) -> DiffStrategy {
+        self.diff_strategy
+    }
+
+    /// change the strategy used by `present`, could be changed at any time
+    pub fn set_diff_strategy(&mut self, strategy: DiffStrategy) {
+        self.diff_strategy = strategy;
+        self.invalidate_hashes();
+    }
+
+    fn invalidate_hashes(&mut self) {
+        self.painted_hashes = vec![None; self.height];
+    }
+
+    fn row_hash(&self, row: usize) -> u64 {
+        let start = row * self.width;
+        let mut hasher = RowHasher::default();
+        self.cells[start..start + self.width].hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// the first and last column that differ from the painted contents
+    fn changed_span(&self, row: usize) -> Option<(usize, usize)> {
+        let start = row * self.width;
+        let end = start + self.width;
+        let cells = &self.cells[start..end];
+        let painted = &self.painted_cells[start..end];
+
+        let first = (0..self.width).find(|&col| cells[col] != painted[col])?;
+        let last = (first..self.width)
+            .rev()
+            .find(|&col| cells[col] != painted[col])
+            .unwrap_or(first);
+        Some((first, last))
+    }
+
     /// get the width of the screen
     #[inline]
     pub fn width(&self) -> usize {
@@ -61,25 +230,195 @@ impl Screen {
 
     fn copy_cells(&self, original: &[Cell], width: usize, height: usize) -> Vec<Cell> {
         let mut new_cells = self.empty_canvas(width, height);
-        use std::cmp;
-        let min_height = cmp::min(height, self.height);
-        let min_width = cmp::min(width, self.width);
+        let min_height = min(height, self.height);
+        let min_width = min(width, self.width);
         for row in 0..min_height {
             let orig_start = row * self.width;
             let orig_end = min_width + orig_start;
             let start = row * width;
             let end = min_width + start;
-            (&mut new_cells[start..end]).copy_from_slice(&original[orig_start..orig_end]);
+            new_cells[start..end].copy_from_slice(&original[orig_start..orig_end]);
         }
         new_cells
     }
 
+    /// the cursor position `(row, col)` if it is visible
+    pub fn cursor(&self) -> Option<(usize, usize)> {
+        if self.cursor.visible {
+            Some((self.cursor.row, self.cursor.col))
+        } else {
+            None
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn cells_mut(&mut self) -> &mut [Cell] {
+        &mut self.cells
+    }
+
+    /// Toggle `effect` of the cell at `(row, col)`, e.g. to highlight a selection on top of the
+    /// contents. Toggling again restores the cell.
+    pub fn toggle_effect(&mut self, row: usize, col: usize, effect: Effect) {
+        if let Ok(index) = self.index(row, col) {
+            self.cells[index].attr.effect.toggle(effect);
+        }
+    }
+
+    /// Extend the attributes of the cells `cols` of `row` with `attr` (see `Attr::extend`),
+    /// keeping their contents, e.g. for a selection bar. A wide character straddling an end of
+    /// the range is highlighted as a whole or left out, according to `boundary`, never half of
+    /// it. Return the columns highlighted.
+    pub fn highlight_row(
+        &mut self,
+        row: usize,
+        cols: Range<usize>,
+        attr: Attr,
+        boundary: WideCharBoundary,
+    ) -> Range<usize> {
+        let (start, end) = (cols.start, min(cols.end, self.width));
+        if row >= self.height || start >= end {
+            return start..start;
+        }
+
+        // the columns taken by the characters, a wide one is followed by a placeholder cell
+        let row_start = row * self.width;
+        let mut highlighted: Option<Range<usize>> = None;
+        let mut col = 0;
+        while col < end {
+            let width = match self.cells[row_start + col].ch {
+                '\n' | '\r' | '\t' | '\0' => 1,
+                ch => char_width(ch).clamp(1, self.width - col),
+            };
+            let (first, last) = (col, col + width);
+            let inside = start <= first && last <= end;
+            let straddling = first < end && start < last;
+            if inside || (straddling && boundary == WideCharBoundary::Extend) {
+                highlighted = Some(match highlighted {
+                    Some(range) => range.start..last,
+                    None => first..last,
+                });
+            }
+            col = last;
+        }
+
+        let highlighted = highlighted.unwrap_or(start..start);
+        for cell in &mut self.cells[row_start + highlighted.start..row_start + highlighted.end] {
+            cell.attr = cell.attr.extend(attr);
+        }
+        highlighted
+    }
+
+    /// Blink the `BLINK` cells in software (many terminals ignore the effect): the effect is
+    /// not sent to the terminal, instead the cells are painted with `style` in the "off" phase,
+    /// see `set_blink_phase`. `None` leaves the effect to the terminal.
+    pub fn set_software_blink(&mut self, style: Option<BlinkStyle>) {
+        self.software_blink = style;
+    }
+
+    pub fn software_blink(&self) -> Option<BlinkStyle> {
+        self.software_blink
+    }
+
+    /// set the phase of the software blink, the cells are repainted by the next `present`
+    pub fn set_blink_phase(&mut self, off: bool) {
+        self.blink_off = off;
+    }
+
+    /// whether any cell has the `BLINK` effect
+    pub fn has_blinking_cells(&self) -> bool {
+        self.cells
+            .iter()
+            .any(|cell| cell.attr.effect.contains(Effect::BLINK))
+    }
+
+    /// Draw `cursors` over the cells on `present`, until replaced. The cells themselves are
+    /// left intact.
+    pub fn set_ghost_cursors(&mut self, cursors: Vec<GhostCursor>) {
+        self.ghost_cursors = cursors;
+    }
+
+    pub fn add_ghost_cursor(&mut self, cursor: GhostCursor) {
+        self.ghost_cursors.push(cursor);
+    }
+
+    pub fn clear_ghost_cursors(&mut self) {
+        self.ghost_cursors.clear();
+    }
+
+    pub fn ghost_cursors(&self) -> &[GhostCursor] {
+        &self.ghost_cursors
+    }
+
+    /// Draw the ghost cursors of the area over the cells, return the original cells to be
+    /// restored
+    fn apply_ghost_cursors(
+        &mut self,
+        top: usize,
+        left: usize,
+        bottom: usize,
+        right: usize,
+    ) -> Vec<(usize, Cell)> {
+        let mut originals = Vec::new();
+        for ghost in self.ghost_cursors.iter() {
+            if !(top..bottom).contains(&ghost.row) || !(left..right).contains(&ghost.col) {
+                continue;
+            }
+            let index = ghost.row * self.width + ghost.col;
+            originals.push((index, self.cells[index]));
+            let cell = &mut self.cells[index];
+            match ghost.style {
+                GhostStyle::Reverse => cell.attr.effect.toggle(Effect::REVERSE),
+                GhostStyle::Bar(bar) => cell.ch = bar,
+                GhostStyle::Style(attr) => cell.attr = cell.attr.extend(attr),
+            }
+        }
+        originals
+    }
+
+    /// Replace the `BLINK` cells of the area by their look in the current phase, return the
+    /// original cells to be restored
+    fn apply_software_blink(
+        &mut self,
+        top: usize,
+        left: usize,
+        bottom: usize,
+        right: usize,
+    ) -> Vec<(usize, Cell)> {
+        let style = match self.software_blink {
+            Some(style) => style,
+            None => return Vec::new(),
+        };
+
+        let mut originals = Vec::new();
+        for row in top..bottom {
+            for col in left..right {
+                let index = row * self.width + col;
+                let cell = self.cells[index];
+                if !cell.attr.effect.contains(Effect::BLINK) {
+                    continue;
+                }
+                originals.push((index, cell));
+                let blinked = &mut self.cells[index];
+                blinked.attr.effect.remove(Effect::BLINK);
+                if self.blink_off {
+                    match style {
+                        BlinkStyle::Hide => blinked.ch = ' ',
+                        BlinkStyle::Reverse => blinked.attr.effect.toggle(Effect::REVERSE),
+                    }
+                }
+            }
+        }
+        originals
+    }
+
     /// to resize the screen to `(width, height)`
     pub fn resize(&mut self, width: usize, height: usize) {
         self.cells = self.copy_cells(&self.cells, width, height);
         self.painted_cells = self.empty_canvas(width, height);
         self.width = width;
         self.height = height;
+        self.invalidate_hashes();
+        self.volatile_rows = vec![false; height];
 
         self.cursor.row = min(self.cursor.row, height);
         self.cursor.col = min(self.cursor.col, width);
@@ -87,6 +426,25 @@ impl Screen {
 
     /// sync internal buffer with the terminal
     pub fn present(&mut self) -> Vec<Command> {
+        self.present_area(0, 0, self.width, self.height)
+    }
+
+    /// Like `present`, but only the cells in the `width` x `height` area at `(top, left)` are
+    /// synced, e.g. to refresh a busy panel more often than the rest of the screen. Row hashes
+    /// are only used for areas spanning whole rows.
+    pub fn present_area(
+        &mut self,
+        top: usize,
+        left: usize,
+        width: usize,
+        height: usize,
+    ) -> Vec<Command> {
+        let bottom = min(top + height, self.height);
+        let right = min(left + width, self.width);
+        let whole_rows = left == 0 && right == self.width;
+        let mut originals = self.apply_software_blink(top, left, bottom, right);
+        originals.extend(self.apply_ghost_cursors(top, left, bottom, right));
+
         let mut commands = Vec::with_capacity(2048);
         let default_attr = Attr::default();
         let mut last_attr = default_attr;
@@ -98,23 +456,40 @@ impl Screen {
 
         let mut last_cursor = Cursor::default();
 
-        for row in 0..self.height {
-            // calculate the last col that has contents
-            let mut empty_col_index = 0;
-            for col in (0..self.width).rev() {
-                let index = self.index(row, col).unwrap();
-                let cell = &self.cells[index];
-                if cell.is_empty() {
-                    self.painted_cells[index] = *cell;
-                } else {
-                    empty_col_index = col + 1;
-                    break;
+        for row in top..bottom {
+            // cells in `force_span` are painted even if they are not changed
+            let mut force_span = None;
+            let mut row_hash = None;
+            if self.diff_strategy == DiffStrategy::RowHash && !self.volatile_rows[row] && whole_rows
+            {
+                let hash = self.row_hash(row);
+                if self.painted_hashes[row] == Some(hash) {
+                    continue;
+                }
+                row_hash = Some(hash);
+                force_span = self.changed_span(row);
+            }
+
+            // calculate the last col that has contents, the trailing empty cells are erased
+            // at once. Not possible for partial rows, their empty cells are compared as well.
+            let mut empty_col_index = right;
+            if whole_rows {
+                empty_col_index = 0;
+                for col in (0..self.width).rev() {
+                    let index = self.index(row, col).unwrap();
+                    let cell = &self.cells[index];
+                    if cell.is_empty() {
+                        self.painted_cells[index] = *cell;
+                    } else {
+                        empty_col_index = col + 1;
+                        break;
+                    }
                 }
             }
 
             // compare cells and print necessary escape codes
             let mut last_ch_is_wide = false;
-            for col in 0..empty_col_index {
+            for col in left..empty_col_index {
                 let index = self.index(row, col).unwrap();
 
                 // advance if the last character is wide
@@ -128,7 +503,8 @@ impl Screen {
                 let cell_painted = self.painted_cells[index];
 
                 // no need to paint if the content did not change
-                if cell_to_paint == cell_painted {
+                let forced = force_span.is_some_and(|(first, last)| first <= col && col <= last);
+                if cell_to_paint == cell_painted && !forced {
                     continue;
                 }
 
@@ -153,7 +529,11 @@ impl Screen {
                     }
                 }
 
-                let display_width = cell_to_paint.ch.width().unwrap_or(2);
+                // the control chars are painted as a space
+                let display_width = match cell_to_paint.ch {
+                    '\n' | '\r' | '\t' | '\0' => 1,
+                    ch => char_width(ch),
+                };
 
                 // wide character
                 if display_width == 2 {
@@ -165,7 +545,7 @@ impl Screen {
                 self.painted_cells[index] = cell_to_paint;
             }
 
-            if empty_col_index != self.width {
+            if whole_rows && empty_col_index != self.width {
                 commands.push(Command::CursorGoto {
                     row,
                     col: empty_col_index,
@@ -174,6 +554,9 @@ impl Screen {
                 commands.push(Command::EraseEndOfLine);
                 last_attr = Attr::default();
             }
+
+            // `None` for volatile rows, they are hashed again once no longer volatile
+            self.painted_hashes[row] = row_hash;
         }
 
         // restore cursor
@@ -186,10 +569,74 @@ impl Screen {
         }
 
         self.painted_cursor = self.cursor;
+        for (index, cell) in originals.into_iter().rev() {
+            self.cells[index] = cell;
+        }
 
         commands
     }
 
+    /// The cells the next `present` would write, without writing them, e.g. to check that an
+    /// update is minimal or to skip notifying when nothing changed. The erased cells are only
+    /// reported if they were not blank.
+    ///
+    /// ```
+    /// use tuikit::canvas::Canvas;
+    /// use tuikit::screen::Screen;
+    ///
+    /// let mut screen = Screen::new(10, 1);
+    /// screen.print(0, 0, "abc").unwrap();
+    /// screen.present();
+    /// screen.print(0, 1, "x").unwrap();
+    /// let changes = screen.pending_diff();
+    /// assert_eq!(1, changes.len());
+    /// assert_eq!(('b', 'x'), (changes[0].before.ch, changes[0].after.ch));
+    /// ```
+    pub fn pending_diff(&self) -> Vec<CellChange> {
+        let mut dry_run = self.clone();
+        let mut changes = Vec::new();
+        let (mut row, mut col) = (0, 0);
+        let mut attr = Attr::default();
+        for command in dry_run.present() {
+            match command {
+                Command::CursorGoto {
+                    row: to_row,
+                    col: to_col,
+                } => {
+                    row = to_row;
+                    col = to_col;
+                }
+                Command::ResetAttributes => attr = Attr::default(),
+                Command::SetAttribute(to_attr) => attr = to_attr,
+                Command::PutChar(ch) => {
+                    let index = row * self.width + col;
+                    changes.push(CellChange {
+                        row,
+                        col,
+                        before: self.painted_cells[index],
+                        after: Cell { ch, attr },
+                    });
+                    col += char_width(ch);
+                }
+                Command::EraseEndOfLine => {
+                    for col in col..self.width {
+                        let before = self.painted_cells[row * self.width + col];
+                        if !before.is_empty() {
+                            changes.push(CellChange {
+                                row,
+                                col,
+                                before,
+                                after: Cell::empty(),
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        changes
+    }
+
     /// ```
     /// use tuikit::cell::Cell;
     /// use tuikit::canvas::Canvas;
@@ -202,12 +649,12 @@ impl Screen {
     /// assert_eq!(Some((0, 0, &Cell{ ch: 'a', ..Cell::default()})), iter.next());
     /// assert_eq!(None, iter.next());
     /// ```
-    pub fn iter_cell(&self) -> CellIterator {
-        return CellIterator {
+    pub fn iter_cell(&self) -> CellIterator<'_> {
+        CellIterator {
             width: self.width,
             index: 0,
             vec: &self.cells,
-        };
+        }
     }
 }
 
@@ -217,17 +664,20 @@ impl Canvas for Screen {
         Ok((self.width(), self.height()))
     }
 
-    /// clear the screen buffer
+    /// clear the screen buffer and the volatile marks
     fn clear(&mut self) -> Result<()> {
         for cell in self.cells.iter_mut() {
             *cell = Cell::empty();
         }
+        for volatile in self.volatile_rows.iter_mut() {
+            *volatile = false;
+        }
         Ok(())
     }
 
     /// change a cell of position `(row, col)` to `cell`
     fn put_cell(&mut self, row: usize, col: usize, cell: Cell) -> Result<usize> {
-        let ch_width = cell.ch.width().unwrap_or(2);
+        let ch_width = char_width(cell.ch);
         if ch_width > 1 {
             let _ = self.index(row, col + 1).map(|index| {
                 self.cells[index - 1] = cell;
@@ -254,6 +704,40 @@ impl Canvas for Screen {
         self.cursor.visible = show;
         Ok(())
     }
+
+    fn mark_volatile(&mut self, row: usize) -> Result<()> {
+        if let Some(volatile) = self.volatile_rows.get_mut(row) {
+            *volatile = true;
+        }
+        Ok(())
+    }
+
+    /// writes the cells directly, the same ones as `put_cell` would
+    fn print_batch_slice(&mut self, batch: &mut [PrintItem]) -> Result<()> {
+        sort_by_row(batch);
+        for &(row, col, text, attr) in batch.iter() {
+            if row >= self.height {
+                // sorted, the rest is out of bound too
+                break;
+            }
+            let start = row * self.width;
+            let mut col = col;
+            for ch in text.chars() {
+                if col >= self.width {
+                    break;
+                }
+                let ch_width = char_width(ch);
+                if ch_width <= 1 {
+                    self.cells[start + col] = Cell { ch, attr };
+                } else if col + 1 < self.width {
+                    self.cells[start + col] = Cell { ch, attr };
+                    self.cells[start + col + 1].ch = ' ';
+                }
+                col += ch_width;
+            }
+        }
+        Ok(())
+    }
 }
 
 pub struct CellIterator<'a> {
@@ -277,26 +761,100 @@ impl<'a> Iterator for CellIterator<'a> {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default)]
 struct Cursor {
     pub row: usize,
     pub col: usize,
     visible: bool,
 }
 
-impl Default for Cursor {
-    fn default() -> Self {
-        Self {
-            row: 0,
-            col: 0,
-            visible: false,
-        }
-    }
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::attr::Color;
+    use crate::canvas::{Align, BoundedCanvas, ColumnSpec};
+
+    #[test]
+    fn print_batch_like_print() {
+        let bold = Attr::from(Effect::BOLD);
+        let batch = vec![
+            (1, 0, "second row", bold),
+            (0, 0, "abcdef", Attr::default()),
+            (0, 2, "XY", bold),
+            (0, 6, "wide中文", bold),
+            (5, 0, "out of bound", bold),
+            (1, 7, "中", bold),
+        ];
+
+        let mut printed = Screen::new(9, 3);
+        for &(row, col, text, attr) in batch.iter() {
+            printed.print_with_attr(row, col, text, attr).unwrap();
+        }
+        let mut batched = Screen::new(9, 3);
+        batched.print_batch(batch.iter().copied()).unwrap();
+        assert_eq!(printed.cells, batched.cells);
+
+        // through a bounded canvas, as a trait object
+        let mut printed = Screen::new(9, 3);
+        let mut batched = Screen::new(9, 3);
+        {
+            let mut canvas = BoundedCanvas::new(1, 2, 5, 1, &mut printed);
+            for &(row, col, text, attr) in batch.iter() {
+                canvas.print_with_attr(row, col, text, attr).unwrap();
+            }
+        }
+        let mut canvas = BoundedCanvas::new(1, 2, 5, 1, &mut batched);
+        let canvas: &mut dyn Canvas = &mut canvas;
+        canvas.print_batch_slice(&mut batch.clone()).unwrap();
+        assert_eq!(printed.cells, batched.cells);
+    }
+
+    #[test]
+    fn highlight_row_with_wide_chars() {
+        let mut screen = Screen::new(8, 1);
+        // a中b文 then two empty cells
+        screen.print(0, 0, "a中b文").unwrap();
+        let bar = Attr::default().bg(Color::BLUE);
+        let highlighted = |screen: &Screen| -> Vec<bool> {
+            (0..8)
+                .map(|col| screen.cells[col].attr.bg == Color::BLUE)
+                .collect()
+        };
+
+        // the range starts in the middle of 中 and ends in the middle of 文
+        assert_eq!(
+            1..6,
+            screen.highlight_row(0, 2..5, bar, WideCharBoundary::Extend)
+        );
+        assert_eq!(
+            vec![false, true, true, true, true, true, false, false],
+            highlighted(&screen)
+        );
+        assert_eq!('中', screen.cells[1].ch);
+
+        let mut screen = Screen::new(8, 1);
+        screen.print(0, 0, "a中b文").unwrap();
+        assert_eq!(
+            3..4,
+            screen.highlight_row(0, 2..5, bar, WideCharBoundary::Shrink)
+        );
+        assert_eq!(
+            2..2,
+            screen.highlight_row(0, 2..3, bar, WideCharBoundary::Shrink)
+        );
+        assert_eq!(
+            6..8,
+            screen.highlight_row(0, 6..20, bar, WideCharBoundary::Shrink)
+        );
+        assert_eq!(
+            vec![false, false, false, true, false, false, true, true],
+            highlighted(&screen)
+        );
+        assert_eq!(
+            0..0,
+            screen.highlight_row(1, 0..8, bar, WideCharBoundary::Extend)
+        );
+    }
 
     #[test]
     fn test_cell_iterator() {
@@ -385,4 +943,205 @@ mod test {
         let mut empty_iter = empty_screen.iter_cell();
         assert_eq!(None, empty_iter.next());
     }
+
+    fn put_chars(commands: &[Command]) -> String {
+        commands
+            .iter()
+            .filter_map(|cmd| match cmd {
+                Command::PutChar(ch) => Some(*ch),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn row_hash_should_skip_unchanged_rows() {
+        let mut screen = Screen::new(6, 2);
+        screen.set_diff_strategy(DiffStrategy::RowHash);
+        let _ = screen.print(0, 0, "abcdef");
+        let _ = screen.print(1, 0, "ghijkl");
+        assert_eq!("abcdefghijkl", put_chars(&screen.present()));
+
+        // nothing changed
+        let commands = screen.present();
+        assert_eq!("", put_chars(&commands));
+        assert!(!commands
+            .iter()
+            .any(|cmd| matches!(cmd, Command::EraseEndOfLine)));
+
+        // the span between the first and last changed cells is repainted
+        let _ = screen.print(1, 1, "H");
+        let _ = screen.print(1, 3, "J");
+        assert_eq!("HiJ", put_chars(&screen.present()));
+    }
+
+    #[test]
+    fn pending_diff_should_not_write() {
+        let mut screen = Screen::new(6, 2);
+        screen.set_diff_strategy(DiffStrategy::RowHash);
+        let _ = screen.clear();
+        let _ = screen.print(0, 0, "abcdef");
+        let _ = screen.print(1, 0, "ghijk");
+        let _ = screen.present();
+        assert!(screen.pending_diff().is_empty());
+
+        // the unchanged `h` between the changed cells is written again, `jk` are erased
+        let _ = screen.print(1, 0, "G");
+        let _ = screen.print(1, 2, "I");
+        screen.cells[9] = Cell::empty();
+        screen.cells[10] = Cell::empty();
+        let diff: Vec<_> = screen
+            .pending_diff()
+            .iter()
+            .map(|change| (change.col, change.after.ch, change.is_visible()))
+            .collect();
+        assert_eq!(
+            vec![
+                (0, 'G', true),
+                (1, 'h', false),
+                (2, 'I', true),
+                (3, '\0', true),
+                (4, '\0', true)
+            ],
+            diff
+        );
+        assert_eq!(5, screen.pending_diff().len());
+        assert_eq!("GhI", put_chars(&screen.present()));
+    }
+
+    #[test]
+    fn volatile_rows_should_be_compared_cell_by_cell() {
+        let mut screen = Screen::new(6, 2);
+        screen.set_diff_strategy(DiffStrategy::RowHash);
+        let _ = screen.print(1, 0, "ghijkl");
+        let _ = screen.mark_volatile(1);
+        assert_eq!("ghijkl", put_chars(&screen.present()));
+
+        let _ = screen.print(1, 1, "H");
+        let _ = screen.print(1, 3, "J");
+        assert_eq!("HJ", put_chars(&screen.present()));
+        assert_eq!(None, screen.painted_hashes[1]);
+    }
+
+    #[test]
+    fn present_area_should_only_sync_the_area() {
+        let mut screen = Screen::new(6, 3);
+        let _ = screen.print(0, 0, "abcdef");
+        let _ = screen.print(1, 0, "ghijkl");
+        assert_eq!("bcd", put_chars(&screen.present_area(0, 1, 3, 1)));
+        assert_eq!("ghijkl", put_chars(&screen.present_area(1, 0, 6, 1)));
+
+        // empty cells of a partial row are painted as spaces
+        let _ = screen.clear();
+        assert_eq!("  ", put_chars(&screen.present_area(0, 1, 2, 1)));
+        assert_eq!("", put_chars(&screen.present()));
+    }
+
+    #[test]
+    fn ghost_cursors_are_drawn_over_the_cells() {
+        let mut screen = Screen::new(4, 1);
+        let _ = screen.print(0, 0, "abcd");
+        let _ = screen.present();
+
+        screen.set_ghost_cursors(vec![
+            GhostCursor::new(0, 1, GhostStyle::Reverse),
+            GhostCursor::new(0, 3, GhostStyle::Bar('|')),
+            GhostCursor::new(1, 0, GhostStyle::Reverse),
+        ]);
+        let commands = screen.present();
+        assert_eq!("b|", put_chars(&commands));
+        assert!(commands.iter().any(|cmd| matches!(
+            cmd,
+            Command::SetAttribute(attr) if attr.effect.contains(Effect::REVERSE)
+        )));
+        // the cells are intact
+        assert_eq!('d', screen.cells[3].ch);
+        assert_eq!(Attr::default(), screen.cells[1].attr);
+
+        // moved away: the old positions are repainted
+        screen.set_ghost_cursors(vec![GhostCursor::new(0, 0, GhostStyle::Reverse)]);
+        assert_eq!("abd", put_chars(&screen.present()));
+        assert_eq!("", put_chars(&screen.present()));
+    }
+
+    #[test]
+    fn software_blink_should_repaint_on_phase_change() {
+        let mut screen = Screen::new(4, 1);
+        screen.set_software_blink(Some(BlinkStyle::Hide));
+        let blink = Attr::default().effect(Effect::BLINK);
+        let _ = screen.print_with_attr(0, 0, "ab", blink);
+        let _ = screen.print(0, 2, "cd");
+        assert!(screen.has_blinking_cells());
+
+        let commands = screen.present();
+        assert_eq!("abcd", put_chars(&commands));
+        assert!(!commands.iter().any(|cmd| matches!(
+            cmd,
+            Command::SetAttribute(attr) if attr.effect.contains(Effect::BLINK)
+        )));
+
+        screen.set_blink_phase(true);
+        assert_eq!("  ", put_chars(&screen.present()));
+        assert_eq!("", put_chars(&screen.present()));
+        screen.set_blink_phase(false);
+        assert_eq!("ab", put_chars(&screen.present()));
+        assert!(screen.has_blinking_cells());
+    }
+
+    #[test]
+    fn strategies_should_paint_the_same_contents() {
+        let mut cell_by_cell = Screen::new(5, 3);
+        let mut row_hash = Screen::new(5, 3);
+        row_hash.set_diff_strategy(DiffStrategy::RowHash);
+
+        for (row, text) in ["hello", "中文", "x"].iter().enumerate() {
+            for screen in [&mut cell_by_cell, &mut row_hash].iter_mut() {
+                let _ = screen.clear();
+                let _ = screen.print(row, 0, text);
+                let _ = screen.present();
+            }
+            assert_eq!(cell_by_cell.painted_cells, row_hash.painted_cells);
+        }
+    }
+
+    fn row_text(screen: &Screen, row: usize) -> String {
+        let cells = &screen.cells[row * screen.width..(row + 1) * screen.width];
+        cells.iter().map(|cell| cell.ch).collect()
+    }
+
+    #[test]
+    fn print_columns_should_align_and_truncate() {
+        let bold = Attr::from(Effect::BOLD);
+        let specs = [
+            ColumnSpec::new(4),
+            ColumnSpec::new(5).align(Align::Right).padding(1),
+            ColumnSpec::new(4).align(Align::Center).attr(bold),
+        ];
+
+        let mut screen = Screen::new(14, 2);
+        screen.print(0, 0, "xxxxxxxxxxxxxx").unwrap();
+        let width = screen
+            .print_columns(0, &specs, &["name", "7", "ok"])
+            .unwrap();
+        assert_eq!(13, width);
+        assert_eq!("name   7  ok x", row_text(&screen, 0));
+        assert_eq!(bold, screen.cells[9].attr);
+
+        // a wide character which doesn't fit is left out
+        screen
+            .print_columns(1, &specs, &["中文字", "1234"])
+            .unwrap();
+        // the trailing cell of a wide character is a space
+        assert_eq!("中 文  123      ", row_text(&screen, 1));
+    }
+
+    #[test]
+    fn print_with_tabs_should_move_to_the_tab_stops() {
+        let mut screen = Screen::new(12, 1);
+        let width = screen
+            .print_with_tabs(0, 1, "ab\tc\t\td", Attr::default(), 4)
+            .unwrap();
+        assert_eq!(13, width);
+        assert_eq!(" ab  c      ", row_text(&screen, 0));
+    }
 }
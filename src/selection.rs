@@ -0,0 +1,391 @@
+//! Library-managed text selection with the mouse.
+//!
+//! With mouse reporting on, terminals no longer select text on drag. `MouseSelector` tracks the
+//! drag itself: a left-button drag selects the cells in between (as a stream of text, like a
+//! terminal does), and on release the selected text is handed over for copying, e.g. to the
+//! system clipboard (see `tuikit::clipboard`). A double click selects a word and a triple click
+//! a line, dragging on from them extends the selection by words or lines.
+//!
+//! `Term::enable_mouse_selection` wires it into the event loop, see `CopyTarget`. The double
+//! and triple clicks are only reported with `TermOptions::click_interval` set.
+//!
+//! `find_links` turns the URLs and file paths shown on the screen into regions, which
+//! `Term::enable_link_detection` opens on `Ctrl` + click.
+
+pub use crate::clipboard::osc52;
+use crate::clipboard::Clipboard;
+use crate::hints::scan_screen;
+use crate::key::{Key, MouseButton};
+use crate::region::Region;
+use crate::screen::Screen;
+use crate::width::char_width;
+use crate::word::WordClass;
+
+/// The name of the regions returned by `find_links`
+pub const LINK_REGION: &str = "link";
+
+/// Opens a link found on the screen, see `Term::enable_link_detection`
+pub type LinkOpener = Box<dyn Fn(&str) + Send>;
+
+/// Where `Term` copies the selected text to on mouse release
+pub enum CopyTarget {
+    /// the system clipboard, through the OSC 52 escape sequence of the terminal
+    Osc52,
+    /// the clipboard of `Term`, detected on start, see `Term::set_clipboard`
+    Clipboard,
+    /// a clipboard of its own
+    Provider(Box<dyn Clipboard>),
+    /// let the application handle the copied text
+    Callback(Box<dyn Fn(&str) + Send>),
+}
+
+/// A selection between two screen positions `(row, col)`, both included
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    /// where the drag started
+    pub anchor: (usize, usize),
+    /// where the drag is now
+    pub head: (usize, usize),
+}
+
+impl Selection {
+    pub fn new(anchor: (usize, usize), head: (usize, usize)) -> Self {
+        Self { anchor, head }
+    }
+
+    /// the start and end positions in reading order
+    pub fn range(&self) -> ((usize, usize), (usize, usize)) {
+        if self.anchor <= self.head {
+            (self.anchor, self.head)
+        } else {
+            (self.head, self.anchor)
+        }
+    }
+
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        let (start, end) = self.range();
+        start <= (row, col) && (row, col) <= end
+    }
+
+    /// The selected text of `screen`, lines are joined by `\n` and their trailing spaces are
+    /// removed
+    pub fn text(&self, screen: &Screen) -> String {
+        let ((start_row, start_col), (end_row, end_col)) = self.range();
+        let mut lines: Vec<String> = Vec::new();
+        for row in start_row..=end_row.min(screen.height().saturating_sub(1)) {
+            let mut line = String::new();
+            for (col, (ch, right_half)) in row_chars(screen, row).into_iter().enumerate() {
+                let selected =
+                    (row > start_row || col >= start_col) && (row < end_row || col <= end_col);
+                if selected && !right_half && ch != '\0' {
+                    line.push(ch);
+                }
+            }
+            lines.push(line);
+        }
+
+        lines
+            .iter()
+            .map(|line| line.trim_end())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// What a selection is extended by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Unit {
+    #[default]
+    Cell,
+    Word,
+    Line,
+}
+
+/// Track the mouse events to build a selection
+#[derive(Debug, Default)]
+pub struct MouseSelector {
+    pressed: Option<(usize, usize)>,
+    selection: Option<Selection>,
+    unit: Unit,
+    word_class: WordClass,
+}
+
+/// What happened to the selection after a mouse event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionUpdate {
+    /// the event doesn't concern the selection
+    Unchanged,
+    /// the selection is being dragged or was cleared
+    Changed,
+    /// the mouse was released, the selection should be copied
+    Finished(Selection),
+}
+
+impl MouseSelector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// what a double click selects, `WordClass::Shell` by default
+    pub fn word_class(mut self, word_class: WordClass) -> Self {
+        self.word_class = word_class;
+        self
+    }
+
+    /// the current selection, if any
+    pub fn selection(&self) -> Option<Selection> {
+        self.selection
+    }
+
+    /// drop the selection, return `true` if there was one
+    pub fn clear(&mut self) -> bool {
+        self.pressed = None;
+        self.selection.take().is_some()
+    }
+
+    /// Handle the mouse event `key` on `screen`, mouse coordinates are 1-based and `row_offset`
+    /// is the first terminal row of the screen (i.e. non-fullscreen mode).
+    pub fn on_key(&mut self, key: &Key, row_offset: usize, screen: &Screen) -> SelectionUpdate {
+        let position = |x, y| mouse_position(x, y, row_offset);
+
+        match *key {
+            Key::MousePress(MouseButton::Left, x, y, _) => {
+                self.pressed = Some(position(x, y));
+                self.unit = Unit::Cell;
+                if self.selection.take().is_some() {
+                    SelectionUpdate::Changed
+                } else {
+                    SelectionUpdate::Unchanged
+                }
+            }
+            Key::DoubleClick(x, y) | Key::TripleClick(x, y) => {
+                let anchor = position(x, y);
+                self.pressed = Some(anchor);
+                self.unit = match key {
+                    Key::DoubleClick(..) => Unit::Word,
+                    _ => Unit::Line,
+                };
+                self.selection = Some(self.snap(anchor, anchor, screen));
+                SelectionUpdate::Changed
+            }
+            Key::MouseHold(x, y, _) => match self.pressed {
+                Some(anchor) => {
+                    self.selection = Some(self.snap(anchor, position(x, y), screen));
+                    SelectionUpdate::Changed
+                }
+                None => SelectionUpdate::Unchanged,
+            },
+            Key::MouseRelease(MouseButton::Left, x, y, _) => {
+                match (self.pressed.take(), self.selection) {
+                    (Some(anchor), Some(_)) => {
+                        let selection = self.snap(anchor, position(x, y), screen);
+                        self.selection = Some(selection);
+                        SelectionUpdate::Finished(selection)
+                    }
+                    _ => SelectionUpdate::Unchanged,
+                }
+            }
+            _ => SelectionUpdate::Unchanged,
+        }
+    }
+
+    /// the selection from `anchor` to `head`, extended to whole words or lines
+    fn snap(&self, anchor: (usize, usize), head: (usize, usize), screen: &Screen) -> Selection {
+        let ((start_row, start_col), (end_row, end_col)) = Selection::new(anchor, head).range();
+        match self.unit {
+            Unit::Cell => Selection::new(anchor, head),
+            Unit::Word => {
+                let chars = |row| -> Vec<char> {
+                    row_chars(screen, row)
+                        .into_iter()
+                        .map(|(ch, _)| ch)
+                        .collect()
+                };
+                let start = chars(start_row);
+                let start_col = self
+                    .word_class
+                    .word_at(&start, start_col)
+                    .map_or(start_col, |(start, _)| start);
+                let end = chars(end_row);
+                let end_col = self
+                    .word_class
+                    .word_at(&end, end_col)
+                    .map_or(end_col, |(_, end)| end - 1);
+                Selection::new((start_row, start_col), (end_row, end_col))
+            }
+            Unit::Line => {
+                Selection::new((start_row, 0), (end_row, screen.width().saturating_sub(1)))
+            }
+        }
+    }
+}
+
+/// The screen position `(row, col)` of the 1-based mouse coordinates
+pub(crate) fn mouse_position(x: u16, y: u16, row_offset: usize) -> (usize, usize) {
+    (
+        (y as usize).saturating_sub(1 + row_offset),
+        (x as usize).saturating_sub(1),
+    )
+}
+
+/// The chars of `row`, one per column, and whether the column is the right half of a wide
+/// char, which repeats it
+fn row_chars(screen: &Screen, row: usize) -> Vec<(char, bool)> {
+    let mut chars: Vec<(char, bool)> = Vec::with_capacity(screen.width());
+    let mut wide = None;
+    for (_, _, cell) in screen
+        .iter_cell()
+        .filter(|(cell_row, _, _)| *cell_row == row)
+    {
+        match wide.take() {
+            Some(ch) => chars.push((ch, true)),
+            None => {
+                if char_width(cell.ch) > 1 {
+                    wide = Some(cell.ch);
+                }
+                chars.push((cell.ch, false));
+            }
+        }
+    }
+    chars
+}
+/// The URLs and file paths shown on `screen` as regions named `LINK_REGION`, the payload is the
+/// text found
+///
+/// ```
+/// use tuikit::canvas::Canvas;
+/// use tuikit::screen::Screen;
+/// use tuikit::selection::find_links;
+///
+/// let mut screen = Screen::new(30, 1);
+/// screen.print(0, 0, "see https://a.io").unwrap();
+/// assert_eq!("https://a.io", find_links(&screen)[0].payload);
+/// ```
+pub fn find_links(screen: &Screen) -> Vec<Region> {
+    scan_screen(screen)
+        .into_iter()
+        .map(|target| {
+            Region::new(LINK_REGION, target.row, target.col, target.width, 1)
+                .payload(&target.payload)
+        })
+        .collect()
+}
+
+/// The link shown at `(row, col)` of `screen`, if any
+pub fn link_at(screen: &Screen, row: usize, col: usize) -> Option<Region> {
+    find_links(screen)
+        .into_iter()
+        .find(|region| region.contains(row, col))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::canvas::Canvas;
+    use crate::key::Modifiers;
+
+    #[test]
+    fn drag_and_release() {
+        let screen = Screen::new(4, 3);
+        let mut selector = MouseSelector::new();
+        let press = Key::MousePress(MouseButton::Left, 3, 2, Modifiers::empty());
+        assert_eq!(
+            SelectionUpdate::Unchanged,
+            selector.on_key(&press, 0, &screen)
+        );
+        assert_eq!(
+            SelectionUpdate::Changed,
+            selector.on_key(&Key::MouseHold(1, 1, Modifiers::empty()), 0, &screen)
+        );
+        assert_eq!(
+            SelectionUpdate::Finished(Selection::new((1, 2), (0, 1))),
+            selector.on_key(
+                &Key::MouseRelease(MouseButton::Left, 2, 1, Modifiers::empty()),
+                0,
+                &screen
+            )
+        );
+
+        // a click without drag clears the selection
+        assert_eq!(
+            SelectionUpdate::Changed,
+            selector.on_key(&press, 0, &screen)
+        );
+        assert_eq!(
+            SelectionUpdate::Unchanged,
+            selector.on_key(
+                &Key::MouseRelease(MouseButton::Left, 3, 2, Modifiers::empty()),
+                0,
+                &screen
+            )
+        );
+        assert_eq!(None, selector.selection());
+    }
+
+    #[test]
+    fn selected_text() {
+        let mut screen = Screen::new(6, 3);
+        screen.print(0, 0, "hello").unwrap();
+        screen.print(1, 0, "big").unwrap();
+        screen.print(2, 0, "world!").unwrap();
+
+        let selection = Selection::new((2, 4), (0, 3));
+        assert!(selection.contains(1, 5));
+        assert!(!selection.contains(2, 5));
+        assert_eq!("lo\nbig\nworld", selection.text(&screen));
+    }
+
+    #[test]
+    fn snap_to_words_and_lines() {
+        let mut screen = Screen::new(20, 2);
+        screen.print(0, 0, "vim src/名前.rs").unwrap();
+        screen.print(1, 0, "cargo test").unwrap();
+        let mut selector = MouseSelector::new();
+        let release = |x, y| Key::MouseRelease(MouseButton::Left, x, y, Modifiers::empty());
+
+        // the wide chars span two columns each
+        selector.on_key(&Key::DoubleClick(11, 1), 0, &screen);
+        assert_eq!(
+            SelectionUpdate::Finished(Selection::new((0, 8), (0, 14))),
+            selector.on_key(&release(11, 1), 0, &screen)
+        );
+
+        // dragging on extends by words
+        selector.on_key(&Key::DoubleClick(2, 1), 0, &screen);
+        selector.on_key(&Key::MouseHold(3, 2, Modifiers::empty()), 0, &screen);
+        let selection = selector.selection().unwrap();
+        assert_eq!("vim src/名前.rs\ncargo", selection.text(&screen));
+
+        // a triple click selects the line, a click starts over
+        selector.on_key(&Key::TripleClick(3, 2), 0, &screen);
+        match selector.on_key(&release(3, 2), 0, &screen) {
+            SelectionUpdate::Finished(selection) => {
+                assert_eq!("cargo test", selection.text(&screen))
+            }
+            update => panic!("unexpected {:?}", update),
+        }
+        let press = Key::MousePress(MouseButton::Left, 3, 2, Modifiers::empty());
+        selector.on_key(&press, 0, &screen);
+        assert_eq!(
+            SelectionUpdate::Unchanged,
+            selector.on_key(&release(3, 2), 0, &screen)
+        );
+    }
+
+    #[test]
+    fn links() {
+        let mut screen = Screen::new(40, 1);
+        screen
+            .print(0, 0, "at https://a.io/x or src/lib.rs")
+            .unwrap();
+
+        let links = find_links(&screen);
+        assert_eq!(2, links.len());
+        assert_eq!(
+            Region::new(LINK_REGION, 0, 3, 14, 1).payload("https://a.io/x"),
+            links[0]
+        );
+        assert_eq!("src/lib.rs", link_at(&screen, 0, 25).unwrap().payload);
+        assert_eq!(None, link_at(&screen, 0, 18));
+    }
+}
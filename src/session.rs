@@ -0,0 +1,397 @@
+//! Save the UI state at exit and restore it on the next start.
+//!
+//! A `StateStore` keeps text values by key. The widgets (or the application for its own state)
+//! implement `Persist` to write their state under a key of their own, e.g. the ratio of a
+//! splitter, the focused widget or the command history, and read it back on start, so users
+//! find the application as they left it.
+//!
+//! Tuikit implements it for `SplitterState`, `FocusRing`, `ListView` (the selection and the
+//! scroll offset) and `CommandPalette` (the commands executed).
+//!
+//! ```no_run
+//! use tuikit::container::{SplitterDirection, SplitterState};
+//! use tuikit::focus::FocusRing;
+//! use tuikit::session::{FileStateStore, Persist, StateStore};
+//!
+//! let mut store = FileStateStore::open(FileStateStore::default_path("myapp").unwrap());
+//! let mut splitter = SplitterState::new(SplitterDirection::Horizontal);
+//! let mut focus = FocusRing::new(&["list", "preview"]);
+//! splitter.restore("main.splitter", &store);
+//! focus.restore("focus", &store);
+//! let mut history = store.load_list("history");
+//!
+//! // ... run the application
+//! history.push("last command".to_string());
+//!
+//! splitter.save("main.splitter", &mut store);
+//! focus.save("focus", &mut store);
+//! store.save_list("history", &history);
+//! store.flush().unwrap();
+//! ```
+
+use crate::container::SplitterState;
+use crate::focus::FocusRing;
+use crate::widget::{CommandPalette, ListView};
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Text values by key
+pub trait StateStore {
+    fn load(&self, key: &str) -> Option<String>;
+
+    fn save(&mut self, key: &str, value: &str);
+
+    fn remove(&mut self, key: &str);
+
+    /// the value of `key` parsed, `None` if missing or invalid
+    fn load_parsed<T: FromStr>(&self, key: &str) -> Option<T>
+    where
+        Self: Sized,
+    {
+        self.load(key).and_then(|value| value.parse().ok())
+    }
+
+    fn save_display(&mut self, key: &str, value: &impl Display)
+    where
+        Self: Sized,
+    {
+        self.save(key, &value.to_string());
+    }
+
+    /// the lines saved by `save_list`, empty if missing
+    fn load_list(&self, key: &str) -> Vec<String>
+    where
+        Self: Sized,
+    {
+        match self.load(key) {
+            Some(value) if !value.is_empty() => value.split('\n').map(str::to_string).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Save `items` (e.g. a command history) as lines, the items should not contain newlines
+    fn save_list(&mut self, key: &str, items: &[String])
+    where
+        Self: Sized,
+    {
+        self.save(key, &items.join("\n"));
+    }
+}
+
+/// Something whose state survives the application, see the module doc
+pub trait Persist {
+    /// write the state under `key`
+    fn save(&self, key: &str, store: &mut dyn StateStore);
+
+    /// Read the state saved under `key`, the state is left as is if there is none or it is
+    /// invalid (e.g. saved by another version)
+    fn restore(&mut self, key: &str, store: &dyn StateStore);
+}
+
+/// The ratio of the first pane
+impl Persist for SplitterState {
+    fn save(&self, key: &str, store: &mut dyn StateStore) {
+        store.save(key, &self.get_ratio().to_string());
+    }
+
+    fn restore(&mut self, key: &str, store: &dyn StateStore) {
+        if let Some(ratio) = store.load(key).and_then(|ratio| ratio.parse().ok()) {
+            self.set_ratio(ratio);
+        }
+    }
+}
+
+/// The focused widget, if it is still in the ring
+impl Persist for FocusRing {
+    fn save(&self, key: &str, store: &mut dyn StateStore) {
+        match self.focused() {
+            Some(name) => store.save(key, name),
+            None => store.remove(key),
+        }
+    }
+
+    fn restore(&mut self, key: &str, store: &dyn StateStore) {
+        if let Some(name) = store.load(key) {
+            self.focus(&name);
+        }
+    }
+}
+
+/// The selected item and the first item shown, as `selected offset`
+impl Persist for ListView {
+    fn save(&self, key: &str, store: &mut dyn StateStore) {
+        store.save(key, &format!("{} {}", self.selected(), self.offset()));
+    }
+
+    fn restore(&mut self, key: &str, store: &dyn StateStore) {
+        let value = match store.load(key) {
+            Some(value) => value,
+            None => return,
+        };
+        let mut numbers = value.split(' ').map(|number| number.parse::<usize>().ok());
+        if let (Some(Some(selected)), Some(Some(offset)), None) =
+            (numbers.next(), numbers.next(), numbers.next())
+        {
+            self.restore_position(selected, offset);
+        }
+    }
+}
+
+/// The commands executed, most recent first, one per line
+impl Persist for CommandPalette {
+    fn save(&self, key: &str, store: &mut dyn StateStore) {
+        store.save(key, &self.history().join("\n"));
+    }
+
+    fn restore(&mut self, key: &str, store: &dyn StateStore) {
+        if let Some(history) = store.load(key) {
+            let history = history
+                .split('\n')
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect();
+            self.set_history(history);
+        }
+    }
+}
+
+/// A store kept in memory, e.g. for tests
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemoryStateStore {
+    values: BTreeMap<String, String>,
+}
+
+impl MemoryStateStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl StateStore for MemoryStateStore {
+    fn load(&self, key: &str) -> Option<String> {
+        self.values.get(key).cloned()
+    }
+
+    fn save(&mut self, key: &str, value: &str) {
+        self.values.insert(key.to_string(), value.to_string());
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.values.remove(key);
+    }
+}
+
+/// A store in a file of `key = value` lines, written by `flush`
+#[derive(Debug)]
+pub struct FileStateStore {
+    path: PathBuf,
+    values: MemoryStateStore,
+}
+
+impl FileStateStore {
+    /// `$XDG_STATE_HOME/<app>/session`, or `~/.local/state/<app>/session`
+    pub fn default_path(app: &str) -> Option<PathBuf> {
+        let state_home = env::var_os("XDG_STATE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| {
+                env::var_os("HOME").map(|home| Path::new(&home).join(".local").join("state"))
+            })?;
+        Some(state_home.join(app).join("session"))
+    }
+
+    /// The store saved in `path`, empty if the file is missing or unreadable: a lost session
+    /// should not prevent the application from starting
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let values = fs::read_to_string(&path)
+            .map(|text| parse(&text))
+            .unwrap_or_default();
+        Self { path, values }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Write the values to the file, creating its directory. The file is replaced at once so
+    /// a crash while writing keeps the previous session.
+    pub fn flush(&self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, format(&self.values))?;
+        fs::rename(&tmp, &self.path)
+    }
+}
+
+impl StateStore for FileStateStore {
+    fn load(&self, key: &str) -> Option<String> {
+        self.values.load(key)
+    }
+
+    fn save(&mut self, key: &str, value: &str) {
+        self.values.save(key, value);
+    }
+
+    fn remove(&mut self, key: &str) {
+        self.values.remove(key);
+    }
+}
+
+fn format(values: &MemoryStateStore) -> String {
+    values
+        .values
+        .iter()
+        .map(|(key, value)| format!("{} = {}\n", escape(key), escape(value)))
+        .collect()
+}
+
+fn parse(text: &str) -> MemoryStateStore {
+    let mut values = MemoryStateStore::new();
+    for line in text.lines() {
+        if let Some((key, value)) = line.split_once(" = ") {
+            values.save(&unescape(key), &unescape(value));
+        }
+    }
+    values
+}
+
+/// `\`, newlines and `=` are escaped so every entry is one line with one ` = `
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '=' => escaped.push_str("\\e"),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            unescaped.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('e') => unescaped.push('='),
+            Some(ch) => unescaped.push(ch),
+            None => {}
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::container::SplitterDirection;
+    use crate::key::Key;
+
+    #[test]
+    fn persist_widgets() {
+        let mut store = MemoryStateStore::new();
+        let splitter = SplitterState::new(SplitterDirection::Horizontal).ratio(0.3);
+        let mut focus = FocusRing::new(&["list", "preview"]);
+        focus.focus_next();
+        splitter.save("splitter", &mut store);
+        focus.save("focus", &mut store);
+
+        let mut splitter = SplitterState::new(SplitterDirection::Horizontal);
+        let mut focus = FocusRing::new(&["list", "preview"]);
+        splitter.restore("splitter", &store);
+        focus.restore("focus", &store);
+        assert_eq!(0.3, splitter.get_ratio());
+        assert_eq!(Some("preview"), focus.focused());
+
+        // invalid or stale values are ignored
+        store.save("splitter", "wide");
+        splitter.restore("splitter", &store);
+        assert_eq!(0.3, splitter.get_ratio());
+        let mut focus = FocusRing::new(&["list"]);
+        focus.restore("focus", &store);
+        assert_eq!(Some("list"), focus.focused());
+    }
+
+    #[test]
+    fn persist_list_and_palette() {
+        let mut store = MemoryStateStore::new();
+        let items = || (0..10).map(|n| n.to_string().as_str().into()).collect();
+        let mut list = ListView::new(items());
+        list.set_size(5, 3);
+        list.on_key(&Key::End);
+        list.on_key(&Key::Up);
+        list.save("list", &mut store);
+        let mut palette = CommandPalette::new()
+            .command("Open", None, "", || {})
+            .command("Quit", None, "", || {});
+        palette.open();
+        palette.on_key(&Key::Down);
+        palette.on_key(&Key::Enter);
+        palette.save("palette", &mut store);
+
+        let mut list = ListView::new(items());
+        list.restore("list", &store);
+        assert_eq!((8, 7), (list.selected(), list.offset()));
+        let mut palette = CommandPalette::new()
+            .command("Open", None, "", || {})
+            .command("Quit", None, "", || {});
+        palette.restore("palette", &store);
+        assert_eq!(vec!["Quit"], palette.history());
+
+        // out of range or invalid
+        let mut list = ListView::new(vec!["only".into()]);
+        list.restore("list", &store);
+        assert_eq!((0, 0), (list.selected(), list.offset()));
+        store.save("list", "1 two");
+        list.restore("list", &store);
+        assert_eq!((0, 0), (list.selected(), list.offset()));
+    }
+
+    #[test]
+    fn typed_values() {
+        let mut store = MemoryStateStore::new();
+        store.save_display("offset", &42);
+        assert_eq!(Some(42), store.load_parsed::<usize>("offset"));
+        assert_eq!(None, store.load_parsed::<usize>("missing"));
+
+        assert!(store.load_list("history").is_empty());
+        let history = vec!["ls".to_string(), "".to_string(), "cd /".to_string()];
+        store.save_list("history", &history);
+        assert_eq!(history, store.load_list("history"));
+    }
+
+    #[test]
+    fn file() {
+        let path = env::temp_dir()
+            .join(format!("tuikit-session-{}", std::process::id()))
+            .join("session");
+        let mut store = FileStateStore::open(&path);
+        assert_eq!(None, store.load("key"));
+        store.save("key = with \\ escapes", "two\nlines = \\n");
+        store.save("plain", "value");
+        store.flush().unwrap();
+
+        let store = FileStateStore::open(&path);
+        assert_eq!(
+            Some("two\nlines = \\n".to_string()),
+            store.load("key = with \\ escapes")
+        );
+        assert_eq!(Some("value".to_string()), store.load("plain"));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
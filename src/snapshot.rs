@@ -0,0 +1,275 @@
+//! Save and load the contents of a `Screen` in a stable binary format.
+//!
+//! Snapshots could be attached to bug reports, compared between versions and loaded back into
+//! a `Screen` to reproduce rendering issues.
+//!
+//! ```
+//! use tuikit::canvas::Canvas;
+//! use tuikit::screen::Screen;
+//! use tuikit::snapshot::ScreenSnapshot;
+//!
+//! let mut screen = Screen::new(5, 1);
+//! screen.print(0, 0, "hello").unwrap();
+//!
+//! let bytes = ScreenSnapshot::from_screen(&screen).encode();
+//! let snapshot = ScreenSnapshot::decode(&bytes).unwrap();
+//! assert_eq!("hello", snapshot.text());
+//! ```
+//!
+//! The format, all integers little endian:
+//!
+//! ```text
+//! magic      b"TKSC"
+//! version    u8 (1)
+//! width      u32
+//! height     u32
+//! cursor     u8 (1 if visible), row u32, col u32
+//! cells      width * height times: char u32, fg color, bg color, effect u8
+//! color      tag u8 (0 default, 1 ansi, 2 rgb), 3 bytes (ansi value or r, g, b)
+//! ```
+//!
+//! New versions only append fields, older snapshots stay readable.
+
+use crate::attr::{Attr, Color, Effect};
+use crate::canvas::Canvas;
+use crate::cell::Cell;
+use crate::screen::Screen;
+use std::io::{self, Read, Write};
+
+const MAGIC: &[u8; 4] = b"TKSC";
+const VERSION: u8 = 1;
+
+/// The cells and cursor of a screen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScreenSnapshot {
+    pub width: usize,
+    pub height: usize,
+    /// `(row, col)` if the cursor is visible
+    pub cursor: Option<(usize, usize)>,
+    /// row by row
+    pub cells: Vec<Cell>,
+}
+
+impl ScreenSnapshot {
+    pub fn from_screen(screen: &Screen) -> Self {
+        Self {
+            width: screen.width(),
+            height: screen.height(),
+            cursor: screen.cursor(),
+            cells: screen.iter_cell().map(|(_, _, cell)| *cell).collect(),
+        }
+    }
+
+    /// a screen with the same contents, e.g. to `present` them again
+    pub fn to_screen(&self) -> Screen {
+        let mut screen = Screen::new(self.width, self.height);
+        screen.cells_mut().copy_from_slice(&self.cells);
+        match self.cursor {
+            Some((row, col)) => {
+                let _ = screen.set_cursor(row, col);
+            }
+            None => {
+                let _ = screen.show_cursor(false);
+            }
+        }
+        screen
+    }
+
+    pub fn cell(&self, row: usize, col: usize) -> Option<&Cell> {
+        if row < self.height && col < self.width {
+            self.cells.get(row * self.width + col)
+        } else {
+            None
+        }
+    }
+
+    /// the characters, one line per row without the trailing spaces
+    pub fn text(&self) -> String {
+        let lines: Vec<String> = self
+            .cells
+            .chunks(self.width.max(1))
+            .map(|row| {
+                let line: String = row
+                    .iter()
+                    .map(|cell| if cell.ch == '\0' { ' ' } else { cell.ch })
+                    .collect();
+                line.trim_end().to_string()
+            })
+            .collect();
+        lines.join("\n")
+    }
+
+    /// The `(row, col)` of the cells that differ, all of them if the sizes differ
+    pub fn diff(&self, other: &Self) -> Vec<(usize, usize)> {
+        let width = self.width.max(other.width);
+        let height = self.height.max(other.height);
+        let mut changed = Vec::new();
+        for row in 0..height {
+            for col in 0..width {
+                if self.width != other.width
+                    || self.height != other.height
+                    || self.cell(row, col) != other.cell(row, col)
+                {
+                    changed.push((row, col));
+                }
+            }
+        }
+        changed
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(18 + self.cells.len() * 13);
+        self.write_to(&mut bytes)
+            .expect("writing to a Vec never fails");
+        bytes
+    }
+
+    pub fn decode(mut bytes: &[u8]) -> io::Result<Self> {
+        Self::read_from(&mut bytes)
+    }
+
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        write_u32(writer, self.width)?;
+        write_u32(writer, self.height)?;
+        let (row, col) = self.cursor.unwrap_or((0, 0));
+        writer.write_all(&[self.cursor.is_some() as u8])?;
+        write_u32(writer, row)?;
+        write_u32(writer, col)?;
+        for cell in self.cells.iter() {
+            writer.write_all(&(cell.ch as u32).to_le_bytes())?;
+            write_color(writer, cell.attr.fg)?;
+            write_color(writer, cell.attr.bg)?;
+            writer.write_all(&[cell.attr.effect.bits()])?;
+        }
+        Ok(())
+    }
+
+    pub fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a screen snapshot"));
+        }
+        let version = read_u8(reader)?;
+        if version == 0 || version > VERSION {
+            return Err(invalid_data(&format!(
+                "unsupported snapshot version {}",
+                version
+            )));
+        }
+
+        let width = read_u32(reader)?;
+        let height = read_u32(reader)?;
+        let cursor_visible = read_u8(reader)? != 0;
+        let cursor = (read_u32(reader)?, read_u32(reader)?);
+        let size = width
+            .checked_mul(height)
+            .ok_or_else(|| invalid_data("screen too large"))?;
+
+        let mut cells = Vec::new();
+        for _ in 0..size {
+            let ch = char::from_u32(read_u32(reader)? as u32)
+                .ok_or_else(|| invalid_data("invalid character"))?;
+            let fg = read_color(reader)?;
+            let bg = read_color(reader)?;
+            let effect = Effect::from_bits_truncate(read_u8(reader)?);
+            cells.push(Cell {
+                ch,
+                attr: Attr { fg, bg, effect },
+            });
+        }
+
+        Ok(Self {
+            width,
+            height,
+            cursor: if cursor_visible { Some(cursor) } else { None },
+            cells,
+        })
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_u32(writer: &mut impl Write, value: usize) -> io::Result<()> {
+    writer.write_all(&(value as u32).to_le_bytes())
+}
+
+fn write_color(writer: &mut impl Write, color: Color) -> io::Result<()> {
+    let bytes = match color {
+        Color::AnsiValue(value) => [1, value, 0, 0],
+        Color::Rgb(r, g, b) => [2, r, g, b],
+        _ => [0, 0, 0, 0],
+    };
+    writer.write_all(&bytes)
+}
+
+fn read_u8(reader: &mut impl Read) -> io::Result<u8> {
+    let mut byte = [0];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<usize> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes) as usize)
+}
+
+fn read_color(reader: &mut impl Read) -> io::Result<Color> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    match bytes[0] {
+        0 => Ok(Color::Default),
+        1 => Ok(Color::AnsiValue(bytes[1])),
+        2 => Ok(Color::Rgb(bytes[1], bytes[2], bytes[3])),
+        tag => Err(invalid_data(&format!("invalid color tag {}", tag))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut screen = Screen::new(4, 2);
+        let attr = Attr::default()
+            .fg(Color::Rgb(1, 2, 3))
+            .bg(Color::RED)
+            .effect(Effect::BOLD | Effect::REVERSE);
+        let _ = screen.print_with_attr(0, 0, "ab", attr);
+        let _ = screen.print(1, 1, "中");
+        let _ = screen.set_cursor(1, 3);
+
+        let snapshot = ScreenSnapshot::from_screen(&screen);
+        let decoded = ScreenSnapshot::decode(&snapshot.encode()).unwrap();
+        assert_eq!(snapshot, decoded);
+        assert_eq!(Some((1, 3)), decoded.cursor);
+        assert_eq!(snapshot, ScreenSnapshot::from_screen(&decoded.to_screen()));
+        assert_eq!("ab\n 中", decoded.text());
+    }
+
+    #[test]
+    fn diff_cells() {
+        let mut screen = Screen::new(3, 1);
+        let before = ScreenSnapshot::from_screen(&screen);
+        let _ = screen.print(0, 1, "x");
+        let after = ScreenSnapshot::from_screen(&screen);
+        assert_eq!(vec![(0, 1)], before.diff(&after));
+        let taller = ScreenSnapshot::from_screen(&Screen::new(3, 2));
+        assert_eq!(6, before.diff(&taller).len());
+    }
+
+    #[test]
+    fn reject_invalid_data() {
+        assert!(ScreenSnapshot::decode(b"nope").is_err());
+        let mut bytes = ScreenSnapshot::from_screen(&Screen::new(1, 1)).encode();
+        bytes[4] = 9;
+        assert!(ScreenSnapshot::decode(&bytes).is_err());
+        assert!(ScreenSnapshot::decode(&bytes[..10]).is_err());
+    }
+}
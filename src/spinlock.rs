@@ -1,4 +1,4 @@
-///! SpinLock implemented using AtomicBool
+//! SpinLock implemented using AtomicBool
 use std::cell::UnsafeCell;
 use std::ops::Deref;
 use std::ops::DerefMut;
@@ -43,13 +43,22 @@ impl<T> SpinLock<T> {
 }
 
 impl<T: ?Sized> SpinLock<T> {
-    pub fn lock(&self) -> SpinLockGuard<T> {
-        while let Err(_) =
-            self.locked
-                .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+    pub fn lock(&self) -> SpinLockGuard<'_, T> {
+        while self
+            .locked
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
         {}
         SpinLockGuard::new(self)
     }
+
+    /// acquire the lock only if it is not held, useful where blocking is not an option
+    pub fn try_lock(&self) -> Option<SpinLockGuard<'_, T>> {
+        self.locked
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .ok()
+            .map(|_| SpinLockGuard::new(self))
+    }
 }
 
 impl<'mutex, T: ?Sized> Deref for SpinLockGuard<'mutex, T> {
@@ -69,10 +78,11 @@ impl<'mutex, T: ?Sized> DerefMut for SpinLockGuard<'mutex, T> {
 impl<'a, T: ?Sized> Drop for SpinLockGuard<'a, T> {
     #[inline]
     fn drop(&mut self) {
-        while let Err(_) =
-            self.__lock
-                .locked
-                .compare_exchange(true, false, Ordering::SeqCst, Ordering::SeqCst)
+        while self
+            .__lock
+            .locked
+            .compare_exchange(true, false, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
         {}
     }
 }
@@ -84,6 +94,7 @@ mod tests {
     use std::sync::Arc;
     use std::thread;
 
+    #[allow(dead_code)]
     #[derive(Eq, PartialEq, Debug)]
     struct NonCopy(i32);
 
@@ -130,6 +141,16 @@ mod tests {
         assert_eq!(*m.lock(), J * K * 2);
     }
 
+    #[test]
+    fn try_lock() {
+        let m = SpinLock::new(());
+        let guard = m.try_lock();
+        assert!(guard.is_some());
+        assert!(m.try_lock().is_none());
+        drop(guard);
+        assert!(m.try_lock().is_some());
+    }
+
     #[test]
     fn test_mutex_unsized() {
         let mutex: &SpinLock<[i32]> = &SpinLock::new([1, 2, 3]);
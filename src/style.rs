@@ -0,0 +1,273 @@
+//! Named style slots, resolved through a theme, with per-instance overrides.
+//!
+//! Widgets draw with the styles of named slots (e.g. `"palette.selected"`) instead of
+//! hard-coded attributes, so an application could restyle any widget, including third-party
+//! ones, without forking it. A slot is resolved in order from:
+//!
+//! 1. the overrides of the widget instance (`Styled::style`),
+//! 2. the theme (the instance's own or the global one set by `set_theme`), trying the slot then
+//!    its less specific classes: `"palette.selected"`, then `"selected"`,
+//! 3. the widget's default.
+//!
+//! ```
+//! use tuikit::attr::{Attr, Color, Effect};
+//! use tuikit::style::{Styled, Theme};
+//! use tuikit::widget::CommandPalette;
+//!
+//! // every "selected" slot of every widget is green, unless more specific
+//! let theme = Theme::new().style("selected", Color::GREEN);
+//! tuikit::style::set_theme(theme);
+//!
+//! // only this palette
+//! let palette = CommandPalette::new().style("palette", Attr::from(Effect::BOLD));
+//! assert!(palette.style_slots().contains(&"palette.selected"));
+//! ```
+//!
+//! Themes could also be loaded from files, see `Theme::parse`.
+
+use crate::attr::{Attr, Color, Effect};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+lazy_static! {
+    static ref THEME: RwLock<Arc<Theme>> = RwLock::new(Arc::new(Theme::new()));
+}
+
+/// Replace the global theme, used by the widgets without their own theme
+pub fn set_theme(theme: Theme) {
+    *THEME.write().expect("set_theme: failed to get lock") = Arc::new(theme);
+}
+
+/// The global theme
+pub fn theme() -> Arc<Theme> {
+    THEME.read().expect("theme: failed to get lock").clone()
+}
+
+/// The styles of the slot classes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Theme {
+    styles: HashMap<String, Attr>,
+}
+
+// Builder
+impl Theme {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn style(mut self, class: &str, attr: impl Into<Attr>) -> Self {
+        self.set(class, attr);
+        self
+    }
+
+    /// Parse lines of `class = [fg] [on bg] [effects]`, lines starting with `#` are comments.
+    /// The colors are read by `Color::from_str` (names, `#rrggbb`, `color<n>`), the effects are
+    /// `bold`, `dim`, `underline`, `blink` and `reverse`. Invalid lines are ignored.
+    ///
+    /// ```
+    /// use tuikit::attr::{Attr, Color, Effect};
+    /// use tuikit::style::Theme;
+    ///
+    /// let theme = Theme::parse("# dark\nselected = white on dodgerblue bold\nborder = #444");
+    /// let selected = Attr::from(Color::Rgb(255, 255, 255))
+    ///     .bg(Color::Rgb(30, 144, 255))
+    ///     .effect(Effect::BOLD);
+    /// assert_eq!(Some(selected), theme.get("selected"));
+    /// assert_eq!(Some(Attr::from(Color::Rgb(0x44, 0x44, 0x44))), theme.get("border"));
+    /// ```
+    pub fn parse(text: &str) -> Self {
+        let mut theme = Self::new();
+        for line in text.lines().map(str::trim) {
+            if line.starts_with('#') {
+                continue;
+            }
+            if let Some((class, value)) = line.split_once('=') {
+                if let Some(attr) = parse_attr(value) {
+                    theme.set(class.trim(), attr);
+                }
+            }
+        }
+        theme
+    }
+
+    pub fn from_file(path: &Path) -> io::Result<Self> {
+        fs::read_to_string(path).map(|text| Self::parse(&text))
+    }
+}
+
+impl Theme {
+    pub fn set(&mut self, class: &str, attr: impl Into<Attr>) {
+        self.styles.insert(class.to_string(), attr.into());
+    }
+
+    /// the style of `class` exactly
+    pub fn get(&self, class: &str) -> Option<Attr> {
+        self.styles.get(class).copied()
+    }
+
+    /// the style of `slot` or of its less specific classes, see the module doc
+    pub fn resolve(&self, slot: &str) -> Option<Attr> {
+        let mut class = slot;
+        loop {
+            if let Some(attr) = self.get(class) {
+                return Some(attr);
+            }
+            match class.find('.') {
+                Some(dot) => class = &class[dot + 1..],
+                None => return None,
+            }
+        }
+    }
+}
+
+/// `[fg] [on bg] [effects]`, `None` if any word is invalid
+fn parse_attr(value: &str) -> Option<Attr> {
+    let mut attr = Attr::default();
+    let mut words = value.split_whitespace();
+    while let Some(word) = words.next() {
+        let effect = match word.to_lowercase().as_ref() {
+            "on" => {
+                attr.bg = words.next()?.parse().ok()?;
+                continue;
+            }
+            "bold" => Effect::BOLD,
+            "dim" => Effect::DIM,
+            "underline" => Effect::UNDERLINE,
+            "blink" => Effect::BLINK,
+            "reverse" => Effect::REVERSE,
+            _ => {
+                attr.fg = word.parse::<Color>().ok()?;
+                continue;
+            }
+        };
+        attr.effect |= effect;
+    }
+    Some(attr)
+}
+
+/// The style overrides of a widget instance
+#[derive(Debug, Clone, Default)]
+pub struct Styles {
+    overrides: HashMap<String, Attr>,
+    theme: Option<Arc<Theme>>,
+}
+
+impl Styles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// override the style of `slot` for this instance
+    pub fn set(&mut self, slot: &str, attr: impl Into<Attr>) {
+        self.overrides.insert(slot.to_string(), attr.into());
+    }
+
+    /// use `theme` instead of the global one
+    pub fn set_theme(&mut self, theme: Arc<Theme>) {
+        self.theme = Some(theme);
+    }
+
+    /// the style of `slot`, `default` if neither overridden nor themed
+    pub fn resolve(&self, slot: &str, default: Attr) -> Attr {
+        if let Some(attr) = self.overrides.get(slot) {
+            return *attr;
+        }
+        let themed = match self.theme.as_ref() {
+            Some(theme) => theme.resolve(slot),
+            None => theme().resolve(slot),
+        };
+        themed.unwrap_or(default)
+    }
+}
+
+/// A widget drawn with named style slots
+pub trait Styled {
+    /// the slots the widget draws with, e.g. `["palette", "palette.selected"]`
+    fn style_slots(&self) -> &'static [&'static str];
+
+    fn styles_mut(&mut self) -> &mut Styles;
+
+    /// override the style of `slot` for this instance
+    fn style(mut self, slot: &str, attr: impl Into<Attr>) -> Self
+    where
+        Self: Sized,
+    {
+        self.styles_mut().set(slot, attr);
+        self
+    }
+
+    /// use `theme` for this instance instead of the global one
+    fn theme(mut self, theme: Arc<Theme>) -> Self
+    where
+        Self: Sized,
+    {
+        self.styles_mut().set_theme(theme);
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cascade() {
+        let theme = Theme::new()
+            .style("selected", Color::GREEN)
+            .style("list.selected.focused", Color::RED);
+        assert_eq!(
+            Some(Attr::from(Color::GREEN)),
+            theme.resolve("list.selected")
+        );
+        assert_eq!(
+            Some(Attr::from(Color::RED)),
+            theme.resolve("list.selected.focused")
+        );
+        assert_eq!(
+            Some(Attr::from(Color::GREEN)),
+            theme.resolve("tree.selected")
+        );
+        assert_eq!(None, theme.resolve("list"));
+    }
+
+    #[test]
+    fn parse_theme_file() {
+        let theme = Theme::parse(
+            "list.selected = on color4 Reverse\nborder = grey\nbad = bold sparkly\n\
+             missing = on\n# comment = red",
+        );
+        assert_eq!(
+            Some(Attr::default().bg(Color::BLUE).effect(Effect::REVERSE)),
+            theme.get("list.selected")
+        );
+        assert_eq!(
+            Some(Attr::from(Color::Rgb(128, 128, 128))),
+            theme.get("border")
+        );
+        assert_eq!(None, theme.get("bad"));
+        assert_eq!(None, theme.get("missing"));
+        assert_eq!(None, theme.get("# comment"));
+    }
+
+    #[test]
+    fn overrides_win() {
+        let mut styles = Styles::new();
+        styles.set_theme(Arc::new(Theme::new().style("selected", Color::GREEN)));
+        let default = Attr::from(Effect::BOLD);
+        assert_eq!(default, styles.resolve("list", default));
+        assert_eq!(
+            Attr::from(Color::GREEN),
+            styles.resolve("list.selected", default)
+        );
+
+        styles.set("list.selected", Color::BLUE);
+        assert_eq!(
+            Attr::from(Color::BLUE),
+            styles.resolve("list.selected", default)
+        );
+    }
+}
@@ -1,31 +1,41 @@
+use nix::errno::Errno;
 use nix::sys::select;
 use nix::sys::time::{TimeVal, TimeValLike};
-use std::error::Error;
+use std::io;
 use std::os::unix::io::RawFd;
-use std::time::Duration;
-
-pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
+use std::time::{Duration, Instant};
 
+/// rounded up: waking up a few microseconds early would only poll again until the deadline
 fn duration_to_timeval(duration: Duration) -> TimeVal {
-    let sec = duration.as_secs() * 1000 + (duration.subsec_millis() as u64);
-    TimeVal::milliseconds(sec as i64)
+    let micros = duration.as_nanos().div_ceil(1000);
+    TimeVal::microseconds(micros as i64)
 }
 
-pub fn wait_until_ready(fd: RawFd, signal_fd: Option<RawFd>, timeout: Duration) -> Result<()> {
-    let mut timeout_spec = if timeout == Duration::new(0, 0) {
-        None
-    } else {
-        Some(duration_to_timeval(timeout))
-    };
+/// Wait until `fd` (or one of `signal_fds`) is readable, at the latest until `deadline`, forever if
+/// `None`: `false` if it passed. A signal interrupting the wait doesn't end it, it goes on for
+/// the time left.
+pub fn wait_until_ready(
+    fd: RawFd,
+    signal_fds: &[RawFd],
+    deadline: Option<Instant>,
+) -> io::Result<bool> {
+    loop {
+        let mut timeout_spec = deadline.map(|deadline| {
+            duration_to_timeval(deadline.saturating_duration_since(Instant::now()))
+        });
 
-    let mut fdset = select::FdSet::new();
-    fdset.insert(fd);
-    signal_fd.map(|fd| fdset.insert(fd));
-    let n = select::select(None, &mut fdset, None, None, &mut timeout_spec)?;
+        let mut fdset = select::FdSet::new();
+        fdset.insert(fd);
+        for &fd in signal_fds {
+            fdset.insert(fd);
+        }
 
-    if n == 1 {
-        Ok(())
-    } else {
-        Err("select return file descriptor other than 1".into())
+        match select::select(None, &mut fdset, None, None, &mut timeout_spec) {
+            Ok(0) => return Ok(false),
+            Ok(_) => return Ok(true),
+            Err(nix::Error::Sys(Errno::EINTR)) => continue,
+            Err(nix::Error::Sys(errno)) => return Err(io::Error::from_raw_os_error(errno as i32)),
+            Err(err) => return Err(io::Error::other(err.to_string())),
+        }
     }
 }
@@ -10,7 +10,7 @@
 //! let term = Term::new().unwrap();
 //!
 //! while let Ok(ev) = term.poll_event() {
-//!     if let Event::Key(Key::Char('q')) = ev {
+//!     if ev.key() == Some(&Key::Char('q')) {
 //!         break;
 //!     }
 //!
@@ -22,32 +22,65 @@
 //! Term is modeled after [termbox](https://github.com/nsf/termbox). The main idea is viewing
 //! terminals as a table of fixed-size cells and input being a stream of structured messages
 
-use crate::attr::Attr;
-use crate::canvas::Canvas;
+use crate::accessibility::{AccessibilityOptions, Announcement, Announcer};
+use crate::animation::set_animation_enabled;
+use crate::attr::{Attr, Effect};
+use crate::canvas::{Canvas, PrintItem};
+use crate::capability::{AttrSupport, Capabilities, TERMCAP_PROBES};
 use crate::cell::Cell;
-use crate::draw::Draw;
-use crate::event::Event;
-use crate::input::{KeyBoard, KeyboardHandler};
-use crate::key::Key;
+use crate::clipboard::{self, Clipboard, MemoryClipboard};
+use crate::config::{ColorDepth, Config};
+use crate::draw::{Draw, DrawContext};
+use crate::draw_handle::{DetachedCanvas, DrawHandle, DrawQueue};
+use crate::event::{Event, TerminalEvent};
+use crate::event_log::{dump_on_panic, EventLog, DEFAULT_EVENT_LOG_CAPACITY};
+use crate::frame_hook::{FrameHook, FrameHooks};
+#[cfg(feature = "fs-watch")]
+use crate::fs_watch::{self, ConfigFiles, FsWatcher};
+use crate::grab::{Grabbed, InputConsumer, InputGrab};
+use crate::hints::Hints;
+use crate::input::{Deadline, InputError, KeyBoard, KeyboardHandler, MouseEncoding};
+use crate::key::{Key, Modifiers, MouseButton};
+use crate::key_release::KeyReleaseDetector;
 use crate::output::Command;
-use crate::output::Output;
-use crate::raw::{get_tty, IntoRawMode};
-use crate::screen::Screen;
+use crate::output::{LineDrawing, Output};
+use crate::plugin::{self, EventSender, Plugin, Plugins};
+use crate::raw::{lock_tty, open_tty, open_tty_path, IntoRawMode, TtyFallback, TtyLock};
+use crate::region::{Region, RegionRegistry};
+use crate::resize::{ResizeDebouncer, ResizeState, SizePoller};
+use crate::screen::{BlinkStyle, DiffStrategy, GhostCursor, Screen, WideCharBoundary};
+use crate::selection::{
+    link_at, mouse_position, osc52, CopyTarget, LinkOpener, MouseSelector, SelectionUpdate,
+};
+use crate::snapshot::ScreenSnapshot;
 use crate::spinlock::SpinLock;
 use crate::sys::signal::{initialize_signals, notify_on_sigwinch, unregister_sigwinch};
+use crate::sys::size::terminal_size;
+use crate::timeline::Timeline;
+use crate::timer::TimerWheel;
+use crate::widget::PaletteCommand;
+use crate::widget_id::{WidgetId, WidgetStates};
 use std::cmp::{max, min};
+use std::collections::{HashSet, VecDeque};
 use std::error::Error;
+use std::fs::File;
+use std::io;
+use std::ops::Range;
+use std::os::unix::io::AsRawFd;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::mpsc::{channel, Receiver, Sender};
-use std::sync::{Arc, RwLock};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 pub type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
 const MIN_HEIGHT: usize = 1;
 const WAIT_TIMEOUT: Duration = Duration::from_millis(300);
 const POLLING_TIMEOUT: Duration = Duration::from_millis(10);
+const BLINK_INTERVAL: Duration = Duration::from_millis(500);
+const RESIZE_DEBOUNCE: Duration = Duration::from_millis(50);
 
 #[derive(Debug)]
 pub enum TermHeight {
@@ -55,6 +88,16 @@ pub enum TermHeight {
     Percent(usize),
 }
 
+/// What `present` did with the frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresentStatus {
+    /// written to the terminal
+    Presented,
+    /// Nothing was written: the term is paused, or the terminal went away. The frame is kept
+    /// and presented by `restart`.
+    Deferred,
+}
+
 pub struct Term {
     stopped: Arc<RwLock<bool>>,
     components_to_stop: Arc<AtomicUsize>,
@@ -63,26 +106,139 @@ pub struct Term {
     term_lock: SpinLock<TermLock>,
     event_rx: SpinLock<Receiver<Event>>,
     event_tx: Arc<SpinLock<Sender<Event>>>,
+    announcer: SpinLock<Option<Box<dyn Announcer>>>,
+    event_log: Arc<SpinLock<EventLog>>,
+    probe_timeout: Duration,
+    probe_capabilities: bool,
+    capabilities: SpinLock<Capabilities>,
+    color_depth: Option<ColorDepth>,
+    line_drawing: LineDrawing,
+    kitty_keyboard: bool,
+    click_interval: Duration,
+    esc_timeout: Option<Duration>,
+    tty_fallback: TtyFallback,
+    tty_path: Option<PathBuf>,
+    lock_tty: bool,
+    /// held while running, released on pause
+    tty_lock: SpinLock<Option<TtyLock>>,
+    draw_queue: Arc<SpinLock<DrawQueue>>,
+    frame_hooks: SpinLock<FrameHooks>,
+    plugins: SpinLock<Plugins>,
+    config: Config,
+    key_release: SpinLock<Option<KeyReleaseDetector>>,
+    pending_events: SpinLock<VecDeque<Event>>,
+    timeline: SpinLock<Option<Timeline>>,
+    resize_debounce: Duration,
+    size_poll_interval: Option<Duration>,
+    resize_state: SpinLock<ResizeState>,
+    input_grab: SpinLock<InputGrab>,
+    widget_states: SpinLock<WidgetStates>,
+    debug_overlays: SpinLock<HashSet<String>>,
+    mouse_encoding: Arc<SpinLock<Option<MouseEncoding>>>,
+    timers: Arc<SpinLock<TimerWheel>>,
+    /// wakes the timer thread up, `None` until the first timer
+    timer_wake: SpinLock<Option<Sender<()>>>,
+    #[cfg(feature = "fs-watch")]
+    fs_watcher: SpinLock<Option<FsWatcher>>,
+    #[cfg(feature = "fs-watch")]
+    config_files: SpinLock<ConfigFiles>,
 }
 
 pub struct TermOptions {
     max_height: TermHeight,
     min_height: TermHeight,
     height: TermHeight,
+    accessibility: AccessibilityOptions,
+    event_log_capacity: usize,
+    diff_strategy: DiffStrategy,
+    probe_timeout: Duration,
+    probe_capabilities: bool,
+    mouse: bool,
+    config: Config,
+    synthetic_key_release: Option<Duration>,
+    software_blink: Option<BlinkStyle>,
+    resize_debounce: Duration,
+    size_poll_interval: Option<Duration>,
+    color_depth: Option<ColorDepth>,
+    line_drawing: LineDrawing,
+    write_budget: Option<usize>,
+    kitty_keyboard: bool,
+    focus_events: bool,
+    bracketed_paste: bool,
+    click_interval: Duration,
+    esc_timeout: Option<Duration>,
+    tty_fallback: TtyFallback,
+    tty_path: Option<PathBuf>,
+    lock_tty: bool,
 }
 
 impl Default for TermOptions {
+    /// The defaults of tuikit, whatever the user config: see `TermOptions::from_config`
     fn default() -> Self {
         Self {
             max_height: TermHeight::Percent(100),
             min_height: TermHeight::Fixed(3),
             height: TermHeight::Percent(100),
+            accessibility: AccessibilityOptions::default(),
+            event_log_capacity: DEFAULT_EVENT_LOG_CAPACITY,
+            diff_strategy: DiffStrategy::default(),
+            probe_timeout: WAIT_TIMEOUT,
+            probe_capabilities: false,
+            mouse: false,
+            color_depth: None,
+            esc_timeout: None,
+            config: Config::default(),
+            synthetic_key_release: None,
+            software_blink: None,
+            resize_debounce: RESIZE_DEBOUNCE,
+            size_poll_interval: None,
+            line_drawing: LineDrawing::default(),
+            write_budget: None,
+            kitty_keyboard: false,
+            focus_events: false,
+            bracketed_paste: false,
+            click_interval: Duration::new(0, 0),
+            tty_fallback: TtyFallback::default(),
+            tty_path: None,
+            lock_tty: false,
+        }
+    }
+}
+
+impl TermOptions {
+    /// The defaults adjusted by the user config (`Config::load`) and the accessibility options
+    /// of `TUIKIT_ACCESSIBILITY`, for the applications honouring them, see `tuikit::config`.
+    /// The options set after it win.
+    ///
+    /// ```no_run
+    /// use tuikit::term::{Term, TermOptions};
+    ///
+    /// let term = Term::with_options(TermOptions::from_config().mouse(true)).unwrap();
+    /// ```
+    pub fn from_config() -> Self {
+        let mut accessibility = AccessibilityOptions::from_env();
+        let config = Config::load();
+        if let Some(reduce_motion) = config.reduce_motion {
+            accessibility.reduce_motion = reduce_motion;
         }
+        Self::default().accessibility(accessibility).config(config)
     }
 }
 
 // Builder
 impl TermOptions {
+    /// Apply the user preferences of `config` (mouse, colors, `ESC` timeout), kept for
+    /// `Term::config`. The options set after it win.
+    pub fn config(mut self, config: Config) -> Self {
+        if let Some(mouse) = config.mouse {
+            self.mouse = mouse;
+        }
+        self.color_depth = config.color_depth.or(self.color_depth);
+        self.esc_timeout = config.esc_timeout.or(self.esc_timeout);
+        self.config = config;
+        self
+    }
+
     pub fn max_height(mut self, max_height: TermHeight) -> Self {
         self.max_height = max_height;
         self
@@ -96,6 +252,177 @@ impl TermOptions {
         self.height = height;
         self
     }
+
+    /// The accessibility toggles, read from `TUIKIT_ACCESSIBILITY` by `TermOptions::from_config`
+    pub fn accessibility(mut self, accessibility: AccessibilityOptions) -> Self {
+        self.accessibility = accessibility;
+        self
+    }
+
+    /// Number of recent input bytes/events kept for debugging, 0 to disable.
+    /// See `tuikit::event_log` for details.
+    pub fn event_log_capacity(mut self, capacity: usize) -> Self {
+        self.event_log_capacity = capacity;
+        self
+    }
+
+    /// The strategy to find out the cells to repaint on `present`
+    pub fn diff_strategy(mut self, strategy: DiffStrategy) -> Self {
+        self.diff_strategy = strategy;
+        self
+    }
+
+    /// How long the startup waits for the cursor position in total, the only reply needed
+    /// before drawing. Raise it for slow links, a late reply is dropped.
+    pub fn probe_timeout(mut self, timeout: Duration) -> Self {
+        self.probe_timeout = timeout;
+        self
+    }
+
+    /// Query the device attributes and background color on (re)start without waiting for the
+    /// replies, `TerminalEvent::CapabilitiesUpdated` is returned when they arrive.
+    /// See `tuikit::capability`.
+    pub fn probe_capabilities(mut self, probe: bool) -> Self {
+        self.probe_capabilities = probe;
+        self
+    }
+
+    /// Enable mouse support on (re)start, overrides the `mouse` user config
+    pub fn mouse(mut self, mouse: bool) -> Self {
+        self.mouse = mouse;
+        self
+    }
+
+    /// Terminals don't report key releases. With `Some(quiet)`, a held key is considered
+    /// released once its auto-repeat stopped for `quiet` (or another key is pressed), and
+    /// `Event::KeyUp` is returned. Useful for "hold to scroll" interactions, the period should
+    /// be longer than the repeat interval, e.g. 100ms.
+    pub fn synthetic_key_release(mut self, quiet: Option<Duration>) -> Self {
+        self.synthetic_key_release = quiet;
+        self
+    }
+
+    /// Blink the `BLINK` cells in software, toggling them with `style` every 500ms, for the
+    /// terminals ignoring the effect. The screen is repainted by `present`, see
+    /// `Term::next_blink` for when to present again.
+    pub fn software_blink(mut self, style: Option<BlinkStyle>) -> Self {
+        self.software_blink = style;
+        self
+    }
+
+    /// Report a burst of resizes once no size change came for `quiet` (50ms by default), so
+    /// that dragging a window border doesn't repaint for every intermediate size. Resizes back
+    /// to the size already handled are dropped.
+    pub fn resize_debounce(mut self, quiet: Duration) -> Self {
+        self.resize_debounce = quiet;
+        self
+    }
+
+    /// Also compare the terminal size every `interval` (e.g. 1s) and report a resize when it
+    /// changed, for the environments where SIGWINCH isn't delivered reliably (some containers,
+    /// mosh). `None` (the default) relies on SIGWINCH only.
+    pub fn size_poll_interval(mut self, interval: Option<Duration>) -> Self {
+        self.size_poll_interval = interval;
+        self
+    }
+
+    /// The colors to render with, overrides the detection (see `tuikit::capability`) and the
+    /// `color_depth` user config. The colors beyond are converted to their nearest.
+    pub fn color_depth(mut self, depth: Option<ColorDepth>) -> Self {
+        self.color_depth = depth;
+        self
+    }
+
+    /// How the box-drawing characters are written, `LineDrawing::Acs` keeps the borders
+    /// intact on the terminals without Unicode fonts, e.g. the linux console
+    pub fn line_drawing(mut self, line_drawing: LineDrawing) -> Self {
+        self.line_drawing = line_drawing;
+        self
+    }
+
+    /// Write at most `budget` bytes per `present` (e.g. 16KB), the rest of a larger frame is
+    /// written in chunks of the same size while waiting for the events, so a huge first paint
+    /// over a slow link doesn't freeze the input handling. `None` (the default) writes the
+    /// whole frame at once.
+    pub fn write_budget(mut self, budget: Option<usize>) -> Self {
+        self.write_budget = budget;
+        self
+    }
+
+    /// Ask for the kitty keyboard protocol on (re)start, telling apart the keys the legacy
+    /// encoding mixes up, e.g. `Key::CtrlShift('p')` from `Key::Ctrl('p')`. The terminals
+    /// without it ignore the request, those with it set `Capabilities::keyboard_flags`.
+    pub fn kitty_keyboard(mut self, enabled: bool) -> Self {
+        self.kitty_keyboard = enabled;
+        self
+    }
+
+    /// Report the focus changes of the terminal window on (re)start, as
+    /// `TerminalEvent::FocusGained` and `TerminalEvent::FocusLost`
+    pub fn focus_events(mut self, enabled: bool) -> Self {
+        self.focus_events = enabled;
+        self
+    }
+
+    /// Enable bracketed paste on (re)start, a paste is then read as a single
+    /// `Key::BracketedPaste` instead of the keys typed one by one
+    pub fn bracketed_paste(mut self, enabled: bool) -> Self {
+        self.bracketed_paste = enabled;
+        self
+    }
+
+    /// Report the left presses on the same cell in quick succession as
+    /// `MouseEvent::DoubleClick` and `MouseEvent::TripleClick`, at most `interval` apart.
+    /// `Duration::new(0, 0)` (the default) reports every press as `MouseEvent::Press`.
+    pub fn click_interval(mut self, interval: Duration) -> Self {
+        self.click_interval = interval;
+        self
+    }
+
+    /// How long to wait after `ESC` for the rest of an escape sequence, see
+    /// `KeyBoard::esc_timeout`. Overrides the `esc_timeout` user config, 10ms by default.
+    pub fn esc_timeout(mut self, timeout: Duration) -> Self {
+        self.esc_timeout = Some(timeout);
+        self
+    }
+
+    /// Where the terminal is looked for if `/dev/tty` can't be opened, stdin by default. Without
+    /// a terminal, `Term::with_options` fails with a `raw::TtyError::NotATty` (downcast it
+    /// with `Box::<dyn Error>::downcast_ref`).
+    pub fn tty_fallback(mut self, fallback: TtyFallback) -> Self {
+        self.tty_fallback = fallback;
+        self
+    }
+
+    /// Use the terminal at `path` rather than `/dev/tty`, e.g. the pseudo terminal of a
+    /// `testing::FakeTerminal`
+    pub fn tty_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.tty_path = Some(path.into());
+        self
+    }
+
+    /// Hold a lock on the terminal while running (it is released while paused), so that a
+    /// second application locking the same terminal fails to start with a
+    /// `raw::TtyError::InUse`, instead of both garbling the screen. Off by default.
+    ///
+    /// ```no_run
+    /// use tuikit::prelude::*;
+    /// use tuikit::raw::TtyError;
+    /// use tuikit::term::TermOptions;
+    ///
+    /// match Term::with_options(TermOptions::default().lock_tty(true)) {
+    ///     Err(err) if matches!(err.downcast_ref(), Some(TtyError::InUse(_))) => {
+    ///         eprintln!("already running in this terminal: {}", err);
+    ///     }
+    ///     result => {
+    ///         let term: Term = result.unwrap();
+    ///     }
+    /// }
+    /// ```
+    pub fn lock_tty(mut self, enabled: bool) -> Self {
+        self.lock_tty = enabled;
+        self
+    }
 }
 
 impl Term {
@@ -137,16 +464,74 @@ impl Term {
     /// ```
     pub fn with_options(options: TermOptions) -> Result<Term> {
         initialize_signals();
+        set_animation_enabled(!options.accessibility.reduce_motion);
+
+        let event_log = Arc::new(SpinLock::new(EventLog::new(options.event_log_capacity)));
+        if options.event_log_capacity > 0 {
+            dump_on_panic(event_log.clone());
+        }
 
         let (event_tx, event_rx) = channel();
+        let probe_timeout = options.probe_timeout;
+        let probe_capabilities = options.probe_capabilities;
+        let config = options.config.clone();
+        let key_release = options.synthetic_key_release.map(KeyReleaseDetector::new);
+        let resize_debounce = options.resize_debounce;
+        let size_poll_interval = options.size_poll_interval;
+        let capabilities = Capabilities::from_env();
+        let color_depth = options.color_depth;
+        let line_drawing = options.line_drawing;
+        let kitty_keyboard = options.kitty_keyboard;
+        let click_interval = options.click_interval;
+        let esc_timeout = options.esc_timeout;
+        let tty_fallback = options.tty_fallback;
+        let tty_path = options.tty_path.clone();
+        let lock_tty = options.lock_tty;
+        let mut term_lock = TermLock::with_options(options);
+        term_lock.clipboard = SharedClipboard::new(clipboard::detect(&capabilities));
+        term_lock.attr_support = capabilities.attr_support(color_depth);
         let ret = Term {
             stopped: Arc::new(RwLock::new(true)),
             components_to_stop: Arc::new(AtomicUsize::new(0)),
             keyboard_handler: SpinLock::new(None),
             resize_signal_id: Arc::new(AtomicUsize::new(0)),
-            term_lock: SpinLock::new(TermLock::with_options(options)),
+            term_lock: SpinLock::new(term_lock),
             event_tx: Arc::new(SpinLock::new(event_tx)),
             event_rx: SpinLock::new(event_rx),
+            announcer: SpinLock::new(None),
+            event_log,
+            probe_timeout,
+            probe_capabilities,
+            capabilities: SpinLock::new(capabilities),
+            color_depth,
+            line_drawing,
+            kitty_keyboard,
+            click_interval,
+            esc_timeout,
+            tty_fallback,
+            tty_path,
+            lock_tty,
+            tty_lock: SpinLock::new(None),
+            draw_queue: Arc::new(SpinLock::new(DrawQueue::default())),
+            frame_hooks: SpinLock::new(FrameHooks::default()),
+            plugins: SpinLock::new(Plugins::default()),
+            config,
+            key_release: SpinLock::new(key_release),
+            pending_events: SpinLock::new(VecDeque::new()),
+            timeline: SpinLock::new(None),
+            resize_debounce,
+            size_poll_interval,
+            resize_state: SpinLock::new(ResizeState::default()),
+            input_grab: SpinLock::new(InputGrab::new()),
+            widget_states: SpinLock::new(WidgetStates::new()),
+            debug_overlays: SpinLock::new(HashSet::new()),
+            timers: Arc::new(SpinLock::new(TimerWheel::new(Instant::now()))),
+            timer_wake: SpinLock::new(None),
+            #[cfg(feature = "fs-watch")]
+            fs_watcher: SpinLock::new(None),
+            #[cfg(feature = "fs-watch")]
+            config_files: SpinLock::new(ConfigFiles::default()),
+            mouse_encoding: Arc::new(SpinLock::new(None)),
         };
         ret.restart().map(|_| ret)
     }
@@ -170,13 +555,23 @@ impl Term {
     ) -> Result<(usize, usize)> {
         output.ask_for_cpr();
 
-        while let Ok(key) = keyboard.next_key_timeout(WAIT_TIMEOUT) {
-            if let Key::CursorPos(row, col) = key {
-                return Ok((row as usize, col as usize));
+        // the keys typed meanwhile are kept for the key listener
+        match keyboard.cursor_pos_until(Deadline::after(self.probe_timeout)) {
+            Ok((row, col)) => Ok((row as usize, col as usize)),
+            Err(_) => {
+                self.resize_state.lock().cpr_timed_out();
+                Ok((0, 0))
             }
         }
+    }
 
-        Ok((0, 0))
+    /// the terminal at `tty_path` if set, found by `open_tty` otherwise
+    fn open_tty(&self) -> Result<File> {
+        let tty = match self.tty_path.as_ref() {
+            Some(path) => open_tty_path(path)?,
+            None => open_tty(self.tty_fallback)?,
+        };
+        Ok(tty)
     }
 
     /// restart the terminal if it had been stopped
@@ -188,14 +583,44 @@ impl Term {
 
         let mut termlock = self.term_lock.lock();
 
-        let ttyout = get_tty()?.into_raw_mode()?;
+        let tty = self.open_tty()?;
+        if self.lock_tty {
+            self.tty_lock.lock().replace(lock_tty(&tty)?);
+        }
+        let ttyout = tty.into_raw_mode()?;
         let mut output = Output::new(Box::new(ttyout))?;
-        let mut keyboard = KeyBoard::new_with_tty();
+        output.set_line_drawing(self.line_drawing);
+        let mut keyboard = KeyBoard::new(Box::new(self.open_tty()?))
+            .event_log(self.event_log.clone())
+            .mouse_encoding(self.mouse_encoding.clone())
+            .kitty_keyboard(self.kitty_keyboard)
+            .click_interval(self.click_interval);
+        if let Some(timeout) = self.esc_timeout {
+            keyboard = keyboard.esc_timeout(timeout);
+        }
         self.keyboard_handler
             .lock()
             .replace(keyboard.get_interrupt_handler());
         let cursor_pos = self.get_cursor_pos(&mut keyboard, &mut output)?;
+        // the modes enabled before the pause as well
+        termlock.modes.enable(&mut output);
+        if self.kitty_keyboard {
+            output.ask_for_keyboard_flags();
+        }
+        output.flush();
+        if self.probe_capabilities {
+            // the replies are picked up by the key listener
+            output.ask_for_device_attributes();
+            output.ask_for_background_color();
+            for name in TERMCAP_PROBES {
+                output.ask_for_termcap(name);
+            }
+            output.flush();
+        }
         termlock.restart(output, cursor_pos)?;
+        self.resize_state
+            .lock()
+            .size_changed((termlock.screen_width, termlock.screen_height));
 
         // start two listener
         self.components_to_stop.store(0, Ordering::SeqCst);
@@ -203,7 +628,7 @@ impl Term {
         self.start_size_change_listener();
 
         let event_tx = self.event_tx.lock();
-        let _ = event_tx.send(Event::Restarted);
+        let _ = event_tx.send(Event::Terminal(TerminalEvent::Restarted));
 
         *stopped = false;
         Ok(())
@@ -213,7 +638,8 @@ impl Term {
     ///
     /// This function will cause the Term to give away the control to the terminal(such as listening
     /// to the key strokes). After the Term was "paused", `poll_event` will block indefinitely and
-    /// recover after the Term was `restart`ed.
+    /// recover after the Term was `restart`ed. `present` writes nothing meanwhile, the last frame
+    /// is presented on `restart`.
     pub fn pause(&self) -> Result<()> {
         let mut stopped = self.stopped.write().expect("restart: failed to get lock");
         if *stopped {
@@ -223,15 +649,24 @@ impl Term {
         // wait for the components to stop
         // i.e. key_listener & size_change_listener
         self.components_to_stop.store(2, Ordering::SeqCst);
-        self.keyboard_handler.lock().take().map(|h| h.interrupt());
+        let keyboard_handler = self.keyboard_handler.lock().take();
+        if let Some(h) = keyboard_handler.as_ref() {
+            h.interrupt()
+        }
         unregister_sigwinch(self.resize_signal_id.load(Ordering::Relaxed)).map(|tx| tx.send(()));
 
         let mut termlock = self.term_lock.lock();
         termlock.pause()?;
+        // e.g. for the editor run while paused
+        self.tty_lock.lock().take();
 
         // wait for the components to stop
         while self.components_to_stop.load(Ordering::SeqCst) > 0 {
             thread::sleep(POLLING_TIMEOUT);
+            // the key listener drops the interrupts sent before it waits for input
+            if let Some(h) = keyboard_handler.as_ref() {
+                h.interrupt()
+            }
         }
 
         *stopped = true;
@@ -241,10 +676,23 @@ impl Term {
     fn start_key_listener(&self, mut keyboard: KeyBoard) {
         let event_tx_clone = self.event_tx.clone();
         let components_to_stop = self.components_to_stop.clone();
+        let mut closed = false;
         thread::spawn(move || loop {
-            if let Ok(key) = keyboard.next_key() {
-                let event_tx = event_tx_clone.lock();
-                let _ = event_tx.send(Event::Key(key));
+            match keyboard.next_key() {
+                Ok(key) => {
+                    let event_tx = event_tx_clone.lock();
+                    let _ = event_tx.send(Event::from_key(key));
+                }
+                // no more input would come, only wait to be stopped
+                Err(err) if err.is_closed() => {
+                    if !closed {
+                        closed = true;
+                        let event_tx = event_tx_clone.lock();
+                        let _ = event_tx.send(Event::Terminal(TerminalEvent::Closed));
+                    }
+                    thread::sleep(POLLING_TIMEOUT);
+                }
+                Err(_) => {}
             }
 
             if components_to_stop.load(Ordering::Relaxed) > 0 {
@@ -257,60 +705,288 @@ impl Term {
     fn start_size_change_listener(&self) {
         let event_tx_clone = self.event_tx.clone();
         let components_to_stop = self.components_to_stop.clone();
-        let resize_signal_id = self.resize_signal_id.clone();
-        thread::spawn(move || {
-            let (id, sigwinch_rx) = notify_on_sigwinch();
-            resize_signal_id.store(id, Ordering::Relaxed);
-            loop {
-                if let Ok(_) = sigwinch_rx.recv() {
-                    let event_tx = event_tx_clone.lock();
-                    let _ = event_tx.send(Event::Resize {
-                        width: 0,
-                        height: 0,
-                    });
-                }
+        let mut debouncer = ResizeDebouncer::new(self.resize_debounce);
+        let tty = self.size_poll_interval.and_then(|_| self.open_tty().ok());
+        let tty_size = move || {
+            tty.as_ref()
+                .and_then(|tty| terminal_size(tty.as_raw_fd()).ok())
+        };
+        let mut poller = self
+            .size_poll_interval
+            .map(|interval| SizePoller::new(interval, Instant::now(), tty_size()));
+        // registered before the thread runs, for a `pause` right away to unregister it
+        let (id, sigwinch_rx) = notify_on_sigwinch();
+        self.resize_signal_id.store(id, Ordering::Relaxed);
+        thread::spawn(move || loop {
+            let now = Instant::now();
+            let timeout = match (debouncer.timeout(now), poller.as_ref()) {
+                (Some(timeout), Some(poller)) => Some(min(timeout, poller.timeout(now))),
+                (timeout, poller) => timeout.or(poller.map(|poller| poller.timeout(now))),
+            };
+            let received = match timeout {
+                Some(timeout) => sigwinch_rx.recv_timeout(timeout).is_ok(),
+                None => sigwinch_rx.recv().is_ok(),
+            };
+            let now = Instant::now();
+            let polled = poller
+                .as_mut()
+                .is_some_and(|poller| poller.poll(now, &tty_size));
+            if received || polled {
+                debouncer.on_signal(now);
+            }
 
-                if components_to_stop.load(Ordering::Relaxed) > 0 {
-                    components_to_stop.fetch_sub(1, Ordering::SeqCst);
-                    break;
-                }
+            if debouncer.settled(Instant::now()) {
+                let event_tx = event_tx_clone.lock();
+                let _ = event_tx.send(Event::Terminal(TerminalEvent::Resize {
+                    width: 0,
+                    height: 0,
+                }));
+            }
+
+            if components_to_stop.load(Ordering::Relaxed) > 0 {
+                components_to_stop.fetch_sub(1, Ordering::SeqCst);
+                break;
             }
         });
     }
 
-    fn filter_event(&self, event: Event) -> Event {
-        match event {
-            Event::Resize {
-                width: _,
-                height: _,
-            } => {
+    /// `None` for the events handled internally
+    fn filter_event(&self, event: Event) -> Option<Event> {
+        let event = self.resolve_event(event)?;
+        let event = match event {
+            Event::Key(event) => match self.input_grab.lock().on_key(event.key) {
+                Grabbed::No(key) => Event::from_key(key),
+                Grabbed::Consumed => return None,
+                Grabbed::Released(pane) => Event::InputReleased(pane),
+            },
+            Event::Mouse(mouse) => match self.input_grab.lock().on_key(mouse.into()) {
+                Grabbed::No(key) => Event::from_key(key),
+                Grabbed::Consumed => return None,
+                Grabbed::Released(pane) => Event::InputReleased(pane),
+            },
+            event => event,
+        };
+        let released = match &event {
+            Event::Key(event) => self
+                .key_release
+                .lock()
+                .as_mut()
+                .and_then(|detector| detector.on_key(&event.key, Instant::now())),
+            Event::Mouse(mouse) => {
+                let action = self.term_lock.lock().on_mouse_selection(&(*mouse).into());
+                if let Some(action) = action {
+                    self.run_mouse_action(action);
+                }
+                // a mouse event releases the held key too
+                self.key_release
+                    .lock()
+                    .as_mut()
+                    .and_then(|detector| detector.on_key(&(*mouse).into(), Instant::now()))
+            }
+            _ => None,
+        };
+        self.event_log.lock().push_event(&event);
+        self.record_event(&event);
+
+        if let Some(released) = released {
+            // report the release before the key that caused it
+            self.pending_events.lock().push_back(event);
+            let event = Event::KeyUp(released);
+            self.event_log.lock().push_event(&event);
+            self.record_event(&event);
+            return Some(event);
+        }
+        Some(event)
+    }
+
+    /// Run what the mouse selection left to do, with `term_lock` released: a callback could call
+    /// back into `Term`
+    fn run_mouse_action(&self, action: MouseAction) {
+        match action {
+            MouseAction::Call(callback, text) => {
+                let callback = callback.lock().unwrap_or_else(PoisonError::into_inner);
+                callback(&text);
+            }
+            MouseAction::Copy(clipboard, text) if clipboard.blocks => {
+                // e.g. an external command, not to hold the input up; a failed copy is dropped
+                thread::spawn(move || {
+                    let _ = clipboard.lock().copy(&text, &mut io::sink());
+                });
+            }
+            MouseAction::Copy(clipboard, text) => {
+                let mut terminal = Vec::new();
+                // a failed copy should not break the drawing
+                let _ = clipboard.lock().copy(&text, &mut terminal);
+                let _ = self.term_lock.lock().write_raw(&terminal);
+            }
+        }
+    }
+
+    fn resolve_event(&self, event: Event) -> Option<Event> {
+        let event = match event {
+            Event::Terminal(TerminalEvent::Resize { .. }) => {
                 {
                     let mut termlock = self.term_lock.lock();
+                    let size = termlock
+                        .output
+                        .as_ref()
+                        .and_then(|output| output.terminal_size().ok());
+                    if let Some(size) = size {
+                        if !self.resize_state.lock().size_changed(size) {
+                            return None;
+                        }
+                    }
                     let _ = termlock.on_resize();
                 }
                 let (width, height) = self.term_size().unwrap_or((0, 0));
-                Event::Resize { width, height }
+                Event::Terminal(TerminalEvent::Resize { width, height })
+            }
+            Event::Terminal(TerminalEvent::CursorPos { row, col }) => {
+                if self
+                    .resize_state
+                    .lock()
+                    .is_late_reply(&Key::CursorPos(row, col))
+                {
+                    return None;
+                }
+                Event::Terminal(TerminalEvent::CursorPos { row, col })
             }
+            Event::Terminal(TerminalEvent::Reply(key)) => {
+                let mut capabilities = self.capabilities.lock();
+                capabilities.update(&key);
+                self.term_lock.lock().attr_support = capabilities.attr_support(self.color_depth);
+                Event::Terminal(TerminalEvent::CapabilitiesUpdated)
+            }
+            #[cfg(feature = "fs-watch")]
+            Event::Fs(change) => {
+                let (changed, only) = self.config_files.lock().changed(&change);
+                for path in changed.iter() {
+                    // reloaded once the editor is done writing
+                    self.set_timer(&fs_watch::reload_timer(path), fs_watch::RELOAD_DELAY, false);
+                }
+                if only && !changed.is_empty() {
+                    return None;
+                }
+                Event::Fs(change)
+            }
+            #[cfg(feature = "fs-watch")]
+            Event::Timer(id) => match fs_watch::reloaded_file(&id) {
+                Some(path) => Event::ConfigReloaded(path),
+                None => Event::Timer(id),
+            },
             ev => ev,
-        }
+        };
+        Some(event)
     }
 
-    /// Wait an event up to `timeout` and return it
-    pub fn peek_event(&self, timeout: Duration) -> Result<Event> {
-        let event_rx = self.event_rx.lock();
-        event_rx
-            .recv_timeout(timeout)
-            .map(|ev| self.filter_event(ev))
-            .map_err(|_| "timeout".to_string().into())
+    /// Wait an event up to `timeout` and return it, fails with `InputError::Timeout` if none
+    /// came
+    pub fn peek_event(&self, timeout: Duration) -> std::result::Result<Event, InputError> {
+        self.next_event(Some(Instant::now() + timeout))
     }
 
     /// Wait for an event indefinitely and return it
-    pub fn poll_event(&self) -> Result<Event> {
+    pub fn poll_event(&self) -> std::result::Result<Event, InputError> {
+        self.next_event(None)
+    }
+
+    fn next_event(&self, deadline: Option<Instant>) -> std::result::Result<Event, InputError> {
         let event_rx = self.event_rx.lock();
-        event_rx
-            .recv()
-            .map(|ev| self.filter_event(ev))
-            .map_err(|err| err.to_string().into())
+        loop {
+            if let Some(event) = self.pending_events.lock().pop_front() {
+                return Ok(event);
+            }
+
+            let release_at = self
+                .key_release
+                .lock()
+                .as_ref()
+                .and_then(|detector| detector.release_at());
+            let wait_until = match (deadline, release_at) {
+                (Some(deadline), Some(release_at)) => Some(min(deadline, release_at)),
+                (deadline, release_at) => deadline.or(release_at),
+            };
+            // only check for events between the chunks of a large frame
+            let wait_until = if self.term_lock.lock().flush_chunk() {
+                Some(Instant::now())
+            } else {
+                wait_until
+            };
+            let received = match wait_until {
+                Some(wait_until) => {
+                    event_rx.recv_timeout(wait_until.saturating_duration_since(Instant::now()))
+                }
+                None => event_rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+
+            match received {
+                Ok(event) => {
+                    if let Some(event) = self.filter_event(event) {
+                        return Ok(event);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    let now = Instant::now();
+                    let released = self
+                        .key_release
+                        .lock()
+                        .as_mut()
+                        .and_then(|detector| detector.expire(now));
+                    if let Some(key) = released {
+                        let event = Event::KeyUp(key);
+                        self.event_log.lock().push_event(&event);
+                        self.record_event(&event);
+                        return Ok(event);
+                    }
+                    if deadline.is_some_and(|deadline| deadline <= now) {
+                        return Err(InputError::Timeout);
+                    }
+                }
+                Err(err) => {
+                    let closed = io::Error::new(io::ErrorKind::UnexpectedEof, err.to_string());
+                    return Err(InputError::Io(closed));
+                }
+            }
+        }
+    }
+
+    /// A copy of the screen buffer, i.e. what is shown after the next `present`
+    pub fn snapshot(&self) -> ScreenSnapshot {
+        ScreenSnapshot::from_screen(&self.term_lock.lock().screen)
+    }
+
+    /// Start recording the presented frames and the returned events, see `tuikit::timeline`.
+    /// A recording in progress is discarded.
+    ///
+    /// ```no_run
+    /// use tuikit::prelude::*;
+    ///
+    /// let term = Term::new().unwrap();
+    /// term.start_recording();
+    /// // ... the event loop
+    /// if let Some(timeline) = term.stop_recording() {
+    ///     timeline.save("/tmp/timeline.tktl").unwrap();
+    /// }
+    /// ```
+    pub fn start_recording(&self) {
+        self.timeline.lock().replace(Timeline::new());
+    }
+
+    /// Stop recording and return the timeline, `None` if not recording
+    pub fn stop_recording(&self) -> Option<Timeline> {
+        self.timeline.lock().take()
+    }
+
+    fn record_frame(&self, screen: &Screen) {
+        if let Some(timeline) = self.timeline.lock().as_mut() {
+            timeline.record_frame(screen);
+        }
+    }
+
+    fn record_event(&self, event: &Event) {
+        if let Some(timeline) = self.timeline.lock().as_mut() {
+            timeline.record_event(event);
+        }
     }
 
     /// An interface to inject event to the terminal's event queue
@@ -319,83 +995,819 @@ impl Term {
         event_tx.send(event).map_err(|err| err.to_string().into())
     }
 
-    /// Sync internal buffer with terminal
-    pub fn present(&self) -> Result<()> {
+    /// The user config of `TermOptions::config`, e.g. for the `theme` preference, empty unless
+    /// the application opted in. See `tuikit::config`
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Write the operating system command `ESC ] code ; payload` right away, see
+    /// `Output::write_osc`
+    pub fn write_osc(&self, code: usize, payload: &str) -> Result<()> {
+        self.ensure_not_stopped()?;
+        let mut termlock = self.term_lock.lock();
+        let output = termlock.output.as_mut().ok_or("term had been stopped")?;
+        output.write_osc(code, payload);
+        output.flush();
+        Ok(())
+    }
+
+    /// Write the device control string `ESC P payload ESC \` right away, see
+    /// `Output::write_dcs`
+    pub fn write_dcs(&self, payload: &str) -> Result<()> {
+        self.ensure_not_stopped()?;
+        let mut termlock = self.term_lock.lock();
+        let output = termlock.output.as_mut().ok_or("term had been stopped")?;
+        output.write_dcs(payload);
+        output.flush();
+        Ok(())
+    }
+
+    /// Ask for the terminfo capabilities `names` (XTGETTCAP) without waiting,
+    /// `TerminalEvent::CapabilitiesUpdated` is returned when the replies arrive, see
+    /// `Capabilities::termcap`
+    pub fn query_termcap(&self, names: &[&str]) -> Result<()> {
+        self.ensure_not_stopped()?;
+        let mut termlock = self.term_lock.lock();
+        let output = termlock.output.as_mut().ok_or("term had been stopped")?;
+        for name in names {
+            output.ask_for_termcap(name);
+        }
+        output.flush();
+        Ok(())
+    }
+
+    /// The capabilities of the terminal known so far, see `TermOptions::probe_capabilities`
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities.lock().clone()
+    }
+
+    /// Change the strategy to find out the cells to repaint on `present`
+    pub fn set_diff_strategy(&self, strategy: DiffStrategy) {
+        self.term_lock.lock().screen.set_diff_strategy(strategy);
+    }
+
+    /// Write the recent input bytes and events, oldest first
+    pub fn dump_event_log(&self, writer: &mut dyn std::io::Write) -> std::io::Result<()> {
+        self.event_log.lock().dump(writer)
+    }
+
+    /// Push synthetic key events into the event queue, they are returned by `poll_event` just
+    /// like the keys typed by the user. Useful for scripting and automation.
+    ///
+    /// ```no_run
+    /// use tuikit::prelude::*;
+    ///
+    /// let term = Term::new().unwrap();
+    /// term.inject_keys(&[Key::Ctrl('a'), Key::Enter]).unwrap();
+    /// ```
+    pub fn inject_keys(&self, keys: &[Key]) -> Result<()> {
+        let event_tx = self.event_tx.lock();
+        for key in keys {
+            event_tx
+                .send(Event::from_key(key.clone()))
+                .map_err(|err| err.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Queue a message for the widget `target`, returned by `poll_event` as `Event::Message`.
+    /// See `tuikit::widget_id`.
+    pub fn post_to(&self, target: &WidgetId, message: &str) -> Result<()> {
+        let event = Event::Message {
+            target: target.clone(),
+            message: message.to_string(),
+        };
+        self.event_tx
+            .lock()
+            .send(event)
+            .map_err(|err| err.to_string())?;
+        Ok(())
+    }
+
+    /// Replace the state of the widget `id` queried by `widget_state`, e.g. its selection,
+    /// usually published while drawing
+    pub fn publish_widget_state(&self, id: &WidgetId, state: &str) {
+        self.widget_states.lock().publish(id, state);
+    }
+
+    /// Forget the state of `id` and its descendants, once removed from the tree
+    pub fn remove_widget_state(&self, id: &WidgetId) {
+        self.widget_states.lock().remove(id);
+    }
+
+    /// The state last published by the widget `id`
+    pub fn widget_state(&self, id: &WidgetId) -> Option<String> {
+        self.widget_states.lock().state(id).map(str::to_string)
+    }
+
+    /// The ids of the widgets with a published state
+    pub fn widget_ids(&self) -> Vec<WidgetId> {
+        self.widget_states.lock().ids()
+    }
+
+    /// Turn the debug overlay `name` (e.g. `"regions"`) on or off, `Event::DebugOverlay` is
+    /// returned by `poll_event` if it changed. The overlays are drawn by the application, see
+    /// `debug_overlay`.
+    pub fn set_debug_overlay(&self, name: &str, enabled: bool) {
+        let changed = {
+            let mut overlays = self.debug_overlays.lock();
+            if enabled {
+                overlays.insert(name.to_string())
+            } else {
+                overlays.remove(name)
+            }
+        };
+        if changed {
+            let _ = self.event_tx.lock().send(Event::DebugOverlay {
+                name: name.to_string(),
+                enabled,
+            });
+        }
+    }
+
+    /// Whether the debug overlay `name` is on
+    pub fn debug_overlay(&self, name: &str) -> bool {
+        self.debug_overlays.lock().contains(name)
+    }
+
+    /// Start the timer `id`, returned by `poll_event` as `Event::Timer(id)` after `delay`, then
+    /// every `delay` if `repeating`. A running timer of the same id is restarted. See
+    /// `tuikit::timer`.
+    pub fn set_timer(&self, id: &str, delay: Duration, repeating: bool) {
+        self.timers.lock().set(id, delay, repeating, Instant::now());
+        self.wake_timer_thread();
+    }
+
+    /// Stop the timer `id`, return false if it wasn't running
+    pub fn cancel_timer(&self, id: &str) -> bool {
+        self.timers.lock().cancel(id)
+    }
+
+    /// wake the timer thread up to wait for the new timer, starting it if needed
+    fn wake_timer_thread(&self) {
+        let mut timer_wake = self.timer_wake.lock();
+        if let Some(wake) = timer_wake.as_ref() {
+            if wake.send(()).is_ok() {
+                return;
+            }
+        }
+
+        let (wake_tx, wake_rx) = channel();
+        let timers = self.timers.clone();
+        let event_tx = self.event_tx.clone();
+        thread::spawn(move || loop {
+            let (expired, timeout) = {
+                let mut timers = timers.lock();
+                let now = Instant::now();
+                (timers.advance(now), timers.timeout(now))
+            };
+            for id in expired {
+                let _ = event_tx.lock().send(Event::Timer(id));
+            }
+            let woken = match timeout {
+                Some(timeout) => wake_rx.recv_timeout(timeout),
+                None => wake_rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            // the term was dropped
+            if let Err(RecvTimeoutError::Disconnected) = woken {
+                break;
+            }
+        });
+        timer_wake.replace(wake_tx);
+    }
+
+    /// Watch the file or directory `path` (and its subdirectories if `recursive`), its changes
+    /// are returned by `poll_event` as `Event::Fs`. See `tuikit::fs_watch`.
+    #[cfg(feature = "fs-watch")]
+    pub fn watch_path(&self, path: impl AsRef<std::path::Path>, recursive: bool) -> Result<()> {
+        let mut fs_watcher = self.fs_watcher.lock();
+        if fs_watcher.is_none() {
+            fs_watcher.replace(FsWatcher::new(self.event_tx.clone())?);
+        }
+        let watcher = fs_watcher
+            .as_mut()
+            .expect("watch_path: the watcher was just created");
+        watcher.watch(path.as_ref(), recursive)?;
+        Ok(())
+    }
+
+    /// Stop watching `path`
+    #[cfg(feature = "fs-watch")]
+    pub fn unwatch_path(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        if let Some(watcher) = self.fs_watcher.lock().as_mut() {
+            watcher.unwatch(path.as_ref())?;
+        }
+        Ok(())
+    }
+
+    /// Watch the config (or theme, keymap...) file `path`, `Event::ConfigReloaded` is returned
+    /// once it was changed and left unchanged for 50ms. Its directory is watched so that the
+    /// file is still watched after an editor replaced it. See `tuikit::fs_watch`.
+    #[cfg(feature = "fs-watch")]
+    pub fn watch_config_file(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        let (dir, file) = fs_watch::split_config_path(path.as_ref())?;
+        self.watch_path(&dir, false)?;
+        self.config_files.lock().insert(dir.join(file));
+        Ok(())
+    }
+
+    /// Stop watching the config file `path`
+    #[cfg(feature = "fs-watch")]
+    pub fn unwatch_config_file(&self, path: impl AsRef<std::path::Path>) -> Result<()> {
+        let (dir, file) = fs_watch::split_config_path(path.as_ref())?;
+        if !self.config_files.lock().remove(&dir.join(file)) {
+            self.unwatch_path(&dir)?;
+        }
+        Ok(())
+    }
+
+    /// Register the consumer of the input grabbed by `pane`, replacing the previous one, see
+    /// `tuikit::grab`
+    pub fn register_input_consumer(&self, pane: &str, consumer: InputConsumer) {
+        self.input_grab.lock().register(pane, consumer);
+    }
+
+    /// Unregister the consumer of `pane`, the input is released if it grabbed it
+    pub fn unregister_input_consumer(&self, pane: &str) {
+        self.input_grab.lock().unregister(pane);
+    }
+
+    /// Route all the key and mouse events to the consumer of `pane` until the escape chord is
+    /// typed, then `Event::InputReleased` is returned
+    pub fn grab_input(&self, pane: &str) -> Result<()> {
+        if self.input_grab.lock().grab(pane) {
+            Ok(())
+        } else {
+            Err(format!("no input consumer registered for {:?}", pane).into())
+        }
+    }
+
+    /// Give the input back to the application, return the pane that grabbed it
+    pub fn release_input(&self) -> Option<String> {
+        self.input_grab.lock().release()
+    }
+
+    /// The pane grabbing the input
+    pub fn input_grabbed_by(&self) -> Option<String> {
+        self.input_grab.lock().grabbed().map(str::to_string)
+    }
+
+    /// The keys releasing the grabbed input, `Ctrl-b d` by default
+    pub fn set_grab_escape_chord(&self, keys: Vec<Key>) {
+        self.input_grab.lock().set_escape_chord(keys);
+    }
+
+    /// Push the key events of typing `text`: `\n` is sent as `Enter` and `\t` as `Tab`, other
+    /// characters as `Char`.
+    pub fn inject_text(&self, text: &str) -> Result<()> {
+        let keys: Vec<Key> = text
+            .chars()
+            .map(|ch| match ch {
+                '\n' => Key::Enter,
+                '\t' => Key::Tab,
+                ch => Key::Char(ch),
+            })
+            .collect();
+        self.inject_keys(&keys)
+    }
+
+    /// Sync internal buffer with terminal, after applying the commands queued by the
+    /// `DrawHandle`s
+    ///
+    /// While the term is paused (e.g. a shell runs in the foreground) nothing is written to the
+    /// terminal, the frame is deferred to `restart` instead, so it is safe to present from
+    /// background threads at any time.
+    pub fn present(&self) -> Result<PresentStatus> {
+        self.present_with(None, |termlock| termlock.present())
+    }
+
+    /// Sync only the `width` x `height` area at `(top, left)` with the terminal
+    pub fn present_area(
+        &self,
+        top: usize,
+        left: usize,
+        width: usize,
+        height: usize,
+    ) -> Result<PresentStatus> {
+        self.present_with(Some((top, left, width, height)), |termlock| {
+            termlock.present_area(top, left, width, height)
+        })
+    }
+
+    /// `area` is `None` for the whole screen
+    fn present_with(
+        &self,
+        area: Option<(usize, usize, usize, usize)>,
+        present: impl FnOnce(&mut TermLock) -> Result<PresentStatus>,
+    ) -> Result<PresentStatus> {
+        // held while presenting, so `pause` waits for the frame to be written
+        let stopped = self.stopped.read().expect("present: failed to get lock");
+        let mut termlock = self.term_lock.lock();
+        self.draw_queue.lock().apply(&mut termlock.screen);
+        if *stopped {
+            termlock.present_deferred = true;
+            return Ok(PresentStatus::Deferred);
+        }
+        let (width, height) = (termlock.screen.width(), termlock.screen.height());
+        let layers = self.plugins.lock().layers();
+        let frame = self
+            .frame_hooks
+            .lock()
+            .start(area.unwrap_or((0, 0, width, height)));
+        if layers.is_empty() && !frame.has_hooks() {
+            let status = present(&mut termlock)?;
+            if status == PresentStatus::Presented {
+                self.record_frame(&termlock.screen);
+            }
+            return Ok(status);
+        }
+
+        // the layers and the hooks are drawn unlocked, so that they could call the term back
+        drop(termlock);
+        drop(stopped);
+        let mut canvas = DetachedCanvas::new(width, height);
+        plugin::draw_layers(&layers, &mut canvas)?;
+        frame.pre_present(&mut canvas)?;
+
+        let stopped = self.stopped.read().expect("present: failed to get lock");
+        let mut termlock = self.term_lock.lock();
+        canvas.apply(&mut *termlock)?;
+        let status = if *stopped {
+            // paused meanwhile
+            termlock.present_deferred = true;
+            PresentStatus::Deferred
+        } else {
+            present(&mut termlock)?
+        };
+        if status == PresentStatus::Presented {
+            self.record_frame(&termlock.screen);
+        }
+        let screen = termlock.screen.clone();
+        drop(termlock);
+        drop(stopped);
+        frame.post_present(status, &screen);
+        Ok(status)
+    }
+
+    /// Register `hook`, called around each `present` and `present_area`, return its id. See
+    /// `tuikit::frame_hook`.
+    pub fn add_frame_hook(&self, hook: Box<dyn FrameHook>) -> usize {
+        self.frame_hooks.lock().add(hook)
+    }
+
+    /// Unregister the frame hook of `id`, return `false` if there is none
+    pub fn remove_frame_hook(&self, id: usize) -> bool {
+        self.frame_hooks.lock().remove(id)
+    }
+
+    /// Add `plugin`, replacing the one of the same name, see `tuikit::plugin`
+    pub fn add_plugin(&self, mut plugin: Box<dyn Plugin>) {
+        // started and stopped with the plugins unlocked, like the layers are drawn
+        self.remove_plugin(plugin.name());
+        plugin.start(EventSender::new(self.event_tx.clone()));
+        let replaced = self.plugins.lock().add(plugin);
+        if let Some(replaced) = replaced {
+            replaced.lock().stop();
+        }
+    }
+
+    /// A sender of events to this term from other threads, e.g. for a `widget::LogSender`
+    pub fn event_sender(&self) -> EventSender {
+        EventSender::new(self.event_tx.clone())
+    }
+
+    /// Stop and remove the plugin `name`, return `false` if there is none
+    pub fn remove_plugin(&self, name: &str) -> bool {
+        let removed = self.plugins.lock().remove(name);
+        match removed {
+            Some(plugin) => {
+                plugin.lock().stop();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The commands the plugins offer to the command palette
+    pub fn plugin_commands(&self) -> Vec<PaletteCommand> {
+        self.plugins.lock().commands()
+    }
+
+    /// Run the plugin command `name`, e.g. chosen in the palette, return `false` if no plugin
+    /// offers it
+    pub fn run_plugin_command(&self, name: &str) -> bool {
+        let owner = self.plugins.lock().command_owner(name);
+        match owner {
+            Some(plugin) => {
+                plugin.lock().run_command(name);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// With `TermOptions::software_blink`, the time until the blinking cells toggle, i.e. when
+    /// to `present` again. `None` if nothing blinks.
+    ///
+    /// ```no_run
+    /// use tuikit::prelude::*;
+    /// use std::time::Duration;
+    ///
+    /// let term = Term::new().unwrap();
+    /// let timeout = term.next_blink().unwrap_or(Duration::from_secs(1));
+    /// if term.peek_event(timeout).is_err() {
+    ///     term.present().unwrap();
+    /// }
+    /// ```
+    pub fn next_blink(&self) -> Option<Duration> {
+        self.term_lock.lock().next_blink()
+    }
+
+    /// Whether the mouse selection changed since the last frame, its highlight is drawn by the
+    /// next `present`. See `enable_mouse_selection`.
+    pub fn needs_present(&self) -> bool {
+        self.term_lock.lock().selection_dirty
+    }
+
+    /// Mark an area (e.g. a log tail panel) as a refresh zone presented every `interval` by
+    /// `present_zones`, independently of the rest of the screen which is presented on demand.
+    /// Return the id of the zone.
+    ///
+    /// ```no_run
+    /// use tuikit::prelude::*;
+    /// use std::time::Duration;
+    ///
+    /// let term = Term::new().unwrap();
+    /// let (width, height) = term.term_size().unwrap();
+    /// term.add_refresh_zone(height - 5, 0, width, 5, Duration::from_millis(16));
+    /// loop {
+    ///     match term.peek_event(Duration::from_millis(16)) {
+    ///         Ok(_event) => {
+    ///             // handle the event, redraw everything
+    ///             term.present().unwrap();
+    ///         }
+    ///         Err(_) => {
+    ///             // the log panel is drawn by a worker through a `DrawHandle`
+    ///             term.present_zones().unwrap();
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn add_refresh_zone(
+        &self,
+        top: usize,
+        left: usize,
+        width: usize,
+        height: usize,
+        interval: Duration,
+    ) -> usize {
+        let mut termlock = self.term_lock.lock();
+        termlock.next_zone_id += 1;
+        let id = termlock.next_zone_id;
+        termlock.refresh_zones.push(RefreshZone {
+            id,
+            area: (top, left, width, height),
+            interval,
+            last_presented: None,
+        });
+        id
+    }
+
+    pub fn remove_refresh_zone(&self, id: usize) {
+        self.term_lock
+            .lock()
+            .refresh_zones
+            .retain(|zone| zone.id != id);
+    }
+
+    /// Present the refresh zones whose interval elapsed, return `true` if any was presented
+    pub fn present_zones(&self) -> Result<bool> {
+        let stopped = self
+            .stopped
+            .read()
+            .expect("present_zones: failed to get lock");
+        let mut termlock = self.term_lock.lock();
+        self.draw_queue.lock().apply(&mut termlock.screen);
+        if *stopped {
+            termlock.present_deferred = true;
+            return Ok(false);
+        }
+
+        let now = Instant::now();
+        let due: Vec<(usize, usize, usize, usize)> = termlock
+            .refresh_zones
+            .iter_mut()
+            .filter(|zone| {
+                zone.last_presented
+                    .map_or(true, |last| now.duration_since(last) >= zone.interval)
+            })
+            .map(|zone| {
+                zone.last_presented = Some(now);
+                zone.area
+            })
+            .collect();
+        for &(top, left, width, height) in due.iter() {
+            if termlock.present_area(top, left, width, height)? == PresentStatus::Deferred {
+                return Ok(false);
+            }
+        }
+        if !due.is_empty() {
+            self.record_frame(&termlock.screen);
+        }
+        Ok(!due.is_empty())
+    }
+
+    /// A handle for other threads to queue drawing, applied on the next `present`.
+    /// See `tuikit::draw_handle`.
+    pub fn draw_handle(&self) -> DrawHandle {
+        DrawHandle::new(self.draw_queue.clone())
+    }
+
+    /// Return the printable size(width, height) of the term
+    pub fn term_size(&self) -> Result<(usize, usize)> {
+        self.ensure_not_stopped()?;
+        let termlock = self.term_lock.lock();
+        termlock.term_size()
+    }
+
+    /// Clear internal buffer, and the regions registered while drawing
+    pub fn clear(&self) -> Result<()> {
+        self.ensure_not_stopped()?;
+        let mut termlock = self.term_lock.lock();
+        termlock.clear()
+    }
+
+    /// Change a cell of position `(row, col)` to `cell`
+    pub fn put_cell(&self, row: usize, col: usize, cell: Cell) -> Result<usize> {
+        self.ensure_not_stopped()?;
+        let mut termlock = self.term_lock.lock();
+        termlock.put_cell(row, col, cell)
+    }
+
+    /// Print `content` starting with position `(row, col)`
+    pub fn print(&self, row: usize, col: usize, content: &str) -> Result<usize> {
+        self.print_with_attr(row, col, content, Attr::default())
+    }
+
+    /// print `content` starting with position `(row, col)` with `attr`
+    pub fn print_with_attr(
+        &self,
+        row: usize,
+        col: usize,
+        content: &str,
+        attr: impl Into<Attr>,
+    ) -> Result<usize> {
+        self.ensure_not_stopped()?;
+        let mut termlock = self.term_lock.lock();
+        termlock.print_with_attr(row, col, content, attr)
+    }
+
+    /// Print every `(row, col, text, attr)` in order with the lock taken once, see
+    /// `Canvas::print_batch`
+    pub fn print_batch<'t>(&self, batch: impl IntoIterator<Item = PrintItem<'t>>) -> Result<()> {
+        let mut batch: Vec<PrintItem> = batch.into_iter().collect();
+        self.ensure_not_stopped()?;
+        let mut termlock = self.term_lock.lock();
+        termlock.screen.print_batch_slice(&mut batch)
+    }
+
+    /// Set cursor position to (row, col), and show the cursor
+    pub fn set_cursor(&self, row: usize, col: usize) -> Result<()> {
+        self.ensure_not_stopped()?;
+        let mut termlock = self.term_lock.lock();
+        termlock.set_cursor(row, col)
+    }
+
+    /// show/hide cursor, set `show` to `false` to hide the cursor
+    pub fn show_cursor(&self, show: bool) -> Result<()> {
+        self.ensure_not_stopped()?;
+        let mut termlock = self.term_lock.lock();
+        termlock.show_cursor(show)
+    }
+
+    /// Enable mouse support
+    pub fn enable_mouse_support(&self) -> Result<()> {
+        self.ensure_not_stopped()?;
+        let mut termlock = self.term_lock.lock();
+        termlock.enable_mouse_support()
+    }
+
+    /// Disable mouse support
+    pub fn disable_mouse_support(&self) -> Result<()> {
+        self.ensure_not_stopped()?;
+        let mut termlock = self.term_lock.lock();
+        termlock.disable_mouse_support()
+    }
+
+    /// Enable mouse drag tracking, `MouseEvent::Hold` will be reported while a button is held
+    pub fn enable_mouse_drag_support(&self) -> Result<()> {
+        self.ensure_not_stopped()?;
+        let mut termlock = self.term_lock.lock();
+        termlock.enable_mouse_drag_support()
+    }
+
+    /// Copy `text` to the clipboard, see `tuikit::clipboard`
+    pub fn copy_to_clipboard(&self, text: &str) -> Result<()> {
+        self.ensure_not_stopped()?;
+        // copied without `term_lock`, the clipboard could take a while
+        let clipboard = self.term_lock.lock().clipboard.clone();
+        let mut terminal = Vec::new();
+        clipboard.lock().copy(text, &mut terminal)?;
+        self.term_lock.lock().write_raw(&terminal)
+    }
+
+    /// The text of the clipboard, `None` if it could not be read (e.g. with OSC 52)
+    pub fn paste_from_clipboard(&self) -> Option<String> {
+        let clipboard = self.term_lock.lock().clipboard.clone();
+        let text = clipboard.lock().paste();
+        text
+    }
+
+    /// Replace the clipboard detected on start
+    pub fn set_clipboard(&self, clipboard: Box<dyn Clipboard>) {
+        self.term_lock.lock().clipboard = SharedClipboard::new(clipboard);
+    }
+
+    /// e.g. `"osc52"` or `"xclip"`, see `Clipboard::name`
+    pub fn clipboard_name(&self) -> String {
+        let clipboard = self.term_lock.lock().clipboard.clone();
+        let name = clipboard.lock().name().to_string();
+        name
+    }
+
+    /// The encoding of the last mouse report, `None` before the first one. The SGR encoding is
+    /// preferred, with X10 the clicks past the column 223 can't be reported.
+    pub fn mouse_encoding(&self) -> Option<MouseEncoding> {
+        *self.mouse_encoding.lock()
+    }
+
+    /// Disable mouse drag tracking
+    pub fn disable_mouse_drag_support(&self) -> Result<()> {
+        self.ensure_not_stopped()?;
+        let mut termlock = self.term_lock.lock();
+        termlock.disable_mouse_drag_support()
+    }
+
+    /// Enable mouse motion tracking, `MouseEvent::Move` will be reported as the pointer moves
+    /// with no button held, e.g. for tooltips or hover highlights
+    pub fn enable_mouse_motion_support(&self) -> Result<()> {
         self.ensure_not_stopped()?;
         let mut termlock = self.term_lock.lock();
-        termlock.present()
+        termlock.enable_mouse_motion_support()
     }
 
-    /// Return the printable size(width, height) of the term
-    pub fn term_size(&self) -> Result<(usize, usize)> {
+    /// Disable mouse motion tracking
+    pub fn disable_mouse_motion_support(&self) -> Result<()> {
         self.ensure_not_stopped()?;
-        let termlock = self.term_lock.lock();
-        Ok(termlock.term_size()?)
+        let mut termlock = self.term_lock.lock();
+        termlock.disable_mouse_motion_support()
     }
 
-    /// Clear internal buffer
-    pub fn clear(&self) -> Result<()> {
+    /// Let the library handle text selection: a left-button drag highlights the cells and on
+    /// release the selected text is copied to `target`. A double click selects a word and a
+    /// triple click a line, given `TermOptions::click_interval`. The mouse events are still
+    /// returned by `poll_event`. Mouse support and drag tracking are enabled as well.
+    ///
+    /// The highlight is drawn by the next `present`, e.g. after handling the mouse event, see
+    /// `needs_present`.
+    ///
+    /// ```no_run
+    /// use tuikit::prelude::*;
+    /// use tuikit::selection::CopyTarget;
+    ///
+    /// let term = Term::new().unwrap();
+    /// term.enable_mouse_selection(CopyTarget::Clipboard).unwrap();
+    /// ```
+    pub fn enable_mouse_selection(&self, target: CopyTarget) -> Result<()> {
         self.ensure_not_stopped()?;
         let mut termlock = self.term_lock.lock();
-        termlock.clear()
+        termlock.enable_mouse_support()?;
+        termlock.enable_mouse_drag_support()?;
+        termlock.mouse_selection = Some((MouseSelector::new(), SelectionTarget::from(target)));
+        Ok(())
     }
 
-    /// Change a cell of position `(row, col)` to `cell`
-    pub fn put_cell(&self, row: usize, col: usize, cell: Cell) -> Result<usize> {
+    /// Stop handling text selection, mouse support is left as is
+    pub fn disable_mouse_selection(&self) {
+        self.term_lock.lock().mouse_selection = None;
+    }
+
+    /// Open the URLs and file paths shown on the screen with `opener` on `Ctrl` + left click,
+    /// the click is still returned by `poll_event`. Mouse support is enabled as well. See
+    /// `selection::find_links` for what is detected.
+    ///
+    /// ```no_run
+    /// use tuikit::prelude::*;
+    ///
+    /// let term = Term::new().unwrap();
+    /// term.enable_link_detection(Box::new(|link| {
+    ///     let _ = std::process::Command::new("xdg-open").arg(link).spawn();
+    /// }))
+    /// .unwrap();
+    /// ```
+    pub fn enable_link_detection(&self, opener: LinkOpener) -> Result<()> {
         self.ensure_not_stopped()?;
         let mut termlock = self.term_lock.lock();
-        termlock.put_cell(row, col, cell)
+        termlock.enable_mouse_support()?;
+        termlock.link_opener = Some(Arc::new(Mutex::new(opener)));
+        Ok(())
     }
 
-    /// Print `content` starting with position `(row, col)`
-    pub fn print(&self, row: usize, col: usize, content: &str) -> Result<usize> {
-        self.print_with_attr(row, col, content, Attr::default())
+    /// Stop opening the links, mouse support is left as is
+    pub fn disable_link_detection(&self) {
+        self.term_lock.lock().link_opener = None;
     }
 
-    /// print `content` starting with position `(row, col)` with `attr`
-    pub fn print_with_attr(
+    /// Highlight the columns `cols` of `row` drawn so far with `attr`, keeping their contents,
+    /// see `Screen::highlight_row`
+    pub fn highlight_row(
         &self,
         row: usize,
-        col: usize,
-        content: &str,
-        attr: impl Into<Attr>,
-    ) -> Result<usize> {
-        self.ensure_not_stopped()?;
-        let mut termlock = self.term_lock.lock();
-        termlock.print_with_attr(row, col, content, attr)
+        cols: Range<usize>,
+        attr: Attr,
+        boundary: WideCharBoundary,
+    ) -> Range<usize> {
+        self.term_lock
+            .lock()
+            .screen
+            .highlight_row(row, cols, attr, boundary)
     }
 
-    /// Set cursor position to (row, col), and show the cursor
-    pub fn set_cursor(&self, row: usize, col: usize) -> Result<()> {
-        self.ensure_not_stopped()?;
-        let mut termlock = self.term_lock.lock();
-        termlock.set_cursor(row, col)
+    /// Draw additional cursors on `present`, e.g. of the other users of a collaborative editor,
+    /// while the terminal cursor stays where `set_cursor` put it. See `GhostCursor`.
+    pub fn set_ghost_cursors(&self, cursors: Vec<GhostCursor>) {
+        self.term_lock.lock().screen.set_ghost_cursors(cursors);
     }
 
-    /// show/hide cursor, set `show` to `false` to hide the cursor
-    pub fn show_cursor(&self, show: bool) -> Result<()> {
-        self.ensure_not_stopped()?;
-        let mut termlock = self.term_lock.lock();
-        termlock.show_cursor(show)
+    /// Hint that `row` changes on every frame, see `DiffStrategy::RowHash`
+    pub fn mark_volatile(&self, row: usize) -> Result<()> {
+        self.term_lock.lock().screen.mark_volatile(row)
     }
 
-    /// Enable mouse support
-    pub fn enable_mouse_support(&self) -> Result<()> {
-        self.ensure_not_stopped()?;
-        let mut termlock = self.term_lock.lock();
-        termlock.enable_mouse_support()
+    /// Register an actionable region of the screen until the next `clear`, usually called
+    /// through `Canvas::register_region` while drawing
+    pub fn register_region(&self, region: Region) {
+        self.term_lock.lock().regions.register(region);
     }
 
-    /// Disable mouse support
-    pub fn disable_mouse_support(&self) -> Result<()> {
-        self.ensure_not_stopped()?;
-        let mut termlock = self.term_lock.lock();
-        termlock.disable_mouse_support()
+    /// The regions registered for the current frame, e.g. for `FocusRing::focus_towards`
+    pub fn regions(&self) -> Vec<Region> {
+        self.term_lock.lock().regions.regions().to_vec()
+    }
+
+    /// The regions containing `(row, col)` of the screen, topmost first. Note that mouse
+    /// coordinates are 1-based.
+    pub fn regions_at(&self, row: usize, col: usize) -> Vec<Region> {
+        let termlock = self.term_lock.lock();
+        termlock
+            .regions
+            .regions_at(row, col)
+            .into_iter()
+            .cloned()
+            .collect()
     }
 
-    pub fn draw(&self, draw: &Draw) -> Result<()> {
-        let mut canvas = TermCanvas { term: &self };
-        draw.draw(&mut canvas)
+    /// The link hints overlay for the registered regions
+    pub fn region_hints(&self) -> Hints<Region> {
+        self.term_lock.lock().regions.hints()
+    }
+
+    /// Set the side channel for screen readers, `None` to disable announcements
+    pub fn set_announcer(&self, announcer: Option<Box<dyn Announcer>>) {
+        *self.announcer.lock() = announcer;
+    }
+
+    /// Send a semantic event (focus change, selection, status) to the announcer if any
+    pub fn announce(&self, announcement: Announcement) {
+        if let Some(announcer) = self.announcer.lock().as_mut() {
+            announcer.announce(&announcement);
+        }
+    }
+
+    /// Return the accessibility options in use
+    pub fn accessibility(&self) -> AccessibilityOptions {
+        self.term_lock.lock().accessibility
+    }
+
+    /// Change the accessibility options, take effect on next `present`
+    pub fn set_accessibility(&self, accessibility: AccessibilityOptions) {
+        set_animation_enabled(!accessibility.reduce_motion);
+        self.term_lock.lock().accessibility = accessibility;
+    }
+
+    /// Draw `draw` with a `DrawContext` of the whole screen, telling whether it is the first
+    /// frame since a resize
+    pub fn draw(&self, draw: &dyn Draw) -> Result<()> {
+        self.ensure_not_stopped()?;
+        let ctx = {
+            let mut termlock = self.term_lock.lock();
+            let (width, height) = termlock.term_size()?;
+            let resized = std::mem::take(&mut termlock.resized);
+            DrawContext::new(width, height).resized(resized)
+        };
+        let mut canvas = TermCanvas { term: self };
+        draw.draw_with_context(&mut canvas, &ctx)
     }
 }
 
@@ -426,6 +1838,10 @@ impl<'a> Canvas for TermCanvas<'a> {
         self.term.print_with_attr(row, col, content, attr)
     }
 
+    fn print_batch_slice(&mut self, batch: &mut [PrintItem]) -> Result<()> {
+        self.term.print_batch(batch.iter().copied())
+    }
+
     fn set_cursor(&mut self, row: usize, col: usize) -> Result<()> {
         self.term.set_cursor(row, col)
     }
@@ -433,6 +1849,142 @@ impl<'a> Canvas for TermCanvas<'a> {
     fn show_cursor(&mut self, show: bool) -> Result<()> {
         self.term.show_cursor(show)
     }
+
+    fn register_region(&mut self, region: Region) -> Result<()> {
+        self.term.register_region(region);
+        Ok(())
+    }
+
+    fn mark_volatile(&mut self, row: usize) -> Result<()> {
+        self.term.mark_volatile(row)
+    }
+}
+
+struct RefreshZone {
+    id: usize,
+    area: (usize, usize, usize, usize),
+    interval: Duration,
+    last_presented: Option<Instant>,
+}
+
+/// A clipboard shared with the threads copying the mouse selection
+#[derive(Clone)]
+struct SharedClipboard {
+    clipboard: Arc<Mutex<Box<dyn Clipboard>>>,
+    /// `Clipboard::blocks`, kept not to wait for a copy in progress to ask
+    blocks: bool,
+}
+
+impl SharedClipboard {
+    fn new(clipboard: Box<dyn Clipboard>) -> Self {
+        Self {
+            blocks: clipboard.blocks(),
+            clipboard: Arc::new(Mutex::new(clipboard)),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Box<dyn Clipboard>> {
+        // a panic in a clipboard leaves it usable
+        self.clipboard
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A callback of the application, e.g. the `LinkOpener`, run outside of `term_lock`
+type SharedCallback = Arc<Mutex<Box<dyn Fn(&str) + Send>>>;
+
+/// Where the mouse selection is copied to, see `CopyTarget`
+enum SelectionTarget {
+    Osc52,
+    Clipboard,
+    Provider(SharedClipboard),
+    Callback(SharedCallback),
+}
+
+impl From<CopyTarget> for SelectionTarget {
+    fn from(target: CopyTarget) -> Self {
+        match target {
+            CopyTarget::Osc52 => SelectionTarget::Osc52,
+            CopyTarget::Clipboard => SelectionTarget::Clipboard,
+            CopyTarget::Provider(clipboard) => {
+                SelectionTarget::Provider(SharedClipboard::new(clipboard))
+            }
+            CopyTarget::Callback(callback) => {
+                SelectionTarget::Callback(Arc::new(Mutex::new(callback)))
+            }
+        }
+    }
+}
+
+/// What `TermLock::on_mouse_selection` leaves to `Term::run_mouse_action`
+enum MouseAction {
+    /// call back the application with the link or the selected text
+    Call(SharedCallback, String),
+    /// copy the selected text to the clipboard
+    Copy(SharedClipboard, String),
+}
+
+/// The modes of the terminal enabled by `Term`, disabled on pause and enabled again on restart
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct TermModes {
+    /// mouse reporting (1000), in the urxvt (1015) and SGR (1006) encodings
+    mouse: bool,
+    /// drag tracking (1002)
+    mouse_drag: bool,
+    /// motion tracking (1003)
+    mouse_motion: bool,
+    /// the kitty keyboard protocol, pushed and popped
+    kitty_keyboard: bool,
+    /// the focus events (1004)
+    focus_events: bool,
+    /// bracketed paste (2004)
+    bracketed_paste: bool,
+}
+
+impl TermModes {
+    fn enable(&self, output: &mut Output) {
+        if self.mouse {
+            output.enable_mouse_support();
+        }
+        if self.mouse_drag {
+            output.enable_mouse_drag_support();
+        }
+        if self.mouse_motion {
+            output.enable_mouse_motion_support();
+        }
+        if self.kitty_keyboard {
+            output.enable_kitty_keyboard();
+        }
+        if self.focus_events {
+            output.enable_focus_events();
+        }
+        if self.bracketed_paste {
+            output.enable_bracketed_paste();
+        }
+    }
+
+    /// in the reverse order
+    fn disable(&self, output: &mut Output) {
+        if self.bracketed_paste {
+            output.disable_bracketed_paste();
+        }
+        if self.focus_events {
+            output.disable_focus_events();
+        }
+        if self.kitty_keyboard {
+            output.disable_kitty_keyboard();
+        }
+        if self.mouse_motion {
+            output.disable_mouse_motion_support();
+        }
+        if self.mouse_drag {
+            output.disable_mouse_drag_support();
+        }
+        if self.mouse {
+            output.disable_mouse_support();
+        }
+    }
 }
 
 struct TermLock {
@@ -446,6 +1998,24 @@ struct TermLock {
     screen_width: usize,
     screen: Screen,
     output: Option<Output>,
+    accessibility: AccessibilityOptions,
+    attr_support: AttrSupport,
+    mouse_selection: Option<(MouseSelector, SelectionTarget)>,
+    link_opener: Option<SharedCallback>,
+    clipboard: SharedClipboard,
+    regions: RegionRegistry,
+    refresh_zones: Vec<RefreshZone>,
+    next_zone_id: usize,
+    blink_epoch: Instant,
+    /// a frame was not written while paused or disconnected
+    present_deferred: bool,
+    /// the mouse selection changed since the last frame, see `Term::needs_present`
+    selection_dirty: bool,
+    write_budget: Option<usize>,
+    /// enabled on restart, disabled on pause
+    modes: TermModes,
+    /// the screen was resized (or the term started) since the last `Term::draw`
+    resized: bool,
 }
 
 impl Default for TermLock {
@@ -461,6 +2031,20 @@ impl Default for TermLock {
             screen_width: 0,
             screen: Screen::new(0, 0),
             output: None,
+            accessibility: AccessibilityOptions::default(),
+            attr_support: AttrSupport::default(),
+            mouse_selection: None,
+            link_opener: None,
+            clipboard: SharedClipboard::new(Box::<MemoryClipboard>::default()),
+            regions: RegionRegistry::new(),
+            refresh_zones: Vec::new(),
+            next_zone_id: 0,
+            blink_epoch: Instant::now(),
+            present_deferred: false,
+            selection_dirty: false,
+            write_budget: None,
+            modes: TermModes::default(),
+            resized: false,
         }
     }
 }
@@ -471,29 +2055,182 @@ impl TermLock {
         term.prefer_height = options.height;
         term.max_height = options.max_height;
         term.min_height = options.min_height;
+        term.accessibility = options.accessibility;
+        term.write_budget = options.write_budget;
+        term.modes = TermModes {
+            mouse: options.mouse,
+            kitty_keyboard: options.kitty_keyboard,
+            focus_events: options.focus_events,
+            bracketed_paste: options.bracketed_paste,
+            ..TermModes::default()
+        };
+        term.screen.set_diff_strategy(options.diff_strategy);
+        term.screen.set_software_blink(options.software_blink);
         term
     }
 
+    fn blink_enabled(&self) -> bool {
+        self.screen.software_blink().is_some() && !self.accessibility.disable_blink
+    }
+
+    /// the time until the software blink toggles, `None` if nothing blinks
+    pub fn next_blink(&self) -> Option<Duration> {
+        if !self.blink_enabled() || !self.screen.has_blinking_cells() {
+            return None;
+        }
+        let elapsed = self.blink_epoch.elapsed().as_millis();
+        let interval = BLINK_INTERVAL.as_millis();
+        Some(Duration::from_millis(
+            (interval - elapsed % interval) as u64,
+        ))
+    }
+
     /// Present the content to the terminal
-    pub fn present(&mut self) -> Result<()> {
+    pub fn present(&mut self) -> Result<PresentStatus> {
+        let (width, height) = (self.screen.width(), self.screen.height());
+        self.present_area(0, 0, width, height)
+    }
+
+    /// Present the content of an area to the terminal
+    pub fn present_area(
+        &mut self,
+        top: usize,
+        left: usize,
+        width: usize,
+        height: usize,
+    ) -> Result<PresentStatus> {
+        if self
+            .output
+            .as_ref()
+            .map_or(true, |output| output.is_disconnected())
+        {
+            self.present_deferred = true;
+            return Ok(PresentStatus::Deferred);
+        }
+
+        // with blink disabled for accessibility the cells stay visible
+        let blink_off = self.blink_enabled()
+            && (self.blink_epoch.elapsed().as_millis() / BLINK_INTERVAL.as_millis()) % 2 == 1;
+        self.screen.set_blink_phase(blink_off);
+
         let output = self.output.as_mut().ok_or("term had been stopped")?;
-        let mut commands = self.screen.present();
+
+        // highlight the selection only while painting, the buffer is left intact
+        let selection = self
+            .mouse_selection
+            .as_ref()
+            .and_then(|(selector, _)| selector.selection());
+        let toggle_selection = |screen: &mut Screen| {
+            if let Some(selection) = selection {
+                let ((start_row, _), (end_row, _)) = selection.range();
+                for row in start_row..=min(end_row, screen.height().saturating_sub(1)) {
+                    for col in 0..screen.width() {
+                        if selection.contains(row, col) {
+                            screen.toggle_effect(row, col, Effect::REVERSE);
+                        }
+                    }
+                }
+            }
+        };
+        toggle_selection(&mut self.screen);
+        let mut commands = self.screen.present_area(top, left, width, height);
+        toggle_selection(&mut self.screen);
 
         let cursor_row = self.cursor_row;
         // add cursor_row to all CursorGoto commands
         for cmd in commands.iter_mut() {
-            if let Command::CursorGoto { row, col } = *cmd {
-                *cmd = Command::CursorGoto {
-                    row: row + cursor_row,
-                    col,
+            match *cmd {
+                Command::CursorGoto { row, col } => {
+                    *cmd = Command::CursorGoto {
+                        row: row + cursor_row,
+                        col,
+                    }
+                }
+                Command::SetAttribute(attr) => {
+                    let attr = self.accessibility.adjust_attr(attr);
+                    *cmd = Command::SetAttribute(self.attr_support.degrade(attr))
                 }
+                _ => {}
             }
         }
 
         for cmd in commands.into_iter() {
             output.execute(cmd);
         }
-        output.flush();
+        match self.write_budget {
+            Some(budget) => {
+                output.flush_chunk(budget);
+            }
+            None => output.flush(),
+        }
+        if output.is_disconnected() {
+            self.present_deferred = true;
+            return Ok(PresentStatus::Deferred);
+        }
+        self.selection_dirty = false;
+        Ok(PresentStatus::Presented)
+    }
+
+    /// Update the managed selection with the mouse event `key`, copy the text on release, open
+    /// the link under a `Ctrl` + click. The callbacks and the copies to a clipboard are left to
+    /// the caller, to be run once the lock is released.
+    fn on_mouse_selection(&mut self, key: &Key) -> Option<MouseAction> {
+        if let (Some(opener), &Key::MousePress(MouseButton::Left, x, y, modifiers)) =
+            (self.link_opener.as_ref(), key)
+        {
+            if modifiers.contains(Modifiers::CTRL) {
+                let (row, col) = mouse_position(x, y, self.cursor_row);
+                if let Some(link) = link_at(&self.screen, row, col) {
+                    return Some(MouseAction::Call(opener.clone(), link.payload));
+                }
+            }
+        }
+
+        let (selector, target) = self.mouse_selection.as_mut()?;
+
+        let action = match selector.on_key(key, self.cursor_row, &self.screen) {
+            SelectionUpdate::Unchanged => return None,
+            SelectionUpdate::Changed => None,
+            SelectionUpdate::Finished(selection) => {
+                let text = selection.text(&self.screen);
+                match target {
+                    SelectionTarget::Osc52 => {
+                        let _ = self.write_raw(&osc52(&text));
+                        None
+                    }
+                    SelectionTarget::Clipboard => {
+                        Some(MouseAction::Copy(self.clipboard.clone(), text))
+                    }
+                    SelectionTarget::Provider(clipboard) => {
+                        Some(MouseAction::Copy(clipboard.clone(), text))
+                    }
+                    SelectionTarget::Callback(callback) => {
+                        Some(MouseAction::Call(callback.clone(), text))
+                    }
+                }
+            }
+        };
+        // drawn by the next `Term::present`, with the hooks and the draw queue
+        self.selection_dirty = true;
+        action
+    }
+
+    /// Write the next chunk of a frame larger than the write budget, return whether some is
+    /// left
+    fn flush_chunk(&mut self) -> bool {
+        match (self.write_budget, self.output.as_mut()) {
+            (Some(budget), Some(output)) if output.pending() > 0 => output.flush_chunk(budget),
+            _ => false,
+        }
+    }
+
+    /// Write `bytes` to the terminal right away, e.g. the sequences of a clipboard
+    fn write_raw(&mut self, bytes: &[u8]) -> Result<()> {
+        if !bytes.is_empty() {
+            let output = self.output.as_mut().ok_or("term had been stopped")?;
+            output.write_raw(bytes);
+            output.flush();
+        }
         Ok(())
     }
 
@@ -524,11 +2261,12 @@ impl TermLock {
         }
 
         // clear the screen
-        let _ = output.cursor_goto(self.cursor_row, 0);
-        let _ = output.erase_down();
+        output.cursor_goto(self.cursor_row, 0);
+        output.erase_down();
 
         // clear the screen buffer
         self.screen.resize(width, height);
+        self.resized = true;
         Ok(())
     }
 
@@ -557,7 +2295,9 @@ impl TermLock {
 
     /// Pause the terminal
     pub fn pause(&mut self) -> Result<()> {
-        self.output.take().map(|mut output| {
+        if let Some(mut output) = self.output.take() {
+            // kept, to be enabled again on restart
+            self.modes.disable(&mut output);
             // clear drawed contents
             if self.alternate_screen {
                 output.quit_alternate_screen();
@@ -567,7 +2307,7 @@ impl TermLock {
                 output.erase_down();
             }
             output.flush();
-        });
+        }
         Ok(())
     }
 
@@ -621,6 +2361,9 @@ impl TermLock {
         self.output.replace(output);
         self.ensure_height(cursor_pos)?;
         self.on_resize()?;
+        if std::mem::take(&mut self.present_deferred) {
+            self.present()?;
+        }
         Ok(())
     }
 
@@ -631,6 +2374,7 @@ impl TermLock {
 
     /// clear internal buffer
     pub fn clear(&mut self) -> Result<()> {
+        self.regions.clear();
         self.screen.clear()
     }
 
@@ -664,6 +2408,7 @@ impl TermLock {
     pub fn enable_mouse_support(&mut self) -> Result<()> {
         let output = self.output.as_mut().ok_or("term had been stopped")?;
         output.enable_mouse_support();
+        self.modes.mouse = true;
         Ok(())
     }
 
@@ -671,8 +2416,83 @@ impl TermLock {
     pub fn disable_mouse_support(&mut self) -> Result<()> {
         let output = self.output.as_mut().ok_or("term had been stopped")?;
         output.disable_mouse_support();
+        self.modes.mouse = false;
+        Ok(())
+    }
+
+    /// Enable mouse drag tracking
+    pub fn enable_mouse_drag_support(&mut self) -> Result<()> {
+        let output = self.output.as_mut().ok_or("term had been stopped")?;
+        output.enable_mouse_drag_support();
+        self.modes.mouse_drag = true;
+        Ok(())
+    }
+
+    /// Disable mouse drag tracking
+    pub fn disable_mouse_drag_support(&mut self) -> Result<()> {
+        let output = self.output.as_mut().ok_or("term had been stopped")?;
+        output.disable_mouse_drag_support();
+        self.modes.mouse_drag = false;
+        Ok(())
+    }
+
+    /// Enable mouse motion tracking
+    pub fn enable_mouse_motion_support(&mut self) -> Result<()> {
+        let output = self.output.as_mut().ok_or("term had been stopped")?;
+        output.enable_mouse_motion_support();
+        self.modes.mouse_motion = true;
+        Ok(())
+    }
+
+    /// Disable mouse motion tracking
+    pub fn disable_mouse_motion_support(&mut self) -> Result<()> {
+        let output = self.output.as_mut().ok_or("term had been stopped")?;
+        output.disable_mouse_motion_support();
+        self.modes.mouse_motion = false;
+        Ok(())
+    }
+}
+
+/// For the plugin layers and the frame hooks, replayed with the term locked
+impl Canvas for TermLock {
+    fn size(&self) -> Result<(usize, usize)> {
+        self.term_size()
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        TermLock::clear(self)
+    }
+
+    fn put_cell(&mut self, row: usize, col: usize, cell: Cell) -> Result<usize> {
+        TermLock::put_cell(self, row, col, cell)
+    }
+
+    fn print_with_attr(
+        &mut self,
+        row: usize,
+        col: usize,
+        content: &str,
+        attr: Attr,
+    ) -> Result<usize> {
+        TermLock::print_with_attr(self, row, col, content, attr)
+    }
+
+    fn set_cursor(&mut self, row: usize, col: usize) -> Result<()> {
+        TermLock::set_cursor(self, row, col)
+    }
+
+    fn show_cursor(&mut self, show: bool) -> Result<()> {
+        TermLock::show_cursor(self, show)
+    }
+
+    fn register_region(&mut self, region: Region) -> Result<()> {
+        self.regions.register(region);
         Ok(())
     }
+
+    fn mark_volatile(&mut self, row: usize) -> Result<()> {
+        self.screen.mark_volatile(row)
+    }
 }
 
 impl Drop for TermLock {
@@ -680,3 +2500,198 @@ impl Drop for TermLock {
         let _ = self.pause();
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event::MouseEvent;
+    use crate::frame_hook::FrameInfo;
+    use crate::testing::FakeTerminal;
+
+    const TIMEOUT: Duration = Duration::from_secs(2);
+
+    fn fake_term(fake: &FakeTerminal) -> Term {
+        fake_term_with(fake, TermOptions::default())
+    }
+
+    fn fake_term_with(fake: &FakeTerminal, options: TermOptions) -> Term {
+        let options = options.height(TermHeight::Fixed(4)).tty_path(fake.path());
+        Term::with_options(options).unwrap()
+    }
+
+    /// wait up to `TIMEOUT` for the term to write `bytes` past the first `from` bytes
+    fn wait_for_output_from(fake: &FakeTerminal, from: usize, bytes: &[u8]) -> bool {
+        let deadline = Instant::now() + TIMEOUT;
+        while Instant::now() < deadline {
+            let output = fake.output();
+            if output[from.min(output.len())..]
+                .windows(bytes.len())
+                .any(|window| window == bytes)
+            {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        false
+    }
+
+    /// wait for a mouse release, the other events are skipped
+    fn wait_for_release(term: &Term) {
+        while !matches!(
+            term.peek_event(TIMEOUT).unwrap(),
+            Event::Mouse(MouseEvent::Release(..))
+        ) {}
+    }
+
+    #[test]
+    fn user_config_is_opted_into() {
+        let config = Config::parse("mouse = yes\ncolor_depth = 256\nesc_timeout = 50");
+        assert!(!TermOptions::default().mouse);
+        assert_eq!(Config::default(), TermOptions::default().config);
+
+        let options = TermOptions::default().config(config.clone());
+        assert!(options.mouse);
+        assert_eq!(Some(ColorDepth::Ansi256), options.color_depth);
+        assert_eq!(Some(Duration::from_millis(50)), options.esc_timeout);
+        assert_eq!(config, options.config);
+
+        // the options set afterwards win
+        let options = TermOptions::default().config(config).mouse(false);
+        assert!(!options.mouse);
+    }
+
+    #[test]
+    fn modes_across_pause_and_restart() {
+        let fake = FakeTerminal::new(20, 4).unwrap();
+        let term = fake_term_with(&fake, TermOptions::default().bracketed_paste(true));
+        assert!(fake.wait_for_output(b"\x1b[?2004h", TIMEOUT));
+        term.enable_mouse_support().unwrap();
+        term.enable_mouse_motion_support().unwrap();
+
+        term.pause().unwrap();
+        let disabled = b"\x1b[?2004l\x1b[?1003l\x1b[?1000l\x1b[?1015l\x1b[?1006l";
+        assert!(fake.wait_for_output(disabled, TIMEOUT));
+        let paused = fake.output().len();
+
+        term.restart().unwrap();
+        let enabled = b"\x1b[?1000h\x1b[?1015h\x1b[?1006h\x1b[?1003h\x1b[?2004h";
+        assert!(wait_for_output_from(&fake, paused, enabled));
+        let restarted = fake.output().len();
+
+        // the modes disabled meanwhile stay so
+        term.disable_mouse_motion_support().unwrap();
+        drop(term);
+        assert!(wait_for_output_from(
+            &fake,
+            restarted,
+            b"\x1b[?1000l\x1b[?1015l\x1b[?1006l"
+        ));
+        let output = fake.output();
+        assert!(!output[restarted..]
+            .windows(8)
+            .any(|window| window == b"\x1b[?1003h"));
+    }
+
+    #[test]
+    fn present_deferred_while_paused() {
+        let fake = FakeTerminal::new(20, 4).unwrap();
+        let term = fake_term(&fake);
+        term.pause().unwrap();
+
+        term.draw_handle().print(0, 0, "deferred", Attr::default());
+        assert_eq!(PresentStatus::Deferred, term.present().unwrap());
+        thread::sleep(Duration::from_millis(50));
+        let paused = fake.output();
+        assert!(!paused.windows(8).any(|window| window == b"deferred"));
+
+        term.restart().unwrap();
+        assert!(wait_for_output_from(&fake, paused.len(), b"deferred"));
+    }
+
+    #[test]
+    fn selection_callback_calls_back_into_term() {
+        let fake = FakeTerminal::new(20, 4).unwrap();
+        let term = Arc::new(fake_term(&fake));
+        term.print(0, 0, "hello").unwrap();
+        term.present().unwrap();
+
+        let (copied_tx, copied_rx) = channel();
+        let callback = {
+            let term = term.clone();
+            move |text: &str| {
+                // `term_lock` is released, this would deadlock otherwise
+                let _ = copied_tx.send((text.to_string(), term.clipboard_name().is_empty()));
+            }
+        };
+        term.enable_mouse_selection(CopyTarget::Callback(Box::new(callback)))
+            .unwrap();
+
+        fake.type_bytes(b"\x1b[<0;1;1M\x1b[<32;3;1M\x1b[<0;3;1m")
+            .unwrap();
+        wait_for_release(&term);
+        let copied = copied_rx.recv_timeout(TIMEOUT).unwrap();
+        assert_eq!(("hel".to_string(), false), copied);
+
+        // drops the callback, and the term it holds
+        term.disable_mouse_selection();
+    }
+
+    struct CallingBack {
+        term: Arc<Term>,
+        presented: Sender<String>,
+    }
+
+    impl FrameHook for CallingBack {
+        fn pre_present(&mut self, _frame: &FrameInfo, canvas: &mut dyn Canvas) -> Result<()> {
+            let (width, _) = self.term.term_size()?;
+            canvas.print(0, width - 1, "W")?;
+            Ok(())
+        }
+
+        fn post_present(&mut self, _frame: &FrameInfo, screen: &Screen) {
+            let _ = self.term.needs_present();
+            let text = screen.iter_cell().map(|(_, _, cell)| cell.ch).collect();
+            let _ = self.presented.send(text);
+        }
+    }
+
+    #[test]
+    fn frame_hook_calls_back_into_term() {
+        let fake = FakeTerminal::new(20, 4).unwrap();
+        let term = Arc::new(fake_term(&fake));
+        let (presented_tx, presented_rx) = channel();
+        let id = term.add_frame_hook(Box::new(CallingBack {
+            term: term.clone(),
+            presented: presented_tx,
+        }));
+
+        // the hooks deadlock if called with the term locked
+        let presenting = term.clone();
+        thread::spawn(move || {
+            presenting.print(0, 0, "hello").unwrap();
+            presenting.present().unwrap();
+        });
+        let text = presented_rx.recv_timeout(TIMEOUT).unwrap();
+        assert!(text.starts_with("hello"));
+        assert_eq!(Some('W'), text.chars().nth(19));
+        assert!(wait_for_output_from(&fake, 0, b"W"));
+
+        // drops the hook, and the term it holds
+        assert!(term.remove_frame_hook(id));
+    }
+
+    #[test]
+    fn selection_is_drawn_by_present() {
+        let fake = FakeTerminal::new(20, 4).unwrap();
+        let term = fake_term(&fake);
+        term.enable_mouse_selection(CopyTarget::Osc52).unwrap();
+        assert!(!term.needs_present());
+
+        fake.type_bytes(b"\x1b[<0;1;1M\x1b[<32;3;1M\x1b[<0;3;1m")
+            .unwrap();
+        wait_for_release(&term);
+        assert!(term.needs_present());
+        assert_eq!(PresentStatus::Presented, term.present().unwrap());
+        assert!(!term.needs_present());
+    }
+}
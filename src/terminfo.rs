@@ -0,0 +1,249 @@
+//! Built-in capability tables of common terminals, enabled by the `static-terminfo` feature.
+//!
+//! With the tables `Output` could start without reading the terminfo database, which might be
+//! missing or incomplete on the systems statically linked binaries are shipped to. Only the
+//! capabilities used by `Output` are included.
+//!
+//! The tables are generated from the terminfo database of ncurses 6.5 (`xterm-kitty` from
+//! kitty's own terminfo).
+
+use std::collections::HashMap;
+use term::terminfo::TermInfo;
+
+type Capabilities = &'static [(&'static str, &'static [u8])];
+
+#[rustfmt::skip]
+const XTERM_256COLOR: Capabilities = &[
+    ("acsc",  b"``aaffggiijjkkllmmnnooppqqrrssttuuvvwwxxyyzz{{||}}~~"),
+    ("bel",   b"\x07"),
+    ("blink", b"\x1b[5m"),
+    ("bold",  b"\x1b[1m"),
+    ("civis", b"\x1b[?25l"),
+    ("clear", b"\x1b[H\x1b[2J"),
+    ("cnorm", b"\x1b[?12l\x1b[?25h"),
+    ("cub",   b"\x1b[%p1%dD"),
+    ("cub1",  b"\x08"),
+    ("cud",   b"\x1b[%p1%dB"),
+    ("cud1",  b"\n"),
+    ("cuf",   b"\x1b[%p1%dC"),
+    ("cuf1",  b"\x1b[C"),
+    ("cup",   b"\x1b[%i%p1%d;%p2%dH"),
+    ("cuu",   b"\x1b[%p1%dA"),
+    ("cuu1",  b"\x1b[A"),
+    ("dim",   b"\x1b[2m"),
+    ("ed",    b"\x1b[J"),
+    ("el",    b"\x1b[K"),
+    ("rev",   b"\x1b[7m"),
+    ("rmacs", b"\x1b(B"),
+    ("rmam",  b"\x1b[?7l"),
+    ("rmcup", b"\x1b[?1049l\x1b[23;0;0t"),
+    ("setab", b"\x1b[%?%p1%{8}%<%t4%p1%d%e%p1%{16}%<%t10%p1%{8}%-%d%e48;5;%p1%d%;m"),
+    ("setaf", b"\x1b[%?%p1%{8}%<%t3%p1%d%e%p1%{16}%<%t9%p1%{8}%-%d%e38;5;%p1%d%;m"),
+    ("sgr0",  b"\x1b(B\x1b[m"),
+    ("smacs", b"\x1b(0"),
+    ("smam",  b"\x1b[?7h"),
+    ("smcup", b"\x1b[?1049h\x1b[22;0;0t"),
+    ("smul",  b"\x1b[4m"),
+];
+
+#[rustfmt::skip]
+const SCREEN: Capabilities = &[
+    ("acsc",  b"++,,--..00``aaffgghhiijjkkllmmnnooppqqrrssttuuvvwwxxyyzz{{||}}~~"),
+    ("bel",   b"\x07"),
+    ("blink", b"\x1b[5m"),
+    ("bold",  b"\x1b[1m"),
+    ("civis", b"\x1b[?25l"),
+    ("clear", b"\x1b[H\x1b[J"),
+    ("cnorm", b"\x1b[34h\x1b[?25h"),
+    ("cub",   b"\x1b[%p1%dD"),
+    ("cub1",  b"\x08"),
+    ("cud",   b"\x1b[%p1%dB"),
+    ("cud1",  b"\n"),
+    ("cuf",   b"\x1b[%p1%dC"),
+    ("cuf1",  b"\x1b[C"),
+    ("cup",   b"\x1b[%i%p1%d;%p2%dH"),
+    ("cuu",   b"\x1b[%p1%dA"),
+    ("cuu1",  b"\x1bM"),
+    ("dim",   b"\x1b[2m"),
+    ("ed",    b"\x1b[J"),
+    ("el",    b"\x1b[K"),
+    ("rev",   b"\x1b[7m"),
+    ("rmacs", b"\x0f"),
+    ("rmcup", b"\x1b[?1049l"),
+    ("setab", b"\x1b[4%p1%dm"),
+    ("setaf", b"\x1b[3%p1%dm"),
+    ("sgr0",  b"\x1b[m\x0f"),
+    ("smacs", b"\x0e"),
+    ("smcup", b"\x1b[?1049h"),
+    ("smul",  b"\x1b[4m"),
+];
+
+#[rustfmt::skip]
+const TMUX_256COLOR: Capabilities = &[
+    ("acsc",  b"++,,--..00``aaffgghhiijjkkllmmnnooppqqrrssttuuvvwwxxyyzz{{||}}~~"),
+    ("bel",   b"\x07"),
+    ("blink", b"\x1b[5m"),
+    ("bold",  b"\x1b[1m"),
+    ("civis", b"\x1b[?25l"),
+    ("clear", b"\x1b[H\x1b[J"),
+    ("cnorm", b"\x1b[34h\x1b[?25h"),
+    ("cub",   b"\x1b[%p1%dD"),
+    ("cub1",  b"\x08"),
+    ("cud",   b"\x1b[%p1%dB"),
+    ("cud1",  b"\n"),
+    ("cuf",   b"\x1b[%p1%dC"),
+    ("cuf1",  b"\x1b[C"),
+    ("cup",   b"\x1b[%i%p1%d;%p2%dH"),
+    ("cuu",   b"\x1b[%p1%dA"),
+    ("cuu1",  b"\x1bM"),
+    ("dim",   b"\x1b[2m"),
+    ("ed",    b"\x1b[J"),
+    ("el",    b"\x1b[K"),
+    ("rev",   b"\x1b[7m"),
+    ("rmacs", b"\x0f"),
+    ("rmcup", b"\x1b[?1049l"),
+    ("setab", b"\x1b[%?%p1%{8}%<%t4%p1%d%e%p1%{16}%<%t10%p1%{8}%-%d%e48;5;%p1%d%;m"),
+    ("setaf", b"\x1b[%?%p1%{8}%<%t3%p1%d%e%p1%{16}%<%t9%p1%{8}%-%d%e38;5;%p1%d%;m"),
+    ("sgr0",  b"\x1b[m\x0f"),
+    ("smacs", b"\x0e"),
+    ("smcup", b"\x1b[?1049h"),
+    ("smul",  b"\x1b[4m"),
+];
+
+#[rustfmt::skip]
+const ALACRITTY: Capabilities = &[
+    ("acsc",  b"``aaffggiijjkkllmmnnooppqqrrssttuuvvwwxxyyzz{{||}}~~"),
+    ("bel",   b"\x07"),
+    ("blink", b"\x1b[5m"),
+    ("bold",  b"\x1b[1m"),
+    ("civis", b"\x1b[?25l"),
+    ("clear", b"\x1b[H\x1b[2J"),
+    ("cnorm", b"\x1b[?12l\x1b[?25h"),
+    ("cub",   b"\x1b[%p1%dD"),
+    ("cub1",  b"\x08"),
+    ("cud",   b"\x1b[%p1%dB"),
+    ("cud1",  b"\n"),
+    ("cuf",   b"\x1b[%p1%dC"),
+    ("cuf1",  b"\x1b[C"),
+    ("cup",   b"\x1b[%i%p1%d;%p2%dH"),
+    ("cuu",   b"\x1b[%p1%dA"),
+    ("cuu1",  b"\x1b[A"),
+    ("dim",   b"\x1b[2m"),
+    ("ed",    b"\x1b[J"),
+    ("el",    b"\x1b[K"),
+    ("rev",   b"\x1b[7m"),
+    ("rmacs", b"\x1b(B"),
+    ("rmam",  b"\x1b[?7l"),
+    ("rmcup", b"\x1b[?1049l\x1b[23;0;0t"),
+    ("setab", b"\x1b[%?%p1%{8}%<%t4%p1%d%e%p1%{16}%<%t10%p1%{8}%-%d%e48;5;%p1%d%;m"),
+    ("setaf", b"\x1b[%?%p1%{8}%<%t3%p1%d%e%p1%{16}%<%t9%p1%{8}%-%d%e38;5;%p1%d%;m"),
+    ("sgr0",  b"\x1b(B\x1b[m"),
+    ("smacs", b"\x1b(0"),
+    ("smam",  b"\x1b[?7h"),
+    ("smcup", b"\x1b[?1049h\x1b[22;0;0t"),
+    ("smul",  b"\x1b[4m"),
+];
+
+#[rustfmt::skip]
+const LINUX: Capabilities = &[
+    ("acsc",  b"++,,--..00__``aaffgghhiijjkkllmmnnooppqqrrssttuuvvwwxxyyzz{{||}}~~"),
+    ("bel",   b"\x07"),
+    ("blink", b"\x1b[5m"),
+    ("bold",  b"\x1b[1m"),
+    ("civis", b"\x1b[?25l\x1b[?1c"),
+    ("clear", b"\x1b[H\x1b[J"),
+    ("cnorm", b"\x1b[?25h\x1b[?0c"),
+    ("cub",   b"\x1b[%p1%dD"),
+    ("cub1",  b"\x08"),
+    ("cud",   b"\x1b[%p1%dB"),
+    ("cud1",  b"\n"),
+    ("cuf",   b"\x1b[%p1%dC"),
+    ("cuf1",  b"\x1b[C"),
+    ("cup",   b"\x1b[%i%p1%d;%p2%dH"),
+    ("cuu",   b"\x1b[%p1%dA"),
+    ("cuu1",  b"\x1b[A"),
+    ("dim",   b"\x1b[2m"),
+    ("ed",    b"\x1b[J"),
+    ("el",    b"\x1b[K"),
+    ("rev",   b"\x1b[7m"),
+    ("rmacs", b"\x0f"),
+    ("rmam",  b"\x1b[?7l"),
+    ("setab", b"\x1b[4%p1%dm"),
+    ("setaf", b"\x1b[3%p1%dm"),
+    ("sgr0",  b"\x1b[m\x0f"),
+    ("smacs", b"\x0e"),
+    ("smam",  b"\x1b[?7h"),
+    ("smul",  b"\x1b[4m"),
+];
+
+#[rustfmt::skip]
+const XTERM_KITTY: Capabilities = &[
+    ("acsc",  b"++,,--..00``aaffgghhiijjkkllmmnnooppqqrrssttuuvvwwxxyyzz{{||}}~~"),
+    ("bel",   b"\x07"),
+    ("blink", b"\x1b[5m"),
+    ("bold",  b"\x1b[1m"),
+    ("civis", b"\x1b[?25l"),
+    ("clear", b"\x1b[H\x1b[2J"),
+    ("cnorm", b"\x1b[?12h\x1b[?25h"),
+    ("cub",   b"\x1b[%p1%dD"),
+    ("cub1",  b"\x08"),
+    ("cud",   b"\x1b[%p1%dB"),
+    ("cud1",  b"\n"),
+    ("cuf",   b"\x1b[%p1%dC"),
+    ("cuf1",  b"\x1b[C"),
+    ("cup",   b"\x1b[%i%p1%d;%p2%dH"),
+    ("cuu",   b"\x1b[%p1%dA"),
+    ("cuu1",  b"\x1b[A"),
+    ("dim",   b"\x1b[2m"),
+    ("ed",    b"\x1b[J"),
+    ("el",    b"\x1b[K"),
+    ("rev",   b"\x1b[7m"),
+    ("rmacs", b"\x1b(B"),
+    ("rmam",  b"\x1b[?7l"),
+    ("rmcup", b"\x1b[?1049l"),
+    ("setab", b"\x1b[%?%p1%{8}%<%t4%p1%d%e%p1%{16}%<%t10%p1%{8}%-%d%e48;5;%p1%d%;m"),
+    ("setaf", b"\x1b[%?%p1%{8}%<%t3%p1%d%e%p1%{16}%<%t9%p1%{8}%-%d%e38;5;%p1%d%;m"),
+    ("sgr0",  b"\x1b(B\x1b[m"),
+    ("smacs", b"\x1b(0"),
+    ("smam",  b"\x1b[?7h"),
+    ("smcup", b"\x1b[?1049h"),
+    ("smul",  b"\x1b[4m"),
+];
+
+/// Build the `TermInfo` of terminal `name` (i.e. the value of `$TERM`) from the built-in
+/// tables, return `None` if the terminal is unknown.
+pub fn builtin_terminfo(name: &str) -> Option<TermInfo> {
+    let strings = match name {
+        "xterm-256color" => XTERM_256COLOR,
+        "screen" => SCREEN,
+        "tmux-256color" => TMUX_256COLOR,
+        "alacritty" => ALACRITTY,
+        "linux" => LINUX,
+        "xterm-kitty" | "kitty" => XTERM_KITTY,
+        _ => return None,
+    };
+
+    Some(TermInfo {
+        names: vec![name.to_string()],
+        bools: HashMap::new(),
+        numbers: HashMap::new(),
+        strings: strings
+            .iter()
+            .map(|(cap, value)| (*cap, value.to_vec()))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn builtin_terminfo_should_contain_used_capabilities() {
+        let info = builtin_terminfo("xterm-256color").unwrap();
+        assert_eq!(Some(&b"\x1b[K".to_vec()), info.strings.get("el"));
+        assert!(info.strings.contains_key("cup"));
+        assert!(builtin_terminfo("linux").is_some());
+        assert!(builtin_terminfo("vt52").is_none());
+    }
+}
@@ -0,0 +1,601 @@
+//! End-to-end testing of applications: type keys, then check the screen.
+//!
+//! A `Script` is a list of steps run against a `Term` while the application's event loop runs
+//! in another thread. Keys are injected in the event queue (see `Term::inject_keys`) and the
+//! expectations wait for the screen buffer to match, up to a timeout, so the tests don't
+//! depend on how fast the application reacts.
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use std::thread;
+//! use tuikit::prelude::*;
+//! use tuikit::testing::Script;
+//!
+//! let term = Arc::new(Term::new().unwrap());
+//! let app = {
+//!     let term = term.clone();
+//!     thread::spawn(move || {
+//!         let mut query = String::new();
+//!         while let Ok(Event::Key(KeyEvent { key, .. })) = term.poll_event() {
+//!             match key {
+//!                 Key::Char(ch) => query.push(ch),
+//!                 Key::ESC => break,
+//!                 _ => {}
+//!             }
+//!             let _ = term.clear();
+//!             let _ = term.print(0, 0, &format!("> {}", query));
+//!             let _ = term.present();
+//!         }
+//!     })
+//! };
+//!
+//! Script::new()
+//!     .type_text("foo")
+//!     .expect_line(0, "> foo")
+//!     .key(Key::ESC)
+//!     .run(&*term)
+//!     .unwrap();
+//! app.join().unwrap();
+//! ```
+//!
+//! See `examples/e2e_test.rs` for a complete example.
+//!
+//! ## Without a terminal
+//!
+//! A `FakeTerminal` is a pseudo terminal answering the queries the library sends (the cursor
+//! position, the device attributes, the background color...) as a terminal would, for the
+//! features depending on them (inline mode, theme detection) to run headless, e.g. in CI:
+//!
+//! ```no_run
+//! use tuikit::prelude::*;
+//! use tuikit::term::TermOptions;
+//! use tuikit::testing::FakeTerminal;
+//!
+//! let fake = FakeTerminal::new(80, 24).unwrap();
+//! fake.set_cursor_pos(20, 0);
+//! fake.set_background_color(0, 0, 0);
+//! let options = TermOptions::default()
+//!     .height(TermHeight::Fixed(10))
+//!     .tty_path(fake.path())
+//!     .probe_capabilities(true);
+//! let term = Term::with_options(options).unwrap();
+//! ```
+
+use crate::canvas;
+use crate::key::Key;
+use crate::snapshot::ScreenSnapshot;
+use crate::term::Term;
+use nix::pty::{openpty, Winsize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// What a `Script` drives, implemented by `Term`
+pub trait ScriptTarget {
+    fn inject_keys(&self, keys: &[Key]) -> canvas::Result<()>;
+
+    /// the current contents of the screen
+    fn snapshot(&self) -> ScreenSnapshot;
+}
+
+impl ScriptTarget for Term {
+    fn inject_keys(&self, keys: &[Key]) -> canvas::Result<()> {
+        Term::inject_keys(self, keys)
+    }
+
+    fn snapshot(&self) -> ScreenSnapshot {
+        Term::snapshot(self)
+    }
+}
+
+type Check = Box<dyn Fn(&ScreenSnapshot) -> Result<(), String>>;
+
+enum Step {
+    Keys(Vec<Key>),
+    Wait(Duration),
+    /// retried until it passes or the timeout expires
+    Expect(Check),
+}
+
+/// A failed step, with the screen at the time of the failure
+#[derive(Debug)]
+pub struct ScriptError {
+    /// the index of the step
+    pub step: usize,
+    pub message: String,
+    pub screen: String,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "step {}: {}", self.step, self.message)?;
+        writeln!(f, "screen:")?;
+        for line in self.screen.lines() {
+            writeln!(f, "|{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ScriptError {}
+
+/// The steps of an end-to-end test, see the module doc
+pub struct Script {
+    steps: Vec<Step>,
+    timeout: Duration,
+    key_delay: Duration,
+}
+
+impl Default for Script {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Builder
+impl Script {
+    pub fn new() -> Self {
+        Self {
+            steps: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+            key_delay: Duration::from_millis(0),
+        }
+    }
+
+    /// how long the expectations wait for the screen to match
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// the pause after every injected key, e.g. to test debouncing
+    pub fn key_delay(mut self, delay: Duration) -> Self {
+        self.key_delay = delay;
+        self
+    }
+
+    pub fn key(self, key: Key) -> Self {
+        self.keys(&[key])
+    }
+
+    pub fn keys(mut self, keys: &[Key]) -> Self {
+        self.steps.push(Step::Keys(keys.to_vec()));
+        self
+    }
+
+    /// type `text`, `\n` is sent as `Enter` and `\t` as `Tab`
+    pub fn type_text(self, text: &str) -> Self {
+        let keys: Vec<Key> = text
+            .chars()
+            .map(|ch| match ch {
+                '\n' => Key::Enter,
+                '\t' => Key::Tab,
+                ch => Key::Char(ch),
+            })
+            .collect();
+        self.keys(&keys)
+    }
+
+    pub fn wait(mut self, duration: Duration) -> Self {
+        self.steps.push(Step::Wait(duration));
+        self
+    }
+
+    /// expect `text` somewhere on the screen
+    pub fn expect_text(self, text: &str) -> Self {
+        let text = text.to_string();
+        self.expect(
+            &format!("expected {:?} on the screen", text),
+            move |snapshot| snapshot.text().contains(&text),
+        )
+    }
+
+    /// expect `text` not to be on the screen
+    pub fn expect_no_text(self, text: &str) -> Self {
+        let text = text.to_string();
+        self.expect(
+            &format!("expected {:?} not on the screen", text),
+            move |snapshot| !snapshot.text().contains(&text),
+        )
+    }
+
+    /// expect the line `row`, without the trailing spaces, to be `text`
+    pub fn expect_line(self, row: usize, text: &str) -> Self {
+        let text = text.to_string();
+        self.expect(
+            &format!("expected line {} to be {:?}", row, text),
+            move |snapshot| snapshot.text().lines().nth(row).unwrap_or("") == text,
+        )
+    }
+
+    /// expect the cursor to be visible at `(row, col)`
+    pub fn expect_cursor(self, row: usize, col: usize) -> Self {
+        self.expect(
+            &format!("expected the cursor at ({}, {})", row, col),
+            move |snapshot| snapshot.cursor == Some((row, col)),
+        )
+    }
+
+    /// a custom expectation, `description` is reported if it fails
+    pub fn expect(
+        mut self,
+        description: &str,
+        check: impl Fn(&ScreenSnapshot) -> bool + 'static,
+    ) -> Self {
+        let description = description.to_string();
+        self.steps.push(Step::Expect(Box::new(move |snapshot| {
+            if check(snapshot) {
+                Ok(())
+            } else {
+                Err(description.clone())
+            }
+        })));
+        self
+    }
+}
+
+impl Script {
+    /// Run the steps in order, stop at the first failure
+    pub fn run(&self, target: &dyn ScriptTarget) -> Result<(), ScriptError> {
+        for (index, step) in self.steps.iter().enumerate() {
+            match step {
+                Step::Keys(keys) => {
+                    for key in keys.iter() {
+                        target
+                            .inject_keys(std::slice::from_ref(key))
+                            .map_err(|err| ScriptError {
+                                step: index,
+                                message: format!("failed to inject {:?}: {}", key, err),
+                                screen: target.snapshot().text(),
+                            })?;
+                        if self.key_delay > Duration::from_millis(0) {
+                            thread::sleep(self.key_delay);
+                        }
+                    }
+                }
+                Step::Wait(duration) => thread::sleep(*duration),
+                Step::Expect(check) => {
+                    let deadline = Instant::now() + self.timeout;
+                    loop {
+                        let snapshot = target.snapshot();
+                        match check(&snapshot) {
+                            Ok(()) => break,
+                            Err(message) if Instant::now() >= deadline => {
+                                return Err(ScriptError {
+                                    step: index,
+                                    message,
+                                    screen: snapshot.text(),
+                                });
+                            }
+                            Err(_) => thread::sleep(POLL_INTERVAL),
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The replies of a `FakeTerminal`, `None` for the queries left unanswered
+#[derive(Default)]
+struct Replies {
+    /// (row, col), from 0
+    cursor_pos: (usize, usize),
+    device_attributes: Option<Vec<u16>>,
+    background: Option<(u8, u8, u8)>,
+    termcap: Option<BTreeMap<String, String>>,
+    keyboard_flags: Option<u8>,
+}
+
+impl Replies {
+    /// the query starting `output`: its length and the reply if any, `None` while it is
+    /// incomplete. A byte starting no query has no reply.
+    fn scan(&self, output: &[u8]) -> Option<(usize, Option<Vec<u8>>)> {
+        const CPR: &[u8] = b"\x1b[6n";
+        const DA1: &[u8] = b"\x1b[c";
+        const KEYBOARD_FLAGS: &[u8] = b"\x1b[?u";
+        const BACKGROUND: &[u8] = b"\x1b]11;?";
+        const TERMCAP: &[u8] = b"\x1bP+q";
+
+        for query in &[CPR, DA1, KEYBOARD_FLAGS, BACKGROUND, TERMCAP] {
+            if output.len() < query.len() && query.starts_with(output) {
+                return None;
+            }
+        }
+
+        if output.starts_with(CPR) {
+            let (row, col) = self.cursor_pos;
+            let reply = format!("\x1b[{};{}R", row + 1, col + 1);
+            Some((CPR.len(), Some(reply.into_bytes())))
+        } else if output.starts_with(DA1) {
+            let reply = self.device_attributes.as_ref().map(|attributes| {
+                let attributes: Vec<String> = attributes.iter().map(u16::to_string).collect();
+                format!("\x1b[?{}c", attributes.join(";")).into_bytes()
+            });
+            Some((DA1.len(), reply))
+        } else if output.starts_with(KEYBOARD_FLAGS) {
+            let reply = self
+                .keyboard_flags
+                .map(|flags| format!("\x1b[?{}u", flags).into_bytes());
+            Some((KEYBOARD_FLAGS.len(), reply))
+        } else if output.starts_with(BACKGROUND) {
+            let end = output.iter().position(|&byte| byte == b'\x07')?;
+            let reply = self.background.map(|(r, g, b)| {
+                let rgb = format!("rgb:{:02x}{0:02x}/{:02x}{1:02x}/{:02x}{2:02x}", r, g, b);
+                format!("\x1b]11;{}\x1b\\", rgb).into_bytes()
+            });
+            Some((end + 1, reply))
+        } else if output.starts_with(TERMCAP) {
+            let end = output.windows(2).position(|bytes| bytes == b"\x1b\\")?;
+            let names = String::from_utf8_lossy(&output[TERMCAP.len()..end]);
+            let reply = self.termcap.as_ref().map(|termcap| {
+                let mut reply = String::new();
+                for name in names.split(';') {
+                    match termcap.get(&from_hex(name)) {
+                        Some(value) => {
+                            reply += &format!("\x1bP1+r{}={}\x1b\\", name, to_hex(value))
+                        }
+                        None => reply += &format!("\x1bP0+r{}\x1b\\", name),
+                    }
+                }
+                reply.into_bytes()
+            });
+            Some((end + 2, reply))
+        } else {
+            Some((1, None))
+        }
+    }
+}
+
+fn to_hex(text: &str) -> String {
+    text.bytes().map(|byte| format!("{:02X}", byte)).collect()
+}
+
+fn from_hex(hex: &str) -> String {
+    let bytes: Vec<u8> = (0..hex.len() / 2)
+        .filter_map(|i| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok())
+        .collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// A pseudo terminal answering the queries of the library, see the module doc. The cursor
+/// position is always answered, the other queries only once their reply is set, as by a
+/// terminal without the feature otherwise.
+pub struct FakeTerminal {
+    master: File,
+    /// kept open for the pseudo terminal to live as long as `self`
+    slave: File,
+    replies: Arc<Mutex<Replies>>,
+    output: Arc<Mutex<Vec<u8>>>,
+}
+
+impl FakeTerminal {
+    /// A terminal of `width` columns and `height` rows, with the cursor at the top left
+    pub fn new(width: u16, height: u16) -> io::Result<Self> {
+        let size = Winsize {
+            ws_row: height,
+            ws_col: width,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        };
+        let pty = openpty(&size, None).map_err(io::Error::other)?;
+        let master = unsafe { File::from_raw_fd(pty.master) };
+        let slave = unsafe { File::from_raw_fd(pty.slave) };
+
+        let fake = FakeTerminal {
+            master: master.try_clone()?,
+            slave,
+            replies: Arc::new(Mutex::new(Replies::default())),
+            output: Arc::new(Mutex::new(Vec::new())),
+        };
+        let replies = fake.replies.clone();
+        let output = fake.output.clone();
+        let mut reader = master;
+        // until the application and `self` closed the terminal
+        thread::spawn(move || {
+            let mut writer = match reader.try_clone() {
+                Ok(writer) => writer,
+                Err(_) => return,
+            };
+            let mut buf = [0; 4096];
+            let mut scanned = 0;
+            while let Ok(n) = reader.read(&mut buf) {
+                if n == 0 {
+                    break;
+                }
+                let mut output = output.lock().unwrap();
+                output.extend_from_slice(&buf[..n]);
+                let replies = replies.lock().unwrap();
+                while let Some(start) = output[scanned..].iter().position(|&byte| byte == 0x1b) {
+                    scanned += start;
+                    match replies.scan(&output[scanned..]) {
+                        Some((len, reply)) => {
+                            scanned += len;
+                            if let Some(reply) = reply {
+                                let _ = writer.write_all(&reply);
+                            }
+                        }
+                        None => break,
+                    }
+                }
+                if !output[scanned..].contains(&0x1b) {
+                    scanned = output.len();
+                }
+            }
+        });
+        Ok(fake)
+    }
+
+    /// the terminal device, see `TermOptions::tty_path`
+    pub fn path(&self) -> PathBuf {
+        PathBuf::from(format!("/dev/fd/{}", self.slave.as_raw_fd()))
+    }
+
+    /// (row, col), from 0, as reported to the cursor position queries
+    pub fn set_cursor_pos(&self, row: usize, col: usize) {
+        self.replies.lock().unwrap().cursor_pos = (row, col);
+    }
+
+    /// the reply to the primary device attributes (DA1) query, e.g. `[62, 22]`
+    pub fn set_device_attributes(&self, attributes: &[u16]) {
+        self.replies.lock().unwrap().device_attributes = Some(attributes.to_vec());
+    }
+
+    /// the reply to the background color (OSC 11) query
+    pub fn set_background_color(&self, r: u8, g: u8, b: u8) {
+        self.replies.lock().unwrap().background = Some((r, g, b));
+    }
+
+    /// answer the XTGETTCAP queries for `name` with `value`, the other names are answered as
+    /// unknown from then on
+    pub fn set_termcap(&self, name: &str, value: &str) {
+        let mut replies = self.replies.lock().unwrap();
+        let termcap = replies.termcap.get_or_insert_with(BTreeMap::new);
+        termcap.insert(name.to_string(), value.to_string());
+    }
+
+    /// the reply to the kitty keyboard protocol query
+    pub fn set_keyboard_flags(&self, flags: u8) {
+        self.replies.lock().unwrap().keyboard_flags = Some(flags);
+    }
+
+    /// type `input`, read by the application as from a terminal, e.g. `b"\x1b[A"` for `Up`
+    pub fn type_bytes(&self, input: &[u8]) -> io::Result<()> {
+        (&self.master).write_all(input)
+    }
+
+    /// everything the application wrote so far
+    pub fn output(&self) -> Vec<u8> {
+        self.output.lock().unwrap().clone()
+    }
+
+    /// wait up to `timeout` for the application to write `bytes`, return whether it did
+    pub fn wait_for_output(&self, bytes: &[u8], timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self
+                .output()
+                .windows(bytes.len())
+                .any(|window| window == bytes)
+            {
+                return true;
+            }
+            if Instant::now() >= deadline {
+                return false;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::canvas::Canvas;
+    use crate::event::{Event, KeyEvent};
+    use crate::screen::Screen;
+    use crate::term::{TermHeight, TermOptions};
+
+    /// echoes the typed characters on the first line
+    struct Echo(Mutex<Screen>);
+
+    impl ScriptTarget for Echo {
+        fn inject_keys(&self, keys: &[Key]) -> canvas::Result<()> {
+            let mut screen = self.0.lock().unwrap();
+            for key in keys {
+                match key {
+                    Key::Char(ch) => {
+                        let col = screen.cursor().map_or(0, |(_, col)| col);
+                        screen.put_cell(0, col, (*ch).into())?;
+                        screen.set_cursor(0, col + 1)?;
+                    }
+                    Key::Ctrl('u') => {
+                        screen.clear()?;
+                        screen.set_cursor(0, 0)?;
+                    }
+                    _ => {}
+                }
+            }
+            Ok(())
+        }
+
+        fn snapshot(&self) -> ScreenSnapshot {
+            ScreenSnapshot::from_screen(&self.0.lock().unwrap())
+        }
+    }
+
+    #[test]
+    fn run_steps() {
+        let echo = Echo(Mutex::new(Screen::new(10, 2)));
+        Script::new()
+            .type_text("abc")
+            .expect_line(0, "abc")
+            .expect_cursor(0, 3)
+            .key(Key::Ctrl('u'))
+            .expect_no_text("abc")
+            .run(&echo)
+            .unwrap();
+    }
+
+    #[test]
+    fn report_failed_step() {
+        let echo = Echo(Mutex::new(Screen::new(10, 2)));
+        let err = Script::new()
+            .timeout(Duration::from_millis(20))
+            .type_text("ab")
+            .expect_text("abc")
+            .run(&echo)
+            .unwrap_err();
+        assert_eq!(1, err.step);
+        assert_eq!("ab", err.screen.lines().next().unwrap());
+        assert!(err.to_string().contains("\"abc\""));
+    }
+
+    #[test]
+    fn fake_terminal() {
+        let fake = FakeTerminal::new(40, 10).unwrap();
+        fake.set_cursor_pos(8, 0);
+        fake.set_device_attributes(&[62, 22]);
+        fake.set_background_color(0x10, 0x20, 0x30);
+        fake.set_termcap("RGB", "");
+        let options = TermOptions::default()
+            .height(TermHeight::Fixed(4))
+            .tty_path(fake.path())
+            .probe_capabilities(true);
+        let term = Term::with_options(options).unwrap();
+
+        // inline, scrolled up for the 4 rows to fit below the cursor
+        assert_eq!((40, 4), term.term_size().unwrap());
+        assert!(fake.wait_for_output(b"\x1b[7;1H", DEFAULT_TIMEOUT));
+
+        // the replies are picked up with the events, the termcap ones last
+        while !term.capabilities().termcap.contains_key("blink") {
+            term.peek_event(DEFAULT_TIMEOUT).unwrap();
+        }
+        let capabilities = term.capabilities();
+        assert_eq!(Some((0x10, 0x20, 0x30)), capabilities.background);
+        assert_eq!(Some(vec![62, 22]), capabilities.device_attributes);
+        assert_eq!(Some(&Some(String::new())), capabilities.termcap.get("RGB"));
+        assert_eq!(Some(&None), capabilities.termcap.get("Smulx"));
+
+        fake.type_bytes(b"a").unwrap();
+        loop {
+            match term.peek_event(DEFAULT_TIMEOUT).unwrap() {
+                Event::Key(KeyEvent { key, .. }) => break assert_eq!(Key::Char('a'), key),
+                Event::Terminal(_) => {}
+                event => panic!("unexpected {:?}", event),
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! `AttrText` is a line of text with attributes on parts of it.
+
+use crate::attr::Attr;
+use crate::canvas::{Canvas, Result};
+use crate::draw::Draw;
+use crate::highlight::{print_highlighted, Span};
+use crate::width::display_width;
+use std::ops::Range;
+
+/// A hyperlink on the bytes `range` of the text
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    pub range: Range<usize>,
+    pub url: String,
+}
+
+/// A line of styled text
+///
+/// ```
+/// use tuikit::attr::Effect;
+/// use tuikit::text::AttrText;
+///
+/// let mut text = AttrText::new();
+/// text.push_str("hello ", Effect::empty());
+/// text.push_str("world", Effect::BOLD);
+/// assert_eq!("hello world", text.text);
+/// assert_eq!(6..11, text.spans[0].range);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AttrText {
+    pub text: String,
+    pub spans: Vec<Span>,
+    pub links: Vec<Link>,
+}
+
+impl AttrText {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// append `text` with `attr`, spans with default attribute are not recorded
+    pub fn push_str(&mut self, text: &str, attr: impl Into<Attr>) {
+        let attr = attr.into();
+        let start = self.text.len();
+        self.text.push_str(text);
+        let end = self.text.len();
+        if start == end || attr == Attr::default() {
+            return;
+        }
+
+        match self.spans.last_mut() {
+            Some(last) if last.range.end == start && last.attr == attr => last.range.end = end,
+            _ => self.spans.push(Span::new(start..end, attr)),
+        }
+    }
+
+    /// append `text` with `attr` as a link to `url`
+    pub fn push_link(&mut self, text: &str, url: &str, attr: impl Into<Attr>) {
+        let start = self.text.len();
+        self.push_str(text, attr);
+        self.links.push(Link {
+            range: start..self.text.len(),
+            url: url.to_string(),
+        });
+    }
+
+    /// the link at byte offset `pos` of the text, if any
+    pub fn link_at(&self, pos: usize) -> Option<&str> {
+        self.links
+            .iter()
+            .find(|link| link.range.contains(&pos))
+            .map(|link| link.url.as_str())
+    }
+
+    /// the display width of the text
+    pub fn width(&self) -> usize {
+        display_width(&self.text)
+    }
+}
+
+impl From<&str> for AttrText {
+    fn from(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            ..Self::default()
+        }
+    }
+}
+
+impl Draw for AttrText {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
+        print_highlighted(canvas, 0, 0, &self.text, &self.spans, Attr::default())?;
+        Ok(())
+    }
+
+    fn size_hint(&self) -> (Option<usize>, Option<usize>) {
+        (Some(self.width()), Some(1))
+    }
+}
+
+impl Draw for Vec<AttrText> {
+    /// draw the lines from the top of the canvas
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
+        let (_, height) = canvas.size()?;
+        for (row, line) in self.iter().take(height).enumerate() {
+            print_highlighted(canvas, row, 0, &line.text, &line.spans, Attr::default())?;
+        }
+        Ok(())
+    }
+
+    fn size_hint(&self) -> (Option<usize>, Option<usize>) {
+        let width = self.iter().map(AttrText::width).max().unwrap_or(0);
+        (Some(width), Some(self.len()))
+    }
+}
@@ -0,0 +1,227 @@
+//! Record the presented frames and the events between them.
+//!
+//! Reports like "the screen is garbled after a resize" are hard to reproduce. With recording
+//! enabled (`Term::start_recording`), every `present` stores a snapshot of the screen (see
+//! `tuikit::snapshot`) and every event returned by `poll_event`/`peek_event` is noted, the
+//! timeline could then be saved and stepped through with the viewer example:
+//!
+//! ```text
+//! cargo run --example timeline_viewer -- /tmp/timeline.tktl
+//! ```
+//!
+//! The file format, all integers little endian: magic `b"TKTL"`, version u8 (1), then the
+//! entries till the end: tag u8 (0 frame, 1 event), milliseconds since the start u64, then a
+//! snapshot for frames or the `Debug` representation of the event (length u32, UTF-8 bytes).
+
+use crate::event::Event;
+use crate::screen::Screen;
+use crate::snapshot::ScreenSnapshot;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const MAGIC: &[u8; 4] = b"TKTL";
+const VERSION: u8 = 1;
+const TAG_FRAME: u8 = 0;
+const TAG_EVENT: u8 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimelineEntry {
+    Frame(Duration, ScreenSnapshot),
+    /// the `Debug` representation of the event
+    Event(Duration, String),
+}
+
+impl TimelineEntry {
+    /// the time since the recording started
+    pub fn time(&self) -> Duration {
+        match self {
+            TimelineEntry::Frame(time, _) | TimelineEntry::Event(time, _) => *time,
+        }
+    }
+}
+
+/// The frames and events recorded, in order
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    start: Instant,
+    entries: Vec<TimelineEntry>,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Timeline {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn record_frame(&mut self, screen: &Screen) {
+        let snapshot = ScreenSnapshot::from_screen(screen);
+        self.entries
+            .push(TimelineEntry::Frame(self.elapsed(), snapshot));
+    }
+
+    pub fn record_event(&mut self, event: &Event) {
+        self.entries
+            .push(TimelineEntry::Event(self.elapsed(), format!("{:?}", event)));
+    }
+
+    /// in milliseconds, the precision saved
+    fn elapsed(&self) -> Duration {
+        Duration::from_millis(self.start.elapsed().as_millis() as u64)
+    }
+
+    pub fn entries(&self) -> &[TimelineEntry] {
+        &self.entries
+    }
+
+    /// the frames with the events that happened since the previous frame
+    pub fn frames(&self) -> Vec<(&ScreenSnapshot, Vec<&str>)> {
+        let mut frames = Vec::new();
+        let mut events = Vec::new();
+        for entry in self.entries.iter() {
+            match entry {
+                TimelineEntry::Event(_, event) => events.push(event.as_str()),
+                TimelineEntry::Frame(_, snapshot) => {
+                    frames.push((snapshot, std::mem::take(&mut events)))
+                }
+            }
+        }
+        frames
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+
+    pub fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        for entry in self.entries.iter() {
+            let time = entry.time().as_millis() as u64;
+            match entry {
+                TimelineEntry::Frame(_, snapshot) => {
+                    writer.write_all(&[TAG_FRAME])?;
+                    writer.write_all(&time.to_le_bytes())?;
+                    snapshot.write_to(writer)?;
+                }
+                TimelineEntry::Event(_, event) => {
+                    writer.write_all(&[TAG_EVENT])?;
+                    writer.write_all(&time.to_le_bytes())?;
+                    writer.write_all(&(event.len() as u32).to_le_bytes())?;
+                    writer.write_all(event.as_bytes())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        let mut header = [0; 5];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(io::Error::new(ErrorKind::InvalidData, "not a timeline"));
+        }
+        if header[4] != VERSION {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("unsupported timeline version {}", header[4]),
+            ));
+        }
+
+        let mut entries = Vec::new();
+        loop {
+            let mut tag = [0];
+            match reader.read_exact(&mut tag) {
+                Ok(()) => {}
+                Err(err) if err.kind() == ErrorKind::UnexpectedEof => break,
+                Err(err) => return Err(err),
+            }
+            let mut time = [0; 8];
+            reader.read_exact(&mut time)?;
+            let time = Duration::from_millis(u64::from_le_bytes(time));
+
+            match tag[0] {
+                TAG_FRAME => entries.push(TimelineEntry::Frame(
+                    time,
+                    ScreenSnapshot::read_from(reader)?,
+                )),
+                TAG_EVENT => {
+                    let mut len = [0; 4];
+                    reader.read_exact(&mut len)?;
+                    let mut event = vec![0; u32::from_le_bytes(len) as usize];
+                    reader.read_exact(&mut event)?;
+                    let event = String::from_utf8(event)
+                        .map_err(|err| io::Error::new(ErrorKind::InvalidData, err))?;
+                    entries.push(TimelineEntry::Event(time, event));
+                }
+                tag => {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidData,
+                        format!("invalid timeline entry {}", tag),
+                    ))
+                }
+            }
+        }
+
+        Ok(Self {
+            start: Instant::now(),
+            entries,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::canvas::Canvas;
+    use crate::event::TerminalEvent;
+    use crate::key::Key;
+
+    #[test]
+    fn round_trip() {
+        let mut screen = Screen::new(3, 1);
+        let mut timeline = Timeline::new();
+        timeline.record_frame(&screen);
+        timeline.record_event(&Event::from_key(Key::Char('a')));
+        timeline.record_event(&Event::Terminal(TerminalEvent::Resize {
+            width: 3,
+            height: 1,
+        }));
+        let _ = screen.print(0, 0, "a");
+        timeline.record_frame(&screen);
+
+        let mut bytes = Vec::new();
+        timeline.write_to(&mut bytes).unwrap();
+        let loaded = Timeline::read_from(&mut bytes.as_slice()).unwrap();
+        assert_eq!(timeline.entries(), loaded.entries());
+
+        let frames = loaded.frames();
+        assert_eq!(2, frames.len());
+        assert!(frames[0].1.is_empty());
+        assert_eq!(
+            vec![
+                "Key(KeyEvent { key: Char('a'), modifiers: (empty) })",
+                "Terminal(Resize { width: 3, height: 1 })"
+            ],
+            frames[1].1
+        );
+        assert_eq!("a", frames[1].0.text());
+
+        assert!(Timeline::read_from(&mut &bytes[..bytes.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,210 @@
+//! Named timers, delivered through the event loop.
+//!
+//! `Term::set_timer` starts a timer returned by `poll_event` as `Event::Timer(id)` once it
+//! expires, every `delay` if repeating: debounced search as you type, auto dismissed toasts,
+//! panels refreshed at different rates. Setting a timer again with the same id restarts it, so
+//! debouncing is just setting the timer on every key:
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use tuikit::prelude::*;
+//!
+//! let term = Term::new().unwrap();
+//! term.set_timer("clock", Duration::from_secs(1), true);
+//! while let Ok(event) = term.poll_event() {
+//!     match event {
+//!         Event::Key(KeyEvent { key: Key::Char(_), .. }) => term.set_timer("search", Duration::from_millis(200), false),
+//!         Event::Timer(id) if id == "search" => { /* run the search */ }
+//!         Event::Timer(id) if id == "clock" => { /* redraw the clock */ }
+//!         _ => {}
+//!     }
+//! }
+//! ```
+//!
+//! The timers are kept in a hashed timer wheel: setting and cancelling cost the same whatever
+//! the number of timers, and they expire with a resolution of `TICK`.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// The resolution of the timers
+pub const TICK: Duration = Duration::from_millis(10);
+const SLOTS: usize = 256;
+
+#[derive(Debug)]
+struct Entry {
+    id: String,
+    /// the turns of the wheel left before expiring
+    rounds: u64,
+    repeat: Option<Duration>,
+    /// to tell a cancelled or restarted timer from the current one
+    generation: u64,
+}
+
+/// The timers by expiry tick
+#[derive(Debug)]
+pub(crate) struct TimerWheel {
+    slots: Vec<Vec<Entry>>,
+    /// the tick handled last
+    tick: u64,
+    start: Instant,
+    /// the generation of the running timers
+    running: HashMap<String, u64>,
+    next_generation: u64,
+}
+
+impl TimerWheel {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            slots: (0..SLOTS).map(|_| Vec::new()).collect(),
+            tick: 0,
+            start: now,
+            running: HashMap::new(),
+            next_generation: 0,
+        }
+    }
+
+    /// start `id`, replacing the running timer of the same id
+    pub fn set(&mut self, id: &str, delay: Duration, repeating: bool, now: Instant) {
+        let generation = self.next_generation;
+        self.next_generation += 1;
+        self.running.insert(id.to_string(), generation);
+        let repeat = if repeating { Some(delay) } else { None };
+        self.schedule(id.to_string(), delay, repeat, generation, now);
+    }
+
+    /// stop `id`, return false if it wasn't running
+    pub fn cancel(&mut self, id: &str) -> bool {
+        // the entry is dropped once its slot is reached
+        self.running.remove(id).is_some()
+    }
+
+    fn schedule(
+        &mut self,
+        id: String,
+        delay: Duration,
+        repeat: Option<Duration>,
+        generation: u64,
+        now: Instant,
+    ) {
+        // at least the next tick, rounded up so a timer never expires early
+        let deadline = now.saturating_duration_since(self.start) + delay;
+        let ticks = (deadline.as_nanos().div_ceil(TICK.as_nanos()) as u64).max(self.tick + 1);
+        let ahead = ticks - self.tick - 1;
+        let slot = (ticks % SLOTS as u64) as usize;
+        self.slots[slot].push(Entry {
+            id,
+            rounds: ahead / SLOTS as u64,
+            repeat,
+            generation,
+        });
+    }
+
+    /// The ids of the timers expired by `now`, in order of expiry. The repeating ones are
+    /// scheduled again.
+    pub fn advance(&mut self, now: Instant) -> Vec<String> {
+        let current =
+            (now.saturating_duration_since(self.start).as_nanos() / TICK.as_nanos()) as u64;
+        let mut expired = Vec::new();
+        while self.tick < current {
+            self.tick += 1;
+            let slot = (self.tick % SLOTS as u64) as usize;
+            let entries = std::mem::take(&mut self.slots[slot]);
+            for mut entry in entries {
+                if self.running.get(&entry.id) != Some(&entry.generation) {
+                    continue;
+                }
+                if entry.rounds > 0 {
+                    entry.rounds -= 1;
+                    self.slots[slot].push(entry);
+                    continue;
+                }
+                match entry.repeat {
+                    Some(delay) => {
+                        let at = self.start + TICK * self.tick as u32;
+                        self.schedule(entry.id.clone(), delay, entry.repeat, entry.generation, at);
+                    }
+                    None => {
+                        self.running.remove(&entry.id);
+                    }
+                }
+                expired.push(entry.id);
+            }
+        }
+        expired
+    }
+
+    /// how long till the next timer expires, `None` if there is none
+    pub fn timeout(&self, now: Instant) -> Option<Duration> {
+        let next_tick = self
+            .slots
+            .iter()
+            .enumerate()
+            .flat_map(|(slot, entries)| entries.iter().map(move |entry| (slot, entry)))
+            .filter(|(_, entry)| self.running.get(&entry.id) == Some(&entry.generation))
+            .map(|(slot, entry)| {
+                let offset = (slot as u64 + SLOTS as u64 - self.tick % SLOTS as u64) % SLOTS as u64;
+                // the slot of the current tick was handled, it's a full turn away
+                let offset = if offset == 0 { SLOTS as u64 } else { offset };
+                self.tick + offset + entry.rounds * SLOTS as u64
+            })
+            .min()?;
+        let at = self.start + TICK * next_tick as u32;
+        Some(at.saturating_duration_since(now))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn expire_in_order() {
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let mut wheel = TimerWheel::new(start);
+        wheel.set("slow", ms(50), false, start);
+        wheel.set("fast", ms(20), false, start);
+        assert_eq!(Some(ms(20)), wheel.timeout(start));
+
+        assert!(wheel.advance(start + ms(15)).is_empty());
+        assert_eq!(vec!["fast".to_string()], wheel.advance(start + ms(20)));
+        assert_eq!(Some(ms(30)), wheel.timeout(start + ms(20)));
+        assert_eq!(vec!["slow".to_string()], wheel.advance(start + ms(100)));
+        assert!(!wheel.cancel("slow"));
+        assert_eq!(None, wheel.timeout(start + ms(100)));
+    }
+
+    #[test]
+    fn restart_cancel_and_repeat() {
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let mut wheel = TimerWheel::new(start);
+
+        // debounce: set again before it expires
+        wheel.set("search", ms(30), false, start);
+        wheel.set("search", ms(30), false, start + ms(20));
+        assert!(wheel.advance(start + ms(40)).is_empty());
+        assert_eq!(vec!["search".to_string()], wheel.advance(start + ms(50)));
+
+        wheel.set("toast", ms(30), false, start + ms(50));
+        assert!(wheel.cancel("toast"));
+        assert!(!wheel.cancel("toast"));
+        assert!(wheel.advance(start + ms(100)).is_empty());
+
+        wheel.set("tick", ms(20), true, start + ms(100));
+        assert_eq!(2, wheel.advance(start + ms(150)).len());
+        assert_eq!(Some(ms(10)), wheel.timeout(start + ms(150)));
+    }
+
+    #[test]
+    fn longer_than_a_turn() {
+        let start = Instant::now();
+        let mut wheel = TimerWheel::new(start);
+        let turn = TICK * SLOTS as u32;
+        wheel.set("later", turn * 2 + TICK, false, start);
+        assert_eq!(Some(turn * 2 + TICK), wheel.timeout(start));
+        assert!(wheel.advance(start + turn * 2).is_empty());
+        assert_eq!(vec!["later".to_string()], wheel.advance(start + turn * 3));
+    }
+}
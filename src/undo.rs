@@ -0,0 +1,184 @@
+//! A generic undo/redo stack for editing widgets.
+//!
+//! The stack keeps snapshots of the edited state. Record the state *before* each edit with
+//! `push`; consecutive edits of the same kind within a short time are grouped, so that undo
+//! reverts a typed word instead of a single char.
+//!
+//! ```
+//! use tuikit::undo::{EditKind, UndoStack};
+//!
+//! let mut undo = UndoStack::new();
+//! let mut text = String::new();
+//!
+//! for ch in "hi".chars() {
+//!     undo.push(text.clone(), EditKind::Insert);
+//!     text.push(ch);
+//! }
+//!
+//! // the two inserts are grouped
+//! text = undo.undo(text).unwrap();
+//! assert_eq!("", text);
+//! text = undo.redo(text).unwrap();
+//! assert_eq!("hi", text);
+//! ```
+
+use std::time::{Duration, Instant};
+
+const DEFAULT_GROUP_TIMEOUT: Duration = Duration::from_millis(1000);
+const DEFAULT_LIMIT: usize = 100;
+
+/// The kind of an edit, consecutive edits of the same kind could be grouped
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum EditKind {
+    Insert,
+    Delete,
+    /// edits that are never grouped, e.g. paste or replace
+    Other,
+}
+
+#[derive(Debug)]
+pub struct UndoStack<T> {
+    undo: Vec<T>,
+    redo: Vec<T>,
+    limit: usize,
+    group_timeout: Duration,
+    last_edit: Option<(EditKind, Instant)>,
+}
+
+impl<T> Default for UndoStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Builder
+impl<T> UndoStack<T> {
+    pub fn new() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            limit: DEFAULT_LIMIT,
+            group_timeout: DEFAULT_GROUP_TIMEOUT,
+            last_edit: None,
+        }
+    }
+
+    /// max number of undo steps kept, the oldest ones are dropped
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// edits of the same kind within `timeout` of the last one are grouped,
+    /// `Duration::new(0, 0)` disables grouping.
+    pub fn group_timeout(mut self, timeout: Duration) -> Self {
+        self.group_timeout = timeout;
+        self
+    }
+}
+
+impl<T> UndoStack<T> {
+    /// record `state`, the state before an edit of `kind`
+    pub fn push(&mut self, state: T, kind: EditKind) {
+        self.push_at(Instant::now(), state, kind)
+    }
+
+    /// same as `push` with the time of edit specified
+    pub fn push_at(&mut self, now: Instant, state: T, kind: EditKind) {
+        let grouped = match self.last_edit {
+            Some((last_kind, last_time)) => {
+                kind != EditKind::Other
+                    && kind == last_kind
+                    && now.saturating_duration_since(last_time) < self.group_timeout
+            }
+            None => false,
+        };
+
+        self.redo.clear();
+        self.last_edit = Some((kind, now));
+        if grouped && !self.undo.is_empty() {
+            return;
+        }
+
+        self.undo.push(state);
+        if self.undo.len() > self.limit {
+            self.undo.remove(0);
+        }
+    }
+
+    /// stop grouping, the next edit starts a new undo step (e.g. when the cursor moved)
+    pub fn break_group(&mut self) {
+        self.last_edit = None;
+    }
+
+    /// Revert to the previous state, `current` is kept for redo.
+    /// Return `None` if there is nothing to undo.
+    pub fn undo(&mut self, current: T) -> Option<T> {
+        let state = self.undo.pop()?;
+        self.redo.push(current);
+        self.last_edit = None;
+        Some(state)
+    }
+
+    /// Re-apply the last undone state, `current` is kept for undo.
+    /// Return `None` if there is nothing to redo.
+    pub fn redo(&mut self, current: T) -> Option<T> {
+        let state = self.redo.pop()?;
+        self.undo.push(current);
+        self.last_edit = None;
+        Some(state)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.last_edit = None;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn edits_should_be_grouped_by_kind_and_time() {
+        let mut stack = UndoStack::new().group_timeout(Duration::from_millis(100));
+        let start = Instant::now();
+        stack.push_at(start, "", EditKind::Insert);
+        stack.push_at(start + Duration::from_millis(50), "a", EditKind::Insert);
+        stack.push_at(start + Duration::from_millis(60), "ab", EditKind::Delete);
+        stack.push_at(start + Duration::from_millis(500), "a", EditKind::Delete);
+
+        assert_eq!(Some("a"), stack.undo(""));
+        assert_eq!(Some("ab"), stack.undo("a"));
+        assert_eq!(Some(""), stack.undo("ab"));
+        assert_eq!(None, stack.undo(""));
+
+        assert_eq!(Some("ab"), stack.redo(""));
+        assert!(stack.can_redo());
+
+        // a new edit clears the redo history
+        stack.push("ab", EditKind::Other);
+        assert!(!stack.can_redo());
+    }
+
+    #[test]
+    fn stack_should_be_limited() {
+        let mut stack = UndoStack::new().limit(2);
+        for state in 0..5 {
+            stack.push(state, EditKind::Other);
+        }
+
+        assert_eq!(Some(4), stack.undo(5));
+        assert_eq!(Some(3), stack.undo(4));
+        assert_eq!(None, stack.undo(3));
+    }
+}
@@ -0,0 +1,400 @@
+use crate::attr::{Attr, Effect};
+use crate::canvas::{BoundedCanvas, Canvas, Result};
+use crate::draw::Draw;
+use crate::key::Key;
+use crate::locale::{self, Message};
+use crate::width::display_width;
+use std::cmp::{max, min};
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+const SPINNER: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+const SPINNER_INTERVAL_MS: u128 = 80;
+const DEFAULT_MAX_HEIGHT: usize = 8;
+
+type Supplier = dyn Fn(&str) -> Vec<String> + Send + Sync;
+
+/// Events emitted by `CompletionMenu::on_key`
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompletionEvent {
+    /// A candidate was chosen: remove `prefix_len` chars before the cursor (the text typed
+    /// since the menu was opened) and insert `text`.
+    Insert { prefix_len: usize, text: String },
+    /// the menu was closed without choosing a candidate
+    Cancel,
+}
+
+/// A popup menu of completion candidates anchored to the cursor.
+///
+/// The candidates are supplied by a callback that runs in a background thread, a spinner is
+/// drawn until they arrive. Call `poll` regularly (e.g. on `peek_event` timeouts) to pick up
+/// the results, and feed the keys to `on_key` while the menu is open; typed chars filter the
+/// candidates.
+///
+/// ```no_run
+/// use tuikit::widget::{CompletionEvent, CompletionMenu};
+/// use tuikit::key::Key;
+///
+/// let mut menu = CompletionMenu::new(|prefix: &str| {
+///     vec!["print".to_string(), "println".to_string()]
+///         .into_iter()
+///         .filter(|word| word.starts_with(prefix))
+///         .collect()
+/// });
+///
+/// menu.open(0, 4, "pr");
+/// // ... draw the menu as the last layer, call `menu.poll()` until the candidates arrive
+/// if let Some(CompletionEvent::Insert { prefix_len, text }) = menu.on_key(&Key::Enter) {
+///     // replace the last `prefix_len` chars with `text`
+/// }
+/// ```
+pub struct CompletionMenu {
+    supplier: Arc<Supplier>,
+    receiver: Option<Receiver<Vec<String>>>,
+    opened: bool,
+    anchor: (usize, usize),
+    prefix: String,
+    candidates: Vec<String>,
+    matches: Vec<usize>,
+    selected: usize,
+    loading_since: Instant,
+
+    max_height: usize,
+    attr: Attr,
+    selected_attr: Attr,
+}
+
+// Builder
+impl CompletionMenu {
+    pub fn new(supplier: impl Fn(&str) -> Vec<String> + Send + Sync + 'static) -> Self {
+        Self {
+            supplier: Arc::new(supplier),
+            receiver: None,
+            opened: false,
+            anchor: (0, 0),
+            prefix: String::new(),
+            candidates: Vec::new(),
+            matches: Vec::new(),
+            selected: 0,
+            loading_since: Instant::now(),
+            max_height: DEFAULT_MAX_HEIGHT,
+            attr: Attr::default().effect(Effect::REVERSE),
+            selected_attr: Attr::default().effect(Effect::BOLD),
+        }
+    }
+
+    /// max number of candidates shown at a time
+    pub fn max_height(mut self, max_height: usize) -> Self {
+        self.max_height = max(max_height, 1);
+        self
+    }
+
+    pub fn attr(mut self, attr: impl Into<Attr>) -> Self {
+        self.attr = attr.into();
+        self
+    }
+
+    pub fn selected_attr(mut self, attr: impl Into<Attr>) -> Self {
+        self.selected_attr = attr.into();
+        self
+    }
+}
+
+impl CompletionMenu {
+    /// Open the menu at the cursor position `(row, col)` of the canvas it will be drawn on and
+    /// request the candidates for `prefix`.
+    pub fn open(&mut self, row: usize, col: usize, prefix: &str) {
+        self.opened = true;
+        self.anchor = (row, col);
+        self.prefix = prefix.to_string();
+        self.candidates.clear();
+        self.matches.clear();
+        self.selected = 0;
+        self.loading_since = Instant::now();
+
+        // results of the previous request are dropped with the old receiver
+        let (tx, rx) = channel();
+        let supplier = self.supplier.clone();
+        let query = self.prefix.clone();
+        thread::spawn(move || {
+            let _ = tx.send(supplier(&query));
+        });
+        self.receiver = Some(rx);
+    }
+
+    pub fn close(&mut self) {
+        self.opened = false;
+        self.receiver = None;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.opened
+    }
+
+    /// whether the candidates are still being computed
+    pub fn is_loading(&self) -> bool {
+        self.receiver.is_some()
+    }
+
+    /// Pick up the candidates if they are ready, return `true` if the menu should be re-drawn
+    /// (including the spinner animation while loading).
+    pub fn poll(&mut self) -> bool {
+        let result = match self.receiver.as_ref() {
+            Some(receiver) => receiver.try_recv(),
+            None => return false,
+        };
+
+        match result {
+            Ok(candidates) => {
+                self.receiver = None;
+                self.set_candidates(candidates);
+            }
+            Err(TryRecvError::Disconnected) => {
+                // the supplier panicked
+                self.receiver = None;
+            }
+            Err(TryRecvError::Empty) => {}
+        }
+        self.opened
+    }
+
+    /// the candidates matching the current prefix
+    pub fn matches(&self) -> impl Iterator<Item = &str> {
+        self.matches
+            .iter()
+            .map(move |&index| self.candidates[index].as_str())
+    }
+
+    pub fn selected(&self) -> Option<&str> {
+        self.matches().nth(self.selected)
+    }
+
+    fn set_candidates(&mut self, candidates: Vec<String>) {
+        self.candidates = candidates;
+        self.refilter();
+    }
+
+    /// keep the candidates containing the prefix (case insensitive)
+    fn refilter(&mut self) {
+        let prefix = self.prefix.to_lowercase();
+        self.matches = self
+            .candidates
+            .iter()
+            .enumerate()
+            .filter(|(_, candidate)| candidate.to_lowercase().contains(&prefix))
+            .map(|(index, _)| index)
+            .collect();
+        self.selected = min(self.selected, self.matches.len().saturating_sub(1));
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let len = self.matches.len() as isize;
+        if len > 0 {
+            self.selected = (self.selected as isize + delta).rem_euclid(len) as usize;
+        }
+    }
+
+    /// Handle the key while the menu is open, return `Some(event)` if the menu is closed.
+    ///
+    /// - `Up`/`Down`/`Ctrl-P`/`Ctrl-N`/`Tab`/`BackTab` select the candidate
+    /// - `Enter` inserts the selected candidate
+    /// - `ESC`/`Ctrl-G` cancels
+    /// - chars and `Backspace` edit the prefix, erasing the whole prefix cancels
+    pub fn on_key(&mut self, key: &Key) -> Option<CompletionEvent> {
+        if !self.opened {
+            return None;
+        }
+
+        match key {
+            Key::Up | Key::Ctrl('p') | Key::BackTab => self.move_selection(-1),
+            Key::Down | Key::Ctrl('n') | Key::Tab => self.move_selection(1),
+            Key::Enter => {
+                let text = self.selected()?.to_string();
+                let prefix_len = self.prefix.chars().count();
+                self.close();
+                return Some(CompletionEvent::Insert { prefix_len, text });
+            }
+            Key::ESC | Key::Ctrl('g') => {
+                self.close();
+                return Some(CompletionEvent::Cancel);
+            }
+            Key::Backspace => {
+                if self.prefix.pop().is_none() {
+                    self.close();
+                    return Some(CompletionEvent::Cancel);
+                }
+                self.anchor.1 = self.anchor.1.saturating_sub(1);
+                self.refilter();
+            }
+            Key::Char(ch) => {
+                self.prefix.push(*ch);
+                self.anchor.1 += 1;
+                self.refilter();
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn spinner(&self) -> char {
+        let elapsed = self.loading_since.elapsed().as_millis();
+        SPINNER[(elapsed / SPINNER_INTERVAL_MS) as usize % SPINNER.len()]
+    }
+}
+
+impl Draw for CompletionMenu {
+    /// draw the menu below the anchor (or above it if there is not enough space)
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
+        if !self.opened {
+            return Ok(());
+        }
+
+        let (width, height) = canvas.size()?;
+        let (row, col) = self.anchor;
+
+        let lines: Vec<String> = if self.is_loading() {
+            vec![format!(
+                "{} {}",
+                self.spinner(),
+                locale::text(&Message::Loading)
+            )]
+        } else {
+            self.matches().map(|text| format!(" {} ", text)).collect()
+        };
+        if lines.is_empty() {
+            return Ok(());
+        }
+
+        let below = height.saturating_sub(row + 1);
+        let above = min(row, height);
+        let menu_height = min(lines.len(), self.max_height);
+        let (top, menu_height) = if below >= menu_height || below >= above {
+            (row + 1, min(menu_height, below))
+        } else {
+            let menu_height = min(menu_height, above);
+            (row - menu_height, menu_height)
+        };
+
+        // the prefix is typed before the anchor, align the candidates with it
+        let left = col.saturating_sub(self.prefix.chars().count() + 1);
+        let menu_width = lines
+            .iter()
+            .map(|line| display_width(line))
+            .max()
+            .unwrap_or(0);
+        let menu_width = min(menu_width, width.saturating_sub(left));
+        let left = min(left, width.saturating_sub(menu_width));
+
+        // scroll so that the selected item is visible
+        let offset = (self.selected + 1).saturating_sub(menu_height);
+        let direction = locale::direction();
+        let mut menu = BoundedCanvas::new(top, left, menu_width, menu_height, canvas);
+        for (index, line) in lines.iter().enumerate().skip(offset).take(menu_height) {
+            let attr = if !self.is_loading() && index == self.selected {
+                self.attr.extend(self.selected_attr)
+            } else {
+                self.attr
+            };
+            let line_row = index - offset;
+            for fill in 0..menu_width {
+                let _ = menu.print_with_attr(line_row, fill, " ", attr);
+            }
+            let col = locale::align(line, menu_width, direction);
+            menu.print_with_attr(line_row, col, line, attr)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::screen::Screen;
+    use std::time::Duration;
+
+    fn wait_for_candidates(menu: &mut CompletionMenu) {
+        for _ in 0..100 {
+            menu.poll();
+            if !menu.is_loading() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("candidates did not arrive");
+    }
+
+    fn row_text(screen: &Screen, row: usize) -> String {
+        screen
+            .iter_cell()
+            .filter(|(r, _, _)| *r == row)
+            .map(|(_, _, cell)| cell.ch)
+            .collect::<String>()
+            .replace('\0', " ")
+            .trim_end()
+            .to_string()
+    }
+
+    fn new_menu() -> CompletionMenu {
+        CompletionMenu::new(|prefix: &str| {
+            ["src", "Cargo.toml", "CHANGELOG.md"]
+                .iter()
+                .filter(|name| name.to_lowercase().starts_with(&prefix.to_lowercase()))
+                .map(|name| name.to_string())
+                .collect()
+        })
+    }
+
+    #[test]
+    fn typing_should_filter_candidates() {
+        let mut menu = new_menu();
+        menu.open(0, 1, "c");
+        wait_for_candidates(&mut menu);
+        assert_eq!(
+            vec!["Cargo.toml", "CHANGELOG.md"],
+            menu.matches().collect::<Vec<_>>()
+        );
+
+        assert_eq!(None, menu.on_key(&Key::Char('h')));
+        assert_eq!(vec!["CHANGELOG.md"], menu.matches().collect::<Vec<_>>());
+        assert_eq!(None, menu.on_key(&Key::Backspace));
+        assert_eq!(None, menu.on_key(&Key::Up));
+        assert_eq!(
+            Some(CompletionEvent::Insert {
+                prefix_len: 1,
+                text: "CHANGELOG.md".to_string()
+            }),
+            menu.on_key(&Key::Enter)
+        );
+        assert!(!menu.is_open());
+    }
+
+    #[test]
+    fn erasing_prefix_should_cancel() {
+        let mut menu = new_menu();
+        menu.open(0, 0, "");
+        assert_eq!(Some(CompletionEvent::Cancel), menu.on_key(&Key::Backspace));
+    }
+
+    #[test]
+    fn menu_should_be_drawn_below_the_anchor() {
+        let mut menu = new_menu();
+        let mut screen = Screen::new(20, 4);
+
+        menu.open(0, 3, "c");
+        wait_for_candidates(&mut menu);
+        menu.draw(&mut screen).unwrap();
+        assert_eq!("  Cargo.toml", row_text(&screen, 1));
+        assert_eq!("  CHANGELOG.md", row_text(&screen, 2));
+
+        // not enough space below, drawn above
+        let _ = screen.clear();
+        menu.open(3, 3, "c");
+        wait_for_candidates(&mut menu);
+        menu.draw(&mut screen).unwrap();
+        assert_eq!("  Cargo.toml", row_text(&screen, 1));
+        assert_eq!("", row_text(&screen, 3));
+    }
+}
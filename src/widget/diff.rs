@@ -0,0 +1,566 @@
+use crate::attr::{Attr, Color, Effect};
+use crate::canvas::{BoundedCanvas, Canvas, Result};
+use crate::draw::Draw;
+use crate::highlight::{print_highlighted, Highlighter, Span};
+use crate::locale::{self, Message};
+use std::ops::Range;
+
+const DEFAULT_CONTEXT: usize = 3;
+
+/// The kind of a line in a diff
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DiffLineKind {
+    /// file headers such as `diff --git`, `---` and `+++`
+    Header,
+    /// hunk headers `@@ -1,3 +1,4 @@`
+    Hunk,
+    Context,
+    Added,
+    Removed,
+}
+
+/// A line of a diff, `text` is without the leading `+`/`-`/` `
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub text: String,
+    /// the changed part (byte range of `text`) compared to the paired added/removed line
+    pub changed: Option<Range<usize>>,
+}
+
+impl DiffLine {
+    fn new(kind: DiffLineKind, text: &str) -> Self {
+        Self {
+            kind,
+            text: text.to_string(),
+            changed: None,
+        }
+    }
+
+    fn sign(&self) -> &'static str {
+        match self.kind {
+            DiffLineKind::Added => "+",
+            DiffLineKind::Removed => "-",
+            DiffLineKind::Context => " ",
+            DiffLineKind::Header | DiffLineKind::Hunk => "",
+        }
+    }
+}
+
+/// How the `DiffView` lays out the changes
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum DiffMode {
+    /// removed and added lines one after another, like `git diff`
+    #[default]
+    Inline,
+    /// old text on the left, new text on the right
+    SideBySide,
+}
+
+/// the rows shown, by index in the lines
+enum Row {
+    Line(usize),
+    Pair(Option<usize>, Option<usize>),
+    Fold(usize),
+}
+
+/// A widget showing a diff with add/remove coloring, intra-line highlighting and folding of
+/// long unchanged runs. The syntax could be highlighted too, see `highlighter`.
+///
+/// ```
+/// use tuikit::attr::Color;
+/// use tuikit::highlight::KeywordHighlighter;
+/// use tuikit::widget::{DiffMode, DiffView};
+///
+/// let view = DiffView::from_texts("a\nb\nc\n", "a\nB\nc\n")
+///     .mode(DiffMode::SideBySide)
+///     .highlighter(KeywordHighlighter::new().keyword("c", Color::BLUE));
+/// assert_eq!(3, view.height());
+/// ```
+pub struct DiffView {
+    lines: Vec<DiffLine>,
+    /// the syntax spans of each line, see `highlighter`
+    syntax: Vec<Vec<Span>>,
+    mode: DiffMode,
+    context: Option<usize>,
+    offset: usize,
+
+    header_attr: Attr,
+    hunk_attr: Attr,
+    added_attr: Attr,
+    removed_attr: Attr,
+    changed_attr: Attr,
+    fold_attr: Attr,
+}
+
+// Builder
+impl DiffView {
+    pub fn new(lines: Vec<DiffLine>) -> Self {
+        let mut lines = lines;
+        mark_changes(&mut lines);
+        Self {
+            lines,
+            syntax: Vec::new(),
+            mode: DiffMode::default(),
+            context: Some(DEFAULT_CONTEXT),
+            offset: 0,
+            header_attr: Attr::from(Effect::BOLD),
+            hunk_attr: Attr::from(Color::CYAN),
+            added_attr: Attr::from(Color::GREEN),
+            removed_attr: Attr::from(Color::RED),
+            changed_attr: Attr::from(Effect::REVERSE),
+            fold_attr: Attr::from(Color::LIGHT_BLACK),
+        }
+    }
+
+    /// parse the output of `diff -u` or `git diff`
+    pub fn from_unified(diff: &str) -> Self {
+        let lines = diff
+            .lines()
+            .map(|line| {
+                if line.starts_with("@@") {
+                    DiffLine::new(DiffLineKind::Hunk, line)
+                } else if line.starts_with("+++") || line.starts_with("---") {
+                    DiffLine::new(DiffLineKind::Header, line)
+                } else if let Some(text) = line.strip_prefix('+') {
+                    DiffLine::new(DiffLineKind::Added, text)
+                } else if let Some(text) = line.strip_prefix('-') {
+                    DiffLine::new(DiffLineKind::Removed, text)
+                } else if let Some(text) = line.strip_prefix(' ') {
+                    DiffLine::new(DiffLineKind::Context, text)
+                } else if line.is_empty() {
+                    DiffLine::new(DiffLineKind::Context, line)
+                } else {
+                    DiffLine::new(DiffLineKind::Header, line)
+                }
+            })
+            .collect();
+        Self::new(lines)
+    }
+
+    /// compute the line diff between `old` and `new`
+    pub fn from_texts(old: &str, new: &str) -> Self {
+        let old: Vec<&str> = old.lines().collect();
+        let new: Vec<&str> = new.lines().collect();
+        Self::new(diff_lines(&old, &new))
+    }
+
+    pub fn mode(mut self, mode: DiffMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Number of unchanged lines kept around the changes, the rest are folded.
+    /// `None` to show all the lines.
+    pub fn context(mut self, context: Option<usize>) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// number of rows scrolled
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn added_attr(mut self, attr: impl Into<Attr>) -> Self {
+        self.added_attr = attr.into();
+        self
+    }
+
+    pub fn removed_attr(mut self, attr: impl Into<Attr>) -> Self {
+        self.removed_attr = attr.into();
+        self
+    }
+
+    /// the attribute extending added/removed attribute for the changed part of a line
+    pub fn changed_attr(mut self, attr: impl Into<Attr>) -> Self {
+        self.changed_attr = attr.into();
+        self
+    }
+
+    /// Highlight the syntax of the lines with `highlighter`, under the add/remove coloring.
+    /// The old and the new text are highlighted separately, from the start state at every
+    /// file and hunk header; the unchanged lines get the spans of the new text.
+    pub fn highlighter(mut self, highlighter: impl Highlighter) -> Self {
+        let mut old_state = highlighter.start_state();
+        let mut new_state = highlighter.start_state();
+        self.syntax = self
+            .lines
+            .iter()
+            .map(|line| match line.kind {
+                DiffLineKind::Header | DiffLineKind::Hunk => {
+                    old_state = highlighter.start_state();
+                    new_state = highlighter.start_state();
+                    Vec::new()
+                }
+                DiffLineKind::Removed => highlighter.highlight_line(&line.text, &mut old_state),
+                DiffLineKind::Added => highlighter.highlight_line(&line.text, &mut new_state),
+                DiffLineKind::Context => {
+                    highlighter.highlight_line(&line.text, &mut old_state);
+                    highlighter.highlight_line(&line.text, &mut new_state)
+                }
+            })
+            .collect();
+        self
+    }
+}
+
+impl DiffView {
+    pub fn lines(&self) -> &[DiffLine] {
+        &self.lines
+    }
+
+    /// number of rows needed to show the whole diff
+    pub fn height(&self) -> usize {
+        self.rows().len()
+    }
+
+    /// the lines to show, unchanged runs longer than twice the context are folded
+    fn visible(&self) -> Vec<Row> {
+        let mut rows = Vec::new();
+        let mut index = 0;
+        while index < self.lines.len() {
+            if self.lines[index].kind != DiffLineKind::Context {
+                rows.push(Row::Line(index));
+                index += 1;
+                continue;
+            }
+
+            let run_len = self.lines[index..]
+                .iter()
+                .take_while(|line| line.kind == DiffLineKind::Context)
+                .count();
+            let run_end = index + run_len;
+            match self.context {
+                Some(context) if run_len > 2 * context + 1 => {
+                    rows.extend((index..index + context).map(Row::Line));
+                    rows.push(Row::Fold(run_len - 2 * context));
+                    rows.extend((run_end - context..run_end).map(Row::Line));
+                }
+                _ => rows.extend((index..run_end).map(Row::Line)),
+            }
+            index = run_end;
+        }
+        rows
+    }
+
+    fn rows(&self) -> Vec<Row> {
+        let visible = self.visible();
+        if self.mode == DiffMode::Inline {
+            return visible;
+        }
+
+        // pair the removed lines with the following added lines
+        let mut rows = Vec::new();
+        let mut removed = Vec::new();
+        let mut added = Vec::new();
+
+        for row in visible {
+            match row {
+                Row::Line(index) if self.lines[index].kind == DiffLineKind::Removed => {
+                    if !added.is_empty() {
+                        flush(&mut rows, &mut removed, &mut added);
+                    }
+                    removed.push(index);
+                }
+                Row::Line(index) if self.lines[index].kind == DiffLineKind::Added => {
+                    added.push(index)
+                }
+                Row::Line(index) if self.lines[index].kind == DiffLineKind::Context => {
+                    flush(&mut rows, &mut removed, &mut added);
+                    rows.push(Row::Pair(Some(index), Some(index)));
+                }
+                row => {
+                    flush(&mut rows, &mut removed, &mut added);
+                    rows.push(row);
+                }
+            }
+        }
+        flush(&mut rows, &mut removed, &mut added);
+        rows
+    }
+
+    fn line_attr(&self, line: &DiffLine) -> Attr {
+        match line.kind {
+            DiffLineKind::Header => self.header_attr,
+            DiffLineKind::Hunk => self.hunk_attr,
+            DiffLineKind::Added => self.added_attr,
+            DiffLineKind::Removed => self.removed_attr,
+            DiffLineKind::Context => Attr::default(),
+        }
+    }
+
+    fn draw_line(&self, canvas: &mut dyn Canvas, row: usize, index: usize) -> Result<()> {
+        let line = &self.lines[index];
+        let attr = self.line_attr(line);
+        let sign_width = canvas.print_with_attr(row, 0, line.sign(), attr)?;
+        let syntax = self.syntax.get(index).map_or(&[][..], Vec::as_slice);
+        let spans = overlay(syntax, line.changed.as_ref(), self.changed_attr);
+        print_highlighted(canvas, row, sign_width, &line.text, &spans, attr)?;
+        Ok(())
+    }
+}
+
+impl Draw for DiffView {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
+        let (width, height) = canvas.size()?;
+        let left_width = width.saturating_sub(1) / 2;
+        let right_start = left_width + 1;
+
+        for (row, content) in self
+            .rows()
+            .iter()
+            .skip(self.offset)
+            .take(height)
+            .enumerate()
+        {
+            match content {
+                Row::Line(index) => self.draw_line(canvas, row, *index)?,
+                Row::Fold(count) => {
+                    let text = locale::text(&Message::UnchangedLines(*count));
+                    canvas.print_with_attr(row, 0, &text, self.fold_attr)?;
+                }
+                Row::Pair(old, new) => {
+                    if let Some(old) = old {
+                        let mut left = BoundedCanvas::new(0, 0, left_width, height, canvas);
+                        self.draw_line(&mut left, row, *old)?;
+                    }
+                    canvas.print_with_attr(row, left_width, "│", self.fold_attr)?;
+                    if let Some(new) = new {
+                        let mut right = BoundedCanvas::new(
+                            0,
+                            right_start,
+                            width.saturating_sub(right_start),
+                            height,
+                            canvas,
+                        );
+                        self.draw_line(&mut right, row, *new)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn size_hint(&self) -> (Option<usize>, Option<usize>) {
+        (None, Some(self.height()))
+    }
+}
+
+/// move the pending removed/added lines into side by side rows
+fn flush(rows: &mut Vec<Row>, removed: &mut Vec<usize>, added: &mut Vec<usize>) {
+    let len = removed.len().max(added.len());
+    let mut removed = removed.drain(..);
+    let mut added = added.drain(..);
+    for _ in 0..len {
+        rows.push(Row::Pair(removed.next(), added.next()));
+    }
+}
+
+/// the syntax `spans` with the `changed` part on top, ordered and non-overlapping
+fn overlay(spans: &[Span], changed: Option<&Range<usize>>, changed_attr: Attr) -> Vec<Span> {
+    let changed = match changed {
+        Some(changed) => changed,
+        None => return spans.to_vec(),
+    };
+
+    let mut bounds: Vec<usize> = spans
+        .iter()
+        .flat_map(|span| vec![span.range.start, span.range.end])
+        .chain(vec![changed.start, changed.end])
+        .collect();
+    bounds.sort_unstable();
+    bounds.dedup();
+
+    let mut overlaid = Vec::new();
+    for part in bounds.windows(2) {
+        let (start, end) = (part[0], part[1]);
+        let syntax = spans
+            .iter()
+            .find(|span| span.range.start <= start && end <= span.range.end)
+            .map(|span| span.attr);
+        let in_changed = changed.start <= start && end <= changed.end;
+        let attr = match (syntax, in_changed) {
+            (Some(attr), true) => attr.extend(changed_attr),
+            (Some(attr), false) => attr,
+            (None, true) => changed_attr,
+            (None, false) => continue,
+        };
+        overlaid.push(Span::new(start..end, attr));
+    }
+    overlaid
+}
+
+/// line diff based on the longest common subsequence, removed lines come before the added
+/// lines in each change
+fn diff_lines(old: &[&str], new: &[&str]) -> Vec<DiffLine> {
+    // lcs[i][j]: length of the LCS of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(DiffLine::new(DiffLineKind::Context, old[i]));
+            i += 1;
+            j += 1;
+        } else if j >= new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(DiffLine::new(DiffLineKind::Removed, old[i]));
+            i += 1;
+        } else {
+            lines.push(DiffLine::new(DiffLineKind::Added, new[j]));
+            j += 1;
+        }
+    }
+    lines
+}
+
+/// pair the removed lines with the following added lines and mark their changed parts
+fn mark_changes(lines: &mut [DiffLine]) {
+    let mut index = 0;
+    while index < lines.len() {
+        let removed = lines[index..]
+            .iter()
+            .take_while(|line| line.kind == DiffLineKind::Removed)
+            .count();
+        let added = lines[index + removed..]
+            .iter()
+            .take_while(|line| line.kind == DiffLineKind::Added)
+            .count();
+
+        for pair in 0..removed.min(added) {
+            let (old, new) = (index + pair, index + removed + pair);
+            let (old_changed, new_changed) = changed_ranges(&lines[old].text, &lines[new].text);
+            lines[old].changed = Some(old_changed);
+            lines[new].changed = Some(new_changed);
+        }
+        index += (removed + added).max(1);
+    }
+}
+
+/// the byte ranges of `old` and `new` after stripping the common prefix and suffix
+fn changed_ranges(old: &str, new: &str) -> (Range<usize>, Range<usize>) {
+    let prefix: usize = old
+        .chars()
+        .zip(new.chars())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum();
+    let suffix: usize = old[prefix..]
+        .chars()
+        .rev()
+        .zip(new[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum();
+    (prefix..old.len() - suffix, prefix..new.len() - suffix)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::screen::Screen;
+
+    fn kinds(view: &DiffView) -> Vec<DiffLineKind> {
+        view.lines().iter().map(|line| line.kind).collect()
+    }
+
+    fn row_text(screen: &Screen, row: usize) -> String {
+        screen
+            .iter_cell()
+            .filter(|(r, _, _)| *r == row)
+            .map(|(_, _, cell)| cell.ch)
+            .collect::<String>()
+            .replace('\0', " ")
+            .trim_end()
+            .to_string()
+    }
+
+    #[test]
+    fn parse_unified_diff() {
+        let view = DiffView::from_unified(
+            "--- a/f\n+++ b/f\n@@ -1,2 +1,2 @@\n same\n-let x = 1;\n+let x = 2;\n",
+        );
+        use DiffLineKind::*;
+        assert_eq!(
+            vec![Header, Header, Hunk, Context, Removed, Added],
+            kinds(&view)
+        );
+        assert_eq!(Some(8..9), view.lines()[4].changed);
+        assert_eq!(Some(8..9), view.lines()[5].changed);
+    }
+
+    #[test]
+    fn diff_texts_should_put_removed_first() {
+        let view = DiffView::from_texts("a\nb\nc", "a\nx\ny\nc");
+        use DiffLineKind::*;
+        assert_eq!(vec![Context, Removed, Added, Added, Context], kinds(&view));
+    }
+
+    #[test]
+    fn unchanged_lines_should_be_folded() {
+        let old: Vec<String> = (0..10).map(|n| n.to_string()).collect();
+        let mut new = old.clone();
+        new[9] = "nine".to_string();
+        let view = DiffView::from_texts(&old.join("\n"), &new.join("\n")).context(Some(1));
+
+        // 0, fold(7), 8, -9, +nine
+        assert_eq!(5, view.height());
+        assert_eq!(11, view.context(None).height());
+    }
+
+    #[test]
+    fn draw_side_by_side() {
+        let view = DiffView::from_texts("a\nb", "a\nB\nc").mode(DiffMode::SideBySide);
+        let mut screen = Screen::new(9, 3);
+        view.draw(&mut screen).unwrap();
+        assert_eq!(" a  │ a", row_text(&screen, 0));
+        assert_eq!("-b  │+B", row_text(&screen, 1));
+        assert_eq!("    │+c", row_text(&screen, 2));
+    }
+
+    #[test]
+    fn highlight_syntax_under_changes() {
+        use crate::highlight::KeywordHighlighter;
+
+        let highlighter = KeywordHighlighter::new()
+            .keyword("let", Color::BLUE)
+            .block_comment("/*", "*/", Color::LIGHT_BLACK);
+        let view = DiffView::from_texts("let a\n/*\n*/ let b", "let x\n*/ let b")
+            .changed_attr(Effect::BOLD)
+            .highlighter(highlighter);
+        let mut screen = Screen::new(12, 4);
+        view.draw(&mut screen).unwrap();
+
+        let attr_at = |row: usize, col: usize| {
+            screen
+                .iter_cell()
+                .find(|&(r, c, _)| (r, c) == (row, col))
+                .map(|(_, _, cell)| cell.attr)
+                .unwrap()
+        };
+        assert_eq!("-let a", row_text(&screen, 0));
+        let changed = attr_at(0, 5);
+        assert_eq!((Color::RED, Effect::BOLD), (changed.fg, changed.effect));
+        assert_eq!(Color::LIGHT_BLACK, attr_at(1, 1).fg);
+        assert_eq!("+let x", row_text(&screen, 2));
+        let keyword = attr_at(2, 1);
+        assert_eq!((Color::BLUE, Effect::empty()), (keyword.fg, keyword.effect));
+        let changed = attr_at(2, 5);
+        assert_eq!((Color::GREEN, Effect::BOLD), (changed.fg, changed.effect));
+        // the comment is only opened in the old text
+        assert_eq!(" */ let b", row_text(&screen, 3));
+        assert_eq!(Color::Default, attr_at(3, 1).fg);
+        assert_eq!(Color::BLUE, attr_at(3, 4).fg);
+    }
+}
@@ -0,0 +1,448 @@
+use crate::attr::{Attr, Color, Effect};
+use crate::canvas::{Canvas, Result};
+use crate::draw::Draw;
+use crate::key::Key;
+use crate::locale::{self, Message};
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::fmt::Write;
+
+const INDENT: usize = 2;
+const ROOT: &str = "$";
+
+/// A tree of data, e.g. a parsed JSON document
+#[derive(Debug, Clone, PartialEq)]
+pub enum DataNode {
+    Null,
+    Bool(bool),
+    /// numbers are kept as their text representation
+    Number(String),
+    String(String),
+    Array(Vec<DataNode>),
+    /// the entries are kept in order
+    Object(Vec<(String, DataNode)>),
+}
+
+impl DataNode {
+    fn children(&self) -> Vec<(String, &DataNode)> {
+        match self {
+            DataNode::Array(items) => items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| (format!("[{}]", index), item))
+                .collect(),
+            DataNode::Object(entries) => entries
+                .iter()
+                .map(|(key, value)| (key_segment(key), value))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn is_container(&self) -> bool {
+        matches!(self, DataNode::Array(_) | DataNode::Object(_))
+    }
+
+    /// one line description of the value
+    fn summary(&self) -> String {
+        match self {
+            DataNode::Null => "null".to_string(),
+            DataNode::Bool(value) => value.to_string(),
+            DataNode::Number(number) => number.clone(),
+            DataNode::String(string) => format!("{:?}", string),
+            DataNode::Array(items) => locale::text(&Message::ArrayItems(items.len())),
+            DataNode::Object(entries) => locale::text(&Message::ObjectKeys(entries.len())),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<&serde_json::Value> for DataNode {
+    fn from(value: &serde_json::Value) -> Self {
+        use serde_json::Value;
+        match value {
+            Value::Null => DataNode::Null,
+            Value::Bool(value) => DataNode::Bool(*value),
+            Value::Number(number) => DataNode::Number(number.to_string()),
+            Value::String(string) => DataNode::String(string.clone()),
+            Value::Array(items) => DataNode::Array(items.iter().map(DataNode::from).collect()),
+            Value::Object(entries) => DataNode::Object(
+                entries
+                    .iter()
+                    .map(|(key, value)| (key.clone(), DataNode::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Value> for DataNode {
+    fn from(value: serde_json::Value) -> Self {
+        DataNode::from(&value)
+    }
+}
+
+/// the path segment of an object key: `.key`, or `["key"]` if it is not an identifier
+fn key_segment(key: &str) -> String {
+    let is_identifier = !key.is_empty()
+        && !key.starts_with(|ch: char| ch.is_ascii_digit())
+        && key.chars().all(|ch| ch.is_alphanumeric() || ch == '_');
+    if is_identifier {
+        format!(".{}", key)
+    } else {
+        format!("[{:?}]", key)
+    }
+}
+
+struct Row<'a> {
+    depth: usize,
+    path: String,
+    label: String,
+    node: &'a DataNode,
+}
+
+/// A widget to explore tree data (e.g. JSON) with expand/collapse, type-based coloring,
+/// search and path copy.
+///
+/// Keys (via `on_key`):
+/// - `Up`/`Down`/`k`/`j` move the cursor
+/// - `Right`/`l` expands, `Left`/`h` collapses or moves to the parent
+/// - `Enter`/` ` toggles
+///
+/// ```
+/// use tuikit::widget::{DataInspector, DataNode};
+///
+/// let data = DataNode::Object(vec![(
+///     "users".to_string(),
+///     DataNode::Array(vec![DataNode::String("alice".to_string())]),
+/// )]);
+/// let mut inspector = DataInspector::new(data);
+/// assert!(inspector.search("alice"));
+/// assert_eq!("$.users[0]", inspector.selected_path());
+/// ```
+pub struct DataInspector {
+    root: DataNode,
+    expanded: HashSet<String>,
+    cursor: usize,
+    offset: Cell<usize>,
+
+    key_attr: Attr,
+    string_attr: Attr,
+    number_attr: Attr,
+    literal_attr: Attr,
+    summary_attr: Attr,
+    cursor_attr: Attr,
+}
+
+// Builder
+impl DataInspector {
+    /// create an inspector with the root expanded
+    pub fn new(root: impl Into<DataNode>) -> Self {
+        let mut expanded = HashSet::new();
+        expanded.insert(ROOT.to_string());
+        Self {
+            root: root.into(),
+            expanded,
+            cursor: 0,
+            offset: Cell::new(0),
+            key_attr: Attr::from(Color::BLUE),
+            string_attr: Attr::from(Color::GREEN),
+            number_attr: Attr::from(Color::YELLOW),
+            literal_attr: Attr::from(Color::MAGENTA),
+            summary_attr: Attr::from(Color::LIGHT_BLACK),
+            cursor_attr: Attr::from(Effect::REVERSE),
+        }
+    }
+
+    pub fn key_attr(mut self, attr: impl Into<Attr>) -> Self {
+        self.key_attr = attr.into();
+        self
+    }
+
+    pub fn cursor_attr(mut self, attr: impl Into<Attr>) -> Self {
+        self.cursor_attr = attr.into();
+        self
+    }
+}
+
+impl DataInspector {
+    /// the visible rows in display order
+    fn rows(&self) -> Vec<Row<'_>> {
+        let mut rows = Vec::new();
+        self.collect_rows(&mut rows, ROOT.to_string(), ROOT.to_string(), &self.root, 0);
+        rows
+    }
+
+    fn collect_rows<'a>(
+        &self,
+        rows: &mut Vec<Row<'a>>,
+        path: String,
+        label: String,
+        node: &'a DataNode,
+        depth: usize,
+    ) {
+        let expanded = self.expanded.contains(&path);
+        rows.push(Row {
+            depth,
+            path: path.clone(),
+            label,
+            node,
+        });
+
+        if expanded {
+            for (segment, child) in node.children() {
+                let label = segment.trim_start_matches('.').to_string();
+                self.collect_rows(
+                    rows,
+                    format!("{}{}", path, segment),
+                    label,
+                    child,
+                    depth + 1,
+                );
+            }
+        }
+    }
+
+    /// the path (e.g. `$.users[0].name`) of the node under the cursor, for copying
+    pub fn selected_path(&self) -> String {
+        self.rows()
+            .get(self.cursor)
+            .map(|row| row.path.clone())
+            .unwrap_or_else(|| ROOT.to_string())
+    }
+
+    pub fn selected(&self) -> Option<&DataNode> {
+        self.rows().get(self.cursor).map(|row| row.node)
+    }
+
+    fn set_expanded(&mut self, expand: bool) -> bool {
+        let rows = self.rows();
+        let row = match rows.get(self.cursor) {
+            Some(row) if row.node.is_container() => row,
+            _ => return false,
+        };
+        let path = row.path.clone();
+        if expand {
+            self.expanded.insert(path)
+        } else {
+            self.expanded.remove(&path)
+        }
+    }
+
+    fn move_to_parent(&mut self) -> bool {
+        let rows = self.rows();
+        let depth = match rows.get(self.cursor) {
+            Some(row) if row.depth > 0 => row.depth,
+            _ => return false,
+        };
+        if let Some(parent) = (0..self.cursor).rev().find(|&i| rows[i].depth < depth) {
+            self.cursor = parent;
+        }
+        true
+    }
+
+    /// Handle the key, return `true` if the view changed
+    pub fn on_key(&mut self, key: &Key) -> bool {
+        let len = self.rows().len();
+        match key {
+            Key::Up | Key::Char('k') if self.cursor > 0 => self.cursor -= 1,
+            Key::Down | Key::Char('j') if self.cursor + 1 < len => self.cursor += 1,
+            Key::Right | Key::Char('l') => return self.set_expanded(true),
+            Key::Left | Key::Char('h') => {
+                return self.set_expanded(false) || self.move_to_parent();
+            }
+            Key::Enter | Key::Char(' ') => {
+                return self.set_expanded(true) || self.set_expanded(false);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// Move the cursor to the next node (after the cursor, wrapping around) whose key or value
+    /// contains `query` (case insensitive), expanding the collapsed nodes on the way.
+    /// Return `false` if nothing matches.
+    pub fn search(&mut self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        let mut matches = Vec::new();
+        collect_matches(&self.root, ROOT, &query, &mut matches);
+        if matches.is_empty() {
+            return false;
+        }
+
+        let current = self.selected_path();
+        let order: Vec<String> = {
+            let mut all = Vec::new();
+            collect_paths(&self.root, ROOT, &mut all);
+            all
+        };
+        let current_index = order.iter().position(|path| *path == current).unwrap_or(0);
+        let index_of = |path: &String| order.iter().position(|p| p == path).unwrap_or(0);
+        let target = matches
+            .iter()
+            .find(|path| index_of(path) > current_index)
+            .unwrap_or(&matches[0])
+            .clone();
+
+        // expand the ancestors of the target
+        for ancestor in order.iter().filter(|path| is_ancestor(path, &target)) {
+            self.expanded.insert(ancestor.clone());
+        }
+        self.cursor = self
+            .rows()
+            .iter()
+            .position(|row| row.path == target)
+            .unwrap_or(0);
+        true
+    }
+
+    fn value_attr(&self, node: &DataNode) -> Attr {
+        match node {
+            DataNode::String(_) => self.string_attr,
+            DataNode::Number(_) => self.number_attr,
+            DataNode::Null | DataNode::Bool(_) => self.literal_attr,
+            DataNode::Array(_) | DataNode::Object(_) => self.summary_attr,
+        }
+    }
+}
+
+/// whether `path` is a proper ancestor of `descendant`
+fn is_ancestor(path: &str, descendant: &str) -> bool {
+    descendant.len() > path.len()
+        && descendant.starts_with(path)
+        && descendant[path.len()..].starts_with(['.', '['])
+}
+
+/// all the paths in display order, as if every node was expanded
+fn collect_paths(node: &DataNode, path: &str, paths: &mut Vec<String>) {
+    paths.push(path.to_string());
+    for (segment, child) in node.children() {
+        collect_paths(child, &format!("{}{}", path, segment), paths);
+    }
+}
+
+fn collect_matches(node: &DataNode, path: &str, query: &str, matches: &mut Vec<String>) {
+    let mut text = path.rsplit(['.', '[']).next().unwrap_or("").to_lowercase();
+    if !node.is_container() {
+        let _ = write!(text, " {}", node.summary().to_lowercase());
+    }
+    if path != ROOT && text.contains(query) {
+        matches.push(path.to_string());
+    }
+    for (segment, child) in node.children() {
+        collect_matches(child, &format!("{}{}", path, segment), query, matches);
+    }
+}
+
+impl Draw for DataInspector {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
+        let (_, height) = canvas.size()?;
+        let rows = self.rows();
+
+        // keep the cursor visible
+        let mut offset = self.offset.get();
+        if self.cursor < offset {
+            offset = self.cursor;
+        } else if height > 0 && self.cursor >= offset + height {
+            offset = self.cursor + 1 - height;
+        }
+        self.offset.set(offset);
+
+        for (index, row) in rows.iter().enumerate().skip(offset).take(height) {
+            let line = index - offset;
+            let extra = if index == self.cursor {
+                self.cursor_attr
+            } else {
+                Attr::default()
+            };
+
+            let mut col = row.depth * INDENT;
+            let marker = match (row.node.is_container(), self.expanded.contains(&row.path)) {
+                (false, _) => "  ",
+                (true, true) => "▾ ",
+                (true, false) => "▸ ",
+            };
+            col += canvas.print_with_attr(line, col, marker, extra)?;
+            col += canvas.print_with_attr(line, col, &row.label, self.key_attr.extend(extra))?;
+            col += canvas.print_with_attr(line, col, ": ", extra)?;
+            let value_attr = self.value_attr(row.node).extend(extra);
+            canvas.print_with_attr(line, col, &row.node.summary(), value_attr)?;
+        }
+        Ok(())
+    }
+
+    fn size_hint(&self) -> (Option<usize>, Option<usize>) {
+        (None, Some(self.rows().len()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sample() -> DataNode {
+        DataNode::Object(vec![
+            ("name".to_string(), DataNode::String("tuikit".to_string())),
+            (
+                "keywords".to_string(),
+                DataNode::Array(vec![
+                    DataNode::String("tui".to_string()),
+                    DataNode::String("terminal".to_string()),
+                ]),
+            ),
+            ("odd key".to_string(), DataNode::Null),
+        ])
+    }
+
+    #[test]
+    fn expand_and_collapse() {
+        let mut inspector = DataInspector::new(sample());
+        assert_eq!(4, inspector.rows().len());
+
+        inspector.on_key(&Key::Down);
+        inspector.on_key(&Key::Down);
+        assert_eq!("$.keywords", inspector.selected_path());
+        assert!(inspector.on_key(&Key::Right));
+        assert_eq!(6, inspector.rows().len());
+
+        inspector.on_key(&Key::Down);
+        assert_eq!("$.keywords[0]", inspector.selected_path());
+        assert!(inspector.on_key(&Key::Left));
+        assert_eq!("$.keywords", inspector.selected_path());
+        assert!(inspector.on_key(&Key::Left));
+        assert_eq!(4, inspector.rows().len());
+
+        inspector.on_key(&Key::Down);
+        assert_eq!("$[\"odd key\"]", inspector.selected_path());
+    }
+
+    #[test]
+    fn search_should_expand_and_wrap() {
+        let mut inspector = DataInspector::new(sample());
+        inspector.expanded.clear();
+
+        assert!(inspector.search("TERM"));
+        assert_eq!("$.keywords[1]", inspector.selected_path());
+        assert!(inspector.search("t"));
+        assert_eq!("$.name", inspector.selected_path());
+        assert!(!inspector.search("nothing"));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn from_json() {
+        let value: serde_json::Value = serde_json::from_str(r#"{"a": [1, true]}"#).unwrap();
+        let node = DataNode::from(value);
+        assert_eq!(
+            DataNode::Object(vec![(
+                "a".to_string(),
+                DataNode::Array(vec![
+                    DataNode::Number("1".to_string()),
+                    DataNode::Bool(true)
+                ])
+            )]),
+            node
+        );
+    }
+}
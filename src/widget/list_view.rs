@@ -0,0 +1,591 @@
+use crate::attr::{Attr, Color, Effect};
+use crate::canvas::{Canvas, Result};
+use crate::draw::Draw;
+use crate::highlight::print_highlighted;
+use crate::key::{Key, MouseButton};
+use crate::locale;
+use crate::momentum::MomentumScroll;
+use crate::style::{Styled, Styles};
+use crate::text::AttrText;
+use std::cell::Cell;
+use std::time::Instant;
+
+/// the lower half of the first item shown, once scrolled by half a row
+const UPPER_HALF_BLOCK: &str = "▀";
+
+/// Events emitted by `ListView::on_key`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListEvent {
+    /// the item at this index was selected
+    Selected(usize),
+    /// the item `from` was dragged and dropped at `to`, the items are already reordered
+    ItemMoved { from: usize, to: usize },
+}
+
+/// The item being dragged, and where it would be dropped
+#[derive(Debug, Clone, Copy)]
+struct Drag {
+    from: usize,
+    to: usize,
+    /// the direction the list auto-scrolls in while the pointer rests near an edge
+    scrolling: isize,
+}
+
+/// A scrollable list of lines, one selected, reordered by dragging the items with the mouse.
+///
+/// The item pressed with the left button follows the pointer, the list scrolls while it is
+/// held near the top or bottom edge, and the drop emits `ListEvent::ItemMoved`. The motion is
+/// only reported with the drag tracking on, see `Term::enable_mouse_drag_support`. Motion
+/// stops once the pointer rests: call `tick` regularly while `is_dragging` to keep scrolling.
+///
+/// With `smooth_scroll`, the wheel scrolls by half rows: the item half scrolled off the top is
+/// drawn as the upper half blocks of its background, as long as it has a background color
+/// (e.g. the `list.item` or `list.selected` style), blank otherwise.
+///
+/// With `momentum_scroll`, the wheel notches glide to a stop instead: call `tick` on every
+/// frame while `is_scrolling`.
+///
+/// ```
+/// use tuikit::key::{Key, Modifiers, MouseButton};
+/// use tuikit::widget::{ListEvent, ListView};
+///
+/// let mut list = ListView::new(vec!["one".into(), "two".into(), "three".into()]);
+/// list.set_size(10, 3); // normally recorded by `draw`
+/// list.on_key(&Key::MousePress(MouseButton::Left, 1, 1, Modifiers::empty()));
+/// list.on_key(&Key::MouseHold(1, 2, Modifiers::empty()));
+/// assert_eq!(
+///     Some(ListEvent::ItemMoved { from: 0, to: 1 }),
+///     list.on_key(&Key::MouseRelease(MouseButton::Left, 1, 2, Modifiers::empty()))
+/// );
+/// assert_eq!("one", list.items()[1].text);
+/// ```
+pub struct ListView {
+    items: Vec<AttrText>,
+    selected: usize,
+    /// the first item shown
+    offset: Cell<usize>,
+    /// whether the first item shown is half scrolled off, see `smooth_scroll`
+    half: Cell<bool>,
+    smooth: bool,
+    /// the size (width, height) the list was drawn with
+    size: Cell<(usize, usize)>,
+    origin: (usize, usize),
+    scroll_margin: usize,
+    drag: Option<Drag>,
+    momentum: Option<MomentumScroll>,
+    styles: Styles,
+}
+
+// Builder
+impl ListView {
+    pub fn new(items: Vec<AttrText>) -> Self {
+        Self {
+            items,
+            selected: 0,
+            offset: Cell::new(0),
+            half: Cell::new(false),
+            smooth: false,
+            size: Cell::new((0, 0)),
+            origin: (0, 0),
+            scroll_margin: 1,
+            drag: None,
+            momentum: None,
+            styles: Styles::new(),
+        }
+    }
+
+    /// The `(row, col)` of the list's top-left corner on the terminal.
+    /// Mouse events are translated relative to it.
+    pub fn origin(mut self, row: usize, col: usize) -> Self {
+        self.origin = (row, col);
+        self
+    }
+
+    /// the number of rows at each edge where a drag scrolls the list, 1 by default
+    pub fn scroll_margin(mut self, rows: usize) -> Self {
+        self.scroll_margin = rows;
+        self
+    }
+
+    /// Scroll by half rows with the wheel, to make fast scrolling easier to follow
+    pub fn smooth_scroll(mut self, smooth: bool) -> Self {
+        self.smooth = smooth;
+        self
+    }
+
+    /// Feed the wheel through `momentum`, its lines being the rows scrolled (half rows with
+    /// `smooth_scroll`)
+    pub fn momentum_scroll(mut self, momentum: MomentumScroll) -> Self {
+        self.momentum = Some(momentum);
+        self
+    }
+}
+
+impl Styled for ListView {
+    /// the items, the selected one and the one being dragged (on top of it)
+    fn style_slots(&self) -> &'static [&'static str] {
+        &["list.item", "list.selected", "list.dragged"]
+    }
+
+    fn styles_mut(&mut self) -> &mut Styles {
+        &mut self.styles
+    }
+}
+
+impl ListView {
+    pub fn items(&self) -> &[AttrText] {
+        &self.items
+    }
+
+    /// Replace the items, the selection is kept in range
+    pub fn set_items(&mut self, items: Vec<AttrText>) {
+        self.items = items;
+        self.drag = None;
+        self.selected = self.selected.min(self.items.len().saturating_sub(1));
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// whether the wheel momentum is still scrolling, see `momentum_scroll`
+    pub fn is_scrolling(&self) -> bool {
+        self.momentum
+            .as_ref()
+            .is_some_and(MomentumScroll::is_running)
+    }
+
+    /// the first item shown
+    pub fn offset(&self) -> usize {
+        self.offset.get()
+    }
+
+    /// whether the first item shown is half scrolled off, see `smooth_scroll`
+    pub fn is_half_scrolled(&self) -> bool {
+        self.half.get()
+    }
+
+    /// select the item `selected` with the item `offset` shown first, e.g. as left in the last
+    /// run, both kept in range
+    pub fn restore_position(&mut self, selected: usize, offset: usize) {
+        let last = self.items.len().saturating_sub(1);
+        self.selected = selected.min(last);
+        self.offset.set(offset.min(self.selected));
+        self.half.set(false);
+    }
+
+    /// record the size (width, height) the list was drawn with
+    pub fn set_size(&self, width: usize, height: usize) {
+        self.size.set((width, height));
+    }
+
+    fn height(&self) -> usize {
+        self.size.get().1
+    }
+
+    fn max_offset(&self) -> usize {
+        self.items.len().saturating_sub(self.height())
+    }
+
+    fn select(&mut self, index: usize) -> Option<ListEvent> {
+        let index = index.min(self.items.len().checked_sub(1)?);
+        self.selected = index;
+        self.scroll_to(index);
+        Some(ListEvent::Selected(index))
+    }
+
+    /// scroll so that the item `index` is in view
+    fn scroll_to(&self, index: usize) {
+        let height = self.height().max(1);
+        let offset = self.offset.get();
+        if index < offset || (index == offset && self.half.get()) {
+            self.offset.set(index);
+            self.half.set(false);
+        } else if index >= offset + height {
+            self.offset.set(index + 1 - height);
+            self.half.set(false);
+        }
+    }
+
+    /// Scroll by `delta` items (up if negative)
+    pub fn scroll(&mut self, delta: isize) {
+        let offset = self.offset.get() as isize + delta;
+        self.offset
+            .set((offset.max(0) as usize).min(self.max_offset()));
+        self.half.set(false);
+    }
+
+    /// Scroll by `delta` half rows (up if negative)
+    pub fn scroll_half_rows(&mut self, delta: isize) {
+        let position = self.offset.get() as isize * 2 + self.half.get() as isize + delta;
+        let position = (position.max(0) as usize).min(self.max_offset() * 2);
+        self.offset.set(position / 2);
+        self.half.set(position % 2 == 1);
+    }
+
+    fn wheel(&mut self, delta: isize) {
+        if self.smooth {
+            self.scroll_half_rows(delta);
+        } else {
+            self.scroll(delta);
+        }
+    }
+
+    /// Handle the key/mouse event, return `Some(event)` if the selection or the order changed.
+    ///
+    /// - `Up`/`Down` (`Ctrl-P`/`Ctrl-N`), `Home`/`End` select an item, as does a left press.
+    /// - dragging the pressed item with the left button held moves it, dropped on release.
+    /// - the wheel scrolls the list.
+    pub fn on_key(&mut self, key: &Key) -> Option<ListEvent> {
+        if let Some(momentum) = self.momentum.as_mut() {
+            if momentum.on_key(key) {
+                return None;
+            }
+        }
+
+        match key {
+            Key::Up | Key::Ctrl('p') => self.select(self.selected.saturating_sub(1)),
+            Key::Down | Key::Ctrl('n') => self.select(self.selected + 1),
+            Key::Home => self.select(0),
+            Key::End => self.select(usize::MAX),
+            Key::MousePress(MouseButton::WheelUp, ..) => {
+                self.wheel(-1);
+                None
+            }
+            Key::MousePress(MouseButton::WheelDown, ..) => {
+                self.wheel(1);
+                None
+            }
+            Key::MousePress(MouseButton::Left, x, y, _) => {
+                if let Some(momentum) = self.momentum.as_mut() {
+                    momentum.stop();
+                }
+                let index = self.index_at(*x, *y)?;
+                self.drag = Some(Drag {
+                    from: index,
+                    to: index,
+                    scrolling: 0,
+                });
+                self.select(index)
+            }
+            Key::MouseHold(_, y, _) if self.drag.is_some() => {
+                self.drag_to(*y);
+                None
+            }
+            Key::MouseRelease(MouseButton::Left, ..) => self.drop_item(),
+            _ => None,
+        }
+    }
+
+    /// Scroll on while the dragged item is held near an edge or the wheel momentum lasts,
+    /// return whether it did, i.e. the list should be redrawn
+    pub fn tick(&mut self) -> bool {
+        self.tick_at(Instant::now())
+    }
+
+    fn tick_at(&mut self, now: Instant) -> bool {
+        let lines = match self.momentum.as_mut() {
+            Some(momentum) if self.drag.is_none() => momentum.tick(now),
+            _ => 0,
+        };
+        if lines != 0 {
+            let position = (self.offset.get(), self.half.get());
+            self.wheel(lines as isize);
+            return position != (self.offset.get(), self.half.get());
+        }
+
+        let drag = match self.drag {
+            Some(drag) if drag.scrolling != 0 => drag,
+            _ => return false,
+        };
+        let offset = self.offset.get();
+        self.scroll(drag.scrolling);
+        let scrolled = self.offset.get() as isize - offset as isize;
+        let to = (drag.to as isize + scrolled) as usize;
+        self.drag = Some(Drag { to, ..drag });
+        scrolled != 0
+    }
+
+    /// the (1-based) mouse row relative to the top of the list, negative above it
+    fn relative_row(&self, y: u16) -> isize {
+        y as isize - 1 - self.origin.0 as isize
+    }
+
+    fn index_at(&self, x: u16, y: u16) -> Option<usize> {
+        let row = self.relative_row(y);
+        let col = (x as usize).checked_sub(1 + self.origin.1)?;
+        let (width, height) = self.size.get();
+        if row < 0 || row as usize >= height || col >= width {
+            return None;
+        }
+        let index = self.offset.get() + row as usize;
+        if index < self.items.len() {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    fn drag_to(&mut self, y: u16) {
+        let drag = match self.drag {
+            Some(drag) => drag,
+            None => return,
+        };
+        let height = self.height() as isize;
+        let margin = (self.scroll_margin as isize).min(height / 2);
+        let row = self.relative_row(y);
+        let scrolling = if row < margin {
+            -1
+        } else if row >= height - margin {
+            1
+        } else {
+            0
+        };
+        if scrolling != 0 {
+            self.scroll(scrolling);
+        }
+
+        let row = row.clamp(0, (height - 1).max(0));
+        let last = self.items.len().saturating_sub(1);
+        let to = (self.offset.get() + row as usize).min(last);
+        self.drag = Some(Drag {
+            to,
+            scrolling,
+            ..drag
+        });
+    }
+
+    fn drop_item(&mut self) -> Option<ListEvent> {
+        let Drag { from, to, .. } = self.drag.take()?;
+        if from == to {
+            return None;
+        }
+        let item = self.items.remove(from);
+        self.items.insert(to, item);
+        self.selected = to;
+        Some(ListEvent::ItemMoved { from, to })
+    }
+}
+
+impl Draw for ListView {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
+        let (width, height) = canvas.size()?;
+        self.set_size(width, height);
+        if height == 0 {
+            return Ok(());
+        }
+
+        // the dragged item is shown where it would be dropped
+        let mut order: Vec<usize> = (0..self.items.len()).collect();
+        let focus = match self.drag {
+            Some(Drag { from, to, .. }) => {
+                let item = order.remove(from);
+                order.insert(to, item);
+                Some(to)
+            }
+            None => None,
+        };
+        if self.offset.get() >= self.max_offset() {
+            self.offset.set(self.max_offset());
+            self.half.set(false);
+        }
+
+        let item_attr = self.styles.resolve("list.item", Attr::default());
+        let selected_attr = item_attr.extend(
+            self.styles
+                .resolve("list.selected", Attr::from(Effect::REVERSE)),
+        );
+        let dragged_attr = selected_attr.extend(
+            self.styles
+                .resolve("list.dragged", Attr::from(Effect::BOLD)),
+        );
+        let direction = locale::direction();
+        let offset = self.offset.get();
+        for (row, &index) in order.iter().enumerate().skip(offset).take(height) {
+            let attr = match focus {
+                Some(focus) if row == focus => dragged_attr,
+                None if index == self.selected => selected_attr,
+                _ => item_attr,
+            };
+            let row = row - offset;
+            if row == 0 && self.half.get() {
+                print_lower_half(canvas, width, attr)?;
+                continue;
+            }
+            let line = &self.items[index];
+            if attr != Attr::default() {
+                for col in 0..width {
+                    canvas.print_with_attr(row, col, " ", attr)?;
+                }
+            }
+            let col = locale::align(&line.text, width, direction);
+            print_highlighted(canvas, row, col, &line.text, &line.spans, attr)?;
+        }
+        Ok(())
+    }
+}
+
+/// the lower half of a row drawn with `attr`, shown on the top row: upper half blocks of its
+/// background color, blank if it has none
+fn print_lower_half(canvas: &mut dyn Canvas, width: usize, attr: Attr) -> Result<()> {
+    let background = if attr.effect.contains(Effect::REVERSE) {
+        attr.fg
+    } else {
+        attr.bg
+    };
+    if background == Color::Default {
+        return Ok(());
+    }
+    let half = Attr::from(background);
+    for col in 0..width {
+        canvas.print_with_attr(0, col, UPPER_HALF_BLOCK, half)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::key::Modifiers;
+    use crate::screen::Screen;
+    use std::time::Duration;
+
+    fn list(count: usize) -> ListView {
+        ListView::new(
+            (0..count)
+                .map(|n| AttrText::from(format!("item {}", n).as_str()))
+                .collect(),
+        )
+    }
+
+    fn texts(list: &ListView) -> Vec<&str> {
+        list.items().iter().map(|item| item.text.as_str()).collect()
+    }
+
+    fn rows(list: &ListView, height: usize) -> Vec<String> {
+        let mut screen = Screen::new(10, height);
+        list.draw(&mut screen).unwrap();
+        let cells: Vec<char> = screen.iter_cell().map(|(_, _, cell)| cell.ch).collect();
+        cells
+            .chunks(10)
+            .map(|row| row.iter().collect::<String>().trim_end().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn drag_and_drop() {
+        let mut list = list(3).origin(1, 0);
+        assert_eq!(vec!["item 0", "item 1", "item 2"], rows(&list, 3));
+
+        // rows are offset by the origin, the item follows the pointer until dropped
+        let press = Key::MousePress(MouseButton::Left, 1, 4, Modifiers::empty());
+        assert_eq!(Some(ListEvent::Selected(2)), list.on_key(&press));
+        list.on_key(&Key::MouseHold(1, 2, Modifiers::empty()));
+        assert_eq!(vec!["item 2", "item 0", "item 1"], rows(&list, 3));
+        assert_eq!(vec!["item 0", "item 1", "item 2"], texts(&list));
+
+        let release = Key::MouseRelease(MouseButton::Left, 1, 2, Modifiers::empty());
+        assert_eq!(
+            Some(ListEvent::ItemMoved { from: 2, to: 0 }),
+            list.on_key(&release)
+        );
+        assert_eq!(vec!["item 2", "item 0", "item 1"], texts(&list));
+        assert_eq!(0, list.selected());
+
+        // dropped where picked up: nothing moved
+        let press = Key::MousePress(MouseButton::Left, 1, 3, Modifiers::empty());
+        list.on_key(&press);
+        assert_eq!(None, list.on_key(&release));
+    }
+
+    #[test]
+    fn auto_scroll_near_edges() {
+        let mut list = list(10);
+        rows(&list, 4);
+        let press = Key::MousePress(MouseButton::Left, 1, 2, Modifiers::empty());
+        list.on_key(&press);
+
+        // held on the bottom row: scrolls on each motion, then on each tick
+        list.on_key(&Key::MouseHold(1, 4, Modifiers::empty()));
+        assert_eq!(1, list.offset());
+        assert!(list.tick());
+        assert!(list.tick());
+        assert_eq!(3, list.offset());
+        assert_eq!(vec!["item 4", "item 5", "item 6", "item 1"], rows(&list, 4));
+
+        // back to the middle, it stops
+        list.on_key(&Key::MouseHold(1, 2, Modifiers::empty()));
+        assert!(!list.tick());
+        let release = Key::MouseRelease(MouseButton::Left, 1, 2, Modifiers::empty());
+        assert_eq!(
+            Some(ListEvent::ItemMoved { from: 1, to: 4 }),
+            list.on_key(&release)
+        );
+        assert!(!list.is_dragging());
+    }
+
+    #[test]
+    fn smooth_scroll_by_half_rows() {
+        let background = Attr {
+            bg: Color::BLUE,
+            ..Attr::default()
+        };
+        let mut list = list(5).smooth_scroll(true).style("list.item", background);
+        rows(&list, 2);
+        list.on_key(&Key::Down);
+
+        let wheel_down = Key::MousePress(MouseButton::WheelDown, 1, 1, Modifiers::empty());
+        list.on_key(&wheel_down);
+        assert_eq!((0, true), (list.offset(), list.is_half_scrolled()));
+        assert_eq!(vec!["▀▀▀▀▀▀▀▀▀▀", "item 1"], rows(&list, 2));
+        list.on_key(&wheel_down);
+        assert_eq!((1, false), (list.offset(), list.is_half_scrolled()));
+        assert_eq!(vec!["item 1", "item 2"], rows(&list, 2));
+
+        // no background to shade: blank
+        let mut plain = self::list(5).smooth_scroll(true);
+        rows(&plain, 2);
+        plain.on_key(&wheel_down);
+        assert_eq!(vec!["", "item 1"], rows(&plain, 2));
+        // the selection is scrolled back into view in whole rows
+        plain.on_key(&Key::Up);
+        assert_eq!((0, false), (plain.offset(), plain.is_half_scrolled()));
+    }
+
+    #[test]
+    fn momentum_scroll() {
+        let mut list = list(20).momentum_scroll(MomentumScroll::new().lines_per_notch(3.0));
+        rows(&list, 5);
+        let wheel_down = Key::MousePress(MouseButton::WheelDown, 1, 1, Modifiers::empty());
+        list.on_key(&wheel_down);
+        list.on_key(&wheel_down);
+        assert!(list.is_scrolling());
+
+        // the notches glide over the frames
+        let mut now = Instant::now();
+        let mut frames = 0;
+        while list.is_scrolling() {
+            now += Duration::from_millis(16);
+            list.tick_at(now);
+            frames += 1;
+        }
+        assert_eq!(6, list.offset());
+        assert!(frames > 1);
+
+        // a click stops it
+        list.on_key(&wheel_down);
+        list.on_key(&Key::MousePress(
+            MouseButton::Left,
+            1,
+            1,
+            Modifiers::empty(),
+        ));
+        assert!(!list.is_scrolling());
+        assert!(!list.tick_at(now + Duration::from_millis(16)));
+        assert_eq!(6, list.offset());
+    }
+}
@@ -0,0 +1,351 @@
+use crate::attr::{Attr, Effect};
+use crate::canvas::{Canvas, Result};
+use crate::draw::Draw;
+use crate::event::Event;
+use crate::highlight::print_highlighted;
+use crate::locale::{self, Message};
+use crate::plugin::EventSender;
+use crate::spinlock::SpinLock;
+use crate::style::{Styled, Styles};
+use crate::text::AttrText;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+const DEFAULT_CAPACITY: usize = 10_000;
+
+/// The lines sent by the `LogSender`s, not ingested yet
+struct Inbox {
+    lines: VecDeque<AttrText>,
+    /// the lines dropped before they were ingested
+    dropped: usize,
+    capacity: usize,
+    /// whether the event asking to ingest was sent and not handled yet
+    notified: bool,
+}
+
+type SharedInbox = Arc<SpinLock<Inbox>>;
+
+/// The payload of the `Event::App` asking a `LogPane` to ingest its inbox
+struct LogReady(SharedInbox);
+
+/// Sends lines to a `LogPane` from any thread, see `LogPane::sender`.
+///
+/// The lines wait in a buffer bounded by the capacity of the pane (dropping the oldest ones),
+/// a single event is sent until the pane ingests them: a burst of lines doesn't flood the event
+/// queue, nor the memory.
+#[derive(Clone)]
+pub struct LogSender {
+    inbox: SharedInbox,
+    events: EventSender,
+}
+
+impl LogSender {
+    pub fn send(&self, line: impl Into<AttrText>) -> Result<()> {
+        self.send_batch(Some(line.into()))
+    }
+
+    /// Send `lines` at once, fails once the term is dropped
+    pub fn send_batch(&self, lines: impl IntoIterator<Item = AttrText>) -> Result<()> {
+        let notify = {
+            let mut inbox = self.inbox.lock();
+            inbox.lines.extend(lines);
+            let excess = inbox.lines.len().saturating_sub(inbox.capacity);
+            inbox.lines.drain(..excess);
+            inbox.dropped += excess;
+            !std::mem::replace(&mut inbox.notified, true)
+        };
+        if notify {
+            self.events.send(Event::app(LogReady(self.inbox.clone())))?;
+        }
+        Ok(())
+    }
+}
+
+/// A pane of log lines for high-volume streams: at most `capacity` lines are kept, the oldest
+/// ones are dropped and counted by a "N lines dropped" marker on top.
+///
+/// It follows the tail (the newest line stays in view) until scrolled up, `set_follow_tail`
+/// toggles it. The lines come from `push`, or from other threads through a `LogSender`: pass
+/// the events to `on_event`.
+///
+/// ```
+/// use tuikit::widget::LogPane;
+///
+/// let mut pane = LogPane::new().capacity(2);
+/// pane.extend(vec!["one".into(), "two".into(), "three".into()]);
+/// assert_eq!(2, pane.len());
+/// assert_eq!(1, pane.dropped());
+/// ```
+pub struct LogPane {
+    lines: VecDeque<AttrText>,
+    capacity: usize,
+    dropped: usize,
+    follow_tail: bool,
+    /// the first line shown while not following the tail, kept by `draw` in the lines left
+    top: Cell<usize>,
+    /// the number of lines shown, as last drawn
+    page_height: Cell<usize>,
+    inbox: SharedInbox,
+    styles: Styles,
+}
+
+impl Default for LogPane {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Builder
+impl LogPane {
+    pub fn new() -> Self {
+        Self {
+            lines: VecDeque::new(),
+            capacity: DEFAULT_CAPACITY,
+            dropped: 0,
+            follow_tail: true,
+            top: Cell::new(0),
+            page_height: Cell::new(0),
+            inbox: Arc::new(SpinLock::new(Inbox {
+                lines: VecDeque::new(),
+                dropped: 0,
+                capacity: DEFAULT_CAPACITY,
+                notified: false,
+            })),
+            styles: Styles::new(),
+        }
+    }
+
+    /// the max number of lines kept, 10000 by default
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self.inbox.lock().capacity = self.capacity;
+        self.drop_oldest();
+        self
+    }
+
+    pub fn follow_tail(mut self, follow_tail: bool) -> Self {
+        self.follow_tail = follow_tail;
+        self
+    }
+}
+
+impl Styled for LogPane {
+    /// the "N lines dropped" marker
+    fn style_slots(&self) -> &'static [&'static str] {
+        &["log.dropped"]
+    }
+
+    fn styles_mut(&mut self) -> &mut Styles {
+        &mut self.styles
+    }
+}
+
+impl LogPane {
+    /// A sender of lines to this pane, to be moved to other threads. `events` is the sender of
+    /// the term the events are passed to `on_event` from, e.g. `Term::event_sender`.
+    pub fn sender(&self, events: EventSender) -> LogSender {
+        LogSender {
+            inbox: self.inbox.clone(),
+            events,
+        }
+    }
+
+    pub fn push(&mut self, line: impl Into<AttrText>) {
+        self.lines.push_back(line.into());
+        self.drop_oldest();
+    }
+
+    pub fn extend(&mut self, lines: impl IntoIterator<Item = AttrText>) {
+        self.lines.extend(lines);
+        self.drop_oldest();
+    }
+
+    /// Ingest the lines sent by the `LogSender`s if `event` asks to, return whether it did,
+    /// i.e. the pane should be redrawn
+    pub fn on_event(&mut self, event: &Event) -> bool {
+        match event {
+            Event::App(payload) => match payload.downcast_ref::<LogReady>() {
+                Some(LogReady(inbox)) if Arc::ptr_eq(inbox, &self.inbox) => {
+                    self.ingest();
+                    true
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Ingest the lines sent by the `LogSender`s, e.g. once per frame instead of on their event
+    pub fn ingest(&mut self) {
+        let (lines, dropped) = {
+            let mut inbox = self.inbox.lock();
+            inbox.notified = false;
+            let lines = std::mem::take(&mut inbox.lines);
+            (lines, std::mem::take(&mut inbox.dropped))
+        };
+        self.dropped += dropped;
+        self.extend(lines);
+    }
+
+    fn drop_oldest(&mut self) {
+        let excess = self.lines.len().saturating_sub(self.capacity);
+        self.lines.drain(..excess);
+        self.dropped += excess;
+        // the lines in view stay there
+        self.top.set(self.top.get().saturating_sub(excess));
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.dropped = 0;
+        self.top.set(0);
+    }
+
+    /// the lines kept, oldest first
+    pub fn lines(&self) -> impl Iterator<Item = &AttrText> {
+        self.lines.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// the number of lines dropped since created or cleared
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    pub fn is_following_tail(&self) -> bool {
+        self.follow_tail
+    }
+
+    pub fn set_follow_tail(&mut self, follow_tail: bool) {
+        self.follow_tail = follow_tail;
+    }
+
+    /// Scroll by `delta` lines (up if negative): scrolling up stops following the tail,
+    /// scrolling down to it follows it again
+    pub fn scroll(&mut self, delta: isize) {
+        let max_top = self.max_top(self.page_height.get());
+        let top = if self.follow_tail {
+            max_top
+        } else {
+            self.top.get()
+        };
+        let top = if delta < 0 {
+            top.saturating_sub(delta.unsigned_abs())
+        } else {
+            (top + delta as usize).min(max_top)
+        };
+        self.top.set(top);
+        self.follow_tail = top >= max_top;
+    }
+
+    /// the first line showing the tail on a page of `page_height` lines
+    fn max_top(&self, page_height: usize) -> usize {
+        self.lines.len().saturating_sub(page_height)
+    }
+}
+
+impl Draw for LogPane {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
+        let (width, height) = canvas.size()?;
+        if height == 0 {
+            return Ok(());
+        }
+        let direction = locale::direction();
+
+        let mut row = 0;
+        if self.dropped > 0 {
+            let marker_attr = self.styles.resolve("log.dropped", Attr::from(Effect::DIM));
+            let marker = locale::text(&Message::LinesDropped(self.dropped));
+            let col = locale::align(&marker, width, direction);
+            canvas.print_with_attr(row, col, &marker, marker_attr)?;
+            row += 1;
+        }
+
+        let page_height = height - row;
+        let top = if self.follow_tail {
+            self.max_top(page_height)
+        } else {
+            self.top.get().min(self.max_top(page_height))
+        };
+        self.top.set(top);
+        self.page_height.set(page_height);
+
+        for line in self.lines.iter().skip(top).take(page_height) {
+            let col = locale::align(&line.text, width, direction);
+            print_highlighted(canvas, row, col, &line.text, &line.spans, Attr::default())?;
+            row += 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::screen::Screen;
+    use std::sync::mpsc::channel;
+    use std::thread;
+
+    fn rows(pane: &LogPane, height: usize) -> Vec<String> {
+        let mut screen = Screen::new(20, height);
+        pane.draw(&mut screen).unwrap();
+        let cells: Vec<char> = screen.iter_cell().map(|(_, _, cell)| cell.ch).collect();
+        cells
+            .chunks(20)
+            .map(|row| row.iter().collect::<String>().trim_end().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn drop_oldest_and_follow() {
+        let mut pane = LogPane::new().capacity(4);
+        for n in 1..=3 {
+            pane.push(format!("line {}", n).as_str());
+        }
+        assert_eq!(vec!["line 2", "line 3"], rows(&pane, 2));
+
+        // scrolled up, the lines in view stay while the oldest are dropped
+        pane.scroll(-1);
+        assert!(!pane.is_following_tail());
+        pane.extend((4..=5).map(|n| AttrText::from(format!("line {}", n).as_str())));
+        assert_eq!(1, pane.dropped());
+        assert_eq!(vec!["[1 line dropped]", "line 2", "line 3"], rows(&pane, 3));
+
+        pane.scroll(10);
+        assert!(pane.is_following_tail());
+        assert_eq!(vec!["[1 line dropped]", "line 4", "line 5"], rows(&pane, 3));
+    }
+
+    #[test]
+    fn batches_from_threads() {
+        let (event_tx, event_rx) = channel();
+        let events = EventSender::new(Arc::new(SpinLock::new(event_tx)));
+        let mut pane = LogPane::new().capacity(3);
+        let sender = pane.sender(events);
+
+        thread::spawn(move || {
+            for n in 1..=5 {
+                sender.send(format!("line {}", n).as_str()).unwrap();
+            }
+        })
+        .join()
+        .unwrap();
+
+        // a single event for the burst, the lines past the capacity were dropped
+        let event = event_rx.try_recv().unwrap();
+        assert!(event_rx.try_recv().is_err());
+        assert!(!LogPane::new().on_event(&event));
+        assert!(pane.on_event(&event));
+        let lines: Vec<&str> = pane.lines().map(|line| line.text.as_str()).collect();
+        assert_eq!(vec!["line 3", "line 4", "line 5"], lines);
+        assert_eq!(2, pane.dropped());
+    }
+}
@@ -0,0 +1,18 @@
+//! Pre-defined interactive components that implement Draw
+mod completion;
+mod diff;
+mod inspector;
+mod list_view;
+mod log_pane;
+mod notifier;
+mod palette;
+mod table;
+
+pub use self::completion::*;
+pub use self::diff::*;
+pub use self::inspector::*;
+pub use self::list_view::*;
+pub use self::log_pane::*;
+pub use self::notifier::*;
+pub use self::palette::*;
+pub use self::table::*;
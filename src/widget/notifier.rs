@@ -0,0 +1,498 @@
+use crate::animation::{Animator, Easing, TweenId};
+use crate::attr::{Attr, Color, Effect};
+use crate::canvas::{BoundedCanvas, Canvas, Result};
+use crate::draw::Draw;
+use crate::screen::Screen;
+use crate::style::{Styled, Styles};
+use crate::width::{char_width, display_width};
+use std::cmp::{max, min};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(4);
+const DEFAULT_WIDTH: usize = 40;
+const DEFAULT_MAX_TOASTS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    fn icon(self) -> char {
+        match self {
+            Severity::Info => 'i',
+            Severity::Success => '✓',
+            Severity::Warning => '!',
+            Severity::Error => '✗',
+        }
+    }
+
+    fn slot(self) -> &'static str {
+        match self {
+            Severity::Info => "notifier.info",
+            Severity::Success => "notifier.success",
+            Severity::Warning => "notifier.warning",
+            Severity::Error => "notifier.error",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Severity::Info => Color::BLUE,
+            Severity::Success => Color::GREEN,
+            Severity::Warning => Color::YELLOW,
+            Severity::Error => Color::RED,
+        }
+    }
+}
+
+/// Where the toasts are stacked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub severity: Severity,
+    pub message: String,
+    expires_at: Instant,
+    /// the fraction of the toast still hidden past the side edge, see `Notifier::slide_in`
+    slide: Option<TweenId>,
+}
+
+/// Queue transient messages and draw them as stacked toasts in a corner of the canvas.
+///
+/// Call `tick` regularly (e.g. on `peek_event` timeouts) to dismiss the expired toasts, and
+/// draw the notifier as the last layer.
+///
+/// ```
+/// use tuikit::widget::{Corner, Notifier, Severity};
+///
+/// let mut notifier = Notifier::new().corner(Corner::TopRight);
+/// notifier.notify(Severity::Success, "saved");
+/// assert_eq!(1, notifier.toasts().count());
+/// ```
+///
+/// With `slide_in`, the toasts slide in from the side edge: tick on every frame while
+/// `is_animating`, e.g. from a repeating timer. They are shown at once when the animations are
+/// disabled, see `animation::set_animation_enabled`.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use tuikit::prelude::*;
+/// use tuikit::widget::{Notifier, Severity};
+///
+/// const FRAME: &str = "notifier.frame";
+/// let term = Term::new().unwrap();
+/// let mut notifier = Notifier::new().slide_in(Duration::from_millis(150));
+/// notifier.notify(Severity::Info, "saved");
+/// term.set_timer(FRAME, Duration::from_millis(16), true);
+///
+/// while let Ok(ev) = term.poll_event() {
+///     match ev {
+///         Event::Timer(id) if id == FRAME => {
+///             notifier.tick();
+///             if !notifier.is_animating() {
+///                 term.cancel_timer(FRAME);
+///             }
+///         }
+///         Event::Key(_) => break,
+///         _ => {}
+///     }
+///     let _ = term.draw(&notifier);
+///     let _ = term.present();
+/// }
+/// ```
+pub struct Notifier {
+    toasts: VecDeque<Toast>,
+    corner: Corner,
+    timeout: Duration,
+    width: usize,
+    max_toasts: usize,
+    slide_duration: Duration,
+    animator: Animator,
+    styles: Styles,
+}
+
+impl Default for Notifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Builder
+impl Notifier {
+    pub fn new() -> Self {
+        Self {
+            toasts: VecDeque::new(),
+            corner: Corner::default(),
+            timeout: DEFAULT_TIMEOUT,
+            width: DEFAULT_WIDTH,
+            max_toasts: DEFAULT_MAX_TOASTS,
+            slide_duration: Duration::ZERO,
+            animator: Animator::new(),
+            styles: Styles::new(),
+        }
+    }
+
+    pub fn corner(mut self, corner: Corner) -> Self {
+        self.corner = corner;
+        self
+    }
+
+    /// how long a toast is shown by default
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// the max width of a toast, longer messages are wrapped
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = max(width, 5);
+        self
+    }
+
+    /// max number of toasts shown, the oldest ones are dismissed first
+    pub fn max_toasts(mut self, max_toasts: usize) -> Self {
+        self.max_toasts = max(max_toasts, 1);
+        self
+    }
+
+    /// slide the new toasts in from the side edge during `duration`, off by default
+    pub fn slide_in(mut self, duration: Duration) -> Self {
+        self.slide_duration = duration;
+        self
+    }
+
+    /// same as `style("notifier", attr)`
+    pub fn attr(self, attr: impl Into<Attr>) -> Self {
+        self.style("notifier", attr)
+    }
+}
+
+impl Styled for Notifier {
+    /// the toasts, and their icon per severity (on top of the toast)
+    fn style_slots(&self) -> &'static [&'static str] {
+        &[
+            "notifier",
+            "notifier.info",
+            "notifier.success",
+            "notifier.warning",
+            "notifier.error",
+        ]
+    }
+
+    fn styles_mut(&mut self) -> &mut Styles {
+        &mut self.styles
+    }
+}
+
+impl Notifier {
+    pub fn notify(&mut self, severity: Severity, message: &str) {
+        self.notify_with_timeout(severity, message, self.timeout);
+    }
+
+    pub fn notify_with_timeout(&mut self, severity: Severity, message: &str, timeout: Duration) {
+        self.notify_at(Instant::now(), severity, message, timeout);
+    }
+
+    fn notify_at(&mut self, now: Instant, severity: Severity, message: &str, timeout: Duration) {
+        let slide = if self.slide_duration > Duration::ZERO {
+            self.animator.tick(now);
+            Some(
+                self.animator
+                    .tween(1.0, 0.0, self.slide_duration, Easing::EaseOut),
+            )
+        } else {
+            None
+        };
+        self.toasts.push_back(Toast {
+            severity,
+            message: message.to_string(),
+            expires_at: now + timeout,
+            slide,
+        });
+        while self.toasts.len() > self.max_toasts {
+            if let Some(toast) = self.toasts.pop_front() {
+                self.forget(&toast);
+            }
+        }
+    }
+
+    /// Dismiss the expired toasts and advance the slides, return `true` if the screen should
+    /// be redrawn
+    pub fn tick(&mut self) -> bool {
+        self.tick_at(Instant::now())
+    }
+
+    pub fn tick_at(&mut self, now: Instant) -> bool {
+        let sliding = self.animator.is_running();
+        self.animator.tick(now);
+
+        let (kept, expired): (VecDeque<Toast>, VecDeque<Toast>) = self
+            .toasts
+            .drain(..)
+            .partition(|toast| toast.expires_at > now);
+        self.toasts = kept;
+        for toast in expired.iter() {
+            self.forget(toast);
+        }
+        sliding || !expired.is_empty()
+    }
+
+    /// whether a toast is sliding in, i.e. `tick` should be called on every frame
+    pub fn is_animating(&self) -> bool {
+        self.animator.is_running()
+    }
+
+    pub fn dismiss_all(&mut self) {
+        self.toasts.clear();
+        self.animator = Animator::new();
+    }
+
+    /// drop the slide of a dismissed toast
+    fn forget(&mut self, toast: &Toast) {
+        if let Some(id) = toast.slide {
+            self.animator.remove(id);
+        }
+    }
+
+    /// the columns of `toast` still hidden past the side edge
+    fn hidden_width(&self, toast: &Toast, toast_width: usize) -> usize {
+        let hidden = toast
+            .slide
+            .and_then(|id| self.animator.value(id))
+            .unwrap_or(0.0);
+        (hidden * toast_width as f64).round() as usize
+    }
+
+    /// the toasts shown, oldest first
+    pub fn toasts(&self) -> impl Iterator<Item = &Toast> {
+        self.toasts.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.toasts.is_empty()
+    }
+}
+
+/// break `text` into lines of at most `width` columns, at spaces when possible
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        let mut line_width = 0;
+        for word in paragraph.split(' ') {
+            let word_width: usize = display_width(word);
+            if line_width > 0 && line_width + 1 + word_width > width {
+                lines.push(std::mem::take(&mut line));
+                line_width = 0;
+            }
+            if line_width > 0 {
+                line.push(' ');
+                line_width += 1;
+            }
+            for ch in word.chars() {
+                let ch_width = char_width(ch);
+                if line_width + ch_width > width {
+                    lines.push(std::mem::take(&mut line));
+                    line_width = 0;
+                }
+                line.push(ch);
+                line_width += ch_width;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+impl Draw for Notifier {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
+        let (width, height) = canvas.size()?;
+        let toast_width = min(self.width, width);
+        if toast_width < 5 {
+            return Ok(());
+        }
+        // room for the icon and the padding
+        let text_width = toast_width - 4;
+
+        let attr = self.styles.resolve("notifier", Attr::from(Effect::REVERSE));
+
+        let at_top = matches!(self.corner, Corner::TopLeft | Corner::TopRight);
+        let left = match self.corner {
+            Corner::TopLeft | Corner::BottomLeft => 0,
+            Corner::TopRight | Corner::BottomRight => width - toast_width,
+        };
+
+        // the newest toast is the closest to the corner
+        let mut used = 0;
+        for toast in self.toasts.iter().rev() {
+            let lines = wrap(&toast.message, text_width);
+            if used + lines.len() > height {
+                break;
+            }
+            let top = if at_top {
+                used
+            } else {
+                height - used - lines.len()
+            };
+            used += lines.len();
+
+            let hidden = self.hidden_width(toast, toast_width);
+            if hidden == 0 {
+                let mut area = BoundedCanvas::new(top, left, toast_width, lines.len(), canvas);
+                self.draw_toast(&mut area, toast, &lines, attr)?;
+                continue;
+            }
+
+            // drawn aside, then shifted past the side edge
+            let mut sliding = Screen::new(toast_width, lines.len());
+            self.draw_toast(&mut sliding, toast, &lines, attr)?;
+            let mut area = BoundedCanvas::new(top, 0, width, lines.len(), canvas);
+            for (row, col, cell) in sliding.iter_cell() {
+                let col = if left == 0 {
+                    col.checked_sub(hidden)
+                } else {
+                    Some(left + hidden + col)
+                };
+                if let Some(col) = col {
+                    area.put_cell(row, col, *cell)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Notifier {
+    /// draw `toast`, wrapped in `lines`, at the top left of `canvas`
+    fn draw_toast(
+        &self,
+        canvas: &mut dyn Canvas,
+        toast: &Toast,
+        lines: &[String],
+        attr: Attr,
+    ) -> Result<()> {
+        let (toast_width, _) = canvas.size()?;
+        let icon_attr = attr.extend(
+            self.styles
+                .resolve(toast.severity.slot(), Attr::from(toast.severity.color())),
+        );
+        for (row, line) in lines.iter().enumerate() {
+            let icon = if row == 0 {
+                format!(" {} ", toast.severity.icon())
+            } else {
+                "   ".to_string()
+            };
+            let col = canvas.print_with_attr(row, 0, &icon, icon_attr)?;
+            let col = col + canvas.print_with_attr(row, col, line, attr)?;
+            for fill in col..toast_width {
+                canvas.print_with_attr(row, fill, " ", attr)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::screen::Screen;
+
+    fn row_text(screen: &Screen, row: usize) -> String {
+        screen
+            .iter_cell()
+            .filter(|(r, _, _)| *r == row)
+            .map(|(_, _, cell)| cell.ch)
+            .collect::<String>()
+            .replace('\0', " ")
+    }
+
+    #[test]
+    fn toasts_expire() {
+        let now = Instant::now();
+        let mut notifier = Notifier::new().max_toasts(2);
+        notifier.notify_at(now, Severity::Info, "a", Duration::from_secs(1));
+        notifier.notify_at(now, Severity::Info, "b", Duration::from_secs(3));
+        notifier.notify_at(now, Severity::Info, "c", Duration::from_secs(2));
+
+        let messages = |notifier: &Notifier| -> Vec<String> {
+            notifier
+                .toasts()
+                .map(|toast| toast.message.clone())
+                .collect()
+        };
+        assert_eq!(vec!["b", "c"], messages(&notifier));
+        assert!(!notifier.tick_at(now + Duration::from_secs(1)));
+        assert!(notifier.tick_at(now + Duration::from_secs(2)));
+        assert_eq!(vec!["b"], messages(&notifier));
+    }
+
+    #[test]
+    fn wrap_lines() {
+        assert_eq!(vec!["hello", "world"], wrap("hello world", 8));
+        assert_eq!(vec!["abcd", "ef"], wrap("abcdef", 4));
+        assert_eq!(vec!["a", "b"], wrap("a\nb", 4));
+    }
+
+    #[test]
+    fn stacked_in_corner() {
+        let mut notifier = Notifier::new().width(11);
+        notifier.notify(Severity::Error, "old");
+        notifier.notify(Severity::Success, "new one");
+
+        let mut screen = Screen::new(12, 4);
+        notifier.draw(&mut screen).unwrap();
+        assert_eq!("", row_text(&screen, 1).trim());
+        assert_eq!("  ✗ old     ", row_text(&screen, 2));
+        assert_eq!("  ✓ new one ", row_text(&screen, 3));
+    }
+
+    #[test]
+    fn slide_in_from_the_side() {
+        let now = Instant::now();
+        let duration = Duration::from_millis(100);
+        let mut notifier = Notifier::new().width(10).slide_in(duration);
+        notifier.notify_at(now, Severity::Info, "hi", Duration::from_secs(1));
+        assert!(notifier.is_animating());
+
+        let mut screen = Screen::new(12, 1);
+        notifier.draw(&mut screen).unwrap();
+        assert_eq!("", row_text(&screen, 0).trim());
+
+        // eased out: 73% of the width is still hidden after 10% of the time
+        assert!(notifier.tick_at(now + duration / 10));
+        let mut screen = Screen::new(12, 1);
+        notifier.draw(&mut screen).unwrap();
+        assert_eq!("          i ", row_text(&screen, 0));
+
+        // the last frame is still redrawn, then it stops
+        assert!(notifier.tick_at(now + duration));
+        assert!(!notifier.is_animating());
+        assert!(!notifier.tick_at(now + duration * 2));
+        let mut screen = Screen::new(12, 1);
+        notifier.draw(&mut screen).unwrap();
+        assert_eq!("   i hi     ", row_text(&screen, 0));
+
+        // from the left edge in the left corners
+        let mut notifier = Notifier::new()
+            .width(10)
+            .corner(Corner::TopLeft)
+            .slide_in(duration);
+        notifier.notify_at(now, Severity::Info, "hello", Duration::from_secs(1));
+        notifier.tick_at(now + duration / 10);
+        let mut screen = Screen::new(12, 1);
+        notifier.draw(&mut screen).unwrap();
+        assert_eq!("o", row_text(&screen, 0).trim());
+    }
+}
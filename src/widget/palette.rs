@@ -0,0 +1,780 @@
+use crate::attr::{Attr, Effect};
+use crate::canvas::{BoundedCanvas, Canvas, Result};
+use crate::draw::Draw;
+use crate::key::Key;
+use crate::keymap::{Keymap, KeymapEvent};
+use crate::paste::PastePolicy;
+use crate::style::{Styled, Styles};
+use crate::undo::{EditKind, UndoStack};
+use crate::width::display_width;
+use crate::word::WordClass;
+use std::cmp::{max, min};
+
+const PROMPT: &str = "> ";
+const DEFAULT_MAX_HEIGHT: usize = 10;
+const DEFAULT_MAX_WIDTH: usize = 60;
+/// the commands remembered by `CommandPalette::history`
+const MAX_HISTORY: usize = 20;
+
+/// A command listed in the palette
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaletteCommand {
+    pub name: String,
+    /// the key the command is bound to, shown next to the name
+    pub key: Option<Key>,
+    pub description: String,
+}
+
+/// What the keys of a `CommandPalette` are bound to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteAction {
+    Up,
+    Down,
+    Execute,
+    Cancel,
+    /// move the cursor of the query
+    Left,
+    Right,
+    Home,
+    End,
+    /// move to the start of the word before the cursor, see `CommandPalette::word_class`
+    WordBackward,
+    /// move to the end of the word after the cursor
+    WordForward,
+    /// delete the word before the cursor
+    DeleteWordBackward,
+    /// undo the last edit of the query
+    Undo,
+    Redo,
+}
+
+/// Events emitted by `CommandPalette::on_key`
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaletteEvent {
+    /// the command of this name was chosen and its action executed
+    Executed(String),
+    /// the palette was closed without choosing a command
+    Cancel,
+}
+
+/// A Ctrl-P style launcher: fuzzy-filters the registered commands as the user types and
+/// executes the chosen one.
+///
+/// ```
+/// use tuikit::key::Key;
+/// use tuikit::widget::{CommandPalette, PaletteEvent};
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+///
+/// let saved = Rc::new(Cell::new(false));
+/// let saved_clone = saved.clone();
+/// let mut palette = CommandPalette::new()
+///     .command("Save file", Some(Key::Ctrl('s')), "write to disk", move || {
+///         saved_clone.set(true)
+///     })
+///     .command("Quit", Some(Key::Ctrl('q')), "", || {});
+///
+/// palette.open();
+/// palette.on_key(&Key::Char('s'));
+/// palette.on_key(&Key::Char('f'));
+/// let event = palette.on_key(&Key::Enter);
+/// assert_eq!(Some(PaletteEvent::Executed("Save file".to_string())), event);
+/// assert!(saved.get());
+/// ```
+pub struct CommandPalette {
+    commands: Vec<PaletteCommand>,
+    actions: Vec<Box<dyn FnMut()>>,
+    opened: bool,
+    query: String,
+    /// the position of the cursor in the query, in chars
+    cursor: usize,
+    matches: Vec<usize>,
+    selected: usize,
+    keymap: Keymap<PaletteAction>,
+    word_class: WordClass,
+    /// the query and the cursor before the edits
+    undo: UndoStack<(String, usize)>,
+    /// the names of the commands executed, most recent first
+    history: Vec<String>,
+    /// shown next to the query until the next key, e.g. "pasted 3 lines"
+    message: Option<String>,
+
+    max_height: usize,
+    max_width: usize,
+    styles: Styles,
+}
+
+impl Default for CommandPalette {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Builder
+impl CommandPalette {
+    pub fn new() -> Self {
+        let mut keymap = Keymap::new();
+        let bindings = [
+            (Key::Up, PaletteAction::Up),
+            (Key::Ctrl('p'), PaletteAction::Up),
+            (Key::Down, PaletteAction::Down),
+            (Key::Ctrl('n'), PaletteAction::Down),
+            (Key::Enter, PaletteAction::Execute),
+            (Key::ESC, PaletteAction::Cancel),
+            (Key::Ctrl('g'), PaletteAction::Cancel),
+            (Key::Left, PaletteAction::Left),
+            (Key::Right, PaletteAction::Right),
+            (Key::Home, PaletteAction::Home),
+            (Key::Ctrl('a'), PaletteAction::Home),
+            (Key::End, PaletteAction::End),
+            (Key::Ctrl('e'), PaletteAction::End),
+            (Key::Alt('b'), PaletteAction::WordBackward),
+            (Key::Alt('f'), PaletteAction::WordForward),
+            (Key::Ctrl('w'), PaletteAction::DeleteWordBackward),
+            (Key::Ctrl('_'), PaletteAction::Undo),
+            (Key::Ctrl('r'), PaletteAction::Redo),
+        ];
+        for (key, action) in bindings.iter() {
+            keymap.bind_keys(vec![key.clone()], *action);
+        }
+
+        Self {
+            commands: Vec::new(),
+            actions: Vec::new(),
+            opened: false,
+            query: String::new(),
+            cursor: 0,
+            matches: Vec::new(),
+            selected: 0,
+            keymap,
+            word_class: WordClass::default(),
+            undo: UndoStack::new(),
+            history: Vec::new(),
+            message: None,
+            max_height: DEFAULT_MAX_HEIGHT,
+            max_width: DEFAULT_MAX_WIDTH,
+            styles: Styles::new(),
+        }
+    }
+
+    /// register a command, `key` is only displayed
+    pub fn command(
+        mut self,
+        name: &str,
+        key: Option<Key>,
+        description: &str,
+        action: impl FnMut() + 'static,
+    ) -> Self {
+        self.add_command(name, key, description, action);
+        self
+    }
+
+    /// max number of commands shown at a time
+    pub fn max_height(mut self, max_height: usize) -> Self {
+        self.max_height = max(max_height, 1);
+        self
+    }
+
+    /// what `Alt-B`, `Alt-F` and `Ctrl-W` take as a word, `WordClass::Shell` by default
+    pub fn word_class(mut self, word_class: WordClass) -> Self {
+        self.word_class = word_class;
+        self
+    }
+
+    pub fn max_width(mut self, max_width: usize) -> Self {
+        self.max_width = max(max_width, PROMPT.len() + 1);
+        self
+    }
+
+    /// same as `style("palette", attr)`
+    pub fn attr(self, attr: impl Into<Attr>) -> Self {
+        self.style("palette", attr)
+    }
+
+    /// same as `style("palette.selected", attr)`
+    pub fn selected_attr(self, attr: impl Into<Attr>) -> Self {
+        self.style("palette.selected", attr)
+    }
+}
+
+impl Styled for CommandPalette {
+    /// the palette, the selected command (on top of the palette) and the key bindings and
+    /// descriptions (on top of the command)
+    fn style_slots(&self) -> &'static [&'static str] {
+        &["palette", "palette.selected", "palette.key"]
+    }
+
+    fn styles_mut(&mut self) -> &mut Styles {
+        &mut self.styles
+    }
+}
+
+impl CommandPalette {
+    pub fn add_command(
+        &mut self,
+        name: &str,
+        key: Option<Key>,
+        description: &str,
+        action: impl FnMut() + 'static,
+    ) {
+        self.commands.push(PaletteCommand {
+            name: name.to_string(),
+            key,
+            description: description.to_string(),
+        });
+        self.actions.push(Box::new(action));
+        self.refilter();
+    }
+
+    pub fn commands(&self) -> &[PaletteCommand] {
+        &self.commands
+    }
+
+    /// the bindings, e.g. to add `Ctrl-Z` for `Undo`
+    pub fn keymap_mut(&mut self) -> &mut Keymap<PaletteAction> {
+        &mut self.keymap
+    }
+
+    /// open the palette with an empty query and no undo history
+    pub fn open(&mut self) {
+        self.opened = true;
+        self.query.clear();
+        self.cursor = 0;
+        self.undo.clear();
+        self.keymap.cancel();
+        self.message = None;
+        self.selected = 0;
+        self.refilter();
+    }
+
+    pub fn close(&mut self) {
+        self.opened = false;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.opened
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// the position of the cursor in the query, in chars
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// the names of the commands executed, most recent first. They rank first among the
+    /// equal matches, e.g. all of them while the query is empty.
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Replace the history, e.g. restored from the last run. The names of the commands that
+    /// are not registered (any more) are dropped.
+    pub fn set_history(&mut self, history: Vec<String>) {
+        self.history = history;
+        let commands = &self.commands;
+        self.history
+            .retain(|name| commands.iter().any(|command| &command.name == name));
+        self.history.truncate(MAX_HISTORY);
+        self.refilter();
+    }
+
+    /// the confirmation of the last key, e.g. "pasted 3 lines" once lines are joined
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// the commands matching the query, best match first
+    pub fn matches(&self) -> impl Iterator<Item = &PaletteCommand> {
+        self.matches.iter().map(move |&index| &self.commands[index])
+    }
+
+    pub fn selected(&self) -> Option<&PaletteCommand> {
+        self.matches().nth(self.selected)
+    }
+
+    fn refilter(&mut self) {
+        let mut scored: Vec<(i64, usize)> = self
+            .commands
+            .iter()
+            .enumerate()
+            .filter_map(|(index, command)| {
+                let by_name = fuzzy_score(&self.query, &command.name);
+                // a match in the description ranks below any match in the name
+                let by_description =
+                    fuzzy_score(&self.query, &command.description).map(|score| score - 1000);
+                max(by_name, by_description).map(|score| (score, index))
+            })
+            .collect();
+        // the recent commands first for equal scores, then the registration order
+        let recency = |index: usize| {
+            let name = &self.commands[index].name;
+            self.history.iter().position(|recent| recent == name)
+        };
+        scored.sort_by_key(|&(score, index)| (-score, recency(index).unwrap_or(usize::MAX)));
+        self.matches = scored.into_iter().map(|(_, index)| index).collect();
+        self.selected = min(self.selected, self.matches.len().saturating_sub(1));
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let len = self.matches.len() as isize;
+        if len > 0 {
+            self.selected = (self.selected as isize + delta).rem_euclid(len) as usize;
+        }
+    }
+
+    /// Handle the key while the palette is open, return `Some(event)` if it is closed.
+    ///
+    /// - `Up`/`Down`/`Ctrl-P`/`Ctrl-N` select the command
+    /// - `Enter` executes the selected command
+    /// - `ESC`/`Ctrl-G` cancels
+    /// - `Left`/`Right`/`Home`/`End`/`Ctrl-A`/`Ctrl-E` move the cursor of the query, `Alt-B`/`Alt-F`
+    ///   by words
+    /// - `Ctrl-W` deletes the word before the cursor
+    /// - `Ctrl-_`/`Ctrl-R` undo and redo the edits of the query
+    /// - chars and `Backspace` edit the query
+    ///
+    /// The bindings could be changed with `keymap_mut`.
+    pub fn on_key(&mut self, key: &Key) -> Option<PaletteEvent> {
+        if !self.opened {
+            return None;
+        }
+
+        self.message = None;
+        let keys = match self.keymap.on_key(key.clone()) {
+            KeymapEvent::Resolved(action) => return self.run(action),
+            KeymapEvent::Pending => return None,
+            KeymapEvent::Unbound(keys) | KeymapEvent::TimedOut(keys) => keys,
+        };
+        for key in keys {
+            match key {
+                Key::Backspace if self.cursor > 0 => {
+                    let mut chars: Vec<char> = self.query.chars().collect();
+                    chars.remove(self.cursor - 1);
+                    self.edit(EditKind::Delete, chars, self.cursor - 1);
+                }
+                Key::Char(ch) => {
+                    let mut chars: Vec<char> = self.query.chars().collect();
+                    chars.insert(self.cursor, ch);
+                    self.edit(EditKind::Insert, chars, self.cursor + 1);
+                }
+                Key::BracketedPaste(text) => self.paste(&text),
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// replace the query by `chars`, undoable
+    fn edit(&mut self, kind: EditKind, chars: Vec<char>, cursor: usize) {
+        self.undo.push((self.query.clone(), self.cursor), kind);
+        self.query = chars.into_iter().collect();
+        self.cursor = cursor;
+        self.selected = 0;
+        self.refilter();
+    }
+
+    fn move_cursor(&mut self, cursor: usize) {
+        // typing elsewhere is undone separately
+        self.undo.break_group();
+        self.cursor = cursor;
+    }
+
+    fn run(&mut self, action: PaletteAction) -> Option<PaletteEvent> {
+        match action {
+            PaletteAction::Up => self.move_selection(-1),
+            PaletteAction::Down => self.move_selection(1),
+            PaletteAction::Execute => {
+                let index = *self.matches.get(self.selected)?;
+                self.close();
+                (self.actions[index])();
+                let name = self.commands[index].name.clone();
+                self.history.retain(|recent| recent != &name);
+                self.history.insert(0, name.clone());
+                self.history.truncate(MAX_HISTORY);
+                return Some(PaletteEvent::Executed(name));
+            }
+            PaletteAction::Cancel => {
+                self.close();
+                return Some(PaletteEvent::Cancel);
+            }
+            PaletteAction::Left => self.move_cursor(self.cursor.saturating_sub(1)),
+            PaletteAction::Right => {
+                self.move_cursor(min(self.cursor + 1, self.query.chars().count()))
+            }
+            PaletteAction::Home => self.move_cursor(0),
+            PaletteAction::End => self.move_cursor(self.query.chars().count()),
+            PaletteAction::WordBackward => {
+                let chars: Vec<char> = self.query.chars().collect();
+                self.move_cursor(self.word_class.prev_word_start(&chars, self.cursor));
+            }
+            PaletteAction::WordForward => {
+                let chars: Vec<char> = self.query.chars().collect();
+                self.move_cursor(self.word_class.next_word_end(&chars, self.cursor));
+            }
+            PaletteAction::DeleteWordBackward => {
+                let mut chars: Vec<char> = self.query.chars().collect();
+                let start = self.word_class.prev_word_start(&chars, self.cursor);
+                if start < self.cursor {
+                    chars.drain(start..self.cursor);
+                    self.edit(EditKind::Other, chars, start);
+                }
+            }
+            PaletteAction::Undo => {
+                let state = self.undo.undo((self.query.clone(), self.cursor));
+                self.restore(state);
+            }
+            PaletteAction::Redo => {
+                let state = self.undo.redo((self.query.clone(), self.cursor));
+                self.restore(state);
+            }
+        }
+        None
+    }
+
+    /// the query and the cursor restored by undo or redo, if any
+    fn restore(&mut self, state: Option<(String, usize)>) {
+        if let Some((query, cursor)) = state {
+            self.query = query;
+            self.cursor = cursor;
+            self.selected = 0;
+            self.refilter();
+        }
+    }
+}
+
+impl CommandPalette {
+    /// Insert pasted text at the cursor at once, the newlines and control characters stripped
+    /// as the query is a single line. Ignored while closed. `on_key` calls it on
+    /// `Key::BracketedPaste`.
+    ///
+    /// The paste is undone at once, and the number of lines joined is reported by `message`.
+    pub fn paste(&mut self, text: &str) {
+        if !self.opened {
+            return;
+        }
+        let lines = text
+            .split(['\r', '\n'])
+            .filter(|line| !line.trim().is_empty())
+            .count();
+        let text = PastePolicy::default()
+            .single_line(true)
+            .apply(text)
+            .unwrap_or_default();
+        if text.is_empty() {
+            return;
+        }
+        let mut chars: Vec<char> = self.query.chars().collect();
+        let cursor = self.cursor + text.chars().count();
+        chars.splice(self.cursor..self.cursor, text.chars());
+        self.edit(EditKind::Other, chars, cursor);
+        if lines > 1 {
+            self.message = Some(format!("pasted {} lines", lines));
+        }
+    }
+}
+
+/// Score how well `pattern` matches `text` as a case insensitive subsequence, `None` if it
+/// doesn't match. Consecutive chars and chars at the start of words score higher.
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last_match: Option<usize> = None;
+
+    for pattern_ch in pattern.chars().flat_map(char::to_lowercase) {
+        let found = (pos..text.len()).find(|&i| text[i].to_lowercase().eq(Some(pattern_ch)))?;
+        score += 1;
+        if last_match == Some(found.wrapping_sub(1)) {
+            score += 5;
+        }
+        if found == 0 || !text[found - 1].is_alphanumeric() {
+            score += 8;
+        }
+        score -= (found - pos) as i64;
+        last_match = Some(found);
+        pos = found + 1;
+    }
+    Some(score)
+}
+
+impl Draw for CommandPalette {
+    /// draw the palette centered horizontally, near the top of the canvas
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
+        if !self.opened {
+            return Ok(());
+        }
+
+        let (width, height) = canvas.size()?;
+        let palette_width = min(self.max_width, width);
+        let palette_height = min(1 + min(self.matches.len(), self.max_height), height);
+        if palette_width == 0 || palette_height == 0 {
+            return Ok(());
+        }
+        let left = (width - palette_width) / 2;
+        let top = min(height / 5, height - palette_height);
+        let mut palette = BoundedCanvas::new(top, left, palette_width, palette_height, canvas);
+
+        let fill = |canvas: &mut BoundedCanvas, row: usize, from: usize, attr: Attr| {
+            for col in from..palette_width {
+                let _ = canvas.print_with_attr(row, col, " ", attr);
+            }
+        };
+
+        let base_attr = self.styles.resolve("palette", Attr::from(Effect::REVERSE));
+        let selected_attr = self
+            .styles
+            .resolve("palette.selected", Attr::from(Effect::BOLD));
+        let key_attr = self.styles.resolve("palette.key", Attr::from(Effect::DIM));
+
+        let prompt_width = palette.print_with_attr(0, 0, PROMPT, base_attr)?;
+        let printed =
+            prompt_width + palette.print_with_attr(0, prompt_width, &self.query, base_attr)?;
+        fill(&mut palette, 0, printed, base_attr);
+        if let Some(message) = self.message.as_ref() {
+            let message = format!(" {} ", message);
+            let message_col = palette_width.saturating_sub(display_width(&message));
+            if message_col > printed {
+                palette.print_with_attr(0, message_col, &message, base_attr.extend(key_attr))?;
+            }
+        }
+        let before_cursor: String = self.query.chars().take(self.cursor).collect();
+        let cursor_col = prompt_width + display_width(&before_cursor);
+        palette.set_cursor(0, min(cursor_col, palette_width - 1))?;
+
+        // scroll so that the selected command is visible
+        let list_height = palette_height - 1;
+        let offset = (self.selected + 1).saturating_sub(list_height);
+        for (index, command) in self.matches().enumerate().skip(offset).take(list_height) {
+            let row = index - offset + 1;
+            let attr = if index == self.selected {
+                base_attr.extend(selected_attr)
+            } else {
+                base_attr
+            };
+
+            let mut col = palette.print_with_attr(row, 1, &command.name, attr)? + 1;
+            if !command.description.is_empty() {
+                let description = format!("  {}", command.description);
+                col += palette.print_with_attr(row, col, &description, attr.extend(key_attr))?;
+            }
+            fill(&mut palette, row, col, attr);
+
+            if let Some(key) = command.key.as_ref() {
+                let key = format!(" {} ", key.describe());
+                let key_col = palette_width.saturating_sub(display_width(&key));
+                palette.print_with_attr(row, key_col, &key, attr.extend(key_attr))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::screen::Screen;
+
+    fn new_palette() -> CommandPalette {
+        CommandPalette::new()
+            .command("Open file", Some(Key::Ctrl('o')), "", || {})
+            .command("Toggle sidebar", None, "show or hide the file tree", || {})
+            .command("Find in files", Some(Key::Ctrl('f')), "", || {})
+    }
+
+    fn names(palette: &CommandPalette) -> Vec<&str> {
+        palette
+            .matches()
+            .map(|command| command.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn fuzzy() {
+        assert!(fuzzy_score("of", "Open file").is_some());
+        assert!(fuzzy_score("fo", "Open file").is_none());
+        assert!(fuzzy_score("fi", "Find in files") > fuzzy_score("fi", "Open file"));
+        assert_eq!(Some(0), fuzzy_score("", "anything"));
+    }
+
+    #[test]
+    fn filter_and_execute() {
+        let mut palette = new_palette();
+        palette.open();
+        assert_eq!(3, names(&palette).len());
+
+        palette.on_key(&Key::Char('f'));
+        palette.on_key(&Key::Char('i'));
+        assert_eq!(
+            vec!["Find in files", "Open file", "Toggle sidebar"],
+            names(&palette)
+        );
+
+        palette.on_key(&Key::Down);
+        assert_eq!(
+            Some(PaletteEvent::Executed("Open file".to_string())),
+            palette.on_key(&Key::Enter)
+        );
+        assert!(!palette.is_open());
+    }
+
+    #[test]
+    fn recent_commands_first() {
+        let mut palette = new_palette();
+        palette.open();
+        palette.on_key(&Key::Up);
+        palette.on_key(&Key::Enter);
+        palette.open();
+        palette.on_key(&Key::Down);
+        palette.on_key(&Key::Enter);
+        assert_eq!(vec!["Open file", "Find in files"], palette.history());
+
+        palette.open();
+        assert_eq!(
+            vec!["Open file", "Find in files", "Toggle sidebar"],
+            names(&palette)
+        );
+        // a better match still ranks first
+        palette.on_key(&Key::Char('t'));
+        assert_eq!("Toggle sidebar", names(&palette)[0]);
+
+        let mut palette = new_palette();
+        palette.set_history(vec!["Removed".to_string(), "Toggle sidebar".to_string()]);
+        assert_eq!(vec!["Toggle sidebar"], palette.history());
+    }
+
+    #[test]
+    fn paste_query() {
+        let mut palette = new_palette();
+        palette.paste("find");
+        assert_eq!("", palette.query());
+
+        palette.open();
+        palette.paste("find\r\n in\x1b");
+        assert_eq!("find in", palette.query());
+        assert_eq!(vec!["Find in files"], names(&palette));
+    }
+
+    #[test]
+    fn bracketed_paste_query() {
+        let mut palette = new_palette();
+        palette.open();
+        palette.on_key(&Key::BracketedPaste("find\r\n in\x1b".to_string()));
+        assert_eq!("find in", palette.query());
+        assert_eq!(vec!["Find in files"], names(&palette));
+        assert_eq!(Some("pasted 2 lines"), palette.message());
+
+        let mut screen = Screen::new(30, 5);
+        palette.draw(&mut screen).unwrap();
+        let row: String = screen
+            .iter_cell()
+            .filter(|(row, _, _)| *row == 1)
+            .map(|(_, _, cell)| cell.ch)
+            .collect();
+        assert_eq!("> find in      pasted 2 lines ", row);
+
+        // undone at once, and the message is gone with the next key
+        palette.on_key(&Key::Ctrl('_'));
+        assert_eq!("", palette.query());
+        assert_eq!(None, palette.message());
+        palette.on_key(&Key::BracketedPaste("find".to_string()));
+        assert_eq!(None, palette.message());
+    }
+
+    #[test]
+    fn undo_query() {
+        let mut palette = new_palette();
+        palette.open();
+        for ch in "tog".chars() {
+            palette.on_key(&Key::Char(ch));
+        }
+        palette.on_key(&Key::Backspace);
+        palette.on_key(&Key::BracketedPaste("find".to_string()));
+        assert_eq!("tofind", palette.query());
+
+        // the paste, the delete, then the typed chars at once
+        palette.on_key(&Key::Ctrl('_'));
+        assert_eq!("to", palette.query());
+        palette.on_key(&Key::Ctrl('_'));
+        assert_eq!("tog", palette.query());
+        assert_eq!(vec!["Toggle sidebar"], names(&palette));
+        palette.on_key(&Key::Ctrl('_'));
+        assert_eq!("", palette.query());
+        assert_eq!(None, palette.on_key(&Key::Ctrl('_')));
+        assert_eq!(3, names(&palette).len());
+
+        palette.on_key(&Key::Ctrl('r'));
+        assert_eq!("tog", palette.query());
+
+        // rebound, and the history is cleared on open
+        palette
+            .keymap_mut()
+            .bind("ctrl-z", PaletteAction::Undo)
+            .unwrap();
+        palette.on_key(&Key::Ctrl('z'));
+        assert_eq!("", palette.query());
+        palette.on_key(&Key::Char('x'));
+        palette.on_key(&Key::Backspace);
+        palette.open();
+        palette.on_key(&Key::Ctrl('z'));
+        assert_eq!("", palette.query());
+    }
+
+    #[test]
+    fn word_motions() {
+        let mut palette = new_palette();
+        palette.open();
+        palette.paste("open src/foo-bar");
+        palette.on_key(&Key::Alt('b'));
+        assert_eq!(9, palette.cursor());
+        palette.on_key(&Key::Alt('b'));
+        palette.on_key(&Key::Alt('b'));
+        assert_eq!(0, palette.cursor());
+        palette.on_key(&Key::Alt('f'));
+        assert_eq!(4, palette.cursor());
+        palette.on_key(&Key::Char('!'));
+        assert_eq!("open! src/foo-bar", palette.query());
+
+        palette.on_key(&Key::End);
+        palette.on_key(&Key::Ctrl('w'));
+        assert_eq!("open! src/", palette.query());
+        palette.on_key(&Key::Ctrl('w'));
+        assert_eq!("open! ", palette.query());
+
+        // the cursor is restored along with the query
+        palette.on_key(&Key::Ctrl('_'));
+        palette.on_key(&Key::Ctrl('_'));
+        palette.on_key(&Key::Ctrl('_'));
+        assert_eq!(("open src/foo-bar", 4), (palette.query(), palette.cursor()));
+
+        let mut palette = new_palette().word_class(WordClass::Whitespace);
+        palette.open();
+        palette.paste("open src/foo-bar");
+        palette.on_key(&Key::Ctrl('w'));
+        assert_eq!("open ", palette.query());
+    }
+
+    #[test]
+    fn draw_palette() {
+        let mut palette = new_palette().max_width(30);
+        palette.open();
+        let mut screen = Screen::new(30, 5);
+        palette.draw(&mut screen).unwrap();
+
+        let row: String = screen
+            .iter_cell()
+            .filter(|(row, _, _)| *row == 2)
+            .map(|(_, _, cell)| cell.ch)
+            .collect();
+        assert_eq!(" Open file             Ctrl+O ", row);
+
+        // the query is on the 2nd row, the top of the palette
+        palette.paste("open");
+        palette.on_key(&Key::Left);
+        palette.draw(&mut screen).unwrap();
+        assert_eq!(Some((1, 5)), screen.cursor());
+    }
+}
@@ -0,0 +1,450 @@
+use crate::attr::{Attr, Effect};
+use crate::canvas::{Canvas, Result};
+use crate::draw::Draw;
+use crate::key::{Key, MouseButton};
+use crate::region::Region;
+use crate::style::{Styled, Styles};
+use crate::width::char_width;
+use std::cell::Cell;
+use std::cmp::Ordering;
+
+const SEPARATOR: &str = "│";
+const ASCENDING: &str = "▲";
+const DESCENDING: &str = "▼";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+/// Events emitted by `Table::on_key`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableEvent {
+    /// the separator on the right of `column` was dragged, it is now `width` columns wide
+    ColumnResized { column: usize, width: usize },
+    /// the header of `column` was clicked, the rows are now sorted by it
+    SortChanged { column: usize, order: SortOrder },
+}
+
+/// A column of a `Table`: its title and width, kept across the frames
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableColumn {
+    pub title: String,
+    pub width: usize,
+    pub min_width: usize,
+}
+
+impl TableColumn {
+    pub fn new(title: &str, width: usize) -> Self {
+        Self {
+            title: title.to_string(),
+            width,
+            min_width: 1,
+        }
+    }
+
+    /// the width the column can't be resized below, 1 by default
+    pub fn min_width(mut self, min_width: usize) -> Self {
+        self.min_width = min_width.max(1);
+        self.width = self.width.max(self.min_width);
+        self
+    }
+}
+
+/// Rows of cells under a header, the columns resized by dragging the separators of the header
+/// and the rows sorted by clicking a header (again to reverse the order).
+///
+/// The header cells and separators are registered as regions (`table.header`,
+/// `table.separator`, the column as payload) and the same areas are hit by the mouse events
+/// given to `on_key`. Numbers are sorted as numbers, the rest as text.
+///
+/// ```
+/// use tuikit::key::{Key, Modifiers, MouseButton};
+/// use tuikit::widget::{SortOrder, Table, TableColumn, TableEvent};
+///
+/// let mut table = Table::new(vec![TableColumn::new("name", 6), TableColumn::new("size", 4)]);
+/// table.set_rows(vec![vec!["b".into(), "10".into()], vec!["a".into(), "9".into()]]);
+/// table.set_size(20, 5); // normally recorded by `draw`
+///
+/// // a click on the header of "size"
+/// let click = Key::MousePress(MouseButton::Left, 9, 1, Modifiers::empty());
+/// assert_eq!(
+///     Some(TableEvent::SortChanged { column: 1, order: SortOrder::Ascending }),
+///     table.on_key(&click)
+/// );
+/// assert_eq!("9", table.rows()[0][1]);
+/// ```
+pub struct Table {
+    columns: Vec<TableColumn>,
+    rows: Vec<Vec<String>>,
+    sort: Option<(usize, SortOrder)>,
+    /// the first row shown
+    offset: Cell<usize>,
+    /// the size (width, height) the table was drawn with
+    size: Cell<(usize, usize)>,
+    origin: (usize, usize),
+    /// the column whose separator is dragged
+    resizing: Option<usize>,
+    styles: Styles,
+}
+
+// Builder
+impl Table {
+    pub fn new(columns: Vec<TableColumn>) -> Self {
+        Self {
+            columns,
+            rows: Vec::new(),
+            sort: None,
+            offset: Cell::new(0),
+            size: Cell::new((0, 0)),
+            origin: (0, 0),
+            resizing: None,
+            styles: Styles::new(),
+        }
+    }
+
+    /// The `(row, col)` of the table's top-left corner on the terminal.
+    /// Mouse events are translated relative to it.
+    pub fn origin(mut self, row: usize, col: usize) -> Self {
+        self.origin = (row, col);
+        self
+    }
+}
+
+impl Styled for Table {
+    /// the header row, and the title of the column sorted by (on top of it)
+    fn style_slots(&self) -> &'static [&'static str] {
+        &["table.header", "table.sorted"]
+    }
+
+    fn styles_mut(&mut self) -> &mut Styles {
+        &mut self.styles
+    }
+}
+
+impl Table {
+    pub fn columns(&self) -> &[TableColumn] {
+        &self.columns
+    }
+
+    pub fn rows(&self) -> &[Vec<String>] {
+        &self.rows
+    }
+
+    /// Replace the rows, sorted like the ones before
+    pub fn set_rows(&mut self, rows: Vec<Vec<String>>) {
+        self.rows = rows;
+        self.sort_rows();
+    }
+
+    /// the column sorted by and in which order, if any
+    pub fn sort(&self) -> Option<(usize, SortOrder)> {
+        self.sort
+    }
+
+    pub fn set_sort(&mut self, column: usize, order: SortOrder) {
+        self.sort = Some((column, order));
+        self.sort_rows();
+    }
+
+    pub fn set_column_width(&mut self, column: usize, width: usize) {
+        if let Some(col) = self.columns.get_mut(column) {
+            col.width = width.max(col.min_width);
+        }
+    }
+
+    /// record the size (width, height) the table was drawn with
+    pub fn set_size(&self, width: usize, height: usize) {
+        self.size.set((width, height));
+    }
+
+    fn sort_rows(&mut self) {
+        let (column, order) = match self.sort {
+            Some(sort) => sort,
+            None => return,
+        };
+        let empty = String::new();
+        self.rows.sort_by(|a, b| {
+            let a = a.get(column).unwrap_or(&empty);
+            let b = b.get(column).unwrap_or(&empty);
+            let ordering = compare_cells(a, b);
+            match order {
+                SortOrder::Ascending => ordering,
+                SortOrder::Descending => ordering.reverse(),
+            }
+        });
+    }
+
+    /// the header cell and the separator on its right of each column: (column, start, width)
+    fn column_spans(&self) -> impl Iterator<Item = (usize, usize, usize)> + '_ {
+        let mut start = 0;
+        self.columns.iter().enumerate().map(move |(index, column)| {
+            let span = (index, start, column.width);
+            start += column.width + 1;
+            span
+        })
+    }
+
+    /// The areas of the header a click or a drag acts upon: `table.header` to sort by the
+    /// column, `table.separator` to resize it, the column as payload
+    pub fn header_regions(&self) -> Vec<Region> {
+        let (width, _) = self.size.get();
+        let mut regions = Vec::new();
+        for (index, start, column_width) in self.column_spans() {
+            if start >= width {
+                break;
+            }
+            let payload = index.to_string();
+            regions.push(Region::new("table.header", 0, start, column_width, 1).payload(&payload));
+            regions.push(
+                Region::new("table.separator", 0, start + column_width, 1, 1).payload(&payload),
+            );
+        }
+        regions
+    }
+
+    /// Handle the key/mouse event, return `Some(event)` if a column was resized or the sort
+    /// changed.
+    ///
+    /// - a left click on a header sorts by its column, again to reverse the order.
+    /// - pressing the left button on a separator of the header and dragging resizes the
+    ///   column on its left.
+    /// - `Up`/`Down` and the wheel scroll the rows.
+    pub fn on_key(&mut self, key: &Key) -> Option<TableEvent> {
+        match key {
+            Key::Up | Key::MousePress(MouseButton::WheelUp, ..) => {
+                self.scroll(-1);
+                None
+            }
+            Key::Down | Key::MousePress(MouseButton::WheelDown, ..) => {
+                self.scroll(1);
+                None
+            }
+            Key::MousePress(MouseButton::Left, x, y, _) => {
+                let (row, col) = self.relative_pos(*x, *y)?;
+                let region = self
+                    .header_regions()
+                    .into_iter()
+                    .find(|region| region.contains(row, col))?;
+                let column = region.payload.parse().ok()?;
+                if region.name == "table.separator" {
+                    self.resizing = Some(column);
+                    return None;
+                }
+                let order = match self.sort {
+                    Some((sorted, SortOrder::Ascending)) if sorted == column => {
+                        SortOrder::Descending
+                    }
+                    _ => SortOrder::Ascending,
+                };
+                self.set_sort(column, order);
+                Some(TableEvent::SortChanged { column, order })
+            }
+            Key::MouseHold(x, _, _) => {
+                let column = self.resizing?;
+                let col = (*x as usize).checked_sub(1 + self.origin.1)?;
+                let (_, start, old_width) = self.column_spans().nth(column)?;
+                self.set_column_width(column, col.saturating_sub(start));
+                let width = self.columns[column].width;
+                if width != old_width {
+                    Some(TableEvent::ColumnResized { column, width })
+                } else {
+                    None
+                }
+            }
+            Key::MouseRelease(MouseButton::Left, ..) => {
+                self.resizing = None;
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Scroll the rows by `delta` (up if negative)
+    pub fn scroll(&mut self, delta: isize) {
+        let (_, height) = self.size.get();
+        let max_offset = self.rows.len().saturating_sub(height.saturating_sub(1));
+        let offset = (self.offset.get() as isize + delta).max(0) as usize;
+        self.offset.set(offset.min(max_offset));
+    }
+
+    /// the first row shown
+    pub fn offset(&self) -> usize {
+        self.offset.get()
+    }
+
+    /// convert the (1-based) mouse position into the (row, col) relative to the table
+    fn relative_pos(&self, x: u16, y: u16) -> Option<(usize, usize)> {
+        let row = (y as usize).checked_sub(1 + self.origin.0)?;
+        let col = (x as usize).checked_sub(1 + self.origin.1)?;
+        let (width, height) = self.size.get();
+        if row >= height || col >= width {
+            return None;
+        }
+        Some((row, col))
+    }
+}
+
+/// numbers as numbers, e.g. `9` before `10`, the rest as text
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        _ => a.cmp(b),
+    }
+}
+
+/// `text` cut or padded to `width` columns
+fn fit(text: &str, width: usize) -> String {
+    let mut fitted = String::new();
+    let mut used = 0;
+    for ch in text.chars() {
+        let ch_width = char_width(ch);
+        if used + ch_width > width {
+            break;
+        }
+        fitted.push(ch);
+        used += ch_width;
+    }
+    fitted.push_str(&" ".repeat(width - used));
+    fitted
+}
+
+impl Draw for Table {
+    fn draw(&self, canvas: &mut dyn Canvas) -> Result<()> {
+        let (width, height) = canvas.size()?;
+        self.set_size(width, height);
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+
+        let header_attr = self
+            .styles
+            .resolve("table.header", Attr::from(Effect::BOLD | Effect::UNDERLINE));
+        let sorted_attr = header_attr.extend(
+            self.styles
+                .resolve("table.sorted", Attr::from(Effect::REVERSE)),
+        );
+        for (index, start, column_width) in self.column_spans() {
+            if start >= width {
+                break;
+            }
+            let title = &self.columns[index].title;
+            let (title, attr) = match self.sort {
+                Some((sorted, order)) if sorted == index => {
+                    let glyph = match order {
+                        SortOrder::Ascending => ASCENDING,
+                        SortOrder::Descending => DESCENDING,
+                    };
+                    let title = fit(title, column_width.saturating_sub(1));
+                    (format!("{}{}", title, glyph), sorted_attr)
+                }
+                _ => (fit(title, column_width), header_attr),
+            };
+            canvas.print_with_attr(0, start, &title, attr)?;
+            canvas.print_with_attr(0, start + column_width, SEPARATOR, header_attr)?;
+        }
+        for region in self.header_regions() {
+            canvas.register_region(region)?;
+        }
+
+        let offset = self.offset.get().min(self.rows.len());
+        self.offset.set(offset);
+        for (row, cells) in self.rows.iter().skip(offset).take(height - 1).enumerate() {
+            for (index, start, column_width) in self.column_spans() {
+                if start >= width {
+                    break;
+                }
+                let cell = cells.get(index).map(String::as_str).unwrap_or("");
+                canvas.print(row + 1, start, &fit(cell, column_width))?;
+                canvas.print(row + 1, start + column_width, SEPARATOR)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::key::Modifiers;
+    use crate::screen::Screen;
+
+    fn row_text(screen: &Screen, row: usize) -> String {
+        let text: String = screen
+            .iter_cell()
+            .filter(|(r, _, _)| *r == row)
+            .map(|(_, _, cell)| cell.ch)
+            .collect();
+        text.trim_end().to_string()
+    }
+
+    fn table() -> Table {
+        let mut table = Table::new(vec![
+            TableColumn::new("name", 4),
+            TableColumn::new("size", 4),
+        ]);
+        table.set_rows(vec![
+            vec!["b".to_string(), "10".to_string()],
+            vec!["a".to_string(), "9".to_string()],
+        ]);
+        table
+    }
+
+    #[test]
+    fn sort_by_header() {
+        let mut table = table();
+        let mut screen = Screen::new(12, 3);
+        table.draw(&mut screen).unwrap();
+        assert_eq!("name│size│", row_text(&screen, 0));
+        assert_eq!("b   │10  │", row_text(&screen, 1));
+
+        let click = |x| Key::MousePress(MouseButton::Left, x, 1, Modifiers::empty());
+        assert_eq!(
+            Some(TableEvent::SortChanged {
+                column: 1,
+                order: SortOrder::Ascending
+            }),
+            table.on_key(&click(6))
+        );
+        table.draw(&mut screen).unwrap();
+        assert_eq!("name│siz▲│", row_text(&screen, 0));
+        assert_eq!("a   │9   │", row_text(&screen, 1));
+
+        // again: reversed, numbers sorted as numbers
+        table.on_key(&click(6));
+        assert_eq!(Some((1, SortOrder::Descending)), table.sort());
+        assert_eq!("10", table.rows()[0][1]);
+    }
+
+    #[test]
+    fn resize_by_dragging_the_separator() {
+        let mut table = table().origin(1, 2);
+        table.draw(&mut Screen::new(12, 3)).unwrap();
+
+        // the separator of "name" is at col 4 of the table, col 7 (1-based) of the terminal
+        let press = Key::MousePress(MouseButton::Left, 7, 2, Modifiers::empty());
+        assert_eq!(None, table.on_key(&press));
+        assert_eq!(
+            Some(TableEvent::ColumnResized {
+                column: 0,
+                width: 6
+            }),
+            table.on_key(&Key::MouseHold(9, 2, Modifiers::empty()))
+        );
+        // not below the min width
+        table.on_key(&Key::MouseHold(3, 2, Modifiers::empty()));
+        assert_eq!(1, table.columns()[0].width);
+        table.on_key(&Key::MouseRelease(
+            MouseButton::Left,
+            1,
+            2,
+            Modifiers::empty(),
+        ));
+        assert_eq!(
+            None,
+            table.on_key(&Key::MouseHold(9, 2, Modifiers::empty()))
+        );
+        assert_eq!(None, table.sort());
+    }
+}
@@ -0,0 +1,150 @@
+//! Address the widgets by id, for controllers outside of the event loop (IPC, tests, scripts).
+//!
+//! A `WidgetId` is the path of a widget in the tree, e.g. `main/sidebar/list`, built from the id
+//! of its parent with `child` while drawing, so it stays the same from frame to frame as long as
+//! the tree does. A controller then:
+//!
+//! - posts a message to a widget with `Term::post_to`, returned by `poll_event` as
+//!   `Event::Message` for the application to route down the tree (see `WidgetId::contains`),
+//! - reads the state a widget published with `Term::publish_widget_state` through
+//!   `Term::widget_state`.
+//!
+//! ```no_run
+//! use tuikit::prelude::*;
+//! use tuikit::widget_id::WidgetId;
+//!
+//! let term = Term::new().unwrap();
+//! let list = WidgetId::root("main").child("list");
+//! term.publish_widget_state(&list, "selected=3");
+//!
+//! // from a test or the control socket
+//! term.post_to(&list, "select 4").unwrap();
+//! assert_eq!(Some("selected=3".to_string()), term.widget_state(&list));
+//!
+//! while let Ok(Event::Message { target, message }) = term.poll_event() {
+//!     if list.contains(&target) {
+//!         println!("list got {}", message);
+//!     }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+const SEPARATOR: char = '/';
+
+/// The path of a widget in the tree
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct WidgetId(String);
+
+impl WidgetId {
+    pub fn root(name: &str) -> Self {
+        Self(name.to_string())
+    }
+
+    /// the id of the child `name` of this widget
+    pub fn child(&self, name: &str) -> Self {
+        Self(format!("{}{}{}", self.0, SEPARATOR, name))
+    }
+
+    pub fn parent(&self) -> Option<Self> {
+        self.0
+            .rfind(SEPARATOR)
+            .map(|index| Self(self.0[..index].to_string()))
+    }
+
+    /// the name given to `root` or `child`
+    pub fn name(&self) -> &str {
+        self.0.rsplit(SEPARATOR).next().unwrap_or("")
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether `other` is this widget or one of its descendants, i.e. whether a message to
+    /// `other` should be routed through this widget
+    pub fn contains(&self, other: &WidgetId) -> bool {
+        match other.0.strip_prefix(&self.0) {
+            Some(rest) => rest.is_empty() || rest.starts_with(SEPARATOR),
+            None => false,
+        }
+    }
+}
+
+/// `main/sidebar/list` is the path of `list`
+impl From<&str> for WidgetId {
+    fn from(path: &str) -> Self {
+        Self(path.to_string())
+    }
+}
+
+impl fmt::Display for WidgetId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The last state published by every widget
+#[derive(Debug, Default)]
+pub struct WidgetStates {
+    states: BTreeMap<WidgetId, String>,
+}
+
+impl WidgetStates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// replace the state of `id`
+    pub fn publish(&mut self, id: &WidgetId, state: &str) {
+        self.states.insert(id.clone(), state.to_string());
+    }
+
+    /// forget `id` and its descendants, e.g. when the widget is removed from the tree
+    pub fn remove(&mut self, id: &WidgetId) {
+        self.states.retain(|other, _| !id.contains(other));
+    }
+
+    pub fn state(&self, id: &WidgetId) -> Option<&str> {
+        self.states.get(id).map(String::as_str)
+    }
+
+    /// the ids with a published state, in order
+    pub fn ids(&self) -> Vec<WidgetId> {
+        self.states.keys().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn path() {
+        let list = WidgetId::root("main").child("sidebar").child("list");
+        assert_eq!("main/sidebar/list", list.to_string());
+        assert_eq!("list", list.name());
+        assert_eq!(Some(WidgetId::from("main/sidebar")), list.parent());
+        assert_eq!(None, WidgetId::root("main").parent());
+
+        let sidebar = WidgetId::from("main/sidebar");
+        assert!(sidebar.contains(&list));
+        assert!(sidebar.contains(&sidebar));
+        assert!(!list.contains(&sidebar));
+        assert!(!sidebar.contains(&WidgetId::from("main/sidebar2")));
+    }
+
+    #[test]
+    fn states() {
+        let mut states = WidgetStates::new();
+        let sidebar = WidgetId::from("main/sidebar");
+        states.publish(&sidebar, "open");
+        states.publish(&sidebar.child("list"), "selected=1");
+        states.publish(&WidgetId::from("main/input"), "foo");
+        assert_eq!(Some("open"), states.state(&sidebar));
+
+        states.remove(&sidebar);
+        assert_eq!(vec![WidgetId::from("main/input")], states.ids());
+    }
+}
@@ -0,0 +1,126 @@
+//! The display width of text, measured as the renderer lays it out.
+//!
+//! Every character takes a cell, or two for the wide ones (CJK, most emoji). The characters
+//! without a defined width, e.g. the control characters, are counted as wide, as `Screen`
+//! does. Compute the layouts with these functions rather than with another width table, so
+//! that the columns match what is drawn:
+//!
+//! ```
+//! use tuikit::width::{display_width, truncate_to_width};
+//!
+//! assert_eq!(9, display_width("名前: foo"));
+//! assert_eq!("名", truncate_to_width("名前: foo", 3));
+//! ```
+//!
+//! There is no grapheme segmentation: a character combining with the previous one (e.g. an
+//! accent) is a cluster of its own, of width 0.
+
+use unicode_width::UnicodeWidthChar;
+
+/// The number of columns `ch` takes
+pub fn char_width(ch: char) -> usize {
+    ch.width().unwrap_or(2)
+}
+
+/// The number of columns `text` takes
+pub fn display_width(text: &str) -> usize {
+    text.chars().map(char_width).sum()
+}
+
+/// The longest prefix of `text` fitting in `width` columns, a wide character which would be
+/// cut is left out
+pub fn truncate_to_width(text: &str, width: usize) -> &str {
+    for cluster in clusters(text) {
+        if cluster.col + cluster.width > width {
+            return &text[..cluster.index];
+        }
+    }
+    text
+}
+
+/// The longest suffix of `text` fitting in `width` columns, e.g. to show the end of a path
+pub fn truncate_start_to_width(text: &str, width: usize) -> &str {
+    let mut taken = 0;
+    for (index, ch) in text.char_indices().rev() {
+        taken += char_width(ch);
+        if taken > width {
+            return &text[index + ch.len_utf8()..];
+        }
+    }
+    text
+}
+
+/// A character of a text and its place on the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cluster {
+    /// the byte offset in the text
+    pub index: usize,
+    /// the column, from the start of the text
+    pub col: usize,
+    pub ch: char,
+    pub width: usize,
+}
+
+/// The characters of `text` with their columns
+///
+/// ```
+/// use tuikit::width::clusters;
+///
+/// let cols: Vec<usize> = clusters("a名b").map(|cluster| cluster.col).collect();
+/// assert_eq!(vec![0, 1, 3], cols);
+/// ```
+pub fn clusters(text: &str) -> Clusters<'_> {
+    Clusters {
+        chars: text.char_indices(),
+        col: 0,
+    }
+}
+
+/// The iterator of `clusters`
+#[derive(Debug, Clone)]
+pub struct Clusters<'t> {
+    chars: core::str::CharIndices<'t>,
+    col: usize,
+}
+
+impl Iterator for Clusters<'_> {
+    type Item = Cluster;
+
+    fn next(&mut self) -> Option<Cluster> {
+        let (index, ch) = self.chars.next()?;
+        let cluster = Cluster {
+            index,
+            col: self.col,
+            ch,
+            width: char_width(ch),
+        };
+        self.col += cluster.width;
+        Some(cluster)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn measure() {
+        assert_eq!(0, display_width(""));
+        assert_eq!(5, display_width("hello"));
+        assert_eq!(4, display_width("日本"));
+        assert_eq!(2, display_width("\t"));
+        assert_eq!(1, display_width("e\u{301}"));
+    }
+
+    #[test]
+    fn truncate() {
+        assert_eq!("日", truncate_to_width("日本", 3));
+        assert_eq!("日本", truncate_to_width("日本", 4));
+        assert_eq!("", truncate_to_width("日本", 1));
+        assert_eq!("abc", truncate_to_width("abc", 10));
+
+        assert_eq!("本", truncate_start_to_width("日本", 3));
+        assert_eq!("ar/baz", truncate_start_to_width("/foo/bar/baz", 6));
+        assert_eq!("abc", truncate_start_to_width("abc", 10));
+    }
+}
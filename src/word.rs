@@ -0,0 +1,125 @@
+//! Word boundaries for text editing: `Alt-B`/`Alt-F` move by words and `Ctrl-W` deletes the
+//! word before the cursor. The right notion of "word" depends on the application, e.g. a path
+//! picker wants `/` as a boundary while a code search wants identifiers.
+//!
+//! The functions work on `&[char]` with the cursor being a char index.
+//!
+//! ```
+//! use tuikit::word::WordClass;
+//!
+//! let text: Vec<char> = "cd ~/src/tuikit".chars().collect();
+//! assert_eq!(9, WordClass::Shell.prev_word_start(&text, text.len()));
+//! assert_eq!(3, WordClass::Whitespace.prev_word_start(&text, text.len()));
+//! ```
+
+/// chars separating words in `WordClass::Shell`, besides whitespace
+const SHELL_SEPARATORS: &[char] = &[
+    '/', '\\', '|', '&', ';', '<', '>', '(', ')', '$', '`', '"', '\'', '=', ':', ',',
+];
+
+/// The policy deciding which chars form a word
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum WordClass {
+    /// words are separated by whitespace, path separators and shell meta characters, so
+    /// `foo-bar.txt` is a single word in `src/foo-bar.txt`
+    #[default]
+    Shell,
+    /// alphanumeric chars and `_`, like the identifiers of programming languages
+    Identifier,
+    /// anything except whitespace
+    Whitespace,
+}
+
+impl WordClass {
+    /// check if `ch` belongs to a word
+    pub fn is_word_char(self, ch: char) -> bool {
+        match self {
+            WordClass::Shell => !ch.is_whitespace() && !SHELL_SEPARATORS.contains(&ch),
+            WordClass::Identifier => ch.is_alphanumeric() || ch == '_',
+            WordClass::Whitespace => !ch.is_whitespace(),
+        }
+    }
+
+    /// The start of the word before `cursor`, i.e. where `Alt-B` moves to. `Ctrl-W` deletes
+    /// `text[prev_word_start(text, cursor)..cursor]`.
+    pub fn prev_word_start(self, text: &[char], cursor: usize) -> usize {
+        let mut pos = cursor.min(text.len());
+        while pos > 0 && !self.is_word_char(text[pos - 1]) {
+            pos -= 1;
+        }
+        while pos > 0 && self.is_word_char(text[pos - 1]) {
+            pos -= 1;
+        }
+        pos
+    }
+
+    /// The end of the word after `cursor`, i.e. where `Alt-F` moves to.
+    pub fn next_word_end(self, text: &[char], cursor: usize) -> usize {
+        let mut pos = cursor.min(text.len());
+        while pos < text.len() && !self.is_word_char(text[pos]) {
+            pos += 1;
+        }
+        while pos < text.len() && self.is_word_char(text[pos]) {
+            pos += 1;
+        }
+        pos
+    }
+
+    /// The range `(start, end)` of the word containing `text[pos]`, `None` if `text[pos]` is
+    /// not a word char.
+    pub fn word_at(self, text: &[char], pos: usize) -> Option<(usize, usize)> {
+        if !self.is_word_char(*text.get(pos)?) {
+            return None;
+        }
+
+        let mut start = pos;
+        while start > 0 && self.is_word_char(text[start - 1]) {
+            start -= 1;
+        }
+        let mut end = pos + 1;
+        while end < text.len() && self.is_word_char(text[end]) {
+            end += 1;
+        }
+        Some((start, end))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn chars(text: &str) -> Vec<char> {
+        text.chars().collect()
+    }
+
+    #[test]
+    fn word_classes_should_split_differently() {
+        let text = chars("vim src/foo_bar-baz.rs ");
+        let end = text.len();
+        assert_eq!(8, WordClass::Shell.prev_word_start(&text, end));
+        assert_eq!(20, WordClass::Identifier.prev_word_start(&text, end));
+        assert_eq!(4, WordClass::Whitespace.prev_word_start(&text, end));
+
+        assert_eq!(7, WordClass::Shell.next_word_end(&text, 3));
+        assert_eq!(3, WordClass::Identifier.next_word_end(&text, 0));
+        assert_eq!(end - 1, WordClass::Whitespace.next_word_end(&text, 4));
+    }
+
+    #[test]
+    fn cursor_at_boundaries() {
+        let text = chars("  ");
+        assert_eq!(0, WordClass::Shell.prev_word_start(&text, 2));
+        assert_eq!(2, WordClass::Shell.next_word_end(&text, 0));
+        assert_eq!(0, WordClass::Shell.prev_word_start(&[], 10));
+        assert_eq!(0, WordClass::Shell.next_word_end(&[], 10));
+    }
+
+    #[test]
+    fn word_at_position() {
+        let text = chars("open http://example.com");
+        assert_eq!(Some((0, 4)), WordClass::Identifier.word_at(&text, 2));
+        assert_eq!(None, WordClass::Identifier.word_at(&text, 4));
+        assert_eq!(Some((5, 23)), WordClass::Whitespace.word_at(&text, 10));
+        assert_eq!(None, WordClass::Whitespace.word_at(&text, 100));
+    }
+}